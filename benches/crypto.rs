@@ -0,0 +1,114 @@
+//! Verification of beacons, aggregation of partials and chain hash.
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use criterion::Throughput;
+use std::hint::black_box;
+
+use drand::bench;
+use drand::verify;
+use energon::drand::schemes::DefaultScheme;
+use energon::drand::schemes::SigsOnG1Scheme;
+use energon::drand::traits::BeaconDigest;
+use energon::drand::traits::DrandScheme as Scheme;
+use energon::points::KeyPoint;
+use energon::points::SigPoint;
+use energon::traits::Affine;
+use energon::traits::ScalarField;
+
+/// Batch sizes of verified beacons, e.g. a sync batch of consecutive rounds.
+const BATCHES: [u64; 3] = [1, 16, 128];
+/// Group sizes, partials are aggregated at threshold `n / 2 + 1`.
+const GROUPS: [u32; 4] = [4, 16, 64, 128];
+
+/// Consecutive signed beacons: round, previous signature, signature.
+struct Chain<S: Scheme> {
+    public_key: KeyPoint<S>,
+    beacons: Vec<(u64, Vec<u8>, SigPoint<S>)>,
+}
+
+impl<S: Scheme> Chain<S> {
+    fn new(rounds: u64) -> Self {
+        let private = S::Scalar::random();
+        let mut prev = vec![0xaa; 32];
+        let beacons = (1..=rounds)
+            .map(|round| {
+                let msg = S::Beacon::digest(&prev, round);
+                let signature = S::bls_sign(&msg, &private).unwrap();
+                let serialized = signature.serialize().unwrap();
+                let beacon = (round, prev.clone(), signature);
+                prev = AsRef::<[u8]>::as_ref(&serialized).to_vec();
+                beacon
+            })
+            .collect();
+
+        Self {
+            public_key: S::sk_to_pk(&private),
+            beacons,
+        }
+    }
+
+    fn verify(&self, batch: usize) -> bool {
+        self.beacons[..batch]
+            .iter()
+            .all(|(round, prev, signature)| {
+                verify::verify_beacon::<S>(&self.public_key, prev, *round, signature)
+            })
+    }
+}
+
+fn verification<S: Scheme>(c: &mut Criterion, name: &str) {
+    let chain = Chain::<S>::new(*BATCHES.iter().max().unwrap());
+    let mut group = c.benchmark_group(format!("verify/{name}"));
+    for batch in BATCHES {
+        group.throughput(Throughput::Elements(batch));
+        group.bench_with_input(BenchmarkId::from_parameter(batch), &batch, |b, &batch| {
+            let batch = usize::try_from(batch).unwrap();
+            b.iter(|| assert!(chain.verify(black_box(batch))));
+        });
+    }
+    group.finish();
+}
+
+fn verify_beacons(c: &mut Criterion) {
+    verification::<DefaultScheme>(c, "pedersen-bls-chained");
+    verification::<SigsOnG1Scheme>(c, "bls-unchained-g1-rfc9380");
+}
+
+fn aggregation<S: Scheme>(c: &mut Criterion, name: &str) {
+    let msg = S::Beacon::digest(&[], 1);
+    let mut group = c.benchmark_group(format!("aggregate/{name}"));
+    for n in GROUPS {
+        let partials = bench::partials::<S>(n, &msg);
+        let threshold = usize::try_from(n / 2 + 1).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(n), &partials, |b, partials| {
+            b.iter(|| assert!(bench::aggregate(black_box(&partials[..threshold]))));
+        });
+    }
+    group.finish();
+}
+
+fn aggregate_partials(c: &mut Criterion) {
+    aggregation::<DefaultScheme>(c, "pedersen-bls-chained");
+    aggregation::<SigsOnG1Scheme>(c, "bls-unchained-g1-rfc9380");
+}
+
+fn chain_hash(c: &mut Criterion) {
+    let public_key = [0x83; 96];
+    let group_hash = [0xf4; 32];
+    c.bench_function("chain_hash", |b| {
+        b.iter(|| {
+            verify::chain_hash(
+                black_box(3),
+                black_box(1_692_803_367),
+                black_box(&public_key),
+                black_box(&group_hash),
+                black_box("quicknet"),
+            )
+        });
+    });
+}
+
+criterion_group!(benches, verify_beacons, aggregate_partials, chain_hash);
+criterion_main!(benches);
@@ -0,0 +1,61 @@
+//! Throughput of the chain store actor: sequential puts and reads of stored rounds.
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BatchSize;
+use criterion::Criterion;
+use criterion::Throughput;
+use std::hint::black_box;
+
+use drand::bench::Store;
+
+/// Rounds written per iteration of the put benchmark.
+const PUT_ROUNDS: u64 = 1_000;
+/// Rounds stored before the get benchmark.
+const STORED_ROUNDS: u64 = 10_000;
+/// Size of BLS signature on G1.
+const SIGNATURE_LEN: usize = 48;
+
+fn put(c: &mut Criterion) {
+    let mut group = c.benchmark_group("store");
+    group.throughput(Throughput::Elements(PUT_ROUNDS));
+    group.sample_size(10);
+    group.bench_function("put", |b| {
+        b.iter_batched(
+            || {
+                let dir = tempfile::TempDir::new().unwrap();
+                let store = Store::open(dir.path());
+                (dir, store)
+            },
+            |(_dir, store)| {
+                for round in 1..=PUT_ROUNDS {
+                    store.put(round, vec![0xaa; SIGNATURE_LEN]);
+                }
+            },
+            BatchSize::PerIteration,
+        );
+    });
+    group.finish();
+}
+
+fn get(c: &mut Criterion) {
+    let dir = tempfile::TempDir::new().unwrap();
+    let store = Store::open(dir.path());
+    for round in 1..=STORED_ROUNDS {
+        store.put(round, vec![0xaa; SIGNATURE_LEN]);
+    }
+
+    let mut group = c.benchmark_group("store");
+    group.throughput(Throughput::Elements(1));
+    // Rounds are spread over the whole chain, so most reads miss the hot cache.
+    let mut round = 0;
+    group.bench_function("get", |b| {
+        b.iter(|| {
+            round = (round + 7_919) % STORED_ROUNDS + 1;
+            assert!(store.get(black_box(round)));
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, put, get);
+criterion_main!(benches);
@@ -18,6 +18,8 @@ fn main() {
     tonic_build::configure()
         .build_server(true)
         .out_dir(PROTO_DIR)
+        // Beacon signatures are moved from packets to store without copies.
+        .bytes([".drand.BeaconPacket"])
         .emit_rerun_if_changed(false)
         .compile_protos(&proto_files, &["."])
         .unwrap_or_else(|err| panic!("protobuf compile error: {err}"));
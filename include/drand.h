@@ -0,0 +1,86 @@
+#ifndef DRAND_H
+#define DRAND_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Status of a call.
+ */
+typedef enum DrandStatus {
+  /**
+   * Beacon is valid or the output is written.
+   */
+  DRAND_STATUS_OK = 0,
+  /**
+   * Beacon signature does not verify.
+   */
+  DRAND_STATUS_INVALID_BEACON = 1,
+  /**
+   * Scheme id is not known.
+   */
+  DRAND_STATUS_UNKNOWN_SCHEME = 2,
+  /**
+   * Public key or signature can not be deserialized.
+   */
+  DRAND_STATUS_INVALID_POINT = 3,
+  /**
+   * Null pointer or string which is not UTF-8.
+   */
+  DRAND_STATUS_INVALID_ARGUMENT = 4,
+} DrandStatus;
+
+/**
+ * Verifies beacon of the round, `previous_signature` is ignored by unchained schemes.
+ *
+ * # Safety
+ *
+ * `scheme_id` must be a nul-terminated string, buffers must be valid for reads of given length.
+ */
+enum DrandStatus drand_verify_beacon(const char *scheme_id,
+                                     const uint8_t *public_key,
+                                     size_t public_key_len,
+                                     uint64_t round,
+                                     const uint8_t *signature,
+                                     size_t signature_len,
+                                     const uint8_t *previous_signature,
+                                     size_t previous_signature_len);
+
+/**
+ * Writes 32 bytes of the chain hash into `out`, empty beacon id stands for the default chain.
+ *
+ * # Safety
+ *
+ * `beacon_id` must be a nul-terminated string, buffers must be valid for reads of given length
+ * and `out` must be valid for writes of 32 bytes.
+ */
+enum DrandStatus drand_chain_hash(uint32_t period,
+                                  int64_t genesis_time,
+                                  const uint8_t *public_key,
+                                  size_t public_key_len,
+                                  const uint8_t *group_hash,
+                                  size_t group_hash_len,
+                                  const char *beacon_id,
+                                  uint8_t *out);
+
+/**
+ * Writes 32 bytes of the randomness of a beacon into `out`.
+ *
+ * # Safety
+ *
+ * `signature` must be valid for reads of `signature_len` bytes and `out` must be valid for
+ * writes of 32 bytes.
+ */
+enum DrandStatus drand_randomness(const uint8_t *signature, size_t signature_len, uint8_t *out);
+
+/**
+ * Returns the round active at UNIX time `now`, see [`verify::round_at`].
+ */
+uint64_t drand_round_at(uint64_t now, uint32_t period, uint64_t genesis_time);
+
+#endif /* DRAND_H */
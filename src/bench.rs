@@ -0,0 +1,70 @@
+//! Entry points of benchmarks, see targets at `benches/`.
+//!
+//! Aggregation of partials and the chain store are internals of the daemon, they are wrapped
+//! here so criterion targets depend only on the library and the crypto backend. Verification
+//! and chain hash are benchmarked through the public [`crate::verify`] module.
+use crate::chain::BeaconRepr;
+use crate::chain::ChainStore;
+use crate::chain::StorageMode;
+use crate::chain::UnChainedBeacon;
+use crate::key::Scheme;
+use crate::protobuf::drand::BeaconPacket;
+
+use energon::kyber::poly::PriShare;
+use energon::kyber::tbls;
+use energon::kyber::tbls::recover_unchecked;
+use energon::kyber::tbls::SigShare;
+use energon::traits::ScalarField;
+use std::path::Path;
+use tokio::runtime::Runtime;
+
+/// Returns partial signatures of `n` nodes over `msg`.
+///
+/// Shares are random and do not belong to a group key: cost of the recovery does not depend
+/// on the validity of partials, which are verified by the daemon before aggregation.
+pub fn partials<S: Scheme>(n: u32, msg: &[u8]) -> Vec<SigShare<S>> {
+    (1..=n)
+        .map(|i| tbls::sign(&PriShare::new(i, S::Scalar::random()), msg).unwrap())
+        .collect()
+}
+
+/// Recovers signature from threshold of partials as the chain handler does.
+pub fn aggregate<S: Scheme>(threshold_sigs: &[SigShare<S>]) -> bool {
+    recover_unchecked(threshold_sigs).is_ok()
+}
+
+/// Chain store of unchained beacons with a runtime of its actor.
+pub struct Store {
+    rt: Runtime,
+    store: ChainStore<UnChainedBeacon>,
+}
+
+impl Store {
+    /// Opens chain store database in `folder`.
+    pub fn open(folder: &Path) -> Self {
+        let rt = Runtime::new().unwrap();
+        let store = rt
+            .block_on(ChainStore::start(
+                folder.to_path_buf(),
+                "bench".into(),
+                StorageMode::Full,
+            ))
+            .unwrap();
+
+        Self { rt, store }
+    }
+
+    pub fn put(&self, round: u64, signature: Vec<u8>) {
+        let beacon = UnChainedBeacon::from_packet(BeaconPacket {
+            round,
+            signature,
+            ..Default::default()
+        });
+        self.rt.block_on(self.store.put(beacon)).unwrap();
+    }
+
+    /// Returns false if the round is not stored.
+    pub fn get(&self, round: u64) -> bool {
+        self.rt.block_on(self.store.get(round)).is_ok()
+    }
+}
@@ -0,0 +1,245 @@
+//! Format of beacon archives produced by the archiver, see [`crate::core::archiver`].
+//!
+//! Archive of a chain is a [`Manifest`] at `manifest.json` and chunk files listed in it.
+//! Chunk contains beacons of consecutive rounds as JSON lines in format of HTTP relays:
+//! `{"round":1,"signature":"..","previous_signature":".."}`, so archives can be inspected
+//! with standard tools. Manifest records sha256 of each chunk and the chain hash.
+use crate::protobuf::drand::BeaconPacket;
+use crate::protobuf::drand::Metadata;
+
+use serde_json::json;
+use serde_json::Value;
+use sha2::Digest;
+use sha2::Sha256;
+
+/// Manifest file name within archive prefix.
+pub const MANIFEST: &str = "manifest.json";
+/// Default amount of beacons per chunk.
+pub const DEFAULT_CHUNK_SIZE: u64 = 10_000;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ArchiveError {
+    #[error("json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("missing or invalid field '{0}'")]
+    Field(&'static str),
+    #[error("chunk {0}: sha256 mismatch")]
+    ChunkHash(String),
+    #[error("chunk {0}: rounds are not consecutive within its range")]
+    ChunkRange(String),
+}
+
+/// Archived chunk of rounds `[from, to]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    pub name: String,
+    pub from: u64,
+    pub to: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub beacon_id: String,
+    /// Hex-encoded chain hash.
+    pub chain_hash: String,
+    pub scheme_id: String,
+    pub chunk_size: u64,
+    /// Chunks sorted by rounds, without gaps in between.
+    pub chunks: Vec<Chunk>,
+}
+
+impl Manifest {
+    pub fn new(beacon_id: &str, chain_hash: &[u8], scheme_id: &str, chunk_size: u64) -> Self {
+        Self {
+            beacon_id: beacon_id.to_string(),
+            chain_hash: hex::encode(chain_hash),
+            scheme_id: scheme_id.to_string(),
+            chunk_size: chunk_size.max(1),
+            chunks: vec![],
+        }
+    }
+
+    /// Returns the first round which is not archived yet.
+    pub fn next_round(&self) -> u64 {
+        self.chunks.last().map_or(1, |c| c.to + 1)
+    }
+
+    /// Returns the latest archived round, zero for empty archive.
+    pub fn last_round(&self) -> u64 {
+        self.next_round() - 1
+    }
+
+    /// Appends chunk of given beacons, returns name and content of the chunk file.
+    pub fn push(&mut self, beacons: &[BeaconPacket]) -> Option<(String, Vec<u8>)> {
+        let (first, last) = (beacons.first()?, beacons.last()?);
+        let name = format!("beacons-{:012}-{:012}.jsonl", first.round, last.round);
+        let data = encode_chunk(beacons);
+        self.chunks.push(Chunk {
+            name: name.clone(),
+            from: first.round,
+            to: last.round,
+            sha256: hex::encode(Sha256::digest(&data)),
+        });
+
+        Some((name, data))
+    }
+
+    pub fn to_json(&self) -> Vec<u8> {
+        let chunks: Vec<Value> = self
+            .chunks
+            .iter()
+            .map(|c| json!({"name": c.name, "from": c.from, "to": c.to, "sha256": c.sha256}))
+            .collect();
+        let manifest = json!({
+            "beacon_id": self.beacon_id,
+            "chain_hash": self.chain_hash,
+            "scheme_id": self.scheme_id,
+            "chunk_size": self.chunk_size,
+            "chunks": chunks,
+        });
+
+        manifest.to_string().into_bytes()
+    }
+
+    pub fn from_json(data: &[u8]) -> Result<Self, ArchiveError> {
+        let json: Value = serde_json::from_slice(data)?;
+        let mut chunks = vec![];
+        for c in json
+            .get("chunks")
+            .and_then(Value::as_array)
+            .ok_or(ArchiveError::Field("chunks"))?
+        {
+            chunks.push(Chunk {
+                name: str_field(c, "name")?,
+                from: u64_field(c, "from")?,
+                to: u64_field(c, "to")?,
+                sha256: str_field(c, "sha256")?,
+            });
+        }
+
+        Ok(Self {
+            beacon_id: str_field(&json, "beacon_id")?,
+            chain_hash: str_field(&json, "chain_hash")?,
+            scheme_id: str_field(&json, "scheme_id")?,
+            chunk_size: u64_field(&json, "chunk_size")?,
+            chunks,
+        })
+    }
+}
+
+impl Chunk {
+    /// Decodes chunk file, content is checked against the manifest entry.
+    pub fn decode(&self, data: &[u8], beacon_id: &str) -> Result<Vec<BeaconPacket>, ArchiveError> {
+        if hex::encode(Sha256::digest(data)) != self.sha256 {
+            return Err(ArchiveError::ChunkHash(self.name.clone()));
+        }
+        let mut beacons = Vec::new();
+        for line in data.split(|b| *b == b'\n').filter(|l| !l.is_empty()) {
+            let json: Value = serde_json::from_slice(line)?;
+            let previous_signature = match json.get("previous_signature") {
+                Some(_) => hex_field(&json, "previous_signature")?,
+                None => vec![],
+            };
+            beacons.push(BeaconPacket {
+                previous_signature: previous_signature.into(),
+                round: u64_field(&json, "round")?,
+                signature: hex_field(&json, "signature")?.into(),
+                metadata: Some(Metadata::with_id(beacon_id.to_string())),
+            });
+        }
+        let consecutive = beacons
+            .iter()
+            .zip(self.from..=self.to)
+            .all(|(b, round)| b.round == round);
+        if !consecutive || beacons.len() as u64 != (self.to + 1).saturating_sub(self.from) {
+            return Err(ArchiveError::ChunkRange(self.name.clone()));
+        }
+
+        Ok(beacons)
+    }
+}
+
+fn encode_chunk(beacons: &[BeaconPacket]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for b in beacons {
+        let line = if b.previous_signature.is_empty() {
+            json!({"round": b.round, "signature": hex::encode(&b.signature)})
+        } else {
+            json!({
+                "round": b.round,
+                "signature": hex::encode(&b.signature),
+                "previous_signature": hex::encode(&b.previous_signature),
+            })
+        };
+        data.extend_from_slice(line.to_string().as_bytes());
+        data.push(b'\n');
+    }
+
+    data
+}
+
+fn str_field(json: &Value, field: &'static str) -> Result<String, ArchiveError> {
+    json.get(field)
+        .and_then(Value::as_str)
+        .map(ToString::to_string)
+        .ok_or(ArchiveError::Field(field))
+}
+
+fn u64_field(json: &Value, field: &'static str) -> Result<u64, ArchiveError> {
+    json.get(field)
+        .and_then(Value::as_u64)
+        .ok_or(ArchiveError::Field(field))
+}
+
+fn hex_field(json: &Value, field: &'static str) -> Result<Vec<u8>, ArchiveError> {
+    json.get(field)
+        .and_then(Value::as_str)
+        .and_then(|s| hex::decode(s).ok())
+        .ok_or(ArchiveError::Field(field))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn beacons(from: u64, to: u64) -> Vec<BeaconPacket> {
+        (from..=to)
+            .map(|round| BeaconPacket {
+                previous_signature: round.to_be_bytes().to_vec().into(),
+                round,
+                signature: (round + 1).to_be_bytes().to_vec().into(),
+                metadata: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn manifest_roundtrip() {
+        let mut manifest = Manifest::new("default", &[1, 2], "pedersen-bls-chained", 3);
+        assert_eq!(manifest.next_round(), 1);
+        let (name, data) = manifest.push(&beacons(1, 3)).unwrap();
+        assert_eq!(name, "beacons-000000000001-000000000003.jsonl");
+        manifest.push(&beacons(4, 6)).unwrap();
+        assert_eq!(manifest.last_round(), 6);
+
+        let decoded = Manifest::from_json(&manifest.to_json()).unwrap();
+        assert_eq!(decoded, manifest);
+
+        let chunk = &decoded.chunks[0];
+        let received = chunk.decode(&data, "default").unwrap();
+        assert_eq!(received.len(), 3);
+        assert_eq!(received[2].signature, beacons(3, 3)[0].signature);
+        assert_eq!(
+            received[0].metadata.as_ref().map(|m| m.beacon_id.as_str()),
+            Some("default")
+        );
+
+        let mut corrupted = data.clone();
+        corrupted[0] = b' ';
+        assert!(matches!(
+            chunk.decode(&corrupted, "default"),
+            Err(ArchiveError::ChunkHash(_))
+        ));
+    }
+}
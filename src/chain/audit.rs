@@ -0,0 +1,355 @@
+//! Signature spot auditing of relayed chains.
+//!
+//! Every Nth round the relay fetches the latest stored round from all upstream nodes and compares
+//! their signatures with the stored one. BLS signatures of a round are unique, so any divergence
+//! means a split network or a misbehaving node. Divergences are logged, emitted as
+//! [`Event::AuditMismatch`] and counted in metrics served at `/metrics` of the HTTP API.
+//! A divergent signature which is valid for the chain key is recorded as an equivocation.
+use super::equivocation;
+use super::equivocation::Equivocation;
+use super::store::BeaconRepr;
+use super::store::ChainStore;
+
+use crate::core::events::Event;
+use crate::core::events::EventSender;
+use crate::net::public::PublicClient;
+use crate::net::relay::HttpRelay;
+use crate::net::utils::Address;
+use crate::verify::verify_serialized;
+
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tokio::time::timeout;
+use tracing::debug;
+use tracing::error;
+use tracing::Span;
+
+/// Timeout of a request to a single upstream node.
+const UPSTREAM_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Audit counters of a single chain.
+#[derive(Default)]
+pub struct AuditMetrics {
+    /// Audited rounds.
+    rounds: AtomicU64,
+    /// Upstream answers which differ from the stored signature.
+    mismatches: AtomicU64,
+    /// Upstream requests which failed.
+    unreachable: AtomicU64,
+}
+
+impl AuditMetrics {
+    /// Returns counters of chains in Prometheus text exposition format.
+    pub fn render(chains: &[(&str, &Self)]) -> String {
+        let mut m = String::new();
+        let families: [(&str, &str, fn(&Self) -> &AtomicU64); 3] = [
+            (
+                "drand_audit_rounds_total",
+                "Rounds audited against upstream nodes.",
+                |a| &a.rounds,
+            ),
+            (
+                "drand_audit_mismatches_total",
+                "Upstream signatures which differ from the stored one.",
+                |a| &a.mismatches,
+            ),
+            (
+                "drand_audit_unreachable_total",
+                "Failed requests to upstream nodes.",
+                |a| &a.unreachable,
+            ),
+        ];
+        for (name, help, counter) in families {
+            let _ = writeln!(m, "# HELP {name} {help}");
+            let _ = writeln!(m, "# TYPE {name} counter");
+            for (id, audit) in chains {
+                let value = counter(audit).load(Ordering::Relaxed);
+                let _ = writeln!(m, "{name}{{beacon_id=\"{id}\"}} {value}");
+            }
+        }
+
+        m
+    }
+}
+
+/// Upstream nodes of the audited chain.
+pub struct Auditor {
+    pub beacon_id: String,
+    pub peers: Vec<Address>,
+    pub relays: Vec<HttpRelay>,
+    pub scheme_id: String,
+    pub public_key: Vec<u8>,
+    /// Folder of the equivocations log.
+    pub log_dir: PathBuf,
+    pub events: EventSender,
+    pub l: Span,
+}
+
+impl Auditor {
+    /// Compares signature of the latest stored round with signatures served by upstream nodes.
+    /// Upstream nodes are queried concurrently, each request is bounded by [`UPSTREAM_TIMEOUT`].
+    pub async fn audit<B: BeaconRepr>(&self, store: &ChainStore<B>, metrics: &AuditMetrics) {
+        let stored = match store.last().await {
+            Ok(beacon) if beacon.round() > 0 => beacon,
+            Ok(_) => return,
+            Err(err) => {
+                error!(parent: &self.l, "audit: failed to read the latest stored beacon: {err}");
+                return;
+            }
+        };
+        let round = stored.round();
+        metrics.rounds.fetch_add(1, Ordering::Relaxed);
+
+        let mut tasks = JoinSet::new();
+        for peer in self.peers.clone() {
+            let beacon_id = self.beacon_id.clone();
+            tasks.spawn(async move {
+                let received = timeout(UPSTREAM_TIMEOUT, async {
+                    PublicClient::new(&peer)
+                        .await?
+                        .public_rand(round, beacon_id)
+                        .await
+                        .map(|b| (b.signature, b.previous_signature))
+                })
+                .await
+                .unwrap_or_else(|_| Err(anyhow::anyhow!("timeout")));
+                (peer.to_string(), received)
+            });
+        }
+        for relay in self.relays.clone() {
+            let beacon_id = self.beacon_id.clone();
+            tasks.spawn(async move {
+                let received = timeout(UPSTREAM_TIMEOUT, relay.beacon(round, &beacon_id))
+                    .await
+                    .map_err(|_| anyhow::anyhow!("timeout"))
+                    .and_then(|r| {
+                        r.map(|b| (b.signature.to_vec(), b.previous_signature.to_vec()))
+                            .map_err(anyhow::Error::from)
+                    });
+                (relay.url().to_string(), received)
+            });
+        }
+        while let Some(task) = tasks.join_next().await {
+            if let Ok((peer, received)) = task {
+                self.compare(&peer, &stored, received, metrics);
+            }
+        }
+    }
+
+    /// Compares the stored beacon with signature and previous signature received from the peer.
+    fn compare<B: BeaconRepr>(
+        &self,
+        peer: &str,
+        stored: &B,
+        received: anyhow::Result<(Vec<u8>, Vec<u8>)>,
+        metrics: &AuditMetrics,
+    ) {
+        let round = stored.round();
+        match received {
+            Ok((signature, _)) if signature == stored.signature() => {
+                debug!(parent: &self.l, "audit: round {round} matches {peer}");
+            }
+            Ok((signature, previous)) => {
+                metrics.mismatches.fetch_add(1, Ordering::Relaxed);
+                error!(parent: &self.l, "audit: signature of round {round} diverges, stored {}, {peer} {}", hex::encode(stored.signature()), hex::encode(&signature));
+                self.events
+                    .emit(&self.beacon_id, &Event::AuditMismatch { round, peer });
+                if verify_serialized(
+                    &self.scheme_id,
+                    &self.public_key,
+                    &previous,
+                    round,
+                    &signature,
+                ) == Ok(true)
+                {
+                    self.record_equivocation(peer, stored, &signature, &previous);
+                }
+            }
+            Err(err) => {
+                metrics.unreachable.fetch_add(1, Ordering::Relaxed);
+                debug!(parent: &self.l, "audit: failed to get round {round} from {peer}: {err}");
+            }
+        }
+    }
+
+    /// Received beacon is valid, so is the stored one: both are recorded.
+    fn record_equivocation<B: BeaconRepr>(
+        &self,
+        peer: &str,
+        stored: &B,
+        signature: &[u8],
+        previous: &[u8],
+    ) {
+        let round = stored.round();
+        error!(parent: &self.l, "CRITICAL: EQUIVOCATION: {peer} serves another valid beacon for round {round}, threshold of shares might be compromised");
+        let record = Equivocation::new(
+            round,
+            (
+                stored.signature(),
+                stored.prev_signature().unwrap_or_default(),
+            ),
+            (signature, previous),
+            peer,
+        );
+        if let Err(err) = equivocation::record(&self.log_dir, &record) {
+            error!(parent: &self.l, "audit: failed to record equivocation of round {round}: {err}");
+        }
+        self.events.emit(
+            &self.beacon_id,
+            &Event::Equivocation {
+                round,
+                source: peer,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::ChainedBeacon;
+    use crate::chain::StorageMode;
+    use crate::key::keys::Pair;
+    use crate::key::Scheme;
+    use crate::protobuf::drand::BeaconPacket;
+    use energon::drand::schemes::DefaultScheme;
+    use energon::drand::traits::BeaconDigest;
+    use energon::traits::Affine;
+
+    type S = DefaultScheme;
+
+    fn signature(pair: &Pair<S>, previous: &[u8], round: u64) -> Vec<u8> {
+        let msg = <S as Scheme>::Beacon::digest(previous, round);
+        S::bls_sign(&msg, pair.private_key())
+            .unwrap()
+            .serialize()
+            .unwrap()
+            .into()
+    }
+
+    fn auditor(pair: &Pair<S>, log_dir: &std::path::Path) -> Auditor {
+        Auditor {
+            beacon_id: "default".into(),
+            peers: vec![],
+            relays: vec![],
+            scheme_id: S::ID.into(),
+            public_key: pair.public_identity().key().serialize().unwrap().into(),
+            log_dir: log_dir.to_path_buf(),
+            events: EventSender::new(),
+            l: Span::none(),
+        }
+    }
+
+    fn kinds(events: &EventSender) -> Vec<String> {
+        events.recent().into_iter().map(|e| e.kind).collect()
+    }
+
+    #[test]
+    fn compare_with_upstream() {
+        let pair = Pair::<S>::generate(Address::precheck("127.0.0.1:1001").unwrap()).unwrap();
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let auditor = auditor(&pair, temp_dir.path());
+        let metrics = AuditMetrics::default();
+
+        let previous = vec![1; 96];
+        let stored = ChainedBeacon::from_packet(BeaconPacket {
+            previous_signature: previous.clone().into(),
+            round: 5,
+            signature: signature(&pair, &previous, 5).into(),
+            metadata: None,
+        });
+
+        // Same signature.
+        let same = (stored.signature().to_vec(), previous.clone());
+        auditor.compare("peer1", &stored, Ok(same), &metrics);
+        assert!(kinds(&auditor.events).is_empty());
+
+        // Unreachable peer.
+        auditor.compare("peer2", &stored, Err(anyhow::anyhow!("timeout")), &metrics);
+        assert_eq!(metrics.unreachable.load(Ordering::Relaxed), 1);
+        assert!(kinds(&auditor.events).is_empty());
+
+        // Divergent signature which is not valid is a mismatch only.
+        let invalid = (signature(&pair, &previous, 6), previous.clone());
+        auditor.compare("peer3", &stored, Ok(invalid), &metrics);
+        assert_eq!(metrics.mismatches.load(Ordering::Relaxed), 1);
+        assert_eq!(kinds(&auditor.events), ["audit_mismatch"]);
+        assert!(equivocation::list(temp_dir.path()).unwrap().is_empty());
+
+        // Valid signature over another history is an equivocation.
+        let forked = vec![2; 96];
+        let valid = (signature(&pair, &forked, 5), forked.clone());
+        auditor.compare("peer4", &stored, Ok(valid.clone()), &metrics);
+        assert_eq!(metrics.mismatches.load(Ordering::Relaxed), 2);
+        assert_eq!(
+            kinds(&auditor.events),
+            ["audit_mismatch", "audit_mismatch", "equivocation"]
+        );
+        let records = equivocation::list(temp_dir.path()).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].round, 5);
+        assert_eq!(records[0].source, "peer4");
+        assert_eq!(records[0].stored_signature, stored.signature());
+        assert_eq!(records[0].observed_signature, valid.0);
+        assert_eq!(records[0].observed_previous, forked);
+    }
+
+    #[tokio::test]
+    async fn unresponsive_upstream_times_out() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        // Connections are accepted and never answered.
+        tokio::spawn(async move {
+            let mut conns = vec![];
+            while let Ok((conn, _)) = listener.accept().await {
+                conns.push(conn);
+            }
+        });
+
+        let pair = Pair::<S>::generate(Address::precheck("127.0.0.1:1001").unwrap()).unwrap();
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut auditor = auditor(&pair, temp_dir.path());
+        auditor.relays = vec![HttpRelay::new(&url).unwrap()];
+        let store = ChainStore::<ChainedBeacon>::start(
+            temp_dir.path().to_path_buf(),
+            "default".into(),
+            StorageMode::Full,
+        )
+        .await
+        .unwrap();
+        let previous = vec![1; 96];
+        store
+            .put(ChainedBeacon::from_packet(BeaconPacket {
+                previous_signature: previous.clone().into(),
+                round: 1,
+                signature: signature(&pair, &previous, 1).into(),
+                metadata: None,
+            }))
+            .await
+            .unwrap();
+
+        let metrics = AuditMetrics::default();
+        let audit = auditor.audit(&store, &metrics);
+        tokio::time::timeout(UPSTREAM_TIMEOUT * 2, audit)
+            .await
+            .unwrap();
+        assert_eq!(metrics.rounds.load(Ordering::Relaxed), 1);
+        assert_eq!(metrics.unreachable.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn render_metrics() {
+        let quicknet = AuditMetrics::default();
+        quicknet.rounds.fetch_add(3, Ordering::Relaxed);
+        quicknet.mismatches.fetch_add(1, Ordering::Relaxed);
+        let metrics = AuditMetrics::render(&[("quicknet", &quicknet)]);
+
+        assert!(metrics.contains("drand_audit_rounds_total{beacon_id=\"quicknet\"} 3\n"));
+        assert!(metrics.contains("drand_audit_mismatches_total{beacon_id=\"quicknet\"} 1\n"));
+        assert!(metrics.contains("drand_audit_unreachable_total{beacon_id=\"quicknet\"} 0\n"));
+    }
+}
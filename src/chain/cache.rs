@@ -103,6 +103,11 @@ impl<S: Scheme> PartialCache<S> {
         self.valid_sigs.push(valid_sig);
         debug!(parent: &self.l, "{{\"store_partial\": \"{peer}\", \"round\": {}, \"len_partials\": \"{}/{}\"}}\"", self.height +1, self.thr, self.valid_sigs.len());
 
+        self.threshold_sigs()
+    }
+
+    /// Returns slice of sigshares *sorted by index* for `latest_stored + 1` round if their number hits the threshold.
+    pub fn threshold_sigs(&mut self) -> Option<&[SigShare<S>]> {
         if self.valid_sigs.len() < self.thr {
             return None;
         }
@@ -153,9 +158,16 @@ impl<S: Scheme> PartialCache<S> {
     }
 
     /// Aligns cache for new `latest_stored` round, verifying unchecked packets for next round.
+    ///
+    /// Verification stops once threshold is met, remaining packets are discarded unchecked.
     pub fn align(&mut self, ec: &EpochConfig<S>, latest_stored: u64, l: &Span) {
         if let Some(next_round_packets) = self.update(latest_stored) {
-            for packet in next_round_packets {
+            let total = next_round_packets.len();
+            for (i, packet) in next_round_packets.into_iter().enumerate() {
+                if self.valid_sigs.len() >= self.thr {
+                    debug!(parent: l, "update_cache: threshold is met for round {}, discarding {} packets", packet.round, total - i);
+                    break;
+                }
                 let Some(idx) = get_partial_index::<S>(&packet.partial_sig) else {
                     warn!(parent: l, "update_cache: ignoring packet with invalid data");
                     continue;
@@ -196,7 +208,14 @@ pub fn get_partial_index<S: Scheme>(partial: &[u8]) -> Option<u32> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::key::keys::Identity;
+    use crate::key::keys::Pair;
+    use crate::key::node::Node;
     use energon::drand::schemes::DefaultScheme;
+    use energon::drand::traits::BeaconDigest;
+    use energon::kyber::dkg::DistKeyShare;
+    use energon::kyber::poly::PriShare;
+    use energon::kyber::tbls;
 
     /// Returns rounds acceptable by cache.
     fn allowed_rounds(cache: &VecDeque<PartialsUnchecked>) -> Vec<u64> {
@@ -311,4 +330,72 @@ mod test {
         p_cache.update(new_stored);
         assert_eq!(allowed_rounds(&p_cache.rounds_cache), vec![522, 523, 524]);
     }
+
+    /// Returns epoch config of 5 nodes and valid partials of remote nodes for given round.
+    ///
+    /// Secret polynomial is constant, so every index holds the same secret.
+    fn constant_poly_partials(
+        round: u64,
+    ) -> (EpochConfig<DefaultScheme>, Vec<PartialBeaconPacket>) {
+        let pair = Pair::<DefaultScheme>::generate(Address::default()).unwrap();
+        let identity = pair.public_identity();
+        let nodes = (0..5)
+            .map(|i| {
+                let identity = Identity::new(
+                    Address::default(),
+                    identity.key().clone(),
+                    identity.signature().clone(),
+                );
+                Node::new(identity, i)
+            })
+            .collect();
+        let share = |i| PriShare::new(i, pair.private_key().clone());
+        let ec = EpochConfig::new(
+            nodes,
+            DistKeyShare {
+                commits: vec![identity.key().clone()],
+                pri_share: share(0),
+            },
+        );
+        let msg = <DefaultScheme as Scheme>::Beacon::digest(&[], round);
+        let partials = (1..5)
+            .map(|i| PartialBeaconPacket {
+                round,
+                partial_sig: tbls::sign(&share(i), &msg).unwrap().serialize().unwrap(),
+                previous_signature: vec![],
+                metadata: None,
+            })
+            .collect();
+
+        (ec, partials)
+    }
+
+    #[test]
+    fn align_stops_at_threshold() {
+        let thr = 2;
+        let (ec, partials) = constant_poly_partials(2);
+        // Partial with index of remote node signed by another secret.
+        let other = Pair::<DefaultScheme>::generate(Address::default()).unwrap();
+        let msg = <DefaultScheme as Scheme>::Beacon::digest(&[], 2);
+        let invalid = PartialBeaconPacket {
+            partial_sig: tbls::sign(&PriShare::new(4, other.private_key().clone()), &msg)
+                .unwrap()
+                .serialize()
+                .unwrap(),
+            ..partials[0].clone()
+        };
+
+        let mut p_cache = PartialCache::<DefaultScheme>::new(0, thr, Span::none());
+        for p in [&invalid, &partials[2], &partials[0], &partials[1]] {
+            assert_eq!(p_cache.add_packet(p.clone()), Some(true));
+        }
+        assert!(p_cache.threshold_sigs().is_none());
+
+        // Invalid partial is skipped, the last valid one is discarded once threshold is met.
+        p_cache.align(&ec, 1, &Span::none());
+        assert!(!p_cache.is_share_present(4));
+        assert!(!p_cache.is_share_present(2));
+        let sigs = p_cache.threshold_sigs().unwrap();
+        assert_eq!(sigs.iter().map(SigShare::index).collect::<Vec<_>>(), [1, 3]);
+    }
 }
@@ -0,0 +1,123 @@
+//! Catchup mode: while chain is behind the expected height, partials for the next round
+//! are produced after catchup period instead of waiting for the round ticker.
+//!
+//! Catchup period is the minimum period allowed between stored beacon
+//! and subsequent partial generation, see [`CatchupTimer::restart`].
+use super::time::SharedClock;
+
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Sends a single catchup signal after catchup period.
+///
+/// Each signal carries a generation number, signals from aborted generations are discarded.
+pub struct CatchupTimer {
+    /// Sender to be cloned for launching catchup task.
+    /// The task finishes once signal is sent after catchup delay.
+    tx: mpsc::Sender<u64>,
+    /// Generation of the latest launched task.
+    generation: u64,
+    /// Handle for the pending task, `Some` if catchup has been triggered but its signal has not arrived yet.
+    handle: Option<JoinHandle<()>>,
+    clock: SharedClock,
+}
+
+impl CatchupTimer {
+    pub fn new(tx: mpsc::Sender<u64>, clock: SharedClock) -> Self {
+        Self {
+            tx,
+            generation: 0,
+            handle: None,
+            clock,
+        }
+    }
+
+    /// Spawns a task to send a single catchup signal. Has no effect if the signal is pending.
+    pub fn start(&mut self, catchup_period: Duration) {
+        if self.handle.is_none() {
+            self.generation += 1;
+            let generation = self.generation;
+            let tx = self.tx.clone();
+            let clock = self.clock.clone();
+            let deadline = clock.now() + catchup_period;
+
+            self.handle = Some(tokio::task::spawn(async move {
+                clock.sleep_until(deadline).await;
+                let _ = tx.send(generation).await;
+            }));
+        }
+    }
+
+    /// Discards pending signal and starts the delay from now.
+    /// Used once new beacon is stored to keep catchup period between beacon and next partial.
+    pub fn restart(&mut self, catchup_period: Duration) {
+        if let Some(h) = self.handle.take() {
+            h.abort();
+        }
+        self.start(catchup_period);
+    }
+
+    /// Returns `true` if signal is from the latest generation and catchup should proceed.
+    /// Catchup can be started again after the signal is received.
+    pub fn signal_received(&mut self, generation: u64) -> bool {
+        if self.handle.is_some() && generation == self.generation {
+            self.handle = None;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::time::MockClock;
+    use std::sync::Arc;
+
+    /// Returns generation of the signal if it is sent at the current time of the clock.
+    async fn is_signaled(rx: &mut mpsc::Receiver<u64>) -> Option<u64> {
+        // Let the timer task observe the clock.
+        tokio::task::yield_now().await;
+        tokio::time::timeout(Duration::from_millis(50), rx.recv())
+            .await
+            .ok()
+            .flatten()
+    }
+
+    #[tokio::test]
+    async fn catchup_timer() {
+        let period = Duration::from_secs(3);
+        let clock = Arc::new(MockClock::new(Duration::from_secs(100)));
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut timer = CatchupTimer::new(tx, clock.clone());
+
+        // Repeated start has no effect while signal is pending.
+        timer.start(period);
+        timer.start(period);
+        clock.advance(period - Duration::from_secs(1));
+        assert!(is_signaled(&mut rx).await.is_none());
+        clock.advance(Duration::from_secs(1));
+        let generation = is_signaled(&mut rx).await.unwrap();
+        assert!(timer.signal_received(generation));
+        // Signal is accepted only once.
+        assert!(!timer.signal_received(generation));
+
+        // Restart discards pending signal and starts the delay from now.
+        timer.start(period);
+        clock.advance(period - Duration::from_secs(1));
+        timer.restart(period);
+        clock.advance(Duration::from_secs(1));
+        assert!(is_signaled(&mut rx).await.is_none());
+        clock.advance(period - Duration::from_secs(1));
+        let generation = is_signaled(&mut rx).await.unwrap();
+        assert!(timer.signal_received(generation));
+
+        // Outdated signals are ignored.
+        timer.start(period);
+        assert!(!timer.signal_received(generation));
+        clock.advance(period);
+        assert!(timer.signal_received(is_signaled(&mut rx).await.unwrap()));
+    }
+}
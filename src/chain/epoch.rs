@@ -11,6 +11,14 @@ use energon::kyber::poly::PubShare;
 use energon::kyber::tbls;
 use energon::kyber::tbls::SigShare;
 use energon::kyber::tbls::TBlsError;
+use std::net::IpAddr;
+use std::net::ToSocketAddrs;
+use std::time::Duration;
+use tracing::debug;
+use tracing::Span;
+
+/// Time to resolve hostname of a group node, sender of its partials is not checked on timeout.
+const RESOLVE_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Remote node representation per epoch.
 pub struct EpochNode<S: Scheme> {
@@ -18,6 +26,8 @@ pub struct EpochNode<S: Scheme> {
     peer: Address,
     /// Node public share is computed by evaluating public sharing polynomial at the node index.
     share: PubShare<S>,
+    /// IPs of the node address, empty if hostname is not resolved.
+    ips: Vec<IpAddr>,
 }
 
 impl<S: Scheme> EpochNode<S> {
@@ -43,9 +53,13 @@ impl<S: Scheme> EpochConfig<S> {
             // Skip our index.
             .filter(|n| n.index() != share.pri_share.index())
             // Map QUAL into EpochNode.
-            .map(|n| EpochNode {
-                share: poly.eval(n.index()),
-                peer: n.into_peer(),
+            .map(|n| {
+                let peer = n.into_peer();
+                EpochNode {
+                    share: poly.eval(n.index()),
+                    ips: host_ip(&peer).into_iter().collect(),
+                    peer,
+                }
             })
             .collect();
 
@@ -59,6 +73,45 @@ impl<S: Scheme> EpochConfig<S> {
         tbls::sign(&self.share.pri_share, msg)
     }
 
+    /// Resolves hostnames of remote nodes, IPs are used to bind partials to their sender.
+    pub async fn resolve_hosts(&mut self, l: &Span) {
+        for node in self.remote_nodes.iter_mut().filter(|n| n.ips.is_empty()) {
+            let host = node.peer.to_string();
+            let lookup = tokio::task::spawn_blocking(move || {
+                host.to_socket_addrs()
+                    .map(|addrs| addrs.map(|a| a.ip().to_canonical()).collect::<Vec<_>>())
+            });
+            match tokio::time::timeout(RESOLVE_TIMEOUT, lookup).await {
+                Ok(Ok(Ok(ips))) => node.ips = ips,
+                Ok(Ok(Err(err))) => {
+                    debug!(parent: l, "failed to resolve {}, sender of its partials is not checked: {err}", node.peer);
+                }
+                _ => {
+                    debug!(parent: l, "failed to resolve {} in time, sender of its partials is not checked", node.peer);
+                }
+            }
+        }
+    }
+
+    /// Checks that index of the partial is assigned to a remote node of the group and that the
+    /// partial is sent from an IP of that node, it is done before caching to reject packets with
+    /// unknown or spoofed index early.
+    pub fn check_index(&self, index: u32, from: &str) -> Result<(), ChainError> {
+        let node = self
+            .remote_nodes
+            .iter()
+            .find(|n| n.share.i == index)
+            .ok_or(ChainError::UnknownIndex(index))?;
+        if !is_sender(&node.ips, from) {
+            return Err(ChainError::SpoofedIndex {
+                index,
+                from: from.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
     /// Returns [`SigShare`] with node authority if partial signature is valid.
     pub fn verify_partial(
         &self,
@@ -81,6 +134,21 @@ impl<S: Scheme> EpochConfig<S> {
         Ok((sig_share, node.peer()))
     }
 
+    /// Checks our own partial signature against our public share, used in shadow mode.
+    pub fn verify_own_partial(&self, p: &PartialBeaconPacket) -> Result<(), ChainError> {
+        let sig_share = SigShare::deserialize(&p.partial_sig).map_err(ChainError::TBlsError)?;
+        if sig_share.index() != self.our_index() {
+            return Err(ChainError::UnknownIndex(sig_share.index()));
+        }
+        let poly = PubPoly {
+            commits: self.share.commitments().to_vec(),
+        };
+        let msg = S::Beacon::digest(&p.previous_signature, p.round);
+
+        S::bls_verify(&poly.eval(sig_share.index()).v, sig_share.value(), &msg)
+            .map_err(|_| ChainError::InvalidPartialSignature)
+    }
+
     pub fn nodes(&self) -> &[EpochNode<S>] {
         &self.remote_nodes
     }
@@ -93,3 +161,53 @@ impl<S: Scheme> EpochConfig<S> {
         self.share.commits.len()
     }
 }
+
+/// Returns IP of the address if its host is an IP literal.
+fn host_ip(peer: &Address) -> Option<IpAddr> {
+    let host = peer.host().trim_start_matches('[').trim_end_matches(']');
+
+    host.parse::<IpAddr>().ok().map(|ip| ip.to_canonical())
+}
+
+/// Returns false if partial is sent from an IP other than IPs of the node address.
+///
+/// Check is skipped for unknown sender and for unresolved hostnames. Loopback sender is accepted,
+/// as partials might be forwarded by a local proxy without `X-Real-IP`.
+fn is_sender(ips: &[IpAddr], from: &str) -> bool {
+    let Ok(sender) = from.parse::<IpAddr>() else {
+        return true;
+    };
+    let sender = sender.to_canonical();
+
+    ips.is_empty() || sender.is_loopback() || ips.contains(&sender)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_sender() {
+        let ips = |peer: &str| -> Vec<IpAddr> {
+            host_ip(&Address::precheck(peer).unwrap())
+                .into_iter()
+                .collect()
+        };
+        let node = ips("10.0.0.1:4444");
+        assert!(is_sender(&node, "10.0.0.1"));
+        assert!(is_sender(&node, "::ffff:10.0.0.1"));
+        assert!(!is_sender(&node, "10.0.0.2"));
+        // Unknown sender, local proxy.
+        assert!(is_sender(&node, ""));
+        assert!(is_sender(&node, "127.0.0.1"));
+
+        let node = ips("[2001:db8::1]:4444");
+        assert!(is_sender(&node, "2001:db8::1"));
+        assert!(!is_sender(&node, "2001:db8::2"));
+
+        // Hostname is resolved separately, see `EpochConfig::resolve_hosts`.
+        let node = ips("drand.example.org:4444");
+        assert!(node.is_empty());
+        assert!(is_sender(&node, "10.0.0.2"));
+    }
+}
@@ -0,0 +1,237 @@
+//! Append-only log of equivocations: two different valid beacons observed for the same round.
+//!
+//! A valid beacon of a round is unique for a group key and a chain history, so a second one
+//! means that a threshold of shares is compromised or that the chain history is forked. Both
+//! beacons are recorded with the source of the conflicting one, they are never removed.
+//!
+//! Records are JSON lines at `equivocations.jsonl` in the folder of the beacon id for nodes
+//! and in the store folder of the chain for relays, see `drand util equivocations`.
+use super::store::BeaconRepr;
+
+use crate::key::Scheme;
+
+use energon::points::KeyPoint;
+use energon::traits::Affine;
+use serde_json::json;
+use serde_json::Value;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+const EQUIVOCATIONS_FILE: &str = "equivocations.jsonl";
+
+#[derive(thiserror::Error, Debug)]
+pub enum EquivocationError {
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{path}: invalid record at line {line}")]
+    InvalidRecord { path: PathBuf, line: usize },
+}
+
+/// Conflicting beacons of a round, previous signatures are empty for unchained schemes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Equivocation {
+    pub round: u64,
+    pub stored_signature: Vec<u8>,
+    pub stored_previous: Vec<u8>,
+    pub observed_signature: Vec<u8>,
+    pub observed_previous: Vec<u8>,
+    /// Peer or relay which served the observed beacon.
+    pub source: String,
+    /// UNIX time of observation in seconds.
+    pub time: u64,
+}
+
+impl Equivocation {
+    pub fn new(round: u64, stored: (&[u8], &[u8]), observed: (&[u8], &[u8]), source: &str) -> Self {
+        Self {
+            round,
+            stored_signature: stored.0.to_vec(),
+            stored_previous: stored.1.to_vec(),
+            observed_signature: observed.0.to_vec(),
+            observed_previous: observed.1.to_vec(),
+            source: source.to_string(),
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "round": self.round,
+            "time": self.time,
+            "source": self.source,
+            "stored": {
+                "signature": hex::encode(&self.stored_signature),
+                "previous_signature": hex::encode(&self.stored_previous),
+            },
+            "observed": {
+                "signature": hex::encode(&self.observed_signature),
+                "previous_signature": hex::encode(&self.observed_previous),
+            },
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        let hex_field =
+            |beacon: &str, field: &str| hex::decode(value.get(beacon)?.get(field)?.as_str()?).ok();
+
+        Some(Self {
+            round: value.get("round")?.as_u64()?,
+            time: value.get("time")?.as_u64()?,
+            source: value.get("source")?.as_str()?.to_string(),
+            stored_signature: hex_field("stored", "signature")?,
+            stored_previous: hex_field("stored", "previous_signature")?,
+            observed_signature: hex_field("observed", "signature")?,
+            observed_previous: hex_field("observed", "previous_signature")?,
+        })
+    }
+}
+
+/// Returns the record if beacons of the same round differ and both are valid for `key`.
+pub(super) fn detect<S: Scheme, B: BeaconRepr>(
+    key: &KeyPoint<S>,
+    stored: &B,
+    observed: &B,
+    source: &str,
+) -> Option<Equivocation> {
+    if stored.round() != observed.round() || stored.signature() == observed.signature() {
+        return None;
+    }
+    let is_valid = |b: &B| {
+        Affine::deserialize(b.signature()).is_ok_and(|sig| {
+            super::is_valid_signature::<S>(
+                key,
+                b.prev_signature().unwrap_or_default(),
+                b.round(),
+                &sig,
+            )
+        })
+    };
+    if !is_valid(stored) || !is_valid(observed) {
+        return None;
+    }
+
+    Some(Equivocation::new(
+        stored.round(),
+        (
+            stored.signature(),
+            stored.prev_signature().unwrap_or_default(),
+        ),
+        (
+            observed.signature(),
+            observed.prev_signature().unwrap_or_default(),
+        ),
+        source,
+    ))
+}
+
+/// Appends the record to the log in `dir`.
+pub(super) fn record(dir: &Path, equivocation: &Equivocation) -> Result<(), std::io::Error> {
+    let mut f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(EQUIVOCATIONS_FILE))?;
+    // Record is written by a single call to keep lines intact.
+    f.write_all(format!("{}\n", equivocation.to_json()).as_bytes())
+}
+
+/// Returns recorded equivocations from the log in `dir`, from the oldest to the newest.
+pub fn list(dir: &Path) -> Result<Vec<Equivocation>, EquivocationError> {
+    let path = dir.join(EQUIVOCATIONS_FILE);
+    if !path.try_exists()? {
+        return Ok(vec![]);
+    }
+    std::fs::read_to_string(&path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| {
+            serde_json::from_str::<Value>(l)
+                .ok()
+                .as_ref()
+                .and_then(Equivocation::from_json)
+                .ok_or_else(|| EquivocationError::InvalidRecord {
+                    path: path.clone(),
+                    line: i + 1,
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::ChainedBeacon;
+    use crate::key::keys::Pair;
+    use crate::net::utils::Address;
+    use crate::protobuf::drand::BeaconPacket;
+    use energon::drand::schemes::DefaultScheme;
+    use energon::drand::traits::BeaconDigest;
+
+    type S = DefaultScheme;
+
+    fn beacon(pair: &Pair<S>, previous: &[u8], round: u64) -> ChainedBeacon {
+        let msg = <S as Scheme>::Beacon::digest(previous, round);
+        let signature: Vec<u8> = S::bls_sign(&msg, pair.private_key())
+            .unwrap()
+            .serialize()
+            .unwrap()
+            .into();
+        ChainedBeacon::from_packet(BeaconPacket {
+            previous_signature: previous.to_vec().into(),
+            round,
+            signature: signature.into(),
+            metadata: None,
+        })
+    }
+
+    #[test]
+    fn conflicting_beacon_is_detected() {
+        let pair = Pair::<S>::generate(Address::precheck("127.0.0.1:1001").unwrap()).unwrap();
+        let key = pair.public_identity().key();
+        let genesis = beacon(&pair, &[1; 32], 1);
+        let stored = beacon(&pair, genesis.signature(), 2);
+
+        // Same round signed over a forked history.
+        let fork = beacon(&pair, &[2; 96], 2);
+        let found = detect::<S, _>(key, &stored, &fork, "127.0.0.1:4444").unwrap();
+        assert_eq!(found.round, 2);
+        assert_eq!(found.stored_signature, stored.signature());
+        assert_eq!(found.observed_previous, [2; 96]);
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        record(temp_dir.path(), &found).unwrap();
+        assert_eq!(list(temp_dir.path()).unwrap(), [found]);
+
+        // Same beacon, beacon of another round and beacon signed by another key are not recorded.
+        assert!(detect::<S, _>(key, &stored, &stored.clone(), "peer").is_none());
+        assert!(detect::<S, _>(key, &stored, &beacon(&pair, &[2; 96], 3), "peer").is_none());
+        let other = Pair::<S>::generate(Address::precheck("127.0.0.1:1002").unwrap()).unwrap();
+        assert!(detect::<S, _>(key, &stored, &beacon(&other, &[2; 96], 2), "peer").is_none());
+    }
+
+    #[test]
+    fn record_and_list() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        assert!(list(dir).unwrap().is_empty());
+
+        let first = Equivocation::new(7, (&[1], &[2]), (&[3], &[4]), "127.0.0.1:4444");
+        let second = Equivocation::new(9, (&[5], &[]), (&[6], &[]), "https://api.drand.sh");
+        record(dir, &first).unwrap();
+        record(dir, &second).unwrap();
+        assert_eq!(list(dir).unwrap(), [first, second]);
+
+        std::fs::write(dir.join(EQUIVOCATIONS_FILE), "{\"round\":1}\n").unwrap();
+        assert!(matches!(
+            list(dir),
+            Err(EquivocationError::InvalidRecord { line: 1, .. })
+        ));
+    }
+}
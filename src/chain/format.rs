@@ -0,0 +1,124 @@
+//! Output formats of beacons, shared by the CLI and the HTTP API.
+//!
+//! JSON is written byte for byte as by `api.drand.sh`: no whitespace, fields in order `round`,
+//! `randomness`, `signature`, `previous_signature`, the previous signature is omitted for
+//! unchained schemes. Other formats contain only the randomness: lowercase hex, standard base64
+//! with padding or 32 raw bytes.
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum BeaconFormat {
+    #[default]
+    Json,
+    Hex,
+    Base64,
+    Raw,
+}
+
+impl BeaconFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Hex | Self::Base64 => "text/plain",
+            Self::Raw => "application/octet-stream",
+        }
+    }
+
+    /// Returns true for formats which are printed as lines of text.
+    pub fn is_text(self) -> bool {
+        self != Self::Raw
+    }
+
+    /// Encodes a beacon, `previous_signature` is empty for unchained schemes.
+    pub fn encode(
+        self,
+        round: u64,
+        randomness: &[u8],
+        signature: &[u8],
+        previous_signature: &[u8],
+    ) -> Vec<u8> {
+        match self {
+            Self::Json => {
+                // Hex strings never need escaping, fields are written in order of Go drand.
+                let mut json = format!(
+                    "{{\"round\":{round},\"randomness\":\"{}\",\"signature\":\"{}\"",
+                    hex::encode(randomness),
+                    hex::encode(signature)
+                );
+                if !previous_signature.is_empty() {
+                    json.push_str(",\"previous_signature\":\"");
+                    json.push_str(&hex::encode(previous_signature));
+                    json.push('"');
+                }
+                json.push('}');
+                json.into_bytes()
+            }
+            Self::Hex => hex::encode(randomness).into_bytes(),
+            Self::Base64 => BASE64.encode(randomness).into_bytes(),
+            Self::Raw => randomness.to_vec(),
+        }
+    }
+}
+
+impl std::fmt::Display for BeaconFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json => f.write_str("json"),
+            Self::Hex => f.write_str("hex"),
+            Self::Base64 => f.write_str("base64"),
+            Self::Raw => f.write_str("raw"),
+        }
+    }
+}
+
+impl FromStr for BeaconFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "hex" => Ok(Self::Hex),
+            "base64" => Ok(Self::Base64),
+            "raw" => Ok(Self::Raw),
+            _ => Err(format!(
+                "unknown beacon format {s}, expected: json, hex, base64, raw"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_compatible_json() {
+        let unchained = BeaconFormat::Json.encode(1, &[0xaa], &[0xbb], &[]);
+        assert_eq!(
+            unchained,
+            br#"{"round":1,"randomness":"aa","signature":"bb"}"#
+        );
+        let chained = BeaconFormat::Json.encode(2, &[0xaa], &[0xbb], &[0xcc]);
+        assert_eq!(
+            chained,
+            br#"{"round":2,"randomness":"aa","signature":"bb","previous_signature":"cc"}"#
+        );
+
+        assert_eq!(
+            BeaconFormat::Hex.encode(1, &[0xff, 0x01], &[], &[]),
+            b"ff01"
+        );
+        assert_eq!(
+            BeaconFormat::Base64.encode(1, &[0xff, 0x01], &[], &[]),
+            b"/wE="
+        );
+        assert_eq!(
+            BeaconFormat::Raw.encode(1, &[0xff, 0x01], &[], &[]),
+            [0xff, 0x01]
+        );
+        assert_eq!("base64".parse(), Ok(BeaconFormat::Base64));
+        assert!("yaml".parse::<BeaconFormat>().is_err());
+    }
+}
@@ -2,19 +2,30 @@ use super::cache::get_partial_index;
 use super::cache::CACHE_LIMIT_ROUNDS;
 use super::epoch::EpochConfig;
 use super::epoch::EpochNode;
+use super::equivocation;
 use super::info::ChainInfo;
+use super::info::KeySchedule;
 use super::registry::Registry;
+use super::shadow;
+use super::shadow::Shadow;
+use super::skew;
+use super::skew::SkewChange;
+use super::skew::MAX_CLOCK_SKEW_MS;
 use super::store::BeaconRepr;
 use super::store::ChainStore;
+use super::store::StorageMode;
 use super::store::StoreError;
 use super::store::StoreStreamResponse;
 use super::sync::start_follow_chain;
 use super::sync::DefaultSyncer;
 use super::sync::SyncError;
-use super::sync::LOGS_TO_SKIP;
 use super::ticker;
 use super::time;
+use super::time::Clock;
+use super::time::SharedClock;
 
+use crate::core::events::Event;
+use crate::core::events::EventSender;
 use crate::key::group::Group;
 use crate::key::keys::DistPublic;
 use crate::key::store::FileStore;
@@ -22,15 +33,20 @@ use crate::key::store::FileStoreError;
 use crate::key::Scheme;
 
 use crate::net::pool::PoolSender;
+use crate::net::protocol::Buffered;
 use crate::net::protocol::PartialMsg;
 use crate::net::protocol::PartialPacket;
+use crate::net::protocol::SyncMemory;
 use crate::net::utils::Address;
 use crate::net::utils::Callback;
 use crate::net::utils::Seconds;
 
 use crate::protobuf::drand::BeaconPacket;
 use crate::protobuf::drand::ChainInfoPacket;
+use crate::protobuf::drand::MerkleProofResponse;
+use crate::protobuf::drand::Metadata;
 use crate::protobuf::drand::PartialBeaconPacket;
+use crate::protobuf::drand::PublicRandResponse;
 use crate::protobuf::drand::StartSyncRequest;
 use crate::protobuf::drand::StatusResponse;
 use crate::protobuf::drand::SyncProgress;
@@ -43,8 +59,11 @@ use energon::traits::Affine;
 
 use rand::seq::SliceRandom;
 use std::fmt::Debug;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
+use tokio::sync::watch;
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
 use tokio::time::Instant;
@@ -76,6 +95,8 @@ pub enum ChainError {
     UnknownIndex(u32),
     #[error("received partial with invalid signature")]
     InvalidPartialSignature,
+    #[error("partial claims index {index} which belongs to another node, sent from {from}")]
+    SpoofedIndex { index: u32, from: String },
     #[error("internal: failed to proceed chain_info request")]
     FailedToGetInfo,
     #[error("invalid round: {invalid}, instead of {current}")]
@@ -92,18 +113,32 @@ pub enum ChainError {
     FileStoreError(#[from] FileStoreError),
     #[error("no dkg group setup yet")]
     DkgSetupRequired,
+    #[error("resync from round {from} is not allowed: latest stored {latest}, current {current}")]
+    InvalidResyncFrom {
+        from: u64,
+        latest: u64,
+        current: u64,
+    },
 }
 
 /// Handler to initiate and react to the tBLS protocol.
 struct ChainHandler<S: Scheme, B: BeaconRepr> {
     /// Public information of chain.
     chain_info: ChainInfo<S>,
+    /// Public keys of known epochs, used to verify resynced beacons across transitions.
+    keys: KeySchedule<S>,
     /// Minimum period allowed between and subsequent partial generation.
     catchup_period: Duration,
     /// Actor handle for beacon persistent database.
     store: ChainStore<B>,
     /// Actor handle for partial packets connection pool.
     pool: PoolSender,
+    /// Sender for daemon events.
+    events: EventSender,
+    /// Source of time for round ticker and transitions.
+    clock: SharedClock,
+    /// Memory of beacons buffered by resync, shared across beacon ids.
+    sync_memory: SyncMemory,
     /// Used for loading distributed materials after each DKG.
     fs: FileStore,
     /// Epoch config is representation of DKG output.
@@ -113,6 +148,8 @@ struct ChainHandler<S: Scheme, B: BeaconRepr> {
     /// `{private_listen}.{beacon_id}.{dkg_index}`.
     private_listen: String,
     our_addres: Address,
+    /// Shadow mode: own partials are verified before broadcast, see [`super::shadow`].
+    shadow: bool,
     l: Span,
 }
 
@@ -139,6 +176,21 @@ pub enum ChainCmd {
     },
     /// Status request for latest stored round.
     LatestStored(Callback<StatusResponse, StoreError>),
+    /// Inclusion proof request of the round into Merkle tree over stored beacons.
+    MerkleProof {
+        round: u64,
+        cb: Callback<MerkleProofResponse, StoreError>,
+    },
+    /// Request for stored beacon with its randomness, the latest beacon for round 0.
+    PublicRand {
+        round: u64,
+        cb: Callback<PublicRandResponse, StoreError>,
+    },
+    /// Manual resync request, replied with the round up to which resync is started.
+    ForceResync {
+        from_round: u64,
+        cb: Callback<u64, ChainError>,
+    },
 }
 
 /// Holder to simplify channels management, see [`init_chain`] for detailed channels description.
@@ -146,21 +198,28 @@ struct Channels {
     rx_partial: mpsc::Receiver<PartialMsg>,
     tx_cmd: mpsc::Sender<ChainCmd>,
     rx_cmd: mpsc::Receiver<ChainCmd>,
-    tx_resync: mpsc::Sender<BeaconPacket>,
-    rx_resync: mpsc::Receiver<BeaconPacket>,
-    tx_catchup: mpsc::Sender<()>,
-    rx_catchup: mpsc::Receiver<()>,
+    tx_resync: mpsc::Sender<Buffered<BeaconPacket>>,
+    rx_resync: mpsc::Receiver<Buffered<BeaconPacket>>,
+    tx_catchup: mpsc::Sender<u64>,
+    rx_catchup: mpsc::Receiver<u64>,
 }
 
 /// Permanent chain configuration, used during transitions (see [`run_chain`] and [`run_chain_default`]).  
-pub struct ChainConfig<B: BeaconRepr> {
+pub struct ChainConfig<S: Scheme, B: BeaconRepr> {
     chan: Channels,
+    /// Public keys of previous epochs, `None` if chain is not known yet.
+    keys: Option<KeySchedule<S>>,
     pool: PoolSender,
+    events: EventSender,
+    clock: SharedClock,
+    /// Memory of buffered sync streams, shared across beacon ids.
+    sync_memory: SyncMemory,
     fs: FileStore,
     store: ChainStore<B>,
     private_listen: String,
     beacon_id: String,
     our_addres: Address,
+    shadow: bool,
 }
 
 impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
@@ -171,16 +230,21 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
     /// - Immutable chain configuration: [`ChainHandler`].
     /// - Mutable operational state: [`Registry`].
     pub async fn from_config(
-        c: ChainConfig<B>,
+        c: ChainConfig<S, B>,
     ) -> Result<(Self, Registry<S, B>, Channels), FileStoreError> {
         let ChainConfig {
             chan: channels,
+            keys,
             pool,
+            events,
+            clock,
+            sync_memory,
             fs,
             store,
             private_listen,
             beacon_id,
             our_addres,
+            shadow,
         } = c;
 
         // Load group and share from filestore.
@@ -211,15 +275,16 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
         let share = fs.load_share::<S>()?;
         let public_key = commits.swap_remove(0);
         drop(commits);
-        let ec = EpochConfig::new(nodes, share);
+        let mut ec = EpochConfig::new(nodes, share);
 
         // Create spans for handler and partial cache.
         let span_meta = format!("{private_listen}.{}.{}", beacon_id, ec.our_index());
         let l_handler = tracing::info_span!("", chain = span_meta);
         let l_partial = tracing::info_span!("", cache = span_meta);
+        ec.resolve_hosts(&l_handler).await;
 
         // Check corner case for transition.
-        check_transition(period, transition_time, &l_handler).await;
+        check_transition(period, transition_time, clock.as_ref(), &l_handler).await;
 
         // Genesis beacon should always match the group.genesis_seed.
         store
@@ -227,6 +292,19 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
             .await
             .map_err(|_| FileStoreError::InvalidData)?;
 
+        // Public key of the new epoch is active from transition round.
+        let transition_round =
+            time::current_round(transition_time, period.get_value(), genesis_time);
+        let keys = match keys {
+            Some(mut keys) => {
+                if keys.add_transition(transition_round, public_key.clone()) {
+                    warn!(parent: &l_handler, "public key has been changed at transition round {transition_round}");
+                }
+                keys
+            }
+            None => KeySchedule::new(public_key.clone()),
+        };
+
         let chain_info = ChainInfo {
             public_key,
             beacon_id,
@@ -238,13 +316,18 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
 
         let chain_handler = Self {
             chain_info,
+            keys,
             catchup_period,
             store,
             pool,
+            events,
+            clock,
+            sync_memory,
             fs,
             ec,
             private_listen,
             our_addres,
+            shadow,
             l: l_handler,
         };
 
@@ -256,8 +339,13 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
             channels.tx_catchup.clone(),
             channels.tx_resync.clone(),
             chain_handler.ec.thr(),
+            chain_handler.clock.clone(),
             l_partial,
         );
+        chain_handler.events.register_timings(
+            &chain_handler.chain_info.beacon_id,
+            &registry.shared_timings(),
+        );
 
         Ok((chain_handler, registry, channels))
     }
@@ -330,15 +418,18 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
             && round <= c_round
             && !reg.cache().is_share_present(self.ec.our_index())
         {
+            let delay = self.round_delay_ms(round);
             if let Some(thr_sigs) = reg.cache_mut().add_prechecked(sigshare, &self.our_addres) {
                 let Ok(recovered) = recover_unchecked(thr_sigs) else {
                     error!(parent: &self.l, "recover_unchecked: scalar is non-invertable");
                     panic!()
                 };
+                record_threshold_delay(reg, round, delay);
                 if let Err(err) = self.save_recovered(round, &recovered, reg).await {
                     error!(parent: &self.l, "failed to save recovered beacon for round {round}: {err}");
                     panic!()
                 };
+                self.recover_from_cache(reg).await;
             }
         }
 
@@ -362,7 +453,11 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
     }
 
     /// Sends partial to the connection pool to broadcast for nodes subscribed for given beacon ID.
+    /// In shadow mode the partial is verified first.
     async fn broadcast(&self, packet: PartialBeaconPacket) -> Result<(), ChainError> {
+        if self.shadow {
+            self.check_shadow_partial(&packet);
+        }
         let round = packet.round;
         if self.pool.broadcast_partial(packet).await.is_err() {
             error!(parent: &self.l, "failed to broadcast partial, round {round}, error: {}", ChainError::PoolClosedRx);
@@ -372,6 +467,18 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
         Ok(())
     }
 
+    /// Verifies own partial in shadow mode against our public share of the group.
+    fn check_shadow_partial(&self, packet: &PartialBeaconPacket) {
+        let round = packet.round;
+        let delay = self.round_delay_ms(round);
+        match self.ec.verify_own_partial(packet) {
+            Ok(()) => {
+                info!(parent: &self.l, "shadow: valid partial for round {round}, produced with delay {delay}ms")
+            }
+            Err(err) => error!(parent: &self.l, "shadow: invalid partial for round {round}: {err}"),
+        }
+    }
+
     async fn process_partial(
         &self,
         reg: &mut Registry<S, B>,
@@ -382,6 +489,24 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
         let ls_round = reg.latest_stored().round();
         debug!(parent: &self.l, "processing partial: from {}, round {p_round}", partial.from);
 
+        // Sample clock offset from partials for actual rounds, catchup rounds are skipped.
+        if p_round + 1 >= c_round {
+            let offset = skew::round_offset_ms(
+                self.chain_info.period,
+                self.chain_info.genesis_time,
+                p_round,
+            );
+            match reg.clock_skew_mut().observe(&partial.from, offset) {
+                Some(SkewChange::Detected(skew)) => {
+                    error!(parent: &self.l, "CLOCK SKEW DETECTED: median offset to peers {skew}ms exceeds {MAX_CLOCK_SKEW_MS}ms, please check local time synchronization");
+                }
+                Some(SkewChange::Recovered(skew)) => {
+                    info!(parent: &self.l, "clock skew recovered: median offset to peers {skew}ms");
+                }
+                None => {}
+            }
+        }
+
         if p_round <= ls_round {
             debug!(parent: &self.l, "ignoring partial for round: {p_round}, current {c_round}, latest_stored {ls_round}");
             return Ok(());
@@ -395,6 +520,29 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
             });
         }
 
+        let Some(idx) = get_partial_index::<S>(&partial.packet.partial_sig) else {
+            return Err(ChainError::InvalidShareLenght {
+                expected: <S::Sig as energon::traits::Group>::POINT_SIZE + 2,
+                received: partial.packet.partial_sig.len(),
+            });
+        };
+        if let Err(err) = self.ec.check_index(idx, &partial.from) {
+            warn!(parent: &self.l, "rejected partial for round {p_round} from {}: {err}", partial.from);
+            let event = if matches!(err, ChainError::SpoofedIndex { .. }) {
+                Event::SpoofedPartial {
+                    index: idx,
+                    peer: &partial.from,
+                }
+            } else {
+                Event::UnknownIndex {
+                    index: idx,
+                    peer: &partial.from,
+                }
+            };
+            self.events.emit(&self.chain_info.beacon_id, &event);
+            return Err(err);
+        }
+
         // Cache updates once per stored beacon, between updates this call is cheap.
         reg.align_cache(&self.ec, &self.l);
 
@@ -417,19 +565,13 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
 
         // Process packet as sigshare.
         } else if p_round == ls_round + 1 {
-            let Some(idx) = get_partial_index::<S>(&partial.packet.partial_sig) else {
-                return Err(ChainError::InvalidShareLenght {
-                    expected: <S::Sig as energon::traits::Group>::POINT_SIZE + 2,
-                    received: partial.packet.partial_sig.len(),
-                });
-            };
-
             // Ignore already present sigshare.
             if reg.cache().is_share_present(idx) {
                 debug!(parent: &self.l, "ignoring already cached sigshare for round {p_round}");
                 return Ok(());
             }
             let (valid_sigshare, node_addr) = self.ec.verify_partial(&partial.packet)?;
+            let delay = self.round_delay_ms(p_round);
 
             // Recover and save beacon.
             // Note: Sigshares are prechecked and sorted by their index.
@@ -438,16 +580,43 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
                     error!(parent: &self.l, "fatal: recover_unchecked: scalar is non-invertable");
                     panic!()
                 };
+                record_threshold_delay(reg, p_round, delay);
                 if let Err(err) = self.save_recovered(p_round, &recovered, reg).await {
                     error!(parent: &self.l, "fatal: failed to save recovered beacon for round {p_round}: {err}");
                     panic!()
                 };
+                self.recover_from_cache(reg).await;
             }
         }
 
         Ok(())
     }
 
+    /// Threshold-met short-circuit: sigshares for next rounds might be already verified
+    /// from cache during alignment, such beacons are recovered without waiting for new partials.
+    async fn recover_from_cache(&self, reg: &mut Registry<S, B>) {
+        loop {
+            let round = reg.latest_stored().round() + 1;
+            if round > reg.current_round() {
+                return;
+            }
+            let delay = self.round_delay_ms(round);
+            let Some(thr_sigs) = reg.cache_mut().threshold_sigs() else {
+                return;
+            };
+            let Ok(recovered) = recover_unchecked(thr_sigs) else {
+                error!(parent: &self.l, "fatal: recover_unchecked: scalar is non-invertable");
+                panic!()
+            };
+            debug!(parent: &self.l, "threshold is met from cache for round {round}");
+            record_threshold_delay(reg, round, delay);
+            if let Err(err) = self.save_recovered(round, &recovered, reg).await {
+                error!(parent: &self.l, "fatal: failed to save recovered beacon for round {round}: {err}");
+                panic!()
+            };
+        }
+    }
+
     async fn save_recovered(
         &self,
         r_round: u64,
@@ -467,6 +636,7 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
                     error!(parent: &self.l, "round {r_round}: error: {}", ChainError::SerializeRecovered);
                     return Err(ChainError::SerializeRecovered);
                 };
+                let r_sig: Vec<u8> = r_sig.into();
                 B::new(reg.latest_stored(), r_sig.into())
             } else {
                 error!(parent: &self.l, "round {r_round}: error: {}", ChainError::InvalidRecovered);
@@ -483,6 +653,26 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
             self.store.put(valid_beacon.clone()).await?;
             let storage_time = start.elapsed().as_millis();
             info!(parent: &self.l,"{{\"NEW_BEACON_STORED\": \"{{ round: {r_round}, sig: {}, prevSig: {:?} }}\", \"time_discrepancy_ms\": {discrepancy}, \"storage_time_ms\": {storage_time}", valid_beacon.short_sig(), valid_beacon.short_prev_sig().unwrap_or_default());
+            self.events.emit(
+                &self.chain_info.beacon_id,
+                &Event::BeaconStored { round: r_round },
+            );
+            self.events.publish_head(
+                &self.chain_info.beacon_id,
+                r_round,
+                valid_beacon.signature(),
+                valid_beacon.prev_signature(),
+            );
+            // Aggregation delay is meaningful only for actual rounds, catchup rounds are late by design.
+            if r_round >= reg.current_round() {
+                let delay = self.round_delay_ms(r_round);
+                if reg
+                    .timings()
+                    .observe_aggregation(delay, self.chain_info.period)
+                {
+                    warn!(parent: &self.l, "beacon for round {r_round} aggregated with delay {delay}ms, nearing the period budget");
+                }
+            }
             reg.update_latest_stored(valid_beacon);
             reg.align_cache(&self.ec, &self.l);
 
@@ -492,7 +682,8 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
             let catchup_launch = c_round > ls_round;
             debug!(parent: &self.l, "{{\"beacon_loop\": \"catchupmode\", \"last_is\" {ls_round}, \"current\": {c_round}, \"catchup_launch\": {catchup_launch}}}");
             if catchup_launch {
-                reg.start_catchup(self.catchup_period);
+                // Catchup period is counted from the moment of storing the beacon.
+                reg.restart_catchup(self.catchup_period);
             }
         } else {
             // Beacon for this round has already been received via resync.
@@ -513,23 +704,30 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
         reg: &mut Registry<S, B>,
     ) -> Result<(), ChainError> {
         let l = &self.l;
+        if reg.forced_resync().is_some() {
+            return self.rewrite_resynced(p, reg).await;
+        }
         // Check if we still need beacon for this round (it might have already recovered on cathup mode).
         if p.round == reg.latest_stored().round() + 1 {
             let Ok(p_signature) = Affine::deserialize(&p.signature) else {
                 error!(parent: l, "save_resynced: failed to deserialize signature for round {}, aborting resync task..", p.round);
+                reg.resync_metrics()
+                    .invalid_beacons
+                    .fetch_add(1, Ordering::Relaxed);
                 reg.stop_resync();
                 return Ok(());
             };
 
+            // Resynced rounds might belong to previous epoch.
             if super::is_valid_signature::<S>(
-                &self.chain_info.public_key,
+                self.keys.key_for(p.round),
                 reg.latest_stored().signature(),
                 p.round,
                 &p_signature,
             ) {
                 let valid_beacon = B::new(reg.latest_stored(), p.signature);
-                // Skip logs if round is too far from current round.
-                if reg.current_round() - p.round < LOGS_TO_SKIP || p.round % 300 == 0 {
+                // Logs are throttled while the chain is far behind.
+                if let Some(skipped) = reg.resync_log().allow() {
                     let discrepancy = time::round_discrepancy_ms(
                         self.chain_info.period,
                         self.chain_info.genesis_time,
@@ -539,24 +737,187 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
                     let start = Instant::now();
                     self.store.put(valid_beacon.clone()).await?;
                     let storage_time = start.elapsed().as_millis();
-                    info!(parent: l,"NEW_BEACON_STORED: round {}, time_discrepancy_ms: {discrepancy}, storage_time_ms: {storage_time}", p.round);
+                    info!(parent: l,"NEW_BEACON_STORED: round {}, time_discrepancy_ms: {discrepancy}, storage_time_ms: {storage_time}, skipped_logs: {skipped}", p.round);
                 } else {
                     self.store.put(valid_beacon.clone()).await?;
                 }
+                self.events.emit(
+                    &self.chain_info.beacon_id,
+                    &Event::BeaconStored { round: p.round },
+                );
+                self.events.publish_head(
+                    &self.chain_info.beacon_id,
+                    p.round,
+                    valid_beacon.signature(),
+                    valid_beacon.prev_signature(),
+                );
                 reg.update_latest_stored(valid_beacon);
                 reg.extend_resync_expiry_time();
             } else {
                 error!(parent: l, "save_resynced: invalid signature for round {}, aborting resync task..", p.round);
+                reg.resync_metrics()
+                    .invalid_beacons
+                    .fetch_add(1, Ordering::Relaxed);
                 reg.stop_resync();
             }
         } else {
             debug!(parent: l, "save_resynced: ignoring beacon for round {}, latest_stored {}, aborting sync task..", p.round, reg.latest_stored().round());
+            // Round might have been recovered meanwhile, resynced beacon should be the same.
+            if p.round <= reg.latest_stored().round() {
+                if let Ok(stored) = self.store.get(p.round).await {
+                    let source = reg
+                        .resync_peer()
+                        .map_or_else(|| "resync".to_string(), |peer| peer.to_string());
+                    self.check_equivocation(&stored, &B::from_packet(p), &source);
+                }
+            }
             reg.stop_resync();
         }
 
         Ok(())
     }
 
+    /// Verifies beacon of forced resync against the previous one, stored record is rewritten if it differs.
+    async fn rewrite_resynced(
+        &self,
+        p: BeaconPacket,
+        reg: &mut Registry<S, B>,
+    ) -> Result<(), ChainError> {
+        let l = &self.l;
+        let Some(prev) = reg.forced_resync().cloned() else {
+            return Ok(());
+        };
+        // Beacons buffered from the aborted resync task are skipped.
+        if p.round != prev.round() + 1 {
+            debug!(parent: l, "rewrite_resynced: ignoring beacon for round {}, expected {}", p.round, prev.round() + 1);
+            return Ok(());
+        }
+        let is_valid = Affine::deserialize(&p.signature).is_ok_and(|sig| {
+            super::is_valid_signature::<S>(
+                self.keys.key_for(p.round),
+                prev.signature(),
+                p.round,
+                &sig,
+            )
+        });
+        if !is_valid {
+            error!(parent: l, "rewrite_resynced: invalid signature for round {}, aborting resync task..", p.round);
+            reg.resync_metrics()
+                .invalid_beacons
+                .fetch_add(1, Ordering::Relaxed);
+            reg.stop_resync();
+            return Ok(());
+        }
+
+        let beacon = B::new(&prev, p.signature);
+        let stored = self.store.get(beacon.round()).await;
+        if let Ok(stored) = &stored {
+            self.check_equivocation(stored, &beacon, "forced resync");
+        }
+        match stored {
+            Ok(stored)
+                if stored.signature() == beacon.signature()
+                    && stored.prev_signature() == beacon.prev_signature() => {}
+            Ok(_) | Err(StoreError::NotFound | StoreError::Corrupt(_)) => {
+                self.store.repair(beacon.clone()).await?;
+                warn!(parent: l, "rewrite_resynced: stored beacon of round {} is rewritten", beacon.round());
+            }
+            Err(err) => return Err(err.into()),
+        }
+        reg.extend_resync_expiry_time();
+        if beacon.round() == reg.latest_stored().round() {
+            // Next rounds are stored as usual.
+            reg.update_latest_stored(beacon);
+            reg.update_forced_resync(None);
+        } else {
+            reg.update_forced_resync(Some(beacon));
+        }
+
+        Ok(())
+    }
+
+    /// Records both beacons if the stored one differs from the observed one and both are valid.
+    fn check_equivocation(&self, stored: &B, observed: &B, source: &str) {
+        let key = self.keys.key_for(stored.round());
+        let Some(record) = equivocation::detect::<S, B>(key, stored, observed, source) else {
+            return;
+        };
+        let round = record.round;
+        error!(parent: &self.l, "CRITICAL: EQUIVOCATION: two valid beacons for round {round}, stored {}, from {source} {}, threshold of shares might be compromised", hex::encode(stored.signature()), hex::encode(observed.signature()));
+        if let Err(err) = equivocation::record(&self.fs.beacon_path, &record) {
+            error!(parent: &self.l, "failed to record equivocation of round {round}: {err}");
+        }
+        self.events.emit(
+            &self.chain_info.beacon_id,
+            &Event::Equivocation { round, source },
+        );
+    }
+
+    /// Records how late partial for the round has been produced.
+    fn record_partial_delay(&self, reg: &mut Registry<S, B>, round: u64) {
+        let delay = self.round_delay_ms(round);
+        if reg.timings().observe_partial(delay, self.chain_info.period) {
+            warn!(parent: &self.l, "partial for round {round} produced with delay {delay}ms, nearing the period budget");
+        }
+    }
+
+    /// Returns delay in milliseconds of the current time relative to the time of round.
+    fn round_delay_ms(&self, round: u64) -> u64 {
+        let offset =
+            skew::round_offset_ms(self.chain_info.period, self.chain_info.genesis_time, round);
+        offset.try_into().unwrap_or_default()
+    }
+
+    /// Returns other epoch nodes in random order.
+    fn shuffled_peers(&self) -> Vec<Address> {
+        let mut peers: Vec<Address> = self
+            .ec
+            .nodes()
+            .iter()
+            .map(EpochNode::peer)
+            .filter(|peer| **peer != self.our_addres)
+            .cloned()
+            .collect();
+        peers.shuffle(&mut rand::rng());
+
+        peers
+    }
+
+    /// Starts task fetching rounds missing in between of stored beacons.
+    async fn fill_gaps(&self) -> Result<(), ChainError> {
+        let gaps = self.store.missing().await?;
+        if gaps.is_empty() {
+            return Ok(());
+        }
+        let total: u64 = gaps.iter().map(|(from, to)| to - from + 1).sum();
+        warn!(parent: &self.l, "chain store: {total} rounds are missing in {} gaps, fetching from peers", gaps.len());
+        tokio::spawn(super::sync::fill_gaps::<S, B>(
+            gaps,
+            self.shuffled_peers(),
+            self.keys.clone(),
+            self.chain_info.beacon_id.clone(),
+            self.store.clone(),
+            self.l.clone(),
+        ));
+
+        Ok(())
+    }
+
+    /// Re-fetches beacons which failed checksum verification in chain store since the previous call.
+    fn repair_corrupted(&self) {
+        for round in self.store.take_corrupted() {
+            warn!(parent: &self.l, "chain store: round {round} is corrupted, re-fetching from peers");
+            tokio::spawn(super::sync::refetch::<S, B>(
+                round,
+                self.shuffled_peers(),
+                self.keys.key_for(round).clone(),
+                self.chain_info.beacon_id.clone(),
+                self.store.clone(),
+                self.l.clone(),
+            ));
+        }
+    }
+
     /// Trigger for catchup and resync, starting them if needed and not already running.
     pub fn check_resync_catchup(&self, reg: &mut Registry<S, B>) {
         let c_round = reg.current_round();
@@ -565,39 +926,87 @@ impl<S: Scheme, B: BeaconRepr> ChainHandler<S, B> {
         if c_round - ls_round > 1 {
             reg.start_catchup(self.catchup_period);
             if !reg.is_resync_active() {
-                let tx_resync = reg.get_tx_resync();
-                let mut peers: Vec<Address> = self
-                    .ec
-                    .nodes()
-                    .iter()
-                    .map(EpochNode::peer)
-                    .cloned()
-                    .collect();
-                peers.shuffle(&mut rand::rng());
-
-                let id = self.chain_info.beacon_id.clone();
-                let start_from = ls_round + 1;
-                let up_to = c_round;
-                let l = tracing::info_span!(
-                    "",
-                    resync = format!(
-                        "{}.{id}.{} from {start_from} to {up_to}",
-                        self.private_listen,
-                        self.ec.our_index()
-                    )
-                );
-
-                let handle = super::sync::resync(start_from, up_to, peers, id, tx_resync, l);
-                reg.new_resync_handle(self.chain_info.period, handle);
+                if let Some(peer) = reg.demote_stalled_peer() {
+                    warn!(parent: &self.l, "resync: demoting stalled peer {peer}");
+                }
+                self.start_resync(reg, ls_round + 1, c_round);
             }
         }
     }
+
+    /// Spawns resync task for rounds `[start_from, up_to]`.
+    fn start_resync(&self, reg: &mut Registry<S, B>, start_from: u64, up_to: u64) {
+        let tx_resync = reg.get_tx_resync();
+        let mut peers: Vec<Address> = self
+            .ec
+            .nodes()
+            .iter()
+            .map(EpochNode::peer)
+            .cloned()
+            .collect();
+        peers.shuffle(&mut rand::rng());
+        super::sync::rotate_peers(&mut peers, reg.demoted_peers());
+
+        let id = self.chain_info.beacon_id.clone();
+        let l = tracing::info_span!(
+            "",
+            resync = format!(
+                "{}.{id}.{} from {start_from} to {up_to}",
+                self.private_listen,
+                self.ec.our_index()
+            )
+        );
+
+        let (tx_peer, rx_peer) = watch::channel(None);
+        let metrics = Arc::clone(reg.resync_metrics());
+        let handle = super::sync::resync(
+            start_from,
+            up_to,
+            peers,
+            id,
+            tx_resync,
+            self.sync_memory.clone(),
+            tx_peer,
+            metrics,
+            self.events.clone(),
+            l,
+        );
+        reg.new_resync_handle(self.chain_info.period, handle, rx_peer);
+    }
+
+    /// Starts resync from `from_round` even if chain is not late, running resync task is aborted.
+    ///
+    /// Stored rounds starting from `from_round` are verified again, see [`Self::rewrite_resynced`].
+    async fn force_resync(
+        &self,
+        reg: &mut Registry<S, B>,
+        from_round: u64,
+    ) -> Result<u64, ChainError> {
+        let latest = reg.latest_stored().round();
+        let current = reg.current_round();
+        if from_round == 0 || from_round > latest + 1 || from_round > current {
+            return Err(ChainError::InvalidResyncFrom {
+                from: from_round,
+                latest,
+                current,
+            });
+        }
+        let prev = self.store.get(from_round - 1).await?;
+        warn!(parent: &self.l, "resync: forced from round {from_round} up to {current}");
+        reg.stop_resync();
+        if from_round <= latest {
+            reg.start_forced_resync(prev);
+        }
+        self.start_resync(reg, from_round, current);
+
+        Ok(current)
+    }
 }
 
 /// Default chain used for fresh nodes without DKG setup.
 async fn run_chain_default<S: Scheme, B: BeaconRepr>(
-    mut cc: ChainConfig<B>,
-) -> Result<Option<ChainConfig<B>>, ChainError> {
+    mut cc: ChainConfig<S, B>,
+) -> Result<Option<ChainConfig<S, B>>, ChainError> {
     let l = tracing::info_span!(
         "",
         chain = format!("{}.{}", cc.private_listen, cc.beacon_id)
@@ -607,9 +1016,18 @@ async fn run_chain_default<S: Scheme, B: BeaconRepr>(
 
     // Handle for sync task.
     let mut sync_handle: Option<JoinHandle<Result<(), SyncError>>> = None;
+    // Share of the next epoch, checked in shadow mode until the transition.
+    let mut shadow: Option<Shadow<S>> = None;
+    let clock = cc.clock.clone();
+    let mut next_check = clock.now();
 
     loop {
         tokio::select! {
+            () = clock.sleep_until(next_check), if cc.shadow => {
+                next_check = clock.now() + shadow::CHECK_INTERVAL;
+                check_shadow(&cc, &mut shadow, &l).await;
+            }
+
             new_partial = cc.chan.rx_partial.recv()=> {
                 match new_partial{
                     Some((_partial, cb)) => cb.reply(Err(ChainError::DkgSetupRequired)),
@@ -620,6 +1038,10 @@ async fn run_chain_default<S: Scheme, B: BeaconRepr>(
             cmd = cc.chan.rx_cmd.recv()=> {
                 match cmd{
                     Some(ChainCmd::NewEpoch{first_round:_}) => {
+                        // Key of followed chain is kept to verify rounds before transition.
+                        if !chain_info.genesis_seed.is_empty() {
+                            cc.keys = Some(KeySchedule::new(chain_info.public_key.clone()));
+                        }
                         // Chain config will be reused for non-default chain (see: [init_chain]).
                         return Ok(Some(cc))
                     }
@@ -635,16 +1057,19 @@ async fn run_chain_default<S: Scheme, B: BeaconRepr>(
                     Some(ChainCmd::LatestStored(cb))=>{
                         cb.reply(
                             match cc.store.last().await{
-                                Ok(last) => Ok(StatusResponse{latest_stored_round: last.round()}),
+                                Ok(last) => Ok(StatusResponse{latest_stored_round: last.round(), ..Default::default()}),
                                 Err(err) => Err(err),
                             }
                         );
                     }
+                    Some(ChainCmd::MerkleProof{round, cb})=>cb.reply(merkle_proof(&cc.store, round, &cc.beacon_id).await),
+                    Some(ChainCmd::PublicRand{round, cb})=>cb.reply(public_rand(&cc.store, round, &cc.beacon_id).await),
                     Some(ChainCmd::Reload)=> unreachable!("reload is never called on default chain"),
                     // Following the node without DKG setup is forbidden.
                     Some(ChainCmd::ReSync {from_round: _, cb})=> cb.reply(Err(StoreError::Internal)),
                     // Same for ChainInfo.
                     Some(ChainCmd::ChainInfo(cb))=>cb.reply(Err(ChainError::DkgSetupRequired)),
+                    Some(ChainCmd::ForceResync{from_round: _, cb})=>cb.reply(Err(ChainError::DkgSetupRequired)),
                     None => return Err(ChainError::CmdClosedTx),
                 }
             }
@@ -652,8 +1077,32 @@ async fn run_chain_default<S: Scheme, B: BeaconRepr>(
     }
 }
 
+/// Checks the latest stored beacon in shadow mode once the node holds a share, see [`Shadow`].
+async fn check_shadow<S: Scheme, B: BeaconRepr>(
+    cc: &ChainConfig<S, B>,
+    shadow: &mut Option<Shadow<S>>,
+    l: &Span,
+) {
+    if shadow.is_none() {
+        *shadow = Shadow::load(&cc.fs);
+    }
+    let Some(shadow) = shadow else {
+        return;
+    };
+    let Ok(latest) = cc.store.last().await else {
+        return;
+    };
+    match shadow.check(&latest) {
+        Some(Ok(checked)) => {
+            info!(parent: l, "shadow: beacon {} is valid, valid partial for round {} with delay {}ms", latest.round(), checked.round, checked.delay_ms);
+        }
+        Some(Err(err)) => error!(parent: l, "shadow: round {}: {err}", latest.round()),
+        None => {}
+    }
+}
+
 async fn follow_chain<S: Scheme, B: BeaconRepr>(
-    cc: &ChainConfig<B>,
+    cc: &ChainConfig<S, B>,
     req: &StartSyncRequest,
     chain_info: &mut ChainInfo<S>,
     handle: &mut Option<JoinHandle<Result<(), SyncError>>>,
@@ -668,7 +1117,8 @@ async fn follow_chain<S: Scheme, B: BeaconRepr>(
             "",
             follow_chain = format!("{}.{}", cc.private_listen, cc.beacon_id)
         );
-        let new_config = start_follow_chain(req, &cc.beacon_id, &cc.store, l).await?;
+        let new_config =
+            start_follow_chain(req, &cc.beacon_id, &cc.store, cc.sync_memory.clone(), l).await?;
         let new_ci = new_config.chain_info_from_packet()?;
 
         if chain_info.genesis_seed.is_empty() {
@@ -679,7 +1129,7 @@ async fn follow_chain<S: Scheme, B: BeaconRepr>(
 
         // Follow request always has upper boundary.
         let current_round = time::current_round(
-            time::time_now().as_secs(),
+            cc.clock.now().as_secs(),
             chain_info.period.get_value(),
             chain_info.genesis_time,
         );
@@ -689,7 +1139,7 @@ async fn follow_chain<S: Scheme, B: BeaconRepr>(
             current_round
         };
 
-        let syncer = DefaultSyncer::<S, B>::from_config(new_config)?;
+        let syncer = DefaultSyncer::<S, B>::from_config(new_config)?.with_events(cc.events.clone());
         // Channel to display (and keep-alive) sync progress on client side.
         let (tx, rx) = mpsc::channel(128);
 
@@ -702,16 +1152,22 @@ async fn follow_chain<S: Scheme, B: BeaconRepr>(
 }
 
 async fn run_chain<S: Scheme, B: BeaconRepr>(
-    inner: ChainConfig<B>,
-) -> Result<Option<ChainConfig<B>>, ChainError> {
+    inner: ChainConfig<S, B>,
+) -> Result<Option<ChainConfig<S, B>>, ChainError> {
     // Initialize handler and registry.
     let (h, mut reg, mut channels) = ChainHandler::<S, B>::from_config(inner).await?;
 
     // Add epoch nodes into connection pool to broadcast our partial beacon packets.
     h.register_in_pool().await?;
+    // Rounds missing in chain store are fetched in background.
+    h.fill_gaps().await?;
 
     // Start round ticker.
-    let mut rx_round = ticker::start_ticker(h.chain_info.genesis_time, h.chain_info.period);
+    let mut rx_round = ticker::start_ticker(
+        h.chain_info.genesis_time,
+        h.chain_info.period,
+        h.clock.clone(),
+    );
     info!(parent: &h.l, "run_chain: latest stored {}, current {}",  reg.latest_stored().round(), reg.current_round());
 
     loop {
@@ -726,9 +1182,11 @@ async fn run_chain<S: Scheme, B: BeaconRepr>(
                 info!(parent: &h.l, "{{\"beacon_loop\": \"new_round\", \"round\": {}, \"lastbeacon\": {}}}", reg.current_round(), reg.latest_stored().round());
                 let partial = h.sign_partial(&mut reg).await?;
                 h.broadcast(partial).await?;
+                h.record_partial_delay(&mut reg, round);
 
                 // Trigger cachup and resync, starting them if needed and not already running..
                 h.check_resync_catchup(&mut reg);
+                h.repair_corrupted();
             }
 
             // Partial beacon packet received from other nodes.
@@ -741,8 +1199,7 @@ async fn run_chain<S: Scheme, B: BeaconRepr>(
 
             // Signal arrives if catchup mode is active.
             signal = channels.rx_catchup.recv()=>{
-                if signal.is_some(){
-                    reg.catchup_signal_received();
+                if signal.is_some_and(|generation| reg.catchup_signal_received(generation)){
                     let packet = h.sign_partial(&mut reg).await?;
                     h.broadcast(packet).await?;
                 }
@@ -751,7 +1208,7 @@ async fn run_chain<S: Scheme, B: BeaconRepr>(
             // Beacon packet from resync task.
             resynced = channels.rx_resync.recv()=>{
                 if let Some(p)=resynced{
-                    h.save_resynced(p, &mut reg).await?;
+                    h.save_resynced(p.into_inner(), &mut reg).await?;
                 }
             }
 
@@ -786,17 +1243,24 @@ async fn run_chain<S: Scheme, B: BeaconRepr>(
                     Some(ChainCmd::LatestStored(cb))=>{
                         cb.reply(
                             match h.store.last().await{
-                                Ok(last) => Ok(StatusResponse{latest_stored_round: last.round()}),
+                                Ok(last) => Ok(reg.status(last.round())),
                                 Err(err) => Err(err),
                             }
                         );
                     }
+                    Some(ChainCmd::MerkleProof{round, cb})=>cb.reply(merkle_proof(&h.store, round, &h.chain_info.beacon_id).await),
+                    Some(ChainCmd::PublicRand{round, cb})=>cb.reply(public_rand(&h.store, round, &h.chain_info.beacon_id).await),
+                    Some(ChainCmd::ForceResync{from_round, cb})=>cb.reply(h.force_resync(&mut reg, from_round).await),
                 }
             }
         }
     }
 
     // Prepare for transition.
+    // Resync task is aborted with registry, its buffered beacons are stale for the next epoch.
+    drop(reg);
+    while channels.rx_resync.try_recv().is_ok() {}
+
     // Remove old nodes from connection pool.
     h.pool
         .remove_id(h.chain_info.beacon_id.clone())
@@ -805,28 +1269,76 @@ async fn run_chain<S: Scheme, B: BeaconRepr>(
 
     let config_for_next_epoch = ChainConfig {
         chan: channels,
+        keys: Some(h.keys),
         pool: h.pool,
+        events: h.events,
+        clock: h.clock,
+        sync_memory: h.sync_memory,
         store: h.store,
         private_listen: h.private_listen,
         beacon_id: h.chain_info.beacon_id,
         fs: h.fs,
         our_addres: h.our_addres,
+        shadow: h.shadow,
     };
 
     Ok(Some(config_for_next_epoch))
 }
 
+async fn merkle_proof<B: BeaconRepr>(
+    store: &ChainStore<B>,
+    round: u64,
+    beacon_id: &str,
+) -> Result<MerkleProofResponse, StoreError> {
+    let (beacon, proof) = store.merkle_proof(round).await?;
+
+    Ok(MerkleProofResponse {
+        round,
+        signature: beacon.signature().to_vec(),
+        tree_size: proof.tree_size,
+        root: proof.root.to_vec(),
+        path: proof.path.iter().map(|h| h.to_vec()).collect(),
+        metadata: Some(Metadata::with_id(beacon_id.to_string())),
+    })
+}
+
+pub(super) async fn public_rand<B: BeaconRepr>(
+    store: &ChainStore<B>,
+    round: u64,
+    beacon_id: &str,
+) -> Result<PublicRandResponse, StoreError> {
+    let beacon = if round == 0 {
+        store.last().await?
+    } else {
+        store.get(round).await?
+    };
+
+    Ok(PublicRandResponse {
+        round: beacon.round(),
+        signature: beacon.signature().to_vec(),
+        previous_signature: beacon.prev_signature().unwrap_or_default().to_vec(),
+        randomness: beacon.randomness().to_vec(),
+        metadata: Some(Metadata::with_id(beacon_id.to_string())),
+    })
+}
+
 /// Top-level function of chain module.
 ///
 /// Node can be started as fresh [`run_chain_default`] or with DKG setup [`run_chain`].
 /// Outputs with `Ok(None)` indicate graceful shutdown.
+#[allow(clippy::too_many_arguments)]
 pub fn init_chain<S: Scheme, B: BeaconRepr>(
     is_fresh_run: bool,
     fs: FileStore,
     private_listen: String,
     pool: PoolSender,
+    events: EventSender,
+    clock: SharedClock,
+    sync_memory: SyncMemory,
     id: String,
     our_addres: Address,
+    shadow: bool,
+    storage: StorageMode,
     t: &TaskTracker,
 ) -> (mpsc::Sender<PartialMsg>, mpsc::Sender<ChainCmd>) {
     // #[hot]
@@ -837,10 +1349,10 @@ pub fn init_chain<S: Scheme, B: BeaconRepr>(
     let (tx_cmd, rx_cmd) = mpsc::channel::<ChainCmd>(2);
 
     // Notification channel for signals delayed by catchup period.
-    let (tx_catchup, rx_catchup) = mpsc::channel::<()>(1);
+    let (tx_catchup, rx_catchup) = mpsc::channel::<u64>(1);
 
     // Channel for resyncing beacons.
-    let (tx_resync, rx_resync) = mpsc::channel::<BeaconPacket>(64);
+    let (tx_resync, rx_resync) = mpsc::channel::<Buffered<BeaconPacket>>(64);
 
     let chan = Channels {
         rx_partial,
@@ -853,7 +1365,7 @@ pub fn init_chain<S: Scheme, B: BeaconRepr>(
     };
 
     t.spawn(async move {
-        let store = match ChainStore::start(fs.chain_store_path(), id.clone()).await {
+        let store = match ChainStore::start(fs.chain_store_path(), id.clone(), storage).await {
             Ok(store) => store,
             Err(err) => {
                 error!(
@@ -865,12 +1377,17 @@ pub fn init_chain<S: Scheme, B: BeaconRepr>(
 
         let inner = ChainConfig {
             chan,
+            keys: None,
             pool,
+            events,
+            clock,
+            sync_memory,
             fs,
             store,
             private_listen,
             beacon_id: id,
             our_addres,
+            shadow,
         };
 
         // Loaded fresh node.
@@ -949,13 +1466,26 @@ fn wait_last_round<B: BeaconRepr>(
 
 /// Mitigates non-graceful transition corner case, where DKG output is already received
 /// but node reloaded before transition time.
-async fn check_transition(period: Seconds, transition_time: u64, l: &Span) {
+async fn check_transition(period: Seconds, transition_time: u64, clock: &dyn Clock, l: &Span) {
     let epoch_last_round = transition_time - u64::from(period.get_value());
-    let time_now = time::time_now().as_secs();
+    let time_now = clock.now().as_secs();
     if time_now < epoch_last_round {
         // Adding 1 second to skip last round tick of finished epoch.
         let delta = epoch_last_round - time_now + 1;
         warn!(parent: l, "non-graceful transition? time_now: {time_now}, transition_time: {transition_time}, sleeping {delta}s");
-        sleep(Duration::from_secs(delta)).await;
+        clock
+            .sleep_until(Duration::from_secs(epoch_last_round + 1))
+            .await;
+    }
+}
+
+/// Records time to threshold, catchup rounds are late by design and not recorded.
+fn record_threshold_delay<S: Scheme, B: BeaconRepr>(
+    reg: &mut Registry<S, B>,
+    round: u64,
+    delay: u64,
+) {
+    if round >= reg.current_round() {
+        reg.timings().observe_threshold(delay);
     }
 }
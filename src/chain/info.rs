@@ -5,7 +5,6 @@ use crate::protobuf::drand::Metadata;
 
 use energon::points::KeyPoint;
 use energon::traits::Affine;
-use sha2::Digest;
 use tracing::error;
 
 /// Public information that is necessary for a client to verify any beacon present in a randomness chain.
@@ -19,6 +18,10 @@ pub struct ChainInfo<S: Scheme> {
 }
 
 impl<S: Scheme> ChainInfo<S> {
+    /// Decodes chain info of the beacon id, returns `None` if the scheme differs from `S`, the key
+    /// is malformed or genesis time is not positive.
+    ///
+    /// Hash of the packet is not checked, see [`crate::client::verify_chain_info`] and [`Self::hash`].
     pub fn from_packet(packet: &ChainInfoPacket, id: String) -> Option<Self> {
         if S::ID != packet.scheme_id {
             error!(
@@ -62,40 +65,26 @@ impl<S: Scheme> ChainInfo<S> {
                 node_version: None,
                 beacon_id: self.beacon_id.to_string(),
                 chain_hash: hash,
+                features: vec![],
             }),
         };
 
         Some(info)
     }
 
+    /// Returns canonical hash of the chain, see [`crate::verify::chain_hash`].
     pub fn hash(&self) -> Option<[u8; 32]> {
         let pk_bytes = self.public_key.serialize().ok()?;
+        let genesis_time = i64::try_from(self.genesis_time).ok()?;
 
-        let mut h = sha2::Sha256::new();
-        h.update(self.period.get_value().to_be_bytes());
-        h.update(self.genesis_time.to_be_bytes());
-        h.update(&pk_bytes);
-        h.update(&self.genesis_seed);
-        if !crate::core::beacon::is_default_beacon_id(&self.beacon_id) {
-            h.update(self.beacon_id.as_bytes());
-        }
-
-        Some(h.finalize().into())
-    }
-}
-
-/// Returns canonical hash of protobuf encoded info packet for given beacon ID.
-pub fn hash_packet(proto: &ChainInfoPacket, beacon_id: &str) -> [u8; 32] {
-    let mut h = sha2::Sha256::new();
-    h.update(proto.period.to_be_bytes());
-    h.update(proto.genesis_time.to_be_bytes());
-    h.update(&proto.public_key);
-    h.update(&proto.group_hash);
-
-    if !crate::core::beacon::is_default_beacon_id(beacon_id) {
-        h.update(beacon_id.as_bytes());
+        Some(crate::verify::chain_hash(
+            self.period.get_value(),
+            genesis_time,
+            &pk_bytes,
+            &self.genesis_seed,
+            &self.beacon_id,
+        ))
     }
-    h.finalize().into()
 }
 
 /// Returns `None` if genesis time is equal or less then zero.
@@ -108,3 +97,124 @@ fn check_genesis_time(genesis_time: i64) -> Option<u64> {
         None
     }
 }
+
+/// Group public keys per epoch, ordered by the first round of epoch.
+///
+/// Resharing preserves the distributed public key, a new entry is registered only
+/// if key of the next epoch differs, so beacons are verified with the key active at their round.
+#[derive(Clone)]
+pub struct KeySchedule<S: Scheme> {
+    epochs: Vec<(u64, KeyPoint<S>)>,
+}
+
+impl<S: Scheme> KeySchedule<S> {
+    pub fn new(public_key: KeyPoint<S>) -> Self {
+        Self {
+            epochs: vec![(0, public_key)],
+        }
+    }
+
+    /// Registers public key starting from `first_round`, returns `true` if the key has been changed.
+    ///
+    /// Entries at or above `first_round` are replaced.
+    pub fn add_transition(&mut self, first_round: u64, public_key: KeyPoint<S>) -> bool {
+        if *self.key_for(first_round) == public_key {
+            return false;
+        }
+        self.epochs.retain(|(round, _)| *round < first_round);
+        self.epochs.push((first_round, public_key));
+
+        true
+    }
+
+    /// Returns public key of the epoch for given round.
+    pub fn key_for(&self, round: u64) -> &KeyPoint<S> {
+        self.epochs
+            .iter()
+            .rev()
+            .find(|(first_round, _)| *first_round <= round)
+            .map_or(&self.epochs[0].1, |(_, key)| key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::beacon::DEFAULT_BEACON_ID;
+    use crate::key::keys::Pair;
+    use crate::net::utils::Address;
+    use energon::drand::schemes::DefaultScheme;
+
+    #[test]
+    fn key_schedule() {
+        let key = |_| {
+            Pair::<DefaultScheme>::generate(Address::default())
+                .unwrap()
+                .public_identity()
+                .key()
+                .clone()
+        };
+        let keys: Vec<KeyPoint<DefaultScheme>> = (0..3).map(key).collect();
+
+        let mut schedule = KeySchedule::new(keys[0].clone());
+        // Reshare: same key is not registered.
+        assert!(!schedule.add_transition(10, keys[0].clone()));
+        assert!(schedule.epochs.len() == 1);
+
+        assert!(schedule.add_transition(20, keys[1].clone()));
+        assert!(schedule.add_transition(30, keys[2].clone()));
+        assert!(*schedule.key_for(0) == keys[0]);
+        assert!(*schedule.key_for(19) == keys[0]);
+        assert!(*schedule.key_for(20) == keys[1]);
+        assert!(*schedule.key_for(29) == keys[1]);
+        assert!(*schedule.key_for(30) == keys[2]);
+        assert!(*schedule.key_for(u64::MAX) == keys[2]);
+
+        // Transition rounds above are replaced.
+        assert!(schedule.add_transition(25, keys[0].clone()));
+        assert!(*schedule.key_for(30) == keys[0]);
+        assert!(*schedule.key_for(24) == keys[1]);
+    }
+
+    #[test]
+    fn info_serde_roundtrip() {
+        use serde_json::Value;
+
+        let packet = ChainInfoPacket {
+            public_key: vec![0x83, 0xcf],
+            period: 3,
+            genesis_time: 1692803367,
+            hash: vec![0x52, 0xdb],
+            group_hash: vec![0xf4, 0x77],
+            scheme_id: "bls-unchained-g1-rfc9380".into(),
+            metadata: Some(Metadata {
+                beacon_id: "quicknet".into(),
+                ..Default::default()
+            }),
+        };
+        let json = serde_json::to_value(&packet).unwrap();
+        assert_eq!(
+            json,
+            serde_json::from_str::<Value>(
+                r#"{"public_key":"83cf","period":3,"genesis_time":1692803367,"hash":"52db",
+                    "groupHash":"f477","schemeID":"bls-unchained-g1-rfc9380",
+                    "metadata":{"beaconID":"quicknet"}}"#
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            serde_json::from_value::<ChainInfoPacket>(json.clone()).unwrap(),
+            packet
+        );
+
+        // Relays omit beacon id of the default chain.
+        let mut json = json;
+        json.as_object_mut().unwrap().remove("metadata");
+        let default: ChainInfoPacket = serde_json::from_value(json).unwrap();
+        assert_eq!(default.metadata.unwrap().beacon_id, DEFAULT_BEACON_ID);
+
+        let invalid = r#"{"public_key":"zz","period":3,"genesis_time":1,"hash":"",
+            "groupHash":"","schemeID":""}"#;
+        assert!(serde_json::from_str::<ChainInfoPacket>(invalid).is_err());
+    }
+}
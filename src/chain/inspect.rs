@@ -0,0 +1,319 @@
+//! Offline inspection of chain store, used by `drand util db-inspect` and by control RPC
+//! `StoreStats` of `drand util store-stats`.
+//!
+//! Store is opened read-only and can be inspected while the daemon is running. Scheme of the
+//! chain is detected from the table layout: chained stores have `previous_sig` column.
+use super::format::BeaconFormat;
+use super::store::checksum;
+use super::store::DB_NAME;
+
+use crate::verify::randomness;
+
+use rusqlite::Connection;
+use rusqlite::OpenFlags;
+use serde_json::json;
+use std::fmt::Display;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[derive(thiserror::Error, Debug)]
+pub enum InspectError {
+    #[error("chain store not found: {0}")]
+    NotFound(PathBuf),
+    #[error("sqlite: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid round range '{0}', expected e.g. '1,5-10'")]
+    InvalidRange(String),
+}
+
+/// Summary of stored rounds.
+#[derive(Debug, PartialEq)]
+pub struct StoreStats {
+    pub chained: bool,
+    /// Amount of stored beacons, including genesis.
+    pub stored: u64,
+    pub first: Option<u64>,
+    pub last: Option<u64>,
+    /// Missing ranges `[from, to]` in between of stored rounds.
+    pub missing: Vec<(u64, u64)>,
+    /// Size of database files, including WAL.
+    pub size_bytes: u64,
+    /// Size of WAL which is not yet checkpointed into the database file.
+    pub wal_bytes: u64,
+    /// Size of free pages which are reclaimed by `VACUUM`.
+    pub free_bytes: u64,
+}
+
+impl StoreStats {
+    /// Storage backend of chain stores.
+    pub const BACKEND: &'static str = "sqlite";
+}
+
+impl Display for StoreStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let scheme = if self.chained { "chained" } else { "unchained" };
+        writeln!(f, "scheme:   {scheme}")?;
+        writeln!(f, "stored:   {}", self.stored)?;
+        match (self.first, self.last) {
+            (Some(first), Some(last)) => writeln!(f, "rounds:   {first} - {last}")?,
+            _ => writeln!(f, "rounds:   none")?,
+        }
+        writeln!(f, "backend:  {}", Self::BACKEND)?;
+        writeln!(f, "size:     {} bytes", self.size_bytes)?;
+        writeln!(
+            f,
+            "compact:  {} bytes in WAL, {} bytes free",
+            self.wal_bytes, self.free_bytes
+        )?;
+        let total: u64 = self.missing.iter().map(|(from, to)| to - from + 1).sum();
+        write!(f, "missing:  {total} rounds")?;
+        for (from, to) in &self.missing {
+            write!(f, "\n  {from} - {to}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Opens chain store at given `db` folder in read-only mode.
+pub fn open(folder: &Path) -> Result<Connection, InspectError> {
+    let path = folder.join(DB_NAME);
+    if !path.try_exists()? {
+        return Err(InspectError::NotFound(path));
+    }
+
+    Ok(Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY,
+    )?)
+}
+
+pub fn stats(folder: &Path) -> Result<StoreStats, InspectError> {
+    let conn = open(folder)?;
+    let (stored, first, last) = conn.query_row(
+        "SELECT COUNT(*), MIN(round), MAX(round) FROM beacons",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+    let file_len = |suffix| {
+        std::fs::metadata(folder.join(format!("{DB_NAME}{suffix}"))).map_or(0, |meta| meta.len())
+    };
+    let wal_bytes = file_len("-wal");
+    let free_bytes: u64 = conn.query_row(
+        "SELECT f.freelist_count * s.page_size FROM pragma_freelist_count AS f, pragma_page_size AS s",
+        [],
+        |row| row.get(0),
+    )?;
+
+    Ok(StoreStats {
+        chained: is_chained(&conn)?,
+        stored,
+        first,
+        last,
+        missing: missing_ranges(&conn)?,
+        size_bytes: file_len("") + wal_bytes,
+        wal_bytes,
+        free_bytes,
+    })
+}
+
+fn is_chained(conn: &Connection) -> Result<bool, rusqlite::Error> {
+    has_column(conn, "previous_sig")
+}
+
+fn has_column(conn: &Connection, name: &str) -> Result<bool, rusqlite::Error> {
+    conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('beacons') WHERE name = ?1",
+        [name],
+        |row| row.get(0),
+    )
+}
+
+/// Returns name of the column if it exists, `NULL` otherwise.
+fn column_or_null<'a>(conn: &Connection, name: &'a str) -> Result<&'a str, rusqlite::Error> {
+    Ok(if has_column(conn, name)? {
+        name
+    } else {
+        "NULL"
+    })
+}
+
+/// Returns missing ranges `[from, to]` in between of stored rounds.
+pub(super) fn missing_ranges(conn: &Connection) -> Result<Vec<(u64, u64)>, rusqlite::Error> {
+    conn.prepare(
+        "SELECT round + 1, next - 1 FROM (
+            SELECT round, LEAD(round) OVER (ORDER BY round) AS next FROM beacons
+        ) WHERE next > round + 1",
+    )?
+    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+    .collect()
+}
+
+/// Parses comma-separated rounds and inclusive ranges, e.g. `1,5-10`.
+pub fn parse_ranges(s: &str) -> Result<Vec<(u64, u64)>, InspectError> {
+    let invalid = || InspectError::InvalidRange(s.to_string());
+    s.split(',')
+        .map(|part| {
+            let (from, to) = part.trim().split_once('-').unwrap_or((part, part));
+            let from: u64 = from.trim().parse().map_err(|_| invalid())?;
+            let to: u64 = to.trim().parse().map_err(|_| invalid())?;
+            if from > to {
+                return Err(invalid());
+            }
+            Ok((from, to))
+        })
+        .collect()
+}
+
+/// Writes stored beacons within `ranges` as JSON lines, returns amount of written beacons.
+///
+/// Beacons are written in format of HTTP relays with an extra `checksum` field, which is one
+/// of `ok`, `mismatch` or `absent` (record stored before checksums were introduced). If the
+/// `format` is given, beacons are written in it instead, raw randomness without separators.
+pub fn extract(
+    folder: &Path,
+    ranges: &[(u64, u64)],
+    format: Option<BeaconFormat>,
+    out: &mut impl Write,
+) -> Result<u64, InspectError> {
+    let conn = open(folder)?;
+    // Stores are opened read-only and are not migrated: columns added by later versions might
+    // be absent, checksums are stored since per-record checksums and randomness since storage
+    // modes were introduced.
+    let checksum_column = column_or_null(&conn, "checksum")?;
+    let randomness_column = column_or_null(&conn, "randomness")?;
+    let sql = if is_chained(&conn)? {
+        format!("SELECT round, signature, previous_sig, {checksum_column}, {randomness_column},
+            CASE WHEN length(previous_sig) = 0 THEN (SELECT p.signature FROM beacons AS p WHERE p.round = beacons.round - 1) END
+            FROM beacons WHERE round >= ?1 AND round <= ?2 ORDER BY round")
+    } else {
+        format!("SELECT round, signature, NULL, {checksum_column}, {randomness_column}, NULL FROM beacons WHERE round >= ?1 AND round <= ?2 ORDER BY round")
+    };
+    let mut stmt = conn.prepare(&sql)?;
+
+    let mut written = 0;
+    for (from, to) in ranges {
+        let mut rows = stmt.query([from, to])?;
+        while let Some(row) = rows.next()? {
+            let round: u64 = row.get(0)?;
+            let signature: Vec<u8> = row.get(1)?;
+            let previous: Option<Vec<u8>> = row.get(2)?;
+            let stored: Option<Vec<u8>> = row.get(3)?;
+            let stored_randomness: Option<Vec<u8>> = row.get(4)?;
+            // Previous signature of derived records is the signature of the previous round.
+            let previous = match previous {
+                Some(p) if p.is_empty() && round > 0 => {
+                    Some(row.get::<_, Option<Vec<u8>>>(5)?.unwrap_or_default())
+                }
+                previous => previous,
+            };
+            let expected = checksum(
+                round,
+                &signature,
+                previous.as_deref().unwrap_or_default(),
+                stored_randomness.as_deref().unwrap_or_default(),
+            );
+            let status = match stored {
+                None => "absent",
+                Some(c) if c == expected => "ok",
+                Some(_) => "mismatch",
+            };
+            if let Some(format) = format {
+                let previous = previous.unwrap_or_default();
+                out.write_all(&format.encode(
+                    round,
+                    &randomness(&signature),
+                    &signature,
+                    &previous,
+                ))?;
+                if format.is_text() {
+                    writeln!(out)?;
+                }
+                written += 1;
+                continue;
+            }
+            let mut line = json!({
+                "round": round,
+                "randomness": hex::encode(randomness(&signature)),
+                "signature": hex::encode(&signature),
+                "checksum": status,
+            });
+            if let Some(previous) = previous {
+                line["previous_signature"] = hex::encode(previous).into();
+            }
+            writeln!(out, "{line}")?;
+            written += 1;
+        }
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::store::BeaconRepr;
+    use super::super::store::ChainStore;
+    use super::super::store::StorageMode;
+    use super::super::ChainedBeacon;
+    use super::*;
+    use crate::protobuf::drand::BeaconPacket;
+
+    #[tokio::test]
+    async fn inspect_store() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path();
+        assert!(matches!(stats(path), Err(InspectError::NotFound(_))));
+
+        let store = ChainStore::<ChainedBeacon>::start(
+            path.to_path_buf(),
+            "default".into(),
+            StorageMode::Full,
+        )
+        .await
+        .unwrap();
+        for round in [0, 1, 2, 5, 6, 9] {
+            let packet = BeaconPacket {
+                previous_signature: vec![1].into(),
+                round,
+                signature: vec![2, 3].into(),
+                metadata: None,
+            };
+            store.put(ChainedBeacon::from_packet(packet)).await.unwrap();
+        }
+
+        let s = stats(path).unwrap();
+        assert!(s.chained);
+        assert_eq!((s.stored, s.first, s.last), (6, Some(0), Some(9)));
+        assert_eq!(s.missing, [(3, 4), (7, 8)]);
+        assert!(s.size_bytes >= s.wal_bytes && s.wal_bytes > 0);
+        assert_eq!(s.free_bytes, 0);
+
+        let ranges = parse_ranges("1,4-5").unwrap();
+        assert_eq!(ranges, [(1, 1), (4, 5)]);
+        assert!(parse_ranges("5-4").is_err());
+        let mut out = vec![];
+        assert_eq!(extract(path, &ranges, None, &mut out).unwrap(), 2);
+        let line: serde_json::Value =
+            serde_json::from_slice(out.split(|b| *b == b'\n').next().unwrap()).unwrap();
+        assert_eq!(
+            line,
+            json!({
+                "round": 1,
+                "randomness": "ee9040f65c341855e070ff438eb0ea9d5b831b2a2c270fb7ef592d750408e3b3",
+                "signature": "0203",
+                "previous_signature": "01",
+                "checksum": "ok"
+            })
+        );
+
+        let mut out = vec![];
+        extract(path, &[(1, 1)], Some(BeaconFormat::Hex), &mut out).unwrap();
+        assert_eq!(
+            out,
+            b"ee9040f65c341855e070ff438eb0ea9d5b831b2a2c270fb7ef592d750408e3b3\n"
+        );
+    }
+}
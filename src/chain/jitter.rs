@@ -0,0 +1,192 @@
+//! Round scheduling instrumentation.
+//!
+//! Records how late partial was produced and how late aggregation finished
+//! relative to the round time, helping to diagnose overloaded nodes.
+use crate::net::utils::Seconds;
+
+use std::fmt::Write;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+/// Upper bounds of histogram buckets in milliseconds, last bucket is unbounded.
+const BUCKETS_MS: [u64; 10] = [10, 50, 100, 250, 500, 1000, 2500, 5000, 10_000, 30_000];
+
+/// Delay above this part of period (in percents) is reported as nearing the period budget.
+pub const PERIOD_BUDGET_WARN_PERCENT: u64 = 80;
+
+/// Histogram with fixed buckets, see [`BUCKETS_MS`].
+#[derive(Default)]
+pub struct Histogram {
+    counts: [u64; BUCKETS_MS.len() + 1],
+    total: u64,
+    sum: u64,
+}
+
+impl Histogram {
+    pub fn observe(&mut self, value_ms: u64) {
+        let idx = BUCKETS_MS
+            .iter()
+            .position(|bound| value_ms <= *bound)
+            .unwrap_or(BUCKETS_MS.len());
+        self.counts[idx] += 1;
+        self.total += 1;
+        self.sum = self.sum.saturating_add(value_ms);
+    }
+
+    /// Returns upper bound of the bucket containing given percentile.
+    /// Unbounded bucket is reported as [`u64::MAX`], empty histogram as zero.
+    pub fn percentile(&self, percent: u64) -> u64 {
+        if self.total == 0 {
+            return 0;
+        }
+        let rank = (self.total * percent).div_ceil(100).max(1);
+        let mut cumulative = 0;
+        for (idx, count) in self.counts.iter().enumerate() {
+            cumulative += count;
+            if cumulative >= rank {
+                return BUCKETS_MS.get(idx).copied().unwrap_or(u64::MAX);
+            }
+        }
+
+        u64::MAX
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Writes samples of the histogram in Prometheus text exposition format, header is not included.
+    pub fn write_samples(&self, m: &mut String, name: &str, beacon_id: &str) {
+        let mut cumulative = 0;
+        for (bound, count) in BUCKETS_MS.iter().zip(self.counts) {
+            cumulative += count;
+            let _ = writeln!(
+                m,
+                "{name}_bucket{{beacon_id=\"{beacon_id}\",le=\"{bound}\"}} {cumulative}"
+            );
+        }
+        let _ = writeln!(
+            m,
+            "{name}_bucket{{beacon_id=\"{beacon_id}\",le=\"+Inf\"}} {}",
+            self.total
+        );
+        let _ = writeln!(m, "{name}_sum{{beacon_id=\"{beacon_id}\"}} {}", self.sum);
+        let _ = writeln!(
+            m,
+            "{name}_count{{beacon_id=\"{beacon_id}\"}} {}",
+            self.total
+        );
+    }
+}
+
+/// Timings of a beacon process shared between chain handler and metrics.
+pub type SharedTimings = Arc<Mutex<RoundTimings>>;
+
+/// Delays of partial production and aggregation relative to round time.
+#[derive(Default)]
+pub struct RoundTimings {
+    pub partial: Histogram,
+    pub aggregation: Histogram,
+    /// Time to threshold: delay of the moment when threshold sigshares are verified.
+    pub threshold: Histogram,
+}
+
+impl RoundTimings {
+    /// Metric names and help lines of the histograms.
+    pub const METRICS: [(&str, &str); 3] = [
+        (
+            "drand_partial_delay_ms",
+            "Delay of partial production relative to round time.",
+        ),
+        (
+            "drand_aggregation_delay_ms",
+            "Delay of beacon aggregation relative to round time.",
+        ),
+        (
+            "drand_threshold_delay_ms",
+            "Delay of reaching threshold of verified partials relative to round time.",
+        ),
+    ];
+
+    /// Returns histograms in the order of [`Self::METRICS`].
+    pub fn histograms(&self) -> [&Histogram; 3] {
+        [&self.partial, &self.aggregation, &self.threshold]
+    }
+
+    /// Records partial delay, returns `true` if delay is nearing the period budget.
+    pub fn observe_partial(&mut self, delay_ms: u64, period: Seconds) -> bool {
+        self.partial.observe(delay_ms);
+        is_near_budget(delay_ms, period)
+    }
+
+    /// Records time to threshold for the round.
+    pub fn observe_threshold(&mut self, delay_ms: u64) {
+        self.threshold.observe(delay_ms);
+    }
+
+    /// Records aggregation delay, returns `true` if delay is nearing the period budget.
+    pub fn observe_aggregation(&mut self, delay_ms: u64, period: Seconds) -> bool {
+        self.aggregation.observe(delay_ms);
+        is_near_budget(delay_ms, period)
+    }
+}
+
+fn is_near_budget(delay_ms: u64, period: Seconds) -> bool {
+    delay_ms * 100 >= u64::from(period.get_value()) * 1000 * PERIOD_BUDGET_WARN_PERCENT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_percentiles() {
+        let mut h = Histogram::default();
+        assert_eq!(h.percentile(50), 0);
+
+        for v in [1, 5, 20, 30, 40, 70, 200, 600, 900, 40_000] {
+            h.observe(v);
+        }
+        assert_eq!(h.total(), 10);
+        assert_eq!(h.percentile(10), 10);
+        assert_eq!(h.percentile(50), 50);
+        assert_eq!(h.percentile(90), 1000);
+        assert_eq!(h.percentile(100), u64::MAX);
+    }
+
+    #[test]
+    fn histogram_samples() {
+        let mut h = Histogram::default();
+        for v in [5, 20, 40_000] {
+            h.observe(v);
+        }
+        let mut m = String::new();
+        h.write_samples(&mut m, "delay", "default");
+        let lines: Vec<&str> = m.lines().collect();
+        assert_eq!(lines.len(), BUCKETS_MS.len() + 3);
+        assert_eq!(lines[0], "delay_bucket{beacon_id=\"default\",le=\"10\"} 1");
+        assert_eq!(lines[1], "delay_bucket{beacon_id=\"default\",le=\"50\"} 2");
+        assert_eq!(
+            lines[9],
+            "delay_bucket{beacon_id=\"default\",le=\"30000\"} 2"
+        );
+        assert_eq!(
+            lines[10],
+            "delay_bucket{beacon_id=\"default\",le=\"+Inf\"} 3"
+        );
+        assert_eq!(lines[11], "delay_sum{beacon_id=\"default\"} 40025");
+        assert_eq!(lines[12], "delay_count{beacon_id=\"default\"} 3");
+    }
+
+    #[test]
+    fn period_budget() {
+        let period = Seconds::new(3);
+        let mut t = RoundTimings::default();
+        assert!(!t.observe_partial(100, period));
+        assert!(t.observe_partial(2400, period));
+        assert!(t.observe_aggregation(3100, period));
+
+        t.observe_threshold(400);
+        assert_eq!(t.threshold.percentile(50), 500);
+    }
+}
@@ -0,0 +1,258 @@
+//! Append-only Merkle tree over stored beacons, see RFC 6962 (Certificate Transparency).
+//!
+//! Leaf `i` commits to beacon of round `i + 1` as `sha256(0x00 || round || signature)`,
+//! where round is big-endian, genesis is not included. The tree is extended by chain store
+//! actor for rounds stored contiguously from round 1, so it is empty for stores synced from
+//! a checkpoint. Only hashes of perfect subtrees are persisted: they change once complete only if
+//! a stored beacon is repaired, root and inclusion proofs for any tree size are composed from them.
+use rusqlite::params;
+use rusqlite::Connection;
+use rusqlite::Error;
+use sha2::Digest;
+use sha2::Sha256;
+
+pub type Hash = [u8; 32];
+
+/// Inclusion proof of the round into tree of `tree_size` leaves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MerkleProof {
+    pub round: u64,
+    pub tree_size: u64,
+    pub root: Hash,
+    /// Audit path from leaf to root.
+    pub path: Vec<Hash>,
+}
+
+pub fn leaf_hash(round: u64, signature: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0]);
+    hasher.update(round.to_be_bytes());
+    hasher.update(signature);
+
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([1]);
+    hasher.update(left);
+    hasher.update(right);
+
+    hasher.finalize().into()
+}
+
+/// Largest power of two smaller than `n`, `n` should be greater than one.
+fn split(n: u64) -> u64 {
+    1 << (u64::BITS - 1 - (n - 1).leading_zeros())
+}
+
+/// Creates tree table if needed, returns amount of leaves.
+pub(super) fn open(conn: &Connection) -> Result<u64, Error> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS merkle (
+            level INTEGER NOT NULL,
+            idx INTEGER NOT NULL,
+            hash BLOB NOT NULL,
+            PRIMARY KEY (level, idx)
+        ) WITHOUT ROWID",
+        [],
+    )?;
+
+    conn.query_row("SELECT COUNT(*) FROM merkle WHERE level = 0", [], |row| {
+        row.get(0)
+    })
+}
+
+/// Appends leaf of index `size`, completed subtrees are stored within the same transaction.
+pub(super) fn append(conn: &Connection, size: u64, leaf: Hash) -> Result<(), Error> {
+    let tr = conn.unchecked_transaction()?;
+    {
+        let mut insert =
+            tr.prepare_cached("INSERT INTO merkle (level, idx, hash) VALUES (?1, ?2, ?3)")?;
+        let (mut level, mut idx, mut hash) = (0, size, leaf);
+        insert.execute(params![level, idx, hash])?;
+        // Right child completes the parent subtree.
+        while idx & 1 == 1 {
+            hash = node_hash(&node(&tr, level, idx - 1)?, &hash);
+            level += 1;
+            idx >>= 1;
+            insert.execute(params![level, idx, hash])?;
+        }
+    }
+
+    tr.commit()
+}
+
+/// Replaces leaf of index `idx` in tree of `size` leaves, completed subtrees over it are rehashed.
+pub(super) fn replace(conn: &Connection, size: u64, idx: u64, leaf: Hash) -> Result<(), Error> {
+    let tr = conn.unchecked_transaction()?;
+    {
+        let mut update =
+            tr.prepare_cached("UPDATE merkle SET hash = ?3 WHERE level = ?1 AND idx = ?2")?;
+        let (mut level, mut idx, mut hash) = (0, idx, leaf);
+        update.execute(params![level, idx, hash])?;
+        // Parent subtree is stored only if complete.
+        while ((idx >> 1) + 1) << (level + 1) <= size {
+            let sibling = node(&tr, level, idx ^ 1)?;
+            hash = if idx & 1 == 1 {
+                node_hash(&sibling, &hash)
+            } else {
+                node_hash(&hash, &sibling)
+            };
+            level += 1;
+            idx >>= 1;
+            update.execute(params![level, idx, hash])?;
+        }
+    }
+
+    tr.commit()
+}
+
+/// Returns hash of perfect subtree of `2^level` leaves starting from leaf `idx * 2^level`.
+fn node(conn: &Connection, level: u32, idx: u64) -> Result<Hash, Error> {
+    conn.prepare_cached("SELECT hash FROM merkle WHERE level = ?1 AND idx = ?2")?
+        .query_row(params![level, idx], |row| row.get(0))
+}
+
+/// Returns hash of tree over `n` leaves starting from `start`, aligned as in RFC 6962.
+fn subtree(conn: &Connection, start: u64, n: u64) -> Result<Hash, Error> {
+    if n.is_power_of_two() {
+        let level = n.trailing_zeros();
+        return node(conn, level, start >> level);
+    }
+    let k = split(n);
+
+    Ok(node_hash(
+        &subtree(conn, start, k)?,
+        &subtree(conn, start + k, n - k)?,
+    ))
+}
+
+/// Returns inclusion proof of the round into tree of `tree_size` leaves.
+pub(super) fn proof(conn: &Connection, round: u64, tree_size: u64) -> Result<MerkleProof, Error> {
+    let mut path = vec![];
+    let (mut m, mut start, mut n) = (round - 1, 0, tree_size);
+    // Siblings are collected from root to leaf.
+    while n > 1 {
+        let k = split(n);
+        if m < k {
+            path.push(subtree(conn, start + k, n - k)?);
+            n = k;
+        } else {
+            path.push(subtree(conn, start, k)?);
+            (m, start, n) = (m - k, start + k, n - k);
+        }
+    }
+    path.reverse();
+
+    Ok(MerkleProof {
+        round,
+        tree_size,
+        root: subtree(conn, 0, tree_size)?,
+        path,
+    })
+}
+
+/// Verifies that `leaf` is included into tree of `tree_size` leaves with `root` at index `round - 1`.
+pub fn verify(leaf: &Hash, round: u64, tree_size: u64, path: &[Hash], root: &Hash) -> bool {
+    if round == 0 || round > tree_size {
+        return false;
+    }
+    let (mut fn_, mut sn) = (round - 1, tree_size - 1);
+    let mut hash = *leaf;
+    for p in path {
+        if sn == 0 {
+            return false;
+        }
+        if fn_ & 1 == 1 || fn_ == sn {
+            hash = node_hash(p, &hash);
+            while fn_ & 1 == 0 && fn_ != 0 {
+                fn_ >>= 1;
+                sn >>= 1;
+            }
+        } else {
+            hash = node_hash(&hash, p);
+        }
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+
+    sn == 0 && hash == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reference tree hash from RFC 6962 definition.
+    fn mth(leaves: &[Hash]) -> Hash {
+        if leaves.len() == 1 {
+            return leaves[0];
+        }
+        let k = usize::try_from(split(leaves.len() as u64)).unwrap();
+        node_hash(&mth(&leaves[..k]), &mth(&leaves[k..]))
+    }
+
+    #[test]
+    fn inclusion_proofs() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(open(&conn).unwrap(), 0);
+
+        let leaves: Vec<Hash> = (1..=13u64)
+            .map(|r| leaf_hash(r, &r.to_le_bytes()))
+            .collect();
+        for (i, leaf) in leaves.iter().enumerate() {
+            append(&conn, i as u64, *leaf).unwrap();
+        }
+        assert_eq!(open(&conn).unwrap(), 13);
+
+        for size in 1..=13u64 {
+            let root = mth(&leaves[..usize::try_from(size).unwrap()]);
+            for round in 1..=size {
+                let p = proof(&conn, round, size).unwrap();
+                assert_eq!(p.root, root);
+                let leaf = &leaves[usize::try_from(round - 1).unwrap()];
+                assert!(verify(leaf, round, size, &p.path, &root));
+                // Proof is bound to the leaf position.
+                if size > 1 {
+                    let other = round % size + 1;
+                    assert!(!verify(leaf, other, size, &p.path, &root));
+                }
+            }
+        }
+        assert!(!verify(&leaves[0], 0, 13, &[], &leaves[0]));
+    }
+
+    #[test]
+    fn replaced_leaves() {
+        let conn = Connection::open_in_memory().unwrap();
+        open(&conn).unwrap();
+
+        let mut leaves: Vec<Hash> = (1..=13u64)
+            .map(|r| leaf_hash(r, &r.to_le_bytes()))
+            .collect();
+        for (i, leaf) in leaves.iter().enumerate() {
+            append(&conn, i as u64, *leaf).unwrap();
+        }
+        // Rewrite range across complete and incomplete subtrees.
+        for round in 4..=13u64 {
+            let old = leaves[usize::try_from(round - 1).unwrap()];
+            let leaf = leaf_hash(round, &[0xff; 8]);
+            replace(&conn, 13, round - 1, leaf).unwrap();
+            leaves[usize::try_from(round - 1).unwrap()] = leaf;
+
+            let p = proof(&conn, round, 13).unwrap();
+            assert_eq!(p.root, mth(&leaves));
+            assert!(verify(&leaf, round, 13, &p.path, &p.root));
+            assert!(!verify(&old, round, 13, &p.path, &p.root));
+        }
+        for size in 1..=13u64 {
+            let root = mth(&leaves[..usize::try_from(size).unwrap()]);
+            for round in 1..=size {
+                let p = proof(&conn, round, size).unwrap();
+                let leaf = &leaves[usize::try_from(round - 1).unwrap()];
+                assert!(verify(leaf, round, size, &p.path, &root));
+            }
+        }
+    }
+}
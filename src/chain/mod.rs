@@ -1,26 +1,35 @@
+pub mod archive;
+mod audit;
 mod cache;
+mod catchup;
 mod epoch;
+pub mod equivocation;
+pub mod format;
 mod handler;
 mod info;
+pub mod inspect;
+pub mod jitter;
+pub mod merkle;
 mod registry;
+mod relay;
+mod shadow;
+mod skew;
 mod store;
 mod sync;
 mod ticker;
 pub mod time;
+#[cfg(test)]
+mod vectors;
 
 pub use handler::{init_chain, ChainCmd, ChainError};
-pub use store::{ChainedBeacon, StoreError, StoreStreamResponse, UnChainedBeacon};
-pub use sync::SyncError;
+#[cfg(fuzzing)]
+pub use info::ChainInfo;
+pub use relay::{run_relay, RelayChain, RelayConfig};
+#[cfg(feature = "bench")]
+#[allow(unused_imports, reason = "used by benchmarks of the library target")]
+pub(crate) use store::{BeaconRepr, ChainStore};
+pub use store::{ChainedBeacon, StorageMode, StoreError, StoreStreamResponse, UnChainedBeacon};
+pub use sync::{SyncError, VerifyMode, DEFAULT_SPOT_CHECK_EVERY};
 
-use energon::drand::traits::BeaconDigest;
-/// BLS signature check for aggregated or resynced beacons.
-/// Suitable for chained and unchained schemes.
-fn is_valid_signature<S: crate::key::Scheme>(
-    pub_key: &energon::points::KeyPoint<S>,
-    prev_sig: &[u8],
-    new_round: u64,
-    new_sig: &energon::points::SigPoint<S>,
-) -> bool {
-    let msg = S::Beacon::digest(prev_sig, new_round);
-    S::bls_verify(pub_key, new_sig, &msg).is_ok()
-}
+// BLS signature check for aggregated or resynced beacons.
+use crate::verify::verify_beacon as is_valid_signature;
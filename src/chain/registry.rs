@@ -1,16 +1,30 @@
 use super::cache::PartialCache;
+use super::catchup::CatchupTimer;
 use super::epoch::EpochConfig;
 use super::info::ChainInfo;
+use super::jitter::RoundTimings;
+use super::jitter::SharedTimings;
+use super::skew::ClockSkew;
 use super::store::BeaconRepr;
 use super::sync::HandleReSync;
+use super::sync::ResyncMetrics;
 use super::time;
+use super::time::SharedClock;
 use super::SyncError;
 use crate::key::Scheme;
+use crate::log::Throttle;
+use crate::net::protocol::Buffered;
+use crate::net::utils::Address;
 use crate::net::utils::Seconds;
 use crate::protobuf::drand::BeaconPacket;
+use crate::protobuf::drand::StatusResponse;
 
+use std::sync::Arc;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
 use std::time::Duration;
 use tokio::sync::mpsc;
+use tokio::sync::watch;
 use tokio::task::JoinHandle;
 use tracing::Span;
 
@@ -23,28 +37,39 @@ pub struct Registry<S: Scheme, B: BeaconRepr> {
     current_round: u64,
     /// Cache for partial packets and sigshares.
     p_cache: PartialCache<S>,
-    /// Always `true` if catchup has been triggered but its signal has not arrived yet.
-    catchup_enabled: bool,
-    /// Sender to be cloned for launching catchup task.
-    /// The task finishes once signal is sent after catchup delay.
-    tx_catchup: mpsc::Sender<()>,
+    /// Timer for catchup signals.
+    catchup: CatchupTimer,
     /// Sender to be cloned for launching resync task.
-    tx_resync: mpsc::Sender<BeaconPacket>,
+    tx_resync: mpsc::Sender<Buffered<BeaconPacket>>,
     /// Handle for resync task.
     h_resync: Option<HandleReSync>,
+    /// Last verified beacon of forced resync while it rewrites already stored rounds.
+    forced_resync: Option<B>,
+    /// Peers which stalled resync, ordered from the least to the most recently stalled.
+    demoted_peers: Vec<Address>,
+    /// Counters shared with resync tasks.
+    resync_metrics: Arc<ResyncMetrics>,
+    /// Limits logs of stored resynced beacons.
+    resync_log: Throttle,
+    /// Clock skew observed from partials of peers.
+    clock_skew: ClockSkew,
+    /// Delays of partial production and aggregation.
+    timings: SharedTimings,
+    clock: SharedClock,
 }
 
 impl<S: Scheme, B: BeaconRepr> Registry<S, B> {
     pub fn new(
         info: &ChainInfo<S>,
         latest_stored: B,
-        tx_catchup: mpsc::Sender<()>,
-        tx_resync: mpsc::Sender<BeaconPacket>,
+        tx_catchup: mpsc::Sender<u64>,
+        tx_resync: mpsc::Sender<Buffered<BeaconPacket>>,
         thr: usize,
+        clock: SharedClock,
         l_partial: Span,
     ) -> Self {
         let current_round = time::current_round(
-            time::time_now().as_secs(),
+            clock.now().as_secs(),
             info.period.get_value(),
             info.genesis_time,
         );
@@ -54,10 +79,16 @@ impl<S: Scheme, B: BeaconRepr> Registry<S, B> {
             latest_stored,
             current_round,
             p_cache,
-            catchup_enabled: false,
-            tx_catchup,
+            catchup: CatchupTimer::new(tx_catchup, clock.clone()),
             tx_resync,
             h_resync: None,
+            forced_resync: None,
+            demoted_peers: vec![],
+            resync_metrics: Arc::default(),
+            resync_log: Throttle::new(),
+            clock_skew: ClockSkew::default(),
+            timings: SharedTimings::default(),
+            clock,
         }
     }
 
@@ -68,7 +99,7 @@ impl<S: Scheme, B: BeaconRepr> Registry<S, B> {
         }
     }
 
-    pub fn get_tx_resync(&self) -> mpsc::Sender<BeaconPacket> {
+    pub fn get_tx_resync(&self) -> mpsc::Sender<Buffered<BeaconPacket>> {
         self.tx_resync.clone()
     }
 
@@ -85,31 +116,82 @@ impl<S: Scheme, B: BeaconRepr> Registry<S, B> {
         &mut self,
         period: Seconds,
         handle: JoinHandle<Result<(), SyncError>>,
+        peer: watch::Receiver<Option<Address>>,
     ) {
-        self.h_resync = Some(HandleReSync::new(period, handle));
+        self.h_resync = Some(HandleReSync::new(period, handle, peer, self.clock.clone()));
+    }
+
+    /// Demotes the peer which stalled previous resync task, if any.
+    /// Returns the demoted peer.
+    pub fn demote_stalled_peer(&mut self) -> Option<Address> {
+        let peer = self.h_resync.as_ref()?.stalled_peer()?;
+        self.demoted_peers.retain(|p| *p != peer);
+        self.demoted_peers.push(peer.clone());
+
+        Some(peer)
+    }
+
+    /// Returns the peer of running resync task, if any.
+    pub fn resync_peer(&self) -> Option<Address> {
+        self.h_resync.as_ref()?.peer()
+    }
+
+    pub fn demoted_peers(&self) -> &[Address] {
+        &self.demoted_peers
+    }
+
+    pub fn resync_metrics(&self) -> &Arc<ResyncMetrics> {
+        &self.resync_metrics
+    }
+
+    pub fn resync_log(&mut self) -> &mut Throttle {
+        &mut self.resync_log
+    }
+
+    pub fn clock_skew_mut(&mut self) -> &mut ClockSkew {
+        &mut self.clock_skew
+    }
+
+    pub fn timings(&self) -> MutexGuard<'_, RoundTimings> {
+        self.timings.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns timings shared with metrics endpoint.
+    pub fn shared_timings(&self) -> SharedTimings {
+        self.timings.clone()
+    }
+
+    /// Returns status of the chain for given latest stored round.
+    pub fn status(&self, latest_stored_round: u64) -> StatusResponse {
+        let timings = self.timings();
+        StatusResponse {
+            clock_skew_ms: self.clock_skew.estimate().unwrap_or_default(),
+            clock_skewed: self.clock_skew.is_skewed(),
+            partial_delay_p50_ms: timings.partial.percentile(50),
+            partial_delay_p99_ms: timings.partial.percentile(99),
+            aggregation_delay_p50_ms: timings.aggregation.percentile(50),
+            aggregation_delay_p99_ms: timings.aggregation.percentile(99),
+            threshold_delay_p50_ms: timings.threshold.percentile(50),
+            threshold_delay_p99_ms: timings.threshold.percentile(99),
+            ..self.resync_metrics.status(latest_stored_round)
+        }
     }
 
     /// Spawns a task to send a single catch-up signal to the main chain logic.
     /// The registry prevents spawning multiple tasks if the previous signal has not been received.
     pub fn start_catchup(&mut self, catchup_period: Duration) {
-        if !self.catchup_enabled {
-            self.catchup_enabled = true;
-
-            tokio::task::spawn({
-                let tx = self.tx_catchup.clone();
-                let sleep = tokio::time::sleep(catchup_period);
-
-                async move {
-                    sleep.await;
-                    let _ = tx.send(()).await;
-                }
-            });
-        }
+        self.catchup.start(catchup_period);
+    }
+
+    /// Restarts catchup delay, pending signal is discarded (see: [`CatchupTimer::restart`]).
+    pub fn restart_catchup(&mut self, catchup_period: Duration) {
+        self.catchup.restart(catchup_period);
     }
 
     /// Catchup can be enabled again after the signal is received.
-    pub fn catchup_signal_received(&mut self) {
-        self.catchup_enabled = false;
+    /// Returns `false` if signal is outdated and should be ignored.
+    pub fn catchup_signal_received(&mut self, generation: u64) -> bool {
+        self.catchup.signal_received(generation)
     }
 
     pub fn new_round(&mut self, new_round: u64) {
@@ -148,5 +230,20 @@ impl<S: Scheme, B: BeaconRepr> Registry<S, B> {
 
     pub fn stop_resync(&mut self) {
         self.h_resync = None;
+        self.forced_resync = None;
+    }
+
+    /// Resynced beacons are verified from `prev` and rewrite stored rounds up to the latest stored one.
+    pub fn start_forced_resync(&mut self, prev: B) {
+        self.forced_resync = Some(prev);
+    }
+
+    /// Returns the last verified beacon of forced resync, `None` once it reaches the latest stored round.
+    pub fn forced_resync(&self) -> Option<&B> {
+        self.forced_resync.as_ref()
+    }
+
+    pub fn update_forced_resync(&mut self, prev: Option<B>) {
+        self.forced_resync = prev;
     }
 }
@@ -0,0 +1,454 @@
+//! Relay profile of the daemon, see `drand relay`.
+//!
+//! Relay has no keypair and takes no part in DKG: the chain is followed from sync nodes forever
+//! and served over the HTTP API. Once per round a follow request up to the current round is
+//! processed by [`DefaultSyncer`], the same way as `drand sync --follow` of a node without DKG.
+//!
+//! A single relay follows any amount of chains, e.g. `default` and `quicknet` networks, each into
+//! its own store. Chains are served by one HTTP API and addressed by chain hash in the path.
+use super::audit::AuditMetrics;
+use super::audit::Auditor;
+use super::handler::public_rand;
+use super::store::BeaconRepr;
+use super::store::ChainStore;
+use super::store::ChainedBeacon;
+use super::store::StorageMode;
+use super::store::UnChainedBeacon;
+use super::sync::chain_info_from_request;
+use super::sync::parse_nodes;
+use super::sync::start_follow_chain;
+use super::sync::DefaultSyncer;
+use super::sync::DefaultSyncerConfig;
+use super::sync::SyncError;
+use super::time;
+
+use crate::core::beacon::DEFAULT_BEACON_ID;
+use crate::core::events::EventSender;
+use crate::key::Scheme;
+use crate::net::http_api;
+use crate::net::http_api::Backend;
+use crate::net::http_api::HttpConfig;
+use crate::net::protocol::SyncMemory;
+use crate::net::relay::HttpRelay;
+use crate::net::utils::Address;
+use crate::net::utils::ToStatus;
+use crate::protobuf::drand::ChainInfoPacket;
+use crate::protobuf::drand::Metadata;
+use crate::protobuf::drand::PublicRandResponse;
+use crate::protobuf::drand::StartSyncRequest;
+
+use energon::drand::schemes::DefaultScheme;
+use energon::drand::schemes::SigsOnG1Scheme;
+use energon::drand::schemes::UnchainedScheme;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tonic::Status;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+use tracing::Span;
+
+/// Delay after the start of a round before its beacon is requested, chain nodes need time
+/// to aggregate it.
+const ROUND_DELAY: Duration = Duration::from_secs(1);
+
+pub struct RelayConfig {
+    pub chains: Vec<RelayChain>,
+    pub listen: Address,
+    pub http: HttpConfig,
+    /// Interval in rounds between audits of upstream nodes, 0 disables auditing.
+    pub audit_every: u64,
+    /// Memory of beacons buffered by follow streams of all chains.
+    pub sync_memory: SyncMemory,
+}
+
+/// Followed chain of the relay.
+pub struct RelayChain {
+    /// Existing folder of the chain store.
+    pub store_path: PathBuf,
+    /// Follow request, `up_to` is ignored. Sync nodes are shared by all chains, see [`chain_nodes`].
+    pub request: StartSyncRequest,
+}
+
+impl RelayChain {
+    fn beacon_id(&self) -> &str {
+        self.request
+            .metadata
+            .as_ref()
+            .map_or("", |m| m.beacon_id.as_str())
+    }
+
+    fn chain_hash(&self) -> String {
+        self.request
+            .metadata
+            .as_ref()
+            .map(|m| hex::encode(&m.chain_hash))
+            .unwrap_or_default()
+    }
+}
+
+/// Follows the chains and serves them until the token is cancelled.
+pub async fn run_relay(
+    config: RelayConfig,
+    token: CancellationToken,
+    tracker: TaskTracker,
+) -> Result<(), SyncError> {
+    let hashes: Vec<String> = config.chains.iter().map(RelayChain::chain_hash).collect();
+    let mut backends = RelayBackends::default();
+    let mut followers = JoinSet::new();
+    let events = EventSender::new();
+    // Relay has no control server to stream events, alerts are written to log.
+    let mut rx_events = events.subscribe();
+    tracker.spawn(async move {
+        loop {
+            match rx_events.recv().await {
+                Ok(e) => warn!(
+                    "event {}: beacon id {}, round {}, {}",
+                    e.kind, e.beacon_id, e.round, e.detail
+                ),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    for mut chain in config.chains {
+        let hash = chain.chain_hash();
+        let beacon_id = chain.beacon_id().to_string();
+        chain.request.nodes = chain_nodes(&chain.request.nodes, &beacon_id, &hash, &hashes);
+        let l = tracing::info_span!("", relay = beacon_id);
+        // Scheme of the chain is not known until chain info is fetched.
+        let packet = chain_info_from_request(&chain.request, &beacon_id, &l).await?;
+        let audit = (config.audit_every > 0).then(|| (events.clone(), config.audit_every));
+        let memory = config.sync_memory.clone();
+
+        let (backend, metrics) = match packet.scheme_id.as_str() {
+            DefaultScheme::ID => {
+                start::<DefaultScheme, ChainedBeacon>(
+                    chain,
+                    audit,
+                    memory,
+                    &mut followers,
+                    &token,
+                    l,
+                )
+                .await?
+            }
+            UnchainedScheme::ID => {
+                start::<UnchainedScheme, UnChainedBeacon>(
+                    chain,
+                    audit,
+                    memory,
+                    &mut followers,
+                    &token,
+                    l,
+                )
+                .await?
+            }
+            SigsOnG1Scheme::ID => {
+                start::<SigsOnG1Scheme, UnChainedBeacon>(
+                    chain,
+                    audit,
+                    memory,
+                    &mut followers,
+                    &token,
+                    l,
+                )
+                .await?
+            }
+            unknown => {
+                error!(parent: &l, "unknown scheme of chain info: {unknown}");
+                return Err(SyncError::InvalidInfoPacket);
+            }
+        };
+        backends.audits.push((beacon_id, metrics));
+        backends.chains.push(backend);
+    }
+
+    let mut server = tracker.spawn(http_api::start_server(
+        backends,
+        config.listen,
+        config.http,
+        token.clone(),
+        tracker.clone(),
+    ));
+    loop {
+        tokio::select! {
+            _ = &mut server => {
+                error!("{}", SyncError::HttpApi);
+                return Err(SyncError::HttpApi);
+            }
+            Some(result) = followers.join_next() => match result {
+                Ok(Err(err)) => {
+                    // Other chains are still served.
+                    error!("relay: stopped following a chain: {err}");
+                }
+                Ok(Ok(())) | Err(_) => {}
+            },
+            () = token.cancelled() => break,
+        }
+    }
+    info!("relay is shutting down");
+
+    Ok(())
+}
+
+/// Opens the store and verifies chain info, the chain is followed in background.
+/// Upstream nodes are audited every given amount of rounds.
+async fn start<S: Scheme, B: BeaconRepr>(
+    chain: RelayChain,
+    audit: Option<(EventSender, u64)>,
+    memory: SyncMemory,
+    followers: &mut JoinSet<Result<(), SyncError>>,
+    token: &CancellationToken,
+    l: Span,
+) -> Result<(Box<dyn Backend>, Arc<AuditMetrics>), SyncError> {
+    let beacon_id = chain.beacon_id().to_string();
+    let log_dir = chain.store_path.clone();
+    let store =
+        ChainStore::<B>::start(chain.store_path, beacon_id.clone(), StorageMode::Full).await?;
+    let syncer = start_follow_chain(&chain.request, &beacon_id, &store, memory, l.clone()).await?;
+    let info = syncer.chain_info_from_packet::<S>()?;
+    let mut packet = syncer.packet().clone();
+    packet.metadata = Some(Metadata::with_id(beacon_id.clone()));
+    info!(parent: &l, "relaying chain {}", hex::encode(&packet.hash));
+
+    let audit_every = audit.as_ref().map_or(0, |(_, every)| *every);
+    let auditor = match audit {
+        Some((events, _)) => {
+            let (peers, relays) = parse_nodes(&chain.request.nodes, &l)?;
+            Some(Arc::new(Auditor {
+                beacon_id: beacon_id.clone(),
+                peers,
+                relays,
+                scheme_id: packet.scheme_id.clone(),
+                public_key: packet.public_key.clone(),
+                log_dir,
+                events,
+                l: l.clone(),
+            }))
+        }
+        None => None,
+    };
+    let metrics = Arc::new(AuditMetrics::default());
+    let follower = Follower {
+        syncer,
+        store: store.clone(),
+        period: info.period.get_value(),
+        genesis: info.genesis_time,
+        auditor,
+        audit_every,
+        metrics: metrics.clone(),
+        l,
+    };
+    followers.spawn(follower.run::<S>(token.clone()));
+
+    let backend = RelayBackend {
+        beacon_id,
+        packet,
+        store,
+    };
+
+    Ok((Box::new(backend), metrics))
+}
+
+struct Follower<B: BeaconRepr> {
+    syncer: DefaultSyncerConfig<B>,
+    store: ChainStore<B>,
+    period: u32,
+    genesis: u64,
+    auditor: Option<Arc<Auditor>>,
+    /// Interval in rounds between audits.
+    audit_every: u64,
+    metrics: Arc<AuditMetrics>,
+    l: Span,
+}
+
+impl<B: BeaconRepr> Follower<B> {
+    async fn run<S: Scheme>(self, token: CancellationToken) -> Result<(), SyncError> {
+        let l = &self.l;
+        let mut audited = 0;
+        let mut audit: Option<JoinHandle<()>> = None;
+        loop {
+            let now = time::time_now().as_secs();
+            let target = time::current_round(now, self.period, self.genesis);
+            let last_stored = self.store.last().await?.round();
+            if last_stored < target {
+                let (tx, mut rx) = mpsc::channel(128);
+                let handle = DefaultSyncer::<S, B>::from_config(self.syncer.clone())?
+                    .process_follow_request(target, tx);
+                // Progress is reported to CLI only.
+                while rx.recv().await.is_some() {}
+                match handle.await {
+                    Ok(Ok(())) => debug!(parent: l, "followed up to round {target}"),
+                    Ok(Err(err)) => {
+                        warn!(parent: l, "failed to follow up to round {target}: {err}")
+                    }
+                    Err(err) => error!(parent: l, "follow task: {err}"),
+                }
+            }
+            // Audit runs aside of following, the next one is skipped while the previous is running.
+            if let Some(auditor) = &self.auditor {
+                let idle = audit.as_ref().is_none_or(JoinHandle::is_finished);
+                if idle && target >= audited + self.audit_every {
+                    let (auditor, store, metrics) =
+                        (auditor.clone(), self.store.clone(), self.metrics.clone());
+                    audit = Some(tokio::spawn(async move {
+                        auditor.audit(&store, &metrics).await;
+                    }));
+                    audited = target;
+                }
+            }
+
+            let next = time::time_of_round(self.period, self.genesis, target + 1);
+            let wait = Duration::from_secs(next.saturating_sub(time::time_now().as_secs()));
+            tokio::select! {
+                () = tokio::time::sleep(wait + ROUND_DELAY) => {}
+                () = token.cancelled() => {
+                    if let Some(audit) = audit {
+                        audit.abort();
+                    }
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Returns sync nodes of the chain. gRPC nodes serve all chains, HTTP relay URLs serve the chain
+/// whose hash is in the URL, URLs without any of followed hashes serve the default chain.
+fn chain_nodes(nodes: &[String], beacon_id: &str, hash: &str, hashes: &[String]) -> Vec<String> {
+    nodes
+        .iter()
+        .filter(|node| {
+            !HttpRelay::is_relay_url(node)
+                || node.contains(hash)
+                || (beacon_id == DEFAULT_BEACON_ID && !hashes.iter().any(|h| node.contains(h)))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Backends of followed chains.
+#[derive(Default)]
+struct RelayBackends {
+    chains: Vec<Box<dyn Backend>>,
+    audits: Vec<(String, Arc<AuditMetrics>)>,
+}
+
+impl RelayBackends {
+    fn get(&self, id: &str) -> Result<&dyn Backend, Status> {
+        self.chains
+            .iter()
+            .find(|b| b.ids().iter().any(|i| i == id))
+            .map(AsRef::as_ref)
+            .ok_or_else(|| Status::not_found(format!("unknown beacon id '{id}'")))
+    }
+}
+
+#[tonic::async_trait]
+impl Backend for RelayBackends {
+    fn ids(&self) -> Vec<String> {
+        self.chains.iter().flat_map(|b| b.ids()).collect()
+    }
+
+    fn metrics(&self) -> Option<String> {
+        let audits: Vec<_> = self
+            .audits
+            .iter()
+            .map(|(id, metrics)| (id.as_str(), metrics.as_ref()))
+            .collect();
+
+        Some(AuditMetrics::render(&audits))
+    }
+
+    /// The default chain, otherwise the first followed chain.
+    fn default_id(&self) -> String {
+        let ids = self.ids();
+        if ids.iter().any(|id| id == DEFAULT_BEACON_ID) {
+            return DEFAULT_BEACON_ID.to_string();
+        }
+
+        ids.into_iter().next().unwrap_or_default()
+    }
+
+    async fn chain_info(&self, id: &str) -> Result<ChainInfoPacket, Status> {
+        self.get(id)?.chain_info(id).await
+    }
+
+    async fn public_rand(&self, id: &str, round: u64) -> Result<PublicRandResponse, Status> {
+        self.get(id)?.public_rand(id, round).await
+    }
+}
+
+/// Serves a single followed chain.
+struct RelayBackend<B: BeaconRepr> {
+    beacon_id: String,
+    packet: ChainInfoPacket,
+    store: ChainStore<B>,
+}
+
+impl<B: BeaconRepr> RelayBackend<B> {
+    fn check_id(&self, id: &str) -> Result<(), Status> {
+        if id == self.beacon_id {
+            Ok(())
+        } else {
+            Err(Status::not_found(format!("unknown beacon id '{id}'")))
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl<B: BeaconRepr> Backend for RelayBackend<B> {
+    fn ids(&self) -> Vec<String> {
+        vec![self.beacon_id.clone()]
+    }
+
+    async fn chain_info(&self, id: &str) -> Result<ChainInfoPacket, Status> {
+        self.check_id(id)?;
+
+        Ok(self.packet.clone())
+    }
+
+    async fn public_rand(&self, id: &str, round: u64) -> Result<PublicRandResponse, Status> {
+        self.check_id(id)?;
+
+        public_rand(&self.store, round, id)
+            .await
+            .map_err(|err| err.to_status(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nodes_of_chains() {
+        let quicknet = "52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971";
+        let default = "8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce";
+        let hashes = [quicknet.to_string(), default.to_string()];
+        let nodes = [
+            "127.0.0.1:4444".to_string(),
+            format!("https://api.drand.sh/{quicknet}"),
+            "https://api.drand.sh".to_string(),
+        ];
+
+        assert_eq!(
+            chain_nodes(&nodes, "quicknet", quicknet, &hashes),
+            nodes[..2]
+        );
+        assert_eq!(
+            chain_nodes(&nodes, DEFAULT_BEACON_ID, default, &hashes),
+            [nodes[0].clone(), nodes[2].clone()]
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Shadow mode of nodes which hold a share of a group that is not active yet, e.g. joiners of a
+//! reshare until the transition. Partials for the followed chain are signed with the share and
+//! verified against own public share, followed beacons are verified against the distributed key
+//! of the group. Nothing is broadcast.
+//!
+//! Members of the active group broadcast their partials as usual, shadow mode only verifies them.
+use super::epoch::EpochConfig;
+use super::handler::ChainError;
+use super::skew;
+use super::store::BeaconRepr;
+use crate::key::group::Group;
+use crate::key::store::FileStore;
+use crate::key::Scheme;
+use crate::net::utils::Seconds;
+use crate::protobuf::drand::PartialBeaconPacket;
+
+use energon::drand::traits::BeaconDigest;
+use energon::kyber::dkg::DistKeyShare;
+use energon::points::KeyPoint;
+use energon::traits::Affine;
+use std::time::Duration;
+
+/// Interval of shadow checks of nodes without active group.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct Shadow<S: Scheme> {
+    ec: EpochConfig<S>,
+    public_key: KeyPoint<S>,
+    period: Seconds,
+    genesis_time: u64,
+    /// Latest checked beacon round.
+    checked: Option<u64>,
+}
+
+/// Outcome of a shadow check of the latest beacon.
+#[derive(Debug)]
+pub struct Checked {
+    /// Round of the signed partial.
+    pub round: u64,
+    /// Delay of the partial from the start of its round.
+    pub delay_ms: i64,
+}
+
+impl<S: Scheme> Shadow<S> {
+    pub fn new(group: Group<S>, share: DistKeyShare<S>) -> Self {
+        let public_key = group.dist_key.commits.first().cloned().unwrap_or_default();
+
+        Self {
+            period: group.period,
+            genesis_time: group.genesis_time,
+            ec: EpochConfig::new(group.nodes, share),
+            public_key,
+            checked: None,
+        }
+    }
+
+    /// Loads group and share of the node, `None` until a DKG gives the node a share.
+    pub fn load(fs: &FileStore) -> Option<Self> {
+        let group = fs.load_group::<S>().ok()?;
+        let share = fs.load_share::<S>().ok()?;
+
+        Some(Self::new(group, share))
+    }
+
+    /// Checks the latest beacon of the followed chain once: the beacon is verified against the
+    /// distributed key and the partial of the next round is signed and verified against own public
+    /// share. Returns `None` if the beacon is already checked.
+    pub fn check<B: BeaconRepr>(&mut self, latest: &B) -> Option<Result<Checked, ChainError>> {
+        if self.checked == Some(latest.round()) {
+            return None;
+        }
+        self.checked = Some(latest.round());
+
+        Some(self.verify(latest))
+    }
+
+    fn verify<B: BeaconRepr>(&self, latest: &B) -> Result<Checked, ChainError> {
+        // Round zero is the genesis seed.
+        if latest.round() > 0 {
+            let sig = Affine::deserialize(latest.signature())
+                .map_err(|_| ChainError::InvalidRecovered)?;
+            if !super::is_valid_signature::<S>(
+                &self.public_key,
+                latest.prev_signature().unwrap_or_default(),
+                latest.round(),
+                &sig,
+            ) {
+                return Err(ChainError::InvalidRecovered);
+            }
+        }
+
+        let round = latest.round() + 1;
+        let msg = S::Beacon::digest(latest.signature(), round);
+        let partial_sig = self.ec.sign_partial(&msg)?.serialize()?;
+        let delay_ms = skew::round_offset_ms(self.period, self.genesis_time, round);
+        self.ec.verify_own_partial(&PartialBeaconPacket {
+            round,
+            previous_signature: latest.signature().to_vec(),
+            partial_sig,
+            metadata: None,
+        })?;
+
+        Ok(Checked { round, delay_ms })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::ChainedBeacon;
+    use crate::chain::UnChainedBeacon;
+    use crate::key::keys::DistPublic;
+    use energon::drand::schemes::DefaultScheme;
+    use energon::drand::schemes::UnchainedScheme;
+    use energon::kyber::poly::PriShare;
+    use energon::traits::ScalarField;
+
+    /// Group of a single node, the share is the private key of the chain.
+    fn single_node<S: Scheme>() -> (Shadow<S>, S::Scalar) {
+        let private = S::Scalar::random();
+        let commits = vec![S::sk_to_pk(&private)];
+        let group = Group {
+            period: Seconds::new(3),
+            dist_key: DistPublic {
+                commits: commits.clone(),
+            },
+            ..Default::default()
+        };
+        let share = DistKeyShare {
+            commits,
+            pri_share: PriShare::new(0, private.clone()),
+        };
+
+        (Shadow::new(group, share), private)
+    }
+
+    fn signed<S: Scheme, B: BeaconRepr>(prev: &B, private: &S::Scalar) -> B {
+        let msg = S::Beacon::digest(prev.signature(), prev.round() + 1);
+        let sig = S::bls_sign(&msg, private).unwrap().serialize().unwrap();
+
+        B::new(prev, sig.to_vec().into())
+    }
+
+    fn beacons_of_group<S: Scheme, B: BeaconRepr>() {
+        let (mut shadow, private) = single_node::<S>();
+        let genesis = B::from_seed(vec![0xaa; 32]);
+        assert_eq!(shadow.check(&genesis).unwrap().unwrap().round, 1);
+        // Beacon is checked once.
+        assert!(shadow.check(&genesis).is_none());
+
+        let first = signed::<S, B>(&genesis, &private);
+        assert_eq!(shadow.check(&first).unwrap().unwrap().round, 2);
+
+        // Beacon of another key is not the one the group would have produced.
+        let (_, other) = single_node::<S>();
+        let forged = signed::<S, B>(&first, &other);
+        assert!(matches!(
+            shadow.check(&forged),
+            Some(Err(ChainError::InvalidRecovered))
+        ));
+    }
+
+    #[test]
+    fn shadow_checks_followed_beacons() {
+        beacons_of_group::<DefaultScheme, ChainedBeacon>();
+        beacons_of_group::<UnchainedScheme, UnChainedBeacon>();
+    }
+}
@@ -0,0 +1,103 @@
+//! Clock skew detection based on round timestamps observed from peers.
+//!
+//! Partial beacons are broadcasted by peers at the beginning of the round,
+//! the offset between local arrival time and expected round time is sampled per peer.
+//! Median offset across peers beyond [`MAX_CLOCK_SKEW_MS`] indicates local clock skew,
+//! which silently breaks round production and triggers needless resyncs.
+use super::time;
+use crate::net::utils::Seconds;
+
+use std::collections::HashMap;
+
+/// Maximum allowed median offset between local clock and round times observed from peers.
+pub const MAX_CLOCK_SKEW_MS: i64 = 1000;
+
+/// Tracks latest observed offset per peer.
+#[derive(Default)]
+pub struct ClockSkew {
+    offsets: HashMap<String, i64>,
+    skewed: bool,
+}
+
+/// Change of skew state, returned by [`ClockSkew::observe`].
+#[derive(Debug, PartialEq)]
+pub enum SkewChange {
+    /// Median offset exceeded the threshold.
+    Detected(i64),
+    /// Median offset is back within the threshold.
+    Recovered(i64),
+}
+
+impl ClockSkew {
+    /// Records offset of the peer. Returns [`SkewChange`] if skew state is changed.
+    pub fn observe(&mut self, peer: &str, offset_ms: i64) -> Option<SkewChange> {
+        self.offsets.insert(peer.to_string(), offset_ms);
+        let estimate = self.estimate()?;
+        let skewed = estimate.abs() > MAX_CLOCK_SKEW_MS;
+
+        if skewed == self.skewed {
+            return None;
+        }
+        self.skewed = skewed;
+
+        if skewed {
+            Some(SkewChange::Detected(estimate))
+        } else {
+            Some(SkewChange::Recovered(estimate))
+        }
+    }
+
+    /// Returns median offset across peers.
+    pub fn estimate(&self) -> Option<i64> {
+        if self.offsets.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<i64> = self.offsets.values().copied().collect();
+        sorted.sort_unstable();
+
+        Some(sorted[sorted.len() / 2])
+    }
+
+    pub fn is_skewed(&self) -> bool {
+        self.skewed
+    }
+}
+
+/// Returns offset in milliseconds between local time and expected time of the round.
+///
+/// Positive value: round is observed later than expected (local clock might be ahead).
+/// Negative value: round is observed earlier than expected (local clock might be behind).
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+pub fn round_offset_ms(period: Seconds, genesis_time: u64, round: u64) -> i64 {
+    let expected = i128::from(time::time_of_round(period.get_value(), genesis_time, round)) * 1000;
+    let now = time::time_now().as_millis() as i128;
+
+    (now - expected) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skew_detection() {
+        let mut skew = ClockSkew::default();
+        assert!(skew.estimate().is_none());
+
+        // Offsets within the threshold.
+        assert!(skew.observe("a", 100).is_none());
+        assert!(skew.observe("b", -200).is_none());
+        assert!(!skew.is_skewed());
+
+        // Single slow peer does not move the median.
+        assert!(skew.observe("c", 5000).is_none());
+
+        // Majority of peers observed far from local clock.
+        assert_eq!(skew.observe("b", 4000), Some(SkewChange::Detected(4000)));
+        assert!(skew.is_skewed());
+
+        // Back to normal.
+        assert_eq!(skew.observe("c", 50), Some(SkewChange::Recovered(100)));
+        assert!(!skew.is_skewed());
+    }
+}
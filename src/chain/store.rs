@@ -1,44 +1,112 @@
+use super::inspect;
+use super::merkle;
+use super::merkle::MerkleProof;
+
+use crate::core::runtime::spawn_store;
+use crate::core::runtime::spawn_store_actor;
 use crate::net::utils::Callback;
 use crate::protobuf::drand::BeaconPacket;
 use crate::protobuf::drand::Metadata;
+use crate::verify::randomness;
 
+use prost::bytes::Bytes;
 use rusqlite::params;
+use rusqlite::types::Type;
 use rusqlite::Connection;
 use rusqlite::Error;
 use rusqlite::OpenFlags;
+use rusqlite::OptionalExtension;
+use rusqlite::Row;
+use sha2::Digest;
+use sha2::Sha256;
 
+use std::collections::BTreeSet;
+use std::collections::VecDeque;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
 use tokio::sync::mpsc;
-use tokio::task;
 use tracing::error;
 use tracing::warn;
 use tracing::Span;
 
 /// Number of beacons retrieved in a single query from chain DB.
 const BATCH_SIZE: u64 = 300;
-const DB_NAME: &str = "rusqlite.db";
+pub(super) const DB_NAME: &str = "rusqlite.db";
+/// Number of recently used beacons kept in memory by chain store actor.
+const HOT_CACHE_SIZE: usize = 16;
+/// Timeout for RO connection to wait for WAL lock held by writer.
+const RO_BUSY_TIMEOUT: Duration = Duration::from_millis(500);
+/// Length of truncated sha256 checksum stored per beacon record.
+const CHECKSUM_LEN: usize = 8;
+/// Columns read by [`ChainedBeacon::from_row`], the last one is the signature of the previous
+/// round for records stored without previous signature.
+pub(super) const CHAINED_COLUMNS: &str = "round, signature, previous_sig, checksum, randomness,
+    CASE WHEN length(previous_sig) = 0 THEN (SELECT p.signature FROM beacons AS p WHERE p.round = beacons.round - 1) END";
 
 pub type StoreStreamResponse = Result<BeaconPacket, tonic::Status>;
 
+/// Fields kept by chain store per beacon id, see `--derived-storage` and `--precomputed-storage`
+/// of `drand start`.
+///
+/// Stores in all modes are read the same way, so the mode of an existing store can be changed
+/// at any start: it applies to records written afterwards.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum StorageMode {
+    /// Fields of beacon packets are stored: round, signature and previous signature.
+    #[default]
+    Full,
+    /// Randomness is stored along with the fields of [`Self::Full`], so it is not recomputed on
+    /// read, trading disk for CPU.
+    Precomputed,
+    /// Only round and signature are stored. Previous signature is read from the record of the
+    /// previous round and randomness is recomputed, trading CPU on reads for disk.
+    Derived,
+}
+
 /// Inner beacon representation for chained schemes.
-#[derive(Clone, PartialEq)]
+///
+/// Signatures are reference-counted: cloning a beacon or moving it from
+/// [`BeaconPacket`] into the store does not copy the signature bytes.
+#[derive(Clone)]
 pub struct ChainedBeacon {
     round: u64,
-    signature: Vec<u8>,
-    previous_signature: Vec<u8>,
+    signature: Bytes,
+    previous_signature: Bytes,
+    /// Randomness read from the store, if kept.
+    randomness: Option<[u8; 32]>,
 }
 
 /// Inner beacon representation for unchained schemes.
-#[derive(Clone, PartialEq)]
+#[derive(Clone)]
 pub struct UnChainedBeacon {
     round: u64,
-    signature: Vec<u8>,
+    signature: Bytes,
+    /// Randomness read from the store, if kept.
+    randomness: Option<[u8; 32]>,
+}
+
+/// Randomness is derived from the signature and is not compared.
+impl PartialEq for ChainedBeacon {
+    fn eq(&self, other: &Self) -> bool {
+        self.round == other.round
+            && self.signature == other.signature
+            && self.previous_signature == other.previous_signature
+    }
+}
+
+impl PartialEq for UnChainedBeacon {
+    fn eq(&self, other: &Self) -> bool {
+        self.round == other.round && self.signature == other.signature
+    }
 }
 
 #[allow(private_bounds)]
 pub trait BeaconRepr: 'static + Executor + Sized + Send + Sync + Clone {
-    fn new(prev: &Self, recovered_sig: Vec<u8>) -> Self;
+    fn new(prev: &Self, recovered_sig: Bytes) -> Self;
     fn round(&self) -> u64;
     fn signature(&self) -> &[u8];
     fn prev_signature(&self) -> Option<&[u8]>;
@@ -51,15 +119,20 @@ pub trait BeaconRepr: 'static + Executor + Sized + Send + Sync + Clone {
         self.prev_signature()
             .map(|p_sig| hex::encode(p_sig.get(..3).expect("value is prechecked")))
     }
+    /// Randomness of the beacon, see [`randomness`].
+    fn randomness(&self) -> [u8; 32] {
+        randomness(self.signature())
+    }
 }
 
 impl BeaconRepr for ChainedBeacon {
     /// WARNING: monotonic round check for new beacon is shifted to caller side.
-    fn new(prev: &Self, new_sig: Vec<u8>) -> Self {
+    fn new(prev: &Self, new_sig: Bytes) -> Self {
         Self {
             round: prev.round + 1,
             signature: new_sig,
             previous_signature: prev.signature.clone(),
+            randomness: None,
         }
     }
 
@@ -86,24 +159,32 @@ impl BeaconRepr for ChainedBeacon {
             round,
             signature,
             previous_signature,
+            randomness: None,
         }
     }
 
     fn from_seed(genesis_seed: Vec<u8>) -> Self {
         Self {
             round: 0,
-            signature: genesis_seed,
-            previous_signature: vec![0],
+            signature: genesis_seed.into(),
+            previous_signature: Bytes::from_static(&[0]),
+            randomness: None,
         }
     }
+
+    fn randomness(&self) -> [u8; 32] {
+        self.randomness
+            .unwrap_or_else(|| randomness(&self.signature))
+    }
 }
 
 impl BeaconRepr for UnChainedBeacon {
     /// WARNING: monotonic round check for new beacon is shifted to caller side.
-    fn new(prev: &Self, new_sig: Vec<u8>) -> Self {
+    fn new(prev: &Self, new_sig: Bytes) -> Self {
         Self {
             round: prev.round + 1,
             signature: new_sig,
+            randomness: None,
         }
     }
 
@@ -123,26 +204,37 @@ impl BeaconRepr for UnChainedBeacon {
         Self {
             round: p.round,
             signature: p.signature,
+            randomness: None,
         }
     }
 
     fn from_seed(genesis_seed: Vec<u8>) -> Self {
         Self {
             round: 0,
-            signature: genesis_seed,
+            signature: genesis_seed.into(),
+            randomness: None,
         }
     }
+
+    fn randomness(&self) -> [u8; 32] {
+        self.randomness
+            .unwrap_or_else(|| randomness(&self.signature))
+    }
 }
 
 /// SQL statement executor for [`BeaconRepr`].
 trait Executor: Sized {
     fn open(path: &Path) -> Result<Connection, Error>;
     fn get(conn: &Connection, round: u64) -> Result<Self, Error>;
-    fn put(self, conn: &mut Connection) -> Result<(), Error>;
+    fn put(&self, conn: &mut Connection, mode: StorageMode) -> Result<(), Error>;
+    /// Overwrites corrupted record of the beacon round.
+    fn repair(&self, conn: &mut Connection, mode: StorageMode) -> Result<(), Error>;
     fn last(conn: &Connection) -> Result<Self, Error>;
+    /// Returns batch of beacons within `[from_round, to_round]`.
     fn get_batch_proto(
         conn: &Connection,
         from_round: u64,
+        to_round: u64,
         id: &str,
     ) -> Result<Vec<BeaconPacket>, Error>;
 }
@@ -157,81 +249,73 @@ impl Executor for ChainedBeacon {
             "CREATE TABLE IF NOT EXISTS beacons (
             round INTEGER PRIMARY KEY,
             signature BLOB NOT NULL,
-            previous_sig BLOB NOT NULL
+            previous_sig BLOB NOT NULL,
+            checksum BLOB
         ) WITHOUT ROWID",
             [],
         )?;
+        add_column(&conn, "checksum")?;
+        add_column(&conn, "randomness")?;
 
         Ok(conn)
     }
 
     fn get(conn: &Connection, round: u64) -> Result<Self, Error> {
-        let mut stmt = conn.prepare_cached(
-            "SELECT round, signature, previous_sig FROM beacons WHERE round = ?1",
-        )?;
-        stmt.query_row([round], |row| {
-            Ok(Self {
-                round: row.get(0)?,
-                signature: row.get(1)?,
-                previous_signature: row.get(2)?,
-            })
-        })
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {CHAINED_COLUMNS} FROM beacons WHERE round = ?1"
+        ))?;
+        stmt.query_row([round], Self::from_row)
     }
 
-    fn put(self, conn: &mut Connection) -> Result<(), Error> {
-        let tr = conn.transaction()?;
-
-        {
-            let mut stmt = tr.prepare_cached(
-                "INSERT INTO beacons (round, signature, previous_sig) VALUES (?1, ?2, ?3)",
-            )?;
-            stmt.execute(params![
-                self.round,
-                &self.signature,
-                &self.previous_signature,
-            ])?;
-        }
+    fn put(&self, conn: &mut Connection, mode: StorageMode) -> Result<(), Error> {
+        self.insert(
+            conn,
+            "INSERT INTO beacons (round, signature, previous_sig, checksum, randomness) VALUES (?1, ?2, ?3, ?4, ?5)",
+            mode,
+        )
+    }
 
-        tr.commit()
+    fn repair(&self, conn: &mut Connection, mode: StorageMode) -> Result<(), Error> {
+        self.insert(
+            conn,
+            "INSERT OR REPLACE INTO beacons (round, signature, previous_sig, checksum, randomness) VALUES (?1, ?2, ?3, ?4, ?5)",
+            mode,
+        )
     }
 
     fn last(conn: &Connection) -> Result<Self, Error> {
-        let mut stmt = conn.prepare_cached(
-            "SELECT round, signature, previous_sig
-         FROM beacons 
-         WHERE round = (SELECT MAX(round) FROM beacons)",
-        )?;
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {CHAINED_COLUMNS} FROM beacons WHERE round = (SELECT MAX(round) FROM beacons)"
+        ))?;
 
-        stmt.query_row([], |row| {
-            Ok(Self {
-                round: row.get(0)?,
-                signature: row.get(1)?,
-                previous_signature: row.get(2)?,
-            })
-        })
+        stmt.query_row([], Self::from_row)
     }
 
     fn get_batch_proto(
         conn: &Connection,
         from_round: u64,
+        to_round: u64,
         id: &str,
     ) -> Result<Vec<BeaconPacket>, Error> {
-        conn.prepare_cached(
-            "SELECT round, signature, previous_sig  
-         FROM beacons 
-         WHERE round >= ?1 
-         ORDER BY round ASC 
-         LIMIT ?2",
-        )?
-        .query_map([from_round, BATCH_SIZE], |row| {
+        conn.prepare_cached(&format!(
+            "SELECT {CHAINED_COLUMNS} FROM beacons WHERE round >= ?1 AND round <= ?2 ORDER BY round ASC LIMIT ?3"
+        ))?
+        .query_map([from_round, to_round, BATCH_SIZE], |row| {
+            let Self {
+                round,
+                signature,
+                previous_signature,
+                randomness: _,
+            } = Self::from_row(row)?;
             Ok(BeaconPacket {
-                round: row.get(0)?,
-                signature: row.get(1)?,
-                previous_signature: row.get(2)?,
+                round,
+                signature,
+                previous_signature,
                 metadata: Some(Metadata {
                     node_version: None,
                     beacon_id: id.to_string(),
                     chain_hash: vec![],
+                    features: vec![],
                 }),
             })
         })?
@@ -239,6 +323,78 @@ impl Executor for ChainedBeacon {
     }
 }
 
+impl ChainedBeacon {
+    /// Reads beacon from [`CHAINED_COLUMNS`].
+    fn from_row(row: &Row<'_>) -> Result<Self, Error> {
+        let round = row.get(0)?;
+        let signature = blob(row, 1)?;
+        let stored_previous = blob(row, 2)?;
+        let stored_randomness = row.get::<_, Option<Vec<u8>>>(4)?;
+        let previous_signature = if stored_previous.is_empty() && round > 0 {
+            // Record of the previous round is absent.
+            row.get::<_, Option<Vec<u8>>>(5)?
+                .map(Bytes::from)
+                .ok_or_else(|| corrupt(5, round))?
+        } else {
+            stored_previous
+        };
+        // Derived previous signature is covered as well: record of the previous round might
+        // be replaced by a different but self-consistent one.
+        let expected = checksum(
+            round,
+            &signature,
+            &previous_signature,
+            stored_randomness.as_deref().unwrap_or_default(),
+        );
+        verify_checksum(row, 3, round, &expected)?;
+
+        Ok(Self {
+            round,
+            signature,
+            previous_signature,
+            randomness: stored_randomness
+                .map(|r| r.try_into().map_err(|_| corrupt(4, round)))
+                .transpose()?,
+        })
+    }
+
+    fn insert(&self, conn: &mut Connection, sql: &str, mode: StorageMode) -> Result<(), Error> {
+        let tr = conn.transaction()?;
+
+        {
+            // Previous signature is derived only if it is equal to the stored signature of the previous round.
+            let derived = mode == StorageMode::Derived
+                && self.round > 0
+                && tr
+                    .prepare_cached("SELECT signature FROM beacons WHERE round = ?1")?
+                    .query_row([self.round - 1], |row| row.get::<_, Vec<u8>>(0))
+                    .optional()?
+                    .is_some_and(|sig| sig == self.previous_signature.as_ref());
+            let previous: &[u8] = if derived {
+                &[]
+            } else {
+                &self.previous_signature
+            };
+            let randomness = stored_randomness(&self.signature, mode);
+            let mut stmt = tr.prepare_cached(sql)?;
+            stmt.execute(params![
+                self.round,
+                self.signature.as_ref(),
+                previous,
+                checksum(
+                    self.round,
+                    &self.signature,
+                    &self.previous_signature,
+                    randomness.as_deref().unwrap_or_default()
+                ),
+                randomness,
+            ])?;
+        }
+
+        tr.commit()
+    }
+}
+
 impl Executor for UnChainedBeacon {
     fn open(path: &Path) -> Result<Connection, Error> {
         let conn = Connection::open(path.join(DB_NAME))?;
@@ -248,74 +404,79 @@ impl Executor for UnChainedBeacon {
         conn.execute(
             "CREATE TABLE IF NOT EXISTS beacons (
             round INTEGER PRIMARY KEY,
-            signature BLOB NOT NULL
+            signature BLOB NOT NULL,
+            checksum BLOB
         ) WITHOUT ROWID",
             [],
         )?;
+        add_column(&conn, "checksum")?;
+        add_column(&conn, "randomness")?;
 
         Ok(conn)
     }
 
     fn get(conn: &Connection, round: u64) -> Result<Self, Error> {
-        let mut stmt =
-            conn.prepare_cached("SELECT round, signature FROM beacons WHERE round = ?1")?;
+        let mut stmt = conn.prepare_cached(
+            "SELECT round, signature, checksum, randomness FROM beacons WHERE round = ?1",
+        )?;
 
-        stmt.query_row([round], |row| {
-            Ok(Self {
-                round: row.get(0)?,
-                signature: row.get(1)?,
-            })
-        })
+        stmt.query_row([round], Self::from_row)
     }
 
-    fn put(self, conn: &mut Connection) -> Result<(), Error> {
-        let tr = conn.transaction()?;
-
-        {
-            let mut stmt =
-                tr.prepare_cached("INSERT INTO beacons (round, signature) VALUES (?1, ?2)")?;
-            stmt.execute(params![self.round, &self.signature])?;
-        }
+    fn put(&self, conn: &mut Connection, mode: StorageMode) -> Result<(), Error> {
+        self.insert(
+            conn,
+            "INSERT INTO beacons (round, signature, checksum, randomness) VALUES (?1, ?2, ?3, ?4)",
+            mode,
+        )
+    }
 
-        tr.commit()
+    fn repair(&self, conn: &mut Connection, mode: StorageMode) -> Result<(), Error> {
+        self.insert(
+            conn,
+            "INSERT OR REPLACE INTO beacons (round, signature, checksum, randomness) VALUES (?1, ?2, ?3, ?4)",
+            mode,
+        )
     }
 
     fn last(conn: &Connection) -> Result<Self, Error> {
         let mut stmt = conn.prepare_cached(
-            "SELECT round, signature
+            "SELECT round, signature, checksum, randomness
          FROM beacons 
          WHERE round = (SELECT MAX(round) FROM beacons)",
         )?;
 
-        stmt.query_row([], |row| {
-            Ok(Self {
-                round: row.get(0)?,
-                signature: row.get(1)?,
-            })
-        })
+        stmt.query_row([], Self::from_row)
     }
 
     fn get_batch_proto(
         conn: &Connection,
         from_round: u64,
+        to_round: u64,
         id: &str,
     ) -> Result<Vec<BeaconPacket>, Error> {
         conn.prepare_cached(
-            "SELECT round, signature 
+            "SELECT round, signature, checksum, randomness
          FROM beacons 
-         WHERE round >= ?1 
+         WHERE round >= ?1 AND round <= ?2
          ORDER BY round ASC 
-         LIMIT ?2",
+         LIMIT ?3",
         )?
-        .query_map([from_round, BATCH_SIZE], |row| {
+        .query_map([from_round, to_round, BATCH_SIZE], |row| {
+            let Self {
+                round,
+                signature,
+                randomness: _,
+            } = Self::from_row(row)?;
             Ok(BeaconPacket {
-                round: row.get(0)?,
-                signature: row.get(1)?,
-                previous_signature: vec![],
+                round,
+                signature,
+                previous_signature: Bytes::new(),
                 metadata: Some(Metadata {
                     node_version: None,
                     beacon_id: id.to_string(),
                     chain_hash: vec![],
+                    features: vec![],
                 }),
             })
         })?
@@ -323,10 +484,183 @@ impl Executor for UnChainedBeacon {
     }
 }
 
+impl UnChainedBeacon {
+    /// Reads beacon from `round, signature, checksum, randomness` columns.
+    fn from_row(row: &Row<'_>) -> Result<Self, Error> {
+        let round = row.get(0)?;
+        let signature = blob(row, 1)?;
+        let stored_randomness = row.get::<_, Option<Vec<u8>>>(3)?;
+        verify_checksum(
+            row,
+            2,
+            round,
+            &checksum(
+                round,
+                &signature,
+                &[],
+                stored_randomness.as_deref().unwrap_or_default(),
+            ),
+        )?;
+
+        Ok(Self {
+            round,
+            signature,
+            randomness: stored_randomness
+                .map(|r| r.try_into().map_err(|_| corrupt(3, round)))
+                .transpose()?,
+        })
+    }
+
+    fn insert(&self, conn: &mut Connection, sql: &str, mode: StorageMode) -> Result<(), Error> {
+        let tr = conn.transaction()?;
+
+        {
+            let randomness = stored_randomness(&self.signature, mode);
+            let mut stmt = tr.prepare_cached(sql)?;
+            stmt.execute(params![
+                self.round,
+                self.signature.as_ref(),
+                checksum(
+                    self.round,
+                    &self.signature,
+                    &[],
+                    randomness.as_deref().unwrap_or_default()
+                ),
+                randomness,
+            ])?;
+        }
+
+        tr.commit()
+    }
+}
+
+/// Returns randomness to store with the signature, `Some` only in [`StorageMode::Precomputed`].
+fn stored_randomness(signature: &[u8], mode: StorageMode) -> Option<Vec<u8>> {
+    (mode == StorageMode::Precomputed).then(|| randomness(signature).to_vec())
+}
+
+/// Returned within [`Error::FromSqlConversionFailure`] if beacon record does not match its checksum.
+#[derive(thiserror::Error, Debug)]
+#[error("checksum mismatch for round {0}")]
+struct Corrupt(u64);
+
+impl Corrupt {
+    /// Returns round of corrupted record if error is caused by checksum mismatch.
+    fn round(err: &Error) -> Option<u64> {
+        match err {
+            Error::FromSqlConversionFailure(_, _, err) => err.downcast_ref::<Self>().map(|c| c.0),
+            _ => None,
+        }
+    }
+}
+
+/// Rounds of corrupted records detected on read, shared by chain store actor and sync streams.
+type CorruptRounds = Arc<Mutex<BTreeSet<u64>>>;
+
+fn mark_corrupt(corrupt: &CorruptRounds, round: u64) {
+    corrupt
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(round);
+}
+
+/// Truncated sha256 over fields of beacon record: previous signature is the one of the beacon,
+/// also if it is derived from the record of the previous round, and randomness is empty if not
+/// stored. Randomness is hashed last, so checksums of records written before it could be stored
+/// are unchanged.
+pub(super) fn checksum(
+    round: u64,
+    signature: &[u8],
+    previous_signature: &[u8],
+    randomness: &[u8],
+) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(round.to_be_bytes());
+    hasher.update(signature);
+    hasher.update(previous_signature);
+    hasher.update(randomness);
+
+    hasher.finalize()[..CHECKSUM_LEN].to_vec()
+}
+
+/// Returns error of a record which can not be read as is, it is re-fetched as a corrupted one.
+fn corrupt(idx: usize, round: u64) -> Error {
+    Error::FromSqlConversionFailure(idx, Type::Blob, Box::new(Corrupt(round)))
+}
+
+/// Compares checksum column with `expected`. Records stored before checksums were introduced
+/// have NULL checksum and are not verified.
+fn verify_checksum(row: &Row<'_>, idx: usize, round: u64, expected: &[u8]) -> Result<(), Error> {
+    match row.get::<_, Option<Vec<u8>>>(idx)? {
+        Some(stored) if stored != expected => Err(corrupt(idx, round)),
+        _ => Ok(()),
+    }
+}
+
+/// Adds BLOB column to chain stores created by previous versions.
+fn add_column(conn: &Connection, name: &str) -> Result<(), Error> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('beacons') WHERE name = ?1",
+        [name],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute(&format!("ALTER TABLE beacons ADD COLUMN {name} BLOB"), [])?;
+    }
+
+    Ok(())
+}
+
+/// Reads BLOB column into [`Bytes`], taking ownership of the buffer allocated by sqlite row.
+fn blob(row: &Row<'_>, idx: usize) -> Result<Bytes, Error> {
+    row.get::<_, Vec<u8>>(idx).map(Bytes::from)
+}
+
+/// LRU cache for hot rounds, served by chain store actor without hitting disk.
+///
+/// Beacons are ordered from least to most recently used.
+struct HotCache<B: BeaconRepr> {
+    beacons: VecDeque<B>,
+    capacity: usize,
+}
+
+impl<B: BeaconRepr> HotCache<B> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            beacons: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Returns beacon for given round, marking it as most recently used.
+    fn get(&mut self, round: u64) -> Option<B> {
+        let pos = self.beacons.iter().position(|b| b.round() == round)?;
+        let beacon = self.beacons.remove(pos)?;
+        self.beacons.push_back(beacon.clone());
+
+        Some(beacon)
+    }
+
+    /// Inserts beacon as most recently used, evicting the least recently used one if cache is full.
+    fn insert(&mut self, beacon: B) {
+        if let Some(pos) = self
+            .beacons
+            .iter()
+            .position(|b| b.round() == beacon.round())
+        {
+            self.beacons.remove(pos);
+        } else if self.beacons.len() == self.capacity {
+            self.beacons.pop_front();
+        }
+        self.beacons.push_back(beacon);
+    }
+}
+
 /// Handle for chain store actor.
 #[derive(Clone)]
 pub struct ChainStore<B: BeaconRepr> {
     sender: mpsc::Sender<Cmd<B>>,
+    corrupt: CorruptRounds,
 }
 
 /// Commands for chain store actor.
@@ -335,6 +669,10 @@ enum Cmd<B: BeaconRepr> {
         beacon: B,
         cb: Callback<(), StoreError>,
     },
+    Repair {
+        beacon: B,
+        cb: Callback<(), StoreError>,
+    },
     Last {
         cb: Callback<B, StoreError>,
     },
@@ -346,6 +684,13 @@ enum Cmd<B: BeaconRepr> {
         from_round: u64,
         cb: Callback<mpsc::Receiver<StoreStreamResponse>, StoreError>,
     },
+    MerkleProof {
+        round: u64,
+        cb: Callback<(B, MerkleProof), StoreError>,
+    },
+    Missing {
+        cb: Callback<Vec<(u64, u64)>, StoreError>,
+    },
 }
 
 /// Error details are traced within chain store actor (see: [`ChainStore::start`]).
@@ -357,6 +702,9 @@ pub enum StoreError {
     NotFound,
     #[error("genesis mismatch")]
     GenesisMismatch,
+    /// Record failed checksum verification, beacon should be re-fetched from peers.
+    #[error("beacon of round {0} is corrupted in chain store")]
+    Corrupt(u64),
     #[error("actor receiver has been closed unexpectedly")]
     ActorClosedRx,
     #[error("cb sender has been closed unexpectedly")]
@@ -367,56 +715,184 @@ impl<B: BeaconRepr> ChainStore<B> {
     /// Starts chain store actor and returns its handle.
     ///
     /// Current implementation is [rusqlite] specific for connection management and execution.
-    pub async fn start(path: PathBuf, beacon_id: String) -> Result<Self, StoreError> {
+    pub async fn start(
+        path: PathBuf,
+        beacon_id: String,
+        mode: StorageMode,
+    ) -> Result<Self, StoreError> {
         // Callback for the current request.
         let (cb_tx, cb_rx) = Callback::new();
         // Channel for communicating with storage actor.
         let (cmd_tx, mut cmd_rx) = mpsc::channel::<Cmd<B>>(1);
         let l = tracing::info_span!("", chain_store = beacon_id);
+        let corrupt = CorruptRounds::default();
+        let actor_corrupt = corrupt.clone();
+        let actor_l = l.clone();
 
-        task::spawn_blocking(move || {
+        let actor = spawn_store_actor(move || {
+            let corrupt = actor_corrupt;
+            let l = actor_l;
             // Open a single RW connection to be reused for all actor requests except for [sync].
             let mut rw_conn = match B::open(&path) {
-                Ok(conn) => {
-                    cb_tx.reply(Ok(()));
-                    conn
-                }
+                Ok(conn) => conn,
                 Err(err) => {
                     error!(parent: &l, "failed to open RW connection: {err}");
                     cb_tx.reply(Err(StoreError::Internal));
                     return;
                 }
             };
+            let mut merkle_size = match extend_merkle::<B>(&rw_conn) {
+                Ok(size) => size,
+                Err(err) => {
+                    error!(parent: &l, "failed to load merkle tree: {err}");
+                    cb_tx.reply(Err(StoreError::Internal));
+                    return;
+                }
+            };
+            cb_tx.reply(Ok(()));
+            let mut hot = HotCache::<B>::new(HOT_CACHE_SIZE);
+            // Latest stored round is tracked to serve [`Cmd::Last`] from cache.
+            let mut last_round = None;
             while let Some(cmd) = cmd_rx.blocking_recv() {
                 match cmd {
-                    Cmd::Put { beacon, cb } => match beacon.put(&mut rw_conn) {
-                        Ok(()) => cb.reply(Ok(())),
+                    Cmd::Put { beacon, cb } => match beacon.put(&mut rw_conn, mode) {
+                        Ok(()) => {
+                            if last_round.is_some_and(|last| last < beacon.round()) {
+                                last_round = Some(beacon.round());
+                            }
+                            if beacon.round() == merkle_size + 1 {
+                                let leaf = merkle::leaf_hash(beacon.round(), beacon.signature());
+                                match merkle::append(&rw_conn, merkle_size, leaf) {
+                                    Ok(()) => merkle_size += 1,
+                                    // Tree is extended again on the next start.
+                                    Err(err) => {
+                                        error!(parent: &l, "failed to append merkle leaf for round {}: {err}", beacon.round())
+                                    }
+                                }
+                                // Filled gap might be followed by already stored rounds.
+                                if last_round.is_none_or(|last| last > beacon.round()) {
+                                    match extend_merkle_from::<B>(&rw_conn, merkle_size) {
+                                        Ok(size) => merkle_size = size,
+                                        Err(err) => {
+                                            error!(parent: &l, "failed to extend merkle tree from round {}: {err}", beacon.round())
+                                        }
+                                    }
+                                }
+                            }
+                            hot.insert(beacon);
+                            cb.reply(Ok(()));
+                        }
                         Err(err) => {
                             error!(parent: &l, "failed to put beacon: {err}");
                             cb.reply(Err(StoreError::Internal));
                             return;
                         }
                     },
-                    Cmd::Last { cb } => match B::last(&rw_conn) {
-                        Ok(beacon) => cb.reply(Ok(beacon)),
-                        Err(Error::QueryReturnedNoRows) => cb.reply(Err(StoreError::NotFound)),
+                    Cmd::Repair { beacon, cb } => match beacon.repair(&mut rw_conn, mode) {
+                        Ok(()) => {
+                            // Rewritten rows are covered by the tree, leaves are rewritten as well.
+                            if (1..=merkle_size).contains(&beacon.round()) {
+                                let leaf = merkle::leaf_hash(beacon.round(), beacon.signature());
+                                if let Err(err) =
+                                    merkle::replace(&rw_conn, merkle_size, beacon.round() - 1, leaf)
+                                {
+                                    error!(parent: &l, "failed to replace merkle leaf for round {}: {err}", beacon.round());
+                                    cb.reply(Err(StoreError::Internal));
+                                    return;
+                                }
+                            }
+                            corrupt
+                                .lock()
+                                .unwrap_or_else(PoisonError::into_inner)
+                                .remove(&beacon.round());
+                            warn!(parent: &l, "repaired corrupted beacon of round {}", beacon.round());
+                            hot.insert(beacon);
+                            cb.reply(Ok(()));
+                        }
                         Err(err) => {
-                            error!(parent: &l, "failed to get last beacon: {err}");
+                            error!(parent: &l, "failed to repair beacon: {err}");
                             cb.reply(Err(StoreError::Internal));
                             return;
                         }
                     },
-                    Cmd::Get { round, cb } => match B::get(&rw_conn, round) {
-                        Ok(beacon) => cb.reply(Ok(beacon)),
-                        Err(Error::QueryReturnedNoRows) => cb.reply(Err(StoreError::NotFound)),
+                    Cmd::Last { cb } => {
+                        if let Some(beacon) = last_round.and_then(|round| hot.get(round)) {
+                            cb.reply(Ok(beacon));
+                            continue;
+                        }
+                        match B::last(&rw_conn) {
+                            Ok(beacon) => {
+                                last_round = Some(beacon.round());
+                                hot.insert(beacon.clone());
+                                cb.reply(Ok(beacon));
+                            }
+                            Err(Error::QueryReturnedNoRows) => cb.reply(Err(StoreError::NotFound)),
+                            Err(err) => {
+                                error!(parent: &l, "failed to get last beacon: {err}");
+                                if let Some(round) = Corrupt::round(&err) {
+                                    mark_corrupt(&corrupt, round);
+                                    cb.reply(Err(StoreError::Corrupt(round)));
+                                    continue;
+                                }
+                                cb.reply(Err(StoreError::Internal));
+                                return;
+                            }
+                        }
+                    }
+                    Cmd::Get { round, cb } => {
+                        if let Some(beacon) = hot.get(round) {
+                            cb.reply(Ok(beacon));
+                            continue;
+                        }
+                        match B::get(&rw_conn, round) {
+                            Ok(beacon) => {
+                                hot.insert(beacon.clone());
+                                cb.reply(Ok(beacon));
+                            }
+                            Err(Error::QueryReturnedNoRows) => cb.reply(Err(StoreError::NotFound)),
+                            Err(err) => {
+                                error!(parent: &l, "failed to get beacon of round {round}: {err}");
+                                if Corrupt::round(&err).is_some() {
+                                    mark_corrupt(&corrupt, round);
+                                    cb.reply(Err(StoreError::Corrupt(round)));
+                                    continue;
+                                }
+                                cb.reply(Err(StoreError::Internal));
+                                return;
+                            }
+                        }
+                    }
+                    Cmd::MerkleProof { round, cb } => {
+                        if round == 0 || round > merkle_size {
+                            cb.reply(Err(StoreError::NotFound));
+                            continue;
+                        }
+                        match B::get(&rw_conn, round)
+                            .and_then(|b| Ok((merkle::proof(&rw_conn, round, merkle_size)?, b)))
+                        {
+                            Ok((proof, beacon)) => cb.reply(Ok((beacon, proof))),
+                            Err(err) => {
+                                error!(parent: &l, "failed to get merkle proof for round {round}: {err}");
+                                if Corrupt::round(&err).is_some() {
+                                    mark_corrupt(&corrupt, round);
+                                    cb.reply(Err(StoreError::Corrupt(round)));
+                                    continue;
+                                }
+                                cb.reply(Err(StoreError::Internal));
+                                return;
+                            }
+                        }
+                    }
+                    Cmd::Missing { cb } => match inspect::missing_ranges(&rw_conn) {
+                        Ok(ranges) => cb.reply(Ok(ranges)),
                         Err(err) => {
-                            error!(parent: &l, "failed to get beacon of round {round}: {err}");
+                            error!(parent: &l, "failed to get missing rounds: {err}");
                             cb.reply(Err(StoreError::Internal));
                             return;
                         }
                     },
                     Cmd::Sync { from_round, cb } => {
-                        match sync::<B>(&path, from_round, &beacon_id) {
+                        match sync::<B>(&path, from_round, &beacon_id, corrupt.clone()) {
                             Ok(client_rx) => cb.reply(Ok(client_rx)),
                             Err(err) => {
                                 error!(parent: &l, "sync: failed to open RO connection: {err}");
@@ -428,10 +904,17 @@ impl<B: BeaconRepr> ChainStore<B> {
                 }
             }
         });
+        if let Err(err) = actor {
+            error!(parent: &l, "failed to start actor: {err}");
+            return Err(StoreError::Internal);
+        }
 
         cb_rx.await??;
 
-        Ok(Self { sender: cmd_tx })
+        Ok(Self {
+            sender: cmd_tx,
+            corrupt,
+        })
     }
 
     pub async fn put(&self, beacon: B) -> Result<(), StoreError> {
@@ -444,6 +927,46 @@ impl<B: BeaconRepr> ChainStore<B> {
         cb_rx.await?
     }
 
+    /// Replaces record which failed checksum verification with beacon re-fetched from peers.
+    pub async fn repair(&self, beacon: B) -> Result<(), StoreError> {
+        let (cb_tx, cb_rx) = Callback::new();
+        self.sender
+            .send(Cmd::Repair { beacon, cb: cb_tx })
+            .await
+            .map_err(|_| StoreError::ActorClosedRx)?;
+
+        cb_rx.await?
+    }
+
+    /// Returns beacon with its inclusion proof into Merkle tree over stored rounds.
+    pub async fn merkle_proof(&self, round: u64) -> Result<(B, MerkleProof), StoreError> {
+        let (cb_tx, cb_rx) = Callback::new();
+        self.sender
+            .send(Cmd::MerkleProof { round, cb: cb_tx })
+            .await
+            .map_err(|_| StoreError::ActorClosedRx)?;
+
+        cb_rx.await?
+    }
+
+    /// Returns missing ranges `[from, to]` in between of stored rounds.
+    pub async fn missing(&self) -> Result<Vec<(u64, u64)>, StoreError> {
+        let (cb_tx, cb_rx) = Callback::new();
+        self.sender
+            .send(Cmd::Missing { cb: cb_tx })
+            .await
+            .map_err(|_| StoreError::ActorClosedRx)?;
+
+        cb_rx.await?
+    }
+
+    /// Takes rounds of corrupted records detected since the previous call.
+    pub fn take_corrupted(&self) -> Vec<u64> {
+        std::mem::take(&mut *self.corrupt.lock().unwrap_or_else(PoisonError::into_inner))
+            .into_iter()
+            .collect()
+    }
+
     pub async fn get(&self, round: u64) -> Result<B, StoreError> {
         let (cb_tx, cb_rx) = Callback::new();
         self.sender
@@ -502,43 +1025,58 @@ impl<B: BeaconRepr> ChainStore<B> {
 }
 
 /// Note: Store abstraction is intentionally leaked (see [`StoreStreamResponse`]) for purpose of single channel usage.
-#[allow(unused_assignments)]
+///
+/// Stream is bounded by snapshot of the latest stored round taken at the moment of request.
+/// Reads are served by separate RO connection: in WAL mode the writer is never blocked by
+/// readers and each reader sees a consistent, append-only view limited by its snapshot.
+///
+/// Stream is terminated with `DATA_LOSS` status at the first corrupted record, its round is
+/// reported via `corrupt` to be re-fetched by the chain.
 fn sync<B: BeaconRepr>(
     path: &Path,
     start_from: u64,
     id: &str,
+    corrupt: CorruptRounds,
 ) -> Result<mpsc::Receiver<StoreStreamResponse>, Error> {
     let ro_conn =
         Connection::open_with_flags(path.join(DB_NAME), OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    ro_conn.busy_timeout(RO_BUSY_TIMEOUT)?;
+    let head = snapshot_head(&ro_conn)?;
     let batch_size = usize::try_from(BATCH_SIZE).unwrap();
     let (tx, rx) = mpsc::channel::<StoreStreamResponse>(batch_size);
     let id = id.to_string();
 
     let mut from = start_from;
-    let mut sent_total = 0;
-    let mut received_len = 0;
-    tokio::task::spawn_blocking(move || loop {
-        match B::get_batch_proto(&ro_conn, from, &id) {
+    spawn_store(move || loop {
+        let Some(to) = head.filter(|head| from <= *head) else {
+            let _ = tx.blocking_send(Err(tonic::Status::not_found(format!(
+                "no beacons stored above {} round",
+                from.saturating_sub(1)
+            ))));
+            break;
+        };
+        match B::get_batch_proto(&ro_conn, from, to, &id) {
             Ok(beacons) => {
-                received_len = beacons.len();
-                sent_total += received_len;
-
+                let Some(last) = beacons.last() else {
+                    // Gap in rounds, should not happen for append-only store.
+                    error!("sync for [{id}]: no beacons found within [{from}, {to}] rounds");
+                    break;
+                };
+                from = last.round + 1;
                 for b in beacons {
                     if tx.blocking_send(Ok(b)).is_err() {
-                        break;
+                        return;
                     };
                 }
-                if received_len < batch_size {
-                    let _ = tx.blocking_send(Err(tonic::Status::not_found(format!(
-                        "no beacons stored above {} round",
-                        sent_total as u64 + start_from - 1
-                    ))));
-                    break;
-                }
-                from += BATCH_SIZE;
             }
             Err(err) => {
                 error!("failed to get batch proto for [{id}]: get_batch_proto: {err}");
+                if let Some(round) = Corrupt::round(&err) {
+                    mark_corrupt(&corrupt, round);
+                    let _ = tx.blocking_send(Err(tonic::Status::data_loss(
+                        StoreError::Corrupt(round).to_string(),
+                    )));
+                }
                 break;
             }
         };
@@ -547,6 +1085,39 @@ fn sync<B: BeaconRepr>(
     Ok(rx)
 }
 
+/// Extends Merkle tree with contiguous rounds stored since its last update, returns tree size.
+fn extend_merkle<B: BeaconRepr>(conn: &Connection) -> Result<u64, Error> {
+    extend_merkle_from::<B>(conn, merkle::open(conn)?)
+}
+
+/// Extends Merkle tree of `size` leaves with contiguous stored rounds, returns new tree size.
+fn extend_merkle_from<B: BeaconRepr>(conn: &Connection, mut size: u64) -> Result<u64, Error> {
+    let Some(head) = snapshot_head(conn)? else {
+        return Ok(size);
+    };
+    while size < head {
+        let beacons = B::get_batch_proto(conn, size + 1, head, "")?;
+        if beacons.first().is_none_or(|b| b.round != size + 1) {
+            // Gap in rounds, e.g. store is synced from a checkpoint.
+            break;
+        }
+        for b in beacons {
+            if b.round != size + 1 {
+                return Ok(size);
+            }
+            merkle::append(conn, size, merkle::leaf_hash(b.round, &b.signature))?;
+            size += 1;
+        }
+    }
+
+    Ok(size)
+}
+
+/// Returns latest stored round, `None` is returned for empty store.
+fn snapshot_head(conn: &Connection) -> Result<Option<u64>, Error> {
+    conn.query_row("SELECT MAX(round) FROM beacons", [], |row| row.get(0))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -555,7 +1126,8 @@ mod test {
         (0..=rounds)
             .map(|r| UnChainedBeacon {
                 round: r,
-                signature: r.to_be_bytes().into(),
+                signature: Bytes::copy_from_slice(&r.to_be_bytes()),
+                randomness: None,
             })
             .collect()
     }
@@ -566,20 +1138,73 @@ mod test {
                 if r == 0 {
                     ChainedBeacon {
                         round: r,
-                        signature: r.to_be_bytes().into(),
-                        previous_signature: vec![],
+                        signature: Bytes::copy_from_slice(&r.to_be_bytes()),
+                        previous_signature: Bytes::new(),
+                        randomness: None,
                     }
                 } else {
                     ChainedBeacon {
                         round: r,
-                        signature: r.to_be_bytes().into(),
-                        previous_signature: (r - 1).to_be_bytes().into(),
+                        signature: Bytes::copy_from_slice(&r.to_be_bytes()),
+                        previous_signature: Bytes::copy_from_slice(&(r - 1).to_be_bytes()),
+                        randomness: None,
                     }
                 }
             })
             .collect()
     }
 
+    #[test]
+    fn randomness_vectors() {
+        // Round 2 of `pedersen-bls-chained` and `bls-unchained-g1-rfc9380` chains of a local
+        // Go drand v2.1.2 network, randomness as of `crypto.RandomnessFromSignature`.
+        let chained = ChainedBeacon {
+            round: 2,
+            signature: hex::decode("b97954ee1a661a16c50800bdac1c7867320f056ed95e9b62b17eb1aab661b8b26e114f9b3a92051aaf635ae284a40cff04d5fc916be48d3aa80cec4902a9383a44f24fb650ed9efdda55fd4353cc81e9c33d957852e8cafbe5525421afd939f4").unwrap().into(),
+            previous_signature: hex::decode("84b25f31695f8df14402298120202352b0add7a9cc88c8a2ef882eaa1c3873bb9621bf2745deb8ea31146db7ad3f1d7c050603a87bbd8e8afc886947f8906fc09edbd93d9d3501c66f8fb7a741c808b41039d58384b40b07df50a000940383a5").unwrap().into(),
+            randomness: None,
+        };
+        assert_eq!(
+            hex::encode(chained.randomness()),
+            "b071381f8db17369c8d967680dbd65813435570ac7e292cf2297a53fc309fd3f"
+        );
+        let unchained = UnChainedBeacon {
+            round: 2,
+            signature: hex::decode("a738e019bf68b0c263b7c96a1eddb21af7e5123a6e512ddafb6842443fdfdcbf93037d3306dd4b37d5906d40838f106f").unwrap().into(),
+            randomness: None,
+        };
+        assert_eq!(
+            hex::encode(unchained.randomness()),
+            "9fb731c2cf0c08115a0f6b020a1cacfdca89ba454149e943f758f5acdafe3422"
+        );
+        // Previous signature does not affect randomness.
+        let other = ChainedBeacon {
+            previous_signature: Bytes::from_static(&[2]),
+            ..chained.clone()
+        };
+        assert_eq!(other.randomness(), chained.randomness());
+    }
+
+    #[test]
+    fn hot_cache_lru() {
+        let beacons = generate_unchained(4);
+        let mut hot = HotCache::new(3);
+        for b in &beacons[..3] {
+            hot.insert(b.clone());
+        }
+        // Round 0 becomes most recently used, round 1 is evicted next.
+        assert!(hot.get(0).unwrap() == beacons[0]);
+        hot.insert(beacons[3].clone());
+        assert!(hot.get(1).is_none());
+        assert!(hot.get(2).unwrap() == beacons[2]);
+        assert!(hot.get(3).unwrap() == beacons[3]);
+        assert!(hot.get(0).unwrap() == beacons[0]);
+
+        // Reinserted round is not duplicated.
+        hot.insert(beacons[2].clone());
+        assert!(hot.beacons.len() == 3);
+    }
+
     #[tokio::test]
     async fn unchained_store() {
         let temp_dir = tempfile::TempDir::new().unwrap();
@@ -588,9 +1213,13 @@ mod test {
 
         let total_beacons = 555;
         let beacons = generate_unchained(total_beacons);
-        let store = ChainStore::<UnChainedBeacon>::start(db_path.to_path_buf(), id.to_string())
-            .await
-            .unwrap();
+        let store = ChainStore::<UnChainedBeacon>::start(
+            db_path.to_path_buf(),
+            id.to_string(),
+            StorageMode::Full,
+        )
+        .await
+        .unwrap();
 
         // Add all beacons to the store.
         for b in &beacons {
@@ -605,7 +1234,8 @@ mod test {
 
         // Sync from this store; get all beacons as protobuf packets.
         let from_round = 1;
-        let mut stream_rx = sync::<UnChainedBeacon>(db_path, from_round, id).unwrap();
+        let mut stream_rx =
+            sync::<UnChainedBeacon>(db_path, from_round, id, CorruptRounds::default()).unwrap();
 
         // Streamed data should match internal repr.
         let expected_prev_sig: Vec<u8> = vec![];
@@ -637,9 +1267,13 @@ mod test {
 
         let total_beacons = 555;
         let beacons = generate_chained(total_beacons);
-        let store = ChainStore::<ChainedBeacon>::start(db_path.to_path_buf(), id.to_string())
-            .await
-            .unwrap();
+        let store = ChainStore::<ChainedBeacon>::start(
+            db_path.to_path_buf(),
+            id.to_string(),
+            StorageMode::Full,
+        )
+        .await
+        .unwrap();
 
         // Add all beacons to the store.
         for b in &beacons {
@@ -652,9 +1286,26 @@ mod test {
             assert!(store.get(i).await.unwrap() == beacons[usize::try_from(i).unwrap()]);
         }
 
+        // Merkle tree covers all rounds except genesis.
+        let (beacon, proof) = store.merkle_proof(100).await.unwrap();
+        assert!(proof.tree_size == total_beacons);
+        let leaf = merkle::leaf_hash(100, beacon.signature());
+        assert!(merkle::verify(
+            &leaf,
+            100,
+            total_beacons,
+            &proof.path,
+            &proof.root
+        ));
+        assert!(matches!(
+            store.merkle_proof(total_beacons + 1).await,
+            Err(StoreError::NotFound)
+        ));
+
         // Sync from this store; get all beacons as protobuf packets.
         let from_round = 1;
-        let mut stream_rx = sync::<ChainedBeacon>(db_path, from_round, id).unwrap();
+        let mut stream_rx =
+            sync::<ChainedBeacon>(db_path, from_round, id, CorruptRounds::default()).unwrap();
 
         // Streamed data should match internal repr.
         for i in 1..=total_beacons {
@@ -676,4 +1327,281 @@ mod test {
             }
         }
     }
+
+    #[tokio::test]
+    async fn corrupted_record() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path();
+        let id = "some_id";
+
+        let beacons = generate_chained(10);
+        let store = ChainStore::<ChainedBeacon>::start(
+            db_path.to_path_buf(),
+            id.to_string(),
+            StorageMode::Full,
+        )
+        .await
+        .unwrap();
+        for b in &beacons {
+            store.put(b.clone()).await.unwrap();
+        }
+
+        // Flip signature of round 5 bypassing the store, reopened store has empty hot cache.
+        let conn = Connection::open(db_path.join(DB_NAME)).unwrap();
+        conn.execute("UPDATE beacons SET signature = x'00' WHERE round = 5", [])
+            .unwrap();
+        drop(store);
+        let store = ChainStore::<ChainedBeacon>::start(
+            db_path.to_path_buf(),
+            id.to_string(),
+            StorageMode::Full,
+        )
+        .await
+        .unwrap();
+        assert!(store.get(4).await.unwrap() == beacons[4]);
+        assert!(matches!(store.get(5).await, Err(StoreError::Corrupt(5))));
+
+        // Sync stream stops at corrupted record.
+        let mut stream_rx = sync::<ChainedBeacon>(db_path, 1, id, store.corrupt.clone()).unwrap();
+        let err = stream_rx.recv().await.unwrap().unwrap_err();
+        assert!(err.code() == tonic::Code::DataLoss);
+        assert!(store.take_corrupted() == [5]);
+        assert!(store.take_corrupted().is_empty());
+
+        store.repair(beacons[5].clone()).await.unwrap();
+        assert!(store.get(5).await.unwrap() == beacons[5]);
+    }
+
+    #[tokio::test]
+    async fn filled_gap() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path();
+        let id = "some_id";
+
+        let beacons = generate_chained(10);
+        let store = ChainStore::<ChainedBeacon>::start(
+            db_path.to_path_buf(),
+            id.to_string(),
+            StorageMode::Full,
+        )
+        .await
+        .unwrap();
+        for b in beacons.iter().filter(|b| !(4..=6).contains(&b.round)) {
+            store.put(b.clone()).await.unwrap();
+        }
+        assert!(store.missing().await.unwrap() == [(4, 6)]);
+        assert!(matches!(
+            store.merkle_proof(8).await,
+            Err(StoreError::NotFound)
+        ));
+
+        // Merkle tree continues over rounds stored after the gap.
+        for b in &beacons[4..=6] {
+            store.put(b.clone()).await.unwrap();
+        }
+        assert!(store.missing().await.unwrap().is_empty());
+        let (_, proof) = store.merkle_proof(8).await.unwrap();
+        assert!(proof.tree_size == 10);
+    }
+
+    #[tokio::test]
+    async fn repaired_range_proofs() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path();
+        let id = "some_id";
+
+        let beacons = generate_chained(20);
+        let store = ChainStore::<ChainedBeacon>::start(
+            db_path.to_path_buf(),
+            id.to_string(),
+            StorageMode::Full,
+        )
+        .await
+        .unwrap();
+        for b in &beacons {
+            store.put(b.clone()).await.unwrap();
+        }
+
+        // Range is rewritten as by forced resync.
+        for b in &beacons[5..=12] {
+            let mut b = b.clone();
+            b.signature = Bytes::copy_from_slice(&(b.round + 100).to_be_bytes());
+            store.repair(b).await.unwrap();
+        }
+        for round in 1..=20u64 {
+            let (beacon, proof) = store.merkle_proof(round).await.unwrap();
+            assert!(proof.tree_size == 20);
+            let leaf = merkle::leaf_hash(round, beacon.signature());
+            assert!(merkle::verify(&leaf, round, 20, &proof.path, &proof.root));
+            if (5..=12).contains(&round) {
+                let old = merkle::leaf_hash(round, &round.to_be_bytes());
+                assert!(!merkle::verify(&old, round, 20, &proof.path, &proof.root));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn derived_storage() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path();
+        let id = "some_id";
+
+        let beacons = generate_chained(6);
+        let store = ChainStore::<ChainedBeacon>::start(
+            db_path.to_path_buf(),
+            id.to_string(),
+            StorageMode::Derived,
+        )
+        .await
+        .unwrap();
+        // Round 3 is stored without its previous round, so it keeps the previous signature.
+        for b in beacons.iter().filter(|b| b.round != 2) {
+            store.put(b.clone()).await.unwrap();
+        }
+        store.put(beacons[2].clone()).await.unwrap();
+        drop(store);
+
+        let conn = Connection::open(db_path.join(DB_NAME)).unwrap();
+        let stored: Vec<(u64, usize, Option<Vec<u8>>)> = conn
+            .prepare("SELECT round, length(previous_sig), randomness FROM beacons ORDER BY round")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let previous_lens: Vec<_> = stored.iter().map(|(_, len, _)| *len).collect();
+        assert_eq!(previous_lens, [0, 0, 0, 8, 0, 0, 0]);
+        assert!(stored.iter().all(|(_, _, r)| r.is_none()));
+
+        // Records are read the same way in all modes.
+        let store = ChainStore::<ChainedBeacon>::start(
+            db_path.to_path_buf(),
+            id.to_string(),
+            StorageMode::Full,
+        )
+        .await
+        .unwrap();
+        for b in &beacons {
+            let stored = store.get(b.round).await.unwrap();
+            assert!(stored == *b);
+            assert_eq!(stored.randomness(), randomness(&b.signature));
+        }
+        // Default mode keeps the layout of beacon packets, randomness is not stored.
+        let next = generate_chained(8);
+        store.put(next[7].clone()).await.unwrap();
+        drop(store);
+        let stored_row = |round: u64| -> (Vec<u8>, Option<Vec<u8>>) {
+            conn.query_row(
+                "SELECT previous_sig, randomness FROM beacons WHERE round = ?1",
+                [round],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap()
+        };
+        let (previous, stored_randomness) = stored_row(7);
+        assert!(previous == next[7].previous_signature);
+        assert!(stored_randomness.is_none());
+
+        let store = ChainStore::<ChainedBeacon>::start(
+            db_path.to_path_buf(),
+            id.to_string(),
+            StorageMode::Precomputed,
+        )
+        .await
+        .unwrap();
+        store.put(next[8].clone()).await.unwrap();
+        let (previous, stored_randomness) = stored_row(8);
+        assert!(previous == next[8].previous_signature);
+        assert!(stored_randomness == Some(randomness(&next[8].signature).to_vec()));
+        drop(store);
+
+        // Record of round 4 is replaced by a self-consistent one, derived previous signature of
+        // round 5 no longer matches its checksum.
+        let tampered = [0xff; 8];
+        conn.execute(
+            "UPDATE beacons SET signature = ?1, checksum = ?2 WHERE round = 4",
+            params![
+                tampered.as_slice(),
+                checksum(4, &tampered, &beacons[3].signature, &[])
+            ],
+        )
+        .unwrap();
+        let store = ChainStore::<ChainedBeacon>::start(
+            db_path.to_path_buf(),
+            id.to_string(),
+            StorageMode::Derived,
+        )
+        .await
+        .unwrap();
+        assert!(store.get(4).await.is_ok());
+        assert!(matches!(store.get(5).await, Err(StoreError::Corrupt(5))));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn concurrent_follow_in_follow_out() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().to_path_buf();
+        let id = "some_id";
+
+        let total_beacons = 1000;
+        let beacons = generate_chained(total_beacons);
+        let store =
+            ChainStore::<ChainedBeacon>::start(db_path.clone(), id.to_string(), StorageMode::Full)
+                .await
+                .unwrap();
+        store.put(beacons[0].clone()).await.unwrap();
+
+        // Follow-in: beacons are ingested one by one.
+        let writer = tokio::spawn({
+            let store = store.clone();
+            async move {
+                for b in beacons.into_iter().skip(1) {
+                    store.put(b).await.unwrap();
+                }
+            }
+        });
+
+        // Follow-out: readers are streaming from the store while it is written.
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let db_path = db_path.clone();
+                tokio::spawn(async move {
+                    let mut latest_head = 0;
+                    while latest_head < total_beacons {
+                        let mut stream_rx =
+                            sync::<ChainedBeacon>(&db_path, 1, id, CorruptRounds::default())
+                                .unwrap();
+                        let mut expected = 1;
+                        let head = loop {
+                            match stream_rx.recv().await.unwrap() {
+                                Ok(packet) => {
+                                    assert!(packet.round == expected);
+                                    assert!(
+                                        packet.previous_signature
+                                            == (expected - 1).to_be_bytes().as_slice()
+                                    );
+                                    expected += 1;
+                                }
+                                Err(err) => {
+                                    let head = expected - 1;
+                                    let expected_err =
+                                        format!("no beacons stored above {head} round");
+                                    assert!(err.message() == expected_err);
+                                    break head;
+                                }
+                            }
+                        };
+                        // Snapshots are contiguous and never go backwards.
+                        assert!(head >= latest_head);
+                        latest_head = head;
+                    }
+                })
+            })
+            .collect();
+
+        writer.await.unwrap();
+        for reader in readers {
+            reader.await.unwrap();
+        }
+    }
 }
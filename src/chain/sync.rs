@@ -4,29 +4,54 @@
 //!   download historical beacons up to current height from chain node.
 //! - Resync is triggered automatically by chain nodes once latest stored
 //!   beacon is more than one round late for expected chain height.
+//!
+//! Trust model of sync: the chain hash of the request is the only trust root, sync nodes and
+//! relays are not trusted. Chain info is accepted only from a node whose info hashes to the
+//! pinned chain hash, see [`check_trust_root`], nodes serving another chain are skipped. Every
+//! fetched beacon is then verified with the group key of the accepted info, see [`VerifyMode`].
 use super::info::ChainInfo;
+use super::info::KeySchedule;
 use super::store::BeaconRepr;
 use super::store::ChainStore;
+use super::time::SharedClock;
 use super::StoreError;
 
+use crate::core::events::Event;
+use crate::core::events::EventSender;
 use crate::key::Scheme;
+use crate::log::Throttle;
 use crate::net::control::SyncProgressResponse;
+use crate::net::peers::SYNC_PEERS;
+use crate::net::protocol::Buffered;
 use crate::net::protocol::ProtocolClient;
-use crate::net::public::PublicClient;
+use crate::net::protocol::SyncMemory;
+use crate::net::relay::parse_info;
+use crate::net::relay::HttpArchive;
+use crate::net::relay::HttpRelay;
 use crate::net::utils::Address;
 use crate::net::utils::Seconds;
 use crate::protobuf::drand::BeaconPacket;
 use crate::protobuf::drand::ChainInfoPacket;
 use crate::protobuf::drand::StartSyncRequest;
+use crate::protobuf::drand::StatusResponse;
 use crate::protobuf::drand::SyncProgress;
 
+use energon::drand::traits::BeaconDigest;
+use energon::points::KeyPoint;
 use energon::traits::Affine;
 use rand::seq::SliceRandom;
+use serde_json::json;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
 use tokio::sync::mpsc;
+use tokio::sync::watch;
 use tokio::task;
 use tokio::task::JoinHandle;
-use tokio::time::Instant;
 use tonic::Status;
 use tracing::debug;
 use tracing::error;
@@ -37,8 +62,8 @@ use tracing::Span;
 /// Renew resync if no beacons received for factor*period duration.
 const RESYNC_EXPIRY_FACTOR: u8 = 2;
 
-/// Used to reduce log verbosity when doing bulk processes.
-pub const LOGS_TO_SKIP: u64 = 300;
+/// Default interval (in rounds) between verified beacons for [`VerifyMode::SpotCheck`].
+pub const DEFAULT_SPOT_CHECK_EVERY: u64 = 1000;
 
 #[derive(thiserror::Error, Debug)]
 pub enum SyncError {
@@ -50,6 +75,10 @@ pub enum SyncError {
     AlreadySyncing,
     #[error("all peers should be in valid format")]
     PeersInvalidFormat,
+    #[error("invalid relay url: {0}")]
+    InvalidRelay(String),
+    #[error("beacon archive: {0}")]
+    Archive(String),
     #[error("failed to get chain info from all peers")]
     FailedInfoFromAllPeers,
     #[error("chain hash mismatch: {0}")]
@@ -66,16 +95,114 @@ pub enum SyncError {
     TriedAllPers { last: u64 },
     #[error("`follow_request` allowed only for nodes without DKG setup")]
     ForbiddenToFollow,
+    #[error("unknown verification mode: {0}")]
+    InvalidVerifyMode(u32),
+    #[error("checkpoint should have non-zero round and non-empty signature")]
+    InvalidCheckpoint,
+    #[error("relay: http api is stopped")]
+    HttpApi,
+}
+
+/// Verification mode applied to beacons received by `follow` request.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(u32)]
+pub enum VerifyMode {
+    /// Signature of every received beacon is verified.
+    Full,
+    /// Signatures are verified for every Nth round and for the final round.
+    /// Chained schemes still check linkage to previous signature for each beacon.
+    SpotCheck,
+    /// Beacons are stored without signature verification, intended for mirrors.
+    Trust,
+}
+
+impl TryFrom<u32> for VerifyMode {
+    type Error = SyncError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(VerifyMode::Full),
+            1 => Ok(VerifyMode::SpotCheck),
+            2 => Ok(VerifyMode::Trust),
+            _ => Err(SyncError::InvalidVerifyMode(value)),
+        }
+    }
+}
+
+impl std::fmt::Display for VerifyMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerifyMode::Full => f.write_str("full"),
+            VerifyMode::SpotCheck => f.write_str("spot-check"),
+            VerifyMode::Trust => f.write_str("trust"),
+        }
+    }
+}
+
+impl FromStr for VerifyMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(VerifyMode::Full),
+            "spot-check" => Ok(VerifyMode::SpotCheck),
+            "trust" => Ok(VerifyMode::Trust),
+            _ => Err(format!(
+                "unknown verification mode {s}, expected: full, spot-check, trust"
+            )),
+        }
+    }
+}
+
+/// Verification policy of [`DefaultSyncer`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VerifyPolicy {
+    mode: VerifyMode,
+    /// Interval in rounds, used only by [`VerifyMode::SpotCheck`].
+    every: u64,
+}
+
+impl VerifyPolicy {
+    pub fn from_request(req: &StartSyncRequest) -> Result<Self, SyncError> {
+        let mode = VerifyMode::try_from(req.verify_mode)?;
+        let every = if req.spot_check_every == 0 {
+            DEFAULT_SPOT_CHECK_EVERY
+        } else {
+            req.spot_check_every
+        };
+
+        Ok(Self { mode, every })
+    }
+
+    /// Returns `true` if signature for given round should be verified.
+    fn should_verify(self, round: u64, target: u64) -> bool {
+        match self.mode {
+            VerifyMode::Full => true,
+            VerifyMode::SpotCheck => round % self.every == 0 || round == target,
+            VerifyMode::Trust => false,
+        }
+    }
+
+    /// Label reported to control client within sync progress.
+    pub fn label(self) -> String {
+        match self.mode {
+            VerifyMode::SpotCheck => format!("{} (every {} rounds)", self.mode, self.every),
+            _ => self.mode.to_string(),
+        }
+    }
 }
 
 /// Wrapper around `JoinHandle` for resync task, including task state.
 pub struct HandleReSync {
     /// Handle for resync task.
     handle: JoinHandle<Result<(), SyncError>>,
-    /// Time of latest received beacon from resync task.
-    latest_received: Instant,
+    /// Unix time of latest received beacon from resync task.
+    latest_received: Duration,
     /// Expiry factor for the handle.
     factor: Duration,
+    /// Peer currently used by resync task.
+    peer: watch::Receiver<Option<Address>>,
+    clock: SharedClock,
 }
 
 impl Drop for HandleReSync {
@@ -86,37 +213,236 @@ impl Drop for HandleReSync {
 
 impl HandleReSync {
     /// Registers a new resync task.
-    pub fn new(period: Seconds, handle: JoinHandle<Result<(), SyncError>>) -> Self {
+    pub fn new(
+        period: Seconds,
+        handle: JoinHandle<Result<(), SyncError>>,
+        peer: watch::Receiver<Option<Address>>,
+        clock: SharedClock,
+    ) -> Self {
         Self {
-            latest_received: Instant::now(),
+            latest_received: clock.now(),
             handle,
             factor: Duration::from_secs(
                 (period.get_value() * u32::from(RESYNC_EXPIRY_FACTOR)).into(),
             ),
+            peer,
+            clock,
         }
     }
 
+    fn is_expired(&self) -> bool {
+        self.clock.now().saturating_sub(self.latest_received) >= self.factor
+    }
+
+    /// Returns peer which stalled the resync task: task is still running but not making progress.
+    pub fn stalled_peer(&self) -> Option<Address> {
+        if self.handle.is_finished() || !self.is_expired() {
+            None
+        } else {
+            self.peer.borrow().clone()
+        }
+    }
+
+    /// Returns the peer which resync is streaming from.
+    pub fn peer(&self) -> Option<Address> {
+        self.peer.borrow().clone()
+    }
+
     /// Returns `true` if resync is running and making progress.
     pub fn is_running(&self) -> bool {
         if self.handle.is_finished() {
             false
         } else {
-            self.latest_received.elapsed() < self.factor
+            !self.is_expired()
         }
     }
 
     /// Updates handle expiry time once new beacon received.
     pub fn update_last_received_time(&mut self) {
-        self.latest_received = Instant::now();
+        self.latest_received = self.clock.now();
+    }
+}
+
+/// Structured events emitted by resync task.
+pub enum ResyncEvent<'a> {
+    Start { from: u64, up_to: u64 },
+    PeerSwitch { peer: &'a Address, from: u64 },
+    Complete { last: u64 },
+    Failure { reason: &'a SyncError },
+    Summary { summary: &'a str },
+}
+
+impl std::fmt::Display for ResyncEvent<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResyncEvent::Start { from, up_to } => write!(
+                f,
+                "{{\"resync_event\": \"start\", \"from\": {from}, \"up_to\": {up_to}}}"
+            ),
+            ResyncEvent::PeerSwitch { peer, from } => write!(
+                f,
+                "{{\"resync_event\": \"peer_switch\", \"peer\": \"{peer}\", \"from\": {from}}}"
+            ),
+            ResyncEvent::Complete { last } => {
+                write!(f, "{{\"resync_event\": \"complete\", \"last\": {last}}}")
+            }
+            ResyncEvent::Failure { reason } => write!(
+                f,
+                "{{\"resync_event\": \"failure\", \"reason\": \"{reason}\"}}"
+            ),
+            ResyncEvent::Summary { summary } => write!(
+                f,
+                "{{\"resync_event\": \"summary\", \"summary\": {summary}}}"
+            ),
+        }
+    }
+}
+
+/// Counters accumulated across all resync tasks of the chain.
+///
+/// Repeated resyncs usually indicate clock or network trouble.
+#[derive(Default)]
+pub struct ResyncMetrics {
+    /// Amount of started resync tasks.
+    pub started: AtomicU64,
+    /// Amount of beacons received from peers.
+    pub rounds_fetched: AtomicU64,
+    /// Amount of received beacons which failed verification.
+    pub invalid_beacons: AtomicU64,
+    /// Amount of peers skipped due to connection, stream or data errors.
+    pub peer_failures: AtomicU64,
+}
+
+impl ResyncMetrics {
+    /// Returns status response with current counters.
+    pub fn status(&self, latest_stored_round: u64) -> StatusResponse {
+        StatusResponse {
+            latest_stored_round,
+            resync_started: self.started.load(Ordering::Relaxed),
+            resync_rounds_fetched: self.rounds_fetched.load(Ordering::Relaxed),
+            resync_invalid_beacons: self.invalid_beacons.load(Ordering::Relaxed),
+            resync_peer_failures: self.peer_failures.load(Ordering::Relaxed),
+            ..Default::default()
+        }
+    }
+}
+
+/// Summary of a follow or resync session, reported once when the session is over: logged,
+/// emitted as `sync_summary` event and sent with the final [`SyncProgress`] of follow requests.
+pub struct SyncSummary {
+    started: Instant,
+    /// Rounds fetched from each source, in order of use.
+    fetched: Vec<(String, u64)>,
+    /// Sources skipped due to connection, stream or data errors.
+    retries: u32,
+    /// Received beacons which failed verification.
+    verification_failures: u64,
+}
+
+impl SyncSummary {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            fetched: vec![],
+            retries: 0,
+            verification_failures: 0,
+        }
+    }
+
+    /// Counts a round fetched from the source.
+    pub fn fetched(&mut self, source: &str) {
+        match self.fetched.last_mut() {
+            Some((last, rounds)) if last == source => *rounds += 1,
+            _ => self.fetched.push((source.to_string(), 1)),
+        }
+    }
+
+    pub fn retry(&mut self) {
+        self.retries += 1;
+    }
+
+    pub fn verification_failure(&mut self) {
+        self.verification_failures += 1;
+    }
+
+    pub fn rounds(&self) -> u64 {
+        self.fetched.iter().map(|(_, rounds)| rounds).sum()
+    }
+
+    /// Returns summary as a single line JSON, `outcome` is `complete` or the error.
+    pub fn to_json(&self, outcome: &str, last: u64) -> String {
+        let sources: Vec<_> = self
+            .fetched
+            .iter()
+            .map(|(source, rounds)| json!({"source": source, "rounds": rounds}))
+            .collect();
+
+        json!({
+            "outcome": outcome,
+            "last": last,
+            "rounds": self.rounds(),
+            "duration_ms": u64::try_from(self.started.elapsed().as_millis()).unwrap_or(u64::MAX),
+            "retries": self.retries,
+            "verification_failures": self.verification_failures,
+            "sources": sources,
+        })
+        .to_string()
+    }
+}
+
+impl Default for SyncSummary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Trusted `(round, signature)` anchor. Sync starts from the checkpoint instead of genesis:
+/// beacon for checkpoint round is accepted only if its signature matches the trusted one and
+/// is valid for the group key of the chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    round: u64,
+    signature: Vec<u8>,
+}
+
+impl Checkpoint {
+    /// Returns `None` if checkpoint is not set in the request.
+    pub fn from_request(req: &StartSyncRequest) -> Result<Option<Self>, SyncError> {
+        match (req.checkpoint_round, req.checkpoint_signature.is_empty()) {
+            (0, true) => Ok(None),
+            (0, false) | (_, true) => Err(SyncError::InvalidCheckpoint),
+            (round, false) => Ok(Some(Self {
+                round,
+                signature: req.checkpoint_signature.clone(),
+            })),
+        }
+    }
+
+    pub fn round(&self) -> u64 {
+        self.round
+    }
+
+    /// Returns true if the checkpoint signature is valid for the group key, previous signature
+    /// of chained schemes is given by the sync node.
+    fn is_signed<S: Scheme>(&self, public_key: &KeyPoint<S>, previous_signature: &[u8]) -> bool {
+        Affine::deserialize(&self.signature).is_ok_and(|sig| {
+            super::is_valid_signature::<S>(public_key, previous_signature, self.round, &sig)
+        })
     }
 }
 
 /// Initial config for `follow` request. Used to start [`DefaultSyncer`].
+#[derive(Clone)]
 pub struct DefaultSyncerConfig<B: BeaconRepr> {
     store: ChainStore<B>,
     packet: ChainInfoPacket,
     beacon_id: String,
     peers: Vec<Address>,
+    relays: Vec<HttpRelay>,
+    archive: Option<HttpArchive>,
+    policy: VerifyPolicy,
+    checkpoint: Option<Checkpoint>,
+    memory: SyncMemory,
     l: Span,
 }
 
@@ -125,6 +451,11 @@ impl<B: BeaconRepr> DefaultSyncerConfig<B> {
         ChainInfo::<S>::from_packet(&self.packet, self.beacon_id.clone())
             .ok_or(SyncError::InvalidInfoPacket)
     }
+
+    /// Returns chain info packet received from sync nodes.
+    pub fn packet(&self) -> &ChainInfoPacket {
+        &self.packet
+    }
 }
 
 /// Default syncer used for nodes without DKG setup.
@@ -132,6 +463,16 @@ pub struct DefaultSyncer<S: Scheme, B: BeaconRepr> {
     store: ChainStore<B>,
     info: ChainInfo<S>,
     peers: Vec<Address>,
+    /// HTTP relays tried in between of failed peers, see [`interleave`].
+    relays: Vec<HttpRelay>,
+    /// Beacon archive imported before contacting peers.
+    archive: Option<HttpArchive>,
+    policy: VerifyPolicy,
+    checkpoint: Option<Checkpoint>,
+    /// Memory of beacons buffered by relay and archive streams.
+    memory: SyncMemory,
+    /// Receives summary of the session if set.
+    events: Option<EventSender>,
     l: Span,
 }
 
@@ -142,6 +483,11 @@ impl<S: Scheme, B: BeaconRepr> DefaultSyncer<S, B> {
             packet,
             beacon_id,
             peers,
+            relays,
+            archive,
+            policy,
+            checkpoint,
+            memory,
             l,
         } = c;
 
@@ -159,12 +505,24 @@ impl<S: Scheme, B: BeaconRepr> DefaultSyncer<S, B> {
             store,
             info,
             peers,
+            relays,
+            archive,
+            policy,
+            checkpoint,
+            memory,
+            events: None,
             l,
         };
 
         Ok(syncer)
     }
 
+    /// Emits summary of the session as `sync_summary` event.
+    pub fn with_events(mut self, events: EventSender) -> Self {
+        self.events = Some(events);
+        self
+    }
+
     pub fn process_follow_request(
         self,
         target: u64,
@@ -178,39 +536,114 @@ impl<S: Scheme, B: BeaconRepr> DefaultSyncer<S, B> {
                 warn!(parent: l, "request rejected: target {target}, latest_stored {}", last_stored.round());
                 return Ok(());
             }
-            info!(parent: l, "processing request, target: {target}, latest_stored {}", last_stored.round());
-            let started_from = last_stored.round();
+            let mode = self.policy.label();
+            info!(parent: l, "processing request, target: {target}, latest_stored {}, verify_mode: {mode}", last_stored.round());
+            let mut throttle = Throttle::new();
+            let mut summary = SyncSummary::new();
+            let events = self.events.as_ref();
+            // Reports summary of the session, relays follow a round per period, so single rounds are not logged.
+            let report = |summary: &SyncSummary, outcome: &str, last: u64| {
+                let json = summary.to_json(outcome, last);
+                if summary.rounds() > 1 {
+                    info!(parent: l, "sync summary: {json}");
+                } else {
+                    debug!(parent: l, "sync summary: {json}");
+                }
+                if let Some(events) = events {
+                    events.emit(
+                        &self.info.beacon_id,
+                        &Event::SyncSummary {
+                            last,
+                            summary: &json,
+                        },
+                    );
+                }
+                json
+            };
 
-            if target - started_from > LOGS_TO_SKIP {
-                debug!(parent: l, "logging will use rate limiting, skipping logs: {LOGS_TO_SKIP}");
+            // Checkpoint is ignored if chain is already synced beyond it.
+            let mut checkpoint = self
+                .checkpoint
+                .as_ref()
+                .filter(|c| c.round > last_stored.round());
+            if let Some(c) = checkpoint {
+                info!(parent: l, "starting from trusted checkpoint, round {}", c.round);
             }
 
             // Peers are randomly sorted on configuration step (see [start_follow_chain]).
-            'peers: for peer in &self.peers {
-                let from = last_stored.round() + 1;
+            // Archive goes first, sync is continued from peers once it is exhausted.
+            let archive = self.archive.as_ref().map(Source::Archive);
+            if let Some(archive) = &self.archive {
+                info!(parent: l, "importing beacons from archive {} up to round {}", archive.url(), archive.last_round());
+            }
+            let sources = archive
+                .into_iter()
+                .chain(interleave(&self.peers, &self.relays));
+
+            let final_progress = |current: u64, summary: String| SyncProgress {
+                current,
+                target,
+                metadata: None,
+                verify_mode: mode.clone(),
+                summary,
+            };
+
+            'peers: for (attempt, peer) in sources.enumerate() {
+                if attempt > 0 {
+                    summary.retry();
+                }
+                let from = checkpoint.map_or(last_stored.round() + 1, |c| c.round);
                 if target < from {
                     let err = SyncError::InvalidTarget { from, target };
                     error!(parent: l, "latest stored round {}, {err}", last_stored.round());
+                    report(&summary, &err.to_string(), last_stored.round());
                     return Err(err);
                 }
 
-                let mut stream = match ProtocolClient::new(peer).await {
-                    Ok(mut client) => {
-                        match client.sync_chain(from, self.info.beacon_id.clone()).await {
-                            Ok(stream) => stream,
+                // Stream slot of the shared peer connection is held until the peer is done.
+                let mut _lease = None;
+                let mut stream = match peer {
+                    Source::Peer(address) => {
+                        match SYNC_PEERS.lease(address, &self.info.beacon_id).await {
+                            Ok(lease) => {
+                                let mut client = lease.protocol();
+                                match client.sync_chain(from, self.info.beacon_id.clone()).await {
+                                    Ok(stream) => {
+                                        _lease = Some(lease);
+                                        SourceStream::Grpc(stream)
+                                    }
+                                    Err(err) => {
+                                        if is_transport_error(&err) {
+                                            SYNC_PEERS.reset(&lease).await;
+                                        }
+                                        error!(parent: l, "skipping {peer}: failed to get stream: {err}");
+                                        continue;
+                                    }
+                                }
+                            }
                             Err(err) => {
-                                error!(parent: l, "skipping {peer}: failed to get stream: {err}");
+                                error!(parent: l, "skipping {peer}: unable to create client: {err}");
                                 continue;
                             }
                         }
                     }
-                    Err(err) => {
-                        error!(parent: l, "skipping {peer}: unable to create client: {err}");
-                        continue;
-                    }
+                    Source::Relay(relay) => SourceStream::Http(relay.clone().stream(
+                        from,
+                        target,
+                        self.info.beacon_id.clone(),
+                        self.memory.clone(),
+                    )),
+                    Source::Archive(archive) => SourceStream::Http(archive.clone().stream(
+                        from,
+                        target,
+                        self.info.beacon_id.clone(),
+                        self.memory.clone(),
+                    )),
                 };
+                info!(parent: l, "syncing from {peer}, from_round {from}");
+                let source = peer.to_string();
 
-                while let Ok(Some(p)) = stream.message().await {
+                while let Some(p) = stream.message().await {
                     let Some(ref meta) = p.metadata else {
                         error!(parent: l, "stream: skipping {peer}: no metadata for round {}", p.round);
                         continue 'peers;
@@ -220,53 +653,91 @@ impl<S: Scheme, B: BeaconRepr> DefaultSyncer<S, B> {
                         error!(parent: l, "stream: skipping {peer}: invalid beacon_id {} for round {}", meta.beacon_id, p.round);
                         continue 'peers;
                     }
+                    if let Some(c) = checkpoint {
+                        if p.round != c.round || p.signature != c.signature {
+                            error!(parent: l, "stream: skipping {peer}: beacon does not match checkpoint, round expected {}, received {}", c.round, p.round);
+                            continue 'peers;
+                        }
+                        if !c.is_signed(&self.info.public_key, &p.previous_signature) {
+                            error!(parent: l, "stream: skipping {peer}: checkpoint signature is not valid for the group key, round {}", c.round);
+                            summary.verification_failure();
+                            continue 'peers;
+                        }
+                        // Checkpoint beacon is verified - verification continues from it.
+                        let anchor = B::from_packet(p);
+                        if let Err(err) = self.store.put(anchor.clone()).await {
+                            error!(parent: l, "failed to store checkpoint beacon for round {}: {err}", anchor.round());
+                            let err = SyncError::ChainStore(err);
+                            report(&summary, &err.to_string(), last_stored.round());
+                            return Err(err);
+                        }
+                        last_stored = anchor;
+                        checkpoint = None;
+                        summary.fetched(&source);
+
+                        if last_stored.round() == target {
+                            let json = report(&summary, "complete", target);
+                            let _ = tx.send(Ok(final_progress(target, json))).await;
+                            return Ok(());
+                        }
+                        continue;
+                    }
                     if p.round != last_stored.round() + 1 {
                         error!(parent: l, "stream: skipping {peer}: round expected {}, received {}", last_stored.round()+1, p.round);
                         continue 'peers;
                     }
-                    if target - p.round < LOGS_TO_SKIP || p.round % LOGS_TO_SKIP == 0 {
-                        debug!(parent: l, "new_beacon_fetched, peer {peer}, from_round {from}, got_round {}", p.round);
+                    if let Some(skipped) = throttle.allow() {
+                        debug!(parent: l, "new_beacon_fetched, peer {peer}, from_round {from}, got_round {}, skipped_logs {skipped}", p.round);
                     }
 
                     // Verify beacon before moving data from packet.
                     let Ok(new_sig) = Affine::deserialize(&p.signature) else {
                         error!(parent: l, "stream: skipping peer {peer}: failed to deserialize signature for round {}", p.round);
+                        summary.verification_failure();
                         continue 'peers;
                     };
 
-                    if super::is_valid_signature::<S>(
-                        &self.info.public_key,
-                        last_stored.signature(),
-                        p.round,
-                        &new_sig,
-                    ) {
+                    let is_valid = if self.policy.should_verify(p.round, target) {
+                        super::is_valid_signature::<S>(
+                            &self.info.public_key,
+                            last_stored.signature(),
+                            p.round,
+                            &new_sig,
+                        )
+                    } else {
+                        // Unverified chained beacons should still be linked to latest stored.
+                        !S::Beacon::is_chained() || p.previous_signature == last_stored.signature()
+                    };
+
+                    if is_valid {
                         // Signature and round has been checked - beacon is valid.
                         let valid_beacon = B::from_packet(p);
                         if let Err(err) = self.store.put(valid_beacon.clone()).await {
                             error!(parent: l, "failed to store beacon for round {}: {err}", valid_beacon.round());
-                            return Err(SyncError::ChainStore(err));
+                            let err = SyncError::ChainStore(err);
+                            report(&summary, &err.to_string(), last_stored.round());
+                            return Err(err);
                         }
                         last_stored = valid_beacon;
+                        summary.fetched(&source);
 
+                        if last_stored.round() == target {
+                            let json = report(&summary, "complete", target);
+                            let _ = tx.send(Ok(final_progress(target, json))).await;
+                            return Ok(());
+                        }
                         // Report sync progress to control client side.
                         if tx
-                            .send(Ok(SyncProgress {
-                                current: last_stored.round(),
-                                target,
-                                metadata: None,
-                            }))
+                            .send(Ok(final_progress(last_stored.round(), String::new())))
                             .await
                             .is_err()
                         {
-                            debug!(parent: l, "aborted from client side, synced {}, latest_stored {}", last_stored.round() - started_from, last_stored.round());
-                            return Ok(());
-                        }
-                        if last_stored.round() == target {
-                            debug!(parent: l, "finished syncing up_to {target} round");
+                            report(&summary, "aborted by client", last_stored.round());
                             return Ok(());
                         }
                     } else {
                         error!(parent: l, "skipping peer {peer}: invalid beacon signature, round {}", p.round);
+                        summary.verification_failure();
                         continue 'peers;
                     }
                 }
@@ -277,8 +748,10 @@ impl<S: Scheme, B: BeaconRepr> DefaultSyncer<S, B> {
                     last: last_stored.round(),
                 };
 
-                let _ = tx.send(Err(Status::cancelled(err.to_string()))).await;
                 error!(parent: l, "finished with error: {err}");
+                let json = report(&summary, &err.to_string(), last_stored.round());
+                let _ = tx.send(Ok(final_progress(last_stored.round(), json))).await;
+                let _ = tx.send(Err(Status::cancelled(err.to_string()))).await;
                 return Err(err);
             }
 
@@ -291,124 +764,527 @@ pub async fn start_follow_chain<B: BeaconRepr>(
     req: &StartSyncRequest,
     beacon_id: &str,
     store: &ChainStore<B>,
+    memory: SyncMemory,
     l: Span,
 ) -> Result<DefaultSyncerConfig<B>, SyncError> {
-    info!(parent:&l, "start_follow_chain: up_to {}", req.up_to);
+    let policy = VerifyPolicy::from_request(req)?;
+    let checkpoint = Checkpoint::from_request(req)?;
+    info!(parent:&l, "start_follow_chain: up_to {}, verify_mode: {}", req.up_to, policy.label());
+
+    let (peers, relays) = parse_nodes(&req.nodes, &l)?;
+    // Chain info is checked against the chain hash of the request.
+    let packet = request_chain_info(req, &peers, &relays, beacon_id, &l).await?;
+    let hash = crate::client::chain_hash(&packet, beacon_id);
+    store.check_genesis(&packet.group_hash, &l).await?;
+    info!(parent: &l, "start_follow_chain: fetched chain info, hash {}", hex::encode(hash));
 
-    let mut peers = Vec::with_capacity(req.nodes.len());
-    for node in &req.nodes {
+    let archive = if req.archive_url.is_empty() {
+        None
+    } else {
+        let archive = HttpArchive::open(&req.archive_url, beacon_id, &hash)
+            .await
+            .map_err(|err| SyncError::Archive(format!("{}: {err}", req.archive_url)))?;
+        Some(archive)
+    };
+
+    let config = DefaultSyncerConfig {
+        store: store.clone(),
+        packet,
+        beacon_id: beacon_id.to_string(),
+        peers,
+        relays,
+        archive,
+        policy,
+        checkpoint,
+        memory,
+        l,
+    };
+
+    Ok(config)
+}
+
+/// Splits sync nodes into gRPC peers and HTTP relays, both in random order.
+pub(super) fn parse_nodes(
+    nodes: &[String],
+    l: &Span,
+) -> Result<(Vec<Address>, Vec<HttpRelay>), SyncError> {
+    let mut peers = Vec::with_capacity(nodes.len());
+    let mut relays = vec![];
+    for node in nodes {
+        if HttpRelay::is_relay_url(node) {
+            let relay = HttpRelay::new(node).map_err(|err| {
+                error!(parent: l, "invalid relay url {node}: {err}");
+                SyncError::InvalidRelay(node.clone())
+            })?;
+            relays.push(relay);
+            continue;
+        }
         match Address::precheck(node.as_str()) {
             Ok(peer) => peers.push(peer),
             Err(err) => {
-                error!(parent: &l, "invalid peer address: {err}");
+                error!(parent: l, "invalid peer address: {err}");
                 continue;
             }
         }
     }
-    if peers.is_empty() {
+    if peers.is_empty() && relays.is_empty() {
         return Err(SyncError::PeersInvalidFormat);
     }
 
     // Peers will be connected in random order.
     peers.shuffle(&mut rand::rng());
+    relays.shuffle(&mut rand::rng());
 
+    Ok((peers, relays))
+}
+
+/// Checks chain info served by an untrusted node against the pinned chain hash, returns the hash.
+///
+/// The chain hash covers group key, period, genesis time and seed, and beacon id, so info which
+/// hashes to the pinned value describes the pinned chain. Hash reported by the node must be
+/// consistent with the computed one.
+fn check_trust_root(
+    packet: &ChainInfoPacket,
+    beacon_id: &str,
+    pinned: &[u8],
+) -> Result<[u8; 32], SyncError> {
+    let computed = crate::client::chain_hash(packet, beacon_id);
+    if computed[..] != *pinned {
+        return Err(SyncError::ChainHashMismatch(format!(
+            "received {} != pinned {}",
+            hex::encode(computed),
+            hex::encode(pinned)
+        )));
+    }
+    if computed[..] != *packet.hash {
+        return Err(SyncError::ChainHashMismatch(format!(
+            "reported {} != computed {}",
+            hex::encode(&packet.hash),
+            hex::encode(computed)
+        )));
+    }
+
+    Ok(computed)
+}
+
+/// Fetches chain info from peers, relays are tried if all peers failed.
+async fn fetch_chain_info(
+    peers: &[Address],
+    relays: &[HttpRelay],
+    beacon_id: &str,
+    pinned: &[u8],
+    l: &Span,
+) -> Result<ChainInfoPacket, SyncError> {
     // Packet beacon ID from metadata should match the chain config ID.
-    let packet = chain_info_from_peers(&peers, beacon_id, &l).await?;
-    debug!(parent: &l, "received chain info from peers:\n{packet}");
+    match chain_info_from_peers(peers, beacon_id, pinned, l).await {
+        Ok(packet) => Ok(packet),
+        Err(peers_err) => chain_info_from_relays(relays, beacon_id, pinned, l)
+            .await
+            .map_err(|err| match err {
+                SyncError::ChainHashMismatch(_) => err,
+                _ => peers_err,
+            }),
+    }
+}
 
-    // Packet hash should match the chain hash of beacon process recorded in packet metadata.
-    let hash = super::info::hash_packet(&packet, beacon_id);
+/// Returns chain info seeded by the request, otherwise fetches it from sync nodes. Chain info
+/// is accepted only if it matches chain hash of the request, see [`check_trust_root`].
+async fn request_chain_info(
+    req: &StartSyncRequest,
+    peers: &[Address],
+    relays: &[HttpRelay],
+    beacon_id: &str,
+    l: &Span,
+) -> Result<ChainInfoPacket, SyncError> {
+    let pinned = req.metadata.as_ref().map_or(&[][..], |m| &m.chain_hash);
+    if req.chain_info.is_empty() {
+        let packet = fetch_chain_info(peers, relays, beacon_id, pinned, l).await?;
+        debug!(parent: l, "received chain info from peers:\n{packet}");
+        return Ok(packet);
+    }
+    let json = serde_json::from_str(&req.chain_info).map_err(|err| {
+        error!(parent: l, "seeded chain info: {err}");
+        SyncError::InvalidInfoPacket
+    })?;
+    // Beacon id reported by the chain info must match id of the request.
+    let packet = parse_info(&json, beacon_id).map_err(|err| {
+        error!(parent: l, "seeded chain info: {err}");
+        SyncError::InvalidInfoPacket
+    })?;
+    check_trust_root(&packet, beacon_id, pinned).inspect_err(|err| {
+        error!(parent: l, "seeded chain info: {err}");
+    })?;
 
-    if hash
-        != *req
-            .metadata
-            .as_ref()
-            .expect("metadata is already checked")
-            .chain_hash
-    {
-        let err_details = format!(
-            "rcv({}) != bp({})",
-            hex::encode(hash),
-            hex::encode(&packet.group_hash)
-        );
-        return Err(SyncError::ChainHashMismatch(err_details));
+    Ok(packet)
+}
+
+/// Returns chain info seeded by the request or served by sync nodes, used to pick the scheme of
+/// followed chain before the chain store is opened. Only the chain hash is checked, the group
+/// key is decoded for the scheme by [`DefaultSyncer::from_config`].
+pub async fn chain_info_from_request(
+    req: &StartSyncRequest,
+    beacon_id: &str,
+    l: &Span,
+) -> Result<ChainInfoPacket, SyncError> {
+    let (peers, relays) = parse_nodes(&req.nodes, l)?;
+
+    request_chain_info(req, &peers, &relays, beacon_id, l).await
+}
+
+/// Source of beacons for `follow` request.
+#[derive(Clone, Copy)]
+enum Source<'a> {
+    Peer(&'a Address),
+    Relay(&'a HttpRelay),
+    Archive(&'a HttpArchive),
+}
+
+impl Display for Source<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Peer(address) => write!(f, "{address}"),
+            Self::Relay(relay) => write!(f, "relay {}", relay.url()),
+            Self::Archive(archive) => write!(f, "archive {}", archive.url()),
+        }
     }
-    store.check_genesis(&packet.group_hash, &l).await?;
-    info!(parent: &l, "start_follow_chain: fetched chain info, hash {}", hex::encode(hash));
+}
 
-    let config = DefaultSyncerConfig {
-        store: store.clone(),
-        packet,
-        beacon_id: beacon_id.to_string(),
-        peers,
-        l,
+/// Stream of beacons received from [`Source`].
+enum SourceStream {
+    Grpc(tonic::Streaming<BeaconPacket>),
+    Http(mpsc::Receiver<Buffered<BeaconPacket>>),
+}
+
+impl SourceStream {
+    /// Returns `None` once the stream is finished or failed.
+    async fn message(&mut self) -> Option<BeaconPacket> {
+        match self {
+            Self::Grpc(stream) => stream.message().await.ok().flatten(),
+            Self::Http(rx) => rx.recv().await.map(Buffered::into_inner),
+        }
+    }
+}
+
+/// Returns peers with relays placed after each of them, so a relay is tried once a peer
+/// fails. Remaining relays are placed at the tail.
+fn interleave<'a>(peers: &'a [Address], relays: &'a [HttpRelay]) -> Vec<Source<'a>> {
+    let mut sources = Vec::with_capacity(peers.len() + relays.len());
+    let mut relays = relays.iter();
+    for peer in peers {
+        sources.push(Source::Peer(peer));
+        if let Some(relay) = relays.next() {
+            sources.push(Source::Relay(relay));
+        }
+    }
+    sources.extend(relays.map(Source::Relay));
+
+    sources
+}
+
+/// Moves demoted peers to the tail of the list, preserving the order of others.
+///
+/// Demoted peers are ordered from the least to the most recently stalled,
+/// so resync attempts rotate through the group instead of reusing a slow peer.
+pub fn rotate_peers(peers: &mut [Address], demoted: &[Address]) {
+    peers.sort_by_key(|peer| {
+        demoted
+            .iter()
+            .position(|d| d == peer)
+            .map_or(0, |pos| pos + 1)
+    });
+}
+
+/// Fetches beacon of the given round from peers to replace corrupted record of chain store.
+///
+/// Chained beacon should be linked to the stored previous beacon, if it is readable.
+pub async fn refetch<S: Scheme, B: BeaconRepr>(
+    round: u64,
+    peers: Vec<Address>,
+    public_key: KeyPoint<S>,
+    id: String,
+    store: ChainStore<B>,
+    l: Span,
+) {
+    let prev = match round.checked_sub(1) {
+        Some(prev_round) => store.get(prev_round).await.ok(),
+        None => None,
     };
 
-    Ok(config)
+    for peer in &peers {
+        let packet = match ProtocolClient::new(peer).await {
+            Ok(mut conn) => match conn.sync_chain(round, id.clone()).await {
+                Ok(mut stream) => stream.message().await.ok().flatten(),
+                Err(err) => {
+                    debug!(parent: &l, "refetch: failed to get stream from {peer}: {err}");
+                    continue;
+                }
+            },
+            Err(err) => {
+                debug!(parent: &l, "refetch: unable to create client for {peer}: {err}");
+                continue;
+            }
+        };
+        let Some(p) = packet.filter(|p| p.round == round) else {
+            debug!(parent: &l, "refetch: {peer} has not sent beacon for round {round}");
+            continue;
+        };
+        let prev_sig = prev
+            .as_ref()
+            .map_or(&p.previous_signature[..], BeaconRepr::signature);
+        let linked = !S::Beacon::is_chained() || p.previous_signature == prev_sig;
+        let is_valid = linked
+            && Affine::deserialize(&p.signature).is_ok_and(|sig| {
+                super::is_valid_signature::<S>(&public_key, prev_sig, round, &sig)
+            });
+        if !is_valid {
+            error!(parent: &l, "refetch: {peer} has sent invalid beacon for round {round}");
+            continue;
+        }
+        if let Err(err) = store.repair(B::from_packet(p)).await {
+            error!(parent: &l, "refetch: failed to repair round {round}: {err}");
+        }
+        return;
+    }
+    error!(parent: &l, "refetch: no valid beacon for round {round} received from peers");
+}
+
+/// Fetches rounds missing in between of stored beacons from peers, e.g. after trusted import.
+///
+/// Beacons are verified in context of the gap: chain is continued from the stored beacon
+/// before the gap and, for chained schemes, should be linked to the stored beacon after it.
+pub async fn fill_gaps<S: Scheme, B: BeaconRepr>(
+    gaps: Vec<(u64, u64)>,
+    peers: Vec<Address>,
+    keys: KeySchedule<S>,
+    id: String,
+    store: ChainStore<B>,
+    l: Span,
+) {
+    for (from, to) in gaps {
+        info!(parent: &l, "fill_gaps: fetching missing rounds {from} - {to}");
+        match fill_gap::<S, B>(from, to, &peers, &keys, &id, &store, &l).await {
+            Ok(()) => info!(parent: &l, "fill_gaps: stored missing rounds {from} - {to}"),
+            Err(err) => error!(parent: &l, "fill_gaps: rounds {from} - {to}: {err}"),
+        }
+    }
+}
+
+/// Fills gap `[from, to]`, peer is switched on failure and continues from the next missing round.
+async fn fill_gap<S: Scheme, B: BeaconRepr>(
+    from: u64,
+    to: u64,
+    peers: &[Address],
+    keys: &KeySchedule<S>,
+    id: &str,
+    store: &ChainStore<B>,
+    l: &Span,
+) -> Result<(), SyncError> {
+    let mut last_stored = store.get(from - 1).await?;
+    let after = store.get(to + 1).await?;
+
+    'peers: for peer in peers {
+        let mut stream = match ProtocolClient::new(peer).await {
+            Ok(mut conn) => match conn
+                .sync_chain(last_stored.round() + 1, id.to_string())
+                .await
+            {
+                Ok(stream) => stream,
+                Err(err) => {
+                    debug!(parent: l, "fill_gaps: failed to get stream from {peer}: {err}");
+                    continue;
+                }
+            },
+            Err(err) => {
+                debug!(parent: l, "fill_gaps: unable to create client for {peer}: {err}");
+                continue;
+            }
+        };
+
+        while let Ok(Some(p)) = stream.message().await {
+            let round = last_stored.round() + 1;
+            if p.round != round {
+                error!(parent: l, "fill_gaps: {peer}: expected round {round}, received {}", p.round);
+                continue 'peers;
+            }
+            let linked =
+                !S::Beacon::is_chained() || p.previous_signature == last_stored.signature();
+            let is_valid = linked
+                && Affine::deserialize(&p.signature).is_ok_and(|sig| {
+                    super::is_valid_signature::<S>(
+                        keys.key_for(round),
+                        last_stored.signature(),
+                        round,
+                        &sig,
+                    )
+                });
+            if !is_valid {
+                error!(parent: l, "fill_gaps: {peer} has sent invalid beacon for round {round}");
+                continue 'peers;
+            }
+            let beacon = B::from_packet(p);
+            store.put(beacon.clone()).await?;
+            last_stored = beacon;
+
+            if round == to {
+                if S::Beacon::is_chained()
+                    && after.prev_signature() != Some(last_stored.signature())
+                {
+                    error!(parent: l, "fill_gaps: stored round {} is not linked to round {to}", to + 1);
+                }
+                return Ok(());
+            }
+        }
+    }
+
+    Err(SyncError::TriedAllPers {
+        last: last_stored.round(),
+    })
 }
 
 /// Resync is triggered if latest stored beacon is more than one round late for expected chain height.
+///
+/// Peer which is currently used is reported into `tx_peer`. Beacons sent into `tx_synced` hold
+/// their buffer memory, the task is paused while the cap of [`SyncMemory`] is reached.
+#[allow(clippy::too_many_arguments)]
 pub fn resync(
     start_from: u64,
     up_to: u64,
     peers: Vec<Address>,
     id: String,
-    tx_synced: mpsc::Sender<BeaconPacket>,
+    tx_synced: mpsc::Sender<Buffered<BeaconPacket>>,
+    memory: SyncMemory,
+    tx_peer: watch::Sender<Option<Address>>,
+    metrics: Arc<ResyncMetrics>,
+    events: EventSender,
     l: Span,
 ) -> JoinHandle<Result<(), SyncError>> {
     task::spawn(async move {
         let l = &l;
         let mut last_sent = start_from - 1;
+        metrics.started.fetch_add(1, Ordering::Relaxed);
+        info!(parent: l, "{}", ResyncEvent::Start { from: start_from, up_to });
+        events.emit(
+            &id,
+            &Event::ResyncStarted {
+                from: start_from,
+                up_to,
+            },
+        );
+        let peer_error = |peer: &Address, reason: &str| {
+            metrics.peer_failures.fetch_add(1, Ordering::Relaxed);
+            events.emit(
+                &id,
+                &Event::PeerError {
+                    peer: peer.as_str(),
+                    reason,
+                },
+            );
+        };
+        // Beacons are verified by the chain handler, failures are counted by shared metrics.
+        let mut summary = SyncSummary::new();
+        let invalid_before = metrics.invalid_beacons.load(Ordering::Relaxed);
+        let report = |summary: &mut SyncSummary, outcome: &str, last: u64| {
+            summary.verification_failures = metrics
+                .invalid_beacons
+                .load(Ordering::Relaxed)
+                .saturating_sub(invalid_before);
+            let json = summary.to_json(outcome, last);
+            info!(parent: l, "{}", ResyncEvent::Summary { summary: &json });
+            events.emit(
+                &id,
+                &Event::SyncSummary {
+                    last,
+                    summary: &json,
+                },
+            );
+        };
+        let stopped = |last: u64, err: &SyncError, summary: &mut SyncSummary| {
+            error!(parent: l, "{}", ResyncEvent::Failure { reason: err });
+            events.emit(
+                &id,
+                &Event::ResyncStopped {
+                    last,
+                    reason: &err.to_string(),
+                },
+            );
+            report(summary, &err.to_string(), last);
+        };
 
-        'peers: for peer in peers {
+        'peers: for (attempt, peer) in peers.into_iter().enumerate() {
+            if attempt > 0 {
+                summary.retry();
+            }
             if up_to <= last_sent {
-                return Err(SyncError::InvalidTarget {
+                let err = SyncError::InvalidTarget {
                     from: last_sent + 1,
                     target: up_to,
-                });
+                };
+                stopped(last_sent, &err, &mut summary);
+                return Err(err);
             }
             let mut stream = match ProtocolClient::new(&peer).await {
                 Ok(mut conn) => match conn.sync_chain(last_sent + 1, id.clone()).await {
                     Ok(stream) => stream,
                     Err(err) => {
                         error!(parent: l, "failed to get stream from {peer}: {err}");
+                        peer_error(&peer, "failed to get stream");
                         continue;
                     }
                 },
                 Err(err) => {
                     error!(parent: l, "unable to create client for {peer}: {err}");
+                    peer_error(&peer, "unable to create client");
                     continue;
                 }
             };
 
-            debug!(parent: l, "start_resync with peer {peer}, from_round {}, up_to {up_to}", last_sent + 1);
+            info!(parent: l, "{}", ResyncEvent::PeerSwitch { peer: &peer, from: last_sent + 1 });
+            tx_peer.send_replace(Some(peer.clone()));
+            let source = peer.to_string();
             while let Ok(Some(p)) = stream.message().await {
                 let Some(ref meta) = p.metadata else {
                     error!(parent: l, "skipping {peer}: no metadata for round {}", p.round);
+                    peer_error(&peer, "no metadata");
                     continue 'peers;
                 };
                 if id != meta.beacon_id {
                     error!(parent: l, "skipping {peer}: invalid beacon id [{}] for round {}", meta.beacon_id, p.round);
+                    peer_error(&peer, "invalid beacon id");
                     continue 'peers;
                 }
                 if p.round != last_sent + 1 {
                     error!(parent: l, "skipping {peer}: round expected {}, received {}", last_sent+1, p.round);
+                    peer_error(&peer, "unexpected round");
                     continue 'peers;
                 }
-                if tx_synced.send(p).await.is_err() {
-                    return Err(SyncError::SyncClosedTx);
+                if tx_synced.send(memory.buffer(p).await).await.is_err() {
+                    let err = SyncError::SyncClosedTx;
+                    stopped(last_sent, &err, &mut summary);
+                    return Err(err);
                 }
                 last_sent += 1;
+                metrics.rounds_fetched.fetch_add(1, Ordering::Relaxed);
+                summary.fetched(&source);
 
                 // Stop if target is reached
                 if last_sent == up_to {
-                    debug!(parent: l, "stop_resync: with peer {peer}, reached target {up_to}");
+                    info!(parent: l, "{}", ResyncEvent::Complete { last: last_sent });
+                    events.emit(
+                        &id,
+                        &Event::ResyncStopped {
+                            last: last_sent,
+                            reason: "complete",
+                        },
+                    );
+                    report(&mut summary, "complete", last_sent);
                     return Ok(());
                 }
             }
+            // Stream is closed before reaching the target.
+            peer_error(&peer, "stream closed before target");
         }
         let err = SyncError::TriedAllPers { last: last_sent };
-        error!(parent: l, "stop_resync: {err}");
+        stopped(last_sent, &err, &mut summary);
 
         Err(err)
     })
@@ -416,27 +1292,42 @@ pub fn resync(
 
 /// Retrieves public chain information from list of peers with prechecked beacon id.
 /// Used only by nodes without DKG setup.
+/// Returns chain info of the first peer which serves the pinned chain. If no peer does, the
+/// error is [`SyncError::ChainHashMismatch`] if any peer is reachable but serves another chain.
 async fn chain_info_from_peers(
     peers: &[Address],
     beacon_id: &str,
+    pinned: &[u8],
     l: &Span,
 ) -> Result<ChainInfoPacket, SyncError> {
+    let mut mismatch = None;
     for peer in peers {
-        match PublicClient::new(peer).await {
-            Ok(mut client) => {
+        match SYNC_PEERS.connect(peer).await {
+            Ok(lease) => {
+                let mut client = lease.public();
                 debug!(parent: l, "connected to {peer}, sending chain info request..");
                 match client.chain_info(beacon_id.to_string()).await {
                     Ok(packet) => {
                         if let Some(ref m) = packet.metadata {
-                            if m.beacon_id == beacon_id {
-                                return Ok(packet);
+                            if m.beacon_id != beacon_id {
+                                warn!(parent: l, "info_from_peers: skipping {peer}: invalid beacon id: {}", m.beacon_id);
+                                continue;
+                            }
+                            match check_trust_root(&packet, beacon_id, pinned) {
+                                Ok(_) => return Ok(packet),
+                                Err(err) => {
+                                    warn!(parent: l, "info_from_peers: skipping {peer}: {err}");
+                                    mismatch = Some(err);
+                                }
                             }
-                            warn!(parent: l, "info_from_peers: skipping {peer}: invalid beacon id: {}", m.beacon_id);
                         } else {
                             warn!(parent: l, "info_from_peers: skipping {peer}: no metadata received");
                         }
                     }
                     Err(err) => {
+                        if is_transport_error(&err) {
+                            SYNC_PEERS.reset(&lease).await;
+                        }
                         warn!(parent: l, "info_from_peers: skipping {peer}: {err}");
                     }
                 }
@@ -447,5 +1338,263 @@ async fn chain_info_from_peers(
         };
     }
 
-    Err(SyncError::FailedInfoFromAllPeers)
+    Err(mismatch.unwrap_or(SyncError::FailedInfoFromAllPeers))
+}
+
+/// Returns true if the request failed due to connection to the peer rather than a reply.
+fn is_transport_error(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<Status>()
+        .is_none_or(|status| status.code() == tonic::Code::Unavailable)
+}
+
+/// Returns chain info of the first relay which serves the pinned chain, see [`chain_info_from_peers`].
+async fn chain_info_from_relays(
+    relays: &[HttpRelay],
+    beacon_id: &str,
+    pinned: &[u8],
+    l: &Span,
+) -> Result<ChainInfoPacket, SyncError> {
+    let mut mismatch = None;
+    for relay in relays {
+        let checked = match relay.chain_info(beacon_id).await {
+            Ok(packet) => check_trust_root(&packet, beacon_id, pinned).map(|_| packet),
+            Err(err) => {
+                warn!(parent: l, "info_from_relays: skipping {}: {err}", relay.url());
+                continue;
+            }
+        };
+        match checked {
+            Ok(packet) => return Ok(packet),
+            Err(err) => {
+                warn!(parent: l, "info_from_relays: skipping {}: {err}", relay.url());
+                mismatch = Some(err);
+            }
+        }
+    }
+
+    Err(mismatch.unwrap_or(SyncError::FailedInfoFromAllPeers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sync_summary() {
+        let mut summary = SyncSummary::new();
+        summary.fetched("a:1");
+        summary.fetched("a:1");
+        summary.retry();
+        summary.verification_failure();
+        summary.fetched("b:2");
+        summary.fetched("a:1");
+        assert_eq!(summary.rounds(), 4);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&summary.to_json("complete", 12)).unwrap();
+        assert_eq!(json["outcome"], "complete");
+        assert_eq!(json["last"], 12);
+        assert_eq!(json["retries"], 1);
+        assert_eq!(json["verification_failures"], 1);
+        assert_eq!(
+            json["sources"],
+            json!([
+                {"source": "a:1", "rounds": 2},
+                {"source": "b:2", "rounds": 1},
+                {"source": "a:1", "rounds": 1}
+            ])
+        );
+    }
+
+    #[test]
+    fn trust_root() {
+        let packet = ChainInfoPacket {
+            public_key: vec![1; 48],
+            period: 3,
+            genesis_time: 1692803367,
+            group_hash: vec![2; 32],
+            scheme_id: "pedersen-bls-chained".into(),
+            ..Default::default()
+        };
+        let pinned = crate::client::chain_hash(&packet, "quicknet");
+        let served = ChainInfoPacket {
+            hash: pinned.to_vec(),
+            ..packet.clone()
+        };
+        assert_eq!(
+            check_trust_root(&served, "quicknet", &pinned).unwrap(),
+            pinned
+        );
+
+        // Another chain: any field differs from the pinned info, or another beacon id.
+        let other = ChainInfoPacket {
+            period: 30,
+            ..served.clone()
+        };
+        let mismatch = |packet: &ChainInfoPacket, id: &str, pinned: &[u8]| {
+            matches!(
+                check_trust_root(packet, id, pinned),
+                Err(SyncError::ChainHashMismatch(_))
+            )
+        };
+        assert!(mismatch(&other, "quicknet", &pinned));
+        assert!(mismatch(&served, "default", &pinned));
+        // Reported hash is pinned but fields are not.
+        let forged = ChainInfoPacket {
+            public_key: vec![3; 48],
+            ..served.clone()
+        };
+        assert!(mismatch(&forged, "quicknet", &pinned));
+        // Fields are pinned but the reported hash is not consistent.
+        assert!(mismatch(&packet, "quicknet", &pinned));
+        // Empty or malformed trust root matches nothing.
+        assert!(mismatch(&served, "quicknet", &[]));
+        assert!(mismatch(&served, "quicknet", &pinned[..16]));
+    }
+
+    #[test]
+    fn verify_policy() {
+        let mut req = StartSyncRequest {
+            verify_mode: VerifyMode::SpotCheck as u32,
+            spot_check_every: 10,
+            ..Default::default()
+        };
+        let target = 25;
+
+        let policy = VerifyPolicy::from_request(&req).unwrap();
+        let verified: Vec<u64> = (1..=target)
+            .filter(|r| policy.should_verify(*r, target))
+            .collect();
+        assert_eq!(verified, [10, 20, 25]);
+
+        req.verify_mode = VerifyMode::Trust as u32;
+        let policy = VerifyPolicy::from_request(&req).unwrap();
+        assert!((1..=target).all(|r| !policy.should_verify(r, target)));
+
+        req.verify_mode = VerifyMode::Full as u32;
+        let policy = VerifyPolicy::from_request(&req).unwrap();
+        assert!((1..=target).all(|r| policy.should_verify(r, target)));
+
+        req.verify_mode = 3;
+        assert!(VerifyPolicy::from_request(&req).is_err());
+    }
+
+    #[test]
+    fn rotate_demoted_peers() {
+        let peers: Vec<Address> = ["a:1", "b:1", "c:1", "d:1"]
+            .iter()
+            .map(|p| Address::precheck(p).unwrap())
+            .collect();
+
+        // No demoted peers - order is preserved.
+        let mut rotated = peers.clone();
+        rotate_peers(&mut rotated, &[]);
+        assert!(rotated == peers);
+
+        // Least recently stalled peer goes before most recently stalled.
+        let demoted = [peers[0].clone(), peers[2].clone()];
+        rotate_peers(&mut rotated, &demoted);
+        let expected = [
+            peers[1].clone(),
+            peers[3].clone(),
+            peers[0].clone(),
+            peers[2].clone(),
+        ];
+        assert!(rotated == expected);
+    }
+
+    #[test]
+    fn interleave_relays() {
+        let peers: Vec<Address> = ["a:1", "b:1"]
+            .iter()
+            .map(|p| Address::precheck(p).unwrap())
+            .collect();
+        let relays: Vec<HttpRelay> = ["http://r1", "https://r2/", "http://r3"]
+            .iter()
+            .map(|url| HttpRelay::new(url).unwrap())
+            .collect();
+
+        let order: Vec<String> = interleave(&peers, &relays)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            order,
+            [
+                "a:1",
+                "relay http://r1",
+                "b:1",
+                "relay https://r2",
+                "relay http://r3"
+            ]
+        );
+        assert_eq!(interleave(&peers, &[]).len(), peers.len());
+    }
+
+    #[test]
+    fn checkpoint_from_request() {
+        let mut req = StartSyncRequest::default();
+        assert!(Checkpoint::from_request(&req).unwrap().is_none());
+
+        req.checkpoint_round = 100;
+        assert!(Checkpoint::from_request(&req).is_err());
+
+        req.checkpoint_signature = vec![1, 2, 3];
+        let checkpoint = Checkpoint::from_request(&req).unwrap().unwrap();
+        assert_eq!(checkpoint.round(), 100);
+
+        req.checkpoint_round = 0;
+        assert!(Checkpoint::from_request(&req).is_err());
+    }
+
+    #[test]
+    fn checkpoint_signature() {
+        use energon::drand::schemes::DefaultScheme;
+        use energon::traits::ScalarField;
+        type S = DefaultScheme;
+
+        let private = <S as Scheme>::Scalar::random();
+        let public_key = S::sk_to_pk(&private);
+        let previous = vec![0xaa; 96];
+        let msg = <S as Scheme>::Beacon::digest(&previous, 5);
+        let signature = S::bls_sign(&msg, &private).unwrap().serialize().unwrap();
+        let checkpoint = Checkpoint {
+            round: 5,
+            signature: signature.to_vec(),
+        };
+        assert!(checkpoint.is_signed::<S>(&public_key, &previous));
+
+        // Checkpoint of another chain or not linked to the previous signature is rejected.
+        let other_key = S::sk_to_pk(&<S as Scheme>::Scalar::random());
+        assert!(!checkpoint.is_signed::<S>(&other_key, &previous));
+        assert!(!checkpoint.is_signed::<S>(&public_key, &[0xbb; 96]));
+        let forged = Checkpoint {
+            round: 5,
+            signature: vec![1, 2, 3],
+        };
+        assert!(!forged.is_signed::<S>(&public_key, &previous));
+    }
+
+    #[tokio::test]
+    async fn resync_handle_expiry() {
+        use crate::chain::time::MockClock;
+
+        let clock = Arc::new(MockClock::new(Duration::from_secs(100)));
+        let peer = Address::precheck("peer:1").unwrap();
+        let (_tx_peer, rx_peer) = watch::channel(Some(peer.clone()));
+        let handle = tokio::spawn(std::future::pending());
+        let mut h = HandleReSync::new(Seconds::new(3), handle, rx_peer, clock.clone());
+
+        // Expiry time is period * RESYNC_EXPIRY_FACTOR.
+        clock.advance(Duration::from_secs(5));
+        assert!(h.is_running());
+        assert!(h.stalled_peer().is_none());
+
+        clock.advance(Duration::from_secs(1));
+        assert!(!h.is_running());
+        assert_eq!(h.stalled_peer(), Some(peer));
+
+        h.update_last_received_time();
+        assert!(h.is_running());
+    }
 }
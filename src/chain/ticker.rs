@@ -1,4 +1,5 @@
 use super::time;
+use super::time::SharedClock;
 use crate::net::utils::Seconds;
 use std::time::Duration;
 use tokio::sync::mpsc;
@@ -8,34 +9,36 @@ type Round = u64;
 struct RoundTicker {
     period: u32,
     genesis_time: u64,
+    clock: SharedClock,
     tx_next_round: mpsc::Sender<Round>,
 }
 
 impl RoundTicker {
     /// Sends next round value at next round time to associated receiver.
     async fn send_next_round(&self) -> Result<(), mpsc::error::SendError<Round>> {
-        let utc_now = time::time_now();
+        let utc_now = self.clock.now();
         let (next_round, next_time) =
             time::next_round(utc_now.as_secs(), self.period, self.genesis_time);
 
-        let sleep_duration = Duration::from_secs(next_time)
-            .checked_sub(utc_now)
-            .unwrap_or(Duration::ZERO);
-
-        tokio::time::sleep(sleep_duration).await;
+        self.clock.sleep_until(Duration::from_secs(next_time)).await;
         self.tx_next_round.send(next_round).await
     }
 }
 
 /// Starts round ticker for given genesis time and period.
 /// Returns associated receiver for new rounds.
-pub fn start_ticker(genesis_time: u64, period: Seconds) -> mpsc::Receiver<Round> {
+pub fn start_ticker(
+    genesis_time: u64,
+    period: Seconds,
+    clock: SharedClock,
+) -> mpsc::Receiver<Round> {
     let (tx_next_round, rx_next_round) = mpsc::channel(1);
 
     tokio::spawn(async move {
         let t = RoundTicker {
             period: period.get_value(),
             genesis_time,
+            clock,
             tx_next_round,
         };
 
@@ -48,3 +51,26 @@ pub fn start_ticker(genesis_time: u64, period: Seconds) -> mpsc::Receiver<Round>
 
     rx_next_round
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::time::MockClock;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn ticker_follows_clock() {
+        let genesis_time = 100;
+        let clock = Arc::new(MockClock::new(Duration::from_secs(genesis_time)));
+        let mut rx = start_ticker(genesis_time, Seconds::new(3), clock.clone());
+
+        // Let the ticker schedule the next round.
+        tokio::task::yield_now().await;
+        assert!(rx.try_recv().is_err());
+
+        for round in 2..5 {
+            clock.advance(Duration::from_secs(3));
+            assert_eq!(rx.recv().await, Some(round));
+        }
+    }
+}
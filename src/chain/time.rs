@@ -1,4 +1,7 @@
 use crate::net::utils::Seconds;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
 use std::time::Duration;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
@@ -60,6 +63,120 @@ pub fn time_now() -> Duration {
         .expect("system time before Unix epoch")
 }
 
+/// Source of Unix time for round ticker, catchup, resync expiry and DKG execution and transition.
+///
+/// Note: phase timers within a DKG execution are run by energon on tokio time, with
+/// `MockClock` phases are completed once all bundles are received (fast sync).
+pub trait Clock: Send + Sync {
+    /// Returns current Unix time as duration.
+    fn now(&self) -> Duration;
+
+    /// Completes once the clock reaches `deadline` Unix time.
+    fn sleep_until(&self, deadline: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + '_>>;
+}
+
+/// Clock shared across components of beacon process.
+pub type SharedClock = Arc<dyn Clock>;
+
+/// Clock backed by system time.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        time_now()
+    }
+
+    fn sleep_until(&self, deadline: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(tokio::time::sleep(deadline.saturating_sub(time_now())))
+    }
+}
+
+/// Clock which moves only by explicit calls to [`MockClock::advance`].
+#[cfg(test)]
+pub struct MockClock {
+    now: tokio::sync::watch::Sender<Duration>,
+    /// Deadlines of pending sleeps.
+    sleeps: std::sync::Mutex<Vec<Duration>>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    pub fn new(now: Duration) -> Self {
+        let (now, _) = tokio::sync::watch::channel(now);
+
+        Self {
+            now,
+            sleeps: std::sync::Mutex::default(),
+        }
+    }
+
+    /// Moves the clock forward, pending sleeps up to new time are completed.
+    pub fn advance(&self, by: Duration) {
+        self.now.send_modify(|now| *now += by);
+    }
+
+    /// Waits until `count` sleeps are pending past the current time, so tasks sleeping on
+    /// the clock have handled the last advance.
+    pub async fn sleeping(&self, count: usize) {
+        loop {
+            let now = self.now();
+            let pending = self
+                .sleeps
+                .lock()
+                .map_or(0, |sleeps| sleeps.iter().filter(|d| **d > now).count());
+            if pending >= count {
+                return;
+            }
+            tokio::task::yield_now().await;
+        }
+    }
+}
+
+/// Deadline of a sleep on [`MockClock`], removed once the sleep is completed or dropped.
+#[cfg(test)]
+struct PendingSleep<'a> {
+    sleeps: &'a std::sync::Mutex<Vec<Duration>>,
+    deadline: Duration,
+}
+
+#[cfg(test)]
+impl<'a> PendingSleep<'a> {
+    fn new(sleeps: &'a std::sync::Mutex<Vec<Duration>>, deadline: Duration) -> Self {
+        if let Ok(mut sleeps) = sleeps.lock() {
+            sleeps.push(deadline);
+        }
+
+        Self { sleeps, deadline }
+    }
+}
+
+#[cfg(test)]
+impl Drop for PendingSleep<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut sleeps) = self.sleeps.lock() {
+            if let Some(i) = sleeps.iter().position(|d| *d == self.deadline) {
+                sleeps.swap_remove(i);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> Duration {
+        *self.now.borrow()
+    }
+
+    fn sleep_until(&self, deadline: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        let mut rx = self.now.subscribe();
+        let pending = PendingSleep::new(&self.sleeps, deadline);
+        Box::pin(async move {
+            let _ = rx.wait_for(|now| *now >= deadline).await;
+            drop(pending);
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -114,4 +231,37 @@ mod test {
         assert_eq!(round_time, exp_time);
         assert_eq!(exp_time, time_of_round(period, genesis, 3));
     }
+
+    #[tokio::test]
+    async fn mock_clock_sleep() {
+        let clock = MockClock::new(Duration::from_secs(100));
+        let mut sleep = clock.sleep_until(Duration::from_secs(102));
+
+        // Zero timeout polls the sleep exactly once.
+        let poll_once = Duration::ZERO;
+        clock.advance(Duration::from_secs(1));
+        assert!(tokio::time::timeout(poll_once, &mut sleep).await.is_err());
+
+        clock.advance(Duration::from_secs(1));
+        assert!(tokio::time::timeout(poll_once, &mut sleep).await.is_ok());
+        assert_eq!(clock.now(), Duration::from_secs(102));
+    }
+
+    #[tokio::test]
+    async fn mock_clock_sleeping() {
+        let clock = MockClock::new(Duration::from_secs(100));
+        let sleep = clock.sleep_until(Duration::from_secs(101));
+        clock.sleeping(1).await;
+
+        // Sleep which is due is not pending anymore.
+        clock.advance(Duration::from_secs(1));
+        let later = clock.sleep_until(Duration::from_secs(102));
+        clock.sleeping(1).await;
+        assert!(tokio::time::timeout(Duration::ZERO, clock.sleeping(2))
+            .await
+            .is_err());
+
+        drop((sleep, later));
+        assert!(clock.sleeps.lock().unwrap().is_empty());
+    }
 }
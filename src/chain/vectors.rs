@@ -0,0 +1,222 @@
+//! Golden vectors produced by Go drand, hashes and signatures must match them byte for byte.
+//!
+//! Sources:
+//! - chain info of `default` and `quicknet` chains of League of Entropy mainnet, as served at
+//!   `https://api.drand.sh/<chain hash>/info`;
+//! - local demo of Go drand <https://github.com/drand/drand/tree/master/demo#local-demo-of-drand>,
+//!   group file of the demo is checked in [`crate::key::toml`] tests;
+//! - beacon digests as computed by `DigestBeacon` of Go drand `crypto` package;
+//! - chain info and first beacons of local Go drand v2.1.2 network of 3 nodes with threshold 2,
+//!   one chain per scheme, as served by `PublicRand` and `ChainInfo` of the public API.
+use super::info::ChainInfo;
+
+use crate::client::chain_hash;
+use crate::client::verify_chain_info;
+use crate::key::Scheme;
+use crate::net::utils::Address;
+use crate::protobuf::drand::ChainInfoPacket;
+use crate::transport::dkg::Participant;
+use crate::verify::verify_serialized;
+
+use energon::drand::schemes::DefaultScheme;
+use energon::drand::schemes::SigsOnG1Scheme;
+use energon::drand::schemes::UnchainedScheme;
+use energon::drand::traits::BeaconDigest;
+use energon::traits::Affine;
+use energon::traits::ScalarField;
+use sha2::Digest;
+
+struct InfoVector {
+    beacon_id: &'static str,
+    scheme_id: &'static str,
+    period: u32,
+    genesis_time: i64,
+    public_key: &'static str,
+    group_hash: &'static str,
+    hash: &'static str,
+}
+
+const MAINNET_DEFAULT: InfoVector = InfoVector {
+    beacon_id: "default",
+    scheme_id: "pedersen-bls-chained",
+    period: 30,
+    genesis_time: 1_595_431_050,
+    public_key: "868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31",
+    group_hash: "176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a",
+    hash: "8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce",
+};
+
+const MAINNET_QUICKNET: InfoVector = InfoVector {
+    beacon_id: "quicknet",
+    scheme_id: "bls-unchained-g1-rfc9380",
+    period: 3,
+    genesis_time: 1_692_803_367,
+    public_key: "83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a",
+    group_hash: "f477d5c89f21a17c863a7f937c6a6d15859414d2be09cd448d4279af331c5d3e",
+    hash: "52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971",
+};
+
+const GO_CHAINED: InfoVector = InfoVector {
+    beacon_id: "vec",
+    scheme_id: "pedersen-bls-chained",
+    period: 3,
+    genesis_time: 1_792_027_961,
+    public_key: "a5140725a458cbe32586ef6540736eec68325c053697fa84e2729d2c8e5a23073fd7723a4d78270e75b242d436e157cb",
+    group_hash: "4d6dbbaec32ac5c796a916833d279114e9ba5ec8be1c39e66e5125b23acbf90b",
+    hash: "4ffecb89bab66d1b9a3592a36b4b808c4a6d687bb7914eecf9dbb7e69c88137c",
+};
+
+const GO_UNCHAINED: InfoVector = InfoVector {
+    beacon_id: "unch",
+    scheme_id: "pedersen-bls-unchained",
+    period: 3,
+    genesis_time: 1_792_028_001,
+    public_key: "81ad8ccd0e9e94de86e950e7d6f74294e45519f0adca1d093a5ea18504982ea97cce04704f55f7431aa677c5420567ce",
+    group_hash: "eb355ccd100cbfabcf7e701ede7471ed868ea5e205cb45d7ebdbeaacece66b2e",
+    hash: "268d8b12e3b09001d073c09e5e5b9dfd7490467bea202ad71ae33a3b463f9b7f",
+};
+
+const GO_SIGS_ON_G1: InfoVector = InfoVector {
+    beacon_id: "g1",
+    scheme_id: "bls-unchained-g1-rfc9380",
+    period: 3,
+    genesis_time: 1_792_028_005,
+    public_key: "b460300bf094a0363726c863ebc08082af0825ef1291ccc51b487c228f9a7dd8750404eb9162272171bff186f4959bde12275e7896fcaad48bbd7316de719fccdba2816291e85d5f839e7561ff152e1ad2ba63b2301223ce2f89cb68190b231d",
+    group_hash: "5c519ded8a2c5d3e70d0998e13e86def01f36938e807c4717441c6fe510c5e6c",
+    hash: "acad0bab5c2784011e78c29942b5e2e0a5f1bff1678d04ca339690aed96e311b",
+};
+
+/// Beacon as `(round, previous signature, signature)`.
+type BeaconVector = (u64, &'static str, &'static str);
+
+/// First beacons of [`GO_CHAINED`], previous signature of the first round is the genesis seed.
+const GO_CHAINED_BEACONS: [BeaconVector; 3] = [
+    (1, "4d6dbbaec32ac5c796a916833d279114e9ba5ec8be1c39e66e5125b23acbf90b", "84b25f31695f8df14402298120202352b0add7a9cc88c8a2ef882eaa1c3873bb9621bf2745deb8ea31146db7ad3f1d7c050603a87bbd8e8afc886947f8906fc09edbd93d9d3501c66f8fb7a741c808b41039d58384b40b07df50a000940383a5"),
+    (2, "84b25f31695f8df14402298120202352b0add7a9cc88c8a2ef882eaa1c3873bb9621bf2745deb8ea31146db7ad3f1d7c050603a87bbd8e8afc886947f8906fc09edbd93d9d3501c66f8fb7a741c808b41039d58384b40b07df50a000940383a5", "b97954ee1a661a16c50800bdac1c7867320f056ed95e9b62b17eb1aab661b8b26e114f9b3a92051aaf635ae284a40cff04d5fc916be48d3aa80cec4902a9383a44f24fb650ed9efdda55fd4353cc81e9c33d957852e8cafbe5525421afd939f4"),
+    (3, "b97954ee1a661a16c50800bdac1c7867320f056ed95e9b62b17eb1aab661b8b26e114f9b3a92051aaf635ae284a40cff04d5fc916be48d3aa80cec4902a9383a44f24fb650ed9efdda55fd4353cc81e9c33d957852e8cafbe5525421afd939f4", "90f64014ce37dfc5dacf1a25db29fc59705efa1555b7a82f6a1464b5ca747e84e8329f6109fdca7fc5da8f1943f470150c8f5d93aa78a4b701bf83909479425dc7ee84aa4c6f0c3ae8f319d631b53927624825be8e1d84831a19389063fbe219"),
+];
+
+const GO_UNCHAINED_BEACONS: [BeaconVector; 3] = [
+    (1, "", "8c952f6c8db5fddb583f19afa0368171c3eb6247544a68d737e81318ac6e168d4aea620005a395c6b0a0cbbaefbe4cd6120f2aeca23528453a3471e4ae9a358ab4b62068e4df043c380fe612c864a97783d3e453ff7124e114563afda83344bb"),
+    (2, "", "a72ae26fda51ed74fdf9bb85f7797c7b63e5e85b4bb98277225e34f2bbdb23db6a34aece77b2a9eea3468486aec394f513d2ada1519360689df1e56854de24572459e6d5bfd6925402d7209234557e97435c0def9ff9a99a521d8e5b33bfe7ec"),
+    (3, "", "b769bdbd3621ec25561c1e1eccd56f14fe1045e01c98992bd981217409833eb59f694ba04c7526abf68cd6439520ee2a0043e6475fdb75fc0ef9d176e045c335f6f13ae011af18c83a7200235bda31449ef2babf1c7fe376908359251bda71a2"),
+];
+
+const GO_SIGS_ON_G1_BEACONS: [BeaconVector; 3] = [
+    (1, "", "a84f68569bc03b04b304202c09b0c1cff7b7011b89e36bd2bff7b44cca7a84e0e0eaee0c8395a947fdc5dbf1dec091f9"),
+    (2, "", "a738e019bf68b0c263b7c96a1eddb21af7e5123a6e512ddafb6842443fdfdcbf93037d3306dd4b37d5906d40838f106f"),
+    (3, "", "9322403707b524898b0fe95960403ea418be9ba7ae0a294c3686d085be023c6bdd1b233adeb76deffba00725f4f1bba3"),
+];
+
+impl InfoVector {
+    fn packet(&self) -> ChainInfoPacket {
+        ChainInfoPacket {
+            public_key: hex::decode(self.public_key).unwrap(),
+            period: self.period,
+            genesis_time: self.genesis_time,
+            hash: hex::decode(self.hash).unwrap(),
+            group_hash: hex::decode(self.group_hash).unwrap(),
+            scheme_id: self.scheme_id.to_string(),
+            metadata: None,
+        }
+    }
+
+    fn check<S: Scheme>(&self) {
+        let packet = self.packet();
+        assert_eq!(hex::encode(chain_hash(&packet, self.beacon_id)), self.hash);
+        assert!(verify_chain_info::<S>(&packet, self.beacon_id).is_ok());
+
+        let info = ChainInfo::<S>::from_packet(&packet, self.beacon_id.to_string()).unwrap();
+        assert_eq!(hex::encode(info.hash().unwrap()), self.hash);
+        let encoded = info.as_packet().unwrap();
+        assert_eq!(encoded.public_key, packet.public_key);
+        assert_eq!(encoded.hash, packet.hash);
+    }
+
+    /// Verifies beacons of the chain, a beacon does not verify for another round.
+    fn check_beacons(&self, beacons: &[BeaconVector]) {
+        let public_key = hex::decode(self.public_key).unwrap();
+        for (round, previous_signature, signature) in beacons {
+            let prev_sig = hex::decode(previous_signature).unwrap();
+            let sig = hex::decode(signature).unwrap();
+            let verify = |round| {
+                verify_serialized(self.scheme_id, &public_key, &prev_sig, round, &sig).unwrap()
+            };
+            assert!(verify(*round), "round {round} of {}", self.beacon_id);
+            assert!(!verify(round + 1));
+        }
+    }
+}
+
+#[test]
+fn chain_hashes() {
+    MAINNET_DEFAULT.check::<DefaultScheme>();
+    MAINNET_QUICKNET.check::<SigsOnG1Scheme>();
+    GO_CHAINED.check::<DefaultScheme>();
+    GO_UNCHAINED.check::<UnchainedScheme>();
+    GO_SIGS_ON_G1.check::<SigsOnG1Scheme>();
+}
+
+#[test]
+fn beacon_signatures() {
+    GO_CHAINED.check_beacons(&GO_CHAINED_BEACONS);
+    GO_UNCHAINED.check_beacons(&GO_UNCHAINED_BEACONS);
+    GO_SIGS_ON_G1.check_beacons(&GO_SIGS_ON_G1_BEACONS);
+
+    // Chained beacon does not verify with another previous signature.
+    let (round, _, signature) = GO_CHAINED_BEACONS[1];
+    assert!(!verify_serialized(
+        GO_CHAINED.scheme_id,
+        &hex::decode(GO_CHAINED.public_key).unwrap(),
+        &hex::decode(GO_CHAINED_BEACONS[2].2).unwrap(),
+        round,
+        &hex::decode(signature).unwrap(),
+    )
+    .unwrap());
+}
+
+/// Identity of the node `127.0.0.1:38161` of the local demo.
+#[test]
+fn identity_signature() {
+    const PRIVATE: &str = "4dee50f69880dce2b793ed2bad9966bc1d333c0ab28f2eff2794753e65202747";
+    const KEY: &str = "ab37151b401ba77a9a5bf002d8381b7a9b45f789dc4c3dc40faf0421b7d409c1496e487626ae45482517fffa3de12741";
+    const SIGNATURE: &str = "a28ad77f42e540230a475412f860cdc2ff35a98a8ccd93c01f3e1652fd8dcc4c50def4c81718b007e7876f387e45308017b7bbeb69ef658a851b9328178aed2be06a38197d795d709d5a89f247bc698a051866c293714b718576eaccfd8b069d";
+    type S = DefaultScheme;
+
+    let participant = Participant {
+        address: Address::precheck("127.0.0.1:38161").unwrap(),
+        key: hex::decode(KEY).unwrap(),
+        signature: hex::decode(SIGNATURE).unwrap(),
+    };
+    assert!(participant.is_valid_signature::<S>());
+
+    // BLS signatures are deterministic, signing the same message gives the signature of Go.
+    let private = <S as Scheme>::Scalar::from_bytes_be(&hex::decode(PRIVATE).unwrap()).unwrap();
+    let key = S::sk_to_pk(&private);
+    assert_eq!(hex::encode(key.serialize().unwrap()), KEY);
+
+    let mut hasher = crev_common::Blake2b256::new();
+    hasher.update(&participant.key);
+    let msg = [S::ID.as_bytes(), hasher.finalize().as_slice()].concat();
+    let signature = S::bls_sign(&msg, &private).unwrap();
+    assert_eq!(hex::encode(signature.serialize().unwrap()), SIGNATURE);
+}
+
+#[test]
+fn beacon_digests() {
+    let prev_sig = [0xaa; 96];
+    let round = 1000;
+
+    let chained = <DefaultScheme as Scheme>::Beacon::digest(&prev_sig, round);
+    assert_eq!(
+        hex::encode(chained),
+        "03e29499a8bb2c47a916f301c2369b43642b105ddede9970b06275ac746baa33"
+    );
+
+    // Unchained schemes ignore the previous signature.
+    let unchained = "f652498d092acd949bad74e40683bf3824fb817980504a0c7e6722cfc5a9c0a3";
+    let digest = <UnchainedScheme as Scheme>::Beacon::digest(&prev_sig, round);
+    assert_eq!(hex::encode(digest), unchained);
+    let digest = <SigsOnG1Scheme as Scheme>::Beacon::digest(&[], round);
+    assert_eq!(hex::encode(digest), unchained);
+}
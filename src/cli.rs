@@ -1,29 +1,95 @@
+use crate::chain::archive;
+use crate::chain::equivocation;
+use crate::chain::equivocation::Equivocation;
+use crate::chain::format::BeaconFormat;
+use crate::chain::inspect;
+use crate::chain::time;
+use crate::chain::time::SystemClock;
+use crate::chain::RelayChain;
+use crate::chain::RelayConfig;
+use crate::chain::VerifyMode;
+use crate::core::archiver;
+use crate::core::archiver::ArchiveConfig;
+use crate::core::backups;
+use crate::core::backups::BackupConfig;
+use crate::core::backups::BackupTarget;
 use crate::core::beacon;
+use crate::core::crash;
 use crate::core::daemon::Daemon;
+use crate::core::leave;
+use crate::core::multibeacon;
+use crate::core::runtime::RuntimeConfig;
+use crate::core::webhooks;
+use crate::core::webhooks::WebhookConfig;
+use crate::dkg::evidence;
+use crate::dkg::fanout::BeaconFanOut;
+use crate::dkg::proposal;
+use crate::dkg::proposal::ProposalFile;
+use crate::dkg::status::countdown;
+use crate::dkg::status::Status;
+use crate::dkg::testnet;
+use crate::dkg::testnet::TestnetConfig;
+use crate::key::backup;
+use crate::key::diff;
+use crate::key::group::Group;
 use crate::key::keys::Pair;
+use crate::key::migration;
 use crate::key::store::FileStore;
+use crate::key::store::FileStoreError;
+use crate::key::toml::Toml;
+use crate::key::PointSerDeError;
 use crate::key::Scheme;
 use crate::net::control;
 use crate::net::control::ControlClient;
 use crate::net::dkg_control::DkgControlClient;
 use crate::net::health::HealthClient;
+use crate::net::http_api;
+use crate::net::http_api::HttpConfig;
 use crate::net::protocol;
 use crate::net::protocol::ProtocolClient;
+use crate::net::protocol::SyncMemory;
+use crate::net::public::PublicClient;
+use crate::net::public::PublicHandler;
+use crate::net::s3::S3Config;
+use crate::net::top;
 use crate::net::utils::Address;
 use crate::net::utils::ControlListener;
 use crate::net::utils::NodeListener;
+use crate::protobuf::dkg::DkgEntry;
+use crate::protobuf::drand::ChainInfoPacket;
+use crate::protobuf::drand::DaemonEvent;
+use crate::protobuf::drand::Metadata;
+use crate::protobuf::drand::Node as NodePacket;
+use crate::protobuf::drand::StartSyncRequest;
+use crate::secrets;
+use crate::secrets::Secret;
+use crate::secrets::SecretSource;
+use crate::verify;
 
+use anyhow::anyhow;
 use anyhow::bail;
 use anyhow::Result;
 use clap::arg;
 use clap::command;
+use clap::Args;
 use clap::Parser;
 use clap::Subcommand;
 use energon::drand::schemes::DefaultScheme;
 use energon::drand::schemes::SigsOnG1Scheme;
 use energon::drand::schemes::UnchainedScheme;
+use energon::kyber::poly::PubPoly;
+use energon::kyber::tbls;
 use energon::points::KeyPoint;
 use energon::traits::Affine;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::level_filters::LevelFilter;
+
+/// Interval of DKG status polling for `--wait` flag.
+const DKG_WAIT_POLL: Duration = Duration::from_secs(1);
 
 /// Generate the long-term keypair (drand.private, drand.public) for this node, and load it on the drand daemon if it is up and running
 #[derive(Debug, Parser, Clone)]
@@ -42,6 +108,10 @@ pub struct KeyGenConfig {
     pub scheme: String,
     /// The address other nodes will be able to contact this node on (specified as 'private-listen' to the daemon)
     pub address: String,
+    /// Derive the keypair deterministically from the given seed. INSECURE: for reproducible test setups only.
+    #[cfg(any(test, feature = "insecure"))]
+    #[arg(long, default_value = None)]
+    pub insecure_seed: Option<String>,
 }
 
 /// Start the drand daemon.
@@ -59,6 +129,320 @@ pub struct Config {
     /// Indicates the id for the randomness generation process which will be started
     #[arg(long, default_value = None)]
     pub id: Option<String>,
+    /// Comma-separated beacon ids to load, other ids in the folder are skipped. All ids are loaded if not set.
+    #[arg(long, value_delimiter = ',', conflicts_with = "id")]
+    pub only: Vec<String>,
+    /// Comma-separated log levels per beacon id, e.g. `quicknet=debug,default=warn`. Other ids use the default level.
+    #[arg(long, value_delimiter = ',', value_parser = crate::log::parse_beacon_level)]
+    pub log_level: Vec<(String, LevelFilter)>,
+    /// Lines per second logged for each sync or resync of many rounds, peer switches and errors are always logged. Throttling is disabled with 0.
+    #[arg(long, default_value_t = crate::log::DEFAULT_BULK_LOG_RATE)]
+    pub bulk_log_rate: u32,
+    /// Amount of beacons streamed to a syncing node before the stream yields to other tasks.
+    #[arg(long, default_value_t = protocol::DEFAULT_SYNC_BATCH_SIZE)]
+    pub sync_batch_size: usize,
+    /// Maximum amount of concurrent sync streams, extra syncing nodes are rejected until a stream is finished.
+    #[arg(long, default_value_t = protocol::DEFAULT_MAX_SYNC_STREAMS)]
+    pub max_sync_streams: usize,
+    /// Cap of memory in MiB used by beacons buffered across all sync streams, served to followers and received by resync and follow requests.
+    /// Streams are paused while it is reached, and followers which stall meanwhile are dropped.
+    #[arg(long, default_value_t = protocol::DEFAULT_SYNC_BUFFER_MB)]
+    pub sync_buffer_mb: usize,
+    /// Seconds to wait for reply of a beacon process to a control command before failing with DEADLINE_EXCEEDED.
+    #[arg(long, default_value_t = multibeacon::DEFAULT_CALLBACK_TIMEOUT_SECS)]
+    pub callback_timeout: u64,
+    /// Comma-separated beacon ids to run in shadow mode: a node holding a share of a group which is not active yet signs and verifies partials for the followed chain without broadcasting them, members verify own partials before broadcast.
+    #[arg(long, value_delimiter = ',')]
+    pub shadow: Vec<String>,
+    /// Comma-separated beacon ids whose chain store keeps only rounds and signatures: previous signatures and randomness are recomputed on read.
+    #[arg(long, value_delimiter = ',', conflicts_with = "precomputed_storage")]
+    pub derived_storage: Vec<String>,
+    /// Comma-separated beacon ids whose chain store additionally keeps randomness of each beacon, so it is not recomputed on read.
+    #[arg(long, value_delimiter = ',')]
+    pub precomputed_storage: Vec<String>,
+    /// Path of a Unix socket where each newly stored beacon of all beacon ids is written as a JSON line to connected local clients.
+    #[arg(long)]
+    pub ipc_socket: Option<String>,
+    /// Comma-separated fan-out of DKG bundles as `<id>=<strategy>` or `<strategy>` for all beacon ids: `full` (to every participant, as Go drand), `leader-hub` (to the leader, which relays them) or `random:<K>` (to K random participants, each relays once). Default: `full`.
+    #[arg(long, value_delimiter = ',')]
+    pub dkg_fanout: Vec<BeaconFanOut>,
+    #[command(flatten)]
+    pub archive: ArchiveArgs,
+    #[command(flatten)]
+    pub http: HttpArgs,
+    #[command(flatten)]
+    pub backup: BackupArgs,
+    #[command(flatten)]
+    pub runtime: RuntimeArgs,
+    #[command(flatten)]
+    pub pprof: PprofArgs,
+    #[command(flatten)]
+    pub webhooks: WebhookArgs,
+}
+
+/// Webhooks notified about daemon events, disabled if no URL is set.
+#[derive(Debug, Args, Clone, Default)]
+pub struct WebhookArgs {
+    /// URL to POST JSON of daemon events to, can be repeated.
+    #[arg(long = "webhook")]
+    pub webhook_urls: Vec<String>,
+    /// Secret source of the key to sign webhook bodies with HMAC-SHA256: `env:NAME`, `file:PATH` or `cred:NAME`.
+    #[arg(long, value_parser = secrets::parse_source)]
+    pub webhook_secret: Option<SecretSource>,
+    /// Comma-separated kinds of events sent to webhooks, see `drand util events`.
+    #[arg(long, value_delimiter = ',', default_value = webhooks::DEFAULT_KINDS)]
+    pub webhook_events: Vec<String>,
+}
+
+impl WebhookArgs {
+    /// Returns `None` if webhooks are disabled.
+    fn webhook_config(&self) -> Result<Option<WebhookConfig>> {
+        if self.webhook_urls.is_empty() {
+            return Ok(None);
+        }
+        if let Some(url) = self
+            .webhook_urls
+            .iter()
+            .find(|url| !url.starts_with("http://") && !url.starts_with("https://"))
+        {
+            bail!("--webhook: {url} should start with http:// or https://");
+        }
+
+        Ok(Some(WebhookConfig {
+            urls: self.webhook_urls.clone(),
+            secret: self
+                .webhook_secret
+                .as_ref()
+                .map(SecretSource::resolve)
+                .transpose()?,
+            kinds: self.webhook_events.clone(),
+        }))
+    }
+}
+
+/// Profiling endpoints of the daemon, disabled by default.
+#[derive(Debug, Args, Clone, Default)]
+pub struct PprofArgs {
+    /// Serve CPU profiles (`/debug/pprof/profile`) and heap statistics (`/debug/pprof/heap`), requires the `pprof` feature.
+    #[arg(long)]
+    pub enable_pprof: bool,
+    /// Set the listening (binding) address of profiling endpoints, they are not authenticated.
+    #[arg(long, default_value = "127.0.0.1:6060", requires = "enable_pprof")]
+    pub pprof_listen: String,
+}
+
+impl PprofArgs {
+    /// Returns `None` if profiling endpoints are disabled.
+    fn pprof_listen(&self) -> Result<Option<Address>> {
+        if !self.enable_pprof {
+            return Ok(None);
+        }
+        if cfg!(not(feature = "pprof")) {
+            bail!("--enable-pprof: drand is built without the `pprof` feature");
+        }
+
+        Ok(Some(Address::precheck(&self.pprof_listen)?))
+    }
+}
+
+/// Sizing of tokio runtime, see [`RuntimeConfig`].
+#[derive(Debug, Args, Clone, Default)]
+pub struct RuntimeArgs {
+    /// Worker threads of the runtime, amount of CPU cores if not set.
+    #[arg(long)]
+    pub worker_threads: Option<usize>,
+    /// Upper limit of threads for blocking tasks of the runtime, tokio default (512) if not set.
+    #[arg(long)]
+    pub max_blocking_threads: Option<usize>,
+    /// Run chain store I/O on a dedicated pool of up to N threads, so disk latency can not stall other tasks. Each beacon id and each sync stream keeps one thread, so N should be at least beacon ids + '--max-sync-streams' + 1. Store I/O shares the runtime blocking pool if not set.
+    #[arg(long)]
+    pub store_threads: Option<usize>,
+}
+
+impl RuntimeArgs {
+    fn runtime_config(&self, beacons: usize, sync_streams: usize) -> RuntimeConfig {
+        RuntimeConfig {
+            worker_threads: self.worker_threads,
+            max_blocking_threads: self.max_blocking_threads,
+            store_threads: self.store_threads,
+            beacons,
+            sync_streams,
+        }
+    }
+}
+
+/// Periodic backups of keys, group files and DKG store, disabled if interval is not set.
+#[derive(Debug, Args, Clone, Default)]
+pub struct BackupArgs {
+    /// Interval in seconds between backups of all beacon ids, the first backup is written at the start.
+    #[arg(long)]
+    pub backup_interval: Option<u64>,
+    /// Amount of retained backups, older ones are removed.
+    #[arg(long, default_value = "7")]
+    pub backup_keep: usize,
+    /// Folder to write backups into.
+    #[arg(long, requires = "backup_interval")]
+    pub backup_dir: Option<String>,
+    /// Endpoint of S3-compatible storage to upload backups to, used instead of `--backup-dir`.
+    #[arg(long, conflicts_with = "backup_dir", requires_all = ["backup_bucket", "backup_interval", "backup_passphrase"])]
+    pub backup_endpoint: Option<String>,
+    /// Secret source of the storage access key, `env:AWS_ACCESS_KEY_ID` if not set.
+    #[arg(long, value_parser = secrets::parse_source)]
+    pub backup_access_key: Option<SecretSource>,
+    /// Secret source of the storage secret key, `env:AWS_SECRET_ACCESS_KEY` if not set.
+    #[arg(long, value_parser = secrets::parse_source)]
+    pub backup_secret_key: Option<SecretSource>,
+    /// Bucket of backups.
+    #[arg(long, requires = "backup_endpoint")]
+    pub backup_bucket: Option<String>,
+    /// Region used to sign storage requests.
+    #[arg(long, default_value = "us-east-1")]
+    pub backup_region: String,
+    /// Prefix of backup object keys.
+    #[arg(long, default_value = "backups/")]
+    pub backup_prefix: String,
+    /// Secret source of a passphrase to encrypt private keys and shares with, they are archived in plain text if not set.
+    /// Required with '--backup-endpoint', unencrypted archives are never uploaded.
+    #[arg(long, value_parser = secrets::parse_source)]
+    pub backup_passphrase: Option<SecretSource>,
+}
+
+impl BackupArgs {
+    /// Returns `None` if backups are disabled.
+    fn backup_config(&self) -> Result<Option<BackupConfig>> {
+        let Some(interval) = self.backup_interval else {
+            return Ok(None);
+        };
+        let target = match (&self.backup_dir, &self.backup_endpoint, &self.backup_bucket) {
+            (Some(dir), _, _) => {
+                std::fs::create_dir_all(dir)?;
+                BackupTarget::Dir(std::path::absolute(dir)?)
+            }
+            (None, Some(endpoint), Some(bucket)) => {
+                let (access_key, secret_key) = s3_credentials(
+                    self.backup_access_key.as_ref(),
+                    self.backup_secret_key.as_ref(),
+                )?;
+                BackupTarget::S3 {
+                    s3: S3Config {
+                        endpoint: endpoint.clone(),
+                        bucket: bucket.clone(),
+                        region: self.backup_region.clone(),
+                        access_key,
+                        secret_key,
+                    },
+                    prefix: self.backup_prefix.clone(),
+                }
+            }
+            _ => bail!(
+                "backup: either --backup-dir or --backup-endpoint with --backup-bucket is required"
+            ),
+        };
+
+        Ok(Some(BackupConfig {
+            interval: Duration::from_secs(interval.max(1)),
+            keep: self.backup_keep.max(1),
+            target,
+            passphrase: self
+                .backup_passphrase
+                .as_ref()
+                .map(SecretSource::resolve)
+                .transpose()?,
+        }))
+    }
+}
+
+/// HTTP JSON API of public beacons, disabled if listen address is not set.
+#[derive(Debug, Args, Clone, Default)]
+pub struct HttpArgs {
+    /// Set the listening (binding) address of the public HTTP API, e.g. `0.0.0.0:8080`.
+    #[arg(long)]
+    pub public_listen: Option<String>,
+    /// Comma-separated origins allowed to read the public HTTP API from browsers, `*` allows any origin.
+    #[arg(long, value_delimiter = ',', default_value = "*")]
+    pub cors_origins: Vec<String>,
+}
+
+impl HttpArgs {
+    /// Returns `None` if HTTP API is disabled.
+    fn http_config(&self) -> Result<Option<(Address, HttpConfig)>> {
+        let Some(listen) = &self.public_listen else {
+            return Ok(None);
+        };
+        let config = HttpConfig {
+            cors_origins: self.cors_origins.clone(),
+        };
+
+        Ok(Some((Address::precheck(listen)?, config)))
+    }
+}
+
+/// Archiving of finalized beacons to S3-compatible storage, disabled if endpoint is not set.
+#[derive(Debug, Args, Clone, Default)]
+pub struct ArchiveArgs {
+    /// Endpoint of S3-compatible storage to upload finalized beacons to, e.g. `https://s3.us-east-1.amazonaws.com`.
+    #[arg(long, requires = "archive_bucket")]
+    pub archive_endpoint: Option<String>,
+    /// Secret source of the storage access key, `env:AWS_ACCESS_KEY_ID` if not set.
+    #[arg(long, value_parser = secrets::parse_source)]
+    pub archive_access_key: Option<SecretSource>,
+    /// Secret source of the storage secret key, `env:AWS_SECRET_ACCESS_KEY` if not set.
+    #[arg(long, value_parser = secrets::parse_source)]
+    pub archive_secret_key: Option<SecretSource>,
+    /// Bucket of the beacon archive.
+    #[arg(long, requires = "archive_endpoint")]
+    pub archive_bucket: Option<String>,
+    /// Region used to sign storage requests.
+    #[arg(long, default_value = "us-east-1")]
+    pub archive_region: String,
+    /// Prefix of archive object keys, beacons are stored under `<PREFIX><BEACON_ID>/`.
+    #[arg(long, default_value = "")]
+    pub archive_prefix: String,
+    /// Amount of beacons per archive chunk file.
+    #[arg(long, default_value_t = archive::DEFAULT_CHUNK_SIZE)]
+    pub archive_chunk_size: u64,
+}
+
+impl ArchiveArgs {
+    /// Returns `None` if archiving is disabled.
+    fn archive_config(&self) -> Result<Option<ArchiveConfig>> {
+        let (Some(endpoint), Some(bucket)) = (&self.archive_endpoint, &self.archive_bucket) else {
+            return Ok(None);
+        };
+        let (access_key, secret_key) = s3_credentials(
+            self.archive_access_key.as_ref(),
+            self.archive_secret_key.as_ref(),
+        )?;
+
+        Ok(Some(ArchiveConfig {
+            s3: S3Config {
+                endpoint: endpoint.clone(),
+                bucket: bucket.clone(),
+                region: self.archive_region.clone(),
+                access_key,
+                secret_key,
+            },
+            prefix: self.archive_prefix.clone(),
+            chunk_size: self.archive_chunk_size.max(1),
+        }))
+    }
+}
+
+/// Resolves storage credentials, AWS environment variables are used by default.
+fn s3_credentials(
+    access_key: Option<&SecretSource>,
+    secret_key: Option<&SecretSource>,
+) -> Result<(String, Secret)> {
+    let resolve = |source: Option<&SecretSource>, env: &str| match source {
+        Some(source) => source.resolve(),
+        None => SecretSource::Env(env.to_string()).resolve(),
+    };
+    let access_key = resolve(access_key, "AWS_ACCESS_KEY_ID")?;
+
+    Ok((
+        access_key.expose().to_string(),
+        resolve(secret_key, "AWS_SECRET_ACCESS_KEY")?,
+    ))
 }
 
 /// Sync your local randomness chain with other nodes and validate your local beacon chain. To follow a remote node, it requires the use of the 'follow' flag.
@@ -67,10 +451,15 @@ pub struct SyncConfig {
     /// Set the port you want to listen to for control port commands. If not specified, we will use the default value.
     #[arg(long, default_value = control::DEFAULT_CONTROL_PORT)]
     pub control: String,
-    /// The hash of the chain info.
-    #[arg(long)]
+    /// The hash of the chain info, the only trust root of the sync: sync nodes are not trusted, chain info is accepted only from nodes serving this chain. Not required with '--chain-info'.
+    #[arg(long, default_value = "", required_unless_present = "chain_info")]
     pub chain_hash: String,
+    /// Chain info JSON as served at `/info` of HTTP relays, used instead of chain info fetched from sync nodes.
+    /// The chain hash is taken from the file, '--chain-hash' must match it if given.
+    #[arg(long, default_value = None)]
+    pub chain_info: Option<String>,
     /// <ADDRESS:PORT>,<...> of (multiple) reachable drand daemon(s). When checking our local database, using our local daemon address will result in a dry run.
+    /// HTTP relay URLs of the chain (e.g. `https://api.drand.sh/<CHAIN_HASH>`) are accepted as well and are tried in between of failed daemons.
     #[arg(long)]
     pub sync_nodes: Vec<String>,
     /// Specify a round at which the drand daemon will stop syncing the chain, typically used to bootstrap a new node.
@@ -83,6 +472,66 @@ pub struct SyncConfig {
     /// Indicates whether we want to follow another daemon up to latest chain height.
     #[arg(long)]
     pub follow: bool,
+    /// Verification mode for fetched beacons: full, spot-check (every Nth and the final beacon) or trust (store without verification).
+    #[arg(long, default_value = "full")]
+    pub verify: VerifyMode,
+    /// Interval in rounds between verified beacons, used only with '--verify spot-check'.
+    #[arg(long, default_value_t = crate::chain::DEFAULT_SPOT_CHECK_EVERY)]
+    pub spot_check_every: u64,
+    /// Round of trusted checkpoint to start verification from instead of genesis. Requires '--checkpoint-sig'.
+    #[arg(long, default_value = "0", requires = "checkpoint_sig")]
+    pub checkpoint_round: u64,
+    /// Hex-encoded trusted signature of the checkpoint round.
+    #[arg(long, default_value = None, requires = "checkpoint_round")]
+    pub checkpoint_sig: Option<String>,
+    /// URL of beacon archive (see '--archive-endpoint' of `drand start`) to import before syncing from nodes.
+    /// Archived beacons are verified according to '--verify'.
+    #[arg(long, default_value = None)]
+    pub from_archive: Option<String>,
+}
+
+/// Follow a chain from other nodes and serve it over the public HTTP API, without keypair and DKG.
+#[derive(Debug, Parser, Clone)]
+pub struct RelayArgs {
+    /// Folder to keep the followed chains, with absolute path. Beacons are stored under `relay/<ID>`.
+    #[arg(long, default_value_t = FileStore::drand_home())]
+    pub folder: String,
+    /// Comma-separated followed chains as `<ID>=<CHAIN_HASH>`, e.g. `quicknet=52db9b...,default=8990e7...`.
+    #[arg(long, value_delimiter = ',', required = true, value_parser = parse_relay_chain)]
+    pub chains: Vec<(String, String)>,
+    /// <ADDRESS:PORT>,<...> of (multiple) reachable drand daemon(s), used for all chains.
+    /// HTTP relay URLs are used for the chain whose hash is in the URL, URLs without chain hash are used for the default chain.
+    #[arg(long, required = true)]
+    pub sync_nodes: Vec<String>,
+    /// Verification mode for fetched beacons: full, spot-check (every Nth and the final beacon) or trust (store without verification).
+    #[arg(long, default_value = "full")]
+    pub verify: VerifyMode,
+    /// Interval in rounds between verified beacons, used only with '--verify spot-check'.
+    #[arg(long, default_value_t = crate::chain::DEFAULT_SPOT_CHECK_EVERY)]
+    pub spot_check_every: u64,
+    /// Interval in rounds between audits: the latest stored round is fetched from all sync nodes and their signatures are compared with the stored one.
+    /// Divergences are logged and counted at `/metrics` of the HTTP API. Auditing is disabled if set to 0.
+    #[arg(long, default_value = "0")]
+    pub audit_every: u64,
+    /// Cap of memory in MiB used by beacons buffered across follow streams of all chains, streams are paused while it is reached.
+    #[arg(long, default_value_t = protocol::DEFAULT_SYNC_BUFFER_MB)]
+    pub sync_buffer_mb: usize,
+    #[command(flatten)]
+    pub http: HttpArgs,
+}
+
+fn parse_relay_chain(s: &str) -> Result<(String, String), String> {
+    let (id, hash) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected <ID>=<CHAIN_HASH>, received {s}"))?;
+
+    Ok((id.trim().to_string(), hash.trim().to_string()))
+}
+
+/// Parses hex-encoded bytes with an optional `0x` prefix.
+fn parse_hex(s: &str) -> Result<Vec<u8>, String> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    hex::decode(s).map_err(|err| format!("invalid hex {s}: {err}"))
 }
 
 /// Commands for interacting with the DKG
@@ -98,6 +547,14 @@ pub enum Dkg {
         /// Absolute path to the group file of previous epoch
         #[arg(long, default_value = None)]
         group: Option<String>,
+        /// Wait and print DKG status transitions until completion, fails if the DKG is not completed.
+        #[arg(long)]
+        wait: bool,
+        /// Sync the chain from members of the group file: up to the current round before joining and up
+        /// to the transition round once the DKG is completed. Required for joiners of an existing
+        /// network which do not follow the chain yet, implies `--wait`.
+        #[arg(long, requires = "group")]
+        follow: bool,
     },
     Accept {
         /// Set the port you want to listen to for control port commands. If not specified, we will use the default value.
@@ -106,6 +563,119 @@ pub enum Dkg {
         /// Indicates the id for the randomness generation process which will be started
         #[arg(long)]
         id: String,
+        /// Wait and print DKG status transitions until completion, fails if the DKG is not completed.
+        #[arg(long)]
+        wait: bool,
+    },
+    /// Show the current DKG state of the beacon id with deadlines taken from the proposal, e.g. how long is left to accept it.
+    Status {
+        /// Set the port you want to listen to for control port commands. If not specified, we will use the default value.
+        #[arg(long, default_value = control::DEFAULT_CONTROL_PORT)]
+        control: String,
+        /// Indicates the id for the randomness generation process.
+        #[arg(long)]
+        id: String,
+    },
+    /// Assemble a proposal file from public key files of participants.
+    GenerateProposal {
+        /// Path to the public key file of a joining participant, can be repeated.
+        #[arg(long)]
+        joiner: Vec<String>,
+        /// Path to the public key file of a remaining participant, can be repeated.
+        #[arg(long)]
+        remainer: Vec<String>,
+        /// Path to the public key file of a leaving participant, can be repeated.
+        #[arg(long)]
+        leaver: Vec<String>,
+        /// Path of the resulting proposal file.
+        #[arg(long)]
+        out: String,
+        /// Threshold to validate the proposal against, the minimum threshold for the group size is used if not set.
+        #[arg(long)]
+        threshold: Option<usize>,
+        /// Threshold of the current group, checks that enough nodes remain for a reshare.
+        #[arg(long)]
+        previous_threshold: Option<usize>,
+    },
+    /// Propose a reshare with the same members and threshold to refresh key shares without changing the group public key.
+    ///
+    /// Command is sent to the leader, members accept the proposal as for any reshare.
+    Refresh {
+        /// Control port of the leader.
+        #[arg(long, default_value = control::DEFAULT_CONTROL_PORT)]
+        control: String,
+        /// Indicates the id for the randomness generation process.
+        #[arg(long, default_value = beacon::DEFAULT_BEACON_ID)]
+        id: String,
+        /// Address of a group member to fetch the current group from.
+        #[arg(long)]
+        node: String,
+        /// Threshold of the refreshed group, the current threshold is kept if not set.
+        #[arg(long)]
+        threshold: Option<u32>,
+        /// Timeout of the proposal in seconds.
+        #[arg(long, default_value = "86400")]
+        timeout: u64,
+        /// Execute the proposal after the given amount of seconds, members should accept it in the meantime.
+        #[arg(long)]
+        execute_after: Option<u64>,
+        /// Repeat the refresh every given amount of seconds.
+        #[arg(long)]
+        every: Option<u64>,
+    },
+    /// Export signed DKG messages recorded for the given epoch as JSON, lists recorded epochs if epoch is not set.
+    Evidence {
+        /// Folder to keep all drand cryptographic information, with absolute path.
+        #[arg(long, default_value_t = FileStore::drand_home())]
+        folder: String,
+        /// Indicates the id for the randomness generation process.
+        #[arg(long, default_value = beacon::DEFAULT_BEACON_ID)]
+        id: String,
+        #[arg(long)]
+        epoch: Option<u32>,
+        /// Path of the resulting file, evidence is printed to stdout if not set.
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Leave the group: announce leaving, wait until a reshare excluding this node is completed, then unload the beacon process.
+    ///
+    /// Signed leave notice is sent to other members of the group, drand-rs members report it as `leave_notice` event.
+    /// The leader should include this node with `generate-proposal --leaver`, the path of the public key file to pass
+    /// is printed while waiting. The new group must continue the local chain. Node keeps signing until its transition time.
+    Leave {
+        /// Set the port you want to listen to for control port commands. If not specified, we will use the default value.
+        #[arg(long, default_value = control::DEFAULT_CONTROL_PORT)]
+        control: String,
+        /// Folder to keep all drand cryptographic information, with absolute path.
+        #[arg(long, default_value_t = FileStore::drand_home())]
+        folder: String,
+        /// Indicates the id for the randomness generation process.
+        #[arg(long, default_value = beacon::DEFAULT_BEACON_ID)]
+        id: String,
+        /// Address of a remaining member to fetch the new group from, leader of the reshare is used if not set.
+        #[arg(long)]
+        node: Option<String>,
+        /// Archive keys of the beacon id to the given file name in `backups` of the daemon folder before the beacon process is unloaded, see `util backup`.
+        #[arg(long)]
+        backup: Option<String>,
+        /// Secret source of a passphrase to encrypt the archive: `env:NAME`, `file:PATH` or `cred:NAME`.
+        #[arg(long, value_parser = secrets::parse_source, requires = "backup")]
+        passphrase: Option<SecretSource>,
+    },
+    /// Wipe failed or stale DKG state of the beacon id, the last completed state is restored.
+    ///
+    /// Wiped files and evidence log are moved into `dkg/reset/` of the beacon id. A DKG in progress is not reset,
+    /// abort it or wait for its timeout. Beacon id which fails to load is reset as well, load it afterwards.
+    Reset {
+        /// Set the port you want to listen to for control port commands. If not specified, we will use the default value.
+        #[arg(long, default_value = control::DEFAULT_CONTROL_PORT)]
+        control: String,
+        /// Indicates the id for the randomness generation process.
+        #[arg(long, default_value = beacon::DEFAULT_BEACON_ID)]
+        id: String,
+        /// Confirm the reset, nothing is wiped without it.
+        #[arg(long)]
+        yes: bool,
     },
 }
 
@@ -127,6 +697,24 @@ pub enum Show {
         /// Indicates the id for the randomness generation process which will be started
         #[arg(long)]
         id: String,
+        /// Also query the status of all members of the latest group.
+        #[arg(long)]
+        group: bool,
+    },
+    /// Validate the stored distributed key share against the group file and print its public information.
+    Share {
+        /// Folder to keep all drand cryptographic information, with absolute path.
+        #[arg(long, default_value_t = FileStore::drand_home())]
+        folder: String,
+        /// Indicates the id for the randomness generation process.
+        #[arg(long, default_value = beacon::DEFAULT_BEACON_ID)]
+        id: String,
+        /// Print as JSON.
+        #[arg(long)]
+        json: bool,
+        /// Print the private share, otherwise private material is redacted.
+        #[arg(long)]
+        unsafe_print: bool,
     },
 }
 
@@ -140,6 +728,261 @@ pub enum Util {
         id: Option<String>,
         addresses: Vec<String>,
     },
+    /// Check that beacon ids at `FOLDER` created by golang implementation can be loaded in place.
+    CheckMigration {
+        /// Limit the check to a single beacon id.
+        #[arg(long, default_value = None)]
+        id: Option<String>,
+        /// Epoch of the last completed DKG, checks that missing DKG state can be reconstructed from the group file.
+        #[arg(long, requires = "id")]
+        epoch: Option<u32>,
+        folder: String,
+    },
+    /// Reconstruct missing DKG state of a beacon id at `FOLDER` created by golang implementation.
+    ///
+    /// Migration is one-way: DKG state is kept in `multibeacon/<id>/dkg` which golang implementation does not read, and its `dkg.db` is never updated. Going back to golang implementation is only safe before the next DKG of the beacon id.
+    Migrate {
+        /// Beacon id to migrate.
+        #[arg(long)]
+        id: String,
+        /// Epoch of the last completed DKG.
+        #[arg(long)]
+        epoch: u32,
+        folder: String,
+    },
+    /// Print structured events of the local daemon.
+    Events {
+        /// Set the port you want to listen to for control port commands. If not specified, we will use the default value.
+        #[arg(long, default_value = control::DEFAULT_CONTROL_PORT)]
+        control: String,
+        /// Indicates the id for the randomness generation process, events of all ids are printed if not specified.
+        #[arg(long, default_value = None)]
+        id: Option<String>,
+        /// Keep printing events until the daemon is stopped, otherwise exit after the first event.
+        #[arg(long)]
+        follow: bool,
+    },
+    /// Generate keys, a proposal and launch files of a local test network into `OUT` folder.
+    GenerateTestnet {
+        /// Number of nodes, the first node leads the DKG.
+        #[arg(long, default_value = "3")]
+        nodes: u16,
+        /// Beacon period in seconds.
+        #[arg(long, default_value = "3")]
+        period: u32,
+        /// Indicates a set of values drand will use to configure the randomness generation process
+        #[arg(long, default_value = DefaultScheme::ID)]
+        scheme: String,
+        /// Indicates the id for the randomness generation process.
+        #[arg(long, default_value = beacon::DEFAULT_BEACON_ID)]
+        id: String,
+        /// Generate plain shell scripts for localhost instead of docker-compose file.
+        #[arg(long)]
+        scripts: bool,
+        /// Docker image of drand-rs nodes.
+        #[arg(long, default_value = "drand-rs:latest")]
+        image: String,
+        /// Run the first node by golang implementation, drand-rs can not lead a DKG yet. Without it all nodes run drand-rs and dkg.sh exits before the DKG is initiated.
+        #[arg(long)]
+        go_leader: bool,
+        /// Docker image of the golang leader node, see `--go-leader`.
+        #[arg(long, default_value = "drandorg/go-drand:latest")]
+        leader_image: String,
+        /// Output folder, must be empty or absent.
+        #[arg(long)]
+        out: String,
+    },
+    /// Measure round-trip time of requests to the node at `ADDRESS` and show its version, beacon ids and rounds.
+    Ping {
+        /// Number of ping requests.
+        #[arg(long, default_value = "3")]
+        count: u32,
+        address: String,
+    },
+    /// Query the status of the nodes at `ADDRESS` (you can put multiple ones) through the local daemon, all members of the latest group are queried if not specified.
+    RemoteStatus {
+        /// Set the port you want to listen to for control port commands. If not specified, we will use the default value.
+        #[arg(long, default_value = control::DEFAULT_CONTROL_PORT)]
+        control: String,
+        /// Indicates the id for the randomness generation process.
+        #[arg(long, default_value = beacon::DEFAULT_BEACON_ID)]
+        id: String,
+        addresses: Vec<String>,
+    },
+    /// Show a refreshing terminal view of chain height, lag, DKG phase and peer errors of the local daemon.
+    Top {
+        /// Set the port you want to listen to for control port commands. If not specified, we will use the default value.
+        #[arg(long, default_value = control::DEFAULT_CONTROL_PORT)]
+        control: String,
+        /// Indicates the id for the randomness generation process (you can put multiple ones), ids seen in events are shown if not specified.
+        #[arg(long)]
+        id: Vec<String>,
+        /// Refresh interval in seconds.
+        #[arg(long, default_value = "2")]
+        interval: u64,
+    },
+    /// Inspect chain store at `FOLDER` (e.g. `multibeacon/<id>/db`) without running daemon: print stored rounds, missing ranges and size.
+    DbInspect {
+        /// Print beacons of the given rounds as JSON lines instead of stats, e.g. `1,5-10`.
+        #[arg(long)]
+        rounds: Option<String>,
+        /// Print beacons of `--rounds` as JSON of api.drand.sh or their randomness as hex, base64 or raw bytes, instead of JSON lines with checksum status.
+        #[arg(long, requires = "rounds")]
+        format: Option<BeaconFormat>,
+        folder: String,
+    },
+    /// Force resync of the local daemon from the given round, stored beacons from this round are verified again and rewritten if they differ.
+    Resync {
+        /// Set the port you want to listen to for control port commands. If not specified, we will use the default value.
+        #[arg(long, default_value = control::DEFAULT_CONTROL_PORT)]
+        control: String,
+        /// Indicates the id for the randomness generation process.
+        #[arg(long, default_value = beacon::DEFAULT_BEACON_ID)]
+        id: String,
+        /// First round to resync.
+        #[arg(long)]
+        from: u64,
+    },
+    /// Change log level of the beacon id on the local daemon.
+    LogLevel {
+        /// Set the port you want to listen to for control port commands. If not specified, we will use the default value.
+        #[arg(long, default_value = control::DEFAULT_CONTROL_PORT)]
+        control: String,
+        /// Indicates the id for the randomness generation process.
+        #[arg(long, default_value = beacon::DEFAULT_BEACON_ID)]
+        id: String,
+        /// One of: off, error, warn, info, debug, trace. Resets to the default level of the daemon if not set.
+        #[arg(long, value_parser = crate::log::parse_level)]
+        level: Option<LevelFilter>,
+    },
+    /// Show commands queued to beacon processes of the local daemon, a growing age of the oldest command indicates a stuck process.
+    Queue {
+        /// Set the port you want to listen to for control port commands. If not specified, we will use the default value.
+        #[arg(long, default_value = control::DEFAULT_CONTROL_PORT)]
+        control: String,
+        /// Indicates the id for the randomness generation process. All loaded ids are shown if not set.
+        #[arg(long)]
+        id: Option<String>,
+    },
+    /// Show chain stores of the local daemon: stored rounds, size on disk and space reclaimable by compaction.
+    StoreStats {
+        /// Set the port you want to listen to for control port commands. If not specified, we will use the default value.
+        #[arg(long, default_value = control::DEFAULT_CONTROL_PORT)]
+        control: String,
+        /// Indicates the id for the randomness generation process. All loaded ids are shown if not set.
+        #[arg(long)]
+        id: Option<String>,
+        /// Print stores as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Write an archive of keys, group files and DKG store of the local daemon into a new file `OUT`, the chain database is not archived.
+    Backup {
+        /// Set the port you want to listen to for control port commands. If not specified, we will use the default value.
+        #[arg(long, default_value = control::DEFAULT_CONTROL_PORT)]
+        control: String,
+        /// Indicates the id for the randomness generation process. All beacon ids are archived if not set.
+        #[arg(long)]
+        id: Option<String>,
+        /// Secret source of a passphrase to encrypt private keys and shares with: `env:NAME`, `file:PATH` or `cred:NAME`. They are archived in plain text if not set.
+        #[arg(long, value_parser = secrets::parse_source)]
+        passphrase: Option<SecretSource>,
+        /// File name of the archive, e.g. `backup.tar.zst`, must be absent. The archive is written by the daemon into `backups` of its folder.
+        #[arg(long)]
+        out: String,
+    },
+    /// Restore beacon ids from the archive `IN` into the daemon folder, the daemon should be stopped. Existing beacon ids are never overwritten.
+    Restore {
+        /// Folder to keep all drand cryptographic information, with absolute path.
+        #[arg(long, default_value_t = FileStore::drand_home())]
+        folder: String,
+        /// Secret source of the passphrase of an encrypted archive: `env:NAME`, `file:PATH` or `cred:NAME`.
+        #[arg(long, value_parser = secrets::parse_source)]
+        passphrase: Option<SecretSource>,
+        /// Archive created by `util backup`.
+        #[arg(long = "in")]
+        input: String,
+    },
+    /// Collect a sanitized JSON dump of the local daemon to attach to bug reports: version, config without secrets, status, command queues and store stats of beacon ids and recent events.
+    DebugDump {
+        /// Set the port you want to listen to for control port commands. If not specified, we will use the default value.
+        #[arg(long, default_value = control::DEFAULT_CONTROL_PORT)]
+        control: String,
+        /// Output file, the dump is printed to stdout if not set.
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Show bytes sent to and received from each peer IP at node address of the local daemon.
+    Bandwidth {
+        /// Set the port you want to listen to for control port commands. If not specified, we will use the default value.
+        #[arg(long, default_value = control::DEFAULT_CONTROL_PORT)]
+        control: String,
+    },
+    /// Compare group files `OLD` and `NEW` of a chain: membership, threshold, public key and transition time, e.g. to review a reshare before distributing the new group.
+    GroupDiff {
+        /// Print the diff as JSON.
+        #[arg(long)]
+        json: bool,
+        old: String,
+        new: String,
+    },
+    /// Verify a beacon obtained from a third party against trusted chain info, offline.
+    VerifyBeacon {
+        /// Chain info JSON as served at `/info` of HTTP relays, e.g. `https://api.drand.sh/<chain hash>/info`.
+        #[arg(long)]
+        chain_info: String,
+        #[arg(long)]
+        round: u64,
+        /// Hex-encoded signature of the beacon.
+        #[arg(long, value_parser = parse_hex)]
+        signature: Vec<u8>,
+        /// Hex-encoded signature of the previous round, required by chained schemes.
+        #[arg(long, value_parser = parse_hex)]
+        previous: Option<Vec<u8>>,
+    },
+    /// List equivocations recorded by the node or the relay: different valid beacons observed for the same round.
+    Equivocations {
+        /// Folder to keep all drand cryptographic information, with absolute path.
+        #[arg(long, default_value_t = FileStore::drand_home())]
+        folder: String,
+        /// Indicates the id for the randomness generation process.
+        #[arg(long, default_value = beacon::DEFAULT_BEACON_ID)]
+        id: String,
+        /// Print as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Fetch the group used at given epoch from the node at `ADDRESS`.
+    Group {
+        /// Indicates the id for the randomness generation process.
+        #[arg(long)]
+        id: String,
+        /// Epoch of the group, the latest group is returned if not specified.
+        #[arg(long, default_value = "0")]
+        epoch: u32,
+        address: String,
+    },
+}
+
+/// Fetch data of a chain from a node over the public API, everything fetched is verified.
+#[derive(Subcommand, Clone, Debug)]
+pub enum Get {
+    /// Fetch the beacon of `ROUND` (0 for the latest) from the node and print its randomness, derived from the verified signature as `sha256(signature)`.
+    Randomness {
+        /// Address of the node.
+        #[arg(long)]
+        node: String,
+        /// Indicates the id for the randomness generation process.
+        #[arg(long, default_value = beacon::DEFAULT_BEACON_ID)]
+        id: String,
+        /// Hex-encoded hash of the chain, chain info served by the node is trusted if not specified.
+        #[arg(long)]
+        chain_hash: Option<String>,
+        /// Print the randomness as hex, base64 or raw bytes, or the beacon as JSON of api.drand.sh.
+        #[arg(long, default_value = "hex")]
+        format: BeaconFormat,
+        round: u64,
+    },
 }
 
 #[derive(Debug, Parser, Clone)]
@@ -176,38 +1019,237 @@ pub enum Cmd {
         /// Indicates the id for the randomness generation process which will be started
         #[arg(long)]
         id: String,
+        /// Base folder to import the beacon id from (e.g. folder of golang node), the beacon id folder is copied into the daemon folder. Relative path is resolved against the current folder.
+        #[arg(long, default_value = None)]
+        from: Option<String>,
+    },
+    /// Stop the beacon id, the daemon keeps running even if no beacon ids are left.
+    Unload {
+        /// Set the port you want to listen to for control port commands. If not specified, we will use the default value.
+        #[arg(long, default_value = control::DEFAULT_CONTROL_PORT)]
+        control: String,
+        /// Indicates the id to be stopped.
+        #[arg(long)]
+        id: String,
     },
     Sync(SyncConfig),
+    Relay(RelayArgs),
     #[command(subcommand)]
     Dkg(Dkg),
     #[command(subcommand)]
     Show(Show),
     #[command(subcommand)]
     Util(Util),
+    #[command(subcommand)]
+    Get(Get),
 }
 
 impl Cli {
+    /// Builds tokio runtime, sized by [`RuntimeArgs`] of the daemon, other commands use defaults.
+    pub fn runtime(&self) -> std::io::Result<tokio::runtime::Runtime> {
+        match &self.commands {
+            Cmd::Start(config) => {
+                // Beacon ids are counted before loading, so the store pool fits all of them.
+                let beacons = FileStore::read_multibeacon_folder(&config.folder)
+                    .map_or(0, |(_, stores)| stores.len());
+                config
+                    .runtime
+                    .runtime_config(beacons, config.max_sync_streams)
+                    .build()
+            }
+            _ => RuntimeConfig::default().build(),
+        }
+    }
+
     pub async fn run(self) -> Result<()> {
         crate::log::setup_tracing(self.verbose)?;
 
         match self.commands {
             Cmd::GenerateKeypair(config) => keygen_cmd(config).await?,
             Cmd::Start(config) => start_cmd(config).await?,
-            Cmd::Load { control, id } => load_beacon_cmd(&control, id).await?,
+            Cmd::Load { control, id, from } => load_beacon_cmd(&control, id, from).await?,
+            Cmd::Unload { control, id } => unload_beacon_cmd(&control, id).await?,
             Cmd::Stop { control, id } => stop_cmd(&control, id).await?,
             Cmd::Sync(config) => sync_cmd(config).await?,
+            Cmd::Relay(config) => relay_cmd(config).await?,
             Cmd::Dkg(dkg) => match dkg {
-                Dkg::Join { control, id, group } => {
-                    dkg_join_cmd(&control, id, group.as_deref()).await?;
+                Dkg::Join {
+                    control,
+                    id,
+                    group,
+                    wait,
+                    follow,
+                } => dkg_join_cmd(&control, id, group.as_deref(), wait, follow).await?,
+                Dkg::Accept { control, id, wait } => dkg_accept_cmd(&control, id, wait).await?,
+                Dkg::Status { control, id } => dkg_status_cmd(&control, &id).await?,
+                Dkg::GenerateProposal {
+                    joiner,
+                    remainer,
+                    leaver,
+                    out,
+                    threshold,
+                    previous_threshold,
+                } => dkg_generate_proposal_cmd(
+                    &joiner,
+                    &remainer,
+                    &leaver,
+                    &out,
+                    threshold,
+                    previous_threshold,
+                )?,
+                Dkg::Refresh {
+                    control,
+                    id,
+                    node,
+                    threshold,
+                    timeout,
+                    execute_after,
+                    every,
+                } => {
+                    dkg_refresh_cmd(
+                        &control,
+                        id,
+                        &node,
+                        threshold,
+                        Duration::from_secs(timeout),
+                        execute_after.map(Duration::from_secs),
+                        every.map(Duration::from_secs),
+                    )
+                    .await?;
                 }
-                Dkg::Accept { control, id } => dkg_accept_cmd(&control, id).await?,
+                Dkg::Evidence {
+                    folder,
+                    id,
+                    epoch,
+                    out,
+                } => dkg_evidence_cmd(&folder, &id, epoch, out.as_deref())?,
+                Dkg::Leave {
+                    control,
+                    folder,
+                    id,
+                    node,
+                    backup,
+                    passphrase,
+                } => {
+                    dkg_leave_cmd(
+                        &control,
+                        &folder,
+                        &id,
+                        node.as_deref(),
+                        backup.as_deref(),
+                        passphrase.as_ref(),
+                    )
+                    .await?;
+                }
+                Dkg::Reset { control, id, yes } => dkg_reset_cmd(&control, id, yes).await?,
             },
             Cmd::Show(show) => match show {
                 Show::ChainInfo { control, id } => chain_info_cmd(&control, id).await?,
-                Show::Status { control, id } => status_cmd(&control, id).await?,
+                Show::Status { control, id, group } => {
+                    status_cmd(&control, id.clone()).await?;
+                    if group {
+                        println!();
+                        util_remote_status_cmd(&control, id, vec![]).await?;
+                    }
+                }
+                Show::Share {
+                    folder,
+                    id,
+                    json,
+                    unsafe_print,
+                } => show_share_cmd(&folder, &id, json, unsafe_print)?,
             },
             Cmd::Util(util) => match util {
                 Util::Check { id, addresses } => util_check_cmd(id.as_deref(), addresses).await?,
+                Util::Group { id, epoch, address } => util_group_cmd(id, epoch, &address).await?,
+                Util::GroupDiff { json, old, new } => util_group_diff_cmd(&old, &new, json)?,
+                Util::VerifyBeacon {
+                    chain_info,
+                    round,
+                    signature,
+                    previous,
+                } => util_verify_beacon_cmd(&chain_info, round, &signature, previous.as_deref())?,
+                Util::Equivocations { folder, id, json } => {
+                    util_equivocations_cmd(&folder, &id, json)?;
+                }
+                Util::CheckMigration { id, epoch, folder } => {
+                    util_check_migration_cmd(&folder, id.as_deref(), epoch)?;
+                }
+                Util::Migrate { id, epoch, folder } => util_migrate_cmd(&folder, &id, epoch)?,
+                Util::Events {
+                    control,
+                    id,
+                    follow,
+                } => util_events_cmd(&control, id, follow).await?,
+                Util::GenerateTestnet {
+                    nodes,
+                    period,
+                    scheme,
+                    id,
+                    scripts,
+                    image,
+                    go_leader,
+                    leader_image,
+                    out,
+                } => util_generate_testnet_cmd(&TestnetConfig {
+                    nodes,
+                    period,
+                    scheme,
+                    beacon_id: id,
+                    out: out.into(),
+                    scripts,
+                    image,
+                    go_leader,
+                    leader_image,
+                })?,
+                Util::Ping { count, address } => util_ping_cmd(&address, count).await?,
+                Util::RemoteStatus {
+                    control,
+                    id,
+                    addresses,
+                } => util_remote_status_cmd(&control, id, addresses).await?,
+                Util::Top {
+                    control,
+                    id,
+                    interval,
+                } => top::run(&control, id, Duration::from_secs(interval.max(1))).await?,
+                Util::Bandwidth { control } => util_bandwidth_cmd(&control).await?,
+                Util::DbInspect {
+                    rounds,
+                    format,
+                    folder,
+                } => util_db_inspect_cmd(&folder, rounds.as_deref(), format)?,
+                Util::Resync { control, id, from } => util_resync_cmd(&control, id, from).await?,
+                Util::LogLevel { control, id, level } => {
+                    util_log_level_cmd(&control, id, level).await?;
+                }
+                Util::Queue { control, id } => util_queue_cmd(&control, id).await?,
+                Util::StoreStats { control, id, json } => {
+                    util_store_stats_cmd(&control, id, json).await?;
+                }
+                Util::Backup {
+                    control,
+                    id,
+                    passphrase,
+                    out,
+                } => util_backup_cmd(&control, id, passphrase.as_ref(), &out).await?,
+                Util::Restore {
+                    folder,
+                    passphrase,
+                    input,
+                } => util_restore_cmd(&folder, passphrase.as_ref(), &input)?,
+                Util::DebugDump { control, out } => {
+                    util_debug_dump_cmd(&control, out.as_deref()).await?;
+                }
+            },
+            Cmd::Get(get) => match get {
+                Get::Randomness {
+                    node,
+                    id,
+                    chain_hash,
+                    format,
+                    round,
+                } => get_randomness_cmd(&node, id, chain_hash.as_deref(), round, format).await?,
             },
         }
 
@@ -215,7 +1257,8 @@ impl Cli {
     }
 }
 
-async fn keygen_cmd(config: KeyGenConfig) -> Result<()> {
+async fn keygen_cmd(mut config: KeyGenConfig) -> Result<()> {
+    config.id = beacon::canonical_beacon_id(&config.id)?.to_string();
     println!("Generating private / public key pair");
     match config.scheme.as_str() {
         DefaultScheme::ID => keygen::<DefaultScheme>(&config)?,
@@ -227,7 +1270,7 @@ async fn keygen_cmd(config: KeyGenConfig) -> Result<()> {
     // If keys were generated successfully, daemon needs to load them.
     match control::ControlClient::new(&config.control).await {
         Ok(mut client) => {
-            client.load_beacon(config.id).await?;
+            client.load_beacon(config.id, None).await?;
         }
         Err(_) => eprintln!("Keys couldn't be loaded on drand daemon. If it is not running, these new keys will be loaded on startup"),
     }
@@ -238,6 +1281,12 @@ async fn keygen_cmd(config: KeyGenConfig) -> Result<()> {
 /// Generic helper for [`keygen_cmd`]
 fn keygen<S: Scheme>(config: &KeyGenConfig) -> Result<()> {
     let address = Address::precheck(&config.address)?;
+    #[cfg(any(test, feature = "insecure"))]
+    let pair = match &config.insecure_seed {
+        Some(seed) => Pair::<S>::from_insecure_seed(address, seed.as_bytes())?,
+        None => Pair::<S>::generate(address)?,
+    };
+    #[cfg(not(any(test, feature = "insecure")))]
     let pair = Pair::<S>::generate(address)?;
     let store = FileStore::new_checked(&config.folder, &config.id)?;
     store.save_key_pair(&pair)?;
@@ -248,12 +1297,79 @@ fn keygen<S: Scheme>(config: &KeyGenConfig) -> Result<()> {
 async fn start_cmd(config: Config) -> Result<()> {
     let private_listen = Address::precheck(&config.private_listen)?;
     let control_port = config.control.clone();
-    let daemon = Daemon::new(config)?;
+    let archive = config.archive.archive_config()?;
+    let http = config.http.http_config()?;
+    let backup = config.backup.backup_config()?;
+    let pprof = config.pprof.pprof_listen()?;
+    let webhooks = config.webhooks.webhook_config()?;
+    if cfg!(not(unix)) && config.ipc_socket.is_some() {
+        bail!("--ipc-socket: Unix sockets are not supported on this platform");
+    }
+    let ipc_socket = config.ipc_socket.clone();
+    for (id, level) in &config.log_level {
+        crate::log::set_beacon_level(beacon::canonical_beacon_id(id)?, Some(*level));
+    }
+    crate::log::set_bulk_log_rate(config.bulk_log_rate);
+    let daemon = Daemon::new(config, Arc::new(SystemClock))?;
+    crash::install(&daemon);
+    // Start archiver of finalized beacons
+    if let Some(archive) = archive {
+        daemon.tracker.spawn(archiver::run(daemon.clone(), archive));
+    }
+    // Start delivery of events to webhooks
+    if let Some(webhooks) = webhooks {
+        daemon
+            .tracker
+            .spawn(webhooks::run(daemon.clone(), webhooks));
+    }
+    // Start scheduler of backups
+    if let Some(backup) = backup {
+        daemon.tracker.spawn(backups::run(daemon.clone(), backup));
+    }
     // Start control server
     let control = daemon.tracker.spawn({
         let daemon = daemon.clone();
         control::start_server::<ControlListener>(daemon, control_port)
     });
+    // Start HTTP API of public beacons
+    if let Some((listen, http)) = http {
+        daemon.tracker.spawn(http_api::start_server(
+            PublicHandler::new(daemon.clone()),
+            listen,
+            http,
+            daemon.token.clone(),
+            daemon.tracker.clone(),
+        ));
+    }
+    // Start profiling endpoints
+    #[cfg(feature = "pprof")]
+    if let Some(listen) = pprof {
+        daemon.tracker.spawn(crate::net::pprof::start_server(
+            listen,
+            daemon.token.clone(),
+            daemon.tracker.clone(),
+        ));
+    }
+    #[cfg(not(feature = "pprof"))]
+    let _ = pprof;
+    // Start broadcast of chain heads to local clients
+    #[cfg(unix)]
+    if let Some(path) = ipc_socket {
+        daemon.tracker.spawn(crate::net::ipc::start_server(
+            path.into(),
+            daemon.beacons().events().clone(),
+            daemon.token.clone(),
+            daemon.tracker.clone(),
+        ));
+    }
+    #[cfg(not(unix))]
+    let _ = ipc_socket;
+    // Start QUIC server for partial beacons, UDP port is shared with node address.
+    #[cfg(feature = "quic")]
+    daemon.tracker.spawn(crate::net::quic::start_server(
+        daemon.clone(),
+        private_listen.clone(),
+    ));
     // Start node server
     let node = daemon.tracker.spawn({
         let daemon = daemon.clone();
@@ -268,9 +1384,25 @@ async fn start_cmd(config: Config) -> Result<()> {
     Ok(())
 }
 
-async fn load_beacon_cmd(control_port: &str, beacon_id: String) -> Result<()> {
+async fn load_beacon_cmd(
+    control_port: &str,
+    beacon_id: String,
+    folder: Option<String>,
+) -> Result<()> {
+    // Folder is copied by the daemon, relative path is resolved against current folder.
+    let folder = folder
+        .map(|folder| std::path::absolute(folder).map(|path| path.display().to_string()))
+        .transpose()?;
+    let mut client = ControlClient::new(control_port).await?;
+    client.load_beacon(beacon_id, folder).await?;
+
+    Ok(())
+}
+
+async fn unload_beacon_cmd(control_port: &str, beacon_id: String) -> Result<()> {
     let mut client = ControlClient::new(control_port).await?;
-    client.load_beacon(beacon_id).await?;
+    client.unload_beacon(beacon_id.clone()).await?;
+    println!("beacon process [{beacon_id}] unloaded, daemon is running.");
 
     Ok(())
 }
@@ -298,28 +1430,602 @@ async fn stop_cmd(control_port: &str, beacon_id: Option<String>) -> anyhow::Resu
     Ok(())
 }
 
-async fn sync_cmd(config: SyncConfig) -> Result<()> {
+async fn sync_cmd(mut config: SyncConfig) -> Result<()> {
+    if let Some(ref path) = config.chain_info {
+        let info = read_chain_info(path)?;
+        let hash = hex::encode(&info.hash);
+        if !config.chain_hash.is_empty() && config.chain_hash != hash {
+            bail!(
+                "chain hash {} does not match chain info {path} of chain {hash}",
+                config.chain_hash
+            );
+        }
+        config.chain_hash = hash;
+    }
     let mut client = ControlClient::new(&config.control).await?;
     client.sync(config).await?;
 
     Ok(())
 }
 
-async fn dkg_join_cmd(
-    control_port: &str,
-    beacon_id: String,
-    groupfile_path: Option<&str>,
-) -> Result<()> {
-    let mut client = DkgControlClient::new(control_port).await?;
-    client.dkg_join(beacon_id, groupfile_path).await?;
-    println!("Joined the DKG successfully!");
+async fn relay_cmd(config: RelayArgs) -> Result<()> {
+    let Some((listen, http)) = config.http.http_config()? else {
+        bail!("relay: --public-listen is required");
+    };
+    let mut chains = Vec::with_capacity(config.chains.len());
+    let mut ids = vec![];
+    for (id, chain_hash) in &config.chains {
+        let id = beacon::canonical_beacon_id(id)?;
+        if ids.contains(&id) {
+            bail!("relay: beacon id {id} is given twice");
+        }
+        ids.push(id);
+        let store_path = PathBuf::from(&config.folder).join("relay").join(id);
+        std::fs::create_dir_all(&store_path)?;
+        let request = StartSyncRequest {
+            nodes: config.sync_nodes.clone(),
+            up_to: 0,
+            metadata: Some(Metadata::with_chain_hash(id, chain_hash)?),
+            verify_mode: config.verify as u32,
+            spot_check_every: config.spot_check_every,
+            checkpoint_round: 0,
+            checkpoint_signature: vec![],
+            archive_url: String::new(),
+            chain_info: String::new(),
+        };
+        chains.push(RelayChain {
+            store_path,
+            request,
+        });
+    }
+    let relay = RelayConfig {
+        chains,
+        listen,
+        http,
+        audit_every: config.audit_every,
+        sync_memory: SyncMemory::new(config.sync_buffer_mb << 20),
+    };
+    let tracker = TaskTracker::new();
+    crate::chain::run_relay(relay, CancellationToken::new(), tracker.clone()).await?;
+    tracker.close();
+    tracker.wait().await;
 
     Ok(())
 }
 
-async fn dkg_accept_cmd(control_port: &str, beacon_id: String) -> Result<()> {
+async fn dkg_join_cmd(
+    control_port: &str,
+    beacon_id: String,
+    groupfile_path: Option<&str>,
+    wait: bool,
+    follow: bool,
+) -> Result<()> {
+    let followed = match (follow, groupfile_path) {
+        (true, Some(path)) => {
+            let addresses = group_addresses(&std::fs::read_to_string(path)?)?;
+            let info = member_chain_info(&addresses, &beacon_id).await?;
+            let now = time::current_round(time::time_now().as_secs(), info.period, genesis(&info)?);
+            // Bulk of the chain is synced before joining, so the DKG is not held up.
+            follow_up_to(control_port, &beacon_id, &info, &addresses, now).await?;
+            Some((info, addresses))
+        }
+        _ => None,
+    };
     let mut client = DkgControlClient::new(control_port).await?;
-    client.dkg_accept(beacon_id).await?;
+    client.dkg_join(beacon_id.clone(), groupfile_path).await?;
+    println!("Joined the DKG successfully!");
+
+    if let Some((info, addresses)) = followed {
+        // Transition round is set once the DKG is completed, the previous group produces rounds
+        // until then and the chain is followed up to the last one.
+        dkg_wait(control_port, &mut client, &beacon_id).await?;
+        let group = ControlClient::new(control_port)
+            .await?
+            .group_file(beacon_id.clone())
+            .await?;
+        let up_to = last_round_before(group.transition_time, info.period, genesis(&info)?);
+        follow_up_to(control_port, &beacon_id, &info, &addresses, up_to).await?;
+    } else if wait {
+        dkg_wait(control_port, &mut client, &beacon_id).await?;
+    }
+
+    Ok(())
+}
+
+/// Returns chain info of the first reachable member.
+async fn member_chain_info(addresses: &[Address], beacon_id: &str) -> Result<ChainInfoPacket> {
+    for addr in addresses {
+        let fetched = match PublicClient::new(addr).await {
+            Ok(mut client) => client.chain_info(beacon_id.to_string()).await,
+            Err(err) => Err(err),
+        };
+        match fetched {
+            Ok(info) => return Ok(info),
+            Err(err) => eprintln!("failed to get chain info from {addr}: {err}"),
+        }
+    }
+
+    bail!("none of the group members is reachable to get chain info")
+}
+
+fn genesis(info: &ChainInfoPacket) -> Result<u64> {
+    u64::try_from(info.genesis_time)
+        .map_err(|_| anyhow!("invalid genesis time {}", info.genesis_time))
+}
+
+/// Returns the last round produced by the previous group before transition at `transition_time`.
+fn last_round_before(transition_time: u64, period: u32, genesis: u64) -> u64 {
+    time::current_round(transition_time, period, genesis).saturating_sub(1)
+}
+
+/// Syncs the chain up to round `up_to` from the group members, the sync request is served by the
+/// local daemon which tries all members in turn.
+async fn follow_up_to(
+    control_port: &str,
+    beacon_id: &str,
+    info: &ChainInfoPacket,
+    addresses: &[Address],
+    up_to: u64,
+) -> Result<()> {
+    println!("Following the chain up to round {up_to}");
+    let mut client = ControlClient::new(control_port).await?;
+    client
+        .sync(SyncConfig {
+            control: control_port.to_string(),
+            chain_hash: hex::encode(&info.hash),
+            sync_nodes: addresses.iter().map(ToString::to_string).collect(),
+            up_to,
+            id: beacon_id.to_string(),
+            follow: false,
+            verify: VerifyMode::Full,
+            spot_check_every: crate::chain::DEFAULT_SPOT_CHECK_EVERY,
+            checkpoint_round: 0,
+            checkpoint_sig: None,
+            from_archive: None,
+            chain_info: None,
+        })
+        .await?;
+
+    let stored = client
+        .status(beacon_id.to_string())
+        .await?
+        .latest_stored_round;
+    if stored < up_to {
+        bail!("chain is synced up to round {stored}, expected at least {up_to}");
+    }
+
+    Ok(())
+}
+
+/// Returns addresses of all nodes listed in the group file.
+fn group_addresses(group: &str) -> Result<Vec<Address>> {
+    fn addresses<S: Scheme>(doc: &toml_edit::DocumentMut) -> Result<Vec<Address>> {
+        let group: Group<S> =
+            Toml::toml_decode(doc).ok_or_else(|| anyhow!("invalid group file"))?;
+
+        Ok(group
+            .nodes()
+            .iter()
+            .map(|node| node.public().address.clone())
+            .collect())
+    }
+    let doc = group.parse::<toml_edit::DocumentMut>()?;
+
+    match doc.get("SchemeID").and_then(|id| id.as_str()) {
+        Some(DefaultScheme::ID) => addresses::<DefaultScheme>(&doc),
+        Some(UnchainedScheme::ID) => addresses::<UnchainedScheme>(&doc),
+        Some(SigsOnG1Scheme::ID) => addresses::<SigsOnG1Scheme>(&doc),
+        _ => bail!("group file has unknown scheme"),
+    }
+}
+
+async fn dkg_accept_cmd(control_port: &str, beacon_id: String, wait: bool) -> Result<()> {
+    let mut client = DkgControlClient::new(control_port).await?;
+    client.dkg_accept(beacon_id.clone()).await?;
+
+    if wait {
+        dkg_wait(control_port, &mut client, &beacon_id).await?;
+    }
+
+    Ok(())
+}
+
+/// Returns deadlines of the DKG state with time left, empty if nothing is due.
+fn dkg_deadlines(entry: &DkgEntry, status: Status, transition_time: u64) -> String {
+    let secs = |t: Option<&prost_types::Timestamp>| {
+        t.and_then(|t| u64::try_from(t.seconds).ok())
+            .unwrap_or_default()
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    status
+        .deadlines(
+            entry.epoch,
+            secs(entry.timeout.as_ref()),
+            secs(entry.genesis_time.as_ref()),
+            transition_time,
+        )
+        .into_iter()
+        .map(|(what, at)| format!("{what} by {at} ({})", countdown(now, at)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Returns time of transition to the group of a reshare, zero for the first epoch or if the group
+/// is not available.
+///
+/// Until the reshare is completed the time is not fixed, the latest possible transition is returned:
+/// the one of DKG finished at key sharing timeout, see `process_dkg_output`.
+async fn reshare_transition(
+    control_port: &str,
+    beacon_id: &str,
+    entry: &DkgEntry,
+    status: Status,
+) -> u64 {
+    if entry.epoch < 2 {
+        return 0;
+    }
+    let group = match ControlClient::new(control_port).await {
+        Ok(mut client) => client.group_file(beacon_id.to_string()).await,
+        Err(err) => Err(err),
+    };
+    let Ok(group) = group else {
+        return 0;
+    };
+    if status == Status::Complete {
+        return group.transition_time;
+    }
+    let timeout = entry
+        .timeout
+        .as_ref()
+        .and_then(|t| u64::try_from(t.seconds).ok())
+        .unwrap_or_default();
+    let round = time::current_round(timeout, group.period, group.genesis_time);
+
+    time::time_of_round(
+        group.period,
+        group.genesis_time,
+        round + time::ROUNDS_UNTIL_TRANSITION,
+    )
+}
+
+async fn dkg_status_cmd(control_port: &str, beacon_id: &str) -> Result<()> {
+    let mut client = DkgControlClient::new(control_port).await?;
+    let response = client.dkg_status(beacon_id).await?;
+    let Some(current) = response.current else {
+        bail!("dkg status: current state is missing");
+    };
+    let status = Status::try_from(current.state)?;
+
+    println!(
+        "DKG epoch {}: {status}\nThreshold: {}\nLeader: {}\nRemaining: {}, joining: {}, leaving: {}\nAccepted: {}, rejected: {}",
+        current.epoch,
+        current.threshold,
+        current.leader.as_ref().map_or("", |leader| leader.address.as_str()),
+        current.remaining.len(),
+        current.joining.len(),
+        current.leaving.len(),
+        current.acceptors.len(),
+        current.rejectors.len(),
+    );
+    let transition = reshare_transition(control_port, beacon_id, &current, status).await;
+    let deadlines = dkg_deadlines(&current, status, transition);
+    if !deadlines.is_empty() {
+        println!("Deadlines: {deadlines}");
+    }
+    if !response.identity_changes.is_empty() {
+        println!("Identity changes:");
+    }
+    for change in &response.identity_changes {
+        println!(
+            "  epoch {}: {} of {} changed {} -> {}",
+            change.epoch, change.kind, change.address, change.previous, change.current
+        );
+    }
+
+    Ok(())
+}
+
+/// Polls DKG status and prints transitions until the DKG is completed or reaches a terminal state.
+async fn dkg_wait(
+    control_port: &str,
+    client: &mut DkgControlClient,
+    beacon_id: &str,
+) -> Result<()> {
+    use std::io::Write;
+
+    let mut last: Option<(u32, Status)> = None;
+    let mut transition = 0;
+    // Countdown is refreshed in place until the next transition.
+    let mut counting = false;
+    loop {
+        let response = client.dkg_status(beacon_id).await?;
+        let Some(current) = response.current else {
+            bail!("dkg status: current state is missing");
+        };
+        let status = Status::try_from(current.state)?;
+
+        if last != Some((current.epoch, status)) {
+            if counting {
+                println!();
+                counting = false;
+            }
+            println!("DKG epoch {}: {status}", current.epoch);
+            last = Some((current.epoch, status));
+            transition = reshare_transition(control_port, beacon_id, &current, status).await;
+        }
+        if status == Status::Complete {
+            println!("DKG finished successfully!");
+            return Ok(());
+        }
+        if status.is_terminal() {
+            bail!("DKG did not complete, status: {status}");
+        }
+        let deadlines = dkg_deadlines(&current, status, transition);
+        if !deadlines.is_empty() {
+            print!("\r{deadlines}\x1b[K");
+            std::io::stdout().flush()?;
+            counting = true;
+        }
+
+        tokio::time::sleep(DKG_WAIT_POLL).await;
+    }
+}
+
+fn dkg_generate_proposal_cmd(
+    joiners: &[String],
+    remainers: &[String],
+    leavers: &[String],
+    out: &str,
+    threshold: Option<usize>,
+    previous_threshold: Option<usize>,
+) -> Result<()> {
+    let (proposal, scheme) = ProposalFile::from_public_files(joiners, remainers, leavers)?;
+    let threshold = proposal.check_terms(threshold, previous_threshold)?;
+    let Some(doc) = proposal.toml_encode() else {
+        bail!("generate-proposal: failed to encode proposal");
+    };
+    // File content is kept byte-compatible with golang proposals, summary is printed only.
+    std::fs::write(out, doc.to_string())?;
+
+    print!("{}", proposal.header(&scheme, threshold));
+    println!("Proposal is written to {out}");
+
+    Ok(())
+}
+
+async fn dkg_refresh_cmd(
+    control_port: &str,
+    beacon_id: String,
+    node: &str,
+    threshold: Option<u32>,
+    timeout: Duration,
+    execute_after: Option<Duration>,
+    every: Option<Duration>,
+) -> Result<()> {
+    let peer = Address::precheck(node)?;
+    let beacon_id = beacon::canonical_beacon_id(&beacon_id)?.to_string();
+    loop {
+        let group = ProtocolClient::new(&peer)
+            .await?
+            .group_for_epoch(0, beacon_id.clone())
+            .await?;
+        let timeout = prost_types::Timestamp::from(std::time::SystemTime::now() + timeout);
+        let options = proposal::refresh_options(&group, threshold, timeout)?;
+        let mut client = DkgControlClient::new(control_port).await?;
+        client.dkg_reshare(beacon_id.clone(), options).await?;
+        println!(
+            "Share refresh of {} nodes is proposed for beacon id {beacon_id}",
+            group.nodes.len()
+        );
+
+        if let Some(delay) = execute_after {
+            tokio::time::sleep(delay).await;
+            client.dkg_execute(beacon_id.clone()).await?;
+            println!("Share refresh is executed");
+        }
+        let Some(every) = every else {
+            return Ok(());
+        };
+        tokio::time::sleep(every).await;
+    }
+}
+
+async fn dkg_leave_cmd(
+    control_port: &str,
+    folder: &str,
+    beacon_id: &str,
+    node: Option<&str>,
+    backup: Option<&str>,
+    passphrase: Option<&SecretSource>,
+) -> Result<()> {
+    let (_, stores) = FileStore::read_multibeacon_folder(folder)?;
+    let fs = stores
+        .into_iter()
+        .find(|fs| fs.get_beacon_id() == Some(beacon_id))
+        .ok_or(FileStoreError::BeaconNotFound)?;
+    let public_file = fs.public_id_file();
+    let identity: toml_edit::DocumentMut = std::fs::read_to_string(&public_file)?.parse()?;
+    let Some(key) = identity
+        .get("Key")
+        .and_then(|key| key.as_str())
+        .and_then(|key| hex::decode(key).ok())
+    else {
+        bail!(
+            "dkg leave: invalid public key file {}",
+            public_file.display()
+        );
+    };
+    let info = ControlClient::new(control_port)
+        .await?
+        .chain_info(beacon_id.to_string())
+        .await?;
+
+    // Other members report the notice to their operators, the leader includes this node as a leaver.
+    match fs.load_key_pair_toml()?.get_scheme_id() {
+        Some(DefaultScheme::ID) => announce_leave::<DefaultScheme>(&fs, beacon_id).await?,
+        Some(UnchainedScheme::ID) => announce_leave::<UnchainedScheme>(&fs, beacon_id).await?,
+        Some(SigsOnG1Scheme::ID) => announce_leave::<SigsOnG1Scheme>(&fs, beacon_id).await?,
+        _ => bail!("dkg leave: {}", FileStoreError::InvalidPairSchemes),
+    }
+
+    // Wait until the leader executes a proposal where this node is leaving.
+    let mut client = DkgControlClient::new(control_port).await?;
+    let mut last: Option<(u32, Status)> = None;
+    let leader = loop {
+        let response = client.dkg_status(beacon_id).await?;
+        let Some(current) = response.current else {
+            bail!("dkg status: current state is missing");
+        };
+        let status = Status::try_from(current.state)?;
+        let leaving = current.leaving.iter().any(|p| p.key == key);
+
+        if last != Some((current.epoch, status)) {
+            last = Some((current.epoch, status));
+            let epoch = current.epoch;
+            match status {
+                Status::Left if leaving => println!("DKG epoch {epoch}: reshare is executed"),
+                Status::Proposed if leaving => println!("DKG epoch {epoch}: leaving is proposed"),
+                _ => println!(
+                    "DKG epoch {epoch}: {status}, waiting for a proposal with --leaver {}",
+                    public_file.display()
+                ),
+            }
+        }
+        if status == Status::Left && leaving {
+            break current.leader.map(|leader| leader.address);
+        }
+
+        tokio::time::sleep(DKG_WAIT_POLL).await;
+    };
+
+    // Remaining members switch to the new group once their DKG is completed.
+    let Some(peer) = node.map(str::to_string).or(leader) else {
+        bail!("dkg leave: leader of the reshare is unknown, set --node");
+    };
+    let peer = Address::precheck(&peer)?;
+    let is_member =
+        |node: &NodePacket| node.public.as_ref().is_some_and(|public| public.key == key);
+    let group = loop {
+        let group = match ProtocolClient::new(&peer).await {
+            Ok(mut client) => client.group_for_epoch(0, beacon_id.to_string()).await,
+            Err(err) => Err(err),
+        };
+        match group {
+            Ok(group) if !group.nodes.iter().any(is_member) => {
+                // The group is not accepted from a peer of another chain.
+                leave::check_group(&group, &info)
+                    .map_err(|err| anyhow!("dkg leave: group from {peer}: {err}"))?;
+                break group;
+            }
+            Ok(_) => (),
+            Err(err) => println!("failed to fetch group from {peer}: {err}"),
+        }
+        tokio::time::sleep(DKG_WAIT_POLL).await;
+    };
+    println!(
+        "new group of {} nodes is completed, transition time: {}",
+        group.nodes.len(),
+        group.transition_time
+    );
+
+    // Partial signatures of this node are required until the transition.
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs();
+    if group.transition_time > now {
+        println!(
+            "waiting {}s for the transition",
+            group.transition_time - now
+        );
+        tokio::time::sleep(Duration::from_secs(group.transition_time - now)).await;
+    }
+
+    if let Some(out) = backup {
+        util_backup_cmd(control_port, Some(beacon_id.to_string()), passphrase, out).await?;
+    }
+    unload_beacon_cmd(control_port, beacon_id.to_string()).await?;
+    println!("node has left the group of beacon id {beacon_id}");
+
+    Ok(())
+}
+
+/// Sends leave notice to other members of the latest group, see [`leave`].
+async fn announce_leave<S: Scheme>(fs: &FileStore, beacon_id: &str) -> Result<()> {
+    let pair: Pair<S> =
+        Toml::toml_decode(&fs.load_key_pair_toml()?).ok_or(FileStoreError::TomlError)?;
+    let group = fs.load_group::<S>()?;
+    for (peer, sent) in leave::announce(&pair, &group, beacon_id, time::time_now()).await? {
+        match sent {
+            Ok(()) => println!("leave notice is accepted by {peer}"),
+            Err(err) => println!("leave notice is not delivered to {peer}: {err}"),
+        }
+    }
+
+    Ok(())
+}
+
+fn dkg_evidence_cmd(
+    folder: &str,
+    beacon_id: &str,
+    epoch: Option<u32>,
+    out: Option<&str>,
+) -> Result<()> {
+    let (_, stores) = FileStore::read_multibeacon_folder(folder)?;
+    let fs = stores
+        .into_iter()
+        .find(|fs| fs.get_beacon_id() == Some(beacon_id))
+        .ok_or(FileStoreError::BeaconNotFound)?;
+
+    let Some(epoch) = epoch else {
+        let epochs = evidence::epochs(&fs.beacon_path)?;
+        if epochs.is_empty() {
+            println!("No DKG evidence recorded for beacon id {beacon_id}");
+        }
+        for epoch in epochs {
+            println!("{epoch}");
+        }
+        return Ok(());
+    };
+    match out {
+        Some(out) => {
+            let mut f = std::io::BufWriter::new(std::fs::File::create(out)?);
+            let exported = evidence::export(&fs.beacon_path, beacon_id, epoch, &mut f)?;
+            println!("{exported} records of epoch {epoch} are written to {out}");
+        }
+        None => {
+            evidence::export(
+                &fs.beacon_path,
+                beacon_id,
+                epoch,
+                &mut std::io::stdout().lock(),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn dkg_reset_cmd(control_port: &str, beacon_id: String, confirm: bool) -> Result<()> {
+    let mut client = ControlClient::new(control_port).await?;
+    // Unconfirmed request is sent as well, the daemon reports whether the state can be reset.
+    let response = client
+        .dkg_reset(beacon_id.clone(), confirm)
+        .await
+        .map_err(|err| anyhow!("dkg reset: {err}"))?;
+    println!(
+        "DKG state of beacon id {beacon_id} is reset, wiped status: {}, current epoch: {}\nBackup: {}",
+        if response.wiped_status.is_empty() {
+            "undecodable"
+        } else {
+            &response.wiped_status
+        },
+        response.epoch,
+        response.backup,
+    );
 
     Ok(())
 }
@@ -336,13 +2042,162 @@ async fn status_cmd(control_port: &str, beacon_id: String) -> Result<()> {
     let mut client = ControlClient::new(control_port).await?;
     let status = client.status(beacon_id.clone()).await?;
     println!(
-        "Beacon ID: {beacon_id}\nLatest stored round: {}",
-        status.latest_stored_round
+        "Beacon ID: {beacon_id}\nLatest stored round: {}\nResync: started {}, rounds fetched {}, invalid beacons {}, peer failures {}\nClock skew: {}ms{}\nRound delays: partial p50 {}ms p99 {}ms, aggregation p50 {}ms p99 {}ms, threshold p50 {}ms p99 {}ms",
+        status.latest_stored_round,
+        status.resync_started,
+        status.resync_rounds_fetched,
+        status.resync_invalid_beacons,
+        status.resync_peer_failures,
+        status.clock_skew_ms,
+        if status.clock_skewed { " (WARNING: exceeds allowed threshold)" } else { "" },
+        status.partial_delay_p50_ms,
+        status.partial_delay_p99_ms,
+        status.aggregation_delay_p50_ms,
+        status.aggregation_delay_p99_ms,
+        status.threshold_delay_p50_ms,
+        status.threshold_delay_p99_ms,
     );
+    if status.callback_timeouts > 0 {
+        println!(
+            "Control timeouts: {}, pending replies {}{}",
+            status.callback_timeouts,
+            status.pending_replies,
+            if status.pending_replies > 0 {
+                " (WARNING: beacon process might be wedged)"
+            } else {
+                ""
+            },
+        );
+    }
+    if status.last_backup_time > 0 {
+        println!("Latest backup: unix time {}", status.last_backup_time);
+    }
+
+    Ok(())
+}
+
+fn show_share_cmd(folder: &str, beacon_id: &str, json: bool, unsafe_print: bool) -> Result<()> {
+    let (_, stores) = FileStore::read_multibeacon_folder(folder)?;
+    let fs = stores
+        .into_iter()
+        .find(|fs| fs.get_beacon_id() == Some(beacon_id))
+        .ok_or(FileStoreError::BeaconNotFound)?;
+
+    let report = match fs.load_key_pair_toml()?.get_scheme_id() {
+        Some(DefaultScheme::ID) => share_report::<DefaultScheme>(&fs, beacon_id, unsafe_print)?,
+        Some(UnchainedScheme::ID) => share_report::<UnchainedScheme>(&fs, beacon_id, unsafe_print)?,
+        Some(SigsOnG1Scheme::ID) => share_report::<SigsOnG1Scheme>(&fs, beacon_id, unsafe_print)?,
+        _ => bail!("show share: {}", FileStoreError::InvalidPairSchemes),
+    };
+    if json {
+        println!("{}", report.to_json());
+    } else {
+        println!("{report}");
+    }
+    if !report.valid {
+        bail!("share is not valid for the group file");
+    }
 
     Ok(())
 }
 
+/// Public information of the stored share, printed by `show share`.
+struct ShareReport {
+    beacon_id: String,
+    scheme: &'static str,
+    index: u32,
+    public_share: String,
+    /// Redacted unless `--unsafe-print` is set.
+    private_share: String,
+    group_member: bool,
+    commits_match_group: bool,
+    valid: bool,
+}
+
+impl ShareReport {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "beacon_id": self.beacon_id,
+            "scheme": self.scheme,
+            "index": self.index,
+            "public_share": self.public_share,
+            "private_share": self.private_share,
+            "group_member": self.group_member,
+            "commits_match_group": self.commits_match_group,
+            "valid": self.valid,
+        })
+    }
+}
+
+impl std::fmt::Display for ShareReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Beacon ID: {}\nScheme: {}\nIndex: {}\nPublic share: {}\nPrivate share: {}\nGroup member: {}\nCommits match group: {}\nValid: {}",
+            self.beacon_id,
+            self.scheme,
+            self.index,
+            self.public_share,
+            self.private_share,
+            self.group_member,
+            self.commits_match_group,
+            self.valid
+        )
+    }
+}
+
+/// Generic helper for [`show_share_cmd`]
+fn share_report<S: Scheme>(
+    fs: &FileStore,
+    beacon_id: &str,
+    unsafe_print: bool,
+) -> Result<ShareReport> {
+    let share = fs.load_share::<S>()?;
+    let group = fs.load_group::<S>()?;
+    let index = share.pri_share.index();
+
+    // Share is valid if signature of the private share is verified by public share evaluated from group polynomial.
+    let poly = PubPoly {
+        commits: group.dist_key.commits().to_vec(),
+    };
+    let public_share = poly.eval(index);
+    let msg = b"drand share check";
+    let sig_share = tbls::sign(&share.pri_share, msg)?;
+    let group_member = group.nodes.iter().any(|n| n.index() == index);
+    let commits_match_group = share.commitments() == group.dist_key.commits();
+    let valid = group_member
+        && commits_match_group
+        && S::bls_verify(&public_share.v, sig_share.value(), msg).is_ok();
+
+    let public_share = hex::encode(
+        public_share
+            .v
+            .serialize()
+            .map_err(PointSerDeError::KeyPoint)?,
+    );
+    let private_share = if unsafe_print {
+        let bytes = share
+            .pri_share
+            .value()
+            .to_bytes_be()
+            .map_err(|_| anyhow!("failed to encode private share"))?;
+        hex::encode(bytes)
+    } else {
+        "<redacted>".into()
+    };
+
+    Ok(ShareReport {
+        beacon_id: beacon_id.into(),
+        scheme: S::ID,
+        index,
+        public_share,
+        private_share,
+        group_member,
+        commits_match_group,
+        valid,
+    })
+}
+
 async fn util_check_cmd(beacon_id: Option<&str>, addresses: Vec<String>) -> Result<()> {
     let peers = addresses
         .iter()
@@ -375,6 +2230,551 @@ async fn util_check_cmd(beacon_id: Option<&str>, addresses: Vec<String>) -> Resu
     Ok(())
 }
 
+async fn util_ping_cmd(address: &str, count: u32) -> Result<()> {
+    let peer = Address::precheck(address)?;
+    let mut client = ProtocolClient::new(&peer).await?;
+
+    let mut rtts = Vec::with_capacity(count as usize);
+    let mut last = None;
+    for seq in 1..=count.max(1) {
+        match client.ping().await {
+            Ok((response, rtt)) => {
+                println!(
+                    "ping {peer}: seq={seq} time={:.2}ms",
+                    rtt.as_secs_f64() * 1000.0
+                );
+                rtts.push(rtt);
+                last = Some(response);
+            }
+            Err(err) => println!("ping {peer}: seq={seq} error: {err}"),
+        }
+    }
+    let Some(response) = last else {
+        bail!("{peer} did not answer any ping request");
+    };
+
+    let min = rtts.iter().min().copied().unwrap_or_default();
+    let max = rtts.iter().max().copied().unwrap_or_default();
+    let avg = rtts.iter().sum::<Duration>() / u32::try_from(rtts.len())?;
+    println!(
+        "{} answered, rtt min/avg/max = {:.2}/{:.2}/{:.2}ms",
+        rtts.len(),
+        min.as_secs_f64() * 1000.0,
+        avg.as_secs_f64() * 1000.0,
+        max.as_secs_f64() * 1000.0
+    );
+    if let Some(version) = response.metadata.and_then(|m| m.node_version) {
+        println!("version: {version}");
+    }
+    for beacon in response.beacons {
+        println!(
+            "beacon id: {}, latest stored round: {}",
+            beacon.beacon_id, beacon.round
+        );
+    }
+
+    Ok(())
+}
+
+async fn util_remote_status_cmd(
+    control_port: &str,
+    beacon_id: String,
+    addresses: Vec<String>,
+) -> Result<()> {
+    let mut client = ControlClient::new(control_port).await?;
+    let response = client.remote_status(beacon_id, addresses.clone()).await?;
+    let mut statuses: Vec<_> = response.statuses.into_iter().collect();
+    statuses.sort_by(|a, b| a.0.cmp(&b.0));
+
+    println!(
+        "{:<32} {:>10} {:>14} {:>11}",
+        "ADDRESS", "VERSION", "LATEST ROUND", "CLOCK SKEW"
+    );
+    for (address, status) in &statuses {
+        let version = status
+            .metadata
+            .as_ref()
+            .and_then(|m| m.node_version.as_ref())
+            .map_or_else(|| "-".into(), ToString::to_string);
+        println!(
+            "{address:<32} {version:>10} {:>14} {:>9}ms{}",
+            status.latest_stored_round,
+            status.clock_skew_ms,
+            if status.clock_skewed { " !" } else { "" }
+        );
+    }
+    for address in addresses
+        .iter()
+        .filter(|a| !statuses.iter().any(|(answered, _)| answered == *a))
+    {
+        println!("{address:<32} no answer");
+    }
+    if statuses.is_empty() && addresses.is_empty() {
+        println!("no group member answered");
+    }
+
+    Ok(())
+}
+
+async fn util_bandwidth_cmd(control_port: &str) -> Result<()> {
+    let mut client = ControlClient::new(control_port).await?;
+    let response = client.peer_bandwidth().await?;
+
+    println!(
+        "{:<40} {:>16} {:>16}",
+        "PEER", "BYTES SENT", "BYTES RECEIVED"
+    );
+    for peer in &response.peers {
+        println!(
+            "{:<40} {:>16} {:>16}",
+            peer.address, peer.bytes_sent, peer.bytes_received
+        );
+    }
+    if response.peers.is_empty() {
+        println!("no connections accepted yet");
+    }
+
+    Ok(())
+}
+
+async fn util_group_cmd(beacon_id: String, epoch: u32, address: &str) -> Result<()> {
+    let peer = Address::precheck(address)?;
+    let mut client = ProtocolClient::new(&peer).await?;
+    let group = client.group_for_epoch(epoch, beacon_id).await?;
+
+    println!(
+        "threshold: {}\nperiod: {}\ngenesis_time: {}\ntransition_time: {}\npublic_key: {}",
+        group.threshold,
+        group.period,
+        group.genesis_time,
+        group.transition_time,
+        group.dist_key.first().map(hex::encode).unwrap_or_default(),
+    );
+    for node in &group.nodes {
+        println!(
+            "node: index {}, address {}",
+            node.index, node.public.address
+        );
+    }
+
+    Ok(())
+}
+
+async fn get_randomness_cmd(
+    node: &str,
+    beacon_id: String,
+    chain_hash: Option<&str>,
+    round: u64,
+    format: BeaconFormat,
+) -> Result<()> {
+    use std::io::Write;
+
+    let peer = Address::precheck(node)?;
+    let mut client = PublicClient::new(&peer).await?;
+    let info = client.chain_info(beacon_id.clone()).await?;
+    let hash = crate::client::chain_hash(&info, &beacon_id);
+    if hash[..] != info.hash[..] {
+        bail!(
+            "chain info of {peer} is inconsistent: hash {} is reported, computed {}",
+            hex::encode(&info.hash),
+            hex::encode(hash)
+        );
+    }
+    if let Some(pinned) = chain_hash {
+        if !pinned.eq_ignore_ascii_case(&hex::encode(hash)) {
+            bail!(
+                "{peer} serves chain {}, expected {pinned}",
+                hex::encode(hash)
+            );
+        }
+    }
+
+    let beacon = client.public_rand(round, beacon_id).await?;
+    if round != 0 && beacon.round != round {
+        bail!("received round {}, requested {round}", beacon.round);
+    }
+    let chained = verify::is_chained(&info.scheme_id).map_err(|err| anyhow!("{err}"))?;
+    let previous = if chained {
+        &beacon.previous_signature[..]
+    } else {
+        &[]
+    };
+    if !verify::verify_serialized(
+        &info.scheme_id,
+        &info.public_key,
+        previous,
+        beacon.round,
+        &beacon.signature,
+    )
+    .map_err(|err| anyhow!("{err}"))?
+    {
+        bail!(
+            "invalid beacon: signature of round {} is not verified by chain {}",
+            beacon.round,
+            hex::encode(hash)
+        );
+    }
+    let randomness = verify::derive_randomness(&beacon.signature, &info.scheme_id)
+        .map_err(|err| anyhow!("{err}"))?;
+    // Randomness reported by the node is never used, but should not differ.
+    if !beacon.randomness.is_empty() && beacon.randomness[..] != randomness[..] {
+        bail!(
+            "{peer} reported randomness {} of round {}, derived {}",
+            hex::encode(&beacon.randomness),
+            beacon.round,
+            hex::encode(randomness)
+        );
+    }
+
+    let mut out = std::io::stdout().lock();
+    out.write_all(&format.encode(beacon.round, &randomness, &beacon.signature, previous))?;
+    if format.is_text() {
+        out.write_all(b"\n")?;
+    }
+
+    Ok(())
+}
+
+fn util_group_diff_cmd(old: &str, new: &str, json: bool) -> Result<()> {
+    let diff = diff::diff_files(old.as_ref(), new.as_ref())?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff.to_json())?);
+    } else if diff.is_empty() {
+        println!("groups are identical");
+    } else {
+        println!("{diff}");
+    }
+
+    Ok(())
+}
+
+fn util_equivocations_cmd(folder: &str, beacon_id: &str, json: bool) -> Result<()> {
+    // Logs of a node and of a relay sharing the folder are both listed.
+    let mut records = vec![];
+    for dir in [
+        PathBuf::from(folder).join("multibeacon").join(beacon_id),
+        PathBuf::from(folder).join("relay").join(beacon_id),
+    ] {
+        records.extend(equivocation::list(&dir)?);
+    }
+    records.sort_by_key(|r| (r.round, r.time));
+
+    if json {
+        let records: Vec<_> = records.iter().map(Equivocation::to_json).collect();
+        println!("{}", serde_json::to_string_pretty(&records)?);
+    } else if records.is_empty() {
+        println!("no equivocations recorded for beacon id {beacon_id}");
+    } else {
+        for r in &records {
+            println!(
+                "round {} at {}, from {}: stored {}, observed {}",
+                r.round,
+                r.time,
+                r.source,
+                hex::encode(&r.stored_signature),
+                hex::encode(&r.observed_signature)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads chain info JSON as served at `/info` of HTTP relays, the hash must be consistent.
+fn read_chain_info(path: &str) -> Result<ChainInfoPacket> {
+    let info: ChainInfoPacket = serde_json::from_slice(&std::fs::read(path)?)?;
+    let beacon_id = info.metadata.as_ref().map_or("", |m| m.beacon_id.as_str());
+    let hash = crate::client::chain_hash(&info, beacon_id);
+    if hash[..] != info.hash[..] {
+        bail!(
+            "chain info is inconsistent: hash {} is expected, computed {}",
+            hex::encode(&info.hash),
+            hex::encode(hash)
+        );
+    }
+
+    Ok(info)
+}
+
+fn util_verify_beacon_cmd(
+    chain_info: &str,
+    round: u64,
+    signature: &[u8],
+    previous: Option<&[u8]>,
+) -> Result<()> {
+    let info = read_chain_info(chain_info)?;
+    let chained = verify::is_chained(&info.scheme_id).map_err(|err| anyhow!("{err}"))?;
+    let previous = match previous {
+        Some(previous) if chained => previous,
+        None if chained => bail!(
+            "--previous is required by chained scheme {}",
+            info.scheme_id
+        ),
+        _ => &[],
+    };
+    if !verify::verify_serialized(
+        &info.scheme_id,
+        &info.public_key,
+        previous,
+        round,
+        signature,
+    )
+    .map_err(|err| anyhow!("{err}"))?
+    {
+        bail!(
+            "invalid beacon: signature of round {round} is not verified by chain {}",
+            hex::encode(&info.hash)
+        );
+    }
+    println!(
+        "beacon of round {round} is valid for chain {} ({})\nrandomness: {}",
+        hex::encode(&info.hash),
+        info.scheme_id,
+        hex::encode(verify::randomness(signature))
+    );
+
+    Ok(())
+}
+
+async fn util_events_cmd(
+    control_port: &str,
+    beacon_id: Option<String>,
+    follow: bool,
+) -> Result<()> {
+    let mut client = ControlClient::new(control_port).await?;
+    let mut events = client.events(beacon_id).await?;
+
+    while let Some(event) = events.message().await? {
+        println!("{}", event_json(&event));
+        if !follow {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns JSON line of the event printed by `util events`.
+fn event_json(event: &DaemonEvent) -> serde_json::Value {
+    serde_json::json!({
+        "beacon_id": event.beacon_id,
+        "kind": event.kind,
+        "round": event.round,
+        "detail": event.detail,
+        "timestamp_ms": event.timestamp_ms,
+    })
+}
+
+fn util_generate_testnet_cmd(config: &TestnetConfig) -> Result<()> {
+    let files = testnet::generate(config)?;
+    println!(
+        "Generated {} nodes at {}",
+        config.nodes,
+        config.out.display()
+    );
+    for file in files {
+        println!("  {}", file.display());
+    }
+    if !config.go_leader {
+        println!("Warning: {}", testnet::LEADER_UNSUPPORTED);
+    }
+    if config.scripts {
+        println!("Run start.sh, then dkg.sh once all nodes are up");
+    } else {
+        println!("Run `docker compose up -d`, then dkg.sh once all nodes are up");
+    }
+
+    Ok(())
+}
+
+fn util_check_migration_cmd(
+    folder: &str,
+    beacon_id: Option<&str>,
+    epoch: Option<u32>,
+) -> Result<()> {
+    let reports = migration::check_folder(folder, beacon_id, epoch)?;
+    for report in &reports {
+        println!("{report}");
+    }
+    if !reports.iter().all(migration::BeaconReport::is_ready) {
+        bail!("migration check failed");
+    }
+    println!("all beacon ids are ready to be loaded");
+
+    Ok(())
+}
+
+fn util_migrate_cmd(folder: &str, beacon_id: &str, epoch: u32) -> Result<()> {
+    let report = migration::migrate_folder(folder, beacon_id, epoch)?;
+    println!("{report}");
+    if !report.is_ready() {
+        bail!("migration failed");
+    }
+    println!("beacon id {beacon_id} is ready to be loaded");
+
+    Ok(())
+}
+
+async fn util_resync_cmd(control_port: &str, beacon_id: String, from: u64) -> Result<()> {
+    let mut client = ControlClient::new(control_port).await?;
+    let up_to = client.resync(beacon_id, from).await?;
+    println!("resync started from round {from} up to {up_to}");
+
+    Ok(())
+}
+
+async fn util_log_level_cmd(
+    control_port: &str,
+    beacon_id: String,
+    level: Option<LevelFilter>,
+) -> Result<()> {
+    let mut client = ControlClient::new(control_port).await?;
+    let level = client
+        .set_log_level(
+            beacon_id.clone(),
+            level.map(|l| l.to_string()).unwrap_or_default(),
+        )
+        .await?;
+    println!("log level of beacon id {beacon_id}: {level}");
+
+    Ok(())
+}
+
+async fn util_queue_cmd(control_port: &str, beacon_id: Option<String>) -> Result<()> {
+    let mut client = ControlClient::new(control_port).await?;
+    for q in client.queue_status(beacon_id).await? {
+        println!(
+            "{}: queued commands {}, oldest {}ms",
+            q.beacon_id, q.depth, q.oldest_age_ms
+        );
+    }
+
+    Ok(())
+}
+
+async fn util_store_stats_cmd(
+    control_port: &str,
+    beacon_id: Option<String>,
+    json: bool,
+) -> Result<()> {
+    let mut client = ControlClient::new(control_port).await?;
+    let stores = client.store_stats(beacon_id).await?;
+    if json {
+        let stores: Vec<_> = stores
+            .iter()
+            .map(|s| {
+                serde_json::json!({
+                    "beacon_id": s.beacon_id,
+                    "backend": s.backend,
+                    "total_rounds": s.total_rounds,
+                    "first_round": s.first_round,
+                    "last_round": s.last_round,
+                    "missing_rounds": s.missing_rounds,
+                    "disk_bytes": s.disk_bytes,
+                    "wal_bytes": s.wal_bytes,
+                    "free_bytes": s.free_bytes,
+                    "error": s.error,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&stores)?);
+        return Ok(());
+    }
+    for s in stores {
+        if !s.error.is_empty() {
+            println!("{}: {}", s.beacon_id, s.error);
+            continue;
+        }
+        println!(
+            "{}: {} rounds {} - {}, missing {}, {} bytes on disk ({}), compaction: {} bytes in WAL, {} bytes free",
+            s.beacon_id,
+            s.total_rounds,
+            s.first_round,
+            s.last_round,
+            s.missing_rounds,
+            s.disk_bytes,
+            s.backend,
+            s.wal_bytes,
+            s.free_bytes
+        );
+    }
+
+    Ok(())
+}
+
+async fn util_backup_cmd(
+    control_port: &str,
+    beacon_id: Option<String>,
+    passphrase: Option<&SecretSource>,
+    out: &str,
+) -> Result<()> {
+    let passphrase = passphrase
+        .map(|source| source.resolve().map(|p| p.expose().to_string()))
+        .transpose()?;
+    let mut client = ControlClient::new(control_port).await?;
+    let response = client
+        .backup(beacon_id, out.to_string(), passphrase)
+        .await?;
+    println!(
+        "backup of beacon ids [{}] written to {}, files: {}",
+        response.beacon_ids.join(", "),
+        response.path,
+        response.files
+    );
+
+    Ok(())
+}
+
+fn util_restore_cmd(folder: &str, passphrase: Option<&SecretSource>, input: &str) -> Result<()> {
+    let passphrase = passphrase.map(SecretSource::resolve).transpose()?;
+    let manifest = backup::restore(
+        input.as_ref(),
+        folder,
+        passphrase.as_ref().map(Secret::expose),
+    )?;
+    println!(
+        "restored beacon ids [{}] into {folder}, files: {}; chain databases are synced after the daemon start",
+        manifest.beacon_ids.join(", "),
+        manifest.files.len()
+    );
+
+    Ok(())
+}
+
+async fn util_debug_dump_cmd(control_port: &str, out: Option<&str>) -> Result<()> {
+    let mut client = ControlClient::new(control_port).await?;
+    let dump = client.debug_dump().await?;
+    match out {
+        Some(out) => {
+            std::fs::write(out, dump + "\n")?;
+            println!("debug dump written to {out}");
+        }
+        None => println!("{dump}"),
+    }
+
+    Ok(())
+}
+
+fn util_db_inspect_cmd(
+    folder: &str,
+    rounds: Option<&str>,
+    format: Option<BeaconFormat>,
+) -> Result<()> {
+    let folder = std::path::Path::new(folder);
+    match rounds {
+        Some(rounds) => {
+            let ranges = inspect::parse_ranges(rounds)?;
+            let written = inspect::extract(folder, &ranges, format, &mut std::io::stdout().lock())?;
+            if written == 0 {
+                bail!("no beacons stored within {rounds}");
+            }
+        }
+        None => println!("{}", inspect::stats(folder)?),
+    }
+
+    Ok(())
+}
+
 async fn check_identity_address(peer: &Address, beacon_id: String) -> Result<()> {
     let mut client = ProtocolClient::new(peer).await?;
     let resp = client.get_identity(beacon_id).await?;
@@ -399,3 +2799,76 @@ async fn check_identity_address(peer: &Address, beacon_id: String) -> Result<()>
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::toml::tests::toml_samples;
+
+    #[test]
+    fn join_follows_group_members() {
+        let addresses = group_addresses(toml_samples::group()).unwrap();
+        assert_eq!(addresses.len(), 6);
+        assert_eq!(addresses[0].as_str(), "127.0.0.1:36023");
+        assert_eq!(addresses[1].as_str(), "127.0.0.1:44901");
+
+        let unknown = toml_samples::group().replace(
+            "SchemeID = \"pedersen-bls-chained\"",
+            "SchemeID = \"unknown\"",
+        );
+        assert!(group_addresses(&unknown).is_err());
+
+        // Previous group produces rounds up to the one before transition.
+        let (period, genesis) = (3, 1_736_058_215);
+        let transition = time::time_of_round(period, genesis, 100);
+        assert_eq!(last_round_before(transition, period, genesis), 99);
+    }
+
+    #[test]
+    fn share_is_reported() {
+        type S = DefaultScheme;
+        let dir = tempfile::tempdir().unwrap();
+        let fs = FileStore::new_checked(dir.path().to_str().unwrap(), "default").unwrap();
+        std::fs::write(fs.group_file(), toml_samples::group()).unwrap();
+        std::fs::write(fs.private_share_file(), toml_samples::dist_key()).unwrap();
+        let private_share = "675431249f101cf5001b4dd0dd8f25107a2c547b77b0693309731906b12fca98";
+
+        let report = share_report::<S>(&fs, "default", false).unwrap();
+        assert_eq!(report.index, 4);
+        let json = report.to_json();
+        assert_eq!(json["valid"], true);
+        assert_eq!(json["group_member"], true);
+        assert_eq!(json["commits_match_group"], true);
+        assert_eq!(json["private_share"], "<redacted>");
+        assert!(!report.to_string().contains(private_share));
+
+        let report = share_report::<S>(&fs, "default", true).unwrap();
+        assert_eq!(report.to_json()["private_share"], private_share);
+
+        // Share with index out of the group is not valid.
+        let non_member = toml_samples::dist_key().replace("Index = 4", "Index = 7");
+        std::fs::write(fs.private_share_file(), non_member).unwrap();
+        let json = share_report::<S>(&fs, "default", false).unwrap().to_json();
+        assert_eq!(json["group_member"], false);
+        assert_eq!(json["valid"], false);
+    }
+
+    #[test]
+    fn event_is_printed_as_json() {
+        let event = DaemonEvent {
+            beacon_id: "default".into(),
+            kind: "resync_stopped".into(),
+            round: 7,
+            detail: "peer \"a\\b\"\nclosed".into(),
+            timestamp_ms: 1000,
+        };
+        let line = event_json(&event).to_string();
+        assert!(!line.contains('\n'));
+
+        let parsed: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed["kind"], "resync_stopped");
+        assert_eq!(parsed["round"], 7);
+        assert_eq!(parsed["detail"], "peer \"a\\b\"\nclosed");
+        assert_eq!(parsed["timestamp_ms"], 1000);
+    }
+}
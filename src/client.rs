@@ -0,0 +1,261 @@
+//! Verification of chain info packets, with a gRPC client of the public API.
+//!
+//! [`ChainInfoPacket`] implements `Serialize` and `Deserialize` in the JSON schema of the `/info`
+//! endpoint of Go drand nodes and relays, so chain info is loaded from or written to files and
+//! HTTP responses with any serde format.
+//!
+//! This module depends only on the crypto backend and the generated protobuf code, so it builds
+//! without the `daemon` feature. Checks are built on [`crate::verify`], the daemon verifies
+//! received beacons and chain info with the same functions.
+use crate::protobuf::drand::public_client::PublicClient;
+use crate::protobuf::drand::ChainInfoPacket;
+use crate::protobuf::drand::ChainInfoRequest;
+use crate::protobuf::drand::Metadata;
+use crate::protobuf::drand::NodeVersion;
+use crate::protobuf::drand::PublicRandRequest;
+use crate::protobuf::drand::PublicRandResponse;
+
+use crate::verify::verify_beacon;
+
+use energon::drand::traits::DrandScheme as Scheme;
+use energon::points::KeyPoint;
+use energon::traits::Affine;
+use tonic::transport::Channel;
+use tonic::transport::ClientTlsConfig;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ClientError {
+    #[error("invalid uri: {0}")]
+    InvalidUri(String),
+    #[error("transport: {0}")]
+    Transport(#[from] tonic::transport::Error),
+    #[error("request failed: {0}")]
+    Status(#[from] tonic::Status),
+    #[error("scheme expected {expected}, received {received}")]
+    SchemeMismatch { expected: String, received: String },
+    #[error("failed to deserialize group public key")]
+    InvalidKey,
+    #[error("chain hash expected {expected}, computed {computed}")]
+    ChainHashMismatch { expected: String, computed: String },
+    #[error("invalid signature of round {0}")]
+    InvalidSignature(u64),
+    #[error("received round {received}, requested {requested}")]
+    RoundMismatch { requested: u64, received: u64 },
+}
+
+/// Returns canonical hash of chain info packet for given beacon id.
+#[must_use]
+pub fn chain_hash(packet: &ChainInfoPacket, beacon_id: &str) -> [u8; 32] {
+    crate::verify::chain_hash(
+        packet.period,
+        packet.genesis_time,
+        &packet.public_key,
+        &packet.group_hash,
+        beacon_id,
+    )
+}
+
+/// Checks scheme and hash of the chain info, returns the group public key.
+///
+/// # Errors
+///
+/// Returns an error if the scheme differs from `S`, the key is malformed or the hash
+/// of the chain info is not valid.
+pub fn verify_chain_info<S: Scheme>(
+    packet: &ChainInfoPacket,
+    beacon_id: &str,
+) -> Result<KeyPoint<S>, ClientError> {
+    if packet.scheme_id != S::ID {
+        return Err(ClientError::SchemeMismatch {
+            expected: S::ID.to_string(),
+            received: packet.scheme_id.clone(),
+        });
+    }
+    let public_key =
+        Affine::deserialize(&packet.public_key).map_err(|_| ClientError::InvalidKey)?;
+    let computed = chain_hash(packet, beacon_id);
+    if computed != *packet.hash {
+        return Err(ClientError::ChainHashMismatch {
+            expected: hex::encode(&packet.hash),
+            computed: hex::encode(computed),
+        });
+    }
+
+    Ok(public_key)
+}
+
+/// Client of the public API of a drand node, every received beacon is verified.
+pub struct Client<S: Scheme> {
+    inner: PublicClient<Channel>,
+    beacon_id: String,
+    info: ChainInfoPacket,
+    public_key: KeyPoint<S>,
+}
+
+impl<S: Scheme> Client<S> {
+    /// Connects to the node at `uri` (`http://` or `https://`) and fetches chain info of the
+    /// beacon id.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the node is not reachable or the chain info is not valid for `S`,
+    /// see [`verify_chain_info`].
+    pub async fn connect(uri: &str, beacon_id: &str) -> Result<Self, ClientError> {
+        let mut endpoint = Channel::from_shared(uri.to_string())
+            .map_err(|err| ClientError::InvalidUri(format!("{uri}: {err}")))?;
+        if uri.starts_with("https://") {
+            endpoint = endpoint.tls_config(ClientTlsConfig::new().with_native_roots())?;
+        }
+        let mut inner = PublicClient::new(endpoint.connect().await?);
+
+        let request = ChainInfoRequest {
+            metadata: Some(metadata(beacon_id)),
+        };
+        let info = inner.chain_info(request).await?.into_inner();
+        let public_key = verify_chain_info(&info, beacon_id)?;
+
+        Ok(Self {
+            inner,
+            beacon_id: beacon_id.to_string(),
+            info,
+            public_key,
+        })
+    }
+
+    /// Returns verified chain info.
+    #[must_use]
+    pub fn chain_info(&self) -> &ChainInfoPacket {
+        &self.info
+    }
+
+    /// Returns verified beacon of the round, round 0 requests the latest beacon.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the received beacon is not valid.
+    pub async fn public_rand(&mut self, round: u64) -> Result<PublicRandResponse, ClientError> {
+        let request = PublicRandRequest {
+            round,
+            metadata: Some(metadata(&self.beacon_id)),
+        };
+        let beacon = self.inner.public_rand(request).await?.into_inner();
+        if round != 0 && beacon.round != round {
+            return Err(ClientError::RoundMismatch {
+                requested: round,
+                received: beacon.round,
+            });
+        }
+        let valid = Affine::deserialize(&beacon.signature).is_ok_and(|sig| {
+            verify_beacon::<S>(
+                &self.public_key,
+                &beacon.previous_signature,
+                beacon.round,
+                &sig,
+            )
+        });
+        if !valid {
+            return Err(ClientError::InvalidSignature(beacon.round));
+        }
+
+        Ok(beacon)
+    }
+}
+
+/// Metadata of requests, the version is that of compatible Go drand nodes which reject unknown
+/// major versions, see `Metadata::golang_node_version` of the daemon.
+fn metadata(beacon_id: &str) -> Metadata {
+    Metadata {
+        node_version: Some(NodeVersion {
+            major: 2,
+            minor: 1,
+            patch: 2,
+            prerelease: String::new(),
+        }),
+        beacon_id: beacon_id.to_string(),
+        ..Default::default()
+    }
+}
+
+/// Beacon id of chain info JSON without `metadata.beaconID`, relays omit it for the default chain.
+const DEFAULT_BEACON_ID: &str = "default";
+
+/// Chain info JSON as served at `/info`, bytes are hex encoded.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct InfoJson {
+    #[serde(with = "hex_bytes")]
+    public_key: Vec<u8>,
+    period: u32,
+    genesis_time: i64,
+    #[serde(with = "hex_bytes")]
+    hash: Vec<u8>,
+    #[serde(rename = "groupHash", with = "hex_bytes")]
+    group_hash: Vec<u8>,
+    #[serde(rename = "schemeID")]
+    scheme_id: String,
+    #[serde(default)]
+    metadata: Option<InfoMetadata>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct InfoMetadata {
+    #[serde(rename = "beaconID", default)]
+    beacon_id: String,
+}
+
+mod hex_bytes {
+    pub fn serialize<S: serde::Serializer>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+        let hex: String = serde::Deserialize::deserialize(d)?;
+        hex::decode(hex).map_err(serde::de::Error::custom)
+    }
+}
+
+impl serde::Serialize for ChainInfoPacket {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let beacon_id = self
+            .metadata
+            .as_ref()
+            .map_or(DEFAULT_BEACON_ID, |m| m.beacon_id.as_str());
+        InfoJson {
+            public_key: self.public_key.clone(),
+            period: self.period,
+            genesis_time: self.genesis_time,
+            hash: self.hash.clone(),
+            group_hash: self.group_hash.clone(),
+            scheme_id: self.scheme_id.clone(),
+            metadata: Some(InfoMetadata {
+                beacon_id: beacon_id.to_string(),
+            }),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Beacon id is taken from `metadata.beaconID`, the default one if absent. Consistency of the
+/// hash is not checked, see [`verify_chain_info`].
+impl<'de> serde::Deserialize<'de> for ChainInfoPacket {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = InfoJson::deserialize(deserializer)?;
+        let beacon_id = json
+            .metadata
+            .map(|m| m.beacon_id)
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| DEFAULT_BEACON_ID.to_string());
+
+        Ok(Self {
+            public_key: json.public_key,
+            period: json.period,
+            genesis_time: json.genesis_time,
+            hash: json.hash,
+            group_hash: json.group_hash,
+            scheme_id: json.scheme_id,
+            metadata: Some(Metadata {
+                beacon_id,
+                ..Default::default()
+            }),
+        })
+    }
+}
@@ -0,0 +1,199 @@
+//! Optional archiver task uploading finalized beacons to S3-compatible storage.
+//!
+//! Every [`ARCHIVE_INTERVAL`] beacons of each loaded beacon id are read from the chain store
+//! and uploaded as chunk files of [`crate::chain::archive`] format under `{prefix}{beacon_id}/`,
+//! followed by the updated manifest. Only full chunks are uploaded, so chunk objects are never
+//! rewritten. Progress is restored from the manifest in bucket, so the task can be restarted
+//! at any moment. Beacon ids without DKG setup are not archived.
+use super::beacon::BeaconCmd;
+use super::daemon::Daemon;
+use super::multibeacon::BeaconHandlerError;
+
+use crate::chain::archive::ArchiveError;
+use crate::chain::archive::Manifest;
+use crate::chain::archive::MANIFEST;
+use crate::chain::ChainError;
+use crate::chain::StoreError;
+use crate::net::s3::S3Client;
+use crate::net::s3::S3Config;
+use crate::net::s3::S3Error;
+use crate::net::utils::Callback;
+use crate::protobuf::drand::BeaconPacket;
+use crate::protobuf::drand::ChainInfoPacket;
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+/// Interval between checks for new full chunks.
+const ARCHIVE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(thiserror::Error, Debug)]
+pub enum ArchiverError {
+    #[error("s3: {0}")]
+    S3(#[from] S3Error),
+    #[error("archive: {0}")]
+    Archive(#[from] ArchiveError),
+    #[error("beacon id: {0}")]
+    Beacon(#[from] BeaconHandlerError),
+    #[error("chain: {0}")]
+    Chain(#[from] ChainError),
+    #[error("chain store: {0}")]
+    Store(#[from] StoreError),
+    #[error("beacon process is stopped")]
+    Closed,
+    #[error("manifest in bucket belongs to chain {0}")]
+    ChainHashMismatch(String),
+    #[error("chain store stream ended before round {0}")]
+    Incomplete(u64),
+}
+
+#[derive(Debug, Clone)]
+pub struct ArchiveConfig {
+    pub s3: S3Config,
+    /// Prefix of object keys, e.g. `drand/`.
+    pub prefix: String,
+    /// Amount of beacons per chunk file.
+    pub chunk_size: u64,
+}
+
+/// Archives beacons of all loaded beacon ids until the daemon is stopped.
+pub async fn run(daemon: Arc<Daemon>, config: ArchiveConfig) {
+    let client = match S3Client::new(config.s3.clone()) {
+        Ok(client) => client,
+        Err(err) => {
+            error!("archiver: {err}");
+            return;
+        }
+    };
+    info!(
+        "archiver: uploading chunks of {} beacons to {}/{}",
+        config.chunk_size, config.s3.endpoint, config.s3.bucket
+    );
+
+    let mut manifests = HashMap::new();
+    let mut ticker = tokio::time::interval(ARCHIVE_INTERVAL);
+    loop {
+        tokio::select! {
+            () = daemon.token.cancelled() => return,
+            _ = ticker.tick() => (),
+        }
+        let ids: Vec<String> = daemon
+            .beacons()
+            .snapshot()
+            .iter()
+            .map(|h| h.id().as_str().to_string())
+            .collect();
+        for id in ids {
+            if let Err(err) = archive(&daemon, &client, &config, &id, &mut manifests).await {
+                warn!("archiver: [{id}]: {err}");
+            }
+        }
+    }
+}
+
+/// Uploads full chunks of beacon id which are not archived yet.
+async fn archive(
+    daemon: &Daemon,
+    client: &S3Client,
+    config: &ArchiveConfig,
+    id: &str,
+    manifests: &mut HashMap<String, Manifest>,
+) -> Result<(), ArchiverError> {
+    let prefix = format!("{}{id}/", config.prefix);
+    let manifest = match manifests.entry(id.to_string()) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => {
+            let (tx, rx) = Callback::new();
+            daemon.beacons().cmd(BeaconCmd::ChainInfo(tx), id).await?;
+            let info = rx.await.map_err(|_| ArchiverError::Closed)??;
+            entry.insert(load_manifest(client, &prefix, &info, config.chunk_size).await?)
+        }
+    };
+
+    let (tx, rx) = Callback::new();
+    daemon.beacons().cmd(BeaconCmd::Status(tx), id).await?;
+    let latest = rx
+        .await
+        .map_err(|_| ArchiverError::Closed)??
+        .latest_stored_round;
+
+    while manifest.next_round() + manifest.chunk_size <= latest + 1 {
+        let beacons = read_chunk(daemon, id, manifest.next_round(), manifest.chunk_size).await?;
+        let Some((name, data)) = manifest.push(&beacons) else {
+            break;
+        };
+        let uploaded = match client.put(&format!("{prefix}{name}"), data).await {
+            Ok(()) => {
+                client
+                    .put(&format!("{prefix}{MANIFEST}"), manifest.to_json())
+                    .await
+            }
+            Err(err) => Err(err),
+        };
+        if let Err(err) = uploaded {
+            // Chunk is uploaded again on the next attempt.
+            manifest.chunks.pop();
+            return Err(err.into());
+        }
+        debug!("archiver: [{id}]: uploaded {name}");
+    }
+
+    Ok(())
+}
+
+/// Loads manifest from bucket, new manifest is created for empty archive.
+async fn load_manifest(
+    client: &S3Client,
+    prefix: &str,
+    info: &ChainInfoPacket,
+    chunk_size: u64,
+) -> Result<Manifest, ArchiverError> {
+    let id = info
+        .metadata
+        .as_ref()
+        .map(|m| m.beacon_id.as_str())
+        .unwrap_or_default();
+    let Some(data) = client.get(&format!("{prefix}{MANIFEST}")).await? else {
+        return Ok(Manifest::new(id, &info.hash, &info.scheme_id, chunk_size));
+    };
+    let manifest = Manifest::from_json(&data)?;
+    if manifest.chain_hash != hex::encode(&info.hash) {
+        return Err(ArchiverError::ChainHashMismatch(manifest.chain_hash));
+    }
+    info!(
+        "archiver: [{id}]: resuming from round {}",
+        manifest.next_round()
+    );
+
+    Ok(manifest)
+}
+
+/// Reads `size` beacons starting from round `from` from the chain store.
+async fn read_chunk(
+    daemon: &Daemon,
+    id: &str,
+    from: u64,
+    size: u64,
+) -> Result<Vec<BeaconPacket>, ArchiverError> {
+    let (tx, rx) = Callback::new();
+    daemon.beacons().cmd(BeaconCmd::Sync(from, tx), id).await?;
+    let mut stream = rx.await.map_err(|_| ArchiverError::Closed)??;
+
+    let mut beacons = Vec::with_capacity(usize::try_from(size).unwrap_or_default());
+    while (beacons.len() as u64) < size {
+        match stream.recv().await {
+            Some(Ok(beacon)) => beacons.push(beacon),
+            Some(Err(_)) | None => {
+                return Err(ArchiverError::Incomplete(from + beacons.len() as u64));
+            }
+        }
+    }
+
+    Ok(beacons)
+}
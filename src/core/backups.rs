@@ -0,0 +1,441 @@
+//! Optional scheduler of periodic backups of keys, group files and DKG store.
+//!
+//! Every interval an archive of all beacon ids in the daemon folder, see [`crate::key::backup`],
+//! is written into a local folder or uploaded to S3-compatible storage. Only the latest archives
+//! are retained: older files are removed from the folder, older objects are removed from the
+//! bucket using [`INDEX`] object under the prefix, so listing of the bucket is not required.
+//! Archives without passphrase are never uploaded.
+use super::daemon::Daemon;
+
+use crate::key::backup::BackupError;
+use crate::net::s3::S3Client;
+use crate::net::s3::S3Config;
+use crate::net::s3::S3Error;
+use crate::secrets::Secret;
+
+use std::fmt::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+/// JSON array of retained archive names in bucket, from the oldest to the latest.
+pub const INDEX: &str = "index.json";
+const ARCHIVE_PREFIX: &str = "drand-backup-";
+const ARCHIVE_SUFFIX: &str = ".tar.zst";
+
+#[derive(thiserror::Error, Debug)]
+pub enum BackupSchedulerError {
+    #[error("backup: {0}")]
+    Backup(#[from] BackupError),
+    #[error("s3: {0}")]
+    S3(#[from] S3Error),
+    #[error("io: {0}")]
+    IO(#[from] std::io::Error),
+    #[error("index in bucket is not a list of archive names")]
+    InvalidIndex,
+    #[error("passphrase is required to upload archives")]
+    Unencrypted,
+}
+
+/// Destination of scheduled backups.
+#[derive(Debug, Clone)]
+pub enum BackupTarget {
+    Dir(PathBuf),
+    S3 {
+        s3: S3Config,
+        /// Prefix of object keys, e.g. `backups/`.
+        prefix: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct BackupConfig {
+    pub interval: Duration,
+    /// Amount of retained archives, at least one.
+    pub keep: usize,
+    pub target: BackupTarget,
+    /// Private keys and shares are encrypted with the passphrase if set.
+    pub passphrase: Option<Secret>,
+}
+
+/// Outcome of scheduled backups since the daemon start.
+#[derive(Default)]
+pub struct BackupStats {
+    /// UNIX time of the latest successful backup in seconds, zero if none.
+    last_success: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl BackupStats {
+    pub fn last_success(&self) -> u64 {
+        self.last_success.load(Ordering::Relaxed)
+    }
+
+    /// Returns stats in Prometheus text exposition format.
+    pub fn metrics(&self) -> String {
+        let mut m = String::new();
+        let name = "drand_backup_last_success_timestamp_seconds";
+        let _ = writeln!(
+            m,
+            "# HELP {name} UNIX time of the latest successful backup."
+        );
+        let _ = writeln!(m, "# TYPE {name} gauge");
+        let _ = writeln!(m, "{name} {}", self.last_success());
+        let name = "drand_backup_failures_total";
+        let _ = writeln!(m, "# HELP {name} Failed scheduled backups.");
+        let _ = writeln!(m, "# TYPE {name} counter");
+        let _ = writeln!(m, "{name} {}", self.failures.load(Ordering::Relaxed));
+
+        m
+    }
+}
+
+/// Resolved [`BackupTarget`].
+enum Sink {
+    Dir(PathBuf),
+    S3 { client: S3Client, prefix: String },
+}
+
+/// Writes backups of all beacon ids until the daemon is stopped, the first one at the start.
+pub async fn run(daemon: Arc<Daemon>, config: BackupConfig) {
+    let sink = match &config.target {
+        BackupTarget::Dir(dir) => {
+            if config.passphrase.is_none() {
+                warn!(
+                    "backups: passphrase is not set, PRIVATE KEYS AND SHARES ARE WRITTEN IN PLAIN TEXT into {}",
+                    dir.display()
+                );
+            }
+            info!(
+                "backups: writing into {} every {:?}",
+                dir.display(),
+                config.interval
+            );
+            Sink::Dir(dir.clone())
+        }
+        BackupTarget::S3 { .. } if config.passphrase.is_none() => {
+            error!("backups: {}", BackupSchedulerError::Unencrypted);
+            return;
+        }
+        BackupTarget::S3 { s3, prefix } => match S3Client::new(s3.clone()) {
+            Ok(client) => {
+                info!(
+                    "backups: uploading to {}/{}/{prefix} every {:?}",
+                    s3.endpoint, s3.bucket, config.interval
+                );
+                Sink::S3 {
+                    client,
+                    prefix: prefix.clone(),
+                }
+            }
+            Err(err) => {
+                error!("backups: {err}");
+                return;
+            }
+        },
+    };
+
+    let mut ticker = tokio::time::interval(config.interval);
+    loop {
+        tokio::select! {
+            () = daemon.token.cancelled() => return,
+            _ = ticker.tick() => (),
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        match backup(&daemon, &config, &sink, now).await {
+            Ok(name) => {
+                daemon.backups.last_success.store(now, Ordering::Relaxed);
+                info!("backups: written {name}");
+            }
+            Err(err) => {
+                daemon.backups.failures.fetch_add(1, Ordering::Relaxed);
+                error!("backups: {err}");
+            }
+        }
+    }
+}
+
+/// Returns location of the written archive.
+async fn backup(
+    daemon: &Daemon,
+    config: &BackupConfig,
+    sink: &Sink,
+    now: u64,
+) -> Result<String, BackupSchedulerError> {
+    let name = format!("{ARCHIVE_PREFIX}{now}{ARCHIVE_SUFFIX}");
+    let keep = config.keep.max(1);
+    let passphrase = || config.passphrase.as_ref().map(|p| p.expose().to_string());
+    match sink {
+        Sink::Dir(dir) => {
+            let path = dir.join(&name);
+            daemon.backup(vec![], path.clone(), passphrase()).await?;
+            rotate_dir(dir, keep)?;
+
+            Ok(path.display().to_string())
+        }
+        Sink::S3 { client, prefix } => {
+            if config.passphrase.is_none() {
+                return Err(BackupSchedulerError::Unencrypted);
+            }
+            // Private folder of the daemon user, removed on drop.
+            let temp_dir = tempfile::Builder::new().prefix(ARCHIVE_PREFIX).tempdir()?;
+            let path = temp_dir.path().join(&name);
+            daemon.backup(vec![], path.clone(), passphrase()).await?;
+            let data = std::fs::read(&path)?;
+            drop(temp_dir);
+            client.put(&format!("{prefix}{name}"), data).await?;
+            rotate_bucket(client, prefix, name.clone(), keep).await?;
+
+            Ok(format!("{prefix}{name}"))
+        }
+    }
+}
+
+/// Returns UNIX time of the archive name.
+fn archive_time(name: &str) -> Option<u64> {
+    name.strip_prefix(ARCHIVE_PREFIX)?
+        .strip_suffix(ARCHIVE_SUFFIX)?
+        .parse()
+        .ok()
+}
+
+/// Removes archives in the folder except for the latest `keep`.
+fn rotate_dir(dir: &Path, keep: usize) -> std::io::Result<()> {
+    let mut archives = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if let Some(time) = entry.file_name().to_str().and_then(archive_time) {
+            archives.push((time, entry.path()));
+        }
+    }
+    archives.sort();
+    let expired = archives.len().saturating_sub(keep);
+    for (_, path) in &archives[..expired] {
+        std::fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+/// Appends archive to the index and removes objects except for the latest `keep`. Objects which
+/// failed to be removed are kept in the index, so removal is retried by the next backup.
+async fn rotate_bucket(
+    client: &S3Client,
+    prefix: &str,
+    name: String,
+    keep: usize,
+) -> Result<(), BackupSchedulerError> {
+    let index_key = format!("{prefix}{INDEX}");
+    let mut names: Vec<String> = match client.get(&index_key).await? {
+        Some(index) => {
+            serde_json::from_slice(&index).map_err(|_| BackupSchedulerError::InvalidIndex)?
+        }
+        None => vec![],
+    };
+    names.push(name);
+    let expired = names.len().saturating_sub(keep);
+    let mut retained = vec![];
+    for old in names.drain(..expired) {
+        if let Err(err) = client.delete(&format!("{prefix}{old}")).await {
+            warn!("backups: failed to remove expired {prefix}{old}: {err}");
+            retained.push(old);
+        }
+    }
+    retained.append(&mut names);
+    let index = serde_json::to_vec(&retained).map_err(|_| BackupSchedulerError::InvalidIndex)?;
+    client.put(&index_key, index).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::SecretSource;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+
+    type Objects = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+    /// Serves objects of bucket `backups` over plain HTTP, deletion of `failing` key fails.
+    async fn fake_s3(
+        objects: Objects,
+        failing: Arc<Mutex<Option<String>>>,
+        secret: &Path,
+    ) -> S3Client {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = vec![];
+                let mut buf = [0; 4096];
+                let head_end = loop {
+                    let n = stream.read(&mut buf).await.unwrap_or_default();
+                    if n == 0 {
+                        break None;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                    if let Some(i) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break Some(i + 4);
+                    }
+                };
+                let Some(head_end) = head_end else { continue };
+                let head = String::from_utf8_lossy(&request[..head_end]).to_lowercase();
+                let length: usize = head
+                    .lines()
+                    .find_map(|l| l.strip_prefix("content-length: "))
+                    .map_or(0, |l| l.trim().parse().unwrap());
+                while request.len() < head_end + length {
+                    let n = stream.read(&mut buf).await.unwrap_or_default();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let mut words = head.split(' ');
+                let (method, path) = (words.next().unwrap(), words.next().unwrap());
+                let key = path.strip_prefix("/backups/").unwrap().to_string();
+                let (status, body) = {
+                    let mut objects = objects.lock().unwrap();
+                    match method {
+                        "put" => {
+                            objects.insert(key, request[head_end..].to_vec());
+                            ("200 OK", vec![])
+                        }
+                        "get" => match objects.get(&key) {
+                            Some(data) => ("200 OK", data.clone()),
+                            None => ("404 Not Found", vec![]),
+                        },
+                        _ if failing.lock().unwrap().as_ref() == Some(&key) => {
+                            ("500 Internal Server Error", vec![])
+                        }
+                        _ => {
+                            objects.remove(&key);
+                            ("204 No Content", vec![])
+                        }
+                    }
+                };
+                let head = format!(
+                    "HTTP/1.1 {status}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(head.as_bytes()).await;
+                let _ = stream.write_all(&body).await;
+            }
+        });
+
+        std::fs::write(secret, "secret").unwrap();
+        let secret_key = SecretSource::File(secret.to_path_buf()).resolve().unwrap();
+        S3Client::new(S3Config {
+            endpoint,
+            bucket: "backups".into(),
+            region: "us-east-1".into(),
+            access_key: "access".into(),
+            secret_key,
+        })
+        .unwrap()
+    }
+
+    fn name(time: u64) -> String {
+        format!("{ARCHIVE_PREFIX}{time}{ARCHIVE_SUFFIX}")
+    }
+
+    fn index(objects: &Objects) -> Vec<String> {
+        serde_json::from_slice(&objects.lock().unwrap()[&format!("node/{INDEX}")]).unwrap()
+    }
+
+    fn stored(objects: &Objects, time: u64) -> bool {
+        objects
+            .lock()
+            .unwrap()
+            .contains_key(&format!("node/{}", name(time)))
+    }
+
+    #[tokio::test]
+    async fn rotate_archives_in_bucket() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let objects = Objects::default();
+        let failing = Arc::new(Mutex::new(None));
+        let client = fake_s3(
+            objects.clone(),
+            failing.clone(),
+            &temp_dir.path().join("secret"),
+        )
+        .await;
+        let upload = |time: u64| {
+            objects
+                .lock()
+                .unwrap()
+                .insert(format!("node/{}", name(time)), vec![]);
+            rotate_bucket(&client, "node/", name(time), 2)
+        };
+
+        upload(1).await.unwrap();
+        upload(2).await.unwrap();
+        assert_eq!(index(&objects), [name(1), name(2)]);
+        upload(3).await.unwrap();
+        assert_eq!(index(&objects), [name(2), name(3)]);
+        assert!(!stored(&objects, 1));
+
+        // Expired archive which failed to be removed is kept in the index.
+        *failing.lock().unwrap() = Some(format!("node/{}", name(2)));
+        upload(4).await.unwrap();
+        assert_eq!(index(&objects), [name(2), name(3), name(4)]);
+        *failing.lock().unwrap() = None;
+        upload(5).await.unwrap();
+        assert_eq!(index(&objects), [name(4), name(5)]);
+        assert!(!stored(&objects, 2) && !stored(&objects, 3) && stored(&objects, 4));
+
+        objects
+            .lock()
+            .unwrap()
+            .insert(format!("node/{INDEX}"), b"{}".to_vec());
+        assert!(matches!(
+            upload(6).await,
+            Err(BackupSchedulerError::InvalidIndex)
+        ));
+    }
+
+    #[test]
+    fn rotate_archives_in_folder() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        for time in [1_000, 999, 1_001] {
+            std::fs::write(
+                dir.join(format!("{ARCHIVE_PREFIX}{time}{ARCHIVE_SUFFIX}")),
+                "",
+            )
+            .unwrap();
+        }
+        // Unrelated files are never removed.
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+
+        rotate_dir(dir, 2).unwrap();
+        let mut left: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(
+            left,
+            [
+                "drand-backup-1000.tar.zst",
+                "drand-backup-1001.tar.zst",
+                "notes.txt"
+            ]
+        );
+    }
+}
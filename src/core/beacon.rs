@@ -1,8 +1,14 @@
+use super::events::EventSender;
+use super::mailbox;
+use super::mailbox::CmdSender;
 use super::multibeacon::BeaconHandler;
+use super::remote_status::RemoteStatusError;
 use crate::chain::init_chain;
+use crate::chain::time::SharedClock;
 use crate::chain::ChainCmd;
 use crate::chain::ChainError;
 use crate::chain::ChainedBeacon;
+use crate::chain::StorageMode;
 use crate::chain::StoreError;
 use crate::chain::StoreStreamResponse;
 use crate::chain::SyncError;
@@ -10,7 +16,10 @@ use crate::chain::UnChainedBeacon;
 
 use crate::dkg::actions_active::ActionsActive;
 use crate::dkg::actions_passive::ActionsPassive;
+use crate::dkg::actions_signing::is_valid_signature;
 use crate::dkg::execution::ExecuteDkg;
+use crate::dkg::fanout::FanOut;
+use crate::dkg::recovery;
 use crate::dkg::store::DkgStore;
 use crate::dkg::utils::GateKeeper;
 use crate::dkg::ActionsError;
@@ -27,12 +36,20 @@ use crate::key::Scheme;
 use crate::net::control::SyncProgressResponse;
 use crate::net::pool::PoolSender;
 use crate::net::protocol::PartialMsg;
+use crate::net::protocol::SyncMemory;
+use crate::net::utils::Address;
+use crate::protobuf::drand::LeaveNoticeRequest;
+use crate::protobuf::drand::MerkleProofResponse;
+use crate::protobuf::drand::PublicRandResponse;
 use crate::protobuf::drand::StartSyncRequest;
+use crate::protobuf::drand::StatusRequest;
 use crate::protobuf::drand::StatusResponse;
 
 use crate::protobuf::dkg::DkgPacket;
 use crate::protobuf::dkg::DkgStatusResponse;
 use crate::protobuf::drand::ChainInfoPacket;
+use crate::protobuf::drand::DkgResetResponse;
+use crate::protobuf::drand::GroupPacket;
 use crate::protobuf::drand::IdentityResponse;
 
 use crate::net::utils::Callback;
@@ -41,10 +58,11 @@ use crate::transport::dkg::GossipPacket;
 use crate::transport::dkg::Participant;
 
 use energon::drand::traits::BeaconDigest;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio_util::task::TaskTracker;
-use tracing::{error, info_span, Span};
+use tracing::{error, info, info_span, Span};
 
 pub const DEFAULT_BEACON_ID: &str = "default";
 
@@ -53,6 +71,46 @@ pub fn is_default_beacon_id(beacon_id: &str) -> bool {
     beacon_id == DEFAULT_BEACON_ID || beacon_id.is_empty()
 }
 
+/// Maximum length of beacon id, the id is used as folder name of the beacon.
+pub const MAX_BEACON_ID_LEN: usize = 64;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum BeaconIdError {
+    #[error("beacon id '{0}' is longer than {MAX_BEACON_ID_LEN} characters")]
+    TooLong(String),
+    #[error("beacon id '{id}' contains invalid character {ch:?}, allowed are ASCII letters, digits, '-' and '_'")]
+    InvalidChar { id: String, ch: char },
+    #[error("beacon id '{0}' is reserved, use '{DEFAULT_BEACON_ID}' for the default beacon")]
+    Reserved(String),
+}
+
+/// Returns canonical form of beacon id, empty id is the reserved id "default".
+///
+/// Other ids are kept as is, like in golang implementation, so ids which one side could
+/// normalize differently (case variants of "default", whitespaces, path separators) are rejected.
+pub fn canonical_beacon_id(beacon_id: &str) -> Result<&str, BeaconIdError> {
+    if is_default_beacon_id(beacon_id) {
+        return Ok(DEFAULT_BEACON_ID);
+    }
+    if beacon_id.len() > MAX_BEACON_ID_LEN {
+        return Err(BeaconIdError::TooLong(beacon_id.to_string()));
+    }
+    if let Some(ch) = beacon_id
+        .chars()
+        .find(|ch| !(ch.is_ascii_alphanumeric() || *ch == '-' || *ch == '_'))
+    {
+        return Err(BeaconIdError::InvalidChar {
+            id: beacon_id.to_string(),
+            ch,
+        });
+    }
+    if beacon_id.eq_ignore_ascii_case(DEFAULT_BEACON_ID) {
+        return Err(BeaconIdError::Reserved(beacon_id.to_string()));
+    }
+
+    Ok(beacon_id)
+}
+
 #[derive(PartialEq, Eq)]
 pub struct BeaconID {
     inner: Arc<str>,
@@ -84,7 +142,24 @@ pub enum BeaconCmd {
         Callback<mpsc::Receiver<SyncProgressResponse>, SyncError>,
     ),
     ChainInfo(Callback<ChainInfoPacket, ChainError>),
+    /// Request for the group used at given epoch.
+    Group(u32, Callback<GroupPacket, FileStoreError>),
     Status(Callback<StatusResponse, StoreError>),
+    /// Inclusion proof request of the round into Merkle tree over stored beacons.
+    MerkleProof(u64, Callback<MerkleProofResponse, StoreError>),
+    /// Request for stored beacon with its randomness, the latest beacon for round 0.
+    PublicRand(u64, Callback<PublicRandResponse, StoreError>),
+    /// Manual resync request from the given round, replied with the round up to which resync is started.
+    Resync(u64, Callback<u64, ChainError>),
+    /// Status request of a group member.
+    PeerStatus(StatusRequest, Callback<StatusResponse, RemoteStatusError>),
+    /// Leave notice of a group member, see [`super::leave`].
+    LeaveNotice(LeaveNoticeRequest, Callback<(), RemoteStatusError>),
+    /// Request for statuses of given addresses, all group members if empty.
+    RemoteStatus(
+        Vec<Address>,
+        Callback<HashMap<String, StatusResponse>, RemoteStatusError>,
+    ),
     DkgActions(Actions),
     FinishedDkg,
 }
@@ -92,9 +167,19 @@ pub enum BeaconCmd {
 /// Subcommand for DKG actions [`BeaconCmd::DkgActions`]
 pub enum Actions {
     Gossip(GossipPacket, Callback<(), ActionsError>),
+    /// Gossip packet with signature of `msg` verified off the beacon process, see [`BeaconProcess::gossip`].
+    Verified {
+        packet: GossipPacket,
+        msg: Vec<u8>,
+        cb: Callback<(), ActionsError>,
+    },
     Command(Command, Callback<(), ActionsError>),
     Broadcast(DkgPacket, Callback<(), ActionsError>),
     Status(Callback<DkgStatusResponse, ActionsError>),
+    /// Pending proposal is moved into `TimedOut` if its timeout is reached, see [`crate::dkg::recovery`].
+    TimeOut(Callback<(), ActionsError>),
+    /// Failed or stale DKG state is wiped if confirmed, see [`crate::dkg::reset`].
+    Reset(bool, Callback<DkgResetResponse, ActionsError>),
 }
 
 /// `BeaconProcess` is responsible for the main logic of the `BeaconID` instance. It reads the keys / group file, it
@@ -110,26 +195,55 @@ pub struct InnerProcess<S: Scheme> {
     fs: FileStore,
     keypair: Pair<S>,
     dkg_store: DkgStore,
-    process_cmd_tx: mpsc::Sender<BeaconCmd>,
+    events: EventSender,
+    clock: SharedClock,
+    /// Fan-out of DKG bundles, see [`crate::dkg::fanout`].
+    fanout: FanOut,
+    process_cmd_tx: CmdSender,
     pub chain_cmd_tx: mpsc::Sender<ChainCmd>,
     l: Span,
 }
 
 impl<S: Scheme> BeaconProcess<S> {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         fs: FileStore,
         pair: &PairToml,
-        process_cmd_tx: mpsc::Sender<BeaconCmd>,
+        process_cmd_tx: CmdSender,
         pool: PoolSender,
+        events: EventSender,
+        clock: SharedClock,
+        sync_memory: SyncMemory,
         private_listen: String,
+        shadow: bool,
+        storage: StorageMode,
+        fanout: FanOut,
     ) -> Result<(Self, mpsc::Sender<PartialMsg>), FileStoreError> {
         let keypair: Pair<S> = Toml::toml_decode(pair).ok_or(FileStoreError::TomlError)?;
         let our_addr = keypair.public_identity().address.clone();
         let id = fs.get_beacon_id().ok_or(FileStoreError::FailedToReadID)?;
+        fs.complete_layout()?;
         let is_fresh = fs.is_fresh_run()?;
-        let dkg_store = DkgStore::init::<S>(fs.beacon_path.as_path(), is_fresh, id)?;
+        let dkg_store =
+            DkgStore::init::<S>(fs.beacon_path.as_path(), is_fresh, id, events.clone())?;
         let log = info_span!("", id = format!("{private_listen}.{id}"));
+        if shadow {
+            info!(parent: &log, "shadow mode: partials are not broadcast");
+        }
+        match storage {
+            StorageMode::Derived => {
+                info!(parent: &log, "derived storage: previous signatures and randomness are recomputed on read")
+            }
+            StorageMode::Precomputed => {
+                info!(parent: &log, "precomputed storage: randomness is stored with beacons")
+            }
+            StorageMode::Full => {}
+        }
+        if fanout != FanOut::Full {
+            info!(parent: &log, "dkg fan-out: {fanout}");
+        }
         let t = TaskTracker::new();
+        let process_events = events.clone();
 
         let (partial_tx, chain_cmd_tx) = if S::Beacon::is_chained() {
             init_chain::<S, ChainedBeacon>(
@@ -137,8 +251,13 @@ impl<S: Scheme> BeaconProcess<S> {
                 fs.clone(),
                 private_listen,
                 pool,
+                events,
+                clock.clone(),
+                sync_memory,
                 id.to_string(),
                 our_addr,
+                shadow,
+                storage,
                 &t,
             )
         } else {
@@ -147,8 +266,13 @@ impl<S: Scheme> BeaconProcess<S> {
                 fs.clone(),
                 private_listen,
                 pool,
+                events,
+                clock.clone(),
+                sync_memory,
                 id.to_string(),
                 our_addr,
+                shadow,
+                storage,
                 &t,
             )
         };
@@ -160,6 +284,9 @@ impl<S: Scheme> BeaconProcess<S> {
                 keypair,
                 tracker: t,
                 dkg_store,
+                events: process_events,
+                clock,
+                fanout,
                 process_cmd_tx,
                 chain_cmd_tx,
                 l: log,
@@ -169,24 +296,51 @@ impl<S: Scheme> BeaconProcess<S> {
         Ok((process, partial_tx))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn run(
         fs: FileStore,
         pair: &PairToml,
         pool: PoolSender,
+        events: EventSender,
+        clock: SharedClock,
+        sync_memory: SyncMemory,
         private_listen: String,
+        shadow: bool,
+        storage: StorageMode,
+        fanout: FanOut,
     ) -> Result<BeaconHandler, FileStoreError> {
         // Create cmd channel for beacon process
-        let (bp_tx, mut bp_rx) = mpsc::channel::<BeaconCmd>(1);
+        let (bp_tx, mut bp_rx) = mailbox::channel();
         // Initialize beacon process.
-        let (bp, partial_tx) = Self::new(fs, pair, bp_tx.clone(), pool, private_listen)?;
+        let (bp, partial_tx) = Self::new(
+            fs,
+            pair,
+            bp_tx.clone(),
+            pool,
+            events,
+            clock,
+            sync_memory,
+            private_listen,
+            shadow,
+            storage,
+            fanout,
+        )?;
         let beacon_id = bp.beacon_id.clone();
         let tracker = bp.tracker().clone();
+        recovery::start(bp.clone());
 
         tracker.spawn(async move {
             let mut gk = GateKeeper::new(bp.log());
+            bp.resume_dkg(&mut gk).await;
             while let Some(cmd) = bp_rx.recv().await {
                 match cmd {
                     BeaconCmd::Status(cb) =>bp.status(cb).await,
+                    BeaconCmd::MerkleProof(round, cb) => bp.merkle_proof(round, cb).await,
+                    BeaconCmd::PublicRand(round, cb) => bp.public_rand(round, cb).await,
+                    BeaconCmd::Resync(from_round, cb) => bp.resync(from_round, cb).await,
+                    BeaconCmd::PeerStatus(request, cb) => bp.peer_status(request, cb),
+                    BeaconCmd::RemoteStatus(addresses, cb) => bp.remote_status(addresses, cb),
+                    BeaconCmd::LeaveNotice(request, cb) => bp.leave_notice(&request, cb),
                     BeaconCmd::IdentityRequest(cb) => cb.reply(bp.identity().try_into()),
                     BeaconCmd::Sync(from_round, cb) => {
                         if let Err(err)=bp
@@ -203,6 +357,7 @@ impl<S: Scheme> BeaconProcess<S> {
                         }
                     }
                     BeaconCmd::ChainInfo(cb) => bp.chain_info(cb).await,
+                    BeaconCmd::Group(epoch, cb) => cb.reply(bp.group_for_epoch(epoch)),
                     BeaconCmd::DkgActions(action) => bp.dkg_actions(action, &mut gk).await,
                     BeaconCmd::FinishedDkg => gk.set_empty(),
                     BeaconCmd::Shutdown(cb) => {
@@ -231,6 +386,7 @@ impl<S: Scheme> BeaconProcess<S> {
             beacon_id,
             process_tx: bp_tx,
             partial_tx,
+            timeouts: Arc::default(),
         })
     }
 
@@ -238,12 +394,17 @@ impl<S: Scheme> BeaconProcess<S> {
         match request {
             Actions::Status(cb) => cb.reply(self.dkg_status()),
             Actions::Command(cmd, cb) => cb.reply(self.command(cmd).await),
-            Actions::Broadcast(packet, cb) => cb.reply(gk.broadcast(packet).await),
-            Actions::Gossip(packet, cb) => cb.reply(self.gossip(gk, packet).await),
+            Actions::Broadcast(packet, cb) => cb.reply(gk.broadcast(packet)),
+            Actions::Gossip(packet, cb) => self.gossip(gk, packet, cb),
+            Actions::Verified { packet, msg, cb } => {
+                self.verified_gossip(gk, packet, &msg, cb).await;
+            }
+            Actions::TimeOut(cb) => cb.reply(self.time_out_proposal()),
+            Actions::Reset(confirm, cb) => cb.reply(self.reset_dkg(confirm, gk)),
         }
     }
 
-    async fn status(&self, cb: Callback<StatusResponse, StoreError>) {
+    pub(super) async fn status(&self, cb: Callback<StatusResponse, StoreError>) {
         if self
             .chain_cmd_tx
             .send(ChainCmd::LatestStored(cb))
@@ -254,23 +415,97 @@ impl<S: Scheme> BeaconProcess<S> {
         }
     }
 
-    async fn gossip(
-        &self,
-        gk: &mut GateKeeper<S>,
-        packet: GossipPacket,
-    ) -> Result<(), ActionsError> {
+    async fn merkle_proof(&self, round: u64, cb: Callback<MerkleProofResponse, StoreError>) {
+        if self
+            .chain_cmd_tx
+            .send(ChainCmd::MerkleProof { round, cb })
+            .await
+            .is_err()
+        {
+            error!(parent: &self.l, "fatal: chain module in failed state");
+        }
+    }
+
+    async fn public_rand(&self, round: u64, cb: Callback<PublicRandResponse, StoreError>) {
+        if self
+            .chain_cmd_tx
+            .send(ChainCmd::PublicRand { round, cb })
+            .await
+            .is_err()
+        {
+            error!(parent: &self.l, "fatal: chain module in failed state");
+        }
+    }
+
+    async fn resync(&self, from_round: u64, cb: Callback<u64, ChainError>) {
+        if self
+            .chain_cmd_tx
+            .send(ChainCmd::ForceResync { from_round, cb })
+            .await
+            .is_err()
+        {
+            error!(parent: &self.l, "fatal: chain module in failed state");
+        }
+    }
+
+    /// Verifies signature of a new gossip packet on the blocking pool, the packet is applied once
+    /// it is sent back as [`Actions::Verified`], so the process keeps serving other commands.
+    fn gossip(&self, gk: &mut GateKeeper<S>, packet: GossipPacket, cb: Callback<(), ActionsError>) {
         // ignore duplicated or incorrect packets
         if !gk.is_new_packet(&packet) {
-            return Ok(());
+            return cb.reply(Ok(()));
         }
+        self.verify_gossip(packet, cb);
+    }
 
-        match self.packet(packet).await {
+    fn verify_gossip(&self, packet: GossipPacket, cb: Callback<(), ActionsError>) {
+        let (key, msg) = match self.signed_packet(&packet) {
+            Ok(signed) => signed,
+            Err(err) => return cb.reply(Err(err)),
+        };
+        let bp = self.clone();
+        self.tracker().spawn(async move {
+            let sig = packet.metadata.signature.clone();
+            let signed = msg.clone();
+            let is_valid =
+                tokio::task::spawn_blocking(move || is_valid_signature::<S>(&key, &sig, &signed))
+                    .await
+                    .unwrap_or(false);
+            if !is_valid {
+                return cb.reply(Err(ActionsError::InvalidSignature));
+            }
+            let cmd = BeaconCmd::DkgActions(Actions::Verified { packet, msg, cb });
+            if let Err(err) = bp.cmd_tx().send(cmd).await {
+                if let BeaconCmd::DkgActions(Actions::Verified { cb, .. }) = err.0 {
+                    cb.reply(Err(ActionsError::ProtocolIsNotRunning));
+                }
+            }
+        });
+    }
+
+    async fn verified_gossip(
+        &self,
+        gk: &mut GateKeeper<S>,
+        packet: GossipPacket,
+        msg: &[u8],
+        cb: Callback<(), ActionsError>,
+    ) {
+        let relayed = packet.clone();
+        let applied = match self.packet(packet, msg) {
+            // State is changed by another packet, signature is verified again for the new state.
+            Err(ActionsError::StaleVerification) => return self.verify_gossip(relayed, cb),
+            applied => applied,
+        };
+        if applied.is_ok() {
+            self.relay_gossip(&relayed);
+        }
+        cb.reply(match applied {
             Ok(Some(start_execution_time)) => {
                 self.setup_and_run_dkg(start_execution_time, gk).await
             }
             Ok(None) => Ok(()),
             Err(err) => Err(err),
-        }
+        });
     }
 
     async fn shutdown(&self) -> Result<(), ShutdownError> {
@@ -291,6 +526,12 @@ impl<S: Scheme> BeaconProcess<S> {
             .unwrap();
     }
 
+    fn group_for_epoch(&self, epoch: u32) -> Result<GroupPacket, FileStoreError> {
+        let group = self.fs.load_group_epoch::<S>(epoch)?;
+
+        Ok(GroupPacket::try_from(&group)?)
+    }
+
     /// Returns [`Participant`] which is dkg representation of [`Identity`].
     pub fn as_participant(&self) -> Result<Participant, ActionsError> {
         self.identity()
@@ -309,6 +550,11 @@ impl<S: Scheme> BeaconProcess<S> {
         }
     }
 
+    /// Sender of commands into this beacon process.
+    pub fn cmd_tx(&self) -> &CmdSender {
+        &self.process_cmd_tx
+    }
+
     pub fn tracker(&self) -> &TaskTracker {
         &self.tracker
     }
@@ -329,6 +575,18 @@ impl<S: Scheme> BeaconProcess<S> {
         &self.dkg_store
     }
 
+    pub fn events(&self) -> &EventSender {
+        &self.events
+    }
+
+    pub fn clock(&self) -> &SharedClock {
+        &self.clock
+    }
+
+    pub fn fanout(&self) -> FanOut {
+        self.fanout
+    }
+
     pub fn private_key(&self) -> &S::Scalar {
         self.keypair.private_key()
     }
@@ -379,3 +637,32 @@ impl<S: Scheme> Clone for BeaconProcess<S> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beacon_id_validation() {
+        assert_eq!(canonical_beacon_id(""), Ok(DEFAULT_BEACON_ID));
+        assert_eq!(canonical_beacon_id("default"), Ok(DEFAULT_BEACON_ID));
+        assert_eq!(canonical_beacon_id("quicknet-t_2"), Ok("quicknet-t_2"));
+        assert_eq!(
+            canonical_beacon_id("Default"),
+            Err(BeaconIdError::Reserved("Default".into()))
+        );
+        assert_eq!(
+            canonical_beacon_id("../default"),
+            Err(BeaconIdError::InvalidChar {
+                id: "../default".into(),
+                ch: '.'
+            })
+        );
+        assert!(canonical_beacon_id(" default").is_err());
+        assert!(canonical_beacon_id(&"a".repeat(MAX_BEACON_ID_LEN)).is_ok());
+        assert_eq!(
+            canonical_beacon_id(&"a".repeat(MAX_BEACON_ID_LEN + 1)),
+            Err(BeaconIdError::TooLong("a".repeat(MAX_BEACON_ID_LEN + 1)))
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Crash reports of panics for post-mortems of production nodes.
+//!
+//! Panic hook of the daemon writes a JSON report with version, panic message and location,
+//! backtrace, loaded beacon ids and their last stored rounds into [`CRASH_DIR`] of the daemon
+//! folder. Path of the report is logged and emitted as [`Event::Panic`].
+use super::daemon::Daemon;
+use super::events::Event;
+
+use crate::chain::time::time_now;
+
+use serde_json::json;
+use serde_json::Value;
+use std::backtrace::Backtrace;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Weak;
+use tracing::error;
+
+/// Folder of crash reports inside the daemon folder.
+pub const CRASH_DIR: &str = "crash_reports";
+
+/// Installs panic hook writing crash reports, the previous hook is still called first.
+pub fn install(daemon: &Arc<Daemon>) {
+    let dir = daemon
+        .multibeacon_path
+        .parent()
+        .unwrap_or(&daemon.multibeacon_path)
+        .join(CRASH_DIR);
+    let daemon = Arc::downgrade(daemon);
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        previous(info);
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| (*s).to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        let location = info.location().map(ToString::to_string).unwrap_or_default();
+        on_panic(&dir, &daemon, &message, &location);
+    }));
+}
+
+fn on_panic(dir: &Path, daemon: &Weak<Daemon>, message: &str, location: &str) {
+    // Daemon might be already dropped if panic happens at shutdown.
+    let daemon = daemon.upgrade();
+    let beacons = daemon.as_ref().map_or_else(Vec::new, |daemon| {
+        let last_rounds = daemon.beacons().events().last_rounds();
+        daemon
+            .beacons()
+            .snapshot()
+            .iter()
+            .map(|h| {
+                let id = h.id().to_string();
+                let round = last_rounds.get(&id).copied();
+                (id, round)
+            })
+            .collect()
+    });
+    let now_ms = time_now().as_millis();
+    let thread = std::thread::current();
+    let report = report(
+        message,
+        location,
+        thread.name().unwrap_or("unnamed"),
+        &beacons,
+        &Backtrace::force_capture().to_string(),
+        now_ms,
+    );
+
+    match write_report(dir, &report, now_ms) {
+        Ok(path) => {
+            let path = path.display().to_string();
+            error!("panic at {location}: {message}, crash report written to {path}");
+            if let Some(daemon) = daemon {
+                daemon
+                    .beacons()
+                    .events()
+                    .emit("", &Event::Panic { report: &path });
+            }
+        }
+        Err(err) => error!("panic at {location}: {message}, failed to write crash report: {err}"),
+    }
+}
+
+fn report(
+    message: &str,
+    location: &str,
+    thread: &str,
+    beacons: &[(String, Option<u64>)],
+    backtrace: &str,
+    now_ms: u128,
+) -> Value {
+    let beacons: Vec<Value> = beacons
+        .iter()
+        .map(|(id, round)| json!({ "beacon_id": id, "last_stored_round": round }))
+        .collect();
+
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "created_at_ms": now_ms.to_string(),
+        "message": message,
+        "location": location,
+        "thread": thread,
+        "beacons": beacons,
+        "backtrace": backtrace.lines().collect::<Vec<_>>(),
+    })
+}
+
+/// Returns path of the written report `panic-<unix time ms>.json`.
+fn write_report(dir: &Path, report: &Value, now_ms: u128) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("panic-{now_ms}.json"));
+    let data = serde_json::to_vec_pretty(report).map_err(std::io::Error::other)?;
+    std::fs::write(&path, data)?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_crash_report() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = temp_dir.path().join(CRASH_DIR);
+        let beacons = [
+            ("default".to_string(), Some(42)),
+            ("quicknet".to_string(), None),
+        ];
+        let report = report(
+            "boom",
+            "src/chain/handler.rs:1:1",
+            "tokio-runtime-worker",
+            &beacons,
+            "0: main\n1: start",
+            1_000,
+        );
+
+        let path = write_report(&dir, &report, 1_000).unwrap();
+        assert_eq!(path, dir.join("panic-1000.json"));
+        let written: Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(written["message"], "boom");
+        assert_eq!(written["beacons"][0]["last_stored_round"], 42);
+        assert_eq!(written["beacons"][1]["last_stored_round"], Value::Null);
+        assert_eq!(written["backtrace"][1], "1: start");
+    }
+}
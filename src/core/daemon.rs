@@ -1,13 +1,27 @@
+use super::backups::BackupStats;
 use super::beacon::BeaconCmd;
+use super::dump;
 use super::multibeacon::BeaconHandler;
 use super::multibeacon::BeaconHandlerError;
 use super::multibeacon::MultiBeacon;
+use super::runtime::spawn_store;
 
+use crate::chain::inspect;
+use crate::chain::inspect::StoreStats;
+use crate::chain::time::SharedClock;
 use crate::cli::Config;
+use crate::dkg::reset;
+use crate::dkg::reset::ResetError;
+use crate::key::backup;
+use crate::key::backup::BackupError;
+use crate::key::backup::Manifest;
 use crate::key::store::FileStore;
 use crate::key::store::FileStoreError;
+use crate::net::bandwidth::Bandwidth;
+use crate::net::protocol::SyncLimits;
 use crate::net::utils::Callback;
 use crate::net::utils::StartServerError;
+use crate::protobuf::drand::DkgResetResponse;
 
 use tokio::sync::oneshot;
 use tokio::time::sleep;
@@ -37,27 +51,47 @@ pub struct Daemon {
     pub tracker: TaskTracker,
     pub token: CancellationToken,
     pub beacons: MultiBeacon,
-    multibeacon_path: PathBuf,
+    pub(super) multibeacon_path: PathBuf,
+    /// Traffic of connections accepted at node address.
+    pub bandwidth: Bandwidth,
+    /// Flow control of sync streams served to followers.
+    pub sync_limits: SyncLimits,
+    /// Outcome of scheduled backups.
+    pub backups: BackupStats,
+    /// Sanitized config of the daemon, see [`dump::sanitize_config`].
+    pub(super) config_dump: String,
 }
 
 impl Daemon {
-    pub fn new(config: Config) -> Result<Arc<Self>, DaemonError> {
+    pub fn new(config: Config, clock: SharedClock) -> Result<Arc<Self>, DaemonError> {
         let tracker: TaskTracker = TaskTracker::new();
         let token: CancellationToken = CancellationToken::new();
         let private_listen = config.private_listen.clone();
+        let (sync_batch_size, max_sync_streams) = (config.sync_batch_size, config.max_sync_streams);
 
         info!(
             "Drand daemon initializing: private_listen: {}, control_port: {}, folder: {}",
             config.private_listen, config.control, config.folder,
         );
 
-        let (multibeacon_path, beacons) = MultiBeacon::new(config)?;
+        let config_dump = dump::sanitize_config(&config);
+        let (multibeacon_path, beacons) = MultiBeacon::new(config, clock)?;
+        // Buffer memory is shared with streams received by beacon processes.
+        let sync_limits = SyncLimits::new(
+            sync_batch_size,
+            max_sync_streams,
+            beacons.sync_memory().clone(),
+        );
         let daemon = Arc::new(Self {
             private_listen,
             tracker,
             token,
             beacons,
             multibeacon_path,
+            bandwidth: Bandwidth::default(),
+            sync_limits,
+            backups: BackupStats::default(),
+            config_dump,
         });
 
         Ok(daemon)
@@ -78,35 +112,46 @@ impl Daemon {
         } else
         // Otherwise stop id and update multibeacon state
         {
-            let handler = snapshot
-                .iter()
-                .find(|h| h.id().is_eq(id))
-                .ok_or(BeaconHandlerError::UnknownID)?;
-            // TODO: this should be moved into MultiBeacon method
-            let new_store = snapshot
-                .iter()
-                .filter(|x| x.id() != handler.id())
-                .cloned()
-                .collect::<Vec<BeaconHandler>>();
-
-            self.beacons().replace_store(Arc::new(new_store));
-
-            let process_tx = handler.process_tx.clone();
-            tokio::spawn(async move {
-                let (tx, rx) = Callback::new();
-                // Shutdown is graceful:
-                //  - beacon receiver is not dropped,
-                //  - callback awaited is_ok
-                //  - result from callback is_ok
-                let is_graceful = process_tx.send(BeaconCmd::Shutdown(tx)).await.is_ok()
-                    && rx.await.is_ok_and(|result| result.is_ok());
-                let _ = tx_graceful.send(is_graceful);
-            });
-
+            self.unload_id(id, tx_graceful)?;
             Ok(false)
         }
     }
 
+    /// Stops given id and removes it from multibeacon state, daemon keeps running even if no ids are left.
+    pub fn unload_id(
+        &self,
+        id: &str,
+        tx_graceful: oneshot::Sender<bool>,
+    ) -> Result<(), BeaconHandlerError> {
+        let snapshot = self.beacons().snapshot();
+        let handler = snapshot
+            .iter()
+            .find(|h| h.id().is_eq(id))
+            .ok_or(BeaconHandlerError::UnknownID)?;
+        // TODO: this should be moved into MultiBeacon method
+        let new_store = snapshot
+            .iter()
+            .filter(|x| x.id() != handler.id())
+            .cloned()
+            .collect::<Vec<BeaconHandler>>();
+
+        self.beacons().replace_store(Arc::new(new_store));
+
+        let process_tx = handler.process_tx.clone();
+        tokio::spawn(async move {
+            let (tx, rx) = Callback::new();
+            // Shutdown is graceful:
+            //  - beacon receiver is not dropped,
+            //  - callback awaited is_ok
+            //  - result from callback is_ok
+            let is_graceful = process_tx.send(BeaconCmd::Shutdown(tx)).await.is_ok()
+                && rx.await.is_ok_and(|result| result.is_ok());
+            let _ = tx_graceful.send(is_graceful);
+        });
+
+        Ok(())
+    }
+
     /// Stops all beacons and shutdown daemon
     pub fn stop_daemon(&self, tx_graceful: tokio::sync::oneshot::Sender<bool>) {
         let snapshot = self.beacons().snapshot();
@@ -151,26 +196,51 @@ impl Daemon {
         });
     }
 
-    pub fn load_id(&self, id: &str) -> Result<(), BeaconHandlerError> {
+    /// Loads given id from daemon folder. If `folder` is provided, the id is imported from this base folder first.
+    pub fn load_id(&self, id: &str, folder: Option<&str>) -> Result<(), BeaconHandlerError> {
         let store = self.beacons.snapshot();
         // Return error if given id is already loaded
         if store.iter().any(|h| h.beacon_id.is_eq(id)) {
             return Err(BeaconHandlerError::AlreadyLoaded);
         }
-        let store = FileStore {
-            beacon_path: self.multibeacon_path.join(id),
+        let store = match folder {
+            Some(folder) => {
+                FileStore::import(folder, &self.multibeacon_path, id).map_err(|err| {
+                    error!("failed to import store from {folder}: {err}, beacon id: {id}");
+                    BeaconHandlerError::Import(err)
+                })?
+            }
+            None => FileStore {
+                beacon_path: self.multibeacon_path.join(id),
+            },
         };
+        // Copied folder is removed on failure, so the import can be retried.
+        let imported = folder.is_some().then(|| store.clone());
         if let Err(err) = store.validate() {
             error!("failed to validate store: {err}, beacon id: {id}");
+            if let Some(store) = &imported {
+                store.discard_import();
+            }
             return Err(BeaconHandlerError::UnknownID);
         };
-
-        let new_handler =
-            BeaconHandler::new(store, self.beacons.get_pool(), self.private_listen.clone())
-                .map_err(|err| {
-                    error!("failed to initialize BeaconHandler: {err}, beacon id: {id}");
-                    BeaconHandlerError::UnknownID
-                })?;
+        let new_handler = BeaconHandler::new(
+            store,
+            self.beacons.get_pool(),
+            self.beacons.events().clone(),
+            self.beacons.clock(),
+            self.beacons.sync_memory().clone(),
+            self.private_listen.clone(),
+            self.beacons.is_shadow(id),
+            self.beacons.storage_mode(id),
+            self.beacons.fanout(id),
+        )
+        .map_err(|err| {
+            error!("failed to initialize BeaconHandler: {err}, beacon id: {id}");
+            if let Some(store) = &imported {
+                store.discard_import();
+            }
+            BeaconHandlerError::UnknownID
+        })?;
 
         // Update multibeacon storage with new handler
         // TODO: this should be moved into MultiBeacon method
@@ -186,6 +256,56 @@ impl Daemon {
         Ok(())
     }
 
+    /// Resets DKG state of a beacon id which is not loaded, see [`reset`].
+    pub fn reset_unloaded_dkg(
+        &self,
+        id: &str,
+        confirm: bool,
+    ) -> Result<DkgResetResponse, ResetError> {
+        let fs = FileStore {
+            beacon_path: self.multibeacon_path.join(id),
+        };
+        fs.validate()?;
+
+        reset::reset_unloaded(&fs, id, confirm, self.beacons.events().clone())
+    }
+
+    /// Writes backup archive of beacon ids from the daemon folder, see [`backup::create`].
+    pub async fn backup(
+        &self,
+        ids: Vec<String>,
+        out: PathBuf,
+        passphrase: Option<String>,
+    ) -> Result<Manifest, BackupError> {
+        let multibeacon = self.multibeacon_path.clone();
+        tokio::task::spawn_blocking(move || {
+            backup::create(&multibeacon, &ids, &out, passphrase.as_deref())
+        })
+        .await
+        .map_err(|err| BackupError::IO(std::io::Error::other(err)))?
+    }
+
+    /// Returns path of a new archive `name` in backups folder of the base folder.
+    pub fn backup_output(&self, name: &str) -> Result<PathBuf, BackupError> {
+        let base = self
+            .multibeacon_path
+            .parent()
+            .unwrap_or(&self.multibeacon_path);
+        backup::output_path(base, name)
+    }
+
+    /// Returns stats of the chain store of the beacon id, the store is read in a blocking task.
+    pub async fn chain_store_stats(&self, id: &str) -> Result<StoreStats, String> {
+        let path = FileStore {
+            beacon_path: self.multibeacon_path.join(id),
+        }
+        .chain_store_path();
+        match spawn_store(move || inspect::stats(&path)).await {
+            Ok(stats) => stats.map_err(|err| err.to_string()),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
     pub fn beacons(&self) -> &MultiBeacon {
         &self.beacons
     }
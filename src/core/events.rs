@@ -0,0 +1,288 @@
+//! Daemon events shared across beacon processes and streamed by control RPC `Events`.
+//!
+//! Stored beacons are additionally published as [`ChainHead`] to local subscribers, see
+//! [`crate::net::ipc`].
+use crate::chain::jitter::RoundTimings;
+use crate::chain::jitter::SharedTimings;
+use crate::chain::time::time_now;
+use crate::protobuf::drand::DaemonEvent;
+
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::Weak;
+use tokio::sync::broadcast;
+
+/// Capacity of events channel, slow subscribers skip the oldest events.
+const EVENTS_CAPACITY: usize = 256;
+/// Amount of the latest events kept for debug dumps.
+const RECENT_EVENTS: usize = 100;
+/// Capacity of chain heads channel, slow subscribers skip the oldest beacons.
+const HEADS_CAPACITY: usize = 64;
+
+/// Beacon stored by a beacon process, previous signature is empty for unchained schemes.
+#[derive(Clone, Debug)]
+pub struct ChainHead {
+    pub beacon_id: String,
+    pub round: u64,
+    pub signature: Vec<u8>,
+    pub previous_signature: Vec<u8>,
+}
+
+/// Structured event of a beacon process.
+pub enum Event<'a> {
+    BeaconStored { round: u64 },
+    ResyncStarted { from: u64, up_to: u64 },
+    ResyncStopped { last: u64, reason: &'a str },
+    SyncSummary { last: u64, summary: &'a str },
+    DkgStatus { epoch: u32, status: &'a str },
+    PeerError { peer: &'a str, reason: &'a str },
+    AuditMismatch { round: u64, peer: &'a str },
+    UnknownIndex { index: u32, peer: &'a str },
+    SpoofedPartial { index: u32, peer: &'a str },
+    Equivocation { round: u64, source: &'a str },
+    LeaveNotice { peer: &'a str },
+    Panic { report: &'a str },
+}
+
+/// Sender side of events channel, cheap to clone.
+#[derive(Clone)]
+pub struct EventSender {
+    tx: broadcast::Sender<DaemonEvent>,
+    heads: broadcast::Sender<ChainHead>,
+    recent: Arc<Mutex<VecDeque<DaemonEvent>>>,
+    /// The last stored round of each beacon id.
+    last_rounds: Arc<Mutex<BTreeMap<String, u64>>>,
+    /// Partials rejected for index unknown to the group per beacon id.
+    unknown_index_partials: Arc<Mutex<BTreeMap<String, u64>>>,
+    /// Partials rejected for index of another node per beacon id.
+    spoofed_partials: Arc<Mutex<BTreeMap<String, u64>>>,
+    /// Round timings of running beacon processes, exported as histograms.
+    timings: Arc<Mutex<BTreeMap<String, Weak<Mutex<RoundTimings>>>>>,
+}
+
+impl EventSender {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(EVENTS_CAPACITY);
+        let (heads, _) = broadcast::channel(HEADS_CAPACITY);
+
+        Self {
+            tx,
+            heads,
+            recent: Arc::default(),
+            last_rounds: Arc::default(),
+            unknown_index_partials: Arc::default(),
+            spoofed_partials: Arc::default(),
+            timings: Arc::default(),
+        }
+    }
+
+    /// Returns the latest events, from the oldest to the newest.
+    pub fn recent(&self) -> Vec<DaemonEvent> {
+        self.recent
+            .lock()
+            .map(|recent| recent.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the last stored round of each beacon id seen in events.
+    ///
+    /// Lock is never awaited, so it is safe to call from a panic hook: empty map is returned
+    /// if the lock is held.
+    pub fn last_rounds(&self) -> BTreeMap<String, u64> {
+        self.last_rounds
+            .try_lock()
+            .map(|rounds| rounds.clone())
+            .unwrap_or_default()
+    }
+
+    /// Registers round timings of a beacon process, entry is dropped once the process is stopped.
+    pub fn register_timings(&self, beacon_id: &str, timings: &SharedTimings) {
+        if let Ok(mut all) = self.timings.lock() {
+            all.insert(beacon_id.to_string(), Arc::downgrade(timings));
+        }
+    }
+
+    /// Returns counters and round timing histograms in Prometheus text exposition format.
+    pub fn metrics(&self) -> String {
+        let mut m = String::new();
+        let name = "drand_partial_unknown_index_total";
+        let _ = writeln!(
+            m,
+            "# HELP {name} Partials rejected for an index not assigned in the group."
+        );
+        let _ = writeln!(m, "# TYPE {name} counter");
+        if let Ok(rejected) = self.unknown_index_partials.lock() {
+            for (id, count) in rejected.iter() {
+                let _ = writeln!(m, "{name}{{beacon_id=\"{id}\"}} {count}");
+            }
+        }
+        let name = "drand_partial_spoofed_index_total";
+        let _ = writeln!(
+            m,
+            "# HELP {name} Partials rejected for an index of another node."
+        );
+        let _ = writeln!(m, "# TYPE {name} counter");
+        if let Ok(spoofed) = self.spoofed_partials.lock() {
+            for (id, count) in spoofed.iter() {
+                let _ = writeln!(m, "{name}{{beacon_id=\"{id}\"}} {count}");
+            }
+        }
+
+        let timings: Vec<(String, SharedTimings)> = self
+            .timings
+            .lock()
+            .map(|mut all| {
+                all.retain(|_, t| t.strong_count() > 0);
+                all.iter()
+                    .filter_map(|(id, t)| Some((id.clone(), t.upgrade()?)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        for (idx, (name, help)) in RoundTimings::METRICS.iter().enumerate() {
+            let _ = writeln!(m, "# HELP {name} {help}");
+            let _ = writeln!(m, "# TYPE {name} histogram");
+            for (id, t) in &timings {
+                if let Ok(t) = t.lock() {
+                    t.histograms()[idx].write_samples(&mut m, name, id);
+                }
+            }
+        }
+
+        m
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DaemonEvent> {
+        self.tx.subscribe()
+    }
+
+    pub fn subscribe_heads(&self) -> broadcast::Receiver<ChainHead> {
+        self.heads.subscribe()
+    }
+
+    /// Publishes stored beacon, signatures are copied only if there are subscribers.
+    pub fn publish_head(
+        &self,
+        beacon_id: &str,
+        round: u64,
+        signature: &[u8],
+        previous_signature: Option<&[u8]>,
+    ) {
+        if self.heads.receiver_count() > 0 {
+            let _ = self.heads.send(ChainHead {
+                beacon_id: beacon_id.to_string(),
+                round,
+                signature: signature.to_vec(),
+                previous_signature: previous_signature.unwrap_or_default().to_vec(),
+            });
+        }
+    }
+
+    /// Emits event for given beacon id, the latest events are kept for [`Self::recent`].
+    pub fn emit(&self, beacon_id: &str, event: &Event) {
+        let (kind, round, detail) = match event {
+            Event::BeaconStored { round } => ("beacon_stored", *round, String::new()),
+            Event::ResyncStarted { from, up_to } => {
+                ("resync_started", *from, format!("up_to {up_to}"))
+            }
+            Event::ResyncStopped { last, reason } => ("resync_stopped", *last, (*reason).into()),
+            Event::SyncSummary { last, summary } => ("sync_summary", *last, (*summary).into()),
+            Event::DkgStatus { epoch, status } => {
+                ("dkg_status", 0, format!("epoch {epoch}, status {status}"))
+            }
+            Event::PeerError { peer, reason } => ("peer_error", 0, format!("{peer}: {reason}")),
+            Event::AuditMismatch { round, peer } => ("audit_mismatch", *round, (*peer).into()),
+            Event::Equivocation { round, source } => ("equivocation", *round, (*source).into()),
+            Event::LeaveNotice { peer } => ("leave_notice", 0, (*peer).into()),
+            Event::UnknownIndex { index, peer } => {
+                ("unknown_index", 0, format!("{peer}: index {index}"))
+            }
+            Event::SpoofedPartial { index, peer } => {
+                ("spoofed_partial", 0, format!("{peer}: index {index}"))
+            }
+            Event::Panic { report } => ("panic", 0, (*report).into()),
+        };
+        match event {
+            Event::BeaconStored { round } => {
+                if let Ok(mut rounds) = self.last_rounds.lock() {
+                    rounds.insert(beacon_id.to_string(), *round);
+                }
+            }
+            Event::UnknownIndex { .. } => {
+                if let Ok(mut rejected) = self.unknown_index_partials.lock() {
+                    *rejected.entry(beacon_id.to_string()).or_default() += 1;
+                }
+            }
+            Event::SpoofedPartial { .. } => {
+                if let Ok(mut spoofed) = self.spoofed_partials.lock() {
+                    *spoofed.entry(beacon_id.to_string()).or_default() += 1;
+                }
+            }
+            _ => {}
+        }
+        let event = DaemonEvent {
+            beacon_id: beacon_id.to_string(),
+            kind: kind.to_string(),
+            round,
+            detail,
+            timestamp_ms: time_now().as_millis().try_into().unwrap_or_default(),
+        };
+        if let Ok(mut recent) = self.recent.lock() {
+            if recent.len() == RECENT_EVENTS {
+                recent.pop_front();
+            }
+            recent.push_back(event.clone());
+        }
+        if self.tx.receiver_count() > 0 {
+            let _ = self.tx.send(event);
+        }
+    }
+}
+
+impl Default for EventSender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timings_are_exported() {
+        let events = EventSender::new();
+        let timings = SharedTimings::default();
+        events.register_timings("default", &timings);
+        timings.lock().unwrap().partial.observe(20);
+
+        let m = events.metrics();
+        assert!(m.contains("# TYPE drand_partial_delay_ms histogram"));
+        assert!(m.contains("drand_partial_delay_ms_count{beacon_id=\"default\"} 1"));
+        assert!(m.contains("drand_aggregation_delay_ms_count{beacon_id=\"default\"} 0"));
+
+        // Stopped beacon process is not exported.
+        drop(timings);
+        assert!(!events.metrics().contains("beacon_id=\"default\""));
+    }
+
+    #[test]
+    fn rejected_partials_are_counted_separately() {
+        let events = EventSender::new();
+        let (index, peer) = (2, "10.0.0.2");
+        events.emit("default", &Event::UnknownIndex { index, peer });
+        events.emit("default", &Event::SpoofedPartial { index, peer });
+        events.emit("default", &Event::SpoofedPartial { index, peer });
+
+        let m = events.metrics();
+        assert!(m.contains("drand_partial_unknown_index_total{beacon_id=\"default\"} 1"));
+        assert!(m.contains("drand_partial_spoofed_index_total{beacon_id=\"default\"} 2"));
+        let kinds: Vec<String> = events.recent().into_iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            ["unknown_index", "spoofed_partial", "spoofed_partial"]
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Graceful exit of a group member, see `drand dkg leave`.
+//!
+//! The leaving node signs a notice the same way as status requests (see [`super::remote_status`])
+//! and sends it to other members of the latest group. Members which verify the notice report it
+//! as `leave_notice` event, so the operator of the next leader knows to pass the node with
+//! `generate-proposal --leaver`. Golang nodes do not serve notices.
+//!
+//! Group of the reshare is accepted by the leaving node only if it continues the local chain,
+//! see [`check_group`].
+use super::beacon::BeaconProcess;
+use super::events::Event;
+use super::remote_status::authorize_member;
+use super::remote_status::sign_auth;
+use super::remote_status::RemoteStatusError;
+
+use crate::key::group::Group;
+use crate::key::keys::Pair;
+use crate::key::Scheme;
+use crate::net::protocol::ProtocolClient;
+use crate::net::utils::Address;
+use crate::net::utils::Callback;
+use crate::protobuf::drand::ChainInfoPacket;
+use crate::protobuf::drand::LeaveNoticeRequest;
+use crate::protobuf::drand::Metadata;
+use crate::transport::drand::GroupPacket;
+
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tracing::warn;
+
+/// Kind of signed leave notices.
+const LEAVE: &str = "Leave:";
+/// Timeout of leave notice to a single member.
+const NOTICE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Group does not continue the local chain, the value is the differing parameter.
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[error("{0} of the group differs from chain info")]
+pub struct GroupMismatch(pub &'static str);
+
+impl<S: Scheme> BeaconProcess<S> {
+    /// Verifies leave notice of a member of the latest group and reports it as an event.
+    pub(super) fn leave_notice(&self, r: &LeaveNoticeRequest, cb: Callback<(), RemoteStatusError>) {
+        cb.reply(self.verified_notice(r));
+    }
+
+    fn verified_notice(&self, r: &LeaveNoticeRequest) -> Result<(), RemoteStatusError> {
+        let group = self.fs().load_group::<S>()?;
+        authorize_member(
+            LEAVE,
+            &group,
+            self.id(),
+            self.clock().now(),
+            &r.address,
+            r.timestamp_ms,
+            &r.signature,
+        )?;
+        warn!(parent: self.log(), "leave notice: member {} is leaving, include it in the next proposal with --leaver", r.address);
+        self.events()
+            .emit(self.id(), &Event::LeaveNotice { peer: &r.address });
+
+        Ok(())
+    }
+}
+
+/// Returns leave notice of the key pair owner signed at time `now`.
+pub fn sign_notice<S: Scheme>(
+    pair: &Pair<S>,
+    beacon_id: &str,
+    now: Duration,
+) -> Result<LeaveNoticeRequest, RemoteStatusError> {
+    let address = pair.public_identity().address.to_string();
+    let (timestamp_ms, signature) =
+        sign_auth::<S>(LEAVE, beacon_id, &address, pair.private_key(), now)?;
+
+    Ok(LeaveNoticeRequest {
+        address,
+        timestamp_ms,
+        signature,
+        metadata: Some(Metadata::with_id(beacon_id.to_string())),
+    })
+}
+
+/// Sends leave notice of the key pair owner to other members of `group`, returns the result per member.
+pub async fn announce<S: Scheme>(
+    pair: &Pair<S>,
+    group: &Group<S>,
+    beacon_id: &str,
+    now: Duration,
+) -> Result<Vec<(Address, anyhow::Result<()>)>, RemoteStatusError> {
+    let notice = sign_notice(pair, beacon_id, now)?;
+    let mut tasks = JoinSet::new();
+    for peer in group
+        .nodes()
+        .iter()
+        .map(|n| n.public().address.clone())
+        .filter(|a| *a != pair.public_identity().address)
+    {
+        let notice = notice.clone();
+        tasks.spawn(async move {
+            let sent = tokio::time::timeout(NOTICE_TIMEOUT, async {
+                ProtocolClient::new(&peer).await?.leave_notice(notice).await
+            })
+            .await
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("timeout")));
+            (peer, sent)
+        });
+    }
+
+    let mut sent = vec![];
+    while let Some(task) = tasks.join_next().await {
+        if let Ok(result) = task {
+            sent.push(result);
+        }
+    }
+
+    Ok(sent)
+}
+
+/// Checks that `group` continues the chain of `info`: reshare keeps the distributed key and
+/// parameters of the chain.
+pub fn check_group(group: &GroupPacket, info: &ChainInfoPacket) -> Result<(), GroupMismatch> {
+    if group.dist_key.first() != Some(&info.public_key) {
+        return Err(GroupMismatch("public key"));
+    }
+    if i64::try_from(group.genesis_time).ok() != Some(info.genesis_time) {
+        return Err(GroupMismatch("genesis time"));
+    }
+    if group.period.get_value() != info.period {
+        return Err(GroupMismatch("period"));
+    }
+    if group.scheme_id != info.scheme_id {
+        return Err(GroupMismatch("scheme"));
+    }
+    if group.genesis_seed != info.group_hash {
+        return Err(GroupMismatch("genesis seed"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::time::time_now;
+    use crate::key::keys::Identity;
+    use crate::key::node::Node;
+    use energon::drand::schemes::DefaultScheme;
+
+    fn pair(address: &str) -> Pair<DefaultScheme> {
+        Pair::generate(Address::precheck(address).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn notice_of_member_is_verified() {
+        let (a, b) = (pair("127.0.0.1:1001"), pair("127.0.0.1:1002"));
+        let nodes = [&a, &b]
+            .iter()
+            .zip(0..)
+            .map(|(p, index)| {
+                let identity = p.public_identity();
+                Node::new(
+                    Identity::new(
+                        identity.address.clone(),
+                        identity.key().clone(),
+                        identity.signature().clone(),
+                    ),
+                    index,
+                )
+            })
+            .collect();
+        let group = Group {
+            nodes,
+            ..Default::default()
+        };
+        let now = time_now();
+        let verify = |r: &LeaveNoticeRequest, beacon_id: &str| {
+            authorize_member(
+                LEAVE,
+                &group,
+                beacon_id,
+                now,
+                &r.address,
+                r.timestamp_ms,
+                &r.signature,
+            )
+        };
+
+        let notice = sign_notice(&b, "default", now).unwrap();
+        verify(&notice, "default").unwrap();
+        assert!(matches!(
+            verify(&notice, "other"),
+            Err(RemoteStatusError::InvalidSignature)
+        ));
+        assert!(matches!(
+            verify(
+                &sign_notice(&pair("127.0.0.1:1003"), "default", now).unwrap(),
+                "default"
+            ),
+            Err(RemoteStatusError::NotMember(_))
+        ));
+
+        // Signed status request of the member is not a leave notice.
+        let address = b.public_identity().address.to_string();
+        let (timestamp_ms, signature) =
+            sign_auth::<DefaultScheme>("Status:", "default", &address, b.private_key(), now)
+                .unwrap();
+        let status = LeaveNoticeRequest {
+            timestamp_ms,
+            signature,
+            ..notice.clone()
+        };
+        assert!(matches!(
+            verify(&status, "default"),
+            Err(RemoteStatusError::InvalidSignature)
+        ));
+
+        let late = sign_notice(&b, "default", now - Duration::from_secs(60)).unwrap();
+        assert!(matches!(
+            verify(&late, "default"),
+            Err(RemoteStatusError::Expired)
+        ));
+    }
+
+    #[test]
+    fn group_continues_chain() {
+        let info = ChainInfoPacket {
+            public_key: vec![1; 48],
+            period: 3,
+            genesis_time: 1_692_803_367,
+            group_hash: vec![2; 32],
+            scheme_id: DefaultScheme::ID.into(),
+            ..Default::default()
+        };
+        let group = || GroupPacket {
+            period: 3.into(),
+            genesis_time: 1_692_803_367,
+            genesis_seed: vec![2; 32],
+            dist_key: vec![vec![1; 48], vec![3; 48]],
+            scheme_id: DefaultScheme::ID.into(),
+            ..Default::default()
+        };
+        check_group(&group(), &info).unwrap();
+
+        let mut other = group();
+        other.dist_key[0] = vec![4; 48];
+        assert_eq!(check_group(&other, &info), Err(GroupMismatch("public key")));
+        let mut other = group();
+        other.genesis_time += 1;
+        assert_eq!(
+            check_group(&other, &info),
+            Err(GroupMismatch("genesis time"))
+        );
+        let mut other = group();
+        other.period = 30.into();
+        assert_eq!(check_group(&other, &info), Err(GroupMismatch("period")));
+        let mut other = group();
+        other.scheme_id = "bls-unchained-g1-rfc9380".into();
+        assert_eq!(check_group(&other, &info), Err(GroupMismatch("scheme")));
+        let mut other = group();
+        other.genesis_seed = vec![5; 32];
+        assert_eq!(
+            check_group(&other, &info),
+            Err(GroupMismatch("genesis seed"))
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! Command channel of beacon process with priorities and introspection of queued commands.
+//!
+//! Commands are split by [`Priority`] into bounded lanes, the beacon process always takes the
+//! most urgent queued command first, so a burst of peer or operator requests can not delay DKG
+//! and lifecycle commands. Partial signatures bypass the mailbox entirely, see
+//! [`super::multibeacon::BeaconHandler::partial_tx`].
+//!
+//! Every command holds a [`QueueEntry`] from the moment its sending starts until it is received
+//! by the beacon process, so commands waiting for lane capacity are accounted as well. Entry
+//! of a cancelled send is released together with the send future.
+use super::beacon::Actions;
+use super::beacon::BeaconCmd;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::SendError;
+
+/// Capacity of the lane for [`Priority::Critical`] commands.
+const CRITICAL_CAPACITY: usize = 8;
+/// Capacity of the lane for [`Priority::Peer`] commands.
+const PEER_CAPACITY: usize = 4;
+/// Capacity of the lane for [`Priority::Admin`] commands.
+const ADMIN_CAPACITY: usize = 2;
+
+/// Scheduling class of [`BeaconCmd`], lanes are drained in declaration order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// DKG progress and lifecycle of the beacon process.
+    Critical,
+    /// Requests of other nodes.
+    Peer,
+    /// Requests of the local operator.
+    Admin,
+}
+
+impl BeaconCmd {
+    pub fn priority(&self) -> Priority {
+        match self {
+            Self::DkgActions(Actions::Status(_) | Actions::Reset(..)) => Priority::Admin,
+            Self::DkgActions(_) | Self::FinishedDkg | Self::Shutdown(_) => Priority::Critical,
+            Self::IdentityRequest(_)
+            | Self::Sync(..)
+            | Self::ChainInfo(_)
+            | Self::Group(..)
+            | Self::MerkleProof(..)
+            | Self::PublicRand(..)
+            | Self::PeerStatus(..)
+            | Self::LeaveNotice(..) => Priority::Peer,
+            Self::Status(_) | Self::Resync(..) | Self::RemoteStatus(..) | Self::Follow(..) => {
+                Priority::Admin
+            }
+        }
+    }
+}
+
+/// Creates command channel of beacon process.
+pub fn channel() -> (CmdSender, CmdReceiver) {
+    let (critical_tx, critical_rx) = mpsc::channel(CRITICAL_CAPACITY);
+    let (peer_tx, peer_rx) = mpsc::channel(PEER_CAPACITY);
+    let (admin_tx, admin_rx) = mpsc::channel(ADMIN_CAPACITY);
+
+    (
+        CmdSender {
+            critical: critical_tx,
+            peer: peer_tx,
+            admin: admin_tx,
+            queue: Arc::default(),
+        },
+        CmdReceiver {
+            critical: critical_rx,
+            peer: peer_rx,
+            admin: admin_rx,
+        },
+    )
+}
+
+/// Commands which are sent but not yet received by the beacon process.
+#[derive(Default)]
+pub struct QueueStats {
+    inner: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    next: u64,
+    // Ordered by sequence number, the first entry is the oldest one.
+    queued: BTreeMap<u64, Instant>,
+}
+
+impl QueueStats {
+    fn push(self: &Arc<Self>) -> QueueEntry {
+        let mut entries = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        let seq = entries.next;
+        entries.next += 1;
+        entries.queued.insert(seq, Instant::now());
+
+        QueueEntry {
+            queue: Arc::clone(self),
+            seq,
+        }
+    }
+
+    /// Returns amount of queued commands and age of the oldest one.
+    pub fn snapshot(&self) -> (usize, Option<Duration>) {
+        let entries = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        let oldest = entries.queued.values().next().map(Instant::elapsed);
+
+        (entries.queued.len(), oldest)
+    }
+}
+
+/// Registration of a queued command, released on drop.
+struct QueueEntry {
+    queue: Arc<QueueStats>,
+    seq: u64,
+}
+
+impl Drop for QueueEntry {
+    fn drop(&mut self) {
+        self.queue
+            .inner
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .queued
+            .remove(&self.seq);
+    }
+}
+
+struct Queued {
+    cmd: BeaconCmd,
+    _entry: QueueEntry,
+}
+
+/// Sender of commands into beacon process.
+#[derive(Clone)]
+pub struct CmdSender {
+    critical: mpsc::Sender<Queued>,
+    peer: mpsc::Sender<Queued>,
+    admin: mpsc::Sender<Queued>,
+    queue: Arc<QueueStats>,
+}
+
+impl CmdSender {
+    /// Sends command into the lane of its priority, waiting for capacity of that lane only.
+    /// The command is returned back if the beacon process is stopped.
+    pub async fn send(&self, cmd: BeaconCmd) -> Result<(), SendError<BeaconCmd>> {
+        let tx = match cmd.priority() {
+            Priority::Critical => &self.critical,
+            Priority::Peer => &self.peer,
+            Priority::Admin => &self.admin,
+        };
+        let queued = Queued {
+            cmd,
+            _entry: self.queue.push(),
+        };
+        tx.send(queued).await.map_err(|err| SendError(err.0.cmd))
+    }
+
+    /// Completes once the beacon process is stopped.
+    pub async fn closed(&self) {
+        // Lanes are closed together with the receiver.
+        self.critical.closed().await;
+    }
+
+    pub fn queue(&self) -> &QueueStats {
+        &self.queue
+    }
+}
+
+/// Receiving half of the command channel, owned by the beacon process.
+pub struct CmdReceiver {
+    critical: mpsc::Receiver<Queued>,
+    peer: mpsc::Receiver<Queued>,
+    admin: mpsc::Receiver<Queued>,
+}
+
+impl CmdReceiver {
+    /// Returns the most urgent queued command, lower lanes are served only while upper lanes
+    /// are empty.
+    pub async fn recv(&mut self) -> Option<BeaconCmd> {
+        // Entry is released on receive.
+        let queued = tokio::select! {
+            biased;
+            Some(queued) = self.critical.recv() => queued,
+            Some(queued) = self.peer.recv() => queued,
+            Some(queued) = self.admin.recv() => queued,
+            else => return None,
+        };
+
+        Some(queued.cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::utils::Callback;
+
+    fn status() -> BeaconCmd {
+        BeaconCmd::Status(Callback::new().0)
+    }
+
+    #[tokio::test]
+    async fn queued_commands() {
+        let (tx, mut rx) = channel();
+        assert_eq!(tx.queue().snapshot(), (0, None));
+
+        for _ in 0..ADMIN_CAPACITY {
+            tx.send(status()).await.unwrap();
+        }
+        // Full admin lane does not block other lanes.
+        tx.send(BeaconCmd::FinishedDkg).await.unwrap();
+        let blocked = tokio::spawn({
+            let tx = tx.clone();
+            async move { tx.send(status()).await.is_ok() }
+        });
+        while tx.queue().snapshot().0 < ADMIN_CAPACITY + 2 {
+            tokio::task::yield_now().await;
+        }
+        let (_, oldest) = tx.queue().snapshot();
+        assert!(oldest.is_some());
+
+        // Critical command is taken first although it is sent last.
+        assert_eq!(rx.recv().await.unwrap().priority(), Priority::Critical);
+        assert_eq!(rx.recv().await.unwrap().priority(), Priority::Admin);
+        assert!(blocked.await.unwrap());
+        for _ in 0..ADMIN_CAPACITY {
+            assert!(rx.recv().await.is_some());
+        }
+        assert_eq!(tx.queue().snapshot(), (0, None));
+
+        // Entry of a cancelled send is released.
+        for _ in 0..ADMIN_CAPACITY {
+            tx.send(status()).await.unwrap();
+        }
+        let cancelled = tokio::time::timeout(Duration::from_millis(10), tx.send(status())).await;
+        assert!(cancelled.is_err());
+        assert_eq!(tx.queue().snapshot().0, ADMIN_CAPACITY);
+
+        drop(rx);
+        assert!(tx.send(BeaconCmd::FinishedDkg).await.is_err());
+        assert_eq!(tx.queue().snapshot().0, 0);
+    }
+}
@@ -1,4 +1,14 @@
+pub mod archiver;
+pub mod backups;
 pub mod beacon;
+pub mod crash;
 // pub mod chain;
 pub mod daemon;
+pub mod dump;
+pub mod events;
+pub mod leave;
+pub mod mailbox;
 pub mod multibeacon;
+pub mod remote_status;
+pub mod runtime;
+pub mod webhooks;
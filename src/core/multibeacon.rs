@@ -1,14 +1,23 @@
 use super::beacon::BeaconCmd;
 use super::beacon::BeaconID;
 use super::beacon::BeaconProcess;
+use super::events::EventSender;
+use super::mailbox::CmdSender;
 
+use crate::chain::time::SharedClock;
+use crate::chain::StorageMode;
 use crate::cli::Config;
+use crate::dkg::fanout::fanout_of;
+use crate::dkg::fanout::BeaconFanOut;
+use crate::dkg::fanout::FanOut;
 use crate::key::store::FileStore;
 use crate::key::store::FileStoreError;
 use crate::key::Scheme;
 use crate::net::pool::Pool;
 use crate::net::pool::PoolSender;
 use crate::net::protocol::PartialMsg;
+use crate::net::protocol::SyncMemory;
+use crate::net::utils::Callback;
 
 use arc_swap::ArcSwap;
 use arc_swap::ArcSwapAny;
@@ -17,10 +26,20 @@ use energon::drand::schemes::DefaultScheme;
 use energon::drand::schemes::SigsOnG1Scheme;
 use energon::drand::schemes::UnchainedScheme;
 
+use std::error::Error;
 use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
-use tokio::sync::mpsc::Sender;
+use tokio::sync::oneshot;
+use tokio::time::timeout_at;
+use tokio::time::Instant;
+use tracing::warn;
+
+/// Default time to wait for reply of a beacon process to a control command.
+pub const DEFAULT_CALLBACK_TIMEOUT_SECS: u64 = 60;
 
 type Snapshot = Guard<Arc<Vec<BeaconHandler>>>;
 
@@ -29,16 +48,57 @@ type Snapshot = Guard<Arc<Vec<BeaconHandler>>>;
 pub struct BeaconHandler {
     pub beacon_id: BeaconID,
     /// Sender for beacon commands
-    pub process_tx: Sender<BeaconCmd>,
+    pub process_tx: CmdSender,
     /// Sender for partial signature packets (hot path)
     pub partial_tx: mpsc::Sender<PartialMsg>,
+    /// Replies to control commands which were not received in time.
+    pub timeouts: Arc<CallbackTimeouts>,
+}
+
+/// Counters of replies of the beacon process which were not received within callback timeout.
+#[derive(Default)]
+pub struct CallbackTimeouts {
+    total: AtomicU64,
+    pending: AtomicU64,
+}
+
+impl CallbackTimeouts {
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// Returns amount of timed out replies which are still not received.
+    pub fn pending(&self) -> u64 {
+        self.pending.load(Ordering::Relaxed)
+    }
+
+    /// Records timeout, the late reply is awaited in background if command has been sent.
+    fn record<T: Send + 'static>(self: &Arc<Self>, rx: Option<oneshot::Receiver<T>>) {
+        self.total.fetch_add(1, Ordering::Relaxed);
+        if let Some(rx) = rx {
+            self.pending.fetch_add(1, Ordering::Relaxed);
+            let timeouts = Arc::clone(self);
+            tokio::spawn(async move {
+                // Reply is received late or the beacon process is stopped.
+                let _ = rx.await;
+                timeouts.pending.fetch_sub(1, Ordering::Relaxed);
+            });
+        }
+    }
 }
 
 impl BeaconHandler {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         fs: FileStore,
         pool: PoolSender,
+        events: EventSender,
+        clock: SharedClock,
+        sync_memory: SyncMemory,
         private_listen: String,
+        shadow: bool,
+        storage: StorageMode,
+        fanout: FanOut,
     ) -> Result<Self, FileStoreError> {
         let pair = &fs.load_key_pair_toml()?;
         let scheme = pair
@@ -46,15 +106,42 @@ impl BeaconHandler {
             .ok_or(FileStoreError::InvalidPairSchemes)?;
 
         let handler = match scheme {
-            DefaultScheme::ID => {
-                BeaconProcess::<DefaultScheme>::run(fs, pair, pool, private_listen)?
-            }
-            UnchainedScheme::ID => {
-                BeaconProcess::<UnchainedScheme>::run(fs, pair, pool, private_listen)?
-            }
-            SigsOnG1Scheme::ID => {
-                BeaconProcess::<SigsOnG1Scheme>::run(fs, pair, pool, private_listen)?
-            }
+            DefaultScheme::ID => BeaconProcess::<DefaultScheme>::run(
+                fs,
+                pair,
+                pool,
+                events,
+                clock,
+                sync_memory,
+                private_listen,
+                shadow,
+                storage,
+                fanout,
+            )?,
+            UnchainedScheme::ID => BeaconProcess::<UnchainedScheme>::run(
+                fs,
+                pair,
+                pool,
+                events,
+                clock,
+                sync_memory,
+                private_listen,
+                shadow,
+                storage,
+                fanout,
+            )?,
+            SigsOnG1Scheme::ID => BeaconProcess::<SigsOnG1Scheme>::run(
+                fs,
+                pair,
+                pool,
+                events,
+                clock,
+                sync_memory,
+                private_listen,
+                shadow,
+                storage,
+                fanout,
+            )?,
             _ => return Err(FileStoreError::FailedInitID)?,
         };
 
@@ -71,19 +158,40 @@ pub struct MultiBeacon {
     beacons: ArcSwapAny<Arc<Vec<BeaconHandler>>>,
     /// Sender for partial beacons pool.
     tx_pool: PoolSender,
+    /// Sender for daemon events, shared across beacon ids.
+    events: EventSender,
+    /// Clock shared across beacon ids.
+    clock: SharedClock,
+    /// Memory of beacons buffered across all sync streams of the node.
+    sync_memory: SyncMemory,
+    /// Time to wait for reply of a beacon process to a control command.
+    callback_timeout: Duration,
+    /// Beacon ids running in shadow mode.
+    shadow: Vec<String>,
+    /// Beacon ids with [`StorageMode::Derived`] chain stores.
+    derived_storage: Vec<String>,
+    /// Beacon ids with [`StorageMode::Precomputed`] chain stores.
+    precomputed_storage: Vec<String>,
+    /// Fan-out of DKG bundles per beacon id.
+    dkg_fanout: Vec<BeaconFanOut>,
 }
 
 impl MultiBeacon {
     /// This call is success only if *all* detected storages has minimal valid structure.
     /// Succesfull value contains a turple with valid absolute path to multibeacon folder.
-    pub fn new(config: Config) -> Result<(PathBuf, Self), FileStoreError> {
+    pub fn new(config: Config, clock: SharedClock) -> Result<(PathBuf, Self), FileStoreError> {
         let private_listen = config.private_listen.clone();
+        let callback_timeout = Duration::from_secs(config.callback_timeout.max(1));
 
         // Connection pool for partial beacon packets is shared across beacon ids.
         let pool_span = tracing::info_span!("", partials_pool = &private_listen);
         let pool = Pool::start(pool_span);
+        let events = EventSender::new();
+        // Memory of buffered sync streams is shared across beacon ids.
+        let sync_memory = SyncMemory::new(config.sync_buffer_mb << 20);
 
-        let (multibeacon_path, fstores) = FileStore::read_multibeacon_folder(&config.folder)?;
+        let (multibeacon_path, fstores) =
+            FileStore::read_multibeacon_subset(&config.folder, &config.only)?;
         let beacons: Vec<BeaconHandler> = match &config.id {
             // Load single id
             Some(id) => {
@@ -91,17 +199,56 @@ impl MultiBeacon {
                     .into_iter()
                     .find(|fs| fs.get_beacon_id() == Some(id))
                     .ok_or(FileStoreError::BeaconNotFound)?;
-                vec![BeaconHandler::new(fs, pool.clone(), config.private_listen)?]
+                vec![BeaconHandler::new(
+                    fs,
+                    pool.clone(),
+                    events.clone(),
+                    clock.clone(),
+                    sync_memory.clone(),
+                    config.private_listen,
+                    config.shadow.contains(id),
+                    storage_mode(&config.derived_storage, &config.precomputed_storage, id),
+                    fanout_of(&config.dkg_fanout, id),
+                )?]
             }
-            // Load all ids
+            // Load all ids, or listed with `--only`
             None => fstores
                 .into_iter()
-                .map(|fs| BeaconHandler::new(fs, pool.clone(), config.private_listen.clone()))
+                .map(|fs| {
+                    let shadow = fs
+                        .get_beacon_id()
+                        .is_some_and(|id| config.shadow.iter().any(|s| s == id));
+                    let storage = fs.get_beacon_id().map_or(StorageMode::Full, |id| {
+                        storage_mode(&config.derived_storage, &config.precomputed_storage, id)
+                    });
+                    let fanout = fs
+                        .get_beacon_id()
+                        .map_or(FanOut::Full, |id| fanout_of(&config.dkg_fanout, id));
+                    BeaconHandler::new(
+                        fs,
+                        pool.clone(),
+                        events.clone(),
+                        clock.clone(),
+                        sync_memory.clone(),
+                        config.private_listen.clone(),
+                        shadow,
+                        storage,
+                        fanout,
+                    )
+                })
                 .collect::<Result<_, _>>()?,
         };
         let multibeacon = Self {
             beacons: ArcSwap::from(Arc::new(beacons)),
             tx_pool: pool,
+            events,
+            clock,
+            sync_memory,
+            callback_timeout,
+            shadow: config.shadow,
+            derived_storage: config.derived_storage,
+            precomputed_storage: config.precomputed_storage,
+            dkg_fanout: config.dkg_fanout,
         };
 
         Ok((multibeacon_path, multibeacon))
@@ -134,6 +281,70 @@ impl MultiBeacon {
         Ok(())
     }
 
+    /// Sends a command built by `cmd` to the beacon identified by `id` and awaits the reply.
+    ///
+    /// Returns [`BeaconHandlerError::Timeout`] if the command is not sent or replied within the
+    /// callback timeout, `name` of the command is reported in the error.
+    pub async fn call<T, E>(
+        &self,
+        id: &str,
+        name: &'static str,
+        cmd: impl FnOnce(Callback<T, E>) -> BeaconCmd,
+    ) -> Result<Result<T, E>, BeaconHandlerError>
+    where
+        T: Send + 'static,
+        E: Error + Send + 'static,
+    {
+        let (process_tx, timeouts) = {
+            let store = self.beacons.load();
+            let handler = store
+                .iter()
+                .find(|h| h.beacon_id.is_eq(id))
+                .ok_or(BeaconHandlerError::UnknownID)?;
+            (handler.process_tx.clone(), Arc::clone(&handler.timeouts))
+        };
+        let deadline = Instant::now() + self.callback_timeout;
+        let timed_out = |timeouts: &CallbackTimeouts| {
+            warn!(
+                "beacon id '{id}': no reply to {name} within {:?}",
+                self.callback_timeout
+            );
+            BeaconHandlerError::Timeout {
+                cmd: name,
+                secs: self.callback_timeout.as_secs(),
+                pending: timeouts.pending(),
+            }
+        };
+
+        let (tx, mut rx) = Callback::new();
+        match timeout_at(deadline, process_tx.send(cmd(tx))).await {
+            Ok(Ok(())) => (),
+            Ok(Err(_)) => return Err(BeaconHandlerError::SendError),
+            Err(_) => {
+                timeouts.record::<()>(None);
+                return Err(timed_out(&timeouts));
+            }
+        }
+        match timeout_at(deadline, &mut rx).await {
+            Ok(Ok(reply)) => Ok(reply),
+            Ok(Err(_)) => Err(BeaconHandlerError::NoReply(name)),
+            Err(_) => {
+                let err = timed_out(&timeouts);
+                timeouts.record(Some(rx));
+                Err(err)
+            }
+        }
+    }
+
+    /// Returns callback timeouts of the beacon identified by `id`.
+    pub fn callback_timeouts(&self, id: &str) -> Option<Arc<CallbackTimeouts>> {
+        self.beacons
+            .load()
+            .iter()
+            .find(|h| h.beacon_id.is_eq(id))
+            .map(|h| Arc::clone(&h.timeouts))
+    }
+
     pub async fn send_partial(&self, partial: PartialMsg) -> Result<(), BeaconHandlerError> {
         let id = partial.0.packet.metadata.as_ref().map_or_else(
             || Err(BeaconHandlerError::MetadataRequired),
@@ -158,6 +369,43 @@ impl MultiBeacon {
     pub(super) fn get_pool(&self) -> PoolSender {
         self.tx_pool.clone()
     }
+
+    pub fn events(&self) -> &EventSender {
+        &self.events
+    }
+
+    pub(super) fn clock(&self) -> SharedClock {
+        self.clock.clone()
+    }
+
+    pub fn sync_memory(&self) -> &SyncMemory {
+        &self.sync_memory
+    }
+
+    /// Returns `true` if the beacon id is configured to run in shadow mode.
+    pub(super) fn is_shadow(&self, id: &str) -> bool {
+        self.shadow.iter().any(|s| s == id)
+    }
+
+    /// Returns chain store mode configured for the beacon id.
+    pub(super) fn storage_mode(&self, id: &str) -> StorageMode {
+        storage_mode(&self.derived_storage, &self.precomputed_storage, id)
+    }
+
+    /// Returns fan-out of DKG bundles configured for the beacon id.
+    pub(super) fn fanout(&self, id: &str) -> FanOut {
+        fanout_of(&self.dkg_fanout, id)
+    }
+}
+
+fn storage_mode(derived: &[String], precomputed: &[String], id: &str) -> StorageMode {
+    if derived.iter().any(|d| d == id) {
+        StorageMode::Derived
+    } else if precomputed.iter().any(|p| p == id) {
+        StorageMode::Precomputed
+    } else {
+        StorageMode::Full
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -168,6 +416,39 @@ pub enum BeaconHandlerError {
     SendError,
     #[error("Beacon id is already loaded")]
     AlreadyLoaded,
+    #[error("failed to import beacon id: {0}")]
+    Import(FileStoreError),
     #[error("Packet metadata is missing")]
     MetadataRequired,
+    #[error("no reply to {cmd} within {secs}s, {pending} earlier replies are pending, beacon process might be wedged")]
+    Timeout {
+        cmd: &'static str,
+        secs: u64,
+        pending: u64,
+    },
+    #[error("beacon process is stopped before reply to {0}")]
+    NoReply(&'static str),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn late_reply_is_pending() {
+        let timeouts = Arc::new(CallbackTimeouts::default());
+        timeouts.record::<()>(None);
+        assert_eq!((timeouts.total(), timeouts.pending()), (1, 0));
+
+        let (tx, rx) = oneshot::channel::<u64>();
+        timeouts.record(Some(rx));
+        assert_eq!((timeouts.total(), timeouts.pending()), (2, 1));
+
+        // Late reply releases the pending counter.
+        tx.send(5).unwrap();
+        while timeouts.pending() > 0 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(timeouts.total(), 2);
+    }
 }
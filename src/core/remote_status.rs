@@ -0,0 +1,346 @@
+//! Status of group members over protocol `Status` RPC, see `drand util remote-status`.
+//!
+//! Requests carry a signature of the requester key over beacon id, requester address and
+//! request time. Only members of the latest group are served and the request time must be
+//! within [`REQUEST_VALIDITY`] of the local clock, so chain status is not exposed to
+//! arbitrary clients and captured requests can not be replayed later.
+use super::beacon::BeaconProcess;
+
+use crate::chain::StoreError;
+use crate::key::group::Group;
+use crate::key::store::FileStoreError;
+use crate::key::Scheme;
+use crate::net::protocol::ProtocolClient;
+use crate::net::utils::Address;
+use crate::net::utils::Callback;
+use crate::protobuf::drand::Metadata;
+use crate::protobuf::drand::StatusRequest;
+use crate::protobuf::drand::StatusResponse;
+
+use energon::points::SigPoint;
+use energon::traits::Affine;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tracing::debug;
+
+/// Maximum difference between request time and local clock.
+const REQUEST_VALIDITY: Duration = Duration::from_secs(30);
+/// Kind of signed status requests, see [`auth_msg`].
+const STATUS: &str = "Status:";
+/// Timeout of status request to a single peer.
+const PEER_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(thiserror::Error, Debug)]
+pub enum RemoteStatusError {
+    #[error("requester {0} is not a member of the latest group")]
+    NotMember(String),
+    #[error("invalid request signature")]
+    InvalidSignature,
+    #[error("request time is not within {}s of local clock", REQUEST_VALIDITY.as_secs())]
+    Expired,
+    #[error("failed to sign status request")]
+    Sign,
+    #[error("group: {0}")]
+    Group(#[from] FileStoreError),
+    #[error("chain store: {0}")]
+    Store(#[from] StoreError),
+    #[error("chain module is not available")]
+    ChainClosed,
+}
+
+/// Returns the message signed by requester, `kind` separates signatures of different requests.
+fn auth_msg(kind: &str, beacon_id: &str, address: &str, timestamp_ms: u64) -> Vec<u8> {
+    [
+        kind.as_bytes(),
+        beacon_id.as_bytes(),
+        "\n".as_bytes(),
+        address.as_bytes(),
+        "\n".as_bytes(),
+        &timestamp_ms.to_be_bytes(),
+    ]
+    .concat()
+}
+
+impl<S: Scheme> BeaconProcess<S> {
+    /// Replies with status of this node if the request is signed by a member of the latest group.
+    pub(super) fn peer_status(
+        &self,
+        request: StatusRequest,
+        cb: Callback<StatusResponse, RemoteStatusError>,
+    ) {
+        let bp = self.clone();
+        self.tracker()
+            .spawn(async move { cb.reply(bp.verified_status(&request).await) });
+    }
+
+    async fn verified_status(
+        &self,
+        r: &StatusRequest,
+    ) -> Result<StatusResponse, RemoteStatusError> {
+        let group = self.fs().load_group::<S>()?;
+        authorize(&group, self.id(), self.clock().now(), r)?;
+
+        let (tx, rx) = Callback::new();
+        self.status(tx).await;
+        let mut status = rx.await.map_err(|_| RemoteStatusError::ChainClosed)??;
+        status.metadata = Some(Metadata::with_id(self.id().to_string()));
+
+        Ok(status)
+    }
+
+    /// Queries status of given addresses, members of the latest group except this node are
+    /// queried if `addresses` is empty. Addresses which did not answer are absent from the result.
+    pub(super) fn remote_status(
+        &self,
+        addresses: Vec<Address>,
+        cb: Callback<HashMap<String, StatusResponse>, RemoteStatusError>,
+    ) {
+        let bp = self.clone();
+        self.tracker()
+            .spawn(async move { cb.reply(bp.query_peers(addresses).await) });
+    }
+
+    async fn query_peers(
+        &self,
+        mut addresses: Vec<Address>,
+    ) -> Result<HashMap<String, StatusResponse>, RemoteStatusError> {
+        if addresses.is_empty() {
+            addresses = self
+                .fs()
+                .load_group::<S>()?
+                .nodes()
+                .iter()
+                .map(|n| n.public().address.clone())
+                .filter(|a| *a != self.identity().address)
+                .collect();
+        }
+        let request = self.signed_request()?;
+
+        let mut tasks = JoinSet::new();
+        for peer in addresses {
+            let request = request.clone();
+            tasks.spawn(async move {
+                let status = tokio::time::timeout(PEER_TIMEOUT, async {
+                    ProtocolClient::new(&peer).await?.status(request).await
+                })
+                .await;
+                (peer, status)
+            });
+        }
+
+        let mut statuses = HashMap::new();
+        while let Some(task) = tasks.join_next().await {
+            let Ok((peer, status)) = task else { continue };
+            match status {
+                Ok(Ok(status)) => {
+                    statuses.insert(peer.to_string(), status);
+                }
+                Ok(Err(err)) => debug!(parent: self.log(), "remote status: {peer}: {err}"),
+                Err(_) => debug!(parent: self.log(), "remote status: {peer}: timeout"),
+            }
+        }
+
+        Ok(statuses)
+    }
+
+    fn signed_request(&self) -> Result<StatusRequest, RemoteStatusError> {
+        sign_request::<S>(
+            self.id(),
+            self.identity().address.to_string(),
+            self.private_key(),
+            self.clock().now(),
+        )
+    }
+}
+
+/// Returns status request of `address` signed by its private key at time `now`.
+fn sign_request<S: Scheme>(
+    beacon_id: &str,
+    address: String,
+    private_key: &S::Scalar,
+    now: Duration,
+) -> Result<StatusRequest, RemoteStatusError> {
+    let (timestamp_ms, signature) = sign_auth::<S>(STATUS, beacon_id, &address, private_key, now)?;
+
+    Ok(StatusRequest {
+        metadata: Some(Metadata::with_id(beacon_id.to_string())),
+        address,
+        timestamp_ms,
+        signature,
+    })
+}
+
+/// Returns request time and signature of `address` over a request of `kind` at time `now`.
+pub(super) fn sign_auth<S: Scheme>(
+    kind: &str,
+    beacon_id: &str,
+    address: &str,
+    private_key: &S::Scalar,
+    now: Duration,
+) -> Result<(u64, Vec<u8>), RemoteStatusError> {
+    let timestamp_ms = u64::try_from(now.as_millis()).unwrap_or_default();
+    let msg = auth_msg(kind, beacon_id, address, timestamp_ms);
+    let signature = S::bls_sign(&msg, private_key)
+        .map_err(|_| RemoteStatusError::Sign)?
+        .serialize()
+        .map_err(|_| RemoteStatusError::Sign)?
+        .into();
+
+    Ok((timestamp_ms, signature))
+}
+
+/// Checks that the request is recent and signed by the key of a `group` member at requester address.
+fn authorize<S: Scheme>(
+    group: &Group<S>,
+    beacon_id: &str,
+    now: Duration,
+    r: &StatusRequest,
+) -> Result<(), RemoteStatusError> {
+    authorize_member(
+        STATUS,
+        group,
+        beacon_id,
+        now,
+        &r.address,
+        r.timestamp_ms,
+        &r.signature,
+    )
+}
+
+/// Checks that a request of `kind` sent at `timestamp_ms` is recent and signed by the key of
+/// a `group` member at `address`.
+pub(super) fn authorize_member<S: Scheme>(
+    kind: &str,
+    group: &Group<S>,
+    beacon_id: &str,
+    now: Duration,
+    address: &str,
+    timestamp_ms: u64,
+    signature: &[u8],
+) -> Result<(), RemoteStatusError> {
+    let sent = Duration::from_millis(timestamp_ms);
+    if now.abs_diff(sent) > REQUEST_VALIDITY {
+        return Err(RemoteStatusError::Expired);
+    }
+
+    let node = group
+        .nodes()
+        .iter()
+        .find(|n| n.public().address() == address)
+        .ok_or_else(|| RemoteStatusError::NotMember(address.to_string()))?;
+    let signature =
+        SigPoint::<S>::deserialize(signature).map_err(|_| RemoteStatusError::InvalidSignature)?;
+    let msg = auth_msg(kind, beacon_id, address, timestamp_ms);
+    S::bls_verify(node.public().key(), &signature, &msg)
+        .map_err(|_| RemoteStatusError::InvalidSignature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::time::time_now;
+    use crate::key::keys::Identity;
+    use crate::key::keys::Pair;
+    use crate::key::node::Node;
+    use energon::drand::schemes::DefaultScheme;
+
+    fn pair(address: &str) -> Pair<DefaultScheme> {
+        Pair::generate(Address::precheck(address).unwrap()).unwrap()
+    }
+
+    fn group(members: &[&Pair<DefaultScheme>]) -> Group<DefaultScheme> {
+        let nodes = members
+            .iter()
+            .zip(0..)
+            .map(|(p, index)| {
+                let identity = p.public_identity();
+                Node::new(
+                    Identity::new(
+                        identity.address.clone(),
+                        identity.key().clone(),
+                        identity.signature().clone(),
+                    ),
+                    index,
+                )
+            })
+            .collect();
+
+        Group {
+            nodes,
+            ..Default::default()
+        }
+    }
+
+    fn request(p: &Pair<DefaultScheme>, beacon_id: &str, now: Duration) -> StatusRequest {
+        let address = p.public_identity().address.to_string();
+        sign_request::<DefaultScheme>(beacon_id, address, p.private_key(), now).unwrap()
+    }
+
+    #[test]
+    fn member_is_allowed() {
+        let (a, b) = (pair("127.0.0.1:1001"), pair("127.0.0.1:1002"));
+        let group = group(&[&a, &b]);
+        let now = time_now();
+
+        authorize(&group, "default", now, &request(&b, "default", now)).unwrap();
+        // Clocks of nodes are not exactly synchronized.
+        let skewed = request(
+            &b,
+            "default",
+            now - REQUEST_VALIDITY + Duration::from_secs(1),
+        );
+        authorize(&group, "default", now, &skewed).unwrap();
+    }
+
+    #[test]
+    fn invalid_requests_are_denied() {
+        let (a, b) = (pair("127.0.0.1:1001"), pair("127.0.0.1:1002"));
+        let group = group(&[&a, &b]);
+        let now = time_now();
+
+        let outsider = pair("127.0.0.1:1003");
+        assert!(matches!(
+            authorize(&group, "default", now, &request(&outsider, "default", now)),
+            Err(RemoteStatusError::NotMember(_))
+        ));
+
+        // Address of a member signed by another key.
+        let mut spoofed = request(&outsider, "default", now);
+        spoofed.address = a.public_identity().address.to_string();
+        assert!(matches!(
+            authorize(&group, "default", now, &spoofed),
+            Err(RemoteStatusError::InvalidSignature)
+        ));
+
+        // Signature is bound to the beacon id and the request time.
+        assert!(matches!(
+            authorize(&group, "default", now, &request(&a, "other", now)),
+            Err(RemoteStatusError::InvalidSignature)
+        ));
+        let mut shifted = request(&a, "default", now);
+        shifted.timestamp_ms += 1;
+        assert!(matches!(
+            authorize(&group, "default", now, &shifted),
+            Err(RemoteStatusError::InvalidSignature)
+        ));
+
+        // Captured request is not accepted later.
+        let old = request(
+            &a,
+            "default",
+            now - REQUEST_VALIDITY - Duration::from_secs(1),
+        );
+        assert!(matches!(
+            authorize(&group, "default", now, &old),
+            Err(RemoteStatusError::Expired)
+        ));
+
+        let mut malformed = request(&a, "default", now);
+        malformed.signature = vec![1, 2, 3];
+        assert!(matches!(
+            authorize(&group, "default", now, &malformed),
+            Err(RemoteStatusError::InvalidSignature)
+        ));
+    }
+}
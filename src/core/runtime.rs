@@ -0,0 +1,145 @@
+//! Sizing of tokio runtime and isolation of chain store I/O.
+//!
+//! Chain store actors and sync streams block on sqlite, see [`crate::chain::store`]. By default
+//! they share the blocking pool of the main runtime. With [`RuntimeConfig::store_threads`] set
+//! they run on a dedicated pool, so disk latency spikes can not exhaust threads used by other
+//! blocking tasks of the daemon, while network reactor always runs on worker threads.
+//!
+//! Every chain store actor keeps a thread of the pool, so the pool should fit all actors and
+//! streams: smaller pools are refused at start, see [`RuntimeConfig::min_store_threads`], and
+//! actors which would take threads reserved for streams are refused at runtime.
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::OnceLock;
+use tokio::runtime::Builder;
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+
+/// Dedicated pool of chain store threads, set once at daemon start.
+static STORE_POOL: OnceLock<StorePool> = OnceLock::new();
+
+struct StorePool {
+    runtime: Runtime,
+    threads: usize,
+    /// Threads kept for sync streams and store inspection.
+    reserved: usize,
+    /// Running chain store actors.
+    actors: AtomicUsize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RuntimeConfig {
+    /// Worker threads of the main runtime, amount of CPU cores if not set.
+    pub worker_threads: Option<usize>,
+    /// Upper limit of blocking pool of the main runtime, tokio default if not set.
+    pub max_blocking_threads: Option<usize>,
+    /// Upper limit of the dedicated chain store pool, at least [`Self::min_store_threads`].
+    pub store_threads: Option<usize>,
+    /// Beacon ids in the daemon folder at start, each keeps a chain store actor.
+    pub beacons: usize,
+    /// Concurrent sync streams served by the daemon.
+    pub sync_streams: usize,
+}
+
+impl RuntimeConfig {
+    /// Returns the smallest chain store pool: a thread per beacon id and per sync stream, and
+    /// one more for store inspection and local streams.
+    pub fn min_store_threads(&self) -> usize {
+        self.beacons + self.sync_streams + 1
+    }
+
+    /// Builds the main runtime and the dedicated chain store pool if configured.
+    pub fn build(&self) -> std::io::Result<Runtime> {
+        let mut main = Builder::new_multi_thread();
+        main.enable_all().thread_name("drand-worker");
+        if let Some(threads) = self.worker_threads {
+            main.worker_threads(threads.max(1));
+        }
+        if let Some(threads) = self.max_blocking_threads {
+            main.max_blocking_threads(threads.max(1));
+        }
+        if let Some(threads) = self.store_threads {
+            let min = self.min_store_threads();
+            if threads < min {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "store threads {threads} are below minimum {min}: {} beacon ids, {} sync streams and one for inspection",
+                        self.beacons, self.sync_streams
+                    ),
+                ));
+            }
+            let runtime = Builder::new_multi_thread()
+                .worker_threads(1)
+                .max_blocking_threads(threads)
+                .thread_name("drand-store")
+                .enable_all()
+                .build()?;
+            // Pool is configured once per process.
+            let _ = STORE_POOL.set(StorePool {
+                runtime,
+                threads,
+                reserved: self.sync_streams + 1,
+                actors: AtomicUsize::new(0),
+            });
+        }
+
+        main.build()
+    }
+}
+
+/// Runs blocking chain store actor for its lifetime, see [`spawn_store`]. Fails if the actor
+/// would take a thread of the dedicated pool reserved for streams.
+pub fn spawn_store_actor<F>(f: F) -> std::io::Result<JoinHandle<()>>
+where
+    F: FnOnce() + Send + 'static,
+{
+    let Some(pool) = STORE_POOL.get() else {
+        return Ok(tokio::task::spawn_blocking(f));
+    };
+    let actors = pool.actors.fetch_add(1, Ordering::SeqCst) + 1;
+    if actors + pool.reserved > pool.threads {
+        pool.actors.fetch_sub(1, Ordering::SeqCst);
+        return Err(std::io::Error::other(format!(
+            "chain store pool of {} threads is exhausted by {} actors, restart with larger --store-threads",
+            pool.threads,
+            actors - 1
+        )));
+    }
+
+    Ok(pool.runtime.spawn_blocking(move || {
+        f();
+        pool.actors.fetch_sub(1, Ordering::SeqCst);
+    }))
+}
+
+/// Runs blocking chain store closure on the dedicated pool if configured, otherwise on the
+/// blocking pool of the current runtime.
+pub fn spawn_store<F, R>(f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    match STORE_POOL.get() {
+        Some(pool) => pool.runtime.spawn_blocking(f),
+        None => tokio::task::spawn_blocking(f),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_store_pool_is_refused() {
+        let config = RuntimeConfig {
+            store_threads: Some(4),
+            beacons: 2,
+            sync_streams: 2,
+            ..Default::default()
+        };
+        assert_eq!(config.min_store_threads(), 5);
+        let err = config.build().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,210 @@
+//! Optional webhooks notified about daemon events, see `--webhook` of `drand start`.
+//!
+//! Events of selected kinds (new beacons, resync start and stop, DKG status changes by default)
+//! are POSTed to every webhook as JSON with fields of control RPC `Events`:
+//! `{"beacon_id":"..","kind":"..","round":1,"detail":"..","timestamp_ms":1}`. If a secret is set,
+//! the body is signed with header `X-Drand-Signature: sha256=<hex HMAC-SHA256 of the body>`.
+//!
+//! Failed deliveries are retried with exponential backoff, client errors except `429` are not
+//! retried. Each webhook has its own queue, so a webhook which is down does not delay the others,
+//! and new events are dropped while its queue is full.
+use super::daemon::Daemon;
+use super::dump::redact_webhook;
+
+use crate::protobuf::drand::DaemonEvent;
+use crate::secrets::Secret;
+
+use hmac::Hmac;
+use hmac::Mac;
+use serde_json::json;
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+use tracing::info;
+use tracing::warn;
+
+/// Event kinds delivered if not configured.
+pub const DEFAULT_KINDS: &str = "beacon_stored,resync_started,resync_stopped,dkg_status";
+/// Header with signature of the body.
+pub const SIGNATURE_HEADER: &str = "X-Drand-Signature";
+/// Events queued per webhook.
+const QUEUE_CAPACITY: usize = 256;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Deliveries of an event, including the first one.
+const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    pub urls: Vec<String>,
+    /// Bodies are signed with the secret if set.
+    pub secret: Option<Secret>,
+    /// Kinds of delivered events.
+    pub kinds: Vec<String>,
+}
+
+/// Delivers events until the daemon is stopped.
+pub async fn run(daemon: Arc<Daemon>, config: WebhookConfig) {
+    let mut events = daemon.beacons().events().subscribe();
+    let client = match reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(err) => {
+            warn!("webhooks: {err}");
+            return;
+        }
+    };
+    let secret = config.secret.map(Arc::new);
+    let queues: Vec<_> = config
+        .urls
+        .iter()
+        .map(|url| {
+            let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+            daemon.tracker.spawn(deliver(
+                client.clone(),
+                url.clone(),
+                secret.clone(),
+                rx,
+                daemon.token.clone(),
+            ));
+            (redact_webhook(url), tx)
+        })
+        .collect();
+    info!(
+        "webhooks: notifying {} endpoints about events [{}]",
+        queues.len(),
+        config.kinds.join(", ")
+    );
+
+    loop {
+        let event = tokio::select! {
+            () = daemon.token.cancelled() => return,
+            event = events.recv() => match event {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("webhooks: skipped {skipped} events");
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            },
+        };
+        if !config.kinds.contains(&event.kind) {
+            continue;
+        }
+        let body: Arc<[u8]> = body(&event).into();
+        for (url, tx) in &queues {
+            if tx.try_send(body.clone()).is_err() {
+                warn!(
+                    "webhooks: queue of {url} is full, dropped event {}",
+                    event.kind
+                );
+            }
+        }
+    }
+}
+
+fn body(event: &DaemonEvent) -> Vec<u8> {
+    json!({
+        "beacon_id": event.beacon_id,
+        "kind": event.kind,
+        "round": event.round,
+        "detail": event.detail,
+        "timestamp_ms": event.timestamp_ms,
+    })
+    .to_string()
+    .into_bytes()
+}
+
+/// Returns value of [`SIGNATURE_HEADER`].
+fn signature(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any size");
+    mac.update(body);
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Returns delay before the given retry, starting from one.
+fn backoff(retry: u32) -> Duration {
+    INITIAL_BACKOFF
+        .saturating_mul(1 << retry.saturating_sub(1).min(16))
+        .min(MAX_BACKOFF)
+}
+
+/// Returns true if delivery should be retried after the response status.
+fn is_retryable(status: reqwest::StatusCode) -> bool {
+    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+}
+
+async fn deliver(
+    client: reqwest::Client,
+    url: String,
+    secret: Option<Arc<Secret>>,
+    mut rx: mpsc::Receiver<Arc<[u8]>>,
+    token: CancellationToken,
+) {
+    // Webhook URLs often carry tokens, only the host is logged.
+    let target = redact_webhook(&url);
+    while let Some(body) = tokio::select! {
+        () = token.cancelled() => None,
+        body = rx.recv() => body,
+    } {
+        for attempt in 1..=MAX_ATTEMPTS {
+            let mut request = client
+                .post(&url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.to_vec());
+            if let Some(secret) = &secret {
+                request = request.header(
+                    SIGNATURE_HEADER,
+                    signature(secret.expose().as_bytes(), &body),
+                );
+            }
+            let reason = match request.send().await {
+                Ok(response) if response.status().is_success() => break,
+                Ok(response) if !is_retryable(response.status()) => {
+                    warn!(
+                        "webhooks: {target} rejected event with status {}",
+                        response.status()
+                    );
+                    break;
+                }
+                Ok(response) => format!("status {}", response.status()),
+                Err(err) => err.to_string(),
+            };
+            if attempt == MAX_ATTEMPTS {
+                warn!("webhooks: failed to deliver event to {target} after {MAX_ATTEMPTS} attempts: {reason}");
+                break;
+            }
+            let delay = backoff(attempt);
+            debug!("webhooks: delivery to {target} failed: {reason}, retry in {delay:?}");
+            tokio::select! {
+                () = token.cancelled() => return,
+                () = tokio::time::sleep(delay) => (),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_and_backoff() {
+        // RFC 4231, test case 2.
+        assert_eq!(
+            signature(b"Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(4));
+        assert_eq!(backoff(100), MAX_BACKOFF);
+        assert!(is_retryable(reqwest::StatusCode::BAD_GATEWAY));
+        assert!(is_retryable(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable(reqwest::StatusCode::NOT_FOUND));
+    }
+}
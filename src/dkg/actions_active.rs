@@ -46,9 +46,17 @@ impl<S: Scheme> ActionsActive for BeaconProcess<S> {
             }
         };
 
+        let identity_changes = self
+            .dkg_store()
+            .get_identity_changes()?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
         let responce = DkgStatusResponse {
             current: Some(self.dkg_store().get_current::<S>()?.into()),
             complete,
+            identity_changes,
         };
 
         Ok(responce)
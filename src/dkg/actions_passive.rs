@@ -1,45 +1,105 @@
 use super::actions_signing::ActionsSigning;
+use super::actions_signing::GossipAuth;
+use super::state::State;
 use super::ActionsError;
 
 use crate::core::beacon::BeaconProcess;
 use crate::key::Scheme;
 use crate::transport::dkg::GossipPacket;
 use prost_types::Timestamp;
-use std::future::Future;
+use tracing::warn;
 
 /// Contains all internal messaging between nodes triggered by the protocol - things it does automatically
 /// upon receiving messages from other nodes: storing proposals, aborting when the leader aborts, etc
+///
+/// Packet signature is verified off the beacon process: [`Self::signed_packet`] returns the signed
+/// message and the packet is applied once the signature of that message is verified.
 pub trait ActionsPassive {
+    /// Returns key of the packet signer and the message signed by it.
+    fn signed_packet(&self, packet: &GossipPacket) -> Result<(Vec<u8>, Vec<u8>), ActionsError>;
+
     fn packet(
         &self,
         packet: GossipPacket,
-    ) -> impl Future<Output = Result<Option<Timestamp>, ActionsError>>;
+        verified: &[u8],
+    ) -> Result<Option<Timestamp>, ActionsError>;
 
+    /// Applies packet whose signature is verified over `verified` message, the packet is
+    /// rejected with [`ActionsError::StaleVerification`] if the state is changed meanwhile.
     fn apply_packet_to_state(
         &self,
         packet: GossipPacket,
-    ) -> impl Future<Output = Result<Option<Timestamp>, ActionsError>>;
+        verified: &[u8],
+    ) -> Result<Option<Timestamp>, ActionsError>;
 }
 
 impl<S: Scheme> ActionsPassive for BeaconProcess<S> {
-    async fn packet(&self, packet: GossipPacket) -> Result<Option<Timestamp>, ActionsError> {
+    fn signed_packet(&self, packet: &GossipPacket) -> Result<(Vec<u8>, Vec<u8>), ActionsError> {
+        self.signed_msg(packet, &self.next_state(packet)?)
+    }
+
+    fn packet(
+        &self,
+        packet: GossipPacket,
+        verified: &[u8],
+    ) -> Result<Option<Timestamp>, ActionsError> {
         // TODO: (not confirmed): if we're in the DKG protocol phase, we automatically broadcast it as it shouldn't update state
-        self.apply_packet_to_state(packet).await
+        self.apply_packet_to_state(packet, verified)
     }
 
-    async fn apply_packet_to_state(
+    fn apply_packet_to_state(
         &self,
         packet: GossipPacket,
+        verified: &[u8],
     ) -> Result<Option<Timestamp>, ActionsError> {
+        let state = self.next_state(&packet)?;
+        let (_, msg) = self.signed_msg(&packet, &state)?;
+        if msg != verified {
+            return Err(ActionsError::StaleVerification);
+        }
+        self.record_gossip(&packet, &state);
+        let execute = packet.data.get_execute();
+        if let Some(start) = &execute {
+            // Recorded before the state, so an executing state always has its start time.
+            self.dkg_store().save_execution(state.epoch(), start)?;
+        }
+        self.dkg_store().save_current(&state)?;
+
+        Ok(execute)
+    }
+}
+
+impl<S: Scheme> BeaconProcess<S> {
+    /// Returns state with the packet applied, packet is verified against the next state as
+    /// the current state upon first proposal is empty.
+    fn next_state(&self, packet: &GossipPacket) -> Result<State<S>, ActionsError> {
         let mut state = self.dkg_store().get_last_succesful(self.id())?;
         let me = self.as_participant()?;
-
-        // We must verify the message against the next state, as the current state upon first proposal will be empty.
         // Packet data is moved into state, for this reason packet is cloned.
         state.apply(&me, packet.clone())?;
-        self.verify_msg(&packet, &state).await?;
-        self.dkg_store().save_current(&state)?;
 
-        Ok(packet.data.get_execute())
+        Ok(state)
+    }
+
+    /// Records verified gossip packet into evidence log of the proposal epoch on the blocking pool.
+    fn record_gossip(&self, packet: &GossipPacket, state: &State<S>) {
+        let Some(sender) = state
+            .participants()
+            .find(|p| p.address == packet.metadata.address)
+        else {
+            return;
+        };
+        let msg = self.msg_for_signing(packet, &state.encode());
+        let (evidence, packet, key) = (
+            self.dkg_store().evidence(state.epoch()),
+            packet.clone(),
+            sender.key.clone(),
+        );
+        let log = self.log().clone();
+        self.tracker().spawn_blocking(move || {
+            if let Err(err) = evidence.gossip(&packet, &key, &msg) {
+                warn!(parent: &log, "dkg: failed to record evidence: {err}");
+            }
+        });
     }
 }
@@ -15,18 +15,19 @@ use crate::transport::dkg::StartExecution;
 
 use energon::traits::Affine;
 use prost_types::Timestamp;
-use std::future::Future;
 use tracing::debug;
 
 /// Contains logic for signing and validation packets
 pub(super) trait ActionsSigning {
     type Scheme: Scheme;
 
-    fn verify_msg(
+    /// Returns key of the packet signer and the message signed by it, signature is checked
+    /// with [`is_valid_signature`] off the beacon process.
+    fn signed_msg(
         &self,
         packet: &GossipPacket,
         state: &State<Self::Scheme>,
-    ) -> impl Future<Output = Result<(), ActionsError>>;
+    ) -> Result<(Vec<u8>, Vec<u8>), ActionsError>;
 
     fn msg_for_signing(&self, packet: &GossipPacket, enc_state: &[u8]) -> Vec<u8> {
         let mut msg = packet.encode();
@@ -44,13 +45,17 @@ pub(super) trait GossipAuth {
 impl<S: Scheme> ActionsSigning for BeaconProcess<S> {
     type Scheme = S;
 
-    async fn verify_msg(&self, gp: &GossipPacket, state: &State<S>) -> Result<(), ActionsError> {
+    fn signed_msg(
+        &self,
+        gp: &GossipPacket,
+        state: &State<S>,
+    ) -> Result<(Vec<u8>, Vec<u8>), ActionsError> {
         debug!(parent: self.log(), "Verifying gossip packet with beaconID: {}, from: {}", 
                gp.metadata.beacon_id, gp.metadata.address, );
 
         // Find the participant signature is allegedly from.
         // Return error if participant is not found in `remaining` or `joining`.
-        if let Some(participant) = state
+        let participant = state
             .joining
             .iter()
             .find(|p| p.address == gp.metadata.address)
@@ -60,23 +65,16 @@ impl<S: Scheme> ActionsSigning for BeaconProcess<S> {
                     .iter()
                     .find(|p| p.address == gp.metadata.address)
             })
-        {
-            // Verify signature
-            {
-                let msg = self.msg_for_signing(gp, &state.encode());
-                if is_valid_signature::<S>(&participant.key, &gp.metadata.signature, &msg) {
-                    Ok(())
-                } else {
-                    Err(ActionsError::InvalidSignature)
-                }
-            }
-        } else {
-            Err(ActionsError::MissingParticipant)
-        }
+            .ok_or(ActionsError::MissingParticipant)?;
+
+        Ok((
+            participant.key.clone(),
+            self.msg_for_signing(gp, &state.encode()),
+        ))
     }
 }
 
-fn is_valid_signature<S: Scheme>(key: &[u8], sig: &[u8], msg: &[u8]) -> bool {
+pub(crate) fn is_valid_signature<S: Scheme>(key: &[u8], sig: &[u8], msg: &[u8]) -> bool {
     if let Ok(key) = Affine::deserialize(key) {
         if let Ok(sig) = Affine::deserialize(sig) {
             return S::bls_verify(&key, &sig, msg).is_ok();
@@ -85,6 +83,36 @@ fn is_valid_signature<S: Scheme>(key: &[u8], sig: &[u8], msg: &[u8]) -> bool {
     false
 }
 
+/// Returns packet of the leader of `terms` signed over the proposed state, as golang leader signs it.
+///
+/// This implementation does not lead a DKG, leader of simulated network is driven by tests, see
+/// [`crate::net::sim::SimLeader`].
+#[cfg(test)]
+pub(crate) fn leader_packet<S: Scheme>(
+    private: &S::Scalar,
+    terms: &ProposalTerms,
+    data: GossipData,
+) -> Result<GossipPacket, ActionsError> {
+    let state = State::<S>::try_from(terms.clone()).map_err(super::state::DBStateError::from)?;
+    let mut packet = GossipPacket {
+        data,
+        metadata: crate::transport::dkg::GossipMetadata {
+            beacon_id: terms.beacon_id.clone(),
+            address: terms.leader.address.clone(),
+            signature: vec![],
+        },
+    };
+    let mut msg = packet.encode();
+    msg.extend_from_slice(&state.encode());
+    packet.metadata.signature = S::bls_sign(&msg, private)
+        .map_err(|_| ActionsError::Sign)?
+        .serialize()
+        .map_err(|_| ActionsError::Sign)?
+        .into();
+
+    Ok(packet)
+}
+
 /// Implementation for UTC 0, aligned to <https://pkg.go.dev/time#Time.MarshalBinary> [go 1.22.10]
 #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
 pub(super) fn enc_timestamp(t: Timestamp) -> [u8; 15] {
@@ -1,3 +1,7 @@
+use super::evidence::EvidenceLog;
+use super::fanout::FanOut;
+use super::fanout::Source;
+
 use crate::key::Scheme;
 use crate::net::dkg_public::DkgPublicClient;
 use crate::net::utils::Address;
@@ -19,10 +23,13 @@ use energon::points::KeyPoint;
 use energon::traits::Affine;
 use energon::traits::ScalarField;
 
+use std::sync::Arc;
 use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
 use tokio_util::task::TaskTracker;
 use tracing::debug;
 use tracing::error;
+use tracing::warn;
 use tracing::Span;
 
 #[derive(Clone)]
@@ -31,63 +38,113 @@ pub(super) enum BroadcastCmd {
     // TODO: abort DKG
     #[allow(dead_code)]
     Stop,
-    /// Packet to broadcast for other nodes
-    Packet(DkgPacket),
+    /// Packet to send to the listed peers.
+    Packet {
+        packet: DkgPacket,
+        to: Arc<[Address]>,
+    },
 }
 
-pub(super) struct Broadcast {
+/// Sends bundles to peers chosen by the fan-out strategy of the beacon id, see [`super::fanout`].
+#[derive(Clone)]
+pub(super) struct Relay {
     sender: broadcast::Sender<BroadcastCmd>,
+    fanout: FanOut,
+    participants: Arc<[Address]>,
+    me: Address,
+    leader: Address,
+}
+
+impl Relay {
+    /// Returns `false` if broadcast to all peers is already finished.
+    pub(super) fn send(&self, packet: DkgPacket, source: Source<'_>) -> bool {
+        let to = self
+            .fanout
+            .recipients(self.participants.iter(), &self.me, &self.leader, source)
+            .into_iter()
+            .cloned()
+            .collect();
+
+        self.sender
+            .send(BroadcastCmd::Packet { packet, to })
+            .is_ok()
+    }
+}
+
+/// Bundles queued for each peer, relayed bundles of all participants share the queue.
+const CHANNEL_CAPACITY: usize = 256;
+
+pub(super) struct Broadcast {
+    relay: Relay,
     beacon_id: String,
+    /// Bundles issued by this node are recorded before broadcast.
+    evidence: EvidenceLog,
     log: Span,
 }
 
 impl Broadcast {
-    pub(super) fn init(id: &str, log: &Span) -> Self {
-        let (sender, _) = broadcast::channel::<BroadcastCmd>(1);
+    pub(super) fn init(
+        id: &str,
+        participants: &[&Participant],
+        me: &Address,
+        leader: &Address,
+        fanout: FanOut,
+        evidence: EvidenceLog,
+        log: &Span,
+    ) -> Self {
+        let (sender, _) = broadcast::channel::<BroadcastCmd>(CHANNEL_CAPACITY);
+        let relay = Relay {
+            sender,
+            fanout,
+            participants: participants.iter().map(|p| p.address.clone()).collect(),
+            me: me.clone(),
+            leader: leader.clone(),
+        };
 
         Self {
-            sender,
+            relay,
             beacon_id: id.to_owned(),
+            evidence,
             log: log.to_owned(),
         }
     }
 
-    pub(super) fn register_nodes<S: Scheme>(
-        self,
-        t: &TaskTracker,
-        participants: &[&Participant],
-        mut rx: BundleReceiver<S>,
-        me: &Address,
-    ) {
-        for p in participants {
-            if &p.address == me {
+    /// Returns relay of bundles received from other participants.
+    pub(super) fn relay(&self) -> Relay {
+        self.relay.clone()
+    }
+
+    pub(super) fn register_nodes<S: Scheme>(self, t: &TaskTracker, mut rx: BundleReceiver<S>) {
+        debug!(parent: &self.log, "dkg broadcast: fan-out {}", self.relay.fanout);
+        for peer in self.relay.participants.iter() {
+            if *peer == self.relay.me {
                 continue;
             }
 
-            let mut rx = self.sender.subscribe();
-            debug!(parent: &self.log, "dkg broadcast: added new address [{}]", p.address);
-            let peer = p.address.clone();
+            let mut rx = self.relay.sender.subscribe();
+            debug!(parent: &self.log, "dkg broadcast: added new address [{peer}]");
+            let peer = peer.clone();
+            let log = self.log.clone();
             t.spawn(async move {
                 let mut conn_result = DkgPublicClient::new(&peer).await;
 
-                while let Ok(msg) = rx.recv().await {
+                loop {
+                    let msg = match rx.recv().await {
+                        Ok(msg) => msg,
+                        // Skipped bundles are still delivered to the peer by the echo of other participants.
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!(parent: &log, "dkg broadcast: {skipped} packets to {peer} are skipped");
+                            continue;
+                        }
+                        Err(RecvError::Closed) => break,
+                    };
                     match msg {
                         BroadcastCmd::Stop => break,
-                        BroadcastCmd::Packet(packet) => {
-                            if conn_result.is_err() {
-                                conn_result = DkgPublicClient::new(&peer).await;
+                        BroadcastCmd::Packet { to, .. } if !to.contains(&peer) => {}
+                        BroadcastCmd::Packet { packet, .. } => {
+                            if let Err(err) = send(&mut conn_result, &peer, packet).await {
+                                error!("dkg broadcast: send packet to {peer}: {err}");
                             }
-
-                            match conn_result {
-                                Ok(ref mut client) => {
-                                    if let Err(err) = client.broadcast_dkg(packet).await {
-                                        error!("dkg broadcast: send packet to {peer}: {err}");
-                                    }
-                                }
-                                Err(ref err) => {
-                                    error!("dkg broadcast: connect to {peer}: {err}");
-                                }
-                            };
                         }
                     }
                 }
@@ -98,9 +155,15 @@ impl Broadcast {
             while let Some(bundle) = rx.recv().await {
                 let Ok(proto) = into_proto(bundle, &self.beacon_id) else {
                     error!(parent: &self.log, "dkg broadcast: failed to convert bundle to proto");
-                    return;
+                    continue;
                 };
-                if self.sender.send(BroadcastCmd::Packet(proto)).is_err() {
+                let (evidence, record) = (self.evidence.clone(), proto.clone());
+                if let Ok(Err(err)) =
+                    tokio::task::spawn_blocking(move || evidence.bundle(&record, true)).await
+                {
+                    warn!(parent: &self.log, "dkg broadcast: failed to record evidence: {err}");
+                }
+                if !self.relay.send(proto, Source::Own) {
                     error!(parent: &self.log, "dkg broadcast: channel is closed");
                 }
             }
@@ -108,6 +171,25 @@ impl Broadcast {
     }
 }
 
+/// Sends packet to the peer, the packet is sent once more over a new connection if the current one
+/// is broken, e.g. the peer is restarted.
+async fn send(
+    conn: &mut anyhow::Result<DkgPublicClient>,
+    peer: &Address,
+    packet: DkgPacket,
+) -> anyhow::Result<()> {
+    if let Ok(client) = conn {
+        if client.broadcast_dkg(packet.clone()).await.is_ok() {
+            return Ok(());
+        }
+    }
+    *conn = DkgPublicClient::new(peer).await;
+    match conn {
+        Ok(client) => client.broadcast_dkg(packet).await,
+        Err(err) => Err(anyhow::anyhow!("connect: {err}")),
+    }
+}
+
 /// Helper trait to convert [`Bundle`] from/into generic protocol type.
 pub(super) trait Convert: Sized {
     type Proto;
@@ -316,4 +398,3 @@ impl Convert for ResponseBundle {
         Ok(proto)
     }
 }
-
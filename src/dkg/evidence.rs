@@ -0,0 +1,280 @@
+//! Append-only log of signed DKG messages, kept per epoch to resolve disputes about who stalled
+//! or equivocated during a DKG.
+//!
+//! Gossip packets (proposals, accepts, rejects, executions, aborts) are recorded once their
+//! signature is verified, together with the exact signed message and the key of the sender, so
+//! a record can be verified without the node state. Deal, response and justification bundles are
+//! recorded as sent or once received ones are verified, they carry the signature of the issuer
+//! within the bundle. Records are written on the blocking pool, off the beacon process.
+//!
+//! Records are JSON lines at `dkg/evidence/<epoch>.jsonl`, the file is only appended to. Once the
+//! log reaches [`MAX_LOG_BYTES`] new records are dropped, so a flood of bundles can not fill the disk.
+use super::store::DKG_STORE_DIR;
+
+use crate::protobuf::dkg::packet::Bundle as ProtoBundle;
+use crate::protobuf::dkg::DkgPacket;
+use crate::transport::dkg::GossipPacket;
+
+use prost::Message;
+use serde_json::json;
+use serde_json::Value;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+const EVIDENCE_DIR: &str = "evidence";
+/// Maximum size of the log of an epoch.
+pub const MAX_LOG_BYTES: u64 = 64 * 1024 * 1024;
+
+#[derive(thiserror::Error, Debug)]
+pub enum EvidenceError {
+    #[error("evidence log not found: {0}")]
+    NotFound(PathBuf),
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{path}: invalid record at line {line}")]
+    InvalidRecord { path: PathBuf, line: usize },
+}
+
+/// Evidence log of a single epoch.
+#[derive(Clone)]
+pub struct EvidenceLog {
+    path: PathBuf,
+}
+
+impl EvidenceLog {
+    /// Returns log of the given epoch within the `dkg` folder.
+    pub(super) fn new(dkg_path: &Path, epoch: u32) -> Self {
+        Self {
+            path: dkg_path.join(EVIDENCE_DIR).join(format!("{epoch}.jsonl")),
+        }
+    }
+
+    pub(super) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records verified gossip packet, `msg` is the message signed by the sender with `key`.
+    pub(super) fn gossip(
+        &self,
+        packet: &GossipPacket,
+        key: &[u8],
+        msg: &[u8],
+    ) -> Result<(), std::io::Error> {
+        self.append(&json!({
+            "time": unix_time(),
+            "kind": packet.data.to_string(),
+            "from": packet.metadata.address.as_str(),
+            "key": hex::encode(key),
+            "message": hex::encode(msg),
+            "signature": hex::encode(&packet.metadata.signature),
+        }))
+    }
+
+    /// Records DKG bundle, `sent` is `true` for bundles issued by this node.
+    pub(super) fn bundle(&self, packet: &DkgPacket, sent: bool) -> Result<(), std::io::Error> {
+        let (kind, issuer, session_id, signature) =
+            match packet.dkg.as_ref().and_then(|p| p.bundle.as_ref()) {
+                Some(ProtoBundle::Deal(d)) => ("Deal", d.dealer_index, &d.session_id, &d.signature),
+                Some(ProtoBundle::Response(r)) => {
+                    ("Response", r.share_index, &r.session_id, &r.signature)
+                }
+                Some(ProtoBundle::Justification(j)) => {
+                    ("Justification", j.dealer_index, &j.session_id, &j.signature)
+                }
+                None => return Ok(()),
+            };
+
+        self.append(&json!({
+            "time": unix_time(),
+            "kind": kind,
+            "direction": if sent { "sent" } else { "received" },
+            "issuer_index": issuer,
+            "session_id": hex::encode(session_id),
+            "signature": hex::encode(signature),
+            "packet": hex::encode(packet.encode_to_vec()),
+        }))
+    }
+
+    /// Returns recorded bundles in order of recording, `true` marks bundles issued by this node.
+    pub(super) fn bundles(&self) -> Result<Vec<(DkgPacket, bool)>, EvidenceError> {
+        if !self.path.try_exists()? {
+            return Ok(vec![]);
+        }
+        let mut bundles = vec![];
+        for (i, line) in std::fs::read_to_string(&self.path)?.lines().enumerate() {
+            let invalid = || EvidenceError::InvalidRecord {
+                path: self.path.clone(),
+                line: i + 1,
+            };
+            let record: Value = serde_json::from_str(line).map_err(|_| invalid())?;
+            // Gossip records have no packet.
+            let Some(packet) = record.get("packet").and_then(Value::as_str) else {
+                continue;
+            };
+            let packet = hex::decode(packet)
+                .ok()
+                .and_then(|bytes| DkgPacket::decode(bytes.as_slice()).ok())
+                .ok_or_else(invalid)?;
+            bundles.push((packet, record.get("direction") == Some(&json!("sent"))));
+        }
+
+        Ok(bundles)
+    }
+
+    fn append(&self, record: &Value) -> Result<(), std::io::Error> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut f = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let line = format!("{record}\n");
+        if f.metadata()?.len() + line.len() as u64 > MAX_LOG_BYTES {
+            return Err(std::io::Error::other(format!(
+                "{} reached {MAX_LOG_BYTES} bytes, record is dropped",
+                self.path.display()
+            )));
+        }
+        // Record is written by a single call to keep lines intact.
+        f.write_all(line.as_bytes())
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Returns epochs with recorded evidence for beacon at `path_to_id`, sorted in ascending order.
+pub fn epochs(path_to_id: &Path) -> Result<Vec<u32>, EvidenceError> {
+    let dir = path_to_id.join(DKG_STORE_DIR).join(EVIDENCE_DIR);
+    if !dir.try_exists()? {
+        return Ok(vec![]);
+    }
+    let mut epochs = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let name = entry?.file_name();
+        if let Some(epoch) = name
+            .to_str()
+            .and_then(|n| n.strip_suffix(".jsonl"))
+            .and_then(|n| n.parse().ok())
+        {
+            epochs.push(epoch);
+        }
+    }
+    epochs.sort_unstable();
+
+    Ok(epochs)
+}
+
+/// Writes evidence of the epoch as a single JSON document, returns amount of exported records.
+pub fn export(
+    path_to_id: &Path,
+    beacon_id: &str,
+    epoch: u32,
+    out: &mut impl Write,
+) -> Result<usize, EvidenceError> {
+    let path = EvidenceLog::new(&path_to_id.join(DKG_STORE_DIR), epoch).path;
+    if !path.try_exists()? {
+        return Err(EvidenceError::NotFound(path));
+    }
+    let records = std::fs::read_to_string(&path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| {
+            serde_json::from_str(l).map_err(|_| EvidenceError::InvalidRecord {
+                path: path.clone(),
+                line: i + 1,
+            })
+        })
+        .collect::<Result<Vec<Value>, _>>()?;
+    let exported = records.len();
+    let doc = json!({
+        "beacon_id": beacon_id,
+        "epoch": epoch,
+        "records": records,
+    });
+    serde_json::to_writer_pretty(&mut *out, &doc).map_err(std::io::Error::from)?;
+    writeln!(out)?;
+
+    Ok(exported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protobuf::dkg::DealBundle;
+    use crate::protobuf::dkg::Packet;
+    use crate::protobuf::dkg::ResponseBundle;
+
+    #[test]
+    fn export_evidence() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path();
+        assert!(epochs(path).unwrap().is_empty());
+        assert!(matches!(
+            export(path, "default", 1, &mut vec![]),
+            Err(EvidenceError::NotFound(_))
+        ));
+
+        let deal = DkgPacket {
+            dkg: Some(Packet {
+                metadata: None,
+                bundle: Some(ProtoBundle::Deal(DealBundle {
+                    dealer_index: 2,
+                    signature: vec![1, 2],
+                    ..Default::default()
+                })),
+            }),
+        };
+        let response = DkgPacket {
+            dkg: Some(Packet {
+                metadata: None,
+                bundle: Some(ProtoBundle::Response(ResponseBundle {
+                    share_index: 3,
+                    ..Default::default()
+                })),
+            }),
+        };
+        let log = EvidenceLog::new(&path.join(DKG_STORE_DIR), 2);
+        log.bundle(&deal, true).unwrap();
+        log.bundle(&response, false).unwrap();
+        EvidenceLog::new(&path.join(DKG_STORE_DIR), 10)
+            .bundle(&deal, false)
+            .unwrap();
+        assert_eq!(epochs(path).unwrap(), [2, 10]);
+
+        let mut out = vec![];
+        assert_eq!(export(path, "default", 2, &mut out).unwrap(), 2);
+        let doc: Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(doc["epoch"], 2);
+        let deal_record = &doc["records"][0];
+        assert_eq!(deal_record["kind"], "Deal");
+        assert_eq!(deal_record["direction"], "sent");
+        assert_eq!(deal_record["issuer_index"], 2);
+        assert_eq!(deal_record["signature"], "0102");
+        let packet = hex::decode(deal_record["packet"].as_str().unwrap()).unwrap();
+        assert_eq!(DkgPacket::decode(packet.as_slice()).unwrap(), deal);
+        assert_eq!(doc["records"][1]["direction"], "received");
+
+        // Full log is not appended to.
+        let full = EvidenceLog::new(&path.join(DKG_STORE_DIR), 3);
+        full.bundle(&deal, false).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(full.path())
+            .unwrap()
+            .set_len(MAX_LOG_BYTES)
+            .unwrap();
+        assert!(full.bundle(&response, false).is_err());
+        assert_eq!(std::fs::metadata(full.path()).unwrap().len(), MAX_LOG_BYTES);
+    }
+}
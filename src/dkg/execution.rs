@@ -1,11 +1,12 @@
 use super::broadcast::Broadcast;
+use super::identity;
 use super::state::State;
 use super::store::DkgStoreError;
+use super::utils::BundleAuth;
 use super::utils::GateKeeper;
 use super::ActionsError;
 use super::DkgNode;
 
-use crate::chain::time::time_now;
 use crate::chain::time::ROUNDS_UNTIL_TRANSITION;
 use crate::chain::ChainCmd;
 use crate::core::beacon::BeaconProcess;
@@ -32,8 +33,10 @@ use sha2::Sha256;
 use std::future::Future;
 use std::time::Duration;
 use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 use tracing::error;
 use tracing::info;
+use tracing::warn;
 use tracing::Span;
 
 /// Default time of each DKG period by default.
@@ -41,7 +44,9 @@ use tracing::Span;
 /// DKG uses the "fast sync" mode that shorten the first phase
 /// and the second phase, "as fast as possible" when the protocol runs smoothly
 /// (there is no malicious party).
-const DEFAULT_DKG_PHASE_TIMEOUT: Duration = Duration::from_secs(10);
+pub(super) const DEFAULT_DKG_PHASE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Phases of the protocol: deals, responses and justifications.
+pub(super) const DKG_PHASES: u32 = 3;
 
 pub trait ExecuteDkg {
     type Scheme: Scheme;
@@ -74,68 +79,13 @@ impl<S: Scheme> ExecuteDkg for BeaconProcess<S> {
         start_execution: Timestamp,
         gk: &mut GateKeeper<Self::Scheme>,
     ) -> Result<(), ActionsError> {
-        // # Setup DKG #
-        // Get current and last completed states
-        let current = self.dkg_store().get_current::<S>()?;
-        let last_completed = match self.dkg_store().get_finished::<S>() {
-            Ok(state) => Some(state),
-            Err(DkgStoreError::NotFound) => None,
-            Err(err) => return Err(err.into()),
-        };
-
-        // Sort all participants by public_key.
-        let mut sorted_participants =
-            Vec::with_capacity(current.joining.len() + current.remaining.len());
-        sorted_participants.extend(current.joining.iter());
-        sorted_participants.extend(current.remaining.iter());
-        sorted_participants.sort_by_key(|p| &p.key);
-
-        // Map execution time into duration from now.
-        let time_until_execution = SystemTime::try_from(start_execution)
-            .map_err(|_| ActionsError::StartExecutionTimeNotCanonical)?
-            .duration_since(SystemTime::now())
-            .map_err(|_| ActionsError::StartExecutionTimeIsPassed)?;
-
-        // Setup config for DKG protocol.
-        let config = match last_completed {
-            Some(previous) => self.reshare_config(&current, previous, &sorted_participants)?,
-            None => self.initial_config(&current, &sorted_participants)?,
-        };
-        let dkg_log = config.log.clone();
-
-        // Initialize DKG protocol instance with channels for input and output.
-        let (protocol, bundles_rx, bundles_tx) =
-            Protocol::new_dkg(config, DEFAULT_DKG_PHASE_TIMEOUT).map_err(ActionsError::DkgError)?;
-
-        // Gatekeeper holds bundles sender during execution.
-        gk.open_gate(bundles_tx)?;
-
-        // Broadcast holds bundles receiver during execution.
-        let broadcast = Broadcast::init(self.id(), &dkg_log);
-        broadcast.register_nodes(
-            self.tracker(),
-            &sorted_participants,
-            bundles_rx,
-            &self.identity().address,
-        );
-
-        // # Run DKG #
-        let bp = self.clone();
-        self.tracker().spawn({ async move {
-            info!(parent: &dkg_log, "waiting for execution time: {} seconds", time_until_execution.as_secs());
-            tokio::time::sleep(time_until_execution).await;
-
-            let dkg_output=protocol.run().await;
-            bp.dkg_finished_notification().await;
-
-            match dkg_output{
-                Ok(Some(output)) => process_dkg_output(&bp, output, current, &dkg_log).await,
-                Ok(None) => info!(parent: &dkg_log, "DKG[Leaving] finished succesfully"),
-                Err(err) => error!(parent: &dkg_log, "DKG finished with error: {err}"),
-            }
-        }});
+        let execution_time = execution_time(start_execution)?;
+        if execution_time < self.clock().now() {
+            return Err(ActionsError::StartExecutionTimeIsPassed);
+        }
 
-        Ok(())
+        self.run_dkg(execution_time, DEFAULT_DKG_PHASE_TIMEOUT, gk)
+            .await
     }
 
     fn initial_config(
@@ -262,6 +212,120 @@ impl<S: Scheme> ExecuteDkg for BeaconProcess<S> {
     }
 }
 
+/// Compares participants of the last finished epoch with participants of the new epoch,
+/// reports changed addresses and keys and appends them to the identity history.
+fn track_identity_changes<S: Scheme>(bp: &BeaconProcess<S>, current: &State<S>, l: &Span) {
+    let previous = match bp.dkg_store().get_finished::<S>() {
+        Ok(previous) => previous,
+        Err(err) => {
+            error!(parent: l, "identity changes: failed to load the finished state: {err}");
+            return;
+        }
+    };
+    let old: Vec<&Participant> = previous.remaining.iter().chain(&previous.joining).collect();
+    let new: Vec<&Participant> = current.remaining.iter().chain(&current.joining).collect();
+
+    let changes = identity::detect(current.epoch(), &old, &new);
+    if changes.is_empty() {
+        return;
+    }
+    for change in &changes {
+        warn!(parent: l, "{change}");
+    }
+    if let Err(err) = bp.dkg_store().append_identity_changes(&changes) {
+        error!(parent: l, "failed to store identity changes: {err}");
+    }
+}
+
+impl<S: Scheme> BeaconProcess<S> {
+    /// Sets up the protocol with given phase timeout and runs it at `execution_time`, the time
+    /// may have passed for an execution resumed after a restart, see [`super::resume`].
+    pub(super) async fn run_dkg(
+        &self,
+        execution_time: Duration,
+        phase_timeout: Duration,
+        gk: &mut GateKeeper<S>,
+    ) -> Result<(), ActionsError> {
+        // # Setup DKG #
+        // Get current and last completed states
+        let current = self.dkg_store().get_current::<S>()?;
+        let last_completed = match self.dkg_store().get_finished::<S>() {
+            Ok(state) => Some(state),
+            Err(DkgStoreError::NotFound) => None,
+            Err(err) => return Err(err.into()),
+        };
+
+        // Sort all participants by public_key.
+        let mut sorted_participants =
+            Vec::with_capacity(current.joining.len() + current.remaining.len());
+        sorted_participants.extend(current.joining.iter());
+        sorted_participants.extend(current.remaining.iter());
+        sorted_participants.sort_by_key(|p| &p.key);
+
+        let time_until_execution = execution_time.saturating_sub(self.clock().now());
+
+        // Setup config for DKG protocol.
+        let config = match last_completed {
+            Some(previous) => self.reshare_config(&current, previous, &sorted_participants)?,
+            None => self.initial_config(&current, &sorted_participants)?,
+        };
+        let dkg_log = config.log.clone();
+        let auth = BundleAuth::new(&config);
+
+        // Initialize DKG protocol instance with channels for input and output.
+        let (protocol, bundles_rx, bundles_tx) =
+            Protocol::new_dkg(config, phase_timeout).map_err(ActionsError::DkgError)?;
+
+        // Broadcast holds bundles receiver during execution.
+        let evidence = self.dkg_store().evidence(current.epoch());
+        let broadcast = Broadcast::init(
+            self.id(),
+            &sorted_participants,
+            &self.identity().address,
+            &current.leader.address,
+            self.fanout(),
+            evidence.clone(),
+            &dkg_log,
+        );
+
+        // Gatekeeper holds bundles sender during execution.
+        gk.open_gate(
+            bundles_tx,
+            broadcast.relay(),
+            evidence,
+            auth,
+            self.tracker(),
+        )?;
+        broadcast.register_nodes(self.tracker(), bundles_rx);
+
+        // # Run DKG #
+        let bp = self.clone();
+        self.tracker().spawn({ async move {
+            info!(parent: &dkg_log, "waiting for execution time: {} seconds", time_until_execution.as_secs());
+            bp.clock().sleep_until(execution_time).await;
+
+            let dkg_output=protocol.run().await;
+            bp.dkg_finished_notification().await;
+
+            match dkg_output{
+                Ok(Some(output)) => process_dkg_output(&bp, output, current, &dkg_log).await,
+                Ok(None) => info!(parent: &dkg_log, "DKG[Leaving] finished succesfully"),
+                Err(err) => error!(parent: &dkg_log, "DKG finished with error: {err}"),
+            }
+        }});
+
+        Ok(())
+    }
+}
+
+/// Maps start of execution into Unix time.
+pub(super) fn execution_time(start: Timestamp) -> Result<Duration, ActionsError> {
+    SystemTime::try_from(start)
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .ok_or(ActionsError::StartExecutionTimeNotCanonical)
+}
+
 // Returns round of transition to new group.
 async fn process_dkg_output<S: Scheme>(
     bp: &BeaconProcess<S>,
@@ -275,7 +339,7 @@ async fn process_dkg_output<S: Scheme>(
         info!(parent: l, "DKG [Initial] finished succesfully");
         u64::try_from(current.genesis_time.seconds).unwrap()
     } else {
-        let now = time_now().as_secs();
+        let now = bp.clock().now().as_secs();
         info!(parent: l, "DKG [Reshape] finished succesfully");
 
         let beacon_period = current.beacon_period.get_value();
@@ -297,6 +361,14 @@ async fn process_dkg_output<S: Scheme>(
         error!(parent: l, "failed to store groupfile: {err}");
         return;
     }
+    // Group history is not critical for the chain, failure is only reported.
+    if let Err(err) = bp.fs().save_group_epoch(&final_group, current.epoch()) {
+        error!(parent: l, "failed to store groupfile into history for epoch {}: {err}", current.epoch());
+    }
+
+    if !is_first_epoch {
+        track_identity_changes(bp, &current, l);
+    }
 
     let period = final_group.period.get_value();
     let genesis_time = final_group.genesis_time;
@@ -321,13 +393,15 @@ async fn process_dkg_output<S: Scheme>(
     info!(parent: l,"preparing transition to new group at_round: {t_round}");
 
     // Sleep until last round of current epoch.
-    let now = time_now().as_secs();
+    let now = bp.clock().now().as_secs();
     let last_round = Duration::from_secs(transition_time - u64::from(period)).as_secs();
     let delta = last_round.saturating_sub(now);
 
     if delta != 0 {
         info!(parent: l, "sleeping until last round before transition: {delta}s");
-        tokio::time::sleep(Duration::from_secs(delta)).await;
+        bp.clock()
+            .sleep_until(Duration::from_secs(last_round))
+            .await;
     }
 
     if bp
@@ -405,3 +479,85 @@ fn nonce_for_epoch(epoch: u32) -> [u8; 32] {
     h.update(epoch.to_be_bytes());
     h.finalize().into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::time::time_now;
+    use crate::chain::time::Clock;
+    use crate::chain::time::MockClock;
+    use crate::chain::time::SharedClock;
+    use crate::core::beacon::DEFAULT_BEACON_ID;
+    use crate::dkg::status::Status;
+    use crate::net::dkg_public::DkgPublicClient;
+    use crate::net::sim;
+    use crate::net::sim::SimLeader;
+    use crate::protobuf::dkg::packet::Bundle;
+    use crate::protobuf::dkg::DealBundle;
+    use crate::protobuf::dkg::DkgPacket;
+    use crate::protobuf::dkg::Packet;
+    use crate::protobuf::drand::Metadata;
+    use energon::drand::schemes::DefaultScheme;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn dkg_excludes_bad_dealer() {
+        let mock = Arc::new(MockClock::new(time_now()));
+        let clock: SharedClock = mock.clone();
+        // The last participant only sends a forged deal.
+        let seeds: [&[u8]; 4] = [b"dealer-0", b"dealer-1", b"dealer-2", b"bad-dealer"];
+        let (nodes, participants) = sim::participants(&seeds, &vec![clock.clone(); 3]);
+        let bad = participants[3].clone();
+        let mut sorted: Vec<_> = participants.iter().map(|p| p.key.clone()).collect();
+        sorted.sort();
+        let bad_index = u32::try_from(sorted.iter().position(|k| *k == bad.key).unwrap()).unwrap();
+
+        let now = i64::try_from(mock.now().as_secs()).unwrap();
+        let leader =
+            SimLeader::<DefaultScheme>::first_epoch(seeds[0], &participants, 0, 3, now + 3600)
+                .unwrap();
+        leader.run(&nodes, now + 1).await.unwrap();
+        let forged = DkgPacket {
+            dkg: Some(Packet {
+                metadata: Some(Metadata::with_id(DEFAULT_BEACON_ID.into())),
+                bundle: Some(Bundle::Deal(DealBundle {
+                    dealer_index: bad_index,
+                    session_id: nonce_for_epoch(1).to_vec(),
+                    signature: vec![1; 64],
+                    ..Default::default()
+                })),
+            }),
+        };
+        for node in &nodes {
+            let mut client = DkgPublicClient::new(&node.address).await.unwrap();
+            // Bundle is rejected in the background, the request itself succeeds.
+            let _ = client.broadcast_dkg(forged.clone()).await;
+        }
+        mock.advance(Duration::from_secs(2));
+
+        assert!(sim::finished(&nodes)
+            .await
+            .iter()
+            .all(|s| *s == Status::Complete));
+        for node in &nodes {
+            let group: Group<DefaultScheme> = node.store().load_group().unwrap();
+            assert_eq!(group.nodes.len(), 3);
+            assert!(group
+                .nodes
+                .iter()
+                .all(|n| n.public().address() != bad.address.as_str()));
+
+            // Forged deal is not recorded, deals of honest dealers are.
+            let evidence =
+                std::fs::read_to_string(node.dkg_dir().join("evidence").join("1.jsonl")).unwrap();
+            let deals: Vec<u64> = evidence
+                .lines()
+                .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+                .filter(|r| r["kind"] == "Deal")
+                .map(|r| r["issuer_index"].as_u64().unwrap())
+                .collect();
+            assert!(!deals.is_empty());
+            assert!(!deals.contains(&u64::from(bad_index)));
+        }
+    }
+}
@@ -0,0 +1,314 @@
+//! Dissemination of DKG bundles and gossip packets, see `--dkg-fanout` of `drand start`.
+//!
+//! Bundles authored by this node and bundles of other participants (echo broadcast, see
+//! [`super::utils::GateKeeper::broadcast`]) are sent depending on the strategy of the beacon id:
+//! - `full`: the author sends to every participant and every participant relays a new packet to
+//!   all others. Messages grow cubically with the group size, this is the behavior of Go drand.
+//! - `leader-hub`: the author sends to the leader only, the leader relays every new packet to all
+//!   participants. Packets reach the group in two hops with about one message per participant.
+//!   Strategy falls back to `full` if the leader is not a participant of the execution.
+//! - `random:<K>`: the author sends to K random participants and every participant relays a new
+//!   packet to K random participants except the author, about K messages per participant. K of
+//!   `ln(n) + 5` reaches all of n participants with high probability, packets missed by honest
+//!   nodes are recovered by relays of Go nodes and by complaints of the DKG.
+//!
+//! Gossip packets of the leader are sent by the leader to every participant, as Go drand does.
+//! Received ones are relayed depending on the strategy, except with `full`, so Go nodes do not
+//! receive copies. The only gossip packet authored by this node is the abort of the fallback
+//! coordinator, see [`super::recovery`]: it is sent to every participant and never relayed, as
+//! the leader is unreachable and golang nodes accept aborts only from the leader.
+//!
+//! Packets are signed by the author and verified before they are relayed, so relays can not
+//! alter them. Duplicates are dropped by packet id, see [`packet_id`]. Strategies other than
+//! `full` rely on relaying and require all participants to use the same strategy.
+use crate::core::beacon::BeaconProcess;
+use crate::key::Scheme;
+use crate::net::dkg_public::DkgPublicClient;
+use crate::net::utils::Address;
+use crate::transport::dkg::GossipData;
+use crate::transport::dkg::GossipPacket;
+
+use rand::seq::SliceRandom;
+use sha2::Digest;
+use sha2::Sha256;
+use std::str::FromStr;
+use tracing::warn;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum FanOut {
+    #[default]
+    Full,
+    LeaderHub,
+    Random(usize),
+}
+
+/// Source of a disseminated packet.
+#[derive(Debug, Copy, Clone)]
+pub enum Source<'a> {
+    /// Packet authored by this node.
+    Own,
+    /// Packet of another participant, the author is not a recipient if known.
+    Relayed(Option<&'a Address>),
+}
+
+impl FanOut {
+    /// Returns recipients of a packet among `participants`.
+    pub fn recipients<'a>(
+        self,
+        participants: impl IntoIterator<Item = &'a Address>,
+        me: &Address,
+        leader: &Address,
+        source: Source<'_>,
+    ) -> Vec<&'a Address> {
+        let participants: Vec<&Address> = participants.into_iter().collect();
+        let has_leader = participants.contains(&leader);
+        let origin = match source {
+            Source::Own => None,
+            Source::Relayed(origin) => origin,
+        };
+        let mut others: Vec<&Address> = participants
+            .into_iter()
+            .filter(|p| *p != me && Some(*p) != origin)
+            .collect();
+
+        match (self, source) {
+            (Self::Full, _) => others,
+            (Self::LeaderHub, _) if me == leader || !has_leader => others,
+            (Self::LeaderHub, Source::Own) => others.into_iter().filter(|p| *p == leader).collect(),
+            (Self::LeaderHub, Source::Relayed(_)) => vec![],
+            (Self::Random(k), _) => {
+                others.shuffle(&mut rand::rng());
+                others.truncate(k);
+                others
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for FanOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full => f.write_str("full"),
+            Self::LeaderHub => f.write_str("leader-hub"),
+            Self::Random(k) => write!(f, "random:{k}"),
+        }
+    }
+}
+
+impl FromStr for FanOut {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(Self::Full),
+            "leader-hub" => Ok(Self::LeaderHub),
+            _ => match s.strip_prefix("random:").map(str::parse) {
+                Some(Ok(k)) if k > 0 => Ok(Self::Random(k)),
+                _ => Err(format!(
+                    "unknown dkg fan-out {s}, expected: full, leader-hub, random:<K> with K > 0"
+                )),
+            },
+        }
+    }
+}
+
+/// Entry of `--dkg-fanout`: strategy of all beacon ids or `<beacon id>=<strategy>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BeaconFanOut {
+    pub id: Option<String>,
+    pub fanout: FanOut,
+}
+
+impl FromStr for BeaconFanOut {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((id, fanout)) => Ok(Self {
+                id: Some(id.trim().to_string()),
+                fanout: fanout.trim().parse()?,
+            }),
+            None => Ok(Self {
+                id: None,
+                fanout: s.trim().parse()?,
+            }),
+        }
+    }
+}
+
+/// Returns strategy of the beacon id: its own entry, otherwise the entry for all beacon ids,
+/// otherwise `full`.
+pub fn fanout_of(config: &[BeaconFanOut], id: &str) -> FanOut {
+    config
+        .iter()
+        .rfind(|e| e.id.as_deref() == Some(id))
+        .or_else(|| config.iter().rfind(|e| e.id.is_none()))
+        .map_or(FanOut::Full, |e| e.fanout)
+}
+
+/// Returns id of a gossip packet: hash of its signature, which is unique for the author and content.
+pub fn packet_id(packet: &GossipPacket) -> [u8; 32] {
+    Sha256::digest(&packet.metadata.signature).into()
+}
+
+/// Sends the packet to `recipients` in the background, failures are logged.
+pub(super) fn send<S: Scheme>(
+    bp: &BeaconProcess<S>,
+    packet: GossipPacket,
+    recipients: Vec<&Address>,
+) {
+    for address in recipients {
+        let (address, packet, log) = (address.clone(), packet.clone(), bp.log().clone());
+        bp.tracker().spawn(async move {
+            let sent = match DkgPublicClient::new(&address).await {
+                Ok(mut client) => client.packet(packet.into()).await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = sent {
+                warn!(parent: &log, "dkg gossip: failed to send packet to {address}: {err}");
+            }
+        });
+    }
+}
+
+impl<S: Scheme> BeaconProcess<S> {
+    /// Relays a new verified packet of another participant according to the strategy of the
+    /// beacon id. Gossip is not relayed with `full`, as the leader sends it to every participant.
+    pub(crate) fn relay_gossip(&self, packet: &GossipPacket) {
+        let fanout = self.fanout();
+        if fanout == FanOut::Full
+            || packet.metadata.address == self.identity().address
+            || matches!(packet.data, GossipData::Abort(_))
+        {
+            return;
+        }
+        let state = match self.dkg_store().get_current::<S>() {
+            Ok(state) => state,
+            Err(err) => {
+                warn!(parent: self.log(), "dkg gossip: failed to load state: {err}");
+                return;
+            }
+        };
+        let recipients = fanout.recipients(
+            state.participants().map(|p| &p.address),
+            &self.identity().address,
+            &state.leader.address,
+            Source::Relayed(Some(&packet.metadata.address)),
+        );
+        send(self, packet.clone(), recipients);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::collections::VecDeque;
+
+    fn group(n: usize) -> Vec<Address> {
+        (1..=n)
+            .map(|i| Address::precheck(&format!("127.0.0.1:{i}")).unwrap())
+            .collect()
+    }
+
+    /// Disseminates a packet of every participant, each participant relays a packet once on its
+    /// first receipt. Returns amount of sent messages and amount of participants which received
+    /// all packets.
+    fn disseminate(fanout: FanOut, group: &[Address]) -> (usize, usize) {
+        let leader = &group[0];
+        let mut messages = 0;
+        let mut complete = 0;
+        let mut received: Vec<HashSet<&Address>> = vec![HashSet::new(); group.len()];
+        for author in group {
+            let mut queue: VecDeque<&Address> =
+                fanout.recipients(group, author, leader, Source::Own).into();
+            while let Some(to) = queue.pop_front() {
+                messages += 1;
+                let i = group.iter().position(|p| p == to).unwrap();
+                if received[i].insert(author) {
+                    let relayed = Source::Relayed(Some(author));
+                    queue.extend(fanout.recipients(group, to, leader, relayed));
+                }
+            }
+        }
+        for (i, p) in group.iter().enumerate() {
+            // Own packet is not received.
+            if received[i].len() == group.len() - 1 && !received[i].contains(p) {
+                complete += 1;
+            }
+        }
+
+        (messages, complete)
+    }
+
+    #[test]
+    fn messages_by_strategy() {
+        let n = 32;
+        let group = group(n);
+
+        // Every packet is sent by its author and relayed by every receiver.
+        assert_eq!(
+            disseminate(FanOut::Full, &group),
+            (n * (n - 1) * (n - 1), n)
+        );
+        // Packet of the leader is sent once to each participant, packets of others once to the
+        // leader and once by the leader to each other participant.
+        assert_eq!(disseminate(FanOut::LeaderHub, &group), (n * (n - 1), n));
+        // Author and each receiver send K messages.
+        let k = 10;
+        let (messages, complete) = disseminate(FanOut::Random(k), &group);
+        assert!(messages <= n * n * k);
+        assert!(messages >= n * k * (k + 1));
+        assert!(complete > 0);
+    }
+
+    #[test]
+    fn recipients_by_strategy() {
+        let group = group(6);
+        let (leader, me, origin) = (&group[0], &group[1], &group[2]);
+        let count = |fanout: FanOut, me: &Address, source: Source<'_>| {
+            fanout.recipients(&group, me, leader, source).len()
+        };
+        let relayed = Source::Relayed(Some(origin));
+
+        assert_eq!(count(FanOut::Full, me, Source::Own), 5);
+        assert_eq!(count(FanOut::Full, me, relayed), 4);
+        assert_eq!(count(FanOut::Full, me, Source::Relayed(None)), 5);
+        assert!(FanOut::LeaderHub.recipients(&group, me, leader, Source::Own) == [leader]);
+        assert_eq!(count(FanOut::LeaderHub, me, relayed), 0);
+        assert_eq!(count(FanOut::LeaderHub, leader, relayed), 4);
+        assert_eq!(count(FanOut::LeaderHub, leader, Source::Own), 5);
+        // Leader out of the group can not serve as a hub.
+        let outside = Address::precheck("127.0.0.1:100").unwrap();
+        assert_eq!(
+            FanOut::LeaderHub
+                .recipients(&group, me, &outside, Source::Own)
+                .len(),
+            5
+        );
+        assert_eq!(count(FanOut::Random(3), me, Source::Own), 3);
+        assert_eq!(count(FanOut::Random(10), me, relayed), 4);
+        let random = FanOut::Random(2).recipients(&group, me, leader, relayed);
+        assert!(!random.contains(&me) && !random.contains(&origin));
+
+        assert_eq!("random:3".parse(), Ok(FanOut::Random(3)));
+        assert_eq!("full".parse(), Ok(FanOut::Full));
+        assert_eq!("leader-hub".parse(), Ok(FanOut::LeaderHub));
+        assert_eq!(FanOut::Random(3).to_string(), "random:3");
+        assert!("random:0".parse::<FanOut>().is_err());
+        assert!("star".parse::<FanOut>().is_err());
+    }
+
+    #[test]
+    fn fanout_per_beacon_id() {
+        let config: Vec<BeaconFanOut> = ["quicknet=random:4", "leader-hub", "evmnet = full"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        assert_eq!(fanout_of(&config, "quicknet"), FanOut::Random(4));
+        assert_eq!(fanout_of(&config, "evmnet"), FanOut::Full);
+        assert_eq!(fanout_of(&config, "default"), FanOut::LeaderHub);
+        assert_eq!(fanout_of(&[], "default"), FanOut::Full);
+        assert!("quicknet=star".parse::<BeaconFanOut>().is_err());
+    }
+}
@@ -0,0 +1,187 @@
+//! Detection of participant identity changes across reshares.
+//!
+//! A participant is matched between epochs either by its key or by its address.
+//! Matching by key with a different address means the node has moved, matching by
+//! address with a different key means the node has rotated its identity.
+use crate::key::toml::Toml;
+use crate::net::utils::Address;
+use crate::protobuf::dkg as protobuf;
+use crate::transport::dkg::Participant;
+
+use std::fmt::Display;
+use std::str::FromStr;
+use toml_edit::Table;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    Address,
+    Key,
+}
+
+impl Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Address => write!(f, "address"),
+            Self::Key => write!(f, "key"),
+        }
+    }
+}
+
+impl FromStr for ChangeKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "address" => Ok(Self::Address),
+            "key" => Ok(Self::Key),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdentityChange {
+    /// Epoch at which the change took effect.
+    pub epoch: u32,
+    pub kind: ChangeKind,
+    /// Address of the participant in the new epoch.
+    pub address: Address,
+    /// Previous address or hex encoded key, depending on [`ChangeKind`].
+    pub previous: String,
+    /// New address or hex encoded key, depending on [`ChangeKind`].
+    pub current: String,
+}
+
+impl Display for IdentityChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{{\"identity_change\": \"{}\", \"epoch\": {}, \"address\": \"{}\", \"previous\": \"{}\", \"current\": \"{}\"}}",
+            self.kind,
+            self.epoch,
+            self.address,
+            self.previous,
+            self.current
+        )
+    }
+}
+
+/// Returns identity changes between participants of the previous epoch and participants of the new `epoch`.
+///
+/// Participants which are matched neither by key nor by address are joiners and are not reported.
+pub fn detect(epoch: u32, previous: &[&Participant], next: &[&Participant]) -> Vec<IdentityChange> {
+    let mut changes = vec![];
+
+    for p in next {
+        if let Some(old) = previous.iter().find(|old| old.key == p.key) {
+            if old.address != p.address {
+                changes.push(IdentityChange {
+                    epoch,
+                    kind: ChangeKind::Address,
+                    address: p.address.clone(),
+                    previous: old.address.to_string(),
+                    current: p.address.to_string(),
+                });
+            }
+        } else if let Some(old) = previous.iter().find(|old| old.address == p.address) {
+            changes.push(IdentityChange {
+                epoch,
+                kind: ChangeKind::Key,
+                address: p.address.clone(),
+                previous: hex::encode(&old.key),
+                current: hex::encode(&p.key),
+            });
+        }
+    }
+
+    changes
+}
+
+impl Toml for IdentityChange {
+    type Inner = Table;
+
+    fn toml_encode(&self) -> Option<Self::Inner> {
+        let mut table = Self::Inner::new();
+        let _ = table.insert("Epoch", i64::from(self.epoch).into());
+        let _ = table.insert("Kind", self.kind.to_string().into());
+        let _ = table.insert("Address", self.address.as_str().into());
+        let _ = table.insert("Previous", self.previous.as_str().into());
+        let _ = table.insert("Current", self.current.as_str().into());
+
+        Some(table)
+    }
+
+    fn toml_decode(table: &Self::Inner) -> Option<Self> {
+        let epoch = u32::try_from(table.get("Epoch")?.as_integer()?).ok()?;
+        let kind = ChangeKind::from_str(table.get("Kind")?.as_str()?).ok()?;
+        let address = Address::precheck(table.get("Address")?.as_str()?).ok()?;
+        let previous = table.get("Previous")?.as_str()?.to_string();
+        let current = table.get("Current")?.as_str()?.to_string();
+
+        Some(Self {
+            epoch,
+            kind,
+            address,
+            previous,
+            current,
+        })
+    }
+}
+
+impl From<IdentityChange> for protobuf::IdentityChange {
+    fn from(value: IdentityChange) -> Self {
+        let IdentityChange {
+            epoch,
+            kind,
+            address,
+            previous,
+            current,
+        } = value;
+
+        Self {
+            epoch,
+            kind: kind.to_string(),
+            address: address.to_string(),
+            previous,
+            current,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn participant(address: &str, key: &[u8]) -> Participant {
+        Participant {
+            address: Address::precheck(address).unwrap(),
+            key: key.to_vec(),
+            signature: vec![],
+        }
+    }
+
+    #[test]
+    fn detect_changes() {
+        let a = participant("a.com:1111", &[1]);
+        let b = participant("b.com:2222", &[2]);
+        let c = participant("c.com:3333", &[3]);
+
+        // a moved, b rotated the key, c joined.
+        let a_moved = participant("a2.com:1111", &[1]);
+        let b_rotated = participant("b.com:2222", &[20]);
+        let changes = detect(2, &[&a, &b], &[&a_moved, &b_rotated, &c]);
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].kind, ChangeKind::Address);
+        assert_eq!(changes[0].previous, "a.com:1111");
+        assert_eq!(changes[0].current, "a2.com:1111");
+        assert_eq!(changes[1].kind, ChangeKind::Key);
+        assert_eq!(changes[1].previous, "01");
+        assert_eq!(changes[1].current, "14");
+
+        let decoded = IdentityChange::toml_decode(&changes[1].toml_encode().unwrap()).unwrap();
+        assert_eq!(decoded, changes[1]);
+
+        assert!(detect(2, &[&a, &b], &[&a, &b]).is_empty());
+    }
+}
@@ -2,10 +2,20 @@ pub mod actions_active;
 pub mod actions_passive;
 pub mod actions_signing;
 pub mod broadcast;
+pub mod evidence;
 pub mod execution;
+pub mod fanout;
+pub mod identity;
+pub mod pipeline;
+pub mod proposal;
+pub mod recovery;
+pub mod reset;
+pub mod resume;
 pub mod state;
 pub mod status;
 pub mod store;
+pub mod terms;
+pub mod testnet;
 pub mod utils;
 
 pub use energon::kyber::dkg::Node as DkgNode;
@@ -30,8 +40,10 @@ pub enum ActionsError {
     ProtocolIsNotRunning,
     #[error("dkg protocol already running")]
     ProtocolAlreadyRunning,
-    #[error("failed to deserialize bundle from proto")]
-    InvalidProtoBundle,
+    #[error("dkg packet pipeline is full")]
+    PipelineIsFull,
+    #[error("dkg state is changed during verification of gossip packet")]
+    StaleVerification,
     #[error("unknown start execution time - input is not canonical")]
     StartExecutionTimeNotCanonical,
     #[error("received start execution time must be in future")]
@@ -44,6 +56,10 @@ pub enum ActionsError {
     ResharePrevGroupRequired,
     #[error("reshare: previous share can not be empty")]
     ResharePrevShareRequired,
+    #[error("failed to sign gossip packet")]
+    Sign,
+    #[error("reset: {0}")]
+    Reset(#[from] crate::dkg::reset::ResetError),
     #[error("TODO: this dkg action is not implemented yet")]
     Todo,
 }
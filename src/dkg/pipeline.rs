@@ -0,0 +1,150 @@
+//! Processing of DKG bundles received during execution.
+//!
+//! Decoding of a bundle checks its signature and every point of it, which is costly for deals of
+//! large groups. Bundles filtered by the gatekeeper are decoded on the blocking pool, up to [`MAX_PENDING`] at
+//! a time, and applied (passed to the protocol and relayed) in order of arrival by a separate
+//! task. The beacon process keeps serving other commands while a flood of bundles is verified.
+//!
+//! Once the queue is full new bundles are rejected, the echo of other participants delivers them
+//! later, see [`super::broadcast`].
+use super::ActionsError;
+
+use crate::protobuf::dkg::DkgPacket;
+
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::task::JoinHandle;
+use tokio_util::task::TaskTracker;
+use tracing::warn;
+use tracing::Span;
+
+/// Bundles which are being decoded or wait to be applied.
+pub const MAX_PENDING: usize = 64;
+
+/// Decoding bundle with its packet, `true` marks bundles replayed after a restart.
+type Pending<T> = (JoinHandle<Option<T>>, DkgPacket, bool);
+/// Verifies and decodes a bundle, `None` rejects the bundle.
+type Decode<T> = Arc<dyn Fn(DkgPacket) -> Option<T> + Send + Sync>;
+
+pub(super) struct Pipeline<T> {
+    queue: mpsc::Sender<Pending<T>>,
+    decode: Decode<T>,
+}
+
+impl<T: Send + 'static> Pipeline<T> {
+    /// Spawns the task which applies decoded bundles on the tracker, bundles which can not be
+    /// decoded are skipped. The task is stopped once the pipeline is dropped or `apply` returns false.
+    pub(super) fn start<F, Fut>(
+        decode: Decode<T>,
+        mut apply: F,
+        tracker: &TaskTracker,
+        log: &Span,
+    ) -> Self
+    where
+        F: FnMut(T, DkgPacket, bool) -> Fut + Send + 'static,
+        Fut: Future<Output = bool> + Send,
+    {
+        let (queue, mut rx) = mpsc::channel::<Pending<T>>(MAX_PENDING);
+        let log = log.to_owned();
+        tracker.spawn(async move {
+            while let Some((decoded, proto, replayed)) = rx.recv().await {
+                match decoded.await {
+                    Ok(Some(bundle)) => {
+                        if !apply(bundle, proto, replayed).await {
+                            break;
+                        }
+                    }
+                    Ok(None) => {
+                        warn!(parent: &log, "dkg pipeline: rejected bundle which can not be verified or decoded")
+                    }
+                    Err(err) => warn!(parent: &log, "dkg pipeline: decoding failed: {err}"),
+                }
+            }
+        });
+
+        Self { queue, decode }
+    }
+
+    /// Queues the bundle for decoding, it is rejected with [`ActionsError::PipelineIsFull`]
+    /// if [`MAX_PENDING`] bundles are already queued.
+    pub(super) fn push(&self, proto: DkgPacket) -> Result<(), ActionsError> {
+        let permit = self.queue.try_reserve().map_err(|err| match err {
+            TrySendError::Full(()) => ActionsError::PipelineIsFull,
+            TrySendError::Closed(()) => ActionsError::ProtocolIsNotRunning,
+        })?;
+        self.send(permit, proto, false);
+
+        Ok(())
+    }
+
+    /// Queues bundle replayed after a restart for decoding, waits while [`MAX_PENDING`] bundles
+    /// are already queued.
+    pub(super) async fn push_wait(&self, proto: DkgPacket) -> Result<(), ActionsError> {
+        let permit = self
+            .queue
+            .reserve()
+            .await
+            .map_err(|_| ActionsError::ProtocolIsNotRunning)?;
+        self.send(permit, proto, true);
+
+        Ok(())
+    }
+
+    fn send(&self, permit: mpsc::Permit<'_, Pending<T>>, proto: DkgPacket, replayed: bool) {
+        let decode = self.decode.clone();
+        let input = proto.clone();
+        permit.send((
+            tokio::task::spawn_blocking(move || decode(input)),
+            proto,
+            replayed,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protobuf::dkg::Packet;
+
+    fn packet(index: u32) -> DkgPacket {
+        DkgPacket {
+            dkg: (index > 0).then(|| Packet {
+                metadata: Some(crate::protobuf::drand::Metadata::with_id(index.to_string())),
+                bundle: None,
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn applied_in_order() {
+        let decode = |proto: DkgPacket| -> Option<u32> {
+            // Earlier bundles take longer to decode.
+            let index: u32 = proto.dkg?.metadata?.beacon_id.parse().ok()?;
+            std::thread::sleep(std::time::Duration::from_millis(u64::from(10 - index)));
+            Some(index)
+        };
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let pipeline = Pipeline::start(
+            Arc::new(decode),
+            move |index, _, _| {
+                let tx = tx.clone();
+                async move { tx.send(index).is_ok() }
+            },
+            &TaskTracker::new(),
+            &Span::none(),
+        );
+        for index in [1, 2, 0, 3] {
+            pipeline.push(packet(index)).unwrap();
+        }
+        drop(pipeline);
+
+        let mut applied = vec![];
+        while let Some(index) = rx.recv().await {
+            applied.push(index);
+        }
+        // Bundle which can not be decoded is skipped.
+        assert_eq!(applied, [1, 2, 3]);
+    }
+}
@@ -0,0 +1,295 @@
+//! Proposal file assembled from participant public key files.
+//!
+//! The file lists participants by role and is consumed by `dkg init` and `dkg reshare`
+//! of the leader. Encoding is byte-compatible with proposal files of golang implementation:
+//! field names, order of roles and hex encoded keys and signatures are the same.
+//! Threshold and timing values are not part of the file format and are reported
+//! separately, see [`ProposalFile::header`].
+use super::terms;
+use super::terms::TermsError;
+
+use crate::key::group::minimum_t;
+use crate::key::toml::prefix_keys;
+use crate::key::toml::Toml;
+use crate::key::Scheme;
+use crate::protobuf::dkg::ProposalOptions;
+use crate::transport::dkg::Participant;
+use crate::transport::drand::GroupPacket;
+
+use energon::drand::schemes::DefaultScheme;
+use energon::drand::schemes::SigsOnG1Scheme;
+use energon::drand::schemes::UnchainedScheme;
+use prost_types::Timestamp;
+use std::path::Path;
+use toml_edit::ArrayOfTables;
+use toml_edit::DocumentMut;
+use toml_edit::Item;
+use toml_edit::Table;
+
+/// Default timeout of DKG proposal.
+pub const DEFAULT_PROPOSAL_TIMEOUT: &str = "24h";
+/// Default catchup period.
+pub const DEFAULT_CATCHUP_PERIOD: &str = "0s";
+
+#[derive(thiserror::Error, Debug)]
+pub enum ProposalError {
+    #[error("failed to read public key file {0}: {1}")]
+    Read(String, std::io::Error),
+    #[error("public key file {0} is not valid TOML")]
+    ParseStringError(String),
+    #[error("public key file {0} is missing required fields")]
+    MissingFields(String),
+    #[error("public key file {0} has unknown scheme {1}")]
+    UnknownScheme(String, String),
+    #[error("public key file {0} has invalid signature")]
+    InvalidSignature(String),
+    #[error("participants use different schemes: {0} and {1}")]
+    SchemeMismatch(String, String),
+    #[error("{0}")]
+    Terms(#[from] TermsError),
+    #[error("proposal requires at least one joiner or remainer")]
+    Empty,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ProposalFile {
+    pub joining: Vec<Participant>,
+    pub remaining: Vec<Participant>,
+    pub leaving: Vec<Participant>,
+}
+
+impl ProposalFile {
+    /// Assembles a proposal from paths to public key files (`drand_id.public`) of each role.
+    ///
+    /// Returns the proposal along with the scheme shared by all participants.
+    pub fn from_public_files(
+        joining: &[String],
+        remaining: &[String],
+        leaving: &[String],
+    ) -> Result<(Self, String), ProposalError> {
+        if joining.is_empty() && remaining.is_empty() {
+            return Err(ProposalError::Empty);
+        }
+        let mut scheme: Option<String> = None;
+        let mut load = |paths: &[String]| -> Result<Vec<Participant>, ProposalError> {
+            let mut participants = Vec::with_capacity(paths.len());
+            for path in paths {
+                let (participant, id) = read_public_file(Path::new(path))?;
+                match &scheme {
+                    Some(expected) if *expected != id => {
+                        return Err(ProposalError::SchemeMismatch(expected.clone(), id));
+                    }
+                    Some(_) => (),
+                    None => scheme = Some(id),
+                }
+                participants.push(participant);
+            }
+            Ok(participants)
+        };
+
+        let proposal = Self {
+            joining: load(joining)?,
+            remaining: load(remaining)?,
+            leaving: load(leaving)?,
+        };
+        let scheme = scheme.ok_or(ProposalError::Empty)?;
+
+        terms::check_unique(proposal.all())?;
+
+        Ok((proposal, scheme))
+    }
+
+    fn all(&self) -> impl Iterator<Item = &Participant> {
+        self.joining
+            .iter()
+            .chain(&self.remaining)
+            .chain(&self.leaving)
+    }
+
+    /// Size of the group after the proposal is executed.
+    pub fn group_size(&self) -> usize {
+        self.joining.len() + self.remaining.len()
+    }
+
+    /// Minimum threshold for the resulting group.
+    pub fn threshold(&self) -> usize {
+        minimum_t(self.group_size())
+    }
+
+    /// Validates group arithmetic of the proposal, returns the threshold to propose.
+    ///
+    /// Threshold defaults to the minimum one, `previous_threshold` is the threshold of the current group for reshares.
+    pub fn check_terms(
+        &self,
+        threshold: Option<usize>,
+        previous_threshold: Option<usize>,
+    ) -> Result<usize, TermsError> {
+        let threshold = threshold.unwrap_or_else(|| self.threshold());
+        terms::check_threshold(threshold, self.group_size())?;
+        if let Some(previous) = previous_threshold {
+            terms::check_leavers(self.remaining.len(), self.leaving.len(), previous)?;
+        }
+
+        Ok(threshold)
+    }
+
+    /// Informational summary with threshold and timing defaults, formatted as TOML comments.
+    pub fn header(&self, scheme: &str, threshold: usize) -> String {
+        format!(
+            "# Generated proposal, scheme: {scheme}\n# Group size: {}, minimum threshold: {}\n# Defaults: --threshold {threshold} --timeout {DEFAULT_PROPOSAL_TIMEOUT} --catchup-period {DEFAULT_CATCHUP_PERIOD}\n",
+            self.group_size(),
+            self.threshold(),
+        )
+    }
+}
+
+/// Returns options of a reshare with the same members as the given group, which refreshes key
+/// shares without changing the group public key. Group threshold is kept unless overridden.
+pub fn refresh_options(
+    group: &GroupPacket,
+    threshold: Option<u32>,
+    timeout: Timestamp,
+) -> Result<ProposalOptions, TermsError> {
+    let threshold = threshold.unwrap_or(group.threshold);
+    terms::check_threshold(threshold as usize, group.nodes.len())?;
+
+    let mut nodes: Vec<_> = group.nodes.iter().collect();
+    nodes.sort_by_key(|n| n.index);
+    let remaining = nodes
+        .into_iter()
+        .map(|n| {
+            Participant {
+                address: n.public.address.clone(),
+                key: n.public.key.clone(),
+                signature: n.public.signature.clone(),
+            }
+            .into()
+        })
+        .collect();
+
+    Ok(ProposalOptions {
+        timeout: Some(timeout),
+        threshold,
+        catchup_period_seconds: group.catchup_period.get_value(),
+        joining: vec![],
+        leaving: vec![],
+        remaining,
+    })
+}
+
+/// Reads participant from public key file, returns the participant with its scheme.
+fn read_public_file(path: &Path) -> Result<(Participant, String), ProposalError> {
+    let name = path.display().to_string();
+    let file_str =
+        std::fs::read_to_string(path).map_err(|err| ProposalError::Read(name.clone(), err))?;
+    let table = file_str
+        .parse::<DocumentMut>()
+        .map_err(|_| ProposalError::ParseStringError(name.clone()))?;
+
+    let scheme = table
+        .get("SchemeName")
+        .and_then(Item::as_str)
+        .ok_or_else(|| ProposalError::MissingFields(name.clone()))?
+        .to_string();
+    let participant = Participant::toml_decode(table.as_table())
+        .ok_or_else(|| ProposalError::MissingFields(name.clone()))?;
+
+    let is_valid = match scheme.as_str() {
+        DefaultScheme::ID => participant.is_valid_signature::<DefaultScheme>(),
+        UnchainedScheme::ID => participant.is_valid_signature::<UnchainedScheme>(),
+        SigsOnG1Scheme::ID => participant.is_valid_signature::<SigsOnG1Scheme>(),
+        _ => return Err(ProposalError::UnknownScheme(name, scheme)),
+    };
+    if !is_valid {
+        return Err(ProposalError::InvalidSignature(name));
+    }
+
+    Ok((participant, scheme))
+}
+
+impl Toml for ProposalFile {
+    type Inner = DocumentMut;
+
+    fn toml_encode(&self) -> Option<Self::Inner> {
+        let mut doc = Self::Inner::new();
+        // Roles are in order of golang `ProposalFileFormat`, empty roles are omitted.
+        for (role, items) in [
+            ("Joining", &self.joining),
+            ("Leaving", &self.leaving),
+            ("Remaining", &self.remaining),
+        ] {
+            if items.is_empty() {
+                continue;
+            }
+            let mut array = ArrayOfTables::new();
+            for i in items {
+                array.push(prefix_keys(i.toml_encode()?));
+            }
+            doc.insert(role, Item::ArrayOfTables(array));
+        }
+
+        Some(doc)
+    }
+
+    fn toml_decode(table: &Self::Inner) -> Option<Self> {
+        fn from_array(role: &str, table: &Table) -> Option<Vec<Participant>> {
+            match table.get(role) {
+                Some(item) => item
+                    .as_array_of_tables()?
+                    .iter()
+                    .map(Participant::toml_decode)
+                    .collect::<Option<Vec<_>>>(),
+                None => Some(vec![]),
+            }
+        }
+
+        Some(Self {
+            joining: from_array("Joining", table)?,
+            remaining: from_array("Remaining", table)?,
+            leaving: from_array("Leaving", table)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::utils::Address;
+    use crate::transport::drand::Identity;
+    use crate::transport::drand::Node;
+
+    #[test]
+    fn share_refresh_options() {
+        let node = |index: u32| Node {
+            public: Identity {
+                address: Address::precheck(&format!("127.0.0.1:{}", 44000 + index)).unwrap(),
+                key: vec![u8::try_from(index).unwrap()],
+                signature: vec![],
+            },
+            index,
+        };
+        let group = GroupPacket {
+            nodes: vec![node(2), node(0), node(1)],
+            threshold: 2,
+            catchup_period: 5.into(),
+            ..Default::default()
+        };
+
+        let options = refresh_options(&group, None, Timestamp::default()).unwrap();
+        assert_eq!(options.threshold, 2);
+        assert_eq!(options.catchup_period_seconds, 5);
+        assert!(options.joining.is_empty() && options.leaving.is_empty());
+        let keys: Vec<_> = options.remaining.iter().map(|p| p.key.clone()).collect();
+        assert_eq!(keys, [[0], [1], [2]]);
+        assert_eq!(options.remaining[0].address, "127.0.0.1:44000");
+
+        assert_eq!(
+            refresh_options(&group, Some(1), Timestamp::default()),
+            Err(TermsError::ThresholdTooLow {
+                threshold: 1,
+                minimum: 2,
+                nodes: 3
+            })
+        );
+    }
+}
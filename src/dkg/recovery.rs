@@ -0,0 +1,336 @@
+//! Recovery of pending DKG proposal once its leader disappears.
+//!
+//! Execution is signed by the leader and can not be re-issued by another node, so the stuck
+//! proposal is aborted instead:
+//! - the fallback coordinator (see [`State::fallback_coordinator`]) probes the leader while the
+//!   proposal is pending, and broadcasts signed abort to all participants once the leader is
+//!   unreachable for [`MAX_MISSED_PROBES`] consecutive probes. Golang nodes accept abort only from
+//!   the leader, so abort is sent only to peers reporting [`FALLBACK_ABORT`];
+//! - every node moves itself into `TimedOut` once the proposal timeout is reached.
+//!
+//! Checks follow the clock of beacon process, see [`crate::chain::time::Clock`].
+//!
+//! Either way nodes are ready for the next proposal without manual abort on each of them.
+use super::actions_signing::ActionsSigning;
+use super::actions_signing::GossipAuth;
+use super::fanout;
+use super::state::State;
+use super::ActionsError;
+
+use crate::core::beacon::Actions;
+use crate::core::beacon::BeaconCmd;
+use crate::core::beacon::BeaconProcess;
+use crate::key::Scheme;
+use crate::net::handshake::handshake;
+use crate::net::handshake::FALLBACK_ABORT;
+use crate::net::protocol::ProtocolClient;
+use crate::net::utils::Address;
+use crate::net::utils::Callback;
+use crate::protobuf::dkg::AbortDkg;
+use crate::transport::dkg::GossipData;
+use crate::transport::dkg::GossipMetadata;
+use crate::transport::dkg::GossipPacket;
+
+use energon::traits::Affine;
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tracing::debug;
+use tracing::error;
+use tracing::warn;
+
+/// Interval between checks of pending proposal.
+pub(crate) const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// Consecutive failed probes of the leader before the proposal is aborted by the fallback coordinator.
+const MAX_MISSED_PROBES: u32 = 6;
+/// Timeout of a probe of the leader or of a participant supporting abort.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Spawns recovery task, the task is stopped with beacon process.
+pub(crate) fn start<S: Scheme>(bp: BeaconProcess<S>) {
+    let t = bp.tracker().clone();
+    t.spawn(async move {
+        let clock = bp.clock();
+        let mut missed = 0;
+        loop {
+            let next = clock.now() + CHECK_INTERVAL;
+            tokio::select! {
+                () = bp.cmd_tx().closed() => return,
+                () = clock.sleep_until(next) => (),
+            }
+            let state = match bp.dkg_store().get_current::<S>() {
+                Ok(state) if state.is_pending() => state,
+                Ok(_) => {
+                    missed = 0;
+                    continue;
+                }
+                Err(err) => {
+                    error!(parent: bp.log(), "dkg recovery: failed to load state: {err}");
+                    continue;
+                }
+            };
+            if state.time_expired_at(clock.now()) {
+                missed = 0;
+                if !send(&bp, Actions::TimeOut).await {
+                    return;
+                }
+                continue;
+            }
+            if state
+                .fallback_coordinator()
+                .is_none_or(|p| p.address != bp.identity().address)
+            {
+                continue;
+            }
+
+            if is_reachable(&state).await {
+                missed = 0;
+                continue;
+            }
+            missed += 1;
+            debug!(parent: bp.log(), "dkg recovery: leader {} is unreachable, missed probes: {missed}", state.leader.address);
+            if missed < MAX_MISSED_PROBES {
+                continue;
+            }
+            missed = 0;
+            warn!(parent: bp.log(), "dkg recovery: leader {} is unreachable, aborting proposal of epoch {} as fallback coordinator", state.leader.address, state.epoch());
+            let packet = match bp.signed_abort(&state) {
+                Ok(packet) => packet,
+                Err(err) => {
+                    error!(parent: bp.log(), "dkg recovery: {err}");
+                    continue;
+                }
+            };
+            if !send(&bp, |cb| Actions::Gossip(packet.clone(), cb)).await {
+                return;
+            }
+            broadcast(&bp, &state, packet).await;
+        }
+    });
+}
+
+/// Returns `false` if beacon process is stopped.
+async fn send<S: Scheme>(
+    bp: &BeaconProcess<S>,
+    action: impl FnOnce(Callback<(), ActionsError>) -> Actions,
+) -> bool {
+    let (tx, rx) = Callback::new();
+    if bp
+        .cmd_tx()
+        .send(BeaconCmd::DkgActions(action(tx)))
+        .await
+        .is_err()
+    {
+        return false;
+    }
+    match rx.await {
+        Ok(Ok(())) => (),
+        Ok(Err(err)) => error!(parent: bp.log(), "dkg recovery: {err}"),
+        Err(_) => return false,
+    }
+
+    true
+}
+
+async fn is_reachable<S: Scheme>(state: &State<S>) -> bool {
+    tokio::time::timeout(PROBE_TIMEOUT, async {
+        match ProtocolClient::new(&state.leader.address).await {
+            Ok(mut client) => client.ping().await.is_ok(),
+            Err(_) => false,
+        }
+    })
+    .await
+    .unwrap_or(false)
+}
+
+/// Sends abort to every participant of the proposal except the leader, aborts are not relayed,
+/// see [`fanout`]. Participants which do not report [`FALLBACK_ABORT`] are skipped.
+async fn broadcast<S: Scheme>(bp: &BeaconProcess<S>, state: &State<S>, packet: GossipPacket) {
+    let me = &bp.identity().address;
+    let leader = &state.leader.address;
+    let mut probes = JoinSet::new();
+    for address in state
+        .participants()
+        .map(|p| p.address.clone())
+        .filter(|a| a != leader && a != me)
+    {
+        let log = bp.log().clone();
+        probes.spawn(async move {
+            let features = tokio::time::timeout(PROBE_TIMEOUT, async {
+                let mut client = ProtocolClient::new(&address).await.ok()?;
+                Some(handshake(&mut client, &address, &log).await)
+            })
+            .await;
+            let supported = matches!(features, Ok(Some(f)) if supports_abort(&f));
+            (address, supported)
+        });
+    }
+    let mut supported: Vec<Address> = vec![];
+    while let Some(probe) = probes.join_next().await {
+        match probe {
+            Ok((address, true)) => supported.push(address),
+            Ok((address, false)) => {
+                debug!(parent: bp.log(), "dkg recovery: {address} does not support {FALLBACK_ABORT}, skipped");
+            }
+            Err(_) => (),
+        }
+    }
+
+    fanout::send(bp, packet, supported.iter().collect());
+}
+
+fn supports_abort(features: &[String]) -> bool {
+    features.iter().any(|f| f == FALLBACK_ABORT)
+}
+
+impl<S: Scheme> BeaconProcess<S> {
+    /// Moves pending proposal into `TimedOut` if its timeout is reached.
+    pub(crate) fn time_out_proposal(&self) -> Result<(), ActionsError> {
+        let mut state = self.dkg_store().get_current::<S>()?;
+        if state.timed_out(self.clock().now()) {
+            warn!(parent: self.log(), "dkg: proposal of epoch {} is timed out", state.epoch());
+            self.dkg_store().save_current(&state)?;
+        }
+
+        Ok(())
+    }
+
+    fn signed_abort(&self, state: &State<S>) -> Result<GossipPacket, ActionsError> {
+        let mut packet = GossipPacket {
+            data: GossipData::Abort(AbortDkg {
+                reason: format!("leader {} is unreachable", state.leader.address),
+            }),
+            metadata: GossipMetadata {
+                beacon_id: self.id().to_string(),
+                address: self.identity().address.clone(),
+                signature: vec![],
+            },
+        };
+        let msg = self.msg_for_signing(&packet, &state.encode());
+        packet.metadata.signature = S::bls_sign(&msg, self.private_key())
+            .map_err(|_| ActionsError::Sign)?
+            .serialize()
+            .map_err(|_| ActionsError::Sign)?
+            .into();
+
+        Ok(packet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dkg::status::Status;
+    use crate::key::keys::Pair;
+    use crate::transport::dkg::Participant;
+    use crate::transport::dkg::Timestamp;
+    use energon::drand::schemes::DefaultScheme;
+
+    /// Returns pending proposal of three remainers led by the first one.
+    fn pending(timeout: i64) -> State<DefaultScheme> {
+        let remaining: Vec<_> = (1..=3)
+            .map(|i| {
+                let address = Address::precheck(&format!("127.0.0.1:{i}")).unwrap();
+                let pair = Pair::<DefaultScheme>::generate(address).unwrap();
+                Participant::try_from(pair.public_identity()).unwrap()
+            })
+            .collect();
+        let mut state = State::fresh("default");
+        state.status = Status::Proposed;
+        state.epoch = 1;
+        state.timeout = Timestamp {
+            seconds: timeout,
+            nanos: 0,
+        };
+        state.leader = remaining[0].clone();
+        state.remaining = remaining;
+
+        state
+    }
+
+    #[test]
+    fn pending_proposal_times_out() {
+        let mut state = pending(1000);
+        assert!(!state.timed_out(Duration::from_millis(999_999)));
+        assert_eq!(state.status, Status::Proposed);
+        assert!(state.timed_out(Duration::from_secs(1000)));
+        assert_eq!(state.status, Status::TimedOut);
+        assert!(!state.timed_out(Duration::from_secs(2000)));
+
+        // Execution is not a pending proposal.
+        let mut state = pending(1000);
+        state.status = Status::Executing;
+        assert!(!state.timed_out(Duration::from_secs(2000)));
+        assert_eq!(state.status, Status::Executing);
+    }
+
+    #[test]
+    fn abort_from_leader_or_fallback_coordinator() {
+        // Senders by index of remainers: the leader, the fallback coordinator and another node.
+        for (sender, accepted) in [(0, true), (1, true), (2, false)] {
+            let mut state = pending(1000);
+            assert!(state.fallback_coordinator() == Some(&state.remaining[1]));
+            let metadata = GossipMetadata {
+                beacon_id: "default".into(),
+                address: state.remaining[sender].address.clone(),
+                signature: vec![],
+            };
+            assert_eq!(state.aborted(&metadata).is_ok(), accepted);
+            assert_eq!(state.status == Status::Aborted, accepted);
+        }
+
+        // Golang nodes do not report features and do not accept abort of the fallback coordinator.
+        assert!(!supports_abort(&[]));
+        assert!(supports_abort(&crate::net::handshake::features()));
+    }
+
+    #[tokio::test]
+    async fn proposal_of_offline_leader_times_out() {
+        use crate::chain::time::time_now;
+        use crate::chain::time::Clock;
+        use crate::chain::time::MockClock;
+        use crate::chain::time::SharedClock;
+        use crate::net::sim;
+        use crate::net::sim::SimLeader;
+        use std::sync::Arc;
+
+        let mock = Arc::new(MockClock::new(time_now()));
+        let clock: SharedClock = mock.clone();
+        // The node is not the fallback coordinator of the offline leader.
+        let seeds: [&[u8]; 3] = [b"timeout-0", b"timeout-1", b"timeout-2"];
+        let (nodes, mut participants) = sim::participants(&seeds, &[clock.clone()]);
+        participants.rotate_left(1);
+        let now = i64::try_from(mock.now().as_secs()).unwrap();
+        let leader =
+            SimLeader::<DefaultScheme>::first_epoch(seeds[1], &participants, 0, 2, now + 60)
+                .unwrap();
+        leader.propose(&nodes).await.unwrap();
+
+        sim::recovered(&nodes, &mock, Status::TimedOut).await;
+        assert!(mock.now().as_secs() >= u64::try_from(now + 60).unwrap());
+    }
+
+    #[tokio::test]
+    async fn unreachable_leader_is_aborted() {
+        use crate::chain::time::time_now;
+        use crate::chain::time::Clock;
+        use crate::chain::time::MockClock;
+        use crate::chain::time::SharedClock;
+        use crate::net::sim;
+        use crate::net::sim::SimLeader;
+        use std::sync::Arc;
+
+        let mock = Arc::new(MockClock::new(time_now()));
+        let clock: SharedClock = mock.clone();
+        // The first node is the fallback coordinator of the offline leader.
+        let seeds: [&[u8]; 3] = [b"abort-0", b"abort-1", b"abort-leader"];
+        let (nodes, participants) = sim::participants(&seeds, &vec![clock.clone(); 2]);
+        let now = i64::try_from(mock.now().as_secs()).unwrap();
+        let leader =
+            SimLeader::<DefaultScheme>::first_epoch(seeds[2], &participants, 2, 2, now + 3600)
+                .unwrap();
+        leader.propose(&nodes).await.unwrap();
+
+        sim::recovered(&nodes, &mock, Status::Aborted).await;
+        assert!(mock.now().as_secs() < u64::try_from(now + 3600).unwrap());
+    }
+}
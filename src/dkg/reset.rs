@@ -0,0 +1,182 @@
+//! Reset of failed or stale DKG state, see `drand dkg reset`.
+//!
+//! The current state is replaced by the last completed one, or by a fresh state if this node has
+//! not completed a DKG yet, so the node takes part in the next proposal as after the last success.
+//! The wiped state, start time of its execution and its evidence log are moved into
+//! `dkg/reset/<unix time>/` first, nothing is deleted. Evidence of other epochs is kept in place.
+//!
+//! A DKG in progress is not reset: a pending proposal or an execution has to be aborted or reach
+//! its timeout. Fresh, completed and left states have nothing to reset. Current state which can
+//! not be decoded is reset regardless of its status. A beacon id with such state fails to load,
+//! it is reset by the daemon directly and can be loaded again with `drand load`.
+use super::state::State;
+use super::status::Status;
+use super::store::DkgStore;
+use super::store::DkgStoreError;
+use super::utils::GateKeeper;
+use super::ActionsError;
+
+use crate::core::beacon::BeaconProcess;
+use crate::core::events::EventSender;
+use crate::key::store::FileStore;
+use crate::key::store::FileStoreError;
+use crate::key::Scheme;
+use crate::protobuf::drand::DkgResetResponse;
+use crate::protobuf::drand::Metadata;
+
+use energon::drand::schemes::DefaultScheme;
+use energon::drand::schemes::SigsOnG1Scheme;
+use energon::drand::schemes::UnchainedScheme;
+use tracing::warn;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ResetError {
+    #[error("state can be reset, reset is not confirmed")]
+    NotConfirmed,
+    #[error("dkg is in progress with status {0}, abort it or wait for its timeout")]
+    InProgress(Status),
+    #[error("nothing to reset, dkg status is {0}")]
+    NothingToReset(Status),
+    #[error("failed to backup wiped state: {0}")]
+    Backup(std::io::Error),
+    #[error("key store: {0}")]
+    KeyStore(#[from] FileStoreError),
+    #[error("dkg store: {0}")]
+    DKGStore(#[from] DkgStoreError),
+}
+
+/// Returns an error if the state is not failed or stale.
+fn check<S: Scheme>(state: &State<S>) -> Result<(), ResetError> {
+    match state.status() {
+        Status::Failed | Status::TimedOut | Status::Aborted => Ok(()),
+        Status::Proposed
+        | Status::Proposing
+        | Status::Accepted
+        | Status::Rejected
+        | Status::Joined
+        | Status::Executing => {
+            if state.time_expired() {
+                Ok(())
+            } else {
+                Err(ResetError::InProgress(*state.status()))
+            }
+        }
+        Status::Fresh | Status::Complete | Status::Left => {
+            Err(ResetError::NothingToReset(*state.status()))
+        }
+    }
+}
+
+/// Resets the current state of the store, the state is checked before the confirmation.
+pub(super) fn reset<S: Scheme>(
+    store: &DkgStore,
+    confirm: bool,
+) -> Result<DkgResetResponse, ResetError> {
+    let wiped = store.get_current::<S>().ok();
+    if let Some(state) = &wiped {
+        check(state)?;
+    }
+    if !confirm {
+        return Err(ResetError::NotConfirmed);
+    }
+    let next = match store.get_finished::<S>() {
+        Ok(finished) => finished,
+        Err(DkgStoreError::NotFound) => State::fresh(store.beacon_id()),
+        Err(err) => return Err(err.into()),
+    };
+    // Evidence of the completed epoch belongs to the state which is kept.
+    let epoch = wiped
+        .as_ref()
+        .map(State::epoch)
+        .filter(|epoch| *epoch != next.epoch());
+    let backup = store.backup_current(epoch).map_err(ResetError::Backup)?;
+    store.save_current(&next)?;
+
+    Ok(DkgResetResponse {
+        metadata: Some(Metadata::with_id(store.beacon_id().to_string())),
+        wiped_status: wiped.map(|s| s.status().to_string()).unwrap_or_default(),
+        epoch: next.epoch(),
+        backup: backup.display().to_string(),
+    })
+}
+
+/// Resets DKG state of a beacon id which is not loaded, scheme is read from the key pair.
+pub fn reset_unloaded(
+    fs: &FileStore,
+    id: &str,
+    confirm: bool,
+    events: EventSender,
+) -> Result<DkgResetResponse, ResetError> {
+    if !DkgStore::is_initialized(&fs.beacon_path) {
+        return Err(DkgStoreError::NotFound.into());
+    }
+    let pair = fs.load_key_pair_toml()?;
+    let store = DkgStore::open(&fs.beacon_path, id, events);
+    match pair.get_scheme_id() {
+        Some(DefaultScheme::ID) => reset::<DefaultScheme>(&store, confirm),
+        Some(UnchainedScheme::ID) => reset::<UnchainedScheme>(&store, confirm),
+        Some(SigsOnG1Scheme::ID) => reset::<SigsOnG1Scheme>(&store, confirm),
+        _ => Err(FileStoreError::InvalidPairSchemes.into()),
+    }
+}
+
+impl<S: Scheme> BeaconProcess<S> {
+    /// Resets DKG state of the beacon id, packets of the wiped DKG are no longer accepted.
+    pub(crate) fn reset_dkg(
+        &self,
+        confirm: bool,
+        gk: &mut GateKeeper<S>,
+    ) -> Result<DkgResetResponse, ActionsError> {
+        let response = reset::<S>(self.dkg_store(), confirm)?;
+        gk.set_empty();
+        warn!(parent: self.log(), "dkg reset: wiped state '{}', current epoch is {}, backup: {}", response.wiped_status, response.epoch, response.backup);
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reset_failed_state() {
+        type S = DefaultScheme;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path_to_id = temp_dir.path();
+        let store = DkgStore::init::<S>(path_to_id, true, "default", EventSender::new()).unwrap();
+        assert!(matches!(
+            reset::<S>(&store, true),
+            Err(ResetError::NothingToReset(Status::Fresh))
+        ));
+
+        let mut state = State::<S>::fresh("default");
+        state.epoch = 1;
+        state.status = Status::Failed;
+        store.save_current(&state).unwrap();
+        let log = store.evidence(1);
+        std::fs::create_dir_all(log.path().parent().unwrap()).unwrap();
+        std::fs::write(log.path(), "").unwrap();
+        assert!(matches!(
+            reset::<S>(&store, false),
+            Err(ResetError::NotConfirmed)
+        ));
+        assert_eq!(store.get_current::<S>().unwrap().status, Status::Failed);
+
+        let response = reset::<S>(&store, true).unwrap();
+        assert_eq!(response.wiped_status, "Failed");
+        assert_eq!(response.epoch, 0);
+        let backup = std::path::Path::new(&response.backup);
+        assert!(backup.join("current.toml").exists() && backup.join("1.jsonl").exists());
+        assert!(!log.path().exists());
+        assert_eq!(store.get_current::<S>().unwrap().status, Status::Fresh);
+
+        // Undecodable state is reset regardless of its status.
+        std::fs::write(path_to_id.join("dkg/current.toml"), "garbage").unwrap();
+        let store = DkgStore::open(path_to_id, "default", EventSender::new());
+        let response = reset::<S>(&store, true).unwrap();
+        assert!(response.wiped_status.is_empty());
+        assert_ne!(response.backup, backup.display().to_string());
+        assert_eq!(store.get_current::<S>().unwrap().status, Status::Fresh);
+    }
+}
@@ -0,0 +1,290 @@
+//! Resumption of DKG execution interrupted by a daemon restart.
+//!
+//! Start time of an execution is recorded once the node enters `Executing`, and bundles received
+//! during execution are kept in the evidence log of the epoch, see [`super::evidence`]. After a
+//! restart the protocol is set up again on the timeline of the group: it waits for the recorded
+//! start time, or runs right away if the start time has passed with phases shortened to end with
+//! the ones of the group, see [`phase_timeout`]. Received deals, responses and justifications are
+//! replayed in order of arrival, so the node does not miss bundles which are not sent again.
+//!
+//! Secret polynomial of a dealer lives only in memory, a fresh deal would not match the one which
+//! other participants already hold. An execution is therefore resumed only if this node has not
+//! sent its deal yet, otherwise, as well as once the timeout of the epoch is reached, the node is
+//! moved into `Failed` as before.
+use super::evidence::EvidenceError;
+use super::execution::execution_time;
+use super::execution::DEFAULT_DKG_PHASE_TIMEOUT;
+use super::execution::DKG_PHASES;
+use super::state::State;
+use super::status::Status;
+use super::store::DkgStore;
+use super::store::DkgStoreError;
+use super::utils::GateKeeper;
+
+use crate::core::beacon::BeaconProcess;
+use crate::key::Scheme;
+use crate::protobuf::dkg::packet::Bundle as ProtoBundle;
+use crate::protobuf::dkg::DkgPacket;
+use crate::transport::dkg::Timestamp;
+
+use std::time::Duration;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+/// Shortest phase of a resumed execution.
+const MIN_PHASE_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(thiserror::Error, Debug)]
+pub enum ResumeError {
+    #[error("timeout of the epoch is reached")]
+    TimeoutReached,
+    #[error("start time of the execution is not recorded")]
+    UnknownStartTime,
+    #[error("execution of the group is over")]
+    ExecutionOver,
+    #[error("deal of this node is already sent and can not be reproduced")]
+    DealSent,
+    #[error("dkg store: {0}")]
+    DKGStore(#[from] DkgStoreError),
+    #[error("evidence: {0}")]
+    Evidence(#[from] EvidenceError),
+}
+
+/// Returns start time of the interrupted execution and bundles received before the restart.
+pub(super) fn resumable<S: Scheme>(
+    store: &DkgStore,
+    state: &State<S>,
+) -> Result<(Timestamp, Vec<DkgPacket>), ResumeError> {
+    if state.time_expired() {
+        return Err(ResumeError::TimeoutReached);
+    }
+    let start = store
+        .get_execution(state.epoch())?
+        .ok_or(ResumeError::UnknownStartTime)?;
+    let mut received = vec![];
+    for (packet, sent) in store.evidence(state.epoch()).bundles()? {
+        if !sent {
+            received.push(packet);
+        } else if is_deal(&packet) {
+            return Err(ResumeError::DealSent);
+        }
+    }
+
+    Ok((start, received))
+}
+
+/// Returns phase timeout of the execution started at `start` and resumed at `now`, so the phases
+/// end with the ones of the group. `None` if the phases of the group are over.
+pub(super) fn phase_timeout(start: Duration, now: Duration) -> Option<Duration> {
+    let Some(elapsed) = now.checked_sub(start) else {
+        return Some(DEFAULT_DKG_PHASE_TIMEOUT);
+    };
+    let timeout = (DEFAULT_DKG_PHASE_TIMEOUT * DKG_PHASES).checked_sub(elapsed)? / DKG_PHASES;
+
+    (timeout >= MIN_PHASE_TIMEOUT).then_some(timeout)
+}
+
+fn is_deal(packet: &DkgPacket) -> bool {
+    matches!(
+        packet.dkg.as_ref().and_then(|p| p.bundle.as_ref()),
+        Some(ProtoBundle::Deal(_))
+    )
+}
+
+impl<S: Scheme> BeaconProcess<S> {
+    /// Resumes execution of the current epoch if the daemon was restarted during it.
+    pub(crate) async fn resume_dkg(&self, gk: &mut GateKeeper<S>) {
+        let mut state = match self.dkg_store().get_current::<S>() {
+            Ok(state) if state.status() == &Status::Executing => state,
+            Ok(_) => return,
+            Err(err) => {
+                error!(parent: self.log(), "dkg resume: failed to load state: {err}");
+                return;
+            }
+        };
+        let result = match resumable(self.dkg_store(), &state) {
+            Ok((start, received)) => self.rejoin(start, received, gk).await,
+            Err(err) => Err(err.to_string()),
+        };
+        match result {
+            Ok(replayed) => {
+                info!(parent: self.log(), "dkg resume: resumed execution of epoch {}, replayed {replayed} bundles", state.epoch());
+            }
+            Err(err) => {
+                warn!(parent: self.log(), "dkg resume: execution of epoch {} can not be resumed: {err}", state.epoch());
+                state.status = Status::Failed;
+                if let Err(err) = self.dkg_store().save_current(&state) {
+                    error!(parent: self.log(), "dkg resume: failed to save state: {err}");
+                }
+            }
+        }
+    }
+
+    /// Sets up the protocol on the timeline of the group and replays received bundles, returns
+    /// amount of replayed bundles.
+    async fn rejoin(
+        &self,
+        start: Timestamp,
+        received: Vec<DkgPacket>,
+        gk: &mut GateKeeper<S>,
+    ) -> Result<usize, String> {
+        let start = execution_time(start).map_err(|err| err.to_string())?;
+        let timeout = phase_timeout(start, self.clock().now())
+            .ok_or_else(|| ResumeError::ExecutionOver.to_string())?;
+        self.run_dkg(start, timeout, gk)
+            .await
+            .map_err(|err| err.to_string())?;
+
+        let replayed = received.len();
+        for packet in received {
+            gk.replay(packet).await.map_err(|err| err.to_string())?;
+        }
+
+        Ok(replayed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::time::time_now;
+    use crate::core::events::EventSender;
+    use crate::protobuf::dkg::DealBundle;
+    use crate::protobuf::dkg::Packet;
+    use crate::protobuf::dkg::ResponseBundle;
+    use energon::drand::schemes::DefaultScheme;
+
+    fn packet(bundle: ProtoBundle) -> DkgPacket {
+        DkgPacket {
+            dkg: Some(Packet {
+                metadata: None,
+                bundle: Some(bundle),
+            }),
+        }
+    }
+
+    #[test]
+    fn resumed_phases_end_with_group() {
+        let start = Duration::from_secs(1000);
+        let second = Duration::from_secs(1);
+        assert_eq!(
+            phase_timeout(start, start - second),
+            Some(DEFAULT_DKG_PHASE_TIMEOUT)
+        );
+        assert_eq!(phase_timeout(start, start), Some(DEFAULT_DKG_PHASE_TIMEOUT));
+        // Phases of the group end 30s after the start, 18s are left for three phases.
+        assert_eq!(phase_timeout(start, start + 12 * second), Some(6 * second));
+        assert_eq!(phase_timeout(start, start + 29 * second), None);
+        assert_eq!(phase_timeout(start, start + 60 * second), None);
+    }
+
+    #[test]
+    fn received_bundles_are_replayed() {
+        type S = DefaultScheme;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store =
+            DkgStore::init::<S>(temp_dir.path(), true, "default", EventSender::new()).unwrap();
+        let now = i64::try_from(time_now().as_secs()).unwrap();
+        let mut state = State::<S>::fresh("default");
+        state.epoch = 1;
+        state.status = Status::Executing;
+        state.timeout = Timestamp {
+            seconds: now + 3600,
+            nanos: 0,
+        };
+        assert!(matches!(
+            resumable(&store, &state),
+            Err(ResumeError::UnknownStartTime)
+        ));
+
+        let start = Timestamp {
+            seconds: now - 5,
+            nanos: 0,
+        };
+        store.save_execution(1, &start).unwrap();
+        let deal = packet(ProtoBundle::Deal(DealBundle {
+            dealer_index: 1,
+            ..Default::default()
+        }));
+        let response = packet(ProtoBundle::Response(ResponseBundle {
+            share_index: 2,
+            ..Default::default()
+        }));
+        let own_response = packet(ProtoBundle::Response(ResponseBundle::default()));
+        let log = store.evidence(1);
+        std::fs::create_dir_all(log.path().parent().unwrap()).unwrap();
+        log.bundle(&deal, false).unwrap();
+        log.bundle(&own_response, true).unwrap();
+        log.bundle(&response, false).unwrap();
+        // Own responses are issued again by the protocol from replayed deals.
+        let (resumed, received) = resumable(&store, &state).unwrap();
+        assert_eq!(resumed, start);
+        assert_eq!(received, vec![deal, response]);
+
+        // Deal of this node can not be reproduced.
+        log.bundle(&packet(ProtoBundle::Deal(DealBundle::default())), true)
+            .unwrap();
+        assert!(matches!(
+            resumable(&store, &state),
+            Err(ResumeError::DealSent)
+        ));
+
+        state.timeout.seconds = now - 1;
+        assert!(matches!(
+            resumable(&store, &state),
+            Err(ResumeError::TimeoutReached)
+        ));
+    }
+
+    #[tokio::test]
+    async fn dkg_completes_across_restart() {
+        use crate::chain::time::MockClock;
+        use crate::chain::time::SharedClock;
+        use crate::key::group::Group;
+        use crate::key::Hash;
+        use crate::net::sim;
+        use crate::net::sim::SimLeader;
+        use std::sync::Arc;
+
+        // Late nodes start the execution after the early ones, as if their clocks are behind.
+        let base = time_now();
+        let early = Arc::new(MockClock::new(base));
+        let late = Arc::new(MockClock::new(base));
+        let (early_clock, late_clock): (SharedClock, SharedClock) = (early.clone(), late.clone());
+        let seeds: [&[u8]; 4] = [b"resume-0", b"resume-1", b"resume-2", b"resume-3"];
+        let clocks = [
+            early_clock.clone(),
+            early_clock,
+            late_clock.clone(),
+            late_clock.clone(),
+        ];
+        let (mut nodes, participants) = sim::participants(&seeds, &clocks);
+        let now = i64::try_from(base.as_secs()).unwrap();
+        let leader =
+            SimLeader::<DefaultScheme>::first_epoch(seeds[0], &participants, 0, 3, now + 3600)
+                .unwrap();
+        leader.run(&nodes, now + 1).await.unwrap();
+        early.advance(Duration::from_secs(2));
+
+        // Late node is restarted once it received deals of the early nodes, which are not sent again.
+        sim::received_deals(&nodes[2], 2).await;
+        nodes[2].restart(late_clock).await.unwrap();
+        late.advance(Duration::from_secs(2));
+
+        assert!(sim::finished(&nodes)
+            .await
+            .iter()
+            .all(|s| *s == Status::Complete));
+        // All nodes hold shares of the same distributed key.
+        let hashes: Vec<_> = nodes
+            .iter()
+            .map(|node| {
+                let group: Group<DefaultScheme> = node.store().load_group().unwrap();
+                assert_eq!(group.nodes.len(), 4);
+                group.hash()
+            })
+            .collect();
+        assert!(hashes.iter().all(|h| *h == hashes[0]));
+    }
+}
@@ -3,9 +3,10 @@ use super::actions_signing::enc_timestamp;
 use super::actions_signing::GossipAuth;
 use super::status::StateError;
 use super::status::Status;
+use super::terms;
+use super::terms::TermsError;
 use super::ActionsError;
 
-use crate::key::group::minimum_t;
 use crate::key::group::Group;
 use crate::key::toml::Toml;
 use crate::key::PointSerDeError;
@@ -23,6 +24,7 @@ use crate::net::utils::Seconds;
 
 use energon::kyber::dkg::DistKeyShare;
 use std::str::FromStr;
+use std::time::Duration;
 use std::time::SystemTime;
 use toml_edit::ArrayOfTables;
 use toml_edit::DocumentMut;
@@ -73,12 +75,8 @@ pub enum DBStateError {
     MissingNodesInProposal,
     #[error("cannot make a proposal where you are not the leader")]
     CannotProposeAsNonLeader,
-    #[error("the threshold cannot be higher than the count of remaining + joining nodes")]
-    ThresholdHigherThanNodeCount,
-    #[error("the new node count cannot be lower than the prior threshold")]
-    NodeCountTooLow,
-    #[error("the threshold is below the minimum required to allow effective secret recovery given the node count")]
-    ThresholdTooLow,
+    #[error("invalid proposal terms: {0}")]
+    Terms(#[from] TermsError),
     #[error("remaining and leaving nodes contained a node that does not exist in the current epoch - they must be added as joiners")]
     RemainingAndLeavingNodesMustExistInCurrentEpoch,
     #[error("you cannot accept a proposal where your node is leaving")]
@@ -127,6 +125,8 @@ pub enum DBStateError {
     ParticipantSignature,
     #[error("final group for remainers can not be empty")]
     MissingFinalGroupForRemainers,
+    #[error("group file has no nodes")]
+    EmptyGroup,
 }
 
 #[derive(PartialEq)]
@@ -364,6 +364,46 @@ impl<S: Scheme> State<S> {
         }
     }
 
+    /// Completed state reconstructed from the group file and share of a node migrated from golang implementation.
+    ///
+    /// Leader of the epoch is not part of the group file and is left empty, it is not used once DKG is complete.
+    pub(super) fn migrated(
+        group: Group<S>,
+        share: DistKeyShare<S>,
+        epoch: u32,
+    ) -> Result<Self, DBStateError> {
+        let mut remaining = Vec::with_capacity(group.nodes.len());
+        for node in &group.nodes {
+            remaining.push(Participant::try_from(node.public())?);
+        }
+        if remaining.is_empty() {
+            return Err(DBStateError::EmptyGroup);
+        }
+
+        Ok(Self {
+            beacon_id: group.beacon_id.clone(),
+            epoch,
+            status: Status::Complete,
+            threshold: group.threshold,
+            timeout: Timestamp::default(),
+            genesis_time: Timestamp {
+                seconds: i64::try_from(group.genesis_time).unwrap_or_default(),
+                nanos: 0,
+            },
+            genesis_seed: group.genesis_seed.clone(),
+            catchup_period: group.catchup_period,
+            beacon_period: group.period,
+            leader: Participant::default(),
+            remaining,
+            joining: vec![],
+            leaving: vec![],
+            acceptors: vec![],
+            rejectors: vec![],
+            final_group: Some(group),
+            key_share: Some(share),
+        })
+    }
+
     /// Proposed is used by non-leader nodes to set their own state when they receive a proposal
     pub fn proposed(
         &mut self,
@@ -436,10 +476,7 @@ impl<S: Scheme> State<S> {
                 error!("GossipData::Reject is not implemented");
                 Err(ActionsError::Todo)
             }
-            GossipData::Abort(_abort_dkg) => {
-                error!("GossipData::Abort is not implemented");
-                Err(ActionsError::Todo)
-            }
+            GossipData::Abort(_abort_dkg) => self.aborted(metadata).map_err(ActionsError::DBState),
             GossipData::Dkg(_dkg_packet) => {
                 error!("GossipData::Dkg is not implemented");
                 Err(ActionsError::Todo)
@@ -470,6 +507,23 @@ impl<S: Scheme> State<S> {
         Ok(())
     }
 
+    /// Abort is accepted from the leader or from the fallback coordinator, see [`super::recovery`].
+    pub fn aborted(&mut self, metadata: &GossipMetadata) -> Result<(), DBStateError> {
+        self.status.is_valid_state_change(Status::Aborted)?;
+
+        let sender = metadata.address();
+        if &self.leader.address != sender
+            && self
+                .fallback_coordinator()
+                .is_none_or(|p| &p.address != sender)
+        {
+            return Err(DBStateError::OnlyLeaderCanRemoteAbort);
+        }
+        self.status = Status::Aborted;
+
+        Ok(())
+    }
+
     pub fn left(&mut self, me: &Participant) -> Result<(), DBStateError> {
         self.status.is_valid_state_change(Status::Left)?;
 
@@ -486,11 +540,49 @@ impl<S: Scheme> State<S> {
         Ok(())
     }
 
+    /// Returns `true` if proposal is received but not executed yet.
+    pub fn is_pending(&self) -> bool {
+        is_proposal_phase(self)
+    }
+
+    /// Fallback coordinator is the first participant of the proposal other than the leader,
+    /// remainers are preferred over joiners. It is allowed to abort the proposal on behalf of the leader.
+    pub fn fallback_coordinator(&self) -> Option<&Participant> {
+        self.remaining
+            .iter()
+            .chain(&self.joining)
+            .find(|p| p.address != self.leader.address)
+    }
+
+    /// Returns all participants of the proposal.
+    pub fn participants(&self) -> impl Iterator<Item = &Participant> {
+        self.remaining
+            .iter()
+            .chain(&self.joining)
+            .chain(&self.leaving)
+    }
+
+    /// Moves pending proposal into `TimedOut` once its timeout is reached at Unix time `now`,
+    /// returns `true` if status is changed.
+    pub(super) fn timed_out(&mut self, now: Duration) -> bool {
+        if !is_proposal_phase(self) || !self.time_expired_at(now) {
+            return false;
+        }
+        self.status = Status::TimedOut;
+
+        true
+    }
+
     /// Timeout check operates at resolution of seconds.
     pub(super) fn time_expired(&self) -> bool {
         Timestamp::from(SystemTime::now()).seconds >= self.timeout.seconds
     }
 
+    /// Returns `true` if the timeout is reached at Unix time `now`, see [`Self::time_expired`].
+    pub(super) fn time_expired_at(&self, now: Duration) -> bool {
+        i64::try_from(now.as_secs()).unwrap_or(i64::MAX) >= self.timeout.seconds
+    }
+
     pub(super) fn complete(
         &mut self,
         final_group: Group<S>,
@@ -632,10 +724,6 @@ fn validate_reshare_for_remainers<S: Scheme>(
         return Err(DBStateError::MissingNodesInProposal);
     }
 
-    if terms.remaining.len() < current.threshold as usize {
-        return Err(DBStateError::NodeCountTooLow);
-    }
-
     Ok(())
 }
 
@@ -659,9 +747,11 @@ fn validate_reshare_terms<S: Scheme>(
         return Err(DBStateError::LeaderNotRemaining);
     }
 
-    if terms.remaining.len() < current.threshold as usize {
-        return Err(DBStateError::NodeCountTooLow);
-    }
+    terms::check_leavers(
+        terms.remaining.len(),
+        terms.leaving.len(),
+        current.threshold as usize,
+    )?;
 
     Ok(())
 }
@@ -683,10 +773,6 @@ fn validate_first_epoch(terms: &ProposalTerms) -> Result<(), DBStateError> {
         return Err(DBStateError::LeaderNotJoining);
     }
 
-    if terms.joining.len() < terms.threshold as usize {
-        return Err(DBStateError::ThresholdHigherThanNodeCount);
-    }
-
     Ok(())
 }
 
@@ -697,6 +783,8 @@ fn validate_for_all_dkgs<S: Scheme>(
     if current.beacon_id != terms.beacon_id {
         return Err(DBStateError::InvalidBeaconID);
     }
+    terms::validate::<S>(terms)?;
+
     // Validate joiner signatures.
     for j in &terms.joining {
         if !j.is_valid_signature::<S>() {
@@ -709,17 +797,6 @@ fn validate_for_all_dkgs<S: Scheme>(
         return Err(DBStateError::TimeoutReached);
     }
 
-    let node_count = terms.joining.len() + terms.remaining.len();
-    let threshold = terms.threshold as usize;
-
-    if threshold > node_count {
-        return Err(DBStateError::ThresholdHigherThanNodeCount);
-    }
-
-    if threshold < minimum_t(node_count) {
-        return Err(DBStateError::ThresholdTooLow);
-    }
-
     // Validate epoch
     //
     // Epochs should be monotonically increasing
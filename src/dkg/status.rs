@@ -109,6 +109,47 @@ impl Status {
     pub fn is_terminal(self) -> bool {
         matches!(self, Status::Aborted | Status::TimedOut | Status::Failed)
     }
+
+    /// Returns actions due in this state with their deadlines, as UNIX time in seconds.
+    ///
+    /// Deadlines are taken from timing of the proposal: its timeout bounds acceptance and
+    /// execution alike, the first epoch additionally starts the chain at genesis time and a
+    /// reshare switches to the new group at transition time, zero if it is unknown.
+    pub fn deadlines(
+        self,
+        epoch: u32,
+        timeout: u64,
+        genesis_time: u64,
+        transition_time: u64,
+    ) -> Vec<(&'static str, u64)> {
+        let mut deadlines = match self {
+            Status::Proposed => vec![("accept or join", timeout)],
+            Status::Proposing | Status::Accepted | Status::Joined => vec![("execution", timeout)],
+            Status::Executing => vec![("key sharing", timeout)],
+            _ => vec![],
+        };
+        if !deadlines.is_empty() || self == Status::Complete {
+            if epoch == 1 {
+                deadlines.push(("genesis", genesis_time));
+            } else if transition_time != 0 {
+                deadlines.push(("transition", transition_time));
+            }
+        }
+
+        deadlines
+    }
+}
+
+/// Returns time left until the deadline, e.g. `1h02m05s`, or `passed`.
+pub fn countdown(now: u64, deadline: u64) -> String {
+    let left = deadline.saturating_sub(now);
+    let (h, m, s) = (left / 3600, left / 60 % 60, left % 60);
+    match (h, m) {
+        _ if left == 0 => "passed".into(),
+        (0, 0) => format!("{s}s"),
+        (0, _) => format!("{m}m{s:02}s"),
+        _ => format!("{h}h{m:02}m{s:02}s"),
+    }
 }
 
 impl std::fmt::Display for Status {
@@ -140,3 +181,45 @@ impl FromStr for Status {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadlines_and_countdown() {
+        let (timeout, genesis, transition) = (1000, 2000, 3000);
+        assert_eq!(
+            Status::Proposed.deadlines(2, timeout, genesis, 0),
+            [("accept or join", timeout)]
+        );
+        assert_eq!(
+            Status::Executing.deadlines(1, timeout, genesis, 0),
+            [("key sharing", timeout), ("genesis", genesis)]
+        );
+        assert_eq!(
+            Status::Complete.deadlines(1, timeout, genesis, 0),
+            [("genesis", genesis)]
+        );
+        assert_eq!(
+            Status::Executing.deadlines(2, timeout, genesis, transition),
+            [("key sharing", timeout), ("transition", transition)]
+        );
+        assert_eq!(
+            Status::Complete.deadlines(2, timeout, genesis, transition),
+            [("transition", transition)]
+        );
+        assert!(Status::Complete
+            .deadlines(2, timeout, genesis, 0)
+            .is_empty());
+        assert!(Status::TimedOut
+            .deadlines(2, timeout, genesis, transition)
+            .is_empty());
+
+        assert_eq!(countdown(timeout, timeout), "passed");
+        assert_eq!(countdown(timeout + 1, timeout), "passed");
+        assert_eq!(countdown(0, 59), "59s");
+        assert_eq!(countdown(0, 65), "1m05s");
+        assert_eq!(countdown(0, 3725), "1h02m05s");
+    }
+}
@@ -1,23 +1,45 @@
+use super::evidence::EvidenceLog;
+use super::identity::IdentityChange;
+use super::resume;
+use super::state::DBStateError;
 use super::state::State;
 use super::status::Status;
+use crate::core::events::Event;
+use crate::core::events::EventSender;
+use crate::key::group::Group;
+use crate::key::store::set_dir_mode;
+use crate::key::store::set_file_mode;
 use crate::key::toml::Toml;
 use crate::key::Scheme;
+use crate::transport::dkg::Timestamp;
 
+use energon::kyber::dkg::DistKeyShare;
 use std::fs::File;
-use std::fs::Permissions;
 use std::io::Write;
-use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
+use toml_edit::ArrayOfTables;
+use toml_edit::DocumentMut;
+use toml_edit::Item;
 use tracing::error;
+use tracing::warn;
 
 /// Directory located at `base_folder/multibeacon/beacon_id/`.
-const DKG_STORE_DIR: &str = "dkg";
+pub(super) const DKG_STORE_DIR: &str = "dkg";
 /// TOML encoded representation of the current [`State`].
 const CURRENT_FILE: &str = "current.toml";
 /// TOML encoded representation of the finished [`State`].
 const FINISHED_FILE: &str = "finished.toml";
+/// TOML encoded history of [`IdentityChange`]s observed across reshares.
+const IDENTITY_FILE: &str = "identity_changes.toml";
+/// Start time of the last execution, an execution interrupted by a restart is resumed with it.
+const EXECUTION_FILE: &str = "execution.toml";
+/// Files of DKG states wiped by `drand dkg reset`, see [`super::reset`].
+const RESET_DIR: &str = "reset";
 
 /// Permissions
 const DIR_PERM: u32 = 0o755;
@@ -26,6 +48,9 @@ const FILE_PERM: u32 = 0o660;
 /// Store for current and finished DKGs, contains absolute path to [`DKG_STORE_DIR`]
 pub struct DkgStore {
     path: PathBuf,
+    /// Status transitions are reported as daemon events.
+    events: EventSender,
+    beacon_id: String,
 }
 
 impl DkgStore {
@@ -33,21 +58,27 @@ impl DkgStore {
         path_to_id: &Path,
         fresh_run: bool,
         id: &str,
+        events: EventSender,
     ) -> Result<Self, DkgStoreError> {
         let store = Self {
             path: path_to_id.join(DKG_STORE_DIR),
+            events,
+            beacon_id: id.to_string(),
         };
 
         match (fresh_run, store.path.exists()) {
             // Fresh run case
             (true, false) => {
                 std::fs::create_dir(&store.path).map_err(DkgStoreError::CreateDir)?;
-                std::fs::set_permissions(&store.path, Permissions::from_mode(DIR_PERM))
-                    .map_err(DkgStoreError::Permission)?;
+                set_dir_mode(&store.path, DIR_PERM).map_err(DkgStoreError::Permission)?;
             }
-            // Brocken configuration
+            // Brocken configuration or a node migrated from golang implementation.
             (false, false) => {
-                error!("{} at {}", DkgStoreError::NotFound, store.path.display());
+                error!(
+                    "{} at {}, see `drand util check-migration`",
+                    DkgStoreError::NotFound,
+                    store.path.display()
+                );
                 return Err(DkgStoreError::FailedToLoad);
             }
             _ => (),
@@ -64,9 +95,15 @@ impl DkgStore {
                     state.status = Status::TimedOut;
                     store.save_current(&state)?;
                 } else if state.status() == &Status::Executing {
-                    // Node can not be loaded into executing state regardless of timeout(drand-go v2.1.0).
-                    state.status = Status::Failed;
-                    store.save_current(&state)?;
+                    // Unlike drand-go v2.1.0, execution is resumed if possible, see `super::resume`.
+                    if let Err(err) = resume::resumable(&store, &state) {
+                        warn!(
+                            "dkg: execution of epoch {} can not be resumed: {err}",
+                            state.epoch()
+                        );
+                        state.status = Status::Failed;
+                        store.save_current(&state)?;
+                    }
                 }
             }
             Err(DkgStoreError::NotFound) => store.save_current(&State::<S>::fresh(id))?,
@@ -76,6 +113,52 @@ impl DkgStore {
         Ok(store)
     }
 
+    /// Opens the store without checks of the current state, used to reset a store which
+    /// can not be loaded, see [`super::reset`].
+    pub(super) fn open(path_to_id: &Path, id: &str, events: EventSender) -> Self {
+        Self {
+            path: path_to_id.join(DKG_STORE_DIR),
+            events,
+            beacon_id: id.to_string(),
+        }
+    }
+
+    /// Returns `true` if the store folder exists for given beacon id path.
+    pub fn is_initialized(path_to_id: &Path) -> bool {
+        path_to_id.join(DKG_STORE_DIR).exists()
+    }
+
+    /// Initializes the store with completed state of given epoch for a node migrated from golang
+    /// implementation, where DKG state is kept in a database which is not readable by this implementation.
+    pub fn migrate<S: Scheme>(
+        path_to_id: &Path,
+        group: Group<S>,
+        share: DistKeyShare<S>,
+        epoch: u32,
+    ) -> Result<(), DkgStoreError> {
+        let store = Self {
+            path: path_to_id.join(DKG_STORE_DIR),
+            events: EventSender::new(),
+            beacon_id: group.beacon_id.clone(),
+        };
+        let state = State::migrated(group, share, epoch).map_err(DkgStoreError::Migration)?;
+        std::fs::create_dir(&store.path).map_err(DkgStoreError::CreateDir)?;
+        set_dir_mode(&store.path, DIR_PERM).map_err(DkgStoreError::Permission)?;
+
+        store.save_finished(&state)
+    }
+
+    /// Checks that completed state of given epoch can be reconstructed, see [`Self::migrate`].
+    pub fn check_migrate<S: Scheme>(
+        group: Group<S>,
+        share: DistKeyShare<S>,
+        epoch: u32,
+    ) -> Result<(), DkgStoreError> {
+        State::migrated(group, share, epoch)
+            .map(|_| ())
+            .map_err(DkgStoreError::Migration)
+    }
+
     /// Retrieves the last successful state.
     ///
     /// If the current state is terminal, it will attempt to retrieve the finished state.
@@ -111,7 +194,9 @@ impl DkgStore {
             .ok_or(DkgStoreError::TomlError)?
             .to_string();
 
+        let previous = self.get::<S>(CURRENT_FILE).ok();
         self.save(CURRENT_FILE, &toml)?;
+        self.notify(previous.as_ref(), state);
 
         Ok(())
     }
@@ -124,12 +209,150 @@ impl DkgStore {
             .ok_or(DkgStoreError::TomlError)?
             .to_string();
 
+        let previous = self.get::<S>(CURRENT_FILE).ok();
         self.save(FINISHED_FILE, &toml)?;
         self.save(CURRENT_FILE, &toml)?;
+        self.notify(previous.as_ref(), state);
 
         Ok(())
     }
 
+    /// Emits event if status or epoch of the current state has been changed.
+    fn notify<S: Scheme>(&self, previous: Option<&State<S>>, next: &State<S>) {
+        if previous.is_some_and(|p| p.epoch() == next.epoch() && p.status == next.status) {
+            return;
+        }
+        self.events.emit(
+            &self.beacon_id,
+            &Event::DkgStatus {
+                epoch: next.epoch(),
+                status: &next.status.to_string(),
+            },
+        );
+    }
+
+    /// Returns evidence log of the given epoch.
+    pub(super) fn evidence(&self, epoch: u32) -> EvidenceLog {
+        EvidenceLog::new(&self.path, epoch)
+    }
+
+    /// Records start time of the execution of given epoch.
+    pub(super) fn save_execution(
+        &self,
+        epoch: u32,
+        start: &Timestamp,
+    ) -> Result<(), DkgStoreError> {
+        let mut doc = DocumentMut::new();
+        doc.insert("Epoch", i64::from(epoch).into());
+        doc.insert("StartTime", start.to_string().into());
+
+        self.save(EXECUTION_FILE, &doc.to_string())
+    }
+
+    /// Returns start time of the execution of given epoch, `None` if it is not recorded.
+    pub(super) fn get_execution(&self, epoch: u32) -> Result<Option<Timestamp>, DkgStoreError> {
+        let path = self.path.join(EXECUTION_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let file_str = std::fs::read_to_string(path).map_err(DkgStoreError::Read)?;
+        let doc = file_str
+            .parse::<DocumentMut>()
+            .map_err(|_| DkgStoreError::ParseStringError)?;
+        if doc.get("Epoch").and_then(Item::as_integer) != Some(i64::from(epoch)) {
+            return Ok(None);
+        }
+
+        doc.get("StartTime")
+            .and_then(Item::as_str)
+            .and_then(|time| Timestamp::from_str(time).ok())
+            .map(Some)
+            .ok_or(DkgStoreError::TomlError)
+    }
+
+    /// Moves the current state, start time of the execution and evidence log of `epoch` into
+    /// `reset/<unix time>/`, returns path to the backup folder.
+    pub(super) fn backup_current(&self, epoch: Option<u32>) -> Result<PathBuf, std::io::Error> {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        // Backups of resets within the same second are kept apart.
+        let reset_dir = self.path.join(RESET_DIR);
+        let mut backup = reset_dir.join(time.to_string());
+        let mut n = 0;
+        while backup.try_exists()? {
+            n += 1;
+            backup = reset_dir.join(format!("{time}-{n}"));
+        }
+        std::fs::create_dir_all(&backup)?;
+        set_dir_mode(&backup, DIR_PERM)?;
+
+        let mut files = vec![
+            (self.path.join(CURRENT_FILE), backup.join(CURRENT_FILE)),
+            (self.path.join(EXECUTION_FILE), backup.join(EXECUTION_FILE)),
+        ];
+        if let Some(epoch) = epoch {
+            let log = self.evidence(epoch).path().to_path_buf();
+            if let Some(name) = log.file_name() {
+                let to = backup.join(name);
+                files.push((log, to));
+            }
+        }
+        for (from, to) in files {
+            if from.try_exists()? {
+                std::fs::rename(from, to)?;
+            }
+        }
+
+        Ok(backup)
+    }
+
+    pub(super) fn beacon_id(&self) -> &str {
+        &self.beacon_id
+    }
+
+    /// Returns the history of identity changes, empty if nothing has been recorded yet.
+    pub(super) fn get_identity_changes(&self) -> Result<Vec<IdentityChange>, DkgStoreError> {
+        let path = self.path.join(IDENTITY_FILE);
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let file_str = std::fs::read_to_string(path).map_err(DkgStoreError::Read)?;
+        let doc = file_str
+            .parse::<DocumentMut>()
+            .map_err(|_| DkgStoreError::ParseStringError)?;
+
+        match doc.get("Changes") {
+            Some(item) => item
+                .as_array_of_tables()
+                .ok_or(DkgStoreError::TomlError)?
+                .iter()
+                .map(IdentityChange::toml_decode)
+                .collect::<Option<Vec<_>>>()
+                .ok_or(DkgStoreError::TomlError),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Appends identity changes to the history.
+    pub(super) fn append_identity_changes(
+        &self,
+        changes: &[IdentityChange],
+    ) -> Result<(), DkgStoreError> {
+        let mut history = self.get_identity_changes()?;
+        history.extend_from_slice(changes);
+
+        let mut array = ArrayOfTables::new();
+        for change in &history {
+            array.push(change.toml_encode().ok_or(DkgStoreError::TomlError)?);
+        }
+        let mut doc = DocumentMut::new();
+        doc.insert("Changes", Item::ArrayOfTables(array));
+
+        self.save(IDENTITY_FILE, &doc.to_string())
+    }
+
     fn get<S: Scheme>(&self, kind: &str) -> Result<State<S>, DkgStoreError> {
         let path = self.path.join(kind);
         if !path.exists() {
@@ -155,8 +378,7 @@ impl DkgStore {
 
         let mut f = File::create(path_to_file).map_err(DkgStoreError::CreateFile)?;
         if is_new_file {
-            f.set_permissions(Permissions::from_mode(FILE_PERM))
-                .map_err(DkgStoreError::Permission)?;
+            set_file_mode(&f, FILE_PERM).map_err(DkgStoreError::Permission)?;
         }
         f.write_all(toml.as_bytes()).map_err(DkgStoreError::Write)?;
 
@@ -184,6 +406,8 @@ pub enum DkgStoreError {
     ParseStringError,
     #[error("toml error")]
     TomlError,
+    #[error("failed to reconstruct state: {0}")]
+    Migration(DBStateError),
 }
 
 impl PartialEq for DkgStoreError {
@@ -0,0 +1,187 @@
+//! Validation of group arithmetic and scheme consistency of proposal terms.
+//!
+//! Checks are shared by server-side validation of received proposals (see [`validate`]) and by
+//! CLI pre-checks, errors report the offending values so the proposal can be fixed directly.
+use crate::core::beacon::canonical_beacon_id;
+use crate::core::beacon::BeaconIdError;
+use crate::key::group::minimum_t;
+use crate::key::KeyPoint;
+use crate::key::Scheme;
+use crate::net::utils::Address;
+use crate::transport::dkg::Participant;
+use crate::transport::dkg::ProposalTerms;
+
+use energon::traits::Affine;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum TermsError {
+    #[error("proposal has no remaining or joining nodes")]
+    EmptyGroup,
+    #[error(
+        "threshold {threshold} is higher than the count of remaining and joining nodes ({nodes})"
+    )]
+    ThresholdHigherThanNodeCount { threshold: usize, nodes: usize },
+    #[error("threshold {threshold} is below the minimum {minimum} required for {nodes} nodes")]
+    ThresholdTooLow {
+        threshold: usize,
+        minimum: usize,
+        nodes: usize,
+    },
+    #[error("{remaining} remaining nodes are fewer than the previous threshold {previous}, at most {max_leaving} of {leaving} leaving nodes can leave")]
+    TooManyLeavers {
+        remaining: usize,
+        leaving: usize,
+        previous: usize,
+        max_leaving: usize,
+    },
+    #[error("participant {0} is listed more than once")]
+    DuplicateParticipant(Address),
+    #[error("proposal scheme {proposed} differs from the scheme of this node {expected}")]
+    SchemeMismatch { proposed: String, expected: String },
+    #[error("key of participant {address} is not a valid {scheme} key")]
+    ParticipantScheme { address: Address, scheme: String },
+    #[error(transparent)]
+    BeaconId(#[from] BeaconIdError),
+}
+
+/// Checks threshold against the size of the resulting group.
+pub fn check_threshold(threshold: usize, nodes: usize) -> Result<(), TermsError> {
+    if nodes == 0 {
+        return Err(TermsError::EmptyGroup);
+    }
+    if threshold > nodes {
+        return Err(TermsError::ThresholdHigherThanNodeCount { threshold, nodes });
+    }
+    let minimum = minimum_t(nodes);
+    if threshold < minimum {
+        return Err(TermsError::ThresholdTooLow {
+            threshold,
+            minimum,
+            nodes,
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks that enough nodes of the previous group remain to reshare its secret.
+pub fn check_leavers(remaining: usize, leaving: usize, previous: usize) -> Result<(), TermsError> {
+    if remaining < previous {
+        return Err(TermsError::TooManyLeavers {
+            remaining,
+            leaving,
+            previous,
+            max_leaving: (remaining + leaving).saturating_sub(previous),
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks that every participant is listed once across all roles.
+pub fn check_unique<'a>(
+    participants: impl IntoIterator<Item = &'a Participant>,
+) -> Result<(), TermsError> {
+    let mut seen: Vec<&Address> = vec![];
+    for p in participants {
+        if seen.contains(&&p.address) {
+            return Err(TermsError::DuplicateParticipant(p.address.clone()));
+        }
+        seen.push(&p.address);
+    }
+
+    Ok(())
+}
+
+/// Checks that proposal and keys of all participants belong to the scheme of this node.
+pub fn check_scheme<'a, S: Scheme>(
+    scheme_id: &str,
+    participants: impl IntoIterator<Item = &'a Participant>,
+) -> Result<(), TermsError> {
+    if scheme_id != S::ID {
+        return Err(TermsError::SchemeMismatch {
+            proposed: scheme_id.to_string(),
+            expected: S::ID.to_string(),
+        });
+    }
+    for p in participants {
+        if KeyPoint::<S>::deserialize(&p.key).is_err() {
+            return Err(TermsError::ParticipantScheme {
+                address: p.address.clone(),
+                scheme: S::ID.to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Validates beacon id, participants, scheme and threshold of proposal terms.
+///
+/// Reshares should additionally be checked by [`check_leavers`] against the current group.
+pub fn validate<S: Scheme>(terms: &ProposalTerms) -> Result<(), TermsError> {
+    let all = || {
+        std::iter::once(&terms.leader)
+            .chain(&terms.joining)
+            .chain(&terms.remaining)
+            .chain(&terms.leaving)
+    };
+    canonical_beacon_id(&terms.beacon_id)?;
+    check_unique(all().skip(1))?;
+    check_scheme::<S>(&terms.scheme_id, all())?;
+    check_threshold(
+        terms.threshold as usize,
+        terms.joining.len() + terms.remaining.len(),
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_arithmetic() {
+        assert_eq!(check_threshold(3, 0), Err(TermsError::EmptyGroup));
+        assert_eq!(check_threshold(1, 1), Ok(()));
+        assert_eq!(check_threshold(3, 4), Ok(()));
+        assert_eq!(
+            check_threshold(5, 4),
+            Err(TermsError::ThresholdHigherThanNodeCount {
+                threshold: 5,
+                nodes: 4
+            })
+        );
+        assert_eq!(
+            check_threshold(2, 4),
+            Err(TermsError::ThresholdTooLow {
+                threshold: 2,
+                minimum: 3,
+                nodes: 4
+            })
+        );
+
+        assert_eq!(check_leavers(3, 2, 3), Ok(()));
+        let err = check_leavers(2, 3, 3).unwrap_err();
+        assert_eq!(
+            err,
+            TermsError::TooManyLeavers {
+                remaining: 2,
+                leaving: 3,
+                previous: 3,
+                max_leaving: 2
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "2 remaining nodes are fewer than the previous threshold 3, at most 2 of 3 leaving nodes can leave"
+        );
+
+        let p = Participant::default();
+        assert_eq!(
+            check_unique([&p, &p]),
+            Err(TermsError::DuplicateParticipant(p.address.clone()))
+        );
+    }
+}
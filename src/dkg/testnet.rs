@@ -0,0 +1,376 @@
+//! Local test network generated by `drand util generate-testnet`.
+//!
+//! Output folder contains a node folder with fresh key pair per node, a proposal listing
+//! all nodes as joiners and launch files: a docker-compose file or plain shell scripts.
+//! All nodes run this implementation by default. It can not lead a DKG yet, so the generated
+//! `dkg.sh` of such network stops with explanation before the DKG is initiated. With `go_leader`
+//! the first node is run by golang implementation and leads the DKG, key files are shared
+//! between implementations.
+use super::proposal::ProposalError;
+use super::proposal::ProposalFile;
+use super::proposal::DEFAULT_CATCHUP_PERIOD;
+use super::proposal::DEFAULT_PROPOSAL_TIMEOUT;
+
+use crate::key::keys::Pair;
+use crate::key::store::set_file_mode;
+use crate::key::store::FileStore;
+use crate::key::store::FileStoreError;
+use crate::key::toml::Toml;
+use crate::key::Scheme;
+use crate::net::utils::Address;
+use crate::net::utils::InvalidAddress;
+
+use energon::drand::schemes::DefaultScheme;
+use energon::drand::schemes::SigsOnG1Scheme;
+use energon::drand::schemes::UnchainedScheme;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Node port within compose network, each node runs in own container.
+const COMPOSE_NODE_PORT: u16 = 4444;
+/// Control port within compose network.
+const COMPOSE_CONTROL_PORT: u16 = 8888;
+/// First node port on localhost for shell scripts, incremented per node.
+const SCRIPTS_NODE_PORT: u16 = 44000;
+/// First control port on localhost for shell scripts, incremented per node.
+const SCRIPTS_CONTROL_PORT: u16 = 55000;
+/// Delay between DKG execution and genesis.
+const GENESIS_DELAY: &str = "30s";
+/// Upper bound keeps per node ports of shell scripts within valid range.
+const MAX_NODES: u16 = 1000;
+const PROPOSAL_FILE: &str = "proposal.toml";
+const SCRIPT_PERM: u32 = 0o755;
+/// Reason of the failing `dkg.sh` of network without golang leader.
+pub const LEADER_UNSUPPORTED: &str =
+    "drand-rs can not lead a DKG yet: regenerate the testnet with --go-leader to run node0 by golang implementation";
+
+#[derive(thiserror::Error, Debug)]
+pub enum TestnetError {
+    #[error("testnet requires from 2 to {MAX_NODES} nodes, received {0}")]
+    NodesCount(u16),
+    #[error("output folder {0} is not empty")]
+    NotEmpty(PathBuf),
+    #[error("unknown scheme {0}")]
+    UnknownScheme(String),
+    #[error("failed to generate key pair: {0}")]
+    KeyPair(anyhow::Error),
+    #[error(transparent)]
+    Address(#[from] InvalidAddress),
+    #[error("failed to encode proposal")]
+    ProposalEncode,
+    #[error("proposal: {0}")]
+    Proposal(#[from] ProposalError),
+    #[error("file store: {0}")]
+    FileStore(#[from] FileStoreError),
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+pub struct TestnetConfig {
+    pub nodes: u16,
+    /// Beacon period in seconds.
+    pub period: u32,
+    pub scheme: String,
+    pub beacon_id: String,
+    pub out: PathBuf,
+    /// Generate shell scripts instead of docker-compose file.
+    pub scripts: bool,
+    /// Docker image of this implementation.
+    pub image: String,
+    /// First node runs golang implementation and leads the DKG.
+    pub go_leader: bool,
+    /// Docker image of golang implementation, used by the leader if `go_leader` is set.
+    pub leader_image: String,
+}
+
+struct TestnetNode {
+    index: u16,
+    /// Address from key pair, reachable by other nodes.
+    address: Address,
+    control: u16,
+}
+
+impl TestnetNode {
+    fn new(index: u16, scripts: bool) -> Result<Self, TestnetError> {
+        let (address, control) = if scripts {
+            (
+                format!("127.0.0.1:{}", SCRIPTS_NODE_PORT + index),
+                SCRIPTS_CONTROL_PORT + index,
+            )
+        } else {
+            (
+                format!("node{index}:{COMPOSE_NODE_PORT}"),
+                COMPOSE_CONTROL_PORT,
+            )
+        };
+        Ok(Self {
+            index,
+            address: Address::precheck(&address)?,
+            control,
+        })
+    }
+
+    fn name(&self) -> String {
+        format!("node{}", self.index)
+    }
+
+    fn is_leader(&self) -> bool {
+        self.index == 0
+    }
+
+    /// Returns true if the node runs golang implementation.
+    fn is_go(&self, c: &TestnetConfig) -> bool {
+        c.go_leader && self.is_leader()
+    }
+}
+
+/// Generates test network at `c.out`, returns paths of written launch files.
+pub fn generate(c: &TestnetConfig) -> Result<Vec<PathBuf>, TestnetError> {
+    if !(2..=MAX_NODES).contains(&c.nodes) {
+        return Err(TestnetError::NodesCount(c.nodes));
+    }
+    if c.out.try_exists()? && std::fs::read_dir(&c.out)?.next().is_some() {
+        return Err(TestnetError::NotEmpty(c.out.clone()));
+    }
+    std::fs::create_dir_all(&c.out)?;
+    let out = c.out.canonicalize()?;
+
+    let nodes = (0..c.nodes)
+        .map(|i| TestnetNode::new(i, c.scripts))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut public_files = Vec::with_capacity(nodes.len());
+    for node in &nodes {
+        let folder = out.join(node.name());
+        let store = match c.scheme.as_str() {
+            DefaultScheme::ID => new_node::<DefaultScheme>(&folder, &c.beacon_id, node)?,
+            UnchainedScheme::ID => new_node::<UnchainedScheme>(&folder, &c.beacon_id, node)?,
+            SigsOnG1Scheme::ID => new_node::<SigsOnG1Scheme>(&folder, &c.beacon_id, node)?,
+            _ => return Err(TestnetError::UnknownScheme(c.scheme.clone())),
+        };
+        public_files.push(store.public_id_file().display().to_string());
+    }
+
+    let (proposal, _) = ProposalFile::from_public_files(&public_files, &[], &[])?;
+    let doc = proposal.toml_encode().ok_or(TestnetError::ProposalEncode)?;
+    std::fs::write(out.join(PROPOSAL_FILE), doc.to_string())?;
+
+    let files = if c.scripts {
+        vec![
+            write_script(&out, "start.sh", &start_script(c, &nodes))?,
+            write_script(&out, "dkg.sh", &dkg_script(c, &nodes, proposal.threshold()))?,
+            write_script(&out, "stop.sh", &stop_script(c, &nodes))?,
+        ]
+    } else {
+        let compose = out.join("docker-compose.yml");
+        std::fs::write(&compose, compose_file(c, &nodes))?;
+        vec![
+            compose,
+            write_script(&out, "dkg.sh", &dkg_script(c, &nodes, proposal.threshold()))?,
+        ]
+    };
+
+    Ok(files)
+}
+
+fn new_node<S: Scheme>(
+    folder: &Path,
+    beacon_id: &str,
+    node: &TestnetNode,
+) -> Result<FileStore, TestnetError> {
+    let pair = Pair::<S>::generate(node.address.clone()).map_err(TestnetError::KeyPair)?;
+    let folder = folder.to_str().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "path is not valid UTF-8")
+    })?;
+    let store = FileStore::new_checked(folder, beacon_id)?;
+    store.save_key_pair(&pair)?;
+
+    Ok(store)
+}
+
+fn write_script(out: &Path, name: &str, content: &str) -> Result<PathBuf, TestnetError> {
+    let path = out.join(name);
+    std::fs::write(&path, content)?;
+    set_file_mode(&File::open(&path)?, SCRIPT_PERM)?;
+
+    Ok(path)
+}
+
+fn compose_file(c: &TestnetConfig, nodes: &[TestnetNode]) -> String {
+    let mut f = format!(
+        "# Generated by `drand util generate-testnet`: {} nodes, period {}s, scheme {}, beacon id {}.\n# {}\nservices:\n",
+        nodes.len(),
+        c.period,
+        c.scheme,
+        c.beacon_id,
+        leader_note(c),
+    );
+    for node in nodes {
+        let image = if node.is_go(c) {
+            &c.leader_image
+        } else {
+            &c.image
+        };
+        let _ = write!(
+            f,
+            "  {name}:\n    image: {image}\n    command: start --folder /data --private-listen 0.0.0.0:{COMPOSE_NODE_PORT} --control {}\n    volumes:\n      - ./{name}:/data\n",
+            node.control,
+            name = node.name(),
+        );
+        if node.is_leader() {
+            let _ = writeln!(f, "      - ./{PROPOSAL_FILE}:/{PROPOSAL_FILE}:ro");
+        }
+    }
+
+    f
+}
+
+fn start_script(c: &TestnetConfig, nodes: &[TestnetNode]) -> String {
+    let mut f = format!(
+        "#!/bin/sh\n# {}\nDRAND_GO=${{DRAND_GO:-drand-go}}\nDRAND_RS=${{DRAND_RS:-drand}}\nDIR=$(cd \"$(dirname \"$0\")\" && pwd)\n",
+        leader_note(c),
+    );
+    for node in nodes {
+        let bin = if node.is_go(c) {
+            "$DRAND_GO"
+        } else {
+            "$DRAND_RS"
+        };
+        let _ = writeln!(
+            f,
+            "\"{bin}\" start --folder \"$DIR/{name}\" --private-listen {} --control {} > \"$DIR/{name}.log\" 2>&1 &",
+            node.address,
+            node.control,
+            name = node.name(),
+        );
+    }
+
+    f
+}
+
+fn stop_script(c: &TestnetConfig, nodes: &[TestnetNode]) -> String {
+    let mut f =
+        String::from("#!/bin/sh\nDRAND_GO=${DRAND_GO:-drand-go}\nDRAND_RS=${DRAND_RS:-drand}\n");
+    for node in nodes {
+        let bin = if node.is_go(c) {
+            "$DRAND_GO"
+        } else {
+            "$DRAND_RS"
+        };
+        let _ = writeln!(f, "\"{bin}\" stop --control {}", node.control);
+    }
+
+    f
+}
+
+/// Leader proposes the group, members join and the leader executes the DKG.
+///
+/// Script of a network without golang leader exits before the DKG is initiated, see
+/// [`LEADER_UNSUPPORTED`].
+fn dkg_script(c: &TestnetConfig, nodes: &[TestnetNode], threshold: usize) -> String {
+    let (header, proposal, leader, member) = if c.scripts {
+        (
+            "DRAND_GO=${DRAND_GO:-drand-go}\nDRAND_RS=${DRAND_RS:-drand}\nDIR=$(cd \"$(dirname \"$0\")\" && pwd)\n",
+            format!("\"$DIR/{PROPOSAL_FILE}\""),
+            if c.go_leader { "\"$DRAND_GO\"" } else { "\"$DRAND_RS\"" }.to_string(),
+            "\"$DRAND_RS\"".to_string(),
+        )
+    } else {
+        (
+            "cd \"$(dirname \"$0\")\"\n",
+            format!("/{PROPOSAL_FILE}"),
+            "docker compose exec node0 drand".to_string(),
+            "docker compose exec {node} drand".to_string(),
+        )
+    };
+    let id = &c.beacon_id;
+
+    let mut f = format!("#!/bin/sh\nset -e\n{header}");
+    if !c.go_leader {
+        let _ = writeln!(
+            f,
+            "# Remove the next line once drand-rs leads DKG.\necho \"{LEADER_UNSUPPORTED}\" >&2 && exit 1"
+        );
+    }
+    let _ = writeln!(
+        f,
+        "{leader} dkg init --id {id} --scheme {} --period {}s --catchup-period {DEFAULT_CATCHUP_PERIOD} --genesis-delay {GENESIS_DELAY} --timeout {DEFAULT_PROPOSAL_TIMEOUT} --threshold {threshold} --proposal {proposal} --control {}",
+        c.scheme, c.period, nodes[0].control
+    );
+    for node in nodes.iter().skip(1) {
+        let member = member.replace("{node}", &node.name());
+        let _ = writeln!(f, "{member} dkg join --id {id} --control {}", node.control);
+    }
+    let _ = writeln!(
+        f,
+        "{leader} dkg execute --id {id} --control {}",
+        nodes[0].control
+    );
+
+    f
+}
+
+fn leader_note(c: &TestnetConfig) -> &'static str {
+    if c.go_leader {
+        "node0 runs golang implementation and leads the DKG, run dkg.sh once all nodes are up."
+    } else {
+        "All nodes run drand-rs, which can not lead the DKG yet, see dkg.sh."
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::beacon::DEFAULT_BEACON_ID;
+    use toml_edit::DocumentMut;
+
+    #[test]
+    fn generate_testnet() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut c = TestnetConfig {
+            nodes: 3,
+            period: 3,
+            scheme: DefaultScheme::ID.into(),
+            beacon_id: DEFAULT_BEACON_ID.into(),
+            out: tmp.path().join("compose"),
+            scripts: false,
+            image: "drand-rs".into(),
+            go_leader: true,
+            leader_image: "go-drand".into(),
+        };
+
+        let files = generate(&c).unwrap();
+        assert_eq!(files.len(), 2);
+        let compose = std::fs::read_to_string(&files[0]).unwrap();
+        assert!(compose.contains("image: go-drand") && compose.contains("node2:"));
+        let dkg = std::fs::read_to_string(&files[1]).unwrap();
+        assert!(!dkg.contains(LEADER_UNSUPPORTED));
+
+        let proposal = std::fs::read_to_string(c.out.join(PROPOSAL_FILE)).unwrap();
+        let proposal =
+            ProposalFile::toml_decode(&proposal.parse::<DocumentMut>().unwrap()).unwrap();
+        assert_eq!(proposal.joining.len(), 3);
+
+        // Output folder must be empty.
+        assert!(matches!(generate(&c), Err(TestnetError::NotEmpty(_))));
+
+        c.out = tmp.path().join("scripts");
+        c.scripts = true;
+        let files = generate(&c).unwrap();
+        let dkg = std::fs::read_to_string(&files[1]).unwrap();
+        assert!(dkg.contains("--threshold 2") && dkg.contains("--control 55002"));
+
+        // Without golang leader all nodes run drand-rs and DKG is not initiated.
+        c.out = tmp.path().join("rs");
+        c.go_leader = false;
+        let files = generate(&c).unwrap();
+        let start = std::fs::read_to_string(&files[0]).unwrap();
+        assert!(!start.contains("\"$DRAND_GO\" start"));
+        let dkg = std::fs::read_to_string(&files[1]).unwrap();
+        assert!(dkg.contains(LEADER_UNSUPPORTED) && dkg.contains("\"$DRAND_RS\" dkg init"));
+
+        c.nodes = 1;
+        assert!(matches!(generate(&c), Err(TestnetError::NodesCount(1))));
+    }
+}
@@ -1,5 +1,10 @@
 use super::broadcast::Convert;
+use super::broadcast::Relay;
+use super::evidence::EvidenceLog;
+use super::fanout::Source;
+use super::pipeline::Pipeline;
 use super::ActionsError;
+use super::DkgNode;
 
 use crate::key::KeyPoint;
 use crate::key::Scheme;
@@ -11,11 +16,19 @@ use crate::transport::dkg::Participant;
 
 use energon::kyber::dkg::Bundle;
 use energon::kyber::dkg::BundleSender;
+use energon::kyber::dkg::Config;
+use energon::kyber::schnorr;
 use energon::traits::Affine;
 use tracing::trace;
 
+use prost::Message;
+use sha2::Digest;
+use sha2::Sha256;
 use std::collections::HashSet;
+use std::sync::Arc;
+use tokio_util::task::TaskTracker;
 use tracing::debug;
+use tracing::warn;
 use tracing::Span;
 
 const SHORT_SIG_BYTES: usize = 3;
@@ -42,8 +55,13 @@ impl Participant {
 }
 
 pub struct GateKeeper<S: Scheme> {
-    seen_gossip: HashSet<String>,
-    bundle_sender: Option<BundleSender<S>>,
+    /// Ids of received gossip packets, see [`super::fanout::packet_id`].
+    seen_gossip: HashSet<[u8; 32]>,
+    /// Hashes of bundles received during execution.
+    seen_bundles: HashSet<[u8; 32]>,
+    /// New bundles are decoded, passed to the protocol and relayed to other participants, see
+    /// [`Self::broadcast`].
+    pipeline: Option<Pipeline<Bundle<S>>>,
     log: Span,
 }
 
@@ -51,17 +69,57 @@ impl<S: Scheme> GateKeeper<S> {
     pub fn new(log: &Span) -> Self {
         Self {
             seen_gossip: HashSet::new(),
-            bundle_sender: None,
+            seen_bundles: HashSet::new(),
+            pipeline: None,
             log: log.to_owned(),
         }
     }
 
     /// The channel exist only within DKG execution stage, see [`super::execution::ExecuteDkg`].
-    pub fn open_gate(&mut self, tx: BundleSender<S>) -> Result<(), ActionsError> {
-        if self.bundle_sender.is_some() {
+    /// Bundles are relayed and recorded into `evidence` only once verified against `auth` and
+    /// decoded, off the beacon process.
+    pub(super) fn open_gate(
+        &mut self,
+        tx: BundleSender<S>,
+        relay: Relay,
+        evidence: EvidenceLog,
+        auth: BundleAuth<S>,
+        tracker: &TaskTracker,
+    ) -> Result<(), ActionsError> {
+        if self.pipeline.is_some() {
             Err(ActionsError::ProtocolAlreadyRunning)
         } else {
-            self.bundle_sender = Some(tx);
+            let log = self.log.clone();
+            let apply = move |bundle, proto: DkgPacket, replayed| {
+                let (tx, relay, evidence, log) =
+                    (tx.clone(), relay.clone(), evidence.clone(), log.clone());
+                async move {
+                    // Only verified bundles are recorded, replayed ones are already in the log.
+                    if !replayed {
+                        let record = proto.clone();
+                        let recorded =
+                            tokio::task::spawn_blocking(move || evidence.bundle(&record, false))
+                                .await;
+                        if let Ok(Err(err)) = recorded {
+                            warn!(parent: &log, "gatekeeper: failed to record evidence: {err}");
+                        }
+                    }
+                    if tx.send(bundle).await.is_err() {
+                        return false;
+                    }
+                    // Broadcast to all peers might be already finished.
+                    relay.send(proto, Source::Relayed(None));
+                    true
+                }
+            };
+            let decode = move |proto: DkgPacket| {
+                if auth.is_valid(&proto) {
+                    bundle_from_proto::<S>(proto)
+                } else {
+                    None
+                }
+            };
+            self.pipeline = Some(Pipeline::start(Arc::new(decode), apply, tracker, &self.log));
 
             Ok(())
         }
@@ -70,39 +128,182 @@ impl<S: Scheme> GateKeeper<S> {
     /// Resets keeper into empty state.
     pub fn set_empty(&mut self) {
         self.seen_gossip.clear();
-        self.bundle_sender = None;
+        self.seen_bundles.clear();
+        self.pipeline = None;
     }
 
-    /// Returns `true` if gossip packet is not seen and its signature is not less than [`SHORT_SIG_LEN`].
+    /// Returns `true` if gossip packet is not seen and its signature is not less than [`SHORT_SIG_BYTES`].
+    ///
+    /// Packets are relayed by other participants depending on [`super::fanout::FanOut`], so
+    /// the same packet is usually received more than once.
     pub fn is_new_packet(&mut self, p: &GossipPacket) -> bool {
         let mut is_new = false;
 
         if let Some(short_sig) = p.metadata.signature.get(..SHORT_SIG_BYTES) {
             let sig_hex = hex::encode(short_sig);
-            if self.seen_gossip.contains(&sig_hex) {
-                trace!(parent: &self.log, "gatekeeper: ignoring duplicate gossip packet, type: {} sig: {sig_hex}, from: {}", p.data, p.metadata.address);
-            } else {
+            if self.seen_gossip.insert(super::fanout::packet_id(p)) {
                 debug!(parent: &self.log, "gatekeeper: processing DKG gossip packet, type: {}, sig: {sig_hex}, id: {}, allegedly from: {}",
                       p.data, p.metadata.beacon_id, p.metadata.address);
-                is_new = self.seen_gossip.insert(sig_hex);
+                is_new = true;
+            } else {
+                trace!(parent: &self.log, "gatekeeper: ignoring duplicate gossip packet, type: {} sig: {sig_hex}, from: {}", p.data, p.metadata.address);
             }
         } else {
-            tracing::warn!(parent: &self.log, "gatekeeper: ignoring gossip packet with too short signature, allegedly from: {}", p.metadata.address);
+            warn!(parent: &self.log, "gatekeeper: ignoring gossip packet with too short signature, allegedly from: {}", p.metadata.address);
         }
 
         is_new
     }
 
-    pub async fn broadcast(&mut self, proto: DkgPacket) -> Result<(), ActionsError> {
-        if let Some(ref tx) = &self.bundle_sender {
-            let bundle = bundle_from_proto(proto).ok_or(ActionsError::InvalidProtoBundle)?;
-            tx.send(bundle)
-                .await
-                .map_err(|_| ActionsError::ProtocolIsNotRunning)
+    /// Returns `true` if bundle is not seen within the current execution.
+    pub fn is_new_bundle(&mut self, proto: &DkgPacket) -> bool {
+        bundle_hash(proto).is_some_and(|hash| self.seen_bundles.insert(hash))
+    }
+
+    /// Passes bundle to the running protocol and relays it to other participants (echo broadcast),
+    /// recipients are chosen by [`super::fanout::FanOut`].
+    ///
+    /// Dealer might deliver its bundles only to a part of participants, relaying makes sure that
+    /// deals, complaints and justifications are seen by all honest nodes, so misbehaving dealer is
+    /// excluded from qualified set by all of them instead of failing the DKG. Bundles are verified
+    /// and decoded in the background, see [`super::pipeline`], the ones which are not signed by
+    /// their issuer or can not be decoded are rejected individually and are not relayed.
+    pub fn broadcast(&mut self, proto: DkgPacket) -> Result<(), ActionsError> {
+        if self.pipeline.is_none() {
+            return Err(ActionsError::ProtocolIsNotRunning);
+        }
+        if !self.is_new_bundle(&proto) {
+            trace!(parent: &self.log, "gatekeeper: ignoring duplicate dkg bundle");
+            return Ok(());
+        }
+        let hash = bundle_hash(&proto);
+        if let Some(Err(err)) = self.pipeline.as_ref().map(|p| p.push(proto)) {
+            // Rejected bundle is accepted once it is received again.
+            if let Some(hash) = hash {
+                self.seen_bundles.remove(&hash);
+            }
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    /// Passes bundle received before a restart to the running protocol, see [`super::resume`].
+    /// Replayed bundles are already recorded as evidence, see [`Self::open_gate`].
+    pub async fn replay(&mut self, proto: DkgPacket) -> Result<(), ActionsError> {
+        let Some(pipeline) = &self.pipeline else {
+            return Err(ActionsError::ProtocolIsNotRunning);
+        };
+        if let Some(hash) = bundle_hash(&proto) {
+            self.seen_bundles.insert(hash);
+        }
+
+        pipeline.push_wait(proto).await
+    }
+}
+
+/// Issuers of bundles of the running execution, bundles are checked as by `VerifyPacketSignature`
+/// of Go kyber before they are passed to the protocol and relayed.
+pub(super) struct BundleAuth<S: Scheme> {
+    session_id: Vec<u8>,
+    /// Issuers of deals and justifications.
+    dealers: Vec<DkgNode<S>>,
+    /// Issuers of responses.
+    holders: Vec<DkgNode<S>>,
+}
+
+impl<S: Scheme> BundleAuth<S> {
+    /// Dealers are the new nodes unless the config is a resharing.
+    pub(super) fn new(config: &Config<S>) -> Self {
+        let dealers = if config.old_nodes.is_empty() {
+            &config.new_nodes
         } else {
-            Err(ActionsError::ProtocolIsNotRunning)
+            &config.old_nodes
+        };
+
+        Self {
+            session_id: config.nonce.to_vec(),
+            dealers: dealers.clone(),
+            holders: config.new_nodes.clone(),
+        }
+    }
+
+    /// Returns `true` if the bundle belongs to the session and is signed by its issuer.
+    pub(super) fn is_valid(&self, proto: &DkgPacket) -> bool {
+        let Some(bundle) = proto.dkg.as_ref().and_then(|p| p.bundle.as_ref()) else {
+            return false;
+        };
+        let (issuers, issuer, session_id, signature) = match bundle {
+            ProtoBundle::Deal(d) => (&self.dealers, d.dealer_index, &d.session_id, &d.signature),
+            ProtoBundle::Response(r) => (&self.holders, r.share_index, &r.session_id, &r.signature),
+            ProtoBundle::Justification(j) => {
+                (&self.dealers, j.dealer_index, &j.session_id, &j.signature)
+            }
+        };
+        if *session_id != self.session_id {
+            return false;
+        }
+
+        issuers.iter().find(|n| n.index == issuer).is_some_and(|n| {
+            schnorr::verify::<S>(&n.public, &signed_hash(bundle), signature).is_ok()
+        })
+    }
+}
+
+/// Returns hash of the bundle signed by its issuer, as `Hash` of bundles of Go kyber.
+fn signed_hash(bundle: &ProtoBundle) -> [u8; 32] {
+    let mut h = Sha256::new();
+    match bundle {
+        ProtoBundle::Deal(d) => {
+            h.update(d.dealer_index.to_be_bytes());
+            for commit in &d.commits {
+                h.update(commit);
+            }
+            let mut deals: Vec<_> = d.deals.iter().collect();
+            deals.sort_by_key(|deal| deal.share_index);
+            for deal in deals {
+                h.update(deal.share_index.to_be_bytes());
+                h.update(&deal.encrypted_share);
+            }
+            h.update(&d.session_id);
+        }
+        ProtoBundle::Response(r) => {
+            h.update(r.share_index.to_be_bytes());
+            let mut responses: Vec<_> = r.responses.iter().collect();
+            responses.sort_by_key(|response| response.dealer_index);
+            for response in responses {
+                h.update(response.dealer_index.to_be_bytes());
+                h.update([u8::from(response.status)]);
+            }
+            h.update(&r.session_id);
+        }
+        ProtoBundle::Justification(j) => {
+            h.update(j.dealer_index.to_be_bytes());
+            let mut justifications: Vec<_> = j.justifications.iter().collect();
+            justifications.sort_by_key(|justification| justification.share_index);
+            for justification in justifications {
+                h.update(justification.share_index.to_be_bytes());
+                h.update(&justification.share);
+            }
+            h.update(&j.session_id);
         }
     }
+
+    h.finalize().into()
+}
+
+/// Returns hash of the bundle without metadata, relayed copies of a bundle have the same hash.
+fn bundle_hash(proto: &DkgPacket) -> Option<[u8; 32]> {
+    let bundle = proto.dkg.as_ref()?.bundle.as_ref()?;
+    let hash = Sha256::digest(
+        crate::protobuf::dkg::Packet {
+            metadata: None,
+            bundle: Some(bundle.clone()),
+        }
+        .encode_to_vec(),
+    );
+
+    Some(hash.into())
 }
 
 fn bundle_from_proto<S: Scheme>(proto: DkgPacket) -> Option<Bundle<S>> {
@@ -114,3 +315,120 @@ fn bundle_from_proto<S: Scheme>(proto: DkgPacket) -> Option<Bundle<S>> {
 
     Some(bundle)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protobuf::dkg::DealBundle;
+    use crate::protobuf::dkg::Packet;
+    use crate::protobuf::dkg::Response;
+    use crate::protobuf::dkg::ResponseBundle;
+    use energon::drand::schemes::DefaultScheme;
+
+    fn packet(bundle: ProtoBundle) -> DkgPacket {
+        DkgPacket {
+            dkg: Some(Packet {
+                metadata: None,
+                bundle: Some(bundle),
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn reject_bad_bundles() {
+        let mut gk = GateKeeper::<DefaultScheme>::new(&Span::none());
+        // Deal with invalid commitment.
+        let bad_deal = packet(ProtoBundle::Deal(DealBundle {
+            dealer_index: 1,
+            commits: vec![vec![1, 2, 3]],
+            signature: vec![4],
+            ..Default::default()
+        }));
+        let response = packet(ProtoBundle::Response(ResponseBundle {
+            share_index: 2,
+            responses: vec![Response {
+                dealer_index: 1,
+                status: false,
+            }],
+            signature: vec![5],
+            ..Default::default()
+        }));
+        assert!(matches!(
+            gk.broadcast(bad_deal.clone()),
+            Err(ActionsError::ProtocolIsNotRunning)
+        ));
+
+        // Bad deal is rejected without affecting complaint about it.
+        assert!(bundle_from_proto::<DefaultScheme>(bad_deal.clone()).is_none());
+        assert!(matches!(
+            bundle_from_proto::<DefaultScheme>(response.clone()),
+            Some(Bundle::Response(_))
+        ));
+
+        // Relayed bundles are passed to the protocol once, regardless of metadata.
+        assert!(gk.is_new_bundle(&bad_deal));
+        assert!(!gk.is_new_bundle(&bad_deal));
+        let mut relayed = response.clone();
+        relayed.dkg.as_mut().unwrap().metadata =
+            Some(crate::protobuf::drand::Metadata::with_id("default".into()));
+        assert!(gk.is_new_bundle(&response));
+        assert!(!gk.is_new_bundle(&relayed));
+        assert!(!gk.is_new_bundle(&DkgPacket { dkg: None }));
+
+        gk.set_empty();
+        assert!(gk.is_new_bundle(&bad_deal));
+    }
+
+    #[test]
+    fn reject_unauthenticated_bundles() {
+        use crate::key::keys::Pair;
+        use crate::net::utils::Address;
+
+        let nodes = (0..3u32)
+            .map(|index| {
+                let address = Address::precheck(&format!("127.0.0.1:{}", 1000 + index)).unwrap();
+                let pair = Pair::<DefaultScheme>::generate(address).unwrap();
+                DkgNode {
+                    index,
+                    public: pair.public_identity().key().clone(),
+                }
+            })
+            .collect::<Vec<_>>();
+        let auth = BundleAuth::<DefaultScheme> {
+            session_id: vec![7; 32],
+            dealers: nodes.clone(),
+            holders: nodes,
+        };
+        let deal = |dealer_index, session_id: Vec<u8>| {
+            packet(ProtoBundle::Deal(DealBundle {
+                dealer_index,
+                session_id,
+                signature: vec![1; 64],
+                ..Default::default()
+            }))
+        };
+
+        // Forged signature, another session and unknown issuer.
+        assert!(!auth.is_valid(&deal(1, vec![7; 32])));
+        assert!(!auth.is_valid(&deal(1, vec![8; 32])));
+        assert!(!auth.is_valid(&deal(5, vec![7; 32])));
+        assert!(!auth.is_valid(&DkgPacket { dkg: None }));
+
+        // Hash does not depend on order of deals.
+        let mut ordered = DealBundle {
+            dealer_index: 1,
+            deals: (0..3)
+                .map(|share_index| crate::protobuf::dkg::Deal {
+                    share_index,
+                    encrypted_share: share_index.to_be_bytes().to_vec(),
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let hash = signed_hash(&ProtoBundle::Deal(ordered.clone()));
+        ordered.deals.reverse();
+        assert_eq!(signed_hash(&ProtoBundle::Deal(ordered.clone())), hash);
+        ordered.deals[0].encrypted_share = vec![9];
+        assert_ne!(signed_hash(&ProtoBundle::Deal(ordered)), hash);
+    }
+}
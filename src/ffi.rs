@@ -0,0 +1,207 @@
+//! C ABI of the verification core, declared in `include/drand.h`.
+//!
+//! The header is generated by cbindgen from this module, regenerate it after changes with
+//! `cbindgen --config cbindgen.toml --output include/drand.h`. Build with `cargo rustc --lib
+//! --release --no-default-features --features blstrs,ffi --crate-type staticlib,cdylib` and
+//! link `libdrand.a` or `libdrand.so`, the manifest builds only the Rust library.
+//!
+//! Buffers are passed as pointer and length, a null pointer is accepted only with zero length.
+//!
+//! Note: timelock (tlock) encryption is not bound, as the crypto backend exposes no pairing or
+//! identity based encryption primitives to implement it on. The signature of a verified beacon
+//! is the decryption key of ciphertexts locked to its round, see [`drand_round_at`], so C
+//! consumers pass beacons checked with [`drand_verify_beacon`] to a tlock library.
+use crate::verify;
+use crate::verify::VerifyError;
+
+use core::ffi::c_char;
+use core::ffi::CStr;
+
+/// Status of a call.
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub enum DrandStatus {
+    /// Beacon is valid or the output is written.
+    Ok = 0,
+    /// Beacon signature does not verify.
+    InvalidBeacon = 1,
+    /// Scheme id is not known.
+    UnknownScheme = 2,
+    /// Public key or signature can not be deserialized.
+    InvalidPoint = 3,
+    /// Null pointer or string which is not UTF-8.
+    InvalidArgument = 4,
+}
+
+impl From<VerifyError> for DrandStatus {
+    fn from(err: VerifyError) -> Self {
+        match err {
+            VerifyError::UnknownScheme => Self::UnknownScheme,
+            VerifyError::InvalidKey | VerifyError::InvalidSignature => Self::InvalidPoint,
+        }
+    }
+}
+
+/// Returns slice of the buffer, `None` for null pointer with non-zero length.
+unsafe fn slice<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    if ptr.is_null() {
+        return (len == 0).then_some(&[]);
+    }
+
+    Some(core::slice::from_raw_parts(ptr, len))
+}
+
+unsafe fn c_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        return None;
+    }
+
+    CStr::from_ptr(ptr).to_str().ok()
+}
+
+/// Verifies beacon of the round, `previous_signature` is ignored by unchained schemes.
+///
+/// # Safety
+///
+/// `scheme_id` must be a nul-terminated string, buffers must be valid for reads of given length.
+#[no_mangle]
+#[allow(
+    clippy::too_many_arguments,
+    reason = "buffers are passed as pointer and length"
+)]
+pub unsafe extern "C" fn drand_verify_beacon(
+    scheme_id: *const c_char,
+    public_key: *const u8,
+    public_key_len: usize,
+    round: u64,
+    signature: *const u8,
+    signature_len: usize,
+    previous_signature: *const u8,
+    previous_signature_len: usize,
+) -> DrandStatus {
+    let (Some(scheme_id), Some(public_key), Some(signature), Some(previous_signature)) = (
+        c_str(scheme_id),
+        slice(public_key, public_key_len),
+        slice(signature, signature_len),
+        slice(previous_signature, previous_signature_len),
+    ) else {
+        return DrandStatus::InvalidArgument;
+    };
+
+    match verify::verify_serialized(scheme_id, public_key, previous_signature, round, signature) {
+        Ok(true) => DrandStatus::Ok,
+        Ok(false) => DrandStatus::InvalidBeacon,
+        Err(err) => err.into(),
+    }
+}
+
+/// Writes 32 bytes of the chain hash into `out`, empty beacon id stands for the default chain.
+///
+/// # Safety
+///
+/// `beacon_id` must be a nul-terminated string, buffers must be valid for reads of given length
+/// and `out` must be valid for writes of 32 bytes.
+#[no_mangle]
+#[allow(
+    clippy::too_many_arguments,
+    reason = "buffers are passed as pointer and length"
+)]
+pub unsafe extern "C" fn drand_chain_hash(
+    period: u32,
+    genesis_time: i64,
+    public_key: *const u8,
+    public_key_len: usize,
+    group_hash: *const u8,
+    group_hash_len: usize,
+    beacon_id: *const c_char,
+    out: *mut u8,
+) -> DrandStatus {
+    let (Some(public_key), Some(group_hash), Some(beacon_id)) = (
+        slice(public_key, public_key_len),
+        slice(group_hash, group_hash_len),
+        c_str(beacon_id),
+    ) else {
+        return DrandStatus::InvalidArgument;
+    };
+    if out.is_null() {
+        return DrandStatus::InvalidArgument;
+    }
+    let hash = verify::chain_hash(period, genesis_time, public_key, group_hash, beacon_id);
+    core::ptr::copy_nonoverlapping(hash.as_ptr(), out, hash.len());
+
+    DrandStatus::Ok
+}
+
+/// Writes 32 bytes of the randomness of a beacon into `out`.
+///
+/// # Safety
+///
+/// `signature` must be valid for reads of `signature_len` bytes and `out` must be valid for
+/// writes of 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn drand_randomness(
+    signature: *const u8,
+    signature_len: usize,
+    out: *mut u8,
+) -> DrandStatus {
+    let Some(signature) = slice(signature, signature_len) else {
+        return DrandStatus::InvalidArgument;
+    };
+    if out.is_null() {
+        return DrandStatus::InvalidArgument;
+    }
+    let randomness = verify::randomness(signature);
+    core::ptr::copy_nonoverlapping(randomness.as_ptr(), out, randomness.len());
+
+    DrandStatus::Ok
+}
+
+/// Returns the round active at UNIX time `now`, see [`verify::round_at`].
+#[must_use]
+#[no_mangle]
+pub extern "C" fn drand_round_at(now: u64, period: u32, genesis_time: u64) -> u64 {
+    verify::round_at(now, period, genesis_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn c_abi() {
+        let mut out = [0; 32];
+        let status = unsafe {
+            drand_chain_hash(
+                3,
+                1692803367,
+                core::ptr::null(),
+                0,
+                core::ptr::null(),
+                0,
+                c"quicknet".as_ptr(),
+                out.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, DrandStatus::Ok);
+        assert_eq!(out, verify::chain_hash(3, 1692803367, &[], &[], "quicknet"));
+
+        // Null pointer with non-zero length.
+        let status = unsafe { drand_randomness(core::ptr::null(), 1, out.as_mut_ptr()) };
+        assert_eq!(status, DrandStatus::InvalidArgument);
+
+        let status = unsafe {
+            drand_verify_beacon(
+                c"unknown".as_ptr(),
+                core::ptr::null(),
+                0,
+                1,
+                core::ptr::null(),
+                0,
+                core::ptr::null(),
+                0,
+            )
+        };
+        assert_eq!(status, DrandStatus::UnknownScheme);
+        assert_eq!(drand_round_at(1692803370, 3, 1692803367), 2);
+    }
+}
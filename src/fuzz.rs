@@ -0,0 +1,42 @@
+//! Entry points of fuzz targets.
+//!
+//! Arbitrary bytes are decoded as protobuf packets received from peers and passed into
+//! the validation layer, which is expected to reject malformed packets without panics.
+use crate::chain::ChainInfo;
+use crate::protobuf::dkg::DkgCommand;
+use crate::protobuf::dkg::GossipPacket;
+use crate::protobuf::drand::BeaconPacket;
+use crate::protobuf::drand::ChainInfoPacket;
+use crate::transport::ConvertProto;
+
+use energon::drand::schemes::DefaultScheme;
+use energon::drand::schemes::SigsOnG1Scheme;
+use energon::drand::schemes::UnchainedScheme;
+use prost::Message;
+
+fn decode_and_validate<P: Message + Default + ConvertProto>(data: &[u8]) {
+    if let Ok(packet) = P::decode(data) {
+        let _ = packet.validate();
+    }
+}
+
+pub fn dkg_command(data: &[u8]) {
+    decode_and_validate::<DkgCommand>(data);
+}
+
+pub fn gossip_packet(data: &[u8]) {
+    decode_and_validate::<GossipPacket>(data);
+}
+
+pub fn beacon_packet(data: &[u8]) {
+    decode_and_validate::<BeaconPacket>(data);
+}
+
+/// Chain info has no scheme-independent inner type, the packet is validated for each scheme.
+pub fn chain_info_packet(data: &[u8]) {
+    if let Ok(packet) = ChainInfoPacket::decode(data) {
+        let _ = ChainInfo::<DefaultScheme>::from_packet(&packet, String::new());
+        let _ = ChainInfo::<UnchainedScheme>::from_packet(&packet, String::new());
+        let _ = ChainInfo::<SigsOnG1Scheme>::from_packet(&packet, String::new());
+    }
+}
@@ -0,0 +1,552 @@
+//! Backup archives of beacon ids for disaster recovery.
+//!
+//! Archive is a zstd compressed tarball with paths relative to the `multibeacon` folder and
+//! [`MANIFEST_FILE`] at the root. Keys, group files and DKG store are archived, the chain
+//! database is not: it is synced back from the network after restore.
+//!
+//! If a passphrase is given, private key and share files are encrypted with [age] passphrase
+//! encryption. Manifest contains SHA-256 of every plaintext file and is checked on restore.
+//! Permissions are not taken from the archive: restored folders are private to the owner, as
+//! well as key and share files.
+//!
+//! [age]: https://age-encryption.org
+use super::store::absolute_path;
+use super::store::new_secure_dir;
+use super::store::set_dir_mode;
+use super::store::set_file_mode;
+use super::store::FileStore;
+use super::store::FileStoreError;
+use super::store::DB_DIR;
+use super::store::MULTIBEACON_DIR;
+use super::store::PRIVATE_ID_FILE;
+use super::store::PRIVATE_PERM;
+use super::store::PRIVATE_SHARE_FILE;
+use super::store::PUBLIC_PERM;
+
+use age::secrecy::SecretString;
+use serde_json::json;
+use serde_json::Value;
+use sha2::Digest;
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::io::Write;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+/// Manifest of the archive, the first entry of the tarball.
+pub const MANIFEST_FILE: &str = "manifest.json";
+/// Folder of archives requested over control RPC, relative to the base folder.
+pub const BACKUPS_DIR: &str = "backups";
+/// Version of the archive layout.
+const MANIFEST_VERSION: u64 = 1;
+/// Permission of the archive file, it contains private keys.
+const ARCHIVE_PERM: u32 = 0o600;
+/// Permission of restored folders.
+const RESTORED_DIR_PERM: u32 = 0o700;
+
+#[derive(thiserror::Error, Debug)]
+pub enum BackupError {
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+    #[error(transparent)]
+    FileStore(#[from] FileStoreError),
+    #[error("invalid manifest: {0}")]
+    InvalidManifest(String),
+    #[error("unsupported archive version {0}")]
+    UnsupportedVersion(u64),
+    #[error("checksum mismatch of {0}")]
+    Checksum(String),
+    #[error("archive is encrypted, passphrase is required")]
+    PassphraseRequired,
+    #[error("failed to decrypt {0}: {1}")]
+    Decrypt(String, age::DecryptError),
+    #[error(
+        "output {0} is not a file name, archives are written into {BACKUPS_DIR} of the base folder"
+    )]
+    InvalidOutput(String),
+}
+
+/// Content of [`MANIFEST_FILE`].
+#[derive(Debug, PartialEq)]
+pub struct Manifest {
+    /// UNIX time of the backup in seconds.
+    pub created_at: u64,
+    /// Private files are encrypted with passphrase.
+    pub encrypted: bool,
+    pub beacon_ids: Vec<String>,
+    /// Hex encoded SHA-256 of plaintext files keyed by path.
+    pub files: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    fn to_json(&self) -> Vec<u8> {
+        json!({
+            "version": MANIFEST_VERSION,
+            "created_at": self.created_at,
+            "encrypted": self.encrypted,
+            "beacon_ids": self.beacon_ids,
+            "files": self.files,
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    fn from_json(bytes: &[u8]) -> Result<Self, BackupError> {
+        let invalid = |field: &str| BackupError::InvalidManifest(format!("field '{field}'"));
+        let json: Value = serde_json::from_slice(bytes)
+            .map_err(|err| BackupError::InvalidManifest(err.to_string()))?;
+        let version = json["version"].as_u64().ok_or_else(|| invalid("version"))?;
+        if version != MANIFEST_VERSION {
+            return Err(BackupError::UnsupportedVersion(version));
+        }
+        let beacon_ids: Vec<String> = json["beacon_ids"]
+            .as_array()
+            .and_then(|ids| ids.iter().map(|id| id.as_str().map(String::from)).collect())
+            .ok_or_else(|| invalid("beacon_ids"))?;
+        // Beacon ids are folder names, restore must not escape the multibeacon folder.
+        if beacon_ids.iter().any(|id| !is_file_name(id)) {
+            return Err(invalid("beacon_ids"));
+        }
+        let files = json["files"]
+            .as_object()
+            .and_then(|files| {
+                files
+                    .iter()
+                    .map(|(path, hash)| Some((path.clone(), hash.as_str()?.to_string())))
+                    .collect()
+            })
+            .ok_or_else(|| invalid("files"))?;
+
+        Ok(Self {
+            created_at: json["created_at"]
+                .as_u64()
+                .ok_or_else(|| invalid("created_at"))?,
+            encrypted: json["encrypted"]
+                .as_bool()
+                .ok_or_else(|| invalid("encrypted"))?,
+            beacon_ids,
+            files,
+        })
+    }
+}
+
+/// File of the archive with path relative to the `multibeacon` folder.
+struct Entry {
+    path: String,
+    mode: u32,
+    data: Vec<u8>,
+}
+
+/// Writes backup of beacon ids from `multibeacon` folder into a new file `out`, all beacon ids
+/// are archived if `ids` is empty.
+///
+/// Beacon ids are expected to be idle: a DKG running in the meantime may be archived partially.
+pub fn create(
+    multibeacon: &Path,
+    ids: &[String],
+    out: &Path,
+    passphrase: Option<&str>,
+) -> Result<Manifest, BackupError> {
+    let mut beacon_ids = vec![];
+    for entry in std::fs::read_dir(multibeacon)? {
+        let entry = entry?;
+        if let Some(id) = entry.file_name().to_str() {
+            if entry.file_type()?.is_dir() && (ids.is_empty() || ids.iter().any(|i| i == id)) {
+                beacon_ids.push(id.to_string());
+            }
+        }
+    }
+    if beacon_ids.is_empty() || ids.iter().any(|id| !beacon_ids.contains(id)) {
+        return Err(FileStoreError::BeaconNotFound.into());
+    }
+    beacon_ids.sort();
+
+    let mut entries = vec![];
+    for id in &beacon_ids {
+        let store = FileStore {
+            beacon_path: multibeacon.join(id),
+        };
+        store.validate()?;
+        collect(&store.beacon_path, id, &mut entries)?;
+    }
+    let mut manifest = Manifest {
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+        encrypted: passphrase.is_some(),
+        beacon_ids,
+        files: BTreeMap::new(),
+    };
+    for entry in &mut entries {
+        manifest
+            .files
+            .insert(entry.path.clone(), hex::encode(Sha256::digest(&entry.data)));
+        if let Some(passphrase) = passphrase.filter(|_| is_private(&entry.path)) {
+            entry.data = encrypt(&entry.data, passphrase)?;
+        }
+    }
+
+    let file = File::options().write(true).create_new(true).open(out)?;
+    set_file_mode(&file, ARCHIVE_PERM)?;
+    let mut tar = tar::Builder::new(zstd::Encoder::new(file, 0)?);
+    let manifest_entry = Entry {
+        path: MANIFEST_FILE.to_string(),
+        mode: ARCHIVE_PERM,
+        data: manifest.to_json(),
+    };
+    for entry in std::iter::once(&manifest_entry).chain(&entries) {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(entry.data.len() as u64);
+        header.set_mode(entry.mode);
+        header.set_mtime(manifest.created_at);
+        tar.append_data(&mut header, &entry.path, entry.data.as_slice())?;
+    }
+    tar.into_inner()?.finish()?.sync_all()?;
+
+    Ok(manifest)
+}
+
+/// Returns path of a new archive `name` in [`BACKUPS_DIR`] of the `base` folder, the folder is
+/// created if absent. Control clients can not make the daemon write outside of its base folder.
+pub fn output_path(base: &Path, name: &str) -> Result<PathBuf, BackupError> {
+    if !is_file_name(name) {
+        return Err(BackupError::InvalidOutput(name.to_string()));
+    }
+    let dir = base.join(BACKUPS_DIR);
+    if !dir.try_exists()? {
+        new_secure_dir(&dir)?;
+    }
+
+    Ok(dir.join(name))
+}
+
+/// Restores beacon ids from archive into `multibeacon` folder of the base `folder`.
+///
+/// Beacon ids which are already present in the folder are never overwritten, in this case
+/// nothing is restored.
+pub fn restore(
+    archive: &Path,
+    folder: &str,
+    passphrase: Option<&str>,
+) -> Result<Manifest, BackupError> {
+    let (manifest, entries) = read(archive, passphrase)?;
+
+    let base = absolute_path(folder)?;
+    let multibeacon = base.join(MULTIBEACON_DIR);
+    for dir in [&base, &multibeacon] {
+        if !dir.try_exists()? {
+            new_secure_dir(dir)?;
+        }
+    }
+    for id in &manifest.beacon_ids {
+        let beacon_path = multibeacon.join(id);
+        if beacon_path.try_exists()? {
+            return Err(FileStoreError::FileAlreadyExists(beacon_path).into());
+        }
+    }
+    if let Err(err) = write(&multibeacon, &manifest, &entries) {
+        // Partially restored ids would fail validation at the daemon start.
+        for id in &manifest.beacon_ids {
+            let _ = std::fs::remove_dir_all(multibeacon.join(id));
+        }
+        return Err(err);
+    }
+
+    Ok(manifest)
+}
+
+/// Reads and verifies archive, private files are decrypted.
+fn read(archive: &Path, passphrase: Option<&str>) -> Result<(Manifest, Vec<Entry>), BackupError> {
+    let mut tar = tar::Archive::new(zstd::Decoder::new(File::open(archive)?)?);
+    let mut manifest = None;
+    let mut entries = vec![];
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        if !is_relative(&path) {
+            return Err(BackupError::InvalidManifest(format!(
+                "unsafe path {}",
+                path.display()
+            )));
+        }
+        let path = path.to_string_lossy().into_owned();
+        let mode = entry.header().mode()?;
+        let mut data = vec![];
+        entry.read_to_end(&mut data)?;
+        if path == MANIFEST_FILE {
+            manifest = Some(Manifest::from_json(&data)?);
+        } else {
+            entries.push(Entry { path, mode, data });
+        }
+    }
+    let manifest = manifest
+        .ok_or_else(|| BackupError::InvalidManifest(format!("{MANIFEST_FILE} is absent")))?;
+    if manifest.encrypted && passphrase.is_none() {
+        return Err(BackupError::PassphraseRequired);
+    }
+
+    for entry in &mut entries {
+        let expected = manifest
+            .files
+            .get(&entry.path)
+            .ok_or_else(|| BackupError::InvalidManifest(format!("{} is not listed", entry.path)))?;
+        let (id, _) = entry.path.split_once('/').unwrap_or_default();
+        if !manifest.beacon_ids.iter().any(|i| i == id) {
+            return Err(BackupError::InvalidManifest(format!(
+                "{} is outside of beacon ids",
+                entry.path
+            )));
+        }
+        if manifest.encrypted && is_private(&entry.path) {
+            entry.data = decrypt(&entry.data, passphrase.unwrap_or_default())
+                .map_err(|err| BackupError::Decrypt(entry.path.clone(), err))?;
+        }
+        if hex::encode(Sha256::digest(&entry.data)) != *expected {
+            return Err(BackupError::Checksum(entry.path.clone()));
+        }
+    }
+    if let Some(missing) = manifest
+        .files
+        .keys()
+        .find(|path| !entries.iter().any(|e| e.path == **path))
+    {
+        return Err(BackupError::InvalidManifest(format!("{missing} is absent")));
+    }
+
+    Ok((manifest, entries))
+}
+
+fn write(multibeacon: &Path, manifest: &Manifest, entries: &[Entry]) -> Result<(), BackupError> {
+    for entry in entries {
+        let path = Path::new(&entry.path);
+        let mut dir = multibeacon.to_path_buf();
+        for component in path.parent().into_iter().flat_map(Path::components) {
+            dir.push(component);
+            if !dir.try_exists()? {
+                std::fs::create_dir(&dir)?;
+                set_dir_mode(&dir, RESTORED_DIR_PERM)?;
+            }
+        }
+        let mut f = File::create(multibeacon.join(path))?;
+        set_file_mode(&f, restored_mode(entry))?;
+        f.write_all(&entry.data)?;
+    }
+    for id in &manifest.beacon_ids {
+        let store = FileStore {
+            beacon_path: multibeacon.join(id),
+        };
+        store.complete_layout()?;
+        store.validate()?;
+    }
+
+    Ok(())
+}
+
+/// Recursively collects files of the beacon folder, chain database is skipped.
+fn collect(dir: &Path, prefix: &str, entries: &mut Vec<Entry>) -> Result<(), BackupError> {
+    let mut paths = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<PathBuf>, _>>()?;
+    paths.sort();
+    for path in paths {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let relative = format!("{prefix}/{name}");
+        let metadata = std::fs::metadata(&path)?;
+        if metadata.is_dir() {
+            // Chain database is located at `<beacon_id>/db`.
+            if !prefix.contains('/') && name == DB_DIR {
+                continue;
+            }
+            collect(&path, &relative, entries)?;
+        } else {
+            entries.push(Entry {
+                path: relative,
+                mode: file_mode(&metadata),
+                data: std::fs::read(&path)?,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns `true` if the path has no root, parent or current folder components.
+fn is_relative(path: &Path) -> bool {
+    path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+fn is_file_name(name: &str) -> bool {
+    let path = Path::new(name);
+    is_relative(path) && path.components().count() == 1
+}
+
+fn is_private(path: &str) -> bool {
+    path.ends_with(PRIVATE_ID_FILE) || path.ends_with(PRIVATE_SHARE_FILE)
+}
+
+/// Returns permission of restored file, mode of the archive entry is only narrowed.
+fn restored_mode(entry: &Entry) -> u32 {
+    if is_private(&entry.path) {
+        PRIVATE_PERM
+    } else {
+        entry.mode & PUBLIC_PERM
+    }
+}
+
+#[cfg(unix)]
+fn file_mode(metadata: &std::fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o777
+}
+
+#[cfg(not(unix))]
+fn file_mode(_metadata: &std::fs::Metadata) -> u32 {
+    ARCHIVE_PERM
+}
+
+fn encrypt(data: &[u8], passphrase: &str) -> std::io::Result<Vec<u8>> {
+    let encryptor = age::Encryptor::with_user_passphrase(SecretString::from(passphrase.to_owned()));
+    let mut out = vec![];
+    let mut writer = encryptor.wrap_output(&mut out)?;
+    writer.write_all(data)?;
+    writer.finish()?;
+
+    Ok(out)
+}
+
+fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>, age::DecryptError> {
+    let identity = age::scrypt::Identity::new(SecretString::from(passphrase.to_owned()));
+    let mut reader =
+        age::Decryptor::new(data)?.decrypt(std::iter::once(&identity as &dyn age::Identity))?;
+    let mut out = vec![];
+    reader.read_to_end(&mut out)?;
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::keys::Pair;
+    use crate::net::utils::Address;
+    use energon::drand::schemes::DefaultScheme;
+    use energon::kyber::dkg::DistKeyShare;
+
+    #[test]
+    fn backup_and_restore() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let source = temp_dir.path().join("source").display().to_string();
+        let target = temp_dir.path().join("target").display().to_string();
+        let archive = temp_dir.path().join("backup.tar.zst");
+
+        let store = FileStore::new_checked(&source, "some_id").unwrap();
+        let pair: Pair<DefaultScheme> = Pair::generate(Address::default()).unwrap();
+        store.save_key_pair(&pair).unwrap();
+        store
+            .save_share(&DistKeyShare::<DefaultScheme>::default())
+            .unwrap();
+        std::fs::create_dir(store.beacon_path.join("dkg")).unwrap();
+        std::fs::write(store.beacon_path.join("dkg/finished.toml"), "epoch = 1").unwrap();
+        std::fs::write(store.chain_store_path().join("chain.db"), "beacons").unwrap();
+
+        let multibeacon = store.beacon_path.parent().unwrap();
+        let manifest = create(multibeacon, &[], &archive, Some("secret")).unwrap();
+        assert_eq!(manifest.beacon_ids, ["some_id"]);
+        assert!(manifest.files.contains_key("some_id/dkg/finished.toml"));
+        assert!(!manifest.files.contains_key("some_id/db/chain.db"));
+        // Existing archive is never overwritten.
+        assert!(create(multibeacon, &[], &archive, None).is_err());
+
+        assert!(matches!(
+            restore(&archive, &target, None),
+            Err(BackupError::PassphraseRequired)
+        ));
+        assert!(matches!(
+            restore(&archive, &target, Some("wrong")),
+            Err(BackupError::Decrypt(..))
+        ));
+        assert_eq!(
+            restore(&archive, &target, Some("secret")).unwrap(),
+            manifest
+        );
+
+        let restored = FileStore {
+            beacon_path: absolute_path(&target)
+                .unwrap()
+                .join(MULTIBEACON_DIR)
+                .join("some_id"),
+        };
+        assert_eq!(
+            restored.load_key_pair_toml().unwrap().private(),
+            store.load_key_pair_toml().unwrap().private()
+        );
+        assert!(restored.load_share::<DefaultScheme>().is_ok());
+        assert!(restored.chain_store_path().exists());
+        // Restored beacon ids are never overwritten.
+        assert!(matches!(
+            restore(&archive, &target, Some("secret")),
+            Err(BackupError::FileStore(FileStoreError::FileAlreadyExists(_)))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restore_narrows_archived_modes() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let source = temp_dir.path().join("source").display().to_string();
+        let target = temp_dir.path().join("target").display().to_string();
+        let archive = temp_dir.path().join("backup.tar.zst");
+
+        let store = FileStore::new_checked(&source, "some_id").unwrap();
+        let pair: Pair<DefaultScheme> = Pair::generate(Address::default()).unwrap();
+        store.save_key_pair(&pair).unwrap();
+        store
+            .save_share(&DistKeyShare::<DefaultScheme>::default())
+            .unwrap();
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        let open = std::fs::Permissions::from_mode(0o777);
+        for path in [store.private_share_file(), store.public_id_file()] {
+            std::fs::set_permissions(path, open.clone()).unwrap();
+        }
+
+        create(store.beacon_path.parent().unwrap(), &[], &archive, None).unwrap();
+        restore(&archive, &target, None).unwrap();
+
+        let restored = FileStore {
+            beacon_path: absolute_path(&target)
+                .unwrap()
+                .join(MULTIBEACON_DIR)
+                .join("some_id"),
+        };
+        assert_eq!(mode(&restored.private_share_file()), PRIVATE_PERM);
+        assert_eq!(mode(&restored.public_id_file()), PUBLIC_PERM);
+        assert_eq!(mode(&restored.beacon_path), RESTORED_DIR_PERM);
+    }
+
+    #[test]
+    fn output_path_is_inside_base_folder() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = temp_dir.path();
+
+        assert_eq!(
+            output_path(base, "backup.tar.zst").unwrap(),
+            base.join(BACKUPS_DIR).join("backup.tar.zst")
+        );
+        assert!(base.join(BACKUPS_DIR).is_dir());
+        for name in ["../backup.tar.zst", "/tmp/backup.tar.zst", "", ".."] {
+            assert!(matches!(
+                output_path(base, name),
+                Err(BackupError::InvalidOutput(_))
+            ));
+        }
+    }
+}
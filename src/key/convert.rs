@@ -1,8 +1,11 @@
 //! Conversion between inner generic types and their raw representation
 
+use super::group::Group;
 use super::keys::Identity;
 use super::Scheme;
+use crate::protobuf::drand::GroupPacket;
 use crate::protobuf::drand::IdentityResponse;
+use crate::protobuf::drand::Metadata;
 
 use energon::backends::error::BackendsError;
 use energon::traits::Affine;
@@ -93,3 +96,41 @@ impl<S: Scheme> TryFrom<&Identity<S>> for crate::transport::dkg::Participant {
         })
     }
 }
+
+// Group<S> -> proto::GroupPacket
+impl<S: Scheme> TryFrom<&Group<S>> for GroupPacket {
+    type Error = PointSerDeError;
+
+    fn try_from(group: &Group<S>) -> Result<Self, Self::Error> {
+        let mut nodes = Vec::with_capacity(group.nodes.len());
+        for node in &group.nodes {
+            let identity = crate::transport::drand::Identity::try_from(node.public())?;
+            nodes.push(crate::protobuf::drand::Node {
+                public: Some(identity.into()),
+                index: node.index(),
+            });
+        }
+        let mut dist_key = Vec::with_capacity(group.dist_key.commits().len());
+        for commit in group.dist_key.commits() {
+            dist_key.push(
+                commit
+                    .serialize()
+                    .map_err(PointSerDeError::KeyPoint)?
+                    .into(),
+            );
+        }
+
+        Ok(Self {
+            nodes,
+            threshold: group.threshold,
+            period: group.period.get_value(),
+            genesis_time: group.genesis_time,
+            transition_time: group.transition_time,
+            genesis_seed: group.genesis_seed.clone(),
+            dist_key,
+            catchup_period: group.catchup_period.get_value(),
+            scheme_id: S::ID.to_string(),
+            metadata: Some(Metadata::with_id(group.beacon_id.clone())),
+        })
+    }
+}
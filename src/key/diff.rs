@@ -0,0 +1,325 @@
+//! Comparison of group files, used by `drand util group-diff` to review outcome of a reshare.
+//!
+//! Both files are fully decoded with the scheme of the group, so a malformed group is reported
+//! before it is distributed. Members are matched by public key: a member with the same key and
+//! another address or index is reported as changed rather than removed and added.
+use super::group::Group;
+use super::toml::Toml;
+use super::Hash;
+use super::Scheme;
+
+use energon::drand::schemes::DefaultScheme;
+use energon::drand::schemes::SigsOnG1Scheme;
+use energon::drand::schemes::UnchainedScheme;
+use energon::traits::Affine;
+use serde_json::json;
+use std::fmt::Display;
+use std::path::Path;
+use std::path::PathBuf;
+use toml_edit::DocumentMut;
+
+#[derive(thiserror::Error, Debug)]
+pub enum DiffError {
+    #[error("failed to read {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+    #[error("{0} is not a valid group file")]
+    InvalidGroup(PathBuf),
+    #[error("{0}: unknown scheme '{1}'")]
+    UnknownScheme(PathBuf, String),
+    #[error("groups belong to different chains: {0} differs")]
+    DifferentChain(&'static str),
+}
+
+/// Member of a group as written in the group file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub index: u32,
+    pub address: String,
+    /// Hex encoded public key.
+    pub key: String,
+}
+
+/// Scheme independent view of a decoded group.
+struct Summary {
+    beacon_id: String,
+    scheme: &'static str,
+    threshold: u32,
+    period: String,
+    genesis_time: u64,
+    genesis_seed: Vec<u8>,
+    transition_time: u64,
+    /// Hex encoded distributed public key, empty if the group has not ran a DKG yet.
+    public_key: String,
+    hash: String,
+    members: Vec<Member>,
+}
+
+impl Summary {
+    fn new<S: Scheme>(doc: &DocumentMut) -> Option<Self> {
+        let group: Group<S> = Toml::toml_decode(doc)?;
+        let public_key = match group.dist_key.commits().first() {
+            Some(key) => hex::encode(key.serialize().ok()?),
+            None => String::new(),
+        };
+        let mut members = Vec::with_capacity(group.nodes.len());
+        for node in group.nodes() {
+            members.push(Member {
+                index: node.index(),
+                address: node.public().address().to_string(),
+                key: hex::encode(node.public().key().serialize().ok()?),
+            });
+        }
+
+        Some(Self {
+            hash: hex::encode(group.hash()),
+            beacon_id: group.beacon_id,
+            scheme: S::ID,
+            threshold: group.threshold,
+            period: group.period.to_string(),
+            genesis_time: group.genesis_time,
+            genesis_seed: group.genesis_seed,
+            transition_time: group.transition_time,
+            public_key,
+            members,
+        })
+    }
+
+    fn load(path: &Path) -> Result<Self, DiffError> {
+        let content =
+            std::fs::read_to_string(path).map_err(|err| DiffError::Io(path.into(), err))?;
+        let invalid = || DiffError::InvalidGroup(path.into());
+        let doc: DocumentMut = content.parse().map_err(|_| invalid())?;
+        let scheme = doc
+            .get("SchemeID")
+            .and_then(|id| id.as_str())
+            .ok_or_else(invalid)?;
+
+        match scheme {
+            DefaultScheme::ID => Self::new::<DefaultScheme>(&doc),
+            UnchainedScheme::ID => Self::new::<UnchainedScheme>(&doc),
+            SigsOnG1Scheme::ID => Self::new::<SigsOnG1Scheme>(&doc),
+            _ => return Err(DiffError::UnknownScheme(path.into(), scheme.into())),
+        }
+        .ok_or_else(invalid)
+    }
+}
+
+/// Changes between an old and a new group of the same chain.
+#[derive(Debug, PartialEq)]
+pub struct GroupDiff {
+    pub beacon_id: String,
+    pub scheme: &'static str,
+    /// Old and new values.
+    pub threshold: (u32, u32),
+    pub period: (String, String),
+    pub transition_time: (u64, u64),
+    pub public_key: (String, String),
+    pub hash: (String, String),
+    pub added: Vec<Member>,
+    pub removed: Vec<Member>,
+    /// Members with the same key and another address or index.
+    pub changed: Vec<(Member, Member)>,
+    /// Amount of members present in both groups without changes.
+    pub unchanged: usize,
+}
+
+impl GroupDiff {
+    /// Returns true if the groups are identical.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.hash.0 == self.hash.1
+            && self.period.0 == self.period.1
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let member = |m: &Member| json!({"index": m.index, "address": m.address, "key": m.key});
+        let pair = |old, new| json!({"old": old, "new": new});
+        json!({
+            "beacon_id": self.beacon_id,
+            "scheme": self.scheme,
+            "threshold": pair(json!(self.threshold.0), json!(self.threshold.1)),
+            "period": pair(json!(self.period.0), json!(self.period.1)),
+            "transition_time": pair(json!(self.transition_time.0), json!(self.transition_time.1)),
+            "public_key": pair(json!(self.public_key.0), json!(self.public_key.1)),
+            "hash": pair(json!(self.hash.0), json!(self.hash.1)),
+            "added": self.added.iter().map(member).collect::<Vec<_>>(),
+            "removed": self.removed.iter().map(member).collect::<Vec<_>>(),
+            "changed": self
+                .changed
+                .iter()
+                .map(|(old, new)| pair(member(old), member(new)))
+                .collect::<Vec<_>>(),
+            "unchanged": self.unchanged,
+        })
+    }
+}
+
+/// Writes `old -> new`, or a single value if it is not changed.
+fn field<T: Display + PartialEq>(
+    f: &mut std::fmt::Formatter<'_>,
+    name: &str,
+    (old, new): (&T, &T),
+) -> std::fmt::Result {
+    if old == new {
+        writeln!(f, "{name:<17}{old}")
+    } else {
+        writeln!(f, "{name:<17}{old} -> {new}")
+    }
+}
+
+impl Display for GroupDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<17}{} ({})",
+            "beacon id:", self.beacon_id, self.scheme
+        )?;
+        field(f, "threshold:", (&self.threshold.0, &self.threshold.1))?;
+        field(f, "period:", (&self.period.0, &self.period.1))?;
+        field(
+            f,
+            "transition time:",
+            (&self.transition_time.0, &self.transition_time.1),
+        )?;
+        let key = |k: &String| {
+            if k.is_empty() {
+                "none".to_string()
+            } else {
+                k.clone()
+            }
+        };
+        field(
+            f,
+            "public key:",
+            (&key(&self.public_key.0), &key(&self.public_key.1)),
+        )?;
+        field(f, "group hash:", (&self.hash.0, &self.hash.1))?;
+        write!(
+            f,
+            "{:<17}{} unchanged, {} added, {} removed, {} changed",
+            "members:",
+            self.unchanged,
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )?;
+        for m in &self.added {
+            write!(f, "\n  + {} {} {}", m.index, m.address, m.key)?;
+        }
+        for m in &self.removed {
+            write!(f, "\n  - {} {} {}", m.index, m.address, m.key)?;
+        }
+        for (old, new) in &self.changed {
+            write!(
+                f,
+                "\n  ~ {} {} -> {} {} {}",
+                old.index, old.address, new.index, new.address, new.key
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Compares group files at `old` and `new`.
+pub fn diff_files(old: &Path, new: &Path) -> Result<GroupDiff, DiffError> {
+    diff(Summary::load(old)?, Summary::load(new)?)
+}
+
+fn diff(old: Summary, new: Summary) -> Result<GroupDiff, DiffError> {
+    if old.beacon_id != new.beacon_id {
+        return Err(DiffError::DifferentChain("beacon id"));
+    }
+    if old.scheme != new.scheme {
+        return Err(DiffError::DifferentChain("scheme"));
+    }
+    if old.genesis_time != new.genesis_time {
+        return Err(DiffError::DifferentChain("genesis time"));
+    }
+    if old.genesis_seed != new.genesis_seed {
+        return Err(DiffError::DifferentChain("genesis seed"));
+    }
+
+    let mut removed = vec![];
+    let mut changed = vec![];
+    let mut unchanged = 0;
+    for member in &old.members {
+        match new.members.iter().find(|m| m.key == member.key) {
+            Some(m) if m == member => unchanged += 1,
+            Some(m) => changed.push((member.clone(), m.clone())),
+            None => removed.push(member.clone()),
+        }
+    }
+    let added = new
+        .members
+        .iter()
+        .filter(|m| !old.members.iter().any(|o| o.key == m.key))
+        .cloned()
+        .collect();
+
+    Ok(GroupDiff {
+        beacon_id: new.beacon_id,
+        scheme: new.scheme,
+        threshold: (old.threshold, new.threshold),
+        period: (old.period, new.period),
+        transition_time: (old.transition_time, new.transition_time),
+        public_key: (old.public_key, new.public_key),
+        hash: (old.hash, new.hash),
+        added,
+        removed,
+        changed,
+        unchanged,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::toml::tests::toml_samples;
+
+    #[test]
+    fn reshare_diff() {
+        let old_doc: DocumentMut = toml_samples::group().parse().unwrap();
+        let old = Summary::new::<DefaultScheme>(&old_doc).unwrap();
+        assert!(diff(Summary::new::<DefaultScheme>(&old_doc).unwrap(), old)
+            .unwrap()
+            .is_empty());
+
+        // Last node leaves, the first one moves to another address.
+        let mut new_doc = old_doc.clone();
+        new_doc["Threshold"] = toml_edit::value(3);
+        new_doc["TransitionTime"] = toml_edit::value(1736058815);
+        let nodes = new_doc["Nodes"].as_array_of_tables_mut().unwrap();
+        nodes.remove(5);
+        nodes.get_mut(0).unwrap()["Address"] = toml_edit::value("127.0.0.1:36024");
+
+        let diff = diff(
+            Summary::new::<DefaultScheme>(&old_doc).unwrap(),
+            Summary::new::<DefaultScheme>(&new_doc).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(diff.threshold, (4, 3));
+        assert_eq!(diff.transition_time, (1736058215, 1736058815));
+        assert_eq!(diff.public_key.0, diff.public_key.1);
+        assert_ne!(diff.hash.0, diff.hash.1);
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].index, 5);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].1.address, "127.0.0.1:36024");
+        assert_eq!(diff.unchanged, 4);
+        assert_eq!(diff.to_json()["threshold"]["new"], 3);
+
+        let mut other_chain = new_doc;
+        other_chain["ID"] = toml_edit::value("other");
+        assert!(matches!(
+            super::diff(
+                Summary::new::<DefaultScheme>(&old_doc).unwrap(),
+                Summary::new::<DefaultScheme>(&other_chain).unwrap()
+            ),
+            Err(DiffError::DifferentChain("beacon id"))
+        ));
+    }
+}
@@ -22,7 +22,29 @@ impl<S: Scheme> Pair<S> {
 
     /// Returns a freshly created private / public key pair.
     pub fn generate(address: Address) -> Result<Self> {
-        let private = S::Scalar::random();
+        Self::from_private(address, S::Scalar::random())
+    }
+
+    /// Returns a key pair derived deterministically from `seed`, for reproducible test setups only.
+    ///
+    /// Private key is the first valid scalar of `sha256(seed || counter)` for increasing counter.
+    #[cfg(any(test, feature = "insecure"))]
+    pub fn from_insecure_seed(address: Address, seed: &[u8]) -> Result<Self> {
+        use sha2::Digest;
+
+        for counter in 0..=u8::MAX {
+            let mut hasher = sha2::Sha256::new();
+            hasher.update(seed);
+            hasher.update([counter]);
+            if let Ok(private) = S::Scalar::from_bytes_be(&hasher.finalize()) {
+                return Self::from_private(address, private);
+            }
+        }
+
+        anyhow::bail!("failed to derive private key from seed")
+    }
+
+    fn from_private(address: Address, private: S::Scalar) -> Result<Self> {
         let key = S::sk_to_pk(&private);
         let mut msg = S::ID.as_bytes().to_vec();
         msg.extend_from_slice(key.hash()?.as_slice());
@@ -143,3 +165,23 @@ mod default_impl {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::dkg::Participant;
+    use energon::drand::schemes::DefaultScheme;
+
+    #[test]
+    fn insecure_seed_is_deterministic() {
+        let address = Address::precheck("127.0.0.1:1111").unwrap();
+        let a = Pair::<DefaultScheme>::from_insecure_seed(address.clone(), b"seed").unwrap();
+        let b = Pair::<DefaultScheme>::from_insecure_seed(address.clone(), b"seed").unwrap();
+        let c = Pair::<DefaultScheme>::from_insecure_seed(address, b"other").unwrap();
+
+        assert_eq!(a, b);
+        assert_ne!(a.public_identity(), c.public_identity());
+        let participant = Participant::try_from(a.public_identity()).unwrap();
+        assert!(participant.is_valid_signature::<DefaultScheme>());
+    }
+}
@@ -0,0 +1,395 @@
+//! Compatibility of node folders with golang implementation.
+//!
+//! Key pair, group and share files share locations, names and TOML fields with golang
+//! implementation and are readable both ways. The rest of the layout is specific to each
+//! implementation and is ignored by the other one:
+//! - chain database: golang `db/drand.db` is not read, the chain is synced from peers after migration.
+//! - DKG state: golang `dkg.db` is shared across beacon ids and is not read, the state of the last
+//!   completed DKG is reconstructed from the group file, see [`DkgStore::migrate`].
+//!
+//! Migration is one-way: DKG state of this implementation is not written back into `dkg.db`, so
+//! golang implementation does not see DKGs completed after migration.
+use super::group::Group;
+use super::keys::Pair;
+use super::store::absolute_path;
+use super::store::FileStore;
+use super::store::FileStoreError;
+use super::store::MULTIBEACON_DIR;
+use super::toml::PairToml;
+use super::toml::Toml;
+use super::Scheme;
+
+use crate::dkg::store::DkgStore;
+
+use energon::drand::schemes::DefaultScheme;
+use energon::drand::schemes::SigsOnG1Scheme;
+use energon::drand::schemes::UnchainedScheme;
+use energon::kyber::dkg::DistKeyShare;
+use std::fmt::Display;
+
+/// DKG database of golang implementation, located at base folder.
+const GO_DKG_DB: &str = "dkg.db";
+/// Chain database of golang implementation, located at chain store folder.
+const GO_CHAIN_DB: &str = "drand.db";
+
+#[derive(Debug, PartialEq)]
+pub enum Severity {
+    Ok,
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ok => write!(f, "ok"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+pub struct Finding {
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Migration findings of a single beacon id.
+pub struct BeaconReport {
+    pub beacon_id: String,
+    pub findings: Vec<Finding>,
+}
+
+impl BeaconReport {
+    fn new(beacon_id: &str) -> Self {
+        Self {
+            beacon_id: beacon_id.to_string(),
+            findings: vec![],
+        }
+    }
+
+    fn push(&mut self, severity: Severity, message: impl Into<String>) {
+        self.findings.push(Finding {
+            severity,
+            message: message.into(),
+        });
+    }
+
+    /// Beacon id can be loaded if there are no errors.
+    pub fn is_ready(&self) -> bool {
+        !self.findings.iter().any(|f| f.severity == Severity::Error)
+    }
+}
+
+impl Display for BeaconReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "[{}]", self.beacon_id)?;
+        for finding in &self.findings {
+            writeln!(f, "  {}: {}", finding.severity, finding.message)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that beacon ids at given folder can be loaded, optionally limited to a single `beacon_id`.
+///
+/// The folder is not modified. If `epoch` is provided, missing DKG state of completed beacon ids
+/// is checked to be reconstructable for this epoch, see [`migrate_folder`].
+pub fn check_folder(
+    folder: &str,
+    beacon_id: Option<&str>,
+    epoch: Option<u32>,
+) -> Result<Vec<BeaconReport>, FileStoreError> {
+    check(folder, beacon_id, epoch, false)
+}
+
+/// Reconstructs missing DKG state of `beacon_id` for the last completed `epoch` from the group file.
+pub fn migrate_folder(
+    folder: &str,
+    beacon_id: &str,
+    epoch: u32,
+) -> Result<BeaconReport, FileStoreError> {
+    check(folder, Some(beacon_id), Some(epoch), true)?
+        .pop()
+        .ok_or(FileStoreError::BeaconNotFound)
+}
+
+fn check(
+    folder: &str,
+    beacon_id: Option<&str>,
+    epoch: Option<u32>,
+    apply: bool,
+) -> Result<Vec<BeaconReport>, FileStoreError> {
+    let base = absolute_path(folder)?;
+    let multibeacon = base.join(MULTIBEACON_DIR);
+    if !multibeacon.try_exists()? {
+        return Err(FileStoreError::FileNotFound(multibeacon));
+    }
+    let has_go_dkg_db = base.join(GO_DKG_DB).try_exists()?;
+
+    let mut ids = vec![];
+    for entry in std::fs::read_dir(&multibeacon)?.flatten() {
+        if let Some(id) = entry.file_name().to_str() {
+            if entry.path().is_dir() && beacon_id.is_none_or(|b| b == id) {
+                ids.push(id.to_string());
+            }
+        }
+    }
+    if ids.is_empty() {
+        return Err(FileStoreError::BeaconNotFound);
+    }
+    ids.sort();
+
+    let reports = ids
+        .iter()
+        .map(|id| {
+            let fs = FileStore {
+                beacon_path: multibeacon.join(id),
+            };
+            let mut report = BeaconReport::new(id);
+            check_beacon(&fs, has_go_dkg_db, epoch, apply, &mut report);
+            report
+        })
+        .collect();
+
+    Ok(reports)
+}
+
+fn check_beacon(
+    fs: &FileStore,
+    has_go_dkg_db: bool,
+    epoch: Option<u32>,
+    apply: bool,
+    r: &mut BeaconReport,
+) {
+    if let Err(err) = fs.validate() {
+        r.push(Severity::Error, format!("key pair: {err}"));
+        return;
+    }
+    let pair = match fs.load_key_pair_toml() {
+        Ok(pair) => pair,
+        Err(err) => {
+            r.push(Severity::Error, format!("key pair: {err}"));
+            return;
+        }
+    };
+
+    match pair.get_scheme_id() {
+        Some(DefaultScheme::ID) => {
+            check_scheme::<DefaultScheme>(fs, &pair, has_go_dkg_db, epoch, apply, r);
+        }
+        Some(UnchainedScheme::ID) => {
+            check_scheme::<UnchainedScheme>(fs, &pair, has_go_dkg_db, epoch, apply, r);
+        }
+        Some(SigsOnG1Scheme::ID) => {
+            check_scheme::<SigsOnG1Scheme>(fs, &pair, has_go_dkg_db, epoch, apply, r);
+        }
+        _ => r.push(
+            Severity::Error,
+            FileStoreError::InvalidPairSchemes.to_string(),
+        ),
+    }
+
+    let chain_store = fs.chain_store_path();
+    if chain_store.join(GO_CHAIN_DB).exists() {
+        r.push(
+            Severity::Warning,
+            "golang chain database is not read, the chain will be synced from peers",
+        );
+    } else if !chain_store.exists() {
+        r.push(
+            Severity::Ok,
+            "chain database folder will be created on start",
+        );
+    }
+}
+
+fn check_scheme<S: Scheme>(
+    fs: &FileStore,
+    pair: &PairToml,
+    has_go_dkg_db: bool,
+    epoch: Option<u32>,
+    apply: bool,
+    r: &mut BeaconReport,
+) {
+    let Some(pair) = Pair::<S>::toml_decode(pair) else {
+        r.push(Severity::Error, "key pair: failed to decode");
+        return;
+    };
+    r.push(
+        Severity::Ok,
+        format!(
+            "key pair: address {}, scheme {}",
+            pair.public_identity().address(),
+            S::ID
+        ),
+    );
+
+    match fs.is_fresh_run() {
+        Ok(true) => {
+            r.push(Severity::Ok, "group: not found, node is waiting for DKG");
+            return;
+        }
+        Ok(false) => (),
+        Err(err) => {
+            r.push(Severity::Error, format!("group: {err}"));
+            return;
+        }
+    }
+
+    let (group, share) = match (fs.load_group::<S>(), fs.load_share::<S>()) {
+        (Ok(group), Ok(share)) => (group, share),
+        (Err(err), _) => {
+            r.push(Severity::Error, format!("group: {err}"));
+            return;
+        }
+        (_, Err(err)) => {
+            r.push(Severity::Error, format!("share: {err}"));
+            return;
+        }
+    };
+    if !group
+        .nodes
+        .iter()
+        .any(|n| n.public().key() == pair.public_identity().key())
+    {
+        r.push(Severity::Error, "group: node key is not part of the group");
+        return;
+    }
+    r.push(
+        Severity::Ok,
+        format!(
+            "group: {} nodes, threshold {}, share index {}",
+            group.nodes.len(),
+            group.threshold,
+            share.pri_share.index()
+        ),
+    );
+
+    check_dkg_state(fs, group, share, has_go_dkg_db, epoch, apply, r);
+}
+
+fn check_dkg_state<S: Scheme>(
+    fs: &FileStore,
+    group: Group<S>,
+    share: DistKeyShare<S>,
+    has_go_dkg_db: bool,
+    epoch: Option<u32>,
+    apply: bool,
+    r: &mut BeaconReport,
+) {
+    if DkgStore::is_initialized(&fs.beacon_path) {
+        r.push(Severity::Ok, "dkg state: found");
+        return;
+    }
+
+    match epoch {
+        Some(epoch) if apply => match DkgStore::migrate(&fs.beacon_path, group, share, epoch) {
+            Ok(()) => r.push(
+                Severity::Ok,
+                format!("dkg state: reconstructed from the group file for epoch {epoch}"),
+            ),
+            Err(err) => r.push(Severity::Error, format!("dkg state: {err}")),
+        },
+        Some(epoch) => match DkgStore::check_migrate(group, share, epoch) {
+            Ok(()) => r.push(
+                Severity::Error,
+                format!("dkg state: not found, run `drand util migrate --id {} --epoch {epoch}` to reconstruct it from the group file", r.beacon_id),
+            ),
+            Err(err) => r.push(Severity::Error, format!("dkg state: {err}")),
+        },
+        None => {
+            let source = if has_go_dkg_db {
+                "golang dkg.db is not read"
+            } else {
+                "not found"
+            };
+            r.push(
+                Severity::Error,
+                format!("dkg state: {source}, provide --id and --epoch of the last completed DKG to check its reconstruction"),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::toml::tests::toml_samples;
+
+    /// Creates base folder of golang node for the default beacon id from demo samples.
+    fn golang_folder(base: &std::path::Path) -> String {
+        let beacon = base.join(MULTIBEACON_DIR).join("default");
+        for (file, content) in [
+            ("key/drand_id.private", toml_samples::private_key()),
+            ("key/drand_id.public", toml_samples::identity()),
+            ("groups/drand_group.toml", toml_samples::group()),
+            ("groups/dist_key.private", toml_samples::dist_key()),
+            ("db/drand.db", ""),
+        ] {
+            let path = beacon.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        std::fs::write(base.join(GO_DKG_DB), "").unwrap();
+
+        base.display().to_string()
+    }
+
+    /// Returns sorted paths of all entries below the folder.
+    fn tree(folder: &str) -> Vec<std::path::PathBuf> {
+        fn walk(dir: &std::path::Path, out: &mut Vec<std::path::PathBuf>) {
+            for entry in std::fs::read_dir(dir).unwrap().flatten() {
+                out.push(entry.path());
+                if entry.path().is_dir() {
+                    walk(&entry.path(), out);
+                }
+            }
+        }
+        let mut out = vec![];
+        walk(std::path::Path::new(folder), &mut out);
+        out.sort();
+        out
+    }
+
+    #[test]
+    fn check_is_read_only() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let folder = golang_folder(temp_dir.path());
+        let before = tree(&folder);
+
+        let reports = check_folder(&folder, None, None).unwrap();
+        assert!(reports.len() == 1);
+        assert!(!reports[0].is_ready(), "{}", reports[0]);
+
+        // Reconstruction is checked without writing the state.
+        let reports = check_folder(&folder, Some("default"), Some(1)).unwrap();
+        assert!(!reports[0].is_ready(), "{}", reports[0]);
+        assert!(reports[0].to_string().contains("util migrate"));
+        assert_eq!(tree(&folder), before);
+
+        assert!(matches!(
+            check_folder(&folder, Some("other"), None),
+            Err(FileStoreError::BeaconNotFound)
+        ));
+    }
+
+    #[test]
+    fn migrate_golang_folder() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let folder = golang_folder(temp_dir.path());
+
+        let report = migrate_folder(&folder, "default", 1).unwrap();
+        assert!(report.is_ready(), "{report}");
+        assert!(report
+            .findings
+            .iter()
+            .any(|f| f.message.contains("golang chain database")));
+
+        // Reconstructed state is found by the next check.
+        let reports = check_folder(&folder, None, None).unwrap();
+        assert!(reports[0].is_ready(), "{}", reports[0]);
+        assert!(reports[0].to_string().contains("dkg state: found"));
+    }
+}
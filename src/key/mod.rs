@@ -1,6 +1,9 @@
+pub mod backup;
 mod convert;
+pub mod diff;
 pub mod group;
 pub mod keys;
+pub mod migration;
 pub mod node;
 pub mod store;
 pub mod toml;
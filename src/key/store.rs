@@ -6,30 +6,30 @@ use super::Scheme;
 
 use energon::kyber::dkg::DistKeyShare;
 use std::fs::File;
-use std::fs::Permissions;
 use std::io::Write;
-use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 use std::path::PathBuf;
+use tracing::error;
 use tracing::info;
 
 // Filesystem constants
 const DEFAULT_DIR: &str = ".drand";
-const MULTIBEACON_DIR: &str = "multibeacon";
+pub(super) const MULTIBEACON_DIR: &str = "multibeacon";
 const KEY_DIR: &str = "key";
 const GROUP_DIR: &str = "groups";
-const DB_DIR: &str = "db";
-const PRIVATE_ID_FILE: &str = "drand_id.private";
+const GROUP_HISTORY_DIR: &str = "history";
+pub(super) const DB_DIR: &str = "db";
+pub(super) const PRIVATE_ID_FILE: &str = "drand_id.private";
 const PUBLIC_ID_FILE: &str = "drand_id.public";
-const PRIVATE_SHARE_FILE: &str = "dist_key.private";
+pub(super) const PRIVATE_SHARE_FILE: &str = "dist_key.private";
 const GROUP_FILE: &str = "drand_group.toml";
 
 /// Directories permission
 const DIR_PERM: u32 = 0o740;
 /// Private id permission
-const PRIVATE_PERM: u32 = 0o600;
+pub(super) const PRIVATE_PERM: u32 = 0o600;
 /// Public id permission
-const PUBLIC_PERM: u32 = 0o664;
+pub(super) const PUBLIC_PERM: u32 = 0o664;
 
 #[derive(thiserror::Error, Debug)]
 #[error("file_store: {0}")]
@@ -56,6 +56,8 @@ pub enum FileStoreError {
     ChainStore(#[from] crate::chain::StoreError),
     #[error("dkg_store error: {0}")]
     DkgStore(#[from] crate::dkg::store::DkgStoreError),
+    #[error("point conversion error: {0}")]
+    PointSerDe(#[from] super::PointSerDeError),
 }
 
 /// `FileStore` holds absolute path of `beacon_id` and abstracts the
@@ -111,8 +113,79 @@ impl FileStore {
         Ok(())
     }
 
+    /// Copies beacon id folder from another base folder into `multibeacon` folder of the daemon.
+    ///
+    /// Source beacon id is expected to be stopped, the chain database is copied as is.
+    pub fn import(
+        base_path: &str,
+        multibeacon: &Path,
+        beacon_id: &str,
+    ) -> Result<Self, FileStoreError> {
+        let source = Self {
+            beacon_path: absolute_path(base_path)?
+                .join(MULTIBEACON_DIR)
+                .join(beacon_id),
+        };
+        source.validate()?;
+
+        let beacon_path = multibeacon.join(beacon_id);
+        if beacon_path.try_exists()? {
+            return Err(FileStoreError::FileAlreadyExists(beacon_path));
+        }
+        // Partially copied folder would be rejected by the next import.
+        if let Err(err) = copy_dir(&source.beacon_path, &beacon_path) {
+            if let Err(err) = std::fs::remove_dir_all(&beacon_path) {
+                error!(
+                    "failed to remove partially imported folder {}: {err}",
+                    beacon_path.display()
+                );
+            }
+            return Err(err);
+        }
+        info!(
+            "Imported beacon id folder: {} -> {}",
+            source.beacon_path.display(),
+            beacon_path.display()
+        );
+
+        Ok(Self { beacon_path })
+    }
+
+    /// Removes beacon id folder imported by [`Self::import`] which failed to load.
+    pub fn discard_import(&self) {
+        match std::fs::remove_dir_all(&self.beacon_path) {
+            Ok(()) => info!("Removed imported folder: {}", self.beacon_path.display()),
+            Err(err) => error!(
+                "failed to remove imported folder {}: {err}",
+                self.beacon_path.display()
+            ),
+        }
+    }
+
+    /// Creates beacon sub folders which are absent in stores created by golang implementation.
+    pub fn complete_layout(&self) -> Result<(), FileStoreError> {
+        for dir in [GROUP_DIR, DB_DIR] {
+            let path = self.beacon_path.join(dir);
+            if !path.try_exists()? {
+                new_secure_dir(&path)?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Returns an absolute path to multibeacon folder and non-empty list of pre-validated filestores
     pub fn read_multibeacon_folder(folder: &str) -> Result<(PathBuf, Vec<Self>), FileStoreError> {
+        Self::read_multibeacon_subset(folder, &[])
+    }
+
+    /// Same as [`Self::read_multibeacon_folder`] for given beacon ids only, all ids if `only` is empty.
+    ///
+    /// Other stores are skipped without validation, so they can be repaired offline in the meantime.
+    pub fn read_multibeacon_subset(
+        folder: &str,
+        only: &[String],
+    ) -> Result<(PathBuf, Vec<Self>), FileStoreError> {
         // Check if 'multibeacon' exists
         let base = absolute_path(folder)?;
         let multibeacon = base.join(MULTIBEACON_DIR);
@@ -125,6 +198,10 @@ impl FileStore {
 
         for entry in entries.flatten() {
             if let Some(beacon_id) = entry.file_name().to_str() {
+                if !only.is_empty() && !only.iter().any(|id| id == beacon_id) {
+                    info!("Skipping beacon id {beacon_id}, not listed to be loaded");
+                    continue;
+                }
                 let store = Self {
                     beacon_path: multibeacon.join(beacon_id),
                 };
@@ -132,7 +209,11 @@ impl FileStore {
                 stores.push(store);
             }
         }
-        if stores.is_empty() {
+        if stores.is_empty()
+            || only
+                .iter()
+                .any(|id| !stores.iter().any(|s| s.get_beacon_id() == Some(id)))
+        {
             return Err(FileStoreError::BeaconNotFound);
         }
         info!(
@@ -148,12 +229,12 @@ impl FileStore {
 
         // save private
         let mut f = File::create(self.private_id_file())?;
-        f.set_permissions(Permissions::from_mode(PRIVATE_PERM))?;
+        set_file_mode(&f, PRIVATE_PERM)?;
         f.write_all(pair_toml.private().as_bytes())?;
 
         // save public
         let mut f = File::create(self.public_id_file())?;
-        f.set_permissions(Permissions::from_mode(PUBLIC_PERM))?;
+        set_file_mode(&f, PUBLIC_PERM)?;
         f.write_all(pair_toml.public().as_bytes())?;
 
         println!(
@@ -168,25 +249,52 @@ impl FileStore {
     pub fn save_group<S: Scheme>(&self, group: &Group<S>) -> Result<(), FileStoreError> {
         let group_toml = group.toml_encode().ok_or(FileStoreError::TomlError)?;
         let mut f = File::create(self.group_file())?;
-        f.set_permissions(Permissions::from_mode(PUBLIC_PERM))?;
+        set_file_mode(&f, PUBLIC_PERM)?;
         f.write_all(group_toml.to_string().as_bytes())?;
 
         Ok(())
     }
 
     pub fn load_group<S: Scheme>(&self) -> Result<Group<S>, FileStoreError> {
-        let group_str = std::fs::read_to_string(self.group_file())?;
-        let group: Group<S> =
-            Toml::toml_decode(&group_str.parse().map_err(|_| FileStoreError::TomlError)?)
-                .ok_or(FileStoreError::TomlError)?;
+        read_group(&self.group_file())
+    }
+
+    /// Saves a copy of the group into history, groups of all epochs are kept.
+    pub fn save_group_epoch<S: Scheme>(
+        &self,
+        group: &Group<S>,
+        epoch: u32,
+    ) -> Result<(), FileStoreError> {
+        let history = self.beacon_path.join(GROUP_DIR).join(GROUP_HISTORY_DIR);
+        // History folder is absent for stores created by previous versions.
+        if !history.try_exists()? {
+            new_secure_dir(&history)?;
+        }
+        let group_toml = group.toml_encode().ok_or(FileStoreError::TomlError)?;
+        let mut f = File::create(self.group_epoch_file(epoch))?;
+        set_file_mode(&f, PUBLIC_PERM)?;
+        f.write_all(group_toml.to_string().as_bytes())?;
 
-        Ok(group)
+        Ok(())
+    }
+
+    /// Loads the group used at given epoch, `0` stands for the latest group.
+    pub fn load_group_epoch<S: Scheme>(&self, epoch: u32) -> Result<Group<S>, FileStoreError> {
+        if epoch == 0 {
+            return self.load_group();
+        }
+        let path = self.group_epoch_file(epoch);
+        if !path.try_exists()? {
+            return Err(FileStoreError::FileNotFound(path));
+        }
+
+        read_group(&path)
     }
 
     pub fn save_share<S: Scheme>(&self, share: &DistKeyShare<S>) -> Result<(), FileStoreError> {
         let share_toml = share.toml_encode().ok_or(FileStoreError::TomlError)?;
         let mut f = File::create(self.private_share_file())?;
-        f.set_permissions(Permissions::from_mode(PRIVATE_PERM))?;
+        set_file_mode(&f, PRIVATE_PERM)?;
         f.write_all(share_toml.to_string().as_bytes())?;
 
         Ok(())
@@ -238,7 +346,7 @@ impl FileStore {
         self.beacon_path.join(KEY_DIR).join(PRIVATE_ID_FILE)
     }
 
-    fn public_id_file(&self) -> PathBuf {
+    pub fn public_id_file(&self) -> PathBuf {
         self.beacon_path.join(KEY_DIR).join(PUBLIC_ID_FILE)
     }
 
@@ -246,6 +354,13 @@ impl FileStore {
         self.beacon_path.join(GROUP_DIR).join(GROUP_FILE)
     }
 
+    fn group_epoch_file(&self, epoch: u32) -> PathBuf {
+        self.beacon_path
+            .join(GROUP_DIR)
+            .join(GROUP_HISTORY_DIR)
+            .join(format!("{epoch}.{GROUP_FILE}"))
+    }
+
     pub fn private_share_file(&self) -> PathBuf {
         self.beacon_path.join(GROUP_DIR).join(PRIVATE_SHARE_FILE)
     }
@@ -255,7 +370,7 @@ impl FileStore {
     }
 }
 
-fn absolute_path(base_path: &str) -> Result<PathBuf, FileStoreError> {
+pub(super) fn absolute_path(base_path: &str) -> Result<PathBuf, FileStoreError> {
     let absolute = if Path::new(base_path).is_absolute() {
         PathBuf::from(base_path)
     } else {
@@ -266,9 +381,202 @@ fn absolute_path(base_path: &str) -> Result<PathBuf, FileStoreError> {
     Ok(absolute)
 }
 
-fn new_secure_dir(folder: &PathBuf) -> Result<(), FileStoreError> {
+fn read_group<S: Scheme>(path: &Path) -> Result<Group<S>, FileStoreError> {
+    let group_str = std::fs::read_to_string(path)?;
+    let group: Group<S> =
+        Toml::toml_decode(&group_str.parse().map_err(|_| FileStoreError::TomlError)?)
+            .ok_or(FileStoreError::TomlError)?;
+
+    Ok(group)
+}
+
+pub(super) fn new_secure_dir(folder: &PathBuf) -> Result<(), FileStoreError> {
     std::fs::create_dir(folder)?;
-    std::fs::set_permissions(folder, Permissions::from_mode(DIR_PERM))?;
+    set_dir_mode(folder, DIR_PERM)?;
+
+    Ok(())
+}
+
+/// Recursively copies folder content, permissions of files are preserved.
+fn copy_dir(from: &Path, to: &PathBuf) -> Result<(), FileStoreError> {
+    new_secure_dir(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Sets unix permission bits of the file.
+///
+/// On windows the file is restricted to its owner if `mode` grants nothing to others, otherwise
+/// access is inherited from the parent folder. Owner-only modes fail on other platforms.
+#[cfg(unix)]
+pub(crate) fn set_file_mode(f: &File, mode: u32) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    f.set_permissions(std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(windows)]
+pub(crate) fn set_file_mode(f: &File, mode: u32) -> std::io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use std::os::windows::io::FromRawHandle;
+    use std::os::windows::io::OwnedHandle;
+    use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+    use windows_sys::Win32::Storage::FileSystem::ReOpenFile;
+    use windows_sys::Win32::Storage::FileSystem::FILE_SHARE_DELETE;
+    use windows_sys::Win32::Storage::FileSystem::FILE_SHARE_READ;
+    use windows_sys::Win32::Storage::FileSystem::FILE_SHARE_WRITE;
+    use windows_sys::Win32::Storage::FileSystem::READ_CONTROL;
+    use windows_sys::Win32::Storage::FileSystem::WRITE_DAC;
+
+    if !is_owner_only(mode) {
+        return Ok(());
+    }
+    // Handle of a created file has no right to change its access list.
+    // SAFETY: the original handle is valid for the lifetime of `f`.
+    let handle = unsafe {
+        ReOpenFile(
+            f.as_raw_handle(),
+            WRITE_DAC | READ_CONTROL,
+            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+            0,
+        )
+    };
+    if handle == INVALID_HANDLE_VALUE {
+        return Err(std::io::Error::last_os_error());
+    }
+    // SAFETY: the handle is valid and owned, it is closed on drop.
+    let handle = unsafe { OwnedHandle::from_raw_handle(handle) };
+    restrict_to_owner(&handle, OWNER_FILE_ACL)
+}
+
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn set_file_mode(_f: &File, mode: u32) -> std::io::Result<()> {
+    owner_only_unsupported(mode)
+}
+
+/// Sets unix permission bits of the folder, see [`set_file_mode`]. On windows entries created
+/// in the folder inherit its access list.
+#[cfg(unix)]
+pub(crate) fn set_dir_mode(folder: &Path, mode: u32) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(folder, std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(windows)]
+pub(crate) fn set_dir_mode(folder: &Path, mode: u32) -> std::io::Result<()> {
+    use std::os::windows::fs::OpenOptionsExt;
+    use windows_sys::Win32::Storage::FileSystem::FILE_FLAG_BACKUP_SEMANTICS;
+    use windows_sys::Win32::Storage::FileSystem::READ_CONTROL;
+    use windows_sys::Win32::Storage::FileSystem::WRITE_DAC;
+
+    if !is_owner_only(mode) {
+        return Ok(());
+    }
+    // Folders are opened by handle only with backup semantics.
+    let dir = std::fs::OpenOptions::new()
+        .access_mode(WRITE_DAC | READ_CONTROL)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(folder)?;
+    restrict_to_owner(&dir, OWNER_DIR_ACL)
+}
+
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn set_dir_mode(_folder: &Path, mode: u32) -> std::io::Result<()> {
+    owner_only_unsupported(mode)
+}
+
+/// Returns true if permission bits grant nothing to others, windows has no owner group.
+#[cfg(not(unix))]
+fn is_owner_only(mode: u32) -> bool {
+    mode & 0o007 == 0
+}
+
+/// Full access of the owner only, entries of the parent folder are not inherited.
+#[cfg(windows)]
+const OWNER_FILE_ACL: &str = "D:P(A;;FA;;;OW)";
+/// Same as [`OWNER_FILE_ACL`], inherited by files and folders created in the folder.
+#[cfg(windows)]
+const OWNER_DIR_ACL: &str = "D:P(A;OICI;FA;;;OW)";
+
+/// Replaces access list of the object by the protected list in SDDL format.
+#[cfg(windows)]
+fn restrict_to_owner(
+    object: &impl std::os::windows::io::AsRawHandle,
+    sddl: &str,
+) -> std::io::Result<()> {
+    use windows_sys::Win32::Foundation::LocalFree;
+    use windows_sys::Win32::Security::Authorization::ConvertStringSecurityDescriptorToSecurityDescriptorW;
+    use windows_sys::Win32::Security::Authorization::SetSecurityInfo;
+    use windows_sys::Win32::Security::Authorization::SDDL_REVISION_1;
+    use windows_sys::Win32::Security::Authorization::SE_FILE_OBJECT;
+    use windows_sys::Win32::Security::GetSecurityDescriptorDacl;
+    use windows_sys::Win32::Security::ACL;
+    use windows_sys::Win32::Security::DACL_SECURITY_INFORMATION;
+    use windows_sys::Win32::Security::PROTECTED_DACL_SECURITY_INFORMATION;
+    use windows_sys::Win32::Security::PSECURITY_DESCRIPTOR;
+
+    let sddl: Vec<u16> = sddl.encode_utf16().chain(Some(0)).collect();
+    let mut descriptor: PSECURITY_DESCRIPTOR = std::ptr::null_mut();
+    let mut dacl: *mut ACL = std::ptr::null_mut();
+    let (mut present, mut defaulted) = (0, 0);
+    // SAFETY: `sddl` is null terminated, the descriptor is allocated by the system and freed
+    // once the access list is applied, `dacl` points into the descriptor.
+    unsafe {
+        if ConvertStringSecurityDescriptorToSecurityDescriptorW(
+            sddl.as_ptr(),
+            SDDL_REVISION_1,
+            &mut descriptor,
+            std::ptr::null_mut(),
+        ) == 0
+        {
+            return Err(std::io::Error::last_os_error());
+        }
+        let result = if GetSecurityDescriptorDacl(
+            descriptor,
+            &mut present,
+            &mut dacl,
+            &mut defaulted,
+        ) == 0
+        {
+            Err(std::io::Error::last_os_error())
+        } else {
+            match SetSecurityInfo(
+                object.as_raw_handle(),
+                SE_FILE_OBJECT,
+                DACL_SECURITY_INFORMATION | PROTECTED_DACL_SECURITY_INFORMATION,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                dacl,
+                std::ptr::null(),
+            ) {
+                0 => Ok(()),
+                code => Err(i32::try_from(code).map_or_else(
+                    |_| std::io::Error::other(format!("access list is not set: {code}")),
+                    std::io::Error::from_raw_os_error,
+                )),
+            }
+        };
+        LocalFree(descriptor);
+        result
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn owner_only_unsupported(mode: u32) -> std::io::Result<()> {
+    if is_owner_only(mode) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "access of private files can not be restricted on this platform",
+        ));
+    }
 
     Ok(())
 }
@@ -325,7 +633,158 @@ mod tests {
         assert!(share == loaded_share);
     }
 
+    #[test]
+    fn import_beacon_folder() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let source = temp_dir.path().join("source").display().to_string();
+        let target = temp_dir.path().join("target").display().to_string();
+
+        let store = FileStore::new_checked(&source, "some_id").unwrap();
+        let pair: Pair<DefaultScheme> = Pair::generate(Address::default()).unwrap();
+        store.save_key_pair(&pair).unwrap();
+
+        // Target daemon folder with another beacon id.
+        let other = FileStore::new_checked(&target, "other_id").unwrap();
+        let multibeacon = other.beacon_path.parent().unwrap();
+
+        let imported = FileStore::import(&source, multibeacon, "some_id").unwrap();
+        imported.validate().unwrap();
+        assert_perm(imported.private_id_file(), PRIVATE_PERM);
+        assert_eq!(
+            imported.load_key_pair_toml().unwrap().public(),
+            store.load_key_pair_toml().unwrap().public()
+        );
+
+        // Loaded folders are never overwritten.
+        assert!(matches!(
+            FileStore::import(&source, multibeacon, "some_id"),
+            Err(FileStoreError::FileAlreadyExists(_))
+        ));
+        // Beacon id should exist in source folder.
+        assert!(FileStore::import(&source, multibeacon, "other_id").is_err());
+    }
+
+    #[test]
+    fn group_epoch_roundtrip() {
+        use crate::key::toml::tests::toml_samples;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base_path = temp_dir.path().join("testnet").display().to_string();
+        let store = FileStore::new_checked(&base_path, "default").unwrap();
+
+        let decode =
+            |s: &str| -> Group<DefaultScheme> { Toml::toml_decode(&s.parse().unwrap()).unwrap() };
+        let first = decode(toml_samples::group());
+        let mut second = decode(toml_samples::group());
+        second.threshold = 3;
+        second.transition_time += 30;
+        second.nodes.pop();
+
+        // History folder is created with the first saved epoch.
+        assert!(matches!(
+            store.load_group_epoch::<DefaultScheme>(1),
+            Err(FileStoreError::FileNotFound(_))
+        ));
+        store.save_group_epoch(&first, 1).unwrap();
+        store.save_group_epoch(&second, 2).unwrap();
+        store.save_group(&second).unwrap();
+        assert_perm(store.group_epoch_file(1), PUBLIC_PERM);
+
+        assert!(store.load_group_epoch::<DefaultScheme>(1).unwrap() == first);
+        assert!(store.load_group_epoch::<DefaultScheme>(2).unwrap() == second);
+        // Zero stands for the latest group.
+        assert!(store.load_group_epoch::<DefaultScheme>(0).unwrap() == second);
+        assert!(store.load_group_epoch::<DefaultScheme>(3).is_err());
+
+        // Saving an epoch again replaces its group.
+        store.save_group_epoch(&second, 1).unwrap();
+        assert!(store.load_group_epoch::<DefaultScheme>(1).unwrap() == second);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failed_import_is_removed() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let source = temp_dir.path().join("source").display().to_string();
+        let target = temp_dir.path().join("target").display().to_string();
+
+        let store = FileStore::new_checked(&source, "some_id").unwrap();
+        let pair: Pair<DefaultScheme> = Pair::generate(Address::default()).unwrap();
+        store.save_key_pair(&pair).unwrap();
+        // Dangling link fails the copy after some files are copied.
+        std::os::unix::fs::symlink(
+            temp_dir.path().join("missing"),
+            store.beacon_path.join("zz_link"),
+        )
+        .unwrap();
+
+        let other = FileStore::new_checked(&target, "other_id").unwrap();
+        let multibeacon = other.beacon_path.parent().unwrap();
+        assert!(FileStore::import(&source, multibeacon, "some_id").is_err());
+        assert!(!multibeacon.join("some_id").exists());
+
+        // Discarded import does not block the next attempt.
+        std::fs::remove_file(store.beacon_path.join("zz_link")).unwrap();
+        let imported = FileStore::import(&source, multibeacon, "some_id").unwrap();
+        imported.discard_import();
+        assert!(!multibeacon.join("some_id").exists());
+        assert!(store.validate().is_ok());
+    }
+
+    #[test]
+    fn read_beacon_subset() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = temp_dir.path().join("base").display().to_string();
+        let pair: Pair<DefaultScheme> = Pair::generate(Address::default()).unwrap();
+        for id in ["first", "second"] {
+            FileStore::new_checked(&base, id)
+                .unwrap()
+                .save_key_pair(&pair)
+                .unwrap();
+        }
+        // Store without keys, e.g. being repaired.
+        let _ = FileStore::new_checked(&base, "broken").unwrap();
+        assert!(FileStore::read_multibeacon_folder(&base).is_err());
+
+        let (_, stores) = FileStore::read_multibeacon_subset(&base, &["second".into()]).unwrap();
+        assert_eq!(stores.len(), 1);
+        assert_eq!(stores[0].get_beacon_id(), Some("second"));
+
+        // All listed ids should be present.
+        assert!(matches!(
+            FileStore::read_multibeacon_subset(&base, &["first".into(), "third".into()]),
+            Err(FileStoreError::BeaconNotFound)
+        ));
+    }
+
+    #[cfg(unix)]
     fn assert_perm(path: PathBuf, mode: u32) {
+        use std::os::unix::fs::PermissionsExt;
         assert!(std::fs::metadata(path).unwrap().permissions().mode() & 0o777 == mode);
     }
+
+    #[cfg(windows)]
+    fn assert_perm(path: PathBuf, mode: u32) {
+        let acl = std::process::Command::new("icacls")
+            .arg(&path)
+            .output()
+            .unwrap();
+        let acl = String::from_utf8_lossy(&acl.stdout);
+        // Private entries do not inherit access of the temporary folder.
+        if is_owner_only(mode) {
+            assert!(acl.contains("OWNER RIGHTS:"));
+            for other in ["BUILTIN\\Users", "Authenticated Users", "Everyone"] {
+                assert!(
+                    !acl.contains(other),
+                    "{other} has access to {}",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn assert_perm(path: PathBuf, _mode: u32) {
+        assert!(path.exists());
+    }
 }
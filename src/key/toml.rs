@@ -313,7 +313,7 @@ impl<S: Scheme> Toml for Share<S> {
 }
 
 /// Helper function to set table **keys** prefix
-fn prefix_keys(mut table: Table) -> Table {
+pub(crate) fn prefix_keys(mut table: Table) -> Table {
     let keys: Vec<String> = table.iter().map(|(key, _)| key.into()).collect();
 
     for key in keys {
@@ -327,7 +327,7 @@ fn prefix_keys(mut table: Table) -> Table {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use energon::drand::schemes::DefaultScheme;
 
@@ -336,6 +336,17 @@ mod tests {
         default_vectors::<DefaultScheme>();
     }
 
+    #[test]
+    fn group_hash() {
+        // Go drand writes the group hash as GenesisSeed of the first group.
+        let group: Group<DefaultScheme> =
+            Toml::toml_decode(&toml_samples::group().parse().unwrap()).unwrap();
+        assert_eq!(
+            hex::encode(crate::key::Hash::hash(&group)),
+            "023779ea9eac851bf27c35cdff64b8e55774ad8b20752c0258fce264fa70b57c"
+        );
+    }
+
     fn default_vectors<S: Scheme>() {
         // Group<S> from/into drand_group.toml
         let expected = toml_samples::group();
@@ -361,7 +372,7 @@ mod tests {
 
     /// Data is generated by <https://github.com/drand/drand/tree/master/demo#local-demo-of-drand>
     #[rustfmt::skip]
-    mod toml_samples {
+    pub(crate) mod toml_samples {
 
         /// filename: `drand_group.toml`
         pub fn group() -> &'static str {
@@ -0,0 +1,55 @@
+//! Verification of drand beacons and chain info, with a client of the public API.
+//!
+//! Built without default features (enable one of the `blstrs` or `arkworks` backends), the
+//! library has no dependencies of the daemon: servers, storage backends and the tokio runtime.
+//! The daemon is built from `main.rs` with the `daemon` feature.
+//!
+//! Without other features the library contains only [`verify`], the client requires the
+//! `client` feature. Bindings for `wasm32-unknown-unknown` are built with the `wasm` feature,
+//! see [`wasm`], and C ABI with the `ffi` feature, see [`ffi`].
+//!
+//! Note: the library requires `std`, as the crypto backend depends on `std` crates.
+//!
+//! Fuzz targets (`cargo fuzz` sets `--cfg fuzzing`) additionally compile modules of the daemon,
+//! see targets at `fuzz/`. Benchmarks (`cargo bench --features bench`) compile them as well,
+//! see targets at `benches/`.
+#![warn(clippy::pedantic)]
+#![allow(clippy::unreadable_literal)]
+#![cfg_attr(
+    any(fuzzing, feature = "bench"),
+    allow(dead_code, reason = "modules are shared with the binary target")
+)]
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "client")]
+#[allow(clippy::all, clippy::pedantic, reason = "generated by prost")]
+pub mod protobuf;
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(any(fuzzing, feature = "bench"))]
+mod chain;
+#[cfg(any(fuzzing, feature = "bench"))]
+mod cli;
+#[cfg(any(fuzzing, feature = "bench"))]
+mod core;
+#[cfg(any(fuzzing, feature = "bench"))]
+mod dkg;
+#[cfg(any(fuzzing, feature = "bench"))]
+mod key;
+#[cfg(any(fuzzing, feature = "bench"))]
+mod log;
+#[cfg(any(fuzzing, feature = "bench"))]
+mod net;
+#[cfg(any(fuzzing, feature = "bench"))]
+mod secrets;
+#[cfg(any(fuzzing, feature = "bench"))]
+mod transport;
+
+#[cfg(feature = "bench")]
+pub mod bench;
+#[cfg(fuzzing)]
+pub mod fuzz;
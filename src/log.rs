@@ -1,16 +1,60 @@
+//! Tracing setup with log level of `drand` target configurable per beacon id.
+//!
+//! Beacon id of an event is taken from the closest span of the beacon: spans are created with
+//! a single field, its value is either `<beacon id>` or `<address>.<beacon id>[.<suffix>]`.
+//! Events outside of beacon spans use the default level.
+//!
+//! Per-beacon lines of bulk operations, such as sync of many rounds, are limited with [`Throttle`]
+//! to `--bulk-log-rate` lines per second. Peer switches and errors are never throttled.
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use std::sync::RwLock;
+use std::time::Duration;
+use std::time::Instant;
 use tracing::dispatcher;
+use tracing::field::Field;
+use tracing::field::Visit;
+use tracing::level_filters::LevelFilter;
+use tracing::span;
+use tracing::Event;
+use tracing::Metadata;
+use tracing::Subscriber;
 use tracing_subscriber::fmt::time;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::layer::Filter;
 use tracing_subscriber::prelude::__tracing_subscriber_SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::util::SubscriberInitExt;
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::Layer;
+
+struct Levels {
+    default: LevelFilter,
+    per_id: BTreeMap<String, LevelFilter>,
+}
+
+static LEVELS: RwLock<Levels> = RwLock::new(Levels {
+    default: LevelFilter::INFO,
+    per_id: BTreeMap::new(),
+});
+
+/// Default of `--bulk-log-rate`, lines per second.
+pub const DEFAULT_BULK_LOG_RATE: u32 = 5;
+
+/// Lines per second logged by each [`Throttle`], `0` disables throttling.
+static BULK_LOG_RATE: AtomicU32 = AtomicU32::new(DEFAULT_BULK_LOG_RATE);
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[error("invalid log level '{0}', expected one of: off, error, warn, info, debug, trace")]
+pub struct InvalidLevel(String);
 
 pub fn setup_tracing(verbose: bool) -> anyhow::Result<()> {
     if !dispatcher::has_been_set() {
-        let filter = EnvFilter::builder().parse_lossy(if verbose {
-            "drand=debug,energon=debug"
-        } else {
-            "drand=info,energon=debug"
-        });
+        if verbose {
+            if let Ok(mut levels) = LEVELS.write() {
+                levels.default = LevelFilter::DEBUG;
+            }
+        }
 
         let layer = tracing_subscriber::fmt::layer()
             .with_timer(time::time())
@@ -20,10 +64,238 @@ pub fn setup_tracing(verbose: bool) -> anyhow::Result<()> {
             .with_ansi(true);
 
         tracing_subscriber::registry()
-            .with(layer)
-            .with(filter)
+            .with(layer.with_filter(BeaconFilter))
             .try_init()?;
     }
 
     Ok(())
 }
+
+pub fn parse_level(level: &str) -> Result<LevelFilter, InvalidLevel> {
+    level.parse().map_err(|_| InvalidLevel(level.to_string()))
+}
+
+/// Parses `<beacon id>=<level>` pair, used by `--log-level` flag of `drand start`.
+pub fn parse_beacon_level(s: &str) -> Result<(String, LevelFilter), InvalidLevel> {
+    let (id, level) = s
+        .split_once('=')
+        .ok_or_else(|| InvalidLevel(s.to_string()))?;
+
+    Ok((id.trim().to_string(), parse_level(level.trim())?))
+}
+
+/// Sets log level of the beacon id, `None` resets it to the default level.
+/// Returns effective level of the beacon id.
+pub fn set_beacon_level(beacon_id: &str, level: Option<LevelFilter>) -> LevelFilter {
+    let mut levels = LEVELS
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    match level {
+        Some(level) => {
+            levels.per_id.insert(beacon_id.to_string(), level);
+            level
+        }
+        None => {
+            levels.per_id.remove(beacon_id);
+            levels.default
+        }
+    }
+}
+
+/// Sets rate of throttles created afterwards.
+pub fn set_bulk_log_rate(rate: u32) {
+    BULK_LOG_RATE.store(rate, Ordering::Relaxed);
+}
+
+/// Rate limiter of repetitive lines of a bulk operation. Each operation owns its throttle,
+/// so a fast sync of one beacon id does not silence another one.
+pub struct Throttle {
+    rate: u32,
+    window: Option<Instant>,
+    logged: u32,
+    skipped: u64,
+}
+
+impl Throttle {
+    pub fn new() -> Self {
+        Self::with_rate(BULK_LOG_RATE.load(Ordering::Relaxed))
+    }
+
+    fn with_rate(rate: u32) -> Self {
+        Self {
+            rate,
+            window: None,
+            logged: 0,
+            skipped: 0,
+        }
+    }
+
+    /// Returns amount of lines skipped since the previous allowed one if a line can be logged now.
+    pub fn allow(&mut self) -> Option<u64> {
+        self.allow_at(Instant::now())
+    }
+
+    fn allow_at(&mut self, now: Instant) -> Option<u64> {
+        if self.rate != 0 {
+            match self.window {
+                Some(start) if now.duration_since(start) < Duration::from_secs(1) => {
+                    if self.logged >= self.rate {
+                        self.skipped += 1;
+                        return None;
+                    }
+                }
+                _ => {
+                    self.window = Some(now);
+                    self.logged = 0;
+                }
+            }
+            self.logged += 1;
+        }
+
+        Some(std::mem::take(&mut self.skipped))
+    }
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Beacon id of the span, stored in span extensions.
+struct BeaconTag(String);
+
+/// Records value of the first string field.
+#[derive(Default)]
+struct FirstValue(Option<String>);
+
+impl Visit for FirstValue {
+    fn record_str(&mut self, _field: &Field, value: &str) {
+        if self.0.is_none() {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+/// Returns beacon id from the span value, see module documentation.
+fn span_beacon_id(value: &str) -> Option<&str> {
+    let value = value.split(' ').next()?;
+    match value.rsplit_once(':') {
+        // Port is followed by the beacon id.
+        Some((_, rest)) => rest.split('.').nth(1),
+        None => Some(value),
+    }
+}
+
+struct BeaconFilter;
+
+impl<S> Filter<S> for BeaconFilter
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn enabled(&self, meta: &Metadata<'_>, _cx: &Context<'_, S>) -> bool {
+        let target = meta.target();
+        if target.starts_with("energon") {
+            return *meta.level() <= LevelFilter::DEBUG;
+        }
+        if !target.starts_with("drand") {
+            return false;
+        }
+        if meta.is_span() {
+            return true;
+        }
+        // Fine-grained check is done per event, see `event_enabled`.
+        let Ok(levels) = LEVELS.read() else {
+            return true;
+        };
+        let max = levels
+            .per_id
+            .values()
+            .fold(levels.default, |a, b| a.max(*b));
+
+        *meta.level() <= max
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, cx: &Context<'_, S>) -> bool {
+        if !event.metadata().target().starts_with("drand") {
+            return true;
+        }
+        let span = match event.parent() {
+            Some(id) => cx.span(id),
+            None if event.is_contextual() => cx.lookup_current(),
+            None => None,
+        };
+        let Ok(levels) = LEVELS.read() else {
+            return true;
+        };
+        let mut level = levels.default;
+        if let Some(span) = span {
+            for s in span.scope() {
+                if let Some(tag) = s.extensions().get::<BeaconTag>() {
+                    level = levels.per_id.get(&tag.0).copied().unwrap_or(level);
+                    break;
+                }
+            }
+        }
+
+        *event.metadata().level() <= level
+    }
+
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, cx: Context<'_, S>) {
+        let mut value = FirstValue::default();
+        attrs.record(&mut value);
+        let Some(beacon_id) = value.0.as_deref().and_then(span_beacon_id) else {
+            return;
+        };
+        if let Some(span) = cx.span(id) {
+            span.extensions_mut()
+                .insert(BeaconTag(beacon_id.to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beacon_of_span() {
+        assert_eq!(span_beacon_id("default"), Some("default"));
+        assert_eq!(span_beacon_id("127.0.0.1:4444.quicknet"), Some("quicknet"));
+        assert_eq!(
+            span_beacon_id("node.example.com:443.default.3"),
+            Some("default")
+        );
+        assert_eq!(
+            span_beacon_id("[::1]:4444.evmnet.1 from 5 to 10"),
+            Some("evmnet")
+        );
+        // Partials pool is shared across beacon ids.
+        assert_eq!(span_beacon_id("127.0.0.1:4444"), None);
+
+        assert_eq!(
+            parse_beacon_level("quicknet=debug"),
+            Ok(("quicknet".into(), LevelFilter::DEBUG))
+        );
+        assert!(parse_beacon_level("quicknet").is_err());
+        assert_eq!(parse_level("loud"), Err(InvalidLevel("loud".into())));
+    }
+
+    #[test]
+    fn throttle_rate() {
+        let start = Instant::now();
+        let mut throttle = Throttle::with_rate(2);
+        assert_eq!(throttle.allow_at(start), Some(0));
+        assert_eq!(throttle.allow_at(start), Some(0));
+        assert_eq!(throttle.allow_at(start + Duration::from_millis(500)), None);
+        assert_eq!(throttle.allow_at(start + Duration::from_millis(900)), None);
+        // Next window reports lines skipped in the previous one.
+        assert_eq!(throttle.allow_at(start + Duration::from_secs(1)), Some(2));
+        assert_eq!(throttle.allow_at(start + Duration::from_secs(1)), Some(0));
+
+        let mut unlimited = Throttle::with_rate(0);
+        assert!((0..100).all(|_| unlimited.allow_at(start) == Some(0)));
+    }
+}
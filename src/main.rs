@@ -16,6 +16,8 @@
 #![allow(clippy::unreadable_literal)]
 mod chain;
 mod cli;
+#[allow(dead_code, reason = "client API is exported by the library target")]
+mod client;
 mod core;
 mod dkg;
 mod key;
@@ -23,15 +25,27 @@ mod log;
 mod net;
 #[allow(clippy::all, clippy::pedantic, reason = "generated by prost")]
 mod protobuf;
+mod secrets;
 mod transport;
+#[allow(
+    dead_code,
+    reason = "verification API is exported by the library target"
+)]
+mod verify;
 
-#[cfg(test)]
+// Scenarios drive golang binaries through bash scripts.
+#[cfg(all(test, unix))]
 mod test_with_golang;
 
 use clap::Parser;
 use cli::Cli;
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    Cli::parse().run().await
+// Heap statistics of profiling endpoints are provided by jemalloc, see `net::pprof`.
+#[cfg(feature = "pprof")]
+#[global_allocator]
+static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    cli.runtime()?.block_on(cli.run())
 }
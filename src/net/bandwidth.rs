@@ -0,0 +1,323 @@
+//! Bandwidth accounting per peer IP for connections accepted at node address.
+//!
+//! Every accepted TCP stream is wrapped into [`Metered`], which counts bytes read from and
+//! written to the peer, so traffic of protocol, public and DKG public services is included.
+//! Counters are keyed by peer IP and reconnects of the same peer are accumulated. Counters of a
+//! peer without open connections are evicted after [`PEER_IDLE_TIMEOUT`], and metrics export
+//! only [`EXPORTED_PEERS`] top peers, the rest is summed under `peer="other"`. Outgoing
+//! connections to peers are not counted.
+use crate::protobuf::drand::PeerTraffic;
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::pin::Pin;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::ReadBuf;
+use tokio::net::TcpStream;
+use tonic::transport::server::Connected;
+use tonic::transport::server::TcpConnectInfo;
+
+/// Counters of a peer without open connections are evicted after the timeout.
+pub const PEER_IDLE_TIMEOUT: Duration = Duration::from_secs(3600);
+/// Amount of peers exported to metrics, ordered by bytes sent.
+pub const EXPORTED_PEERS: usize = 32;
+
+/// Traffic counters of a single peer IP.
+struct Counters {
+    /// Bytes written to the peer.
+    sent: AtomicU64,
+    /// Bytes read from the peer.
+    received: AtomicU64,
+    /// Open connections of the peer.
+    connections: AtomicUsize,
+    /// Time of the latest closed connection, or of the first one.
+    last_active: Mutex<Instant>,
+}
+
+impl Counters {
+    fn new(now: Instant) -> Self {
+        Self {
+            sent: AtomicU64::default(),
+            received: AtomicU64::default(),
+            connections: AtomicUsize::default(),
+            last_active: Mutex::new(now),
+        }
+    }
+
+    fn is_idle(&self, now: Instant) -> bool {
+        self.connections.load(Ordering::Relaxed) == 0
+            && now.saturating_duration_since(
+                *self
+                    .last_active
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner),
+            ) >= PEER_IDLE_TIMEOUT
+    }
+}
+
+/// Traffic counters of all peers served by the node.
+#[derive(Default)]
+pub struct Bandwidth {
+    peers: Mutex<HashMap<IpAddr, Arc<Counters>>>,
+}
+
+impl Bandwidth {
+    /// Wraps accepted stream to account its traffic to the peer IP.
+    pub fn meter(&self, stream: TcpStream) -> Metered {
+        let ip = stream
+            .peer_addr()
+            .map_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED), |addr| addr.ip());
+        let now = Instant::now();
+        let mut peers = self.peers.lock().unwrap_or_else(PoisonError::into_inner);
+        evict_idle(&mut peers, now);
+        let counters = peers
+            .entry(ip)
+            .or_insert_with(|| Arc::new(Counters::new(now)))
+            .clone();
+        counters.connections.fetch_add(1, Ordering::Relaxed);
+
+        Metered {
+            inner: stream,
+            counters,
+        }
+    }
+
+    /// Returns traffic of all peers, sorted by bytes sent in descending order.
+    pub fn snapshot(&self) -> Vec<PeerTraffic> {
+        let mut peers: Vec<PeerTraffic> = self
+            .peers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(ip, counters)| PeerTraffic {
+                address: ip.to_string(),
+                bytes_sent: counters.sent.load(Ordering::Relaxed),
+                bytes_received: counters.received.load(Ordering::Relaxed),
+            })
+            .collect();
+        peers.sort_by(|a, b| {
+            b.bytes_sent
+                .cmp(&a.bytes_sent)
+                .then_with(|| a.address.cmp(&b.address))
+        });
+
+        peers
+    }
+
+    /// Returns counters in Prometheus text exposition format.
+    pub fn metrics(&self) -> String {
+        let peers = top_peers(self.snapshot(), EXPORTED_PEERS);
+        let mut m = String::new();
+        let name = "drand_peer_bytes_sent_total";
+        let _ = writeln!(m, "# HELP {name} Bytes sent to peer over node address.");
+        let _ = writeln!(m, "# TYPE {name} counter");
+        for p in &peers {
+            let _ = writeln!(m, "{name}{{peer=\"{}\"}} {}", p.address, p.bytes_sent);
+        }
+        let name = "drand_peer_bytes_received_total";
+        let _ = writeln!(
+            m,
+            "# HELP {name} Bytes received from peer over node address."
+        );
+        let _ = writeln!(m, "# TYPE {name} counter");
+        for p in &peers {
+            let _ = writeln!(m, "{name}{{peer=\"{}\"}} {}", p.address, p.bytes_received);
+        }
+
+        m
+    }
+}
+
+/// Removes counters of peers idle for [`PEER_IDLE_TIMEOUT`].
+fn evict_idle(peers: &mut HashMap<IpAddr, Arc<Counters>>, now: Instant) {
+    peers.retain(|_, counters| !counters.is_idle(now));
+}
+
+/// Keeps `n` first peers of the snapshot, traffic of the rest is summed as `other`.
+fn top_peers(mut peers: Vec<PeerTraffic>, n: usize) -> Vec<PeerTraffic> {
+    if peers.len() <= n {
+        return peers;
+    }
+    let other = peers.split_off(n).into_iter().fold(
+        PeerTraffic {
+            address: "other".into(),
+            bytes_sent: 0,
+            bytes_received: 0,
+        },
+        |mut other, p| {
+            other.bytes_sent += p.bytes_sent;
+            other.bytes_received += p.bytes_received;
+            other
+        },
+    );
+    peers.push(other);
+
+    peers
+}
+
+/// Accepted TCP stream which accounts its traffic, see [`Bandwidth::meter`].
+pub struct Metered {
+    inner: TcpStream,
+    counters: Arc<Counters>,
+}
+
+impl AsyncRead for Metered {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+        let poll = Pin::new(&mut this.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            this.counters
+                .received
+                .fetch_add((buf.filled().len() - filled) as u64, Ordering::Relaxed);
+        }
+
+        poll
+    }
+}
+
+impl AsyncWrite for Metered {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.counters.sent.fetch_add(n as u64, Ordering::Relaxed);
+        }
+
+        poll
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_write_vectored(cx, bufs);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.counters.sent.fetch_add(n as u64, Ordering::Relaxed);
+        }
+
+        poll
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+impl Drop for Metered {
+    fn drop(&mut self) {
+        *self
+            .counters
+            .last_active
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Instant::now();
+        self.counters.connections.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Connected for Metered {
+    type ConnectInfo = TcpConnectInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.inner.connect_info()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn count_peer_traffic() {
+        let bandwidth = Bandwidth::default();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut stream = bandwidth.meter(stream);
+
+        client.write_all(b"ping").await.unwrap();
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        stream.write_all(b"pong-pong").await.unwrap();
+
+        let peers = bandwidth.snapshot();
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].address, "127.0.0.1");
+        assert_eq!((peers[0].bytes_sent, peers[0].bytes_received), (9, 4));
+        assert!(bandwidth
+            .metrics()
+            .contains("drand_peer_bytes_sent_total{peer=\"127.0.0.1\"} 9"));
+
+        // Counters of a connected peer are kept, idle peer is evicted after the timeout.
+        let later = Instant::now() + PEER_IDLE_TIMEOUT;
+        let mut peers = bandwidth.peers.lock().unwrap();
+        evict_idle(&mut peers, later);
+        assert_eq!(peers.len(), 1);
+        drop(peers);
+        drop(stream);
+        let mut peers = bandwidth.peers.lock().unwrap();
+        evict_idle(&mut peers, Instant::now());
+        assert_eq!(peers.len(), 1);
+        evict_idle(&mut peers, Instant::now() + PEER_IDLE_TIMEOUT);
+        assert!(peers.is_empty());
+    }
+
+    #[test]
+    fn export_top_peers() {
+        let peers: Vec<PeerTraffic> = (0..5)
+            .map(|i| PeerTraffic {
+                address: format!("10.0.0.{i}"),
+                bytes_sent: 100 - i,
+                bytes_received: 1,
+            })
+            .collect();
+        assert_eq!(top_peers(peers.clone(), 5), peers);
+
+        let top = top_peers(peers, 2);
+        let exported: Vec<_> = top
+            .iter()
+            .map(|p| (p.address.as_str(), p.bytes_sent, p.bytes_received))
+            .collect();
+        assert_eq!(
+            exported,
+            [("10.0.0.0", 100, 1), ("10.0.0.1", 99, 1), ("other", 291, 3)]
+        );
+    }
+}
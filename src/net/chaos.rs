@@ -0,0 +1,425 @@
+//! Fault injection for outgoing requests of peer clients.
+//!
+//! Faults are registered per peer address and applied by [`ChaosChannel`], the channel of
+//! [`ProtocolClient`], [`PublicClient`] and [`DkgPublicClient`]: requests are dropped or delayed
+//! before they are sent, and messages received from the peer are counted, so the peer is
+//! disconnected in the middle of a stream once [`Faults::disconnect_after`] is reached.
+//! Compiled for tests and with `chaos` feature, in which case faults for all peers are read from
+//! [`CHAOS_ENV`] at first request, for example `DRAND_CHAOS=drop=10,latency_ms=50,disconnect_after=100`.
+//!
+//! [`ProtocolClient`]: super::protocol::ProtocolClient
+//! [`PublicClient`]: super::public::PublicClient
+//! [`DkgPublicClient`]: super::dkg_public::DkgPublicClient
+use super::utils::Address;
+
+use hyper::body::Body;
+use hyper::body::Frame;
+use hyper::body::SizeHint;
+use prost::bytes::Bytes;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use tonic::body::BoxBody;
+use tonic::codegen::BoxFuture;
+use tonic::codegen::Service;
+use tonic::codegen::StdError;
+use tonic::transport::Channel;
+use tonic::Status;
+
+/// Environment variable with faults applied to all peers.
+pub const CHAOS_ENV: &str = "DRAND_CHAOS";
+/// Registry key for faults applied to all peers.
+const ANY_PEER: &str = "*";
+
+/// Faults registered for peers, shared by all clients within the process.
+static FAULTS: LazyLock<Mutex<HashMap<String, PeerFaults>>> = LazyLock::new(|| {
+    let mut faults = HashMap::new();
+    if let Ok(value) = std::env::var(CHAOS_ENV) {
+        match value.parse::<Faults>() {
+            Ok(f) => {
+                tracing::warn!("chaos: faults are injected for all peers: {f:?}");
+                faults.insert(
+                    ANY_PEER.to_string(),
+                    PeerFaults {
+                        faults: f,
+                        received: 0,
+                    },
+                );
+            }
+            Err(err) => tracing::error!("chaos: ignoring {CHAOS_ENV}: {err}"),
+        }
+    }
+
+    Mutex::new(faults)
+});
+
+#[derive(Debug, Default, Clone)]
+pub struct Faults {
+    /// Percentage of requests failed with `Unavailable`, values above 100 fail every request.
+    pub drop_percent: u8,
+    /// Delay added to every request which is not dropped.
+    pub latency: Duration,
+    /// Peer is disconnected once given number of messages is received from it: the next message
+    /// of a stream and every next request fail with `Unavailable`.
+    pub disconnect_after: Option<u64>,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[error("expected comma separated drop=<percent>,latency_ms=<ms>,disconnect_after=<messages>, received {0}")]
+pub struct ParseFaultsError(String);
+
+impl std::str::FromStr for Faults {
+    type Err = ParseFaultsError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let err = || ParseFaultsError(value.to_string());
+        let mut faults = Self::default();
+        for kv in value.split(',').filter(|kv| !kv.is_empty()) {
+            let (key, val) = kv.split_once('=').ok_or_else(err)?;
+            match key.trim() {
+                "drop" => faults.drop_percent = val.trim().parse().map_err(|_| err())?,
+                "latency_ms" => {
+                    faults.latency = Duration::from_millis(val.trim().parse().map_err(|_| err())?);
+                }
+                "disconnect_after" => {
+                    faults.disconnect_after = Some(val.trim().parse().map_err(|_| err())?);
+                }
+                _ => return Err(err()),
+            }
+        }
+
+        Ok(faults)
+    }
+}
+
+struct PeerFaults {
+    faults: Faults,
+    /// Messages received from the peer.
+    received: u64,
+}
+
+impl PeerFaults {
+    fn is_disconnected(&self) -> bool {
+        self.faults
+            .disconnect_after
+            .is_some_and(|n| self.received >= n)
+    }
+}
+
+/// Registers faults for requests to given peer, replaces previously registered faults.
+pub fn inject(peer: &Address, faults: Faults) {
+    lock().insert(
+        peer.to_string(),
+        PeerFaults {
+            faults,
+            received: 0,
+        },
+    );
+}
+
+/// Removes faults registered for given peer.
+pub fn clear(peer: &Address) {
+    lock().remove(peer.as_str());
+}
+
+/// Applies faults registered for given peer, returns an error if the request should not be sent.
+async fn apply(peer: &Address) -> Result<(), Status> {
+    let latency = with_faults(peer, |p| {
+        if p.is_disconnected() {
+            return Err(Status::unavailable(format!(
+                "chaos: {peer} is disconnected"
+            )));
+        }
+        if rand::random_range(0..100) < p.faults.drop_percent {
+            return Err(Status::unavailable(format!(
+                "chaos: request to {peer} is dropped"
+            )));
+        }
+        Ok(p.faults.latency)
+    })?;
+    if !latency.is_zero() {
+        tokio::time::sleep(latency).await;
+    }
+
+    Ok(())
+}
+
+/// Counts message received from given peer, returns an error if the peer is disconnected.
+fn receive(peer: &Address) -> Result<(), Status> {
+    with_faults(peer, |p| {
+        if p.is_disconnected() {
+            return Err(Status::unavailable(format!(
+                "chaos: {peer} is disconnected"
+            )));
+        }
+        p.received += 1;
+        Ok(())
+    })
+}
+
+/// Calls `f` with faults of the peer, or with faults of all peers if none are registered.
+fn with_faults<T: Default>(
+    peer: &Address,
+    f: impl FnOnce(&mut PeerFaults) -> Result<T, Status>,
+) -> Result<T, Status> {
+    let mut faults = lock();
+    let key = if faults.contains_key(peer.as_str()) {
+        peer.as_str()
+    } else {
+        ANY_PEER
+    };
+    match faults.get_mut(key) {
+        Some(p) => f(p),
+        None => Ok(T::default()),
+    }
+}
+
+/// Channel of peer clients which applies faults registered for the peer.
+#[derive(Clone)]
+pub struct ChaosChannel {
+    inner: Channel,
+    peer: Address,
+}
+
+impl ChaosChannel {
+    pub fn new(inner: Channel, peer: &Address) -> Self {
+        Self {
+            inner,
+            peer: peer.clone(),
+        }
+    }
+}
+
+impl Service<http::Request<BoxBody>> for ChaosChannel {
+    type Response = http::Response<BoxBody>;
+    type Error = StdError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: http::Request<BoxBody>) -> Self::Future {
+        // Channel is ready, its clone might be not.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let peer = self.peer.clone();
+
+        Box::pin(async move {
+            // Status is returned to the client as is.
+            apply(&peer).await?;
+            let response = inner.call(request).await?;
+
+            Ok(response.map(|body| {
+                BoxBody::new(ChaosBody {
+                    inner: body,
+                    peer,
+                    framing: Framing::default(),
+                })
+            }))
+        })
+    }
+}
+
+/// Response body which counts received messages, see [`receive`].
+struct ChaosBody {
+    inner: BoxBody,
+    peer: Address,
+    framing: Framing,
+}
+
+impl Body for ChaosBody {
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        let frame = ready!(Pin::new(&mut this.inner).poll_frame(cx));
+        if let Some(data) = frame.as_ref().and_then(|f| f.as_ref().ok()?.data_ref()) {
+            for _ in 0..this.framing.count(data) {
+                if let Err(status) = receive(&this.peer) {
+                    return Poll::Ready(Some(Err(status)));
+                }
+            }
+        }
+
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Length-prefixed framing of gRPC messages: compression flag and big endian `u32` length.
+#[derive(Default)]
+struct Framing {
+    header: [u8; 5],
+    header_len: usize,
+    /// Bytes of the current message which are not yet received.
+    remaining: usize,
+}
+
+impl Framing {
+    /// Returns number of messages started within `data`.
+    fn count(&mut self, mut data: &[u8]) -> usize {
+        let mut messages = 0;
+        while !data.is_empty() {
+            if self.remaining > 0 {
+                let n = self.remaining.min(data.len());
+                self.remaining -= n;
+                data = &data[n..];
+                continue;
+            }
+            let n = (self.header.len() - self.header_len).min(data.len());
+            self.header[self.header_len..self.header_len + n].copy_from_slice(&data[..n]);
+            self.header_len += n;
+            data = &data[n..];
+            if self.header_len == self.header.len() {
+                let len = u32::from_be_bytes([
+                    self.header[1],
+                    self.header[2],
+                    self.header[3],
+                    self.header[4],
+                ]);
+                self.remaining = usize::try_from(len).unwrap_or(usize::MAX);
+                self.header_len = 0;
+                messages += 1;
+            }
+        }
+
+        messages
+    }
+}
+
+fn lock() -> std::sync::MutexGuard<'static, HashMap<String, PeerFaults>> {
+    FAULTS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn apply_faults() {
+        let peer = Address::precheck("chaos-peer:1").unwrap();
+        assert!(apply(&peer).await.is_ok());
+
+        inject(
+            &peer,
+            Faults {
+                disconnect_after: Some(2),
+                ..Default::default()
+            },
+        );
+        // Unary request and its response.
+        assert!(apply(&peer).await.is_ok());
+        assert!(receive(&peer).is_ok());
+        // Stream is disconnected after the second message.
+        assert!(apply(&peer).await.is_ok());
+        assert!(receive(&peer).is_ok());
+        assert!(receive(&peer).is_err());
+        assert!(apply(&peer).await.is_err());
+
+        inject(
+            &peer,
+            Faults {
+                drop_percent: 100,
+                ..Default::default()
+            },
+        );
+        for _ in 0..10 {
+            assert!(apply(&peer).await.is_err());
+        }
+
+        clear(&peer);
+        assert!(apply(&peer).await.is_ok());
+    }
+
+    #[test]
+    fn count_messages() {
+        let message = |len: u8| {
+            let mut m = vec![0, 0, 0, 0, len];
+            m.extend(std::iter::repeat_n(7, len.into()));
+            m
+        };
+        let mut framing = Framing::default();
+        assert_eq!(
+            framing.count(&[message(3), message(0), message(2)].concat()),
+            3
+        );
+
+        // Messages split across frames.
+        let data = [message(4), message(1)].concat();
+        let mut framing = Framing::default();
+        let counts: Vec<usize> = data.chunks(3).map(|c| framing.count(c)).collect();
+        assert_eq!(counts.iter().sum::<usize>(), 2);
+        assert_eq!(counts, [0, 1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn parse_faults() {
+        let faults: Faults = "drop=10,latency_ms=50,disconnect_after=100"
+            .parse()
+            .unwrap();
+        assert_eq!(faults.drop_percent, 10);
+        assert_eq!(faults.latency, Duration::from_millis(50));
+        assert_eq!(faults.disconnect_after, Some(100));
+
+        assert!(""
+            .parse::<Faults>()
+            .is_ok_and(|f| f.disconnect_after.is_none()));
+        assert!("drop=10,".parse::<Faults>().is_ok());
+        assert!("drop=x".parse::<Faults>().is_err());
+        assert!("loss=10".parse::<Faults>().is_err());
+    }
+
+    #[tokio::test]
+    async fn injected_faults_fail_requests() {
+        use super::super::dkg_public::DkgPublicClient;
+        use super::super::protocol::ProtocolClient;
+        use super::super::sim::SimNode;
+        use crate::chain::time::time_now;
+        use crate::chain::time::MockClock;
+        use crate::core::beacon::DEFAULT_BEACON_ID;
+        use crate::protobuf::dkg::GossipPacket;
+        use energon::drand::schemes::DefaultScheme;
+        use std::sync::Arc;
+
+        let node = SimNode::start::<DefaultScheme>(
+            DEFAULT_BEACON_ID,
+            b"chaos",
+            Arc::new(MockClock::new(time_now())),
+        )
+        .unwrap();
+        let faults = Faults {
+            disconnect_after: Some(1),
+            ..Default::default()
+        };
+        inject(&node.address, faults);
+
+        let mut client = ProtocolClient::new(&node.address).await.unwrap();
+        assert!(client.get_identity(DEFAULT_BEACON_ID.into()).await.is_ok());
+        assert!(client.get_identity(DEFAULT_BEACON_ID.into()).await.is_err());
+
+        // Faults are applied by the channel, so to every client of the peer.
+        let mut dkg = DkgPublicClient::new(&node.address).await.unwrap();
+        let err = dkg.packet(GossipPacket::default()).await.unwrap_err();
+        assert!(err.to_string().contains("chaos"));
+        clear(&node.address);
+    }
+}
@@ -1,25 +1,44 @@
 //! Client and server implementations for RPC [`Control`] service.
 
 use super::dkg_control::DkgControlHandler;
-use super::utils::Callback;
+use super::error::ErrorCode;
+use super::error::NodeError;
 use super::utils::NewTcpListener;
 use super::utils::StartServerError;
 use super::utils::ToStatus;
-use super::utils::ERR_METADATA_IS_MISSING;
 
+use crate::chain::inspect;
 use crate::cli::SyncConfig;
+use crate::core::beacon::canonical_beacon_id;
+use crate::core::beacon::Actions;
 use crate::core::beacon::BeaconCmd;
 use crate::core::daemon::Daemon;
+use crate::core::dump;
+use crate::core::multibeacon::BeaconHandlerError;
+use crate::key::backup::BackupError;
 use crate::protobuf::dkg::dkg_control_server::DkgControlServer;
 use crate::protobuf::drand as protobuf;
+use crate::transport::utils::ConvertProto;
 
 use protobuf::control_client::ControlClient as _ControlClient;
 use protobuf::control_server::Control;
 use protobuf::control_server::ControlServer;
+use protobuf::metrics_server::Metrics;
+use protobuf::metrics_server::MetricsServer;
 use protobuf::BackupDbRequest;
 use protobuf::BackupDbResponse;
+use protobuf::BackupKeysRequest;
+use protobuf::BackupKeysResponse;
+use protobuf::BeaconStoreStats;
 use protobuf::ChainInfoPacket;
 use protobuf::ChainInfoRequest;
+use protobuf::CommandQueue;
+use protobuf::DaemonEvent;
+use protobuf::DebugDumpRequest;
+use protobuf::DebugDumpResponse;
+use protobuf::DkgResetRequest;
+use protobuf::DkgResetResponse;
+use protobuf::EventsRequest;
 use protobuf::GroupPacket;
 use protobuf::GroupRequest;
 use protobuf::ListSchemesRequest;
@@ -27,19 +46,35 @@ use protobuf::ListSchemesResponse;
 use protobuf::LoadBeaconRequest;
 use protobuf::LoadBeaconResponse;
 use protobuf::Metadata;
+use protobuf::MetricsRequest;
+use protobuf::MetricsResponse;
+use protobuf::PeerBandwidthRequest;
+use protobuf::PeerBandwidthResponse;
 use protobuf::Ping;
 use protobuf::Pong;
 use protobuf::PublicKeyRequest;
 use protobuf::PublicKeyResponse;
+use protobuf::QueueStatusRequest;
+use protobuf::QueueStatusResponse;
 use protobuf::RemoteStatusRequest;
 use protobuf::RemoteStatusResponse;
+use protobuf::ResyncRequest;
+use protobuf::ResyncResponse;
+use protobuf::SetLogLevelRequest;
+use protobuf::SetLogLevelResponse;
 use protobuf::ShutdownRequest;
 use protobuf::ShutdownResponse;
 use protobuf::StartSyncRequest;
 use protobuf::StatusRequest;
 use protobuf::StatusResponse;
+use protobuf::StoreStatsRequest;
+use protobuf::StoreStatsResponse;
 use protobuf::SyncProgress;
+use protobuf::UnloadBeaconRequest;
+use protobuf::UnloadBeaconResponse;
 
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::transport::Channel;
 use tonic::transport::Server;
@@ -64,6 +99,12 @@ type ResponseStream = Pin<Box<dyn Stream<Item = SyncProgressResponse> + Send>>;
 /// Result type yielded by the sync progress response stream.
 pub type SyncProgressResponse = Result<SyncProgress, tonic::Status>;
 
+/// Control server streaming response with daemon events.
+type DaemonEventStream = Pin<Box<dyn Stream<Item = Result<DaemonEvent, tonic::Status>> + Send>>;
+
+/// Buffer of events stream for a single subscriber.
+const EVENTS_STREAM_BUFFER: usize = 32;
+
 /// Implementor for [`Control`] trait for use with `ControlServer`
 pub struct ControlHandler(Arc<Daemon>);
 
@@ -75,6 +116,9 @@ impl Control for ControlHandler {
     /// Server streaming response type for the `start_follow_chain` method
     type StartFollowChainStream = ResponseStream;
 
+    /// Server streaming response type for the `events` method
+    type EventsStream = DaemonEventStream;
+
     /// PingPong simply responds with an empty packet,
     /// proving that this drand node is up and alive.
     async fn ping_pong(&self, _request: Request<Ping>) -> Result<Response<Pong>, Status> {
@@ -91,22 +135,22 @@ impl Control for ControlHandler {
     ) -> Result<Response<StatusResponse>, Status> {
         // Borrow id from metadata.
         let id = request.get_ref().metadata.as_ref().map_or_else(
-            || Err(Status::data_loss(ERR_METADATA_IS_MISSING)),
+            || Err(Status::from(NodeError::MetadataMissing)),
             |meta| Ok(meta.beacon_id.as_str()),
         )?;
-        let (tx, rx) = Callback::new();
-        self.beacons()
-            .cmd(BeaconCmd::Status(tx), id)
-            .await
-            .map_err(|err| err.to_status(id))?;
-
-        // Await response from callback
-        let chain_info = rx
+        let mut status = self
+            .beacons()
+            .call(id, "status", BeaconCmd::Status)
             .await
-            .map_err(|recv_err| recv_err.to_status(id))?
-            .map_err(|chain_info_err| chain_info_err.to_status(id))?;
+            .map_err(|err| err.to_status(id))?
+            .map_err(|status_err| status_err.to_status(id))?;
+        if let Some(timeouts) = self.beacons().callback_timeouts(id) {
+            status.callback_timeouts = timeouts.total();
+            status.pending_replies = timeouts.pending();
+        }
+        status.last_backup_time = self.backups.last_success();
 
-        Ok(Response::new(chain_info))
+        Ok(Response::new(status))
     }
 
     /// ListSchemes responds with the list of ids for the available schemes
@@ -132,20 +176,15 @@ impl Control for ControlHandler {
     ) -> Result<Response<ChainInfoPacket>, Status> {
         // Borrow id from metadata.
         let id = request.get_ref().metadata.as_ref().map_or_else(
-            || Err(Status::data_loss(ERR_METADATA_IS_MISSING)),
+            || Err(Status::from(NodeError::MetadataMissing)),
             |meta| Ok(meta.beacon_id.as_str()),
         )?;
 
-        let (tx, rx) = Callback::new();
-        self.beacons()
-            .cmd(BeaconCmd::ChainInfo(tx), id)
-            .await
-            .map_err(|err| err.to_status(id))?;
-
-        // Await response from callback
-        let chain_info = rx
+        let chain_info = self
+            .beacons()
+            .call(id, "chain info", BeaconCmd::ChainInfo)
             .await
-            .map_err(|recv_err| recv_err.to_status(id))?
+            .map_err(|err| err.to_status(id))?
             .map_err(|chain_info_err| chain_info_err.to_status(id))?;
 
         Ok(Response::new(chain_info))
@@ -154,9 +193,22 @@ impl Control for ControlHandler {
     /// GroupFile returns the TOML-encoded group file, containing the group public key and coefficients
     async fn group_file(
         &self,
-        _request: Request<GroupRequest>,
+        request: Request<GroupRequest>,
     ) -> Result<Response<GroupPacket>, Status> {
-        Err(Status::unimplemented("group_file: GroupRequest"))
+        let request = request.get_ref();
+        let id = request.metadata.as_ref().map_or_else(
+            || Err(Status::from(NodeError::MetadataMissing)),
+            |meta| Ok(meta.beacon_id.as_str()),
+        )?;
+
+        let group = self
+            .beacons()
+            .call(id, "group file", |cb| BeaconCmd::Group(request.epoch, cb))
+            .await
+            .map_err(|err| err.to_status(id))?
+            .map_err(|fs_err| fs_err.to_status(id))?;
+
+        Ok(Response::new(group))
     }
 
     // Metadata is None: stop the daemon
@@ -208,12 +260,14 @@ impl Control for ControlHandler {
         &self,
         request: Request<LoadBeaconRequest>,
     ) -> Result<Response<LoadBeaconResponse>, Status> {
-        let id = request.get_ref().metadata.as_ref().map_or_else(
-            || Err(Status::data_loss(ERR_METADATA_IS_MISSING)),
+        let request = request.get_ref();
+        let id = request.metadata.as_ref().map_or_else(
+            || Err(Status::from(NodeError::MetadataMissing)),
             |meta| Ok(meta.beacon_id.as_str()),
         )?;
+        let folder = (!request.folder.is_empty()).then_some(request.folder.as_str());
 
-        self.load_id(id).map_err(|err| err.to_status(id))?;
+        self.load_id(id, folder).map_err(|err| err.to_status(id))?;
         let response = LoadBeaconResponse {
             metadata: Some(Metadata::with_id(id.to_string())),
         };
@@ -221,26 +275,46 @@ impl Control for ControlHandler {
         Ok(Response::new(response))
     }
 
+    async fn unload_beacon(
+        &self,
+        request: Request<UnloadBeaconRequest>,
+    ) -> Result<Response<UnloadBeaconResponse>, Status> {
+        let id = request.get_ref().metadata.as_ref().map_or_else(
+            || Err(Status::from(NodeError::MetadataMissing)),
+            |meta| Ok(meta.beacon_id.as_str()),
+        )?;
+        let (tx_graceful, rx_graceful) = tokio::sync::oneshot::channel::<bool>();
+
+        self.unload_id(id, tx_graceful)
+            .map_err(|err| err.to_status(id))?;
+        if !rx_graceful.await.map_err(|err| err.to_status(id))? {
+            return Err(Status::internal("unload is not graceful"));
+        }
+        let response = UnloadBeaconResponse {
+            metadata: Some(Metadata::with_id(id.to_string())),
+        };
+
+        Ok(Response::new(response))
+    }
+
     async fn start_follow_chain(
         &self,
         request: Request<StartSyncRequest>,
     ) -> Result<Response<Self::StartFollowChainStream>, Status> {
         let request = request.into_inner();
         let id = request.metadata.as_ref().map_or_else(
-            || Err(Status::data_loss(ERR_METADATA_IS_MISSING)),
+            || Err(Status::from(NodeError::MetadataMissing)),
             |meta| Ok(meta.beacon_id.clone()),
         )?;
-        let (tx, rx) = Callback::new();
-
-        self.beacons()
-            .cmd(BeaconCmd::Follow(request, tx), &id)
-            .await
-            .map_err(|err| Status::unknown(err.to_string()))?;
-
-        let stream_rx = rx
+        let id = canonical_beacon_id(&id)
+            .map_err(NodeError::from)?
+            .to_string();
+        let stream_rx = self
+            .beacons()
+            .call(&id, "follow", |tx| BeaconCmd::Follow(request, tx))
             .await
-            .map_err(|err| Status::unknown(err.to_string()))?
-            .map_err(|err| Status::unknown(err.to_string()))?;
+            .map_err(|err| err.to_status(&id))?
+            .map_err(|sync_err| sync_err.to_status(&id))?;
         Ok(Response::new(Box::pin(ReceiverStream::new(stream_rx))))
     }
 
@@ -258,11 +332,292 @@ impl Control for ControlHandler {
         Err(Status::unimplemented("backup_database: BackupDbRequest"))
     }
 
+    /// Queries statuses of given addresses over protocol service, all members of the latest group
+    /// are queried if no addresses are provided. Addresses which did not answer are absent from response.
     async fn remote_status(
         &self,
-        _request: Request<RemoteStatusRequest>,
+        request: Request<RemoteStatusRequest>,
     ) -> Result<Response<RemoteStatusResponse>, Status> {
-        Err(Status::unimplemented("remote_status: RemoteStatusRequest"))
+        let request = request.into_inner().validate()?;
+        let id = request.metadata.beacon_id.as_str();
+
+        let statuses = self
+            .beacons()
+            .call(id, "remote status", |tx| {
+                BeaconCmd::RemoteStatus(request.addresses, tx)
+            })
+            .await
+            .map_err(|err| err.to_status(id))?
+            .map_err(|status_err| status_err.to_status(id))?;
+
+        Ok(Response::new(RemoteStatusResponse { statuses }))
+    }
+
+    /// Events streams daemon events, filtered by beacon id if metadata is provided.
+    async fn events(
+        &self,
+        request: Request<EventsRequest>,
+    ) -> Result<Response<Self::EventsStream>, Status> {
+        let beacon_id = request.into_inner().metadata.map(|meta| meta.beacon_id);
+        let mut rx_events = self.beacons().events().subscribe();
+        let (tx, rx) = mpsc::channel(EVENTS_STREAM_BUFFER);
+        let token = self.token.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    () = token.cancelled() => return,
+                    event = rx_events.recv() => event,
+                };
+                match event {
+                    Ok(event) => {
+                        if beacon_id.as_ref().is_some_and(|id| *id != event.beacon_id) {
+                            continue;
+                        }
+                        if tx.send(Ok(event)).await.is_err() {
+                            return;
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        debug!("events stream: subscriber is lagging, skipped {skipped} events");
+                    }
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+
+    /// Returns traffic of connections accepted at node address per peer IP.
+    async fn peer_bandwidth(
+        &self,
+        _request: Request<PeerBandwidthRequest>,
+    ) -> Result<Response<PeerBandwidthResponse>, Status> {
+        Ok(Response::new(PeerBandwidthResponse {
+            peers: self.bandwidth.snapshot(),
+        }))
+    }
+
+    /// Forces resync of the chain from given round, stored beacons are verified again.
+    async fn resync(
+        &self,
+        request: Request<ResyncRequest>,
+    ) -> Result<Response<ResyncResponse>, Status> {
+        let request = request.get_ref();
+        let id = request.metadata.as_ref().map_or_else(
+            || Err(Status::from(NodeError::MetadataMissing)),
+            |meta| Ok(meta.beacon_id.as_str()),
+        )?;
+        let up_to = self
+            .beacons()
+            .call(id, "resync", |tx| BeaconCmd::Resync(request.from_round, tx))
+            .await
+            .map_err(|err| err.to_status(id))?
+            .map_err(|chain_err| chain_err.to_status(id))?;
+
+        Ok(Response::new(ResyncResponse {
+            up_to,
+            metadata: Some(Metadata::with_id(id.to_string())),
+        }))
+    }
+
+    /// Sets log level of the beacon id, the id does not have to be loaded.
+    async fn set_log_level(
+        &self,
+        request: Request<SetLogLevelRequest>,
+    ) -> Result<Response<SetLogLevelResponse>, Status> {
+        let request = request.get_ref();
+        let id = request.metadata.as_ref().map_or_else(
+            || Err(Status::from(NodeError::MetadataMissing)),
+            |meta| Ok(meta.beacon_id.as_str()),
+        )?;
+        let id = canonical_beacon_id(id).map_err(NodeError::from)?;
+        let level = if request.level.is_empty() {
+            None
+        } else {
+            Some(crate::log::parse_level(&request.level).map_err(NodeError::from)?)
+        };
+        let level = crate::log::set_beacon_level(id, level);
+
+        Ok(Response::new(SetLogLevelResponse {
+            level: level.to_string(),
+            metadata: Some(Metadata::with_id(id.to_string())),
+        }))
+    }
+
+    /// Reports commands which are sent but not yet received by beacon processes,
+    /// all loaded beacon ids are reported if beacon id is not set.
+    async fn queue_status(
+        &self,
+        request: Request<QueueStatusRequest>,
+    ) -> Result<Response<QueueStatusResponse>, Status> {
+        let id = request
+            .get_ref()
+            .metadata
+            .as_ref()
+            .map_or("", |meta| meta.beacon_id.as_str());
+        let queues = self
+            .beacons()
+            .snapshot()
+            .iter()
+            .filter(|h| id.is_empty() || h.id().is_eq(id))
+            .map(|h| {
+                let (depth, oldest) = h.process_tx.queue().snapshot();
+                CommandQueue {
+                    beacon_id: h.id().to_string(),
+                    depth: u32::try_from(depth).unwrap_or(u32::MAX),
+                    oldest_age_ms: oldest
+                        .map_or(0, |age| u64::try_from(age.as_millis()).unwrap_or(u64::MAX)),
+                }
+            })
+            .collect::<Vec<_>>();
+        if !id.is_empty() && queues.is_empty() {
+            return Err(NodeError::from(BeaconHandlerError::UnknownID).into());
+        }
+
+        Ok(Response::new(QueueStatusResponse { queues }))
+    }
+
+    /// Wipes failed or stale DKG state of the beacon id, see [`crate::dkg::reset`]. Beacon id
+    /// which is not loaded, e.g. because its DKG store can not be decoded, is reset from its folder.
+    async fn dkg_reset(
+        &self,
+        request: Request<DkgResetRequest>,
+    ) -> Result<Response<DkgResetResponse>, Status> {
+        let request = request.get_ref();
+        let id = request.metadata.as_ref().map_or_else(
+            || Err(Status::from(NodeError::MetadataMissing)),
+            |meta| Ok(meta.beacon_id.as_str()),
+        )?;
+        let id = canonical_beacon_id(id).map_err(NodeError::from)?;
+        let confirm = request.confirm;
+        let response = match self
+            .beacons()
+            .call(id, "dkg reset", |tx| {
+                BeaconCmd::DkgActions(Actions::Reset(confirm, tx))
+            })
+            .await
+        {
+            Ok(reply) => reply.map_err(|err| err.to_status(id))?,
+            Err(BeaconHandlerError::UnknownID) => self
+                .reset_unloaded_dkg(id, confirm)
+                .map_err(|err| err.to_status(id))?,
+            Err(err) => return Err(err.to_status(id)),
+        };
+
+        Ok(Response::new(response))
+    }
+
+    async fn debug_dump(
+        &self,
+        _request: Request<DebugDumpRequest>,
+    ) -> Result<Response<DebugDumpResponse>, Status> {
+        let dump = serde_json::to_string_pretty(&dump::collect(self).await)
+            .map_err(|err| Status::internal(err.to_string()))?;
+
+        Ok(Response::new(DebugDumpResponse { dump }))
+    }
+
+    /// Reports chain stores of beacon ids, all loaded beacon ids are reported if beacon id is not set.
+    async fn store_stats(
+        &self,
+        request: Request<StoreStatsRequest>,
+    ) -> Result<Response<StoreStatsResponse>, Status> {
+        let id = request
+            .get_ref()
+            .metadata
+            .as_ref()
+            .map_or("", |meta| meta.beacon_id.as_str());
+        let ids: Vec<String> = self
+            .beacons()
+            .snapshot()
+            .iter()
+            .filter(|h| id.is_empty() || h.id().is_eq(id))
+            .map(|h| h.id().to_string())
+            .collect();
+        if !id.is_empty() && ids.is_empty() {
+            return Err(NodeError::from(BeaconHandlerError::UnknownID).into());
+        }
+
+        let mut stores = Vec::with_capacity(ids.len());
+        for beacon_id in ids {
+            stores.push(match self.chain_store_stats(&beacon_id).await {
+                Ok(stats) => BeaconStoreStats {
+                    beacon_id,
+                    backend: inspect::StoreStats::BACKEND.to_string(),
+                    total_rounds: stats.stored,
+                    first_round: stats.first.unwrap_or_default(),
+                    last_round: stats.last.unwrap_or_default(),
+                    missing_rounds: stats.missing.iter().map(|(from, to)| to - from + 1).sum(),
+                    disk_bytes: stats.size_bytes,
+                    wal_bytes: stats.wal_bytes,
+                    free_bytes: stats.free_bytes,
+                    error: String::new(),
+                },
+                Err(error) => BeaconStoreStats {
+                    beacon_id,
+                    error,
+                    ..Default::default()
+                },
+            });
+        }
+
+        Ok(Response::new(StoreStatsResponse { stores }))
+    }
+
+    /// Writes backup archive of keys, group files and DKG store into a new file in backups folder
+    /// of the daemon base folder, all beacon ids are archived if beacon id is not set.
+    async fn backup_keys(
+        &self,
+        request: Request<BackupKeysRequest>,
+    ) -> Result<Response<BackupKeysResponse>, Status> {
+        let request = request.into_inner();
+        if request.output_file.is_empty() {
+            return Err(Status::invalid_argument("backup: output file is not set"));
+        }
+        let id = request
+            .metadata
+            .map(|meta| meta.beacon_id)
+            .unwrap_or_default();
+        let passphrase = (!request.passphrase.is_empty()).then_some(request.passphrase);
+        let backup_err = |err: BackupError| {
+            error!("backup: {err}");
+            Status::new(err.code(), format!("backup: {err}"))
+        };
+        let out = self
+            .backup_output(&request.output_file)
+            .map_err(backup_err)?;
+        let manifest = self
+            .backup(
+                (!id.is_empty()).then_some(id).into_iter().collect(),
+                out.clone(),
+                passphrase,
+            )
+            .await
+            .map_err(backup_err)?;
+
+        Ok(Response::new(BackupKeysResponse {
+            files: u32::try_from(manifest.files.len()).unwrap_or(u32::MAX),
+            beacon_ids: manifest.beacon_ids,
+            path: out.display().to_string(),
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl Metrics for ControlHandler {
+    /// Returns daemon metrics in Prometheus text exposition format.
+    async fn metrics(
+        &self,
+        _request: Request<MetricsRequest>,
+    ) -> Result<Response<MetricsResponse>, Status> {
+        Ok(Response::new(MetricsResponse {
+            metrics: (self.bandwidth.metrics()
+                + &self.backups.metrics()
+                + &self.beacons().events().metrics())
+                .into_bytes(),
+        }))
     }
 }
 
@@ -281,6 +636,7 @@ pub async fn start_server<N: NewTcpListener>(
 
     Server::builder()
         .add_service(ControlServer::new(ControlHandler(daemon.clone())))
+        .add_service(MetricsServer::new(ControlHandler(daemon.clone())))
         .add_service(DkgControlServer::new(DkgControlHandler::new(
             daemon.clone(),
         )))
@@ -324,20 +680,53 @@ impl ControlClient {
     pub async fn status(&mut self, beacon_id: String) -> anyhow::Result<StatusResponse> {
         let request = StatusRequest {
             metadata: Some(Metadata::with_id(beacon_id)),
+            ..Default::default()
         };
         let responce = self.client.status(request).await?;
         Ok(responce.into_inner())
     }
 
-    pub async fn load_beacon(&mut self, beacon_id: String) -> anyhow::Result<()> {
+    /// Returns statuses of given addresses, all members of the latest group if `addresses` is empty.
+    pub async fn remote_status(
+        &mut self,
+        beacon_id: String,
+        addresses: Vec<String>,
+    ) -> anyhow::Result<RemoteStatusResponse> {
+        let request = RemoteStatusRequest {
+            metadata: Some(Metadata::with_id(beacon_id)),
+            addresses: addresses
+                .into_iter()
+                .map(|address| protobuf::Address { address })
+                .collect(),
+        };
+        let response = self.client.remote_status(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    pub async fn load_beacon(
+        &mut self,
+        beacon_id: String,
+        folder: Option<String>,
+    ) -> anyhow::Result<()> {
         let request = LoadBeaconRequest {
             metadata: Some(Metadata::with_id(beacon_id)),
+            folder: folder.unwrap_or_default(),
         };
         let _ = self.client.load_beacon(request).await?;
 
         Ok(())
     }
 
+    pub async fn unload_beacon(&mut self, beacon_id: String) -> anyhow::Result<()> {
+        let request = UnloadBeaconRequest {
+            metadata: Some(Metadata::with_id(beacon_id)),
+        };
+        let _ = self.client.unload_beacon(request).await?;
+
+        Ok(())
+    }
+
     pub async fn shutdown(&mut self, beacon_id: Option<String>) -> anyhow::Result<bool> {
         let metadata = beacon_id.map(Metadata::with_id);
         let request = ShutdownRequest { metadata };
@@ -348,32 +737,51 @@ impl ControlClient {
 
     pub async fn sync(&mut self, c: SyncConfig) -> anyhow::Result<()> {
         use std::io::Write;
-        let metadata = Metadata::with_chain_hash(&c.id, &c.chain_hash)?;
+        let metadata = Metadata::with_chain_hash(canonical_beacon_id(&c.id)?, &c.chain_hash)?;
+        let checkpoint_signature = match c.checkpoint_sig {
+            Some(ref sig) => hex::decode(sig)?,
+            None => vec![],
+        };
+        let chain_info = match c.chain_info {
+            Some(ref path) => std::fs::read_to_string(path)?,
+            None => String::new(),
+        };
         let request = StartSyncRequest {
             nodes: c.sync_nodes,
             up_to: if c.follow { 0 } else { c.up_to },
             metadata: Some(metadata),
+            verify_mode: c.verify as u32,
+            spot_check_every: c.spot_check_every,
+            checkpoint_round: c.checkpoint_round,
+            checkpoint_signature,
+            archive_url: c.from_archive.unwrap_or_default(),
+            chain_info,
         };
 
         tracing::info!(
-            "Launching a follow request: nodes: {:?}, upTo: {}, hash {}, beaconID: {}",
+            "Launching a follow request: nodes: {:?}, upTo: {}, hash {}, beaconID: {}, verify: {}",
             request.nodes,
             request.up_to,
             c.chain_hash,
-            c.id
+            c.id,
+            c.verify
         );
 
         let mut responce = self.client.start_follow_chain(request).await?.into_inner();
         let mut spinner = ['/', '—', '\\'].iter().cycle();
 
         while let Ok(Some(progress)) = responce.message().await {
+            if !progress.summary.is_empty() {
+                println!("\nsync summary: {}", progress.summary);
+                continue;
+            }
             if progress.current % 300 == 0 {
                 #[allow(clippy::cast_precision_loss)]
                 let percent = (progress.current as f64 / progress.target as f64) * 100.0;
                 let symbol = spinner.next().expect("infallible");
                 print!(
-                    "\r{}  synced round up to {} - current target {}     --> {:.2} %",
-                    symbol, progress.current, progress.target, percent,
+                    "\r{}  synced round up to {} - current target {}     --> {:.2} %  [verify: {}]",
+                    symbol, progress.current, progress.target, percent, progress.verify_mode,
                 );
                 std::io::stdout().flush()?;
             }
@@ -382,6 +790,120 @@ impl ControlClient {
         Ok(())
     }
 
+    /// Subscribes to daemon events, events of all beacons are received if `beacon_id` is not set.
+    pub async fn events(
+        &mut self,
+        beacon_id: Option<String>,
+    ) -> anyhow::Result<tonic::Streaming<DaemonEvent>> {
+        let request = EventsRequest {
+            metadata: beacon_id.map(Metadata::with_id),
+        };
+        let stream = self.client.events(request).await?.into_inner();
+
+        Ok(stream)
+    }
+
+    pub async fn peer_bandwidth(&mut self) -> anyhow::Result<PeerBandwidthResponse> {
+        let request = PeerBandwidthRequest {
+            metadata: Some(Metadata::with_default()),
+        };
+        let response = self.client.peer_bandwidth(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    /// Returns the round up to which resync is started.
+    pub async fn resync(&mut self, beacon_id: String, from_round: u64) -> anyhow::Result<u64> {
+        let request = ResyncRequest {
+            from_round,
+            metadata: Some(Metadata::with_id(beacon_id)),
+        };
+        let response = self.client.resync(request).await?;
+
+        Ok(response.into_inner().up_to)
+    }
+
+    /// Returns effective log level of the beacon id, empty `level` resets it to the default.
+    pub async fn set_log_level(
+        &mut self,
+        beacon_id: String,
+        level: String,
+    ) -> anyhow::Result<String> {
+        let request = SetLogLevelRequest {
+            level,
+            metadata: Some(Metadata::with_id(beacon_id)),
+        };
+        let response = self.client.set_log_level(request).await?;
+
+        Ok(response.into_inner().level)
+    }
+
+    /// Returns command queues of the beacon id, or of all loaded beacon ids if not set.
+    pub async fn queue_status(
+        &mut self,
+        beacon_id: Option<String>,
+    ) -> anyhow::Result<Vec<CommandQueue>> {
+        let request = QueueStatusRequest {
+            metadata: beacon_id.map(Metadata::with_id),
+        };
+        let response = self.client.queue_status(request).await?;
+
+        Ok(response.into_inner().queues)
+    }
+
+    /// Returns the wiped DKG status, current epoch and backup folder of the reset.
+    pub async fn dkg_reset(
+        &mut self,
+        beacon_id: String,
+        confirm: bool,
+    ) -> anyhow::Result<DkgResetResponse> {
+        let request = DkgResetRequest {
+            metadata: Some(Metadata::with_id(beacon_id)),
+            confirm,
+        };
+        let response = self.client.dkg_reset(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    /// Returns sanitized JSON dump of the daemon, see [`dump::collect`].
+    pub async fn debug_dump(&mut self) -> anyhow::Result<String> {
+        let request = DebugDumpRequest { metadata: None };
+        let response = self.client.debug_dump(request).await?;
+
+        Ok(response.into_inner().dump)
+    }
+
+    /// Returns chain stores of the beacon id, or of all loaded beacon ids if not set.
+    pub async fn store_stats(
+        &mut self,
+        beacon_id: Option<String>,
+    ) -> anyhow::Result<Vec<BeaconStoreStats>> {
+        let request = StoreStatsRequest {
+            metadata: beacon_id.map(Metadata::with_id),
+        };
+        let response = self.client.store_stats(request).await?;
+
+        Ok(response.into_inner().stores)
+    }
+
+    /// Returns archived beacon ids and amount of files, all beacon ids are archived if not set.
+    pub async fn backup(
+        &mut self,
+        beacon_id: Option<String>,
+        output_file: String,
+        passphrase: Option<String>,
+    ) -> anyhow::Result<BackupKeysResponse> {
+        let request = BackupKeysRequest {
+            output_file,
+            metadata: beacon_id.map(Metadata::with_id),
+            passphrase: passphrase.unwrap_or_default(),
+        };
+        let response = self.client.backup_keys(request).await?;
+
+        Ok(response.into_inner())
+    }
+
     pub async fn chain_info(&mut self, beacon_id: String) -> anyhow::Result<ChainInfoPacket> {
         let request = ChainInfoRequest {
             metadata: Some(Metadata::with_id(beacon_id)),
@@ -390,6 +912,17 @@ impl ControlClient {
 
         Ok(info)
     }
+
+    /// Returns the latest group of beacon id.
+    pub async fn group_file(&mut self, beacon_id: String) -> anyhow::Result<GroupPacket> {
+        let request = GroupRequest {
+            metadata: Some(Metadata::with_id(beacon_id)),
+            epoch: 0,
+        };
+        let group = self.client.group_file(request).await?.into_inner();
+
+        Ok(group)
+    }
 }
 
 impl Deref for ControlHandler {
@@ -399,3 +932,66 @@ impl Deref for ControlHandler {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::time::time_now;
+    use crate::chain::time::MockClock;
+    use crate::core::beacon::DEFAULT_BEACON_ID;
+    use crate::core::events::Event;
+    use crate::net::sim::SimNode;
+    use energon::drand::schemes::DefaultScheme;
+    use tokio_stream::StreamExt;
+
+    #[tokio::test]
+    async fn events_are_streamed() {
+        let node = SimNode::start::<DefaultScheme>(
+            DEFAULT_BEACON_ID,
+            b"events",
+            Arc::new(MockClock::new(time_now())),
+        )
+        .unwrap();
+        let request = EventsRequest {
+            metadata: Some(Metadata::with_id(DEFAULT_BEACON_ID.into())),
+        };
+        let mut stream = ControlHandler(node.daemon.clone())
+            .events(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner();
+
+        // Events of other beacon ids are filtered out.
+        let events = node.daemon.beacons().events();
+        events.emit("other", &Event::BeaconStored { round: 1 });
+        events.emit(DEFAULT_BEACON_ID, &Event::BeaconStored { round: 5 });
+        events.emit(
+            DEFAULT_BEACON_ID,
+            &Event::ResyncStarted { from: 6, up_to: 9 },
+        );
+        events.emit(
+            DEFAULT_BEACON_ID,
+            &Event::PeerError {
+                peer: "peer:1",
+                reason: "unexpected round",
+            },
+        );
+
+        let mut received = vec![];
+        while received.len() < 3 {
+            let event = stream.next().await.unwrap().unwrap();
+            assert_eq!(event.beacon_id, DEFAULT_BEACON_ID);
+            if ["beacon_stored", "resync_started", "peer_error"].contains(&event.kind.as_str()) {
+                received.push((event.kind, event.round, event.detail));
+            }
+        }
+        assert_eq!(
+            received,
+            [
+                ("beacon_stored".into(), 5, String::new()),
+                ("resync_started".into(), 6, "up_to 9".into()),
+                ("peer_error".into(), 0, "peer:1: unexpected round".into()),
+            ]
+        );
+    }
+}
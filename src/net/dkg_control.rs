@@ -1,7 +1,6 @@
 //! Client and server implementations for [`DkgControl`] service.
 
 use super::control::CONTROL_HOST;
-use super::utils::Callback;
 use super::utils::ToStatus;
 
 use crate::core::beacon::Actions;
@@ -18,7 +17,9 @@ use protobuf::DkgCommand;
 use protobuf::DkgStatusRequest;
 use protobuf::DkgStatusResponse;
 use protobuf::EmptyDkgResponse;
+use protobuf::ExecutionOptions;
 use protobuf::JoinOptions;
+use protobuf::ProposalOptions;
 
 use tonic::transport::Channel;
 use tonic::Request;
@@ -45,15 +46,11 @@ impl DkgControl for DkgControlHandler {
     ) -> Result<Response<EmptyDkgResponse>, Status> {
         let inner = request.into_inner().validate()?;
         let id = inner.metadata.beacon_id.as_str();
-        let (tx, rx) = Callback::new();
-        let cmd = Actions::Command(inner.command, tx);
-
         self.beacons()
-            .cmd(BeaconCmd::DkgActions(cmd), id)
+            .call(id, "dkg command", |tx| {
+                BeaconCmd::DkgActions(Actions::Command(inner.command, tx))
+            })
             .await
-            .map_err(|err| err.to_status(id))?;
-
-        rx.await
             .map_err(|err| err.to_status(id))?
             .map_err(|err| err.to_status(id))?;
 
@@ -65,14 +62,11 @@ impl DkgControl for DkgControlHandler {
         request: Request<DkgStatusRequest>,
     ) -> Result<Response<DkgStatusResponse>, tonic::Status> {
         let id = request.get_ref().beacon_id.as_str();
-        let (tx, rx) = Callback::new();
-
-        self.beacons()
-            .cmd(BeaconCmd::DkgActions(Actions::Status(tx)), id)
-            .await
-            .map_err(|err| err.to_status(id))?;
-
-        let responce = rx
+        let responce = self
+            .beacons()
+            .call(id, "dkg status", |tx| {
+                BeaconCmd::DkgActions(Actions::Status(tx))
+            })
             .await
             .map_err(|err| err.to_status(id))?
             .map_err(|err| err.to_status(id))?;
@@ -121,6 +115,32 @@ impl DkgControlClient {
         Ok(())
     }
 
+    /// Proposes a reshare, only the leader of the network accepts the command.
+    pub async fn dkg_reshare(
+        &mut self,
+        beacon_id: String,
+        options: ProposalOptions,
+    ) -> anyhow::Result<()> {
+        let request = DkgCommand {
+            metadata: Some(CommandMetadata { beacon_id }),
+            command: Some(protobuf::dkg_command::Command::Resharing(options)),
+        };
+        let _ = self.client.command(request).await?;
+
+        Ok(())
+    }
+
+    /// Starts execution of accepted proposal, only the leader of the network accepts the command.
+    pub async fn dkg_execute(&mut self, beacon_id: String) -> anyhow::Result<()> {
+        let request = DkgCommand {
+            metadata: Some(CommandMetadata { beacon_id }),
+            command: Some(protobuf::dkg_command::Command::Execute(ExecutionOptions {})),
+        };
+        let _ = self.client.command(request).await?;
+
+        Ok(())
+    }
+
     pub async fn dkg_accept(&mut self, beacon_id: String) -> anyhow::Result<()> {
         let request = DkgCommand {
             metadata: Some(CommandMetadata { beacon_id }),
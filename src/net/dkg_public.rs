@@ -1,7 +1,9 @@
 //! Client and server implementations [`DkgPublic`] service.
 
+use super::utils::peer_channel;
 use super::utils::Address;
 use super::utils::Callback;
+use super::utils::PeerChannel;
 use super::utils::ToStatus;
 use crate::core::beacon::Actions;
 use crate::core::beacon::BeaconCmd;
@@ -14,7 +16,6 @@ use protobuf::dkg_public_server::DkgPublic;
 use protobuf::DkgPacket;
 use protobuf::EmptyDkgResponse;
 use protobuf::GossipPacket;
-use tonic::transport::Channel;
 
 use std::ops::Deref;
 use std::sync::Arc;
@@ -71,16 +72,21 @@ impl DkgPublic for DkgPublicHandler {
 }
 
 pub struct DkgPublicClient {
-    client: _DkgPublicClient<Channel>,
+    client: _DkgPublicClient<PeerChannel>,
 }
 
 impl DkgPublicClient {
     pub async fn new(address: &Address) -> anyhow::Result<Self> {
         let channel = super::utils::connect(address).await?;
-        let client = _DkgPublicClient::new(channel);
+        let client = _DkgPublicClient::new(peer_channel(channel, address));
         Ok(Self { client })
     }
 
+    pub async fn packet(&mut self, packet: GossipPacket) -> anyhow::Result<()> {
+        let _ = self.client.packet(packet).await?;
+        Ok(())
+    }
+
     pub async fn broadcast_dkg(&mut self, packet: DkgPacket) -> anyhow::Result<()> {
         let _ = self.client.broadcast_dkg(packet).await?;
         Ok(())
@@ -0,0 +1,305 @@
+//! Error taxonomy of RPC handlers with canonical mapping to [`Code`].
+//!
+//! Every error returned by beacon actors implements [`ErrorCode`], so the same failure is
+//! reported with the same code by all services and clients can branch on the code:
+//! - `InvalidArgument`: malformed request, invalid proposal terms, unknown verification mode;
+//! - `NotFound`: beacon id is not loaded, requested beacon or file does not exist;
+//! - `AlreadyExists`: beacon id is already loaded or imported;
+//! - `FailedPrecondition`: request is valid but not in the current state, e.g. no DKG setup yet
+//!   or DKG action not allowed in the current DKG status;
+//! - `PermissionDenied`: requester is not a member or signature is invalid;
+//! - `ResourceExhausted`: limits of concurrent requests are reached;
+//! - `DeadlineExceeded`: beacon process did not reply to control command in time;
+//! - `Unavailable`: beacon actor is stopping or peers are not reachable, request can be retried;
+//! - `DataLoss`: stored data is corrupted;
+//! - `Internal`: bug or local I/O failure.
+use super::utils::InvalidAddress;
+use super::utils::ERR_METADATA_IS_MISSING;
+
+use crate::chain::ChainError;
+use crate::chain::StoreError;
+use crate::chain::SyncError;
+use crate::core::beacon::BeaconIdError;
+use crate::core::multibeacon::BeaconHandlerError;
+use crate::core::remote_status::RemoteStatusError;
+use crate::dkg::reset::ResetError;
+use crate::dkg::state::DBStateError;
+use crate::dkg::ActionsError;
+use crate::key::backup::BackupError;
+use crate::key::store::FileStoreError;
+use crate::key::PointSerDeError;
+use crate::log::InvalidLevel;
+
+use tokio::sync::oneshot::error::RecvError;
+use tonic::Code;
+use tonic::Status;
+
+/// Canonical status code of the error.
+pub trait ErrorCode {
+    fn code(&self) -> Code;
+}
+
+/// Errors of RPC handlers which are not bound to a single module.
+#[derive(thiserror::Error, Debug)]
+pub enum NodeError {
+    #[error("{ERR_METADATA_IS_MISSING}")]
+    MetadataMissing,
+    #[error(transparent)]
+    BeaconId(#[from] BeaconIdError),
+    #[error(transparent)]
+    Handler(#[from] BeaconHandlerError),
+    #[error(transparent)]
+    Address(#[from] InvalidAddress),
+    #[error(transparent)]
+    LogLevel(#[from] InvalidLevel),
+}
+
+impl ErrorCode for NodeError {
+    fn code(&self) -> Code {
+        match self {
+            Self::MetadataMissing => Code::InvalidArgument,
+            Self::BeaconId(err) => err.code(),
+            Self::Handler(err) => err.code(),
+            Self::Address(err) => err.code(),
+            Self::LogLevel(_) => Code::InvalidArgument,
+        }
+    }
+}
+
+impl From<NodeError> for Status {
+    fn from(err: NodeError) -> Self {
+        Status::new(err.code(), err.to_string())
+    }
+}
+
+impl ErrorCode for RecvError {
+    /// Callback sender is dropped without sending, the actor is stopped.
+    fn code(&self) -> Code {
+        Code::Unavailable
+    }
+}
+
+impl ErrorCode for BeaconIdError {
+    fn code(&self) -> Code {
+        Code::InvalidArgument
+    }
+}
+
+impl ErrorCode for InvalidAddress {
+    fn code(&self) -> Code {
+        Code::InvalidArgument
+    }
+}
+
+impl ErrorCode for PointSerDeError {
+    fn code(&self) -> Code {
+        Code::InvalidArgument
+    }
+}
+
+impl ErrorCode for BeaconHandlerError {
+    fn code(&self) -> Code {
+        match self {
+            Self::UnknownID => Code::NotFound,
+            Self::SendError => Code::Unavailable,
+            Self::AlreadyLoaded => Code::AlreadyExists,
+            Self::Import(err) => err.code(),
+            Self::MetadataRequired => Code::InvalidArgument,
+            Self::Timeout { .. } => Code::DeadlineExceeded,
+            Self::NoReply(_) => Code::Unavailable,
+        }
+    }
+}
+
+impl ErrorCode for StoreError {
+    fn code(&self) -> Code {
+        match self {
+            Self::NotFound => Code::NotFound,
+            Self::GenesisMismatch => Code::FailedPrecondition,
+            Self::Corrupt(_) => Code::DataLoss,
+            Self::ActorClosedRx | Self::CbClosedTx(_) => Code::Unavailable,
+            Self::Internal => Code::Internal,
+        }
+    }
+}
+
+impl ErrorCode for FileStoreError {
+    fn code(&self) -> Code {
+        match self {
+            Self::FileNotFound(_) | Self::BeaconNotFound => Code::NotFound,
+            Self::FileAlreadyExists(_) => Code::AlreadyExists,
+            Self::InvalidData
+            | Self::TomlError
+            | Self::InvalidPairSchemes
+            | Self::FailedInitID
+            | Self::FailedToReadID
+            | Self::PointSerDe(_) => Code::DataLoss,
+            Self::ChainStore(err) => err.code(),
+            Self::IO(_) | Self::DkgStore(_) => Code::Internal,
+        }
+    }
+}
+
+impl ErrorCode for BackupError {
+    fn code(&self) -> Code {
+        match self {
+            Self::FileStore(err) => err.code(),
+            Self::IO(err) if err.kind() == std::io::ErrorKind::AlreadyExists => Code::AlreadyExists,
+            Self::IO(_) => Code::Internal,
+            Self::PassphraseRequired | Self::InvalidOutput(_) => Code::InvalidArgument,
+            Self::InvalidManifest(_)
+            | Self::UnsupportedVersion(_)
+            | Self::Checksum(_)
+            | Self::Decrypt(..) => Code::DataLoss,
+        }
+    }
+}
+
+impl ErrorCode for ChainError {
+    fn code(&self) -> Code {
+        match self {
+            Self::InvalidShareLenght { .. }
+            | Self::UnknownIndex(_)
+            | Self::InvalidPartialSignature
+            | Self::InvalidRound { .. }
+            | Self::InvalidResyncFrom { .. } => Code::InvalidArgument,
+            Self::SpoofedIndex { .. } => Code::PermissionDenied,
+            Self::DkgSetupRequired => Code::FailedPrecondition,
+            Self::PartialClosedTx
+            | Self::CmdClosedTx
+            | Self::CmdClosedRx
+            | Self::TickerClosedTx
+            | Self::PoolClosedRx => Code::Unavailable,
+            Self::ChainStoreError(err) => err.code(),
+            Self::FileStoreError(err) => err.code(),
+            Self::FailedToGetInfo
+            | Self::SerializeRecovered
+            | Self::InvalidRecovered
+            | Self::TBlsError(_) => Code::Internal,
+        }
+    }
+}
+
+impl ErrorCode for SyncError {
+    fn code(&self) -> Code {
+        match self {
+            Self::InvalidInfoPacket
+            | Self::PeersInvalidFormat
+            | Self::InvalidRelay(_)
+            | Self::ChainHashMismatch(_)
+            | Self::InvalidTarget { .. }
+            | Self::InvalidVerifyMode(_)
+            | Self::InvalidCheckpoint => Code::InvalidArgument,
+            Self::InfoPacketMismatch | Self::AlreadySyncing | Self::ForbiddenToFollow => {
+                Code::FailedPrecondition
+            }
+            Self::Archive(_)
+            | Self::FailedInfoFromAllPeers
+            | Self::SyncClosedTx
+            | Self::TriedAllPers { .. }
+            | Self::HttpApi => Code::Unavailable,
+            Self::ChainStore(err) => err.code(),
+            Self::Internal => Code::Internal,
+        }
+    }
+}
+
+impl ErrorCode for RemoteStatusError {
+    fn code(&self) -> Code {
+        match self {
+            Self::NotMember(_) | Self::InvalidSignature | Self::Expired => Code::PermissionDenied,
+            Self::Group(err) => err.code(),
+            Self::Store(err) => err.code(),
+            Self::ChainClosed => Code::Unavailable,
+            Self::Sign => Code::Internal,
+        }
+    }
+}
+
+impl ErrorCode for DBStateError {
+    fn code(&self) -> Code {
+        match self {
+            Self::MissingTerms
+            | Self::InvalidBeaconID
+            | Self::InvalidScheme
+            | Self::InvalidEpoch
+            | Self::Terms(_)
+            | Self::ConversionError(_)
+            | Self::ParticipantSignature
+            | Self::InvalidAcceptor
+            | Self::InvalidRejector
+            | Self::EmptyGroup => Code::InvalidArgument,
+            // Remaining errors are violations of DKG rules for the current state.
+            _ => Code::FailedPrecondition,
+        }
+    }
+}
+
+impl ErrorCode for ActionsError {
+    fn code(&self) -> Code {
+        match self {
+            Self::DBState(err) => err.code(),
+            Self::InvalidSignature => Code::PermissionDenied,
+            Self::StartExecutionTimeNotCanonical
+            | Self::StartExecutionTimeIsPassed
+            | Self::GroupFileParse => Code::InvalidArgument,
+            Self::MissingParticipant
+            | Self::ProtocolIsNotRunning
+            | Self::ProtocolAlreadyRunning
+            | Self::GroupfileIsMissing
+            | Self::ResharePrevGroupRequired
+            | Self::ResharePrevShareRequired => Code::FailedPrecondition,
+            Self::PipelineIsFull => Code::ResourceExhausted,
+            Self::StaleVerification => Code::Aborted,
+            Self::Reset(err) => err.code(),
+            Self::Todo => Code::Unimplemented,
+            Self::DKGStore(_)
+            | Self::IntoParticipant
+            | Self::ParticipantsToNewNodes
+            | Self::DkgError(_)
+            | Self::Sign => Code::Internal,
+        }
+    }
+}
+
+impl ErrorCode for ResetError {
+    fn code(&self) -> Code {
+        match self {
+            Self::NotConfirmed | Self::InProgress(_) | Self::NothingToReset(_) => {
+                Code::FailedPrecondition
+            }
+            Self::KeyStore(err) => err.code(),
+            Self::Backup(_) | Self::DKGStore(_) => Code::Internal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::utils::ToStatus;
+
+    #[test]
+    fn canonical_codes() {
+        let status = BeaconHandlerError::UnknownID.to_status("quicknet");
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(
+            status.message(),
+            "beacon id 'quicknet', Beacon id is not found"
+        );
+
+        // Wrapped errors keep the code of the source.
+        let err = ChainError::ChainStoreError(StoreError::Corrupt(5));
+        assert_eq!(err.to_status("default").code(), Code::DataLoss);
+        let err = ActionsError::DBState(DBStateError::DuplicateAcceptance);
+        assert_eq!(err.to_status("default").code(), Code::FailedPrecondition);
+        assert_eq!(
+            SyncError::AlreadySyncing.to_status("default").code(),
+            Code::FailedPrecondition
+        );
+        assert_eq!(
+            Status::from(NodeError::MetadataMissing).code(),
+            Code::InvalidArgument
+        );
+    }
+}
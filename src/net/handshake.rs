@@ -0,0 +1,208 @@
+//! Compatibility check of peer versions and protocol features.
+//!
+//! Nodes of this implementation report [`FEATURES`] in packet metadata. Golang nodes do not
+//! report features, and drand-rs nodes report golang version in some requests to pass the
+//! version check of golang nodes, so versions are compared as follows:
+//! - peer reports features: drand-rs node, versions are compared only below `1.0.0`.
+//! - peer does not report features: golang node if major version is at least `1`, otherwise
+//!   drand-rs node which predates the handshake.
+//!
+//! Before `1.0.0` drand-rs nodes are compatible only within the same minor version, requests of
+//! such peers are rejected, see [`IncompatiblePeer::is_fatal`]. Other mismatches are only logged:
+//! golang nodes of other major versions and peers without version are served as before.
+use super::protocol::ProtocolClient;
+use super::utils::Address;
+use super::utils::VERSION;
+
+use crate::protobuf::drand::Metadata;
+use crate::protobuf::drand::NodeVersion;
+
+use tonic::Code;
+use tracing::debug;
+use tracing::error;
+use tracing::warn;
+use tracing::Span;
+
+/// Protocol features supported by this node.
+pub const FEATURES: &[&str] = &[
+    "ping",
+    "group_for_epoch",
+    "peer_status",
+    "leave_notice",
+    FALLBACK_ABORT,
+];
+
+/// Feature of nodes accepting abort of a pending DKG proposal from the fallback coordinator,
+/// see `src/dkg/recovery.rs`. Golang nodes accept abort only from the leader.
+pub const FALLBACK_ABORT: &str = "fallback_abort";
+
+/// Optional feature of nodes accepting partial beacons over QUIC, see `src/net/quic.rs`.
+/// QUIC is used only with peers reporting it.
+pub const QUIC: &str = "quic";
+
+/// Supported major version of golang nodes.
+const GOLANG_MAJOR: u32 = 2;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum IncompatiblePeer {
+    #[error(
+        "peer runs drand-rs {peer}, incompatible with local {local}: minor versions must match"
+    )]
+    Version {
+        peer: NodeVersion,
+        local: NodeVersion,
+    },
+    #[error("peer runs golang drand {0}, only v{GOLANG_MAJOR} nodes are supported")]
+    Golang(NodeVersion),
+    #[error("peer does not report its version")]
+    MissingVersion,
+}
+
+impl IncompatiblePeer {
+    /// Returns `true` if requests of the peer are rejected, other mismatches are only logged.
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, Self::Version { .. })
+    }
+}
+
+/// Returns features reported in packet metadata.
+pub fn features() -> Vec<String> {
+    let features = FEATURES.iter().map(ToString::to_string);
+    #[cfg(feature = "quic")]
+    let features = features.chain(std::iter::once(QUIC.to_string()));
+
+    features.collect()
+}
+
+/// Checks version in peer metadata, returns local features which are not supported by peer.
+pub fn check(meta: &Metadata) -> Result<Vec<&'static str>, IncompatiblePeer> {
+    let version = meta
+        .node_version
+        .as_ref()
+        .ok_or(IncompatiblePeer::MissingVersion)?;
+
+    if meta.features.is_empty() {
+        if version.major == 0 {
+            // drand-rs node predating the handshake supports none of the features.
+            return Ok(FEATURES.to_vec());
+        }
+        if version.major != GOLANG_MAJOR {
+            return Err(IncompatiblePeer::Golang(version.clone()));
+        }
+        return Ok(vec![]);
+    }
+    if version.major == 0 && (version.major, version.minor) != (VERSION.major, VERSION.minor) {
+        return Err(IncompatiblePeer::Version {
+            peer: version.clone(),
+            local: VERSION,
+        });
+    }
+
+    Ok(FEATURES
+        .iter()
+        .copied()
+        .filter(|f| !meta.features.iter().any(|peer_f| peer_f == f))
+        .collect())
+}
+
+/// Pings connected peer and logs the result of [`check`], errors are not fatal.
+/// Returns features reported by peer, empty if they are unknown.
+///
+/// Peers which do not implement ping are golang nodes or drand-rs nodes predating it,
+/// their version is checked on the first request they send.
+pub async fn handshake(client: &mut ProtocolClient, peer: &Address, l: &Span) -> Vec<String> {
+    let response = match client.ping().await {
+        Ok((response, _)) => response,
+        Err(err) => {
+            if err
+                .downcast_ref::<tonic::Status>()
+                .is_some_and(|s| s.code() == Code::Unimplemented)
+            {
+                debug!(parent: l, "handshake: {peer} does not implement ping");
+            } else {
+                debug!(parent: l, "handshake: {peer}: {err}");
+            }
+            return vec![];
+        }
+    };
+    let Some(meta) = response.metadata else {
+        error!(parent: l, "handshake: {peer}: {}", IncompatiblePeer::MissingVersion);
+        return vec![];
+    };
+
+    match check(&meta) {
+        Ok(missing) if missing.is_empty() => debug!(parent: l, "handshake: {peer} is compatible"),
+        Ok(missing) => {
+            let missing = missing.join(", ");
+            warn!(parent: l, "handshake: {peer} does not support {missing}, related requests fail");
+        }
+        Err(err) if err.is_fatal() => error!(parent: l, "handshake: {peer}: {err}"),
+        Err(err) => warn!(parent: l, "handshake: {peer}: {err}"),
+    }
+
+    meta.features
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(major: u32, minor: u32, features: &[&str]) -> Metadata {
+        Metadata {
+            node_version: Some(NodeVersion {
+                major,
+                minor,
+                patch: 5,
+                prerelease: String::new(),
+            }),
+            features: features.iter().map(ToString::to_string).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn check_peers() {
+        // drand-rs peers
+        assert_eq!(
+            check(&meta(VERSION.major, VERSION.minor, FEATURES)),
+            Ok(vec![])
+        );
+        assert_eq!(
+            check(&meta(VERSION.major, VERSION.minor, &["ping"])),
+            Ok(FEATURES[1..].to_vec())
+        );
+        let mismatch = check(&meta(0, VERSION.minor + 1, FEATURES)).unwrap_err();
+        assert!(matches!(mismatch, IncompatiblePeer::Version { .. }));
+        assert!(mismatch.is_fatal());
+        // Optional features are not required from peers.
+        assert_eq!(
+            check(&meta(
+                VERSION.major,
+                VERSION.minor,
+                &[&FEATURES[..], &[QUIC]].concat()
+            )),
+            Ok(vec![])
+        );
+        assert_eq!(features().iter().any(|f| f == QUIC), cfg!(feature = "quic"));
+        // Version is not compared if drand-rs peer reports golang version.
+        assert_eq!(check(&meta(2, 1, FEATURES)), Ok(vec![]));
+        // drand-rs peers predating the handshake, minor version is not compared.
+        assert_eq!(
+            check(&meta(VERSION.major, VERSION.minor, &[])),
+            Ok(FEATURES.to_vec())
+        );
+        assert_eq!(
+            check(&meta(0, VERSION.minor + 1, &[])),
+            Ok(FEATURES.to_vec())
+        );
+
+        // golang peers, mismatches are not fatal.
+        assert_eq!(check(&meta(2, 1, &[])), Ok(vec![]));
+        let old = check(&meta(1, 5, &[])).unwrap_err();
+        assert!(matches!(old, IncompatiblePeer::Golang(_)));
+        assert!(!old.is_fatal());
+        let missing = check(&Metadata::default()).unwrap_err();
+        assert_eq!(missing, IncompatiblePeer::MissingVersion);
+        assert!(!missing.is_fatal());
+    }
+}
@@ -0,0 +1,645 @@
+//! HTTP JSON API of public beacons, with paths and fields of Go drand relays.
+//!
+//! Routes, chain is either addressed by its hash or is the default chain:
+//! - `GET /chains`: hex encoded hashes of loaded chains;
+//! - `GET /[<chain hash>/]info`: chain info;
+//! - `GET /[<chain hash>/]public/latest`, `GET /[<chain hash>/]public/<round>`: beacon, as JSON of
+//!   `api.drand.sh` or in another format given by `?format=json|hex|base64|raw`;
+//! - `GET /metrics`: metrics in Prometheus text format, if the backend has any.
+//!
+//! Responses are suitable for CDNs: beacons of requested rounds never change and are cached as
+//! immutable, the latest beacon expires once the round after it is due, or within a second if
+//! that round is late, the set of chains is not cached. CORS headers are sent for configured
+//! origins only, `*` allows any origin, other lists make responses vary on `Origin`.
+//!
+//! Beacons and chain info carry ETags derived from the round and the chain hash, clients polling
+//! the latest beacon receive `304 Not Modified` with an empty body until the next round.
+//!
+//! Chains are served by a [`Backend`], the daemon serves loaded beacon processes and the relay
+//! profile serves the followed chain, see [`crate::chain::run_relay`].
+use super::public::PublicHandler;
+use super::utils::Address;
+use super::utils::StartServerError;
+use crate::chain::format::BeaconFormat;
+use crate::chain::time;
+use crate::core::beacon::DEFAULT_BEACON_ID;
+use crate::protobuf::drand::public_server::Public;
+use crate::protobuf::drand::ChainInfoPacket;
+use crate::protobuf::drand::ChainInfoRequest;
+use crate::protobuf::drand::Metadata;
+use crate::protobuf::drand::PublicRandRequest;
+use crate::protobuf::drand::PublicRandResponse;
+
+use http::header;
+use http::HeaderMap;
+use http::HeaderValue;
+use http::Method;
+use http::StatusCode;
+use http_body_util::Full;
+use hyper::body::Incoming;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper_util::rt::TokioIo;
+use prost::bytes::Bytes;
+use serde_json::json;
+use serde_json::Value;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tonic::Code;
+use tonic::Status;
+use tracing::debug;
+use tracing::error;
+
+type Request = http::Request<Incoming>;
+type Response = http::Response<Full<Bytes>>;
+
+/// Cache header of responses which never change.
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+/// Cache header of error responses.
+const NO_CACHE: &str = "no-cache";
+/// Content type of JSON responses.
+const JSON: &str = "application/json";
+/// Lifetime of CORS preflight responses.
+const PREFLIGHT_MAX_AGE: &str = "86400";
+
+/// Configuration of HTTP API, see `--public-listen` of `drand start`.
+#[derive(Clone, Default)]
+pub struct HttpConfig {
+    /// Allowed CORS origins, `*` allows any origin.
+    pub cors_origins: Vec<String>,
+}
+
+/// Source of chains served by the HTTP API.
+#[tonic::async_trait]
+pub trait Backend: Send + Sync + 'static {
+    /// Returns beacon ids of served chains.
+    fn ids(&self) -> Vec<String>;
+
+    /// Returns beacon id of the chain served without chain hash in the path.
+    fn default_id(&self) -> String {
+        DEFAULT_BEACON_ID.to_string()
+    }
+
+    /// Returns metrics in Prometheus text exposition format, `None` if not supported.
+    fn metrics(&self) -> Option<String> {
+        None
+    }
+
+    async fn chain_info(&self, id: &str) -> Result<ChainInfoPacket, Status>;
+
+    /// Returns beacon of the round, round 0 stands for the latest beacon.
+    async fn public_rand(&self, id: &str, round: u64) -> Result<PublicRandResponse, Status>;
+}
+
+#[tonic::async_trait]
+impl Backend for PublicHandler {
+    fn ids(&self) -> Vec<String> {
+        self.beacons()
+            .snapshot()
+            .iter()
+            .map(|h| h.id().as_str().to_string())
+            .collect()
+    }
+
+    async fn chain_info(&self, id: &str) -> Result<ChainInfoPacket, Status> {
+        let request = ChainInfoRequest {
+            metadata: Some(Metadata::with_id(id.to_string())),
+        };
+
+        Ok(Public::chain_info(self, tonic::Request::new(request))
+            .await?
+            .into_inner())
+    }
+
+    async fn public_rand(&self, id: &str, round: u64) -> Result<PublicRandResponse, Status> {
+        let request = PublicRandRequest {
+            round,
+            metadata: Some(Metadata::with_id(id.to_string())),
+        };
+
+        Ok(Public::public_rand(self, tonic::Request::new(request))
+            .await?
+            .into_inner())
+    }
+}
+
+pub async fn start_server<B: Backend>(
+    backend: B,
+    listen: Address,
+    config: HttpConfig,
+    token: CancellationToken,
+    tracker: TaskTracker,
+) -> Result<(), StartServerError> {
+    let listener = TcpListener::bind(listen.as_str()).await.map_err(|err| {
+        error!("listener: {}, {err}", StartServerError::FailedToStartHttp);
+        StartServerError::FailedToStartHttp
+    })?;
+    let api = Arc::new(Api { backend, config });
+
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    debug!("http api: failed to accept connection: {err}");
+                    continue;
+                }
+            },
+            () = token.cancelled() => break,
+        };
+        let api = api.clone();
+        let service = service_fn(move |request| {
+            let api = api.clone();
+            async move { Ok::<_, Infallible>(api.handle(request).await) }
+        });
+        tracker.spawn(async move {
+            if let Err(err) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                debug!("http api: connection closed: {err}");
+            }
+        });
+    }
+    debug!("http api is shutting down");
+
+    Ok(())
+}
+
+struct Api<B> {
+    backend: B,
+    config: HttpConfig,
+}
+
+/// Chain of the request.
+#[derive(Debug, PartialEq)]
+enum Chain<'a> {
+    Default,
+    Hash(&'a str),
+}
+
+#[derive(Debug, PartialEq)]
+enum Route<'a> {
+    Chains,
+    Metrics,
+    Info(Chain<'a>),
+    /// Round 0 stands for the latest beacon.
+    Beacon(Chain<'a>, u64),
+}
+
+fn route(path: &str) -> Option<Route<'_>> {
+    let parts: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let (chain, rest) = match parts.as_slice() {
+        ["chains"] => return Some(Route::Chains),
+        ["metrics"] => return Some(Route::Metrics),
+        [hash, rest @ ..] if hash.len() == 64 && hex::decode(hash).is_ok() => {
+            (Chain::Hash(hash), rest)
+        }
+        rest => (Chain::Default, rest),
+    };
+
+    match rest {
+        ["info"] => Some(Route::Info(chain)),
+        ["public", "latest"] => Some(Route::Beacon(chain, 0)),
+        ["public", round] => match round.parse() {
+            Ok(0) | Err(_) => None,
+            Ok(round) => Some(Route::Beacon(chain, round)),
+        },
+        _ => None,
+    }
+}
+
+/// Returns seconds until the round after the latest stored one is due, at least one: a lagging
+/// chain is revalidated every second until the next beacon is stored.
+fn latest_max_age(now: u64, period: u32, genesis: u64, latest: u64) -> u64 {
+    time::time_of_round(period, genesis, latest + 1)
+        .saturating_sub(now)
+        .max(1)
+}
+
+/// Returns ETag of the beacon, beacons are never modified so the chain hash and the round
+/// identify the content.
+fn beacon_etag(chain_hash: &[u8], round: u64, format: BeaconFormat) -> String {
+    let hash = hex::encode(chain_hash);
+    match format {
+        BeaconFormat::Json => format!("\"{hash}-{round}\""),
+        _ => format!("\"{hash}-{round}-{format}\""),
+    }
+}
+
+/// Returns format of beacons requested by the `format` query parameter, other parameters are ignored.
+fn beacon_format(query: Option<&str>) -> Result<BeaconFormat, String> {
+    query
+        .unwrap_or_default()
+        .split('&')
+        .filter_map(|pair| pair.strip_prefix("format="))
+        .last()
+        .map_or(Ok(BeaconFormat::Json), str::parse)
+}
+
+/// Returns true if the `If-None-Match` header of request matches the ETag.
+fn etag_matches(if_none_match: Option<&str>, etag: &str) -> bool {
+    if_none_match.is_some_and(|header| {
+        header
+            .split(',')
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == etag)
+    })
+}
+
+/// Returns allowed origin for the `Origin` header of request.
+fn allowed_origin(origins: &[String], origin: Option<&str>) -> Option<String> {
+    if origins.iter().any(|o| o == "*") {
+        return Some("*".into());
+    }
+    let origin = origin?;
+
+    origins.iter().any(|o| o == origin).then(|| origin.into())
+}
+
+/// Sets CORS headers of the response. Responses depend on `Origin` unless all origins are
+/// allowed, so `Vary: Origin` is set even if the origin is not allowed: a shared cache must
+/// not serve a response without CORS headers to an allowed origin.
+fn set_cors(origins: &[String], origin: Option<&str>, headers: &mut HeaderMap) {
+    if let Some(allowed) = allowed_origin(origins, origin) {
+        if let Ok(value) = HeaderValue::from_str(&allowed) {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, value);
+        }
+    }
+    if !origins.is_empty() && !origins.iter().any(|o| o == "*") {
+        headers.insert(header::VARY, HeaderValue::from_static("Origin"));
+    }
+}
+
+impl<B: Backend> Api<B> {
+    async fn handle(&self, request: Request) -> Response {
+        let origin = request
+            .headers()
+            .get(header::ORIGIN)
+            .and_then(|o| o.to_str().ok())
+            .map(String::from);
+        let if_none_match = request
+            .headers()
+            .get(header::IF_NONE_MATCH)
+            .and_then(|tag| tag.to_str().ok());
+        let mut response = match *request.method() {
+            Method::OPTIONS => preflight(),
+            Method::GET | Method::HEAD => {
+                match (
+                    route(request.uri().path()),
+                    beacon_format(request.uri().query()),
+                ) {
+                    (Some(route), Ok(format)) => self.serve(route, format, if_none_match).await,
+                    (Some(_), Err(err)) => error_response(StatusCode::BAD_REQUEST, &err),
+                    (None, _) => error_response(StatusCode::NOT_FOUND, "not found"),
+                }
+            }
+            _ => error_response(StatusCode::METHOD_NOT_ALLOWED, "method not allowed"),
+        };
+
+        set_cors(
+            &self.config.cors_origins,
+            origin.as_deref(),
+            response.headers_mut(),
+        );
+        if request.method() == Method::HEAD {
+            *response.body_mut() = Full::default();
+        }
+
+        response
+    }
+
+    async fn serve(
+        &self,
+        route: Route<'_>,
+        format: BeaconFormat,
+        if_none_match: Option<&str>,
+    ) -> Response {
+        let result = match route {
+            Route::Metrics => {
+                return match self.backend.metrics() {
+                    Some(metrics) => text_response(metrics),
+                    None => error_response(StatusCode::NOT_FOUND, "not found"),
+                }
+            }
+            Route::Chains => self.chains().await.map(|hashes| Reply {
+                body: json!(hashes).to_string().into(),
+                content_type: JSON,
+                cache: NO_CACHE.into(),
+                etag: None,
+            }),
+            Route::Info(chain) => self.resolve(&chain).await.map(|(_, info)| Reply {
+                body: json!(info).to_string().into(),
+                content_type: JSON,
+                cache: IMMUTABLE.into(),
+                etag: Some(format!("\"{}\"", hex::encode(&info.hash))),
+            }),
+            Route::Beacon(chain, round) => self.beacon(&chain, round, format).await,
+        };
+
+        match result {
+            Ok(reply) => reply.into_response(if_none_match),
+            Err(status) => error_response(http_status(status.code()), status.message()),
+        }
+    }
+
+    async fn chains(&self) -> Result<Vec<String>, Status> {
+        let mut hashes = vec![];
+        for id in self.backend.ids() {
+            hashes.push(hex::encode(self.backend.chain_info(&id).await?.hash));
+        }
+
+        Ok(hashes)
+    }
+
+    /// Returns beacon of the round, the beacon is read even if the client knows its ETag: the
+    /// round might be not yet produced or missing in the store.
+    async fn beacon(
+        &self,
+        chain: &Chain<'_>,
+        round: u64,
+        format: BeaconFormat,
+    ) -> Result<Reply, Status> {
+        let (id, info) = self.resolve(chain).await?;
+        let beacon = self.backend.public_rand(&id, round).await?;
+
+        let cache = if round == 0 {
+            let genesis = u64::try_from(info.genesis_time).unwrap_or_default();
+            let now = time::time_now().as_secs();
+            let max_age = latest_max_age(now, info.period, genesis, beacon.round);
+            format!("public, max-age={max_age}, must-revalidate")
+        } else {
+            IMMUTABLE.into()
+        };
+        // Previous signature is absent for unchained schemes.
+        let body = format.encode(
+            beacon.round,
+            &beacon.randomness,
+            &beacon.signature,
+            &beacon.previous_signature,
+        );
+
+        Ok(Reply {
+            body: body.into(),
+            content_type: format.content_type(),
+            cache,
+            etag: Some(beacon_etag(&info.hash, beacon.round, format)),
+        })
+    }
+
+    /// Returns beacon id and chain info of the chain.
+    async fn resolve(&self, chain: &Chain<'_>) -> Result<(String, ChainInfoPacket), Status> {
+        match chain {
+            Chain::Default => {
+                let id = self.backend.default_id();
+                let info = self.backend.chain_info(&id).await?;
+                Ok((id, info))
+            }
+            Chain::Hash(hash) => {
+                for id in self.backend.ids() {
+                    let info = self.backend.chain_info(&id).await?;
+                    if hex::encode(&info.hash) == *hash {
+                        return Ok((id, info));
+                    }
+                }
+                Err(Status::not_found(format!("unknown chain {hash}")))
+            }
+        }
+    }
+}
+
+/// Successful response, sent with empty body if the client has the same ETag.
+struct Reply {
+    body: Bytes,
+    content_type: &'static str,
+    cache: String,
+    etag: Option<String>,
+}
+
+impl Reply {
+    fn into_response(self, if_none_match: Option<&str>) -> Response {
+        let not_modified = self
+            .etag
+            .as_deref()
+            .is_some_and(|etag| etag_matches(if_none_match, etag));
+        let mut response = if not_modified {
+            let mut response = Response::new(Full::default());
+            *response.status_mut() = StatusCode::NOT_MODIFIED;
+            if let Ok(value) = HeaderValue::from_str(&self.cache) {
+                response.headers_mut().insert(header::CACHE_CONTROL, value);
+            }
+            response
+        } else {
+            body_response(StatusCode::OK, self.body, self.content_type, &self.cache)
+        };
+        if let Some(Ok(value)) = self.etag.as_deref().map(HeaderValue::from_str) {
+            response.headers_mut().insert(header::ETAG, value);
+        }
+
+        response
+    }
+}
+
+fn http_status(code: Code) -> StatusCode {
+    match code {
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::InvalidArgument => StatusCode::BAD_REQUEST,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn json_response(status: StatusCode, body: &Value, cache: &str) -> Response {
+    body_response(status, body.to_string().into(), JSON, cache)
+}
+
+fn body_response(
+    status: StatusCode,
+    body: Bytes,
+    content_type: &'static str,
+    cache: &str,
+) -> Response {
+    let mut response = Response::new(Full::new(body));
+    *response.status_mut() = status;
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    if let Ok(value) = HeaderValue::from_str(cache) {
+        headers.insert(header::CACHE_CONTROL, value);
+    }
+
+    response
+}
+
+fn text_response(body: String) -> Response {
+    let mut response = Response::new(Full::new(Bytes::from(body)));
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; version=0.0.4"),
+    );
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(NO_CACHE));
+
+    response
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    json_response(status, &json!({ "error": message }), NO_CACHE)
+}
+
+fn preflight() -> Response {
+    let mut response = Response::new(Full::default());
+    *response.status_mut() = StatusCode::NO_CONTENT;
+    let headers = response.headers_mut();
+    headers.insert(
+        header::ACCESS_CONTROL_ALLOW_METHODS,
+        HeaderValue::from_static("GET, HEAD, OPTIONS"),
+    );
+    headers.insert(
+        header::ACCESS_CONTROL_MAX_AGE,
+        HeaderValue::from_static(PREFLIGHT_MAX_AGE),
+    );
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_and_headers() {
+        let hash = "52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971";
+        assert_eq!(route("/chains"), Some(Route::Chains));
+        assert_eq!(route("/metrics"), Some(Route::Metrics));
+        assert_eq!(route("/info"), Some(Route::Info(Chain::Default)));
+        assert_eq!(
+            route(&format!("/{hash}/public/latest")),
+            Some(Route::Beacon(Chain::Hash(hash), 0))
+        );
+        assert_eq!(
+            route("/public/12/"),
+            Some(Route::Beacon(Chain::Default, 12))
+        );
+        assert_eq!(route("/public/0"), None);
+        assert_eq!(route("/quicknet/info"), None);
+
+        assert_eq!(beacon_format(None), Ok(BeaconFormat::Json));
+        assert_eq!(beacon_format(Some("x=1&format=raw")), Ok(BeaconFormat::Raw));
+        assert!(beacon_format(Some("format=yaml")).is_err());
+
+        // Quicknet, round 2 starts at genesis + 3.
+        let genesis = 1692803367;
+        assert_eq!(latest_max_age(genesis, 3, genesis, 1), 3);
+        assert_eq!(latest_max_age(genesis + 2, 3, genesis, 1), 1);
+        // Round 2 is due but not yet stored.
+        assert_eq!(latest_max_age(genesis + 4, 3, genesis, 1), 1);
+        assert_eq!(latest_max_age(genesis + 60, 3, genesis, 1), 1);
+        // Round 3 is stored early.
+        assert_eq!(latest_max_age(genesis + 5, 3, genesis, 3), 4);
+
+        let origins = vec!["https://example.com".to_string()];
+        assert_eq!(
+            allowed_origin(&origins, Some("https://example.com")),
+            Some("https://example.com".into())
+        );
+        assert_eq!(allowed_origin(&origins, Some("https://other.com")), None);
+        assert_eq!(allowed_origin(&origins, None), None);
+        assert_eq!(allowed_origin(&["*".into()], None), Some("*".into()));
+
+        let mut headers = HeaderMap::new();
+        set_cors(&origins, Some("https://other.com"), &mut headers);
+        assert_eq!(headers.get(header::VARY).unwrap(), "Origin");
+        assert!(headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+        let mut headers = HeaderMap::new();
+        set_cors(&origins, None, &mut headers);
+        assert_eq!(headers.get(header::VARY).unwrap(), "Origin");
+        let mut headers = HeaderMap::new();
+        set_cors(&["*".into()], Some("https://other.com"), &mut headers);
+        assert!(headers.get(header::VARY).is_none());
+        assert_eq!(
+            headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+            "*"
+        );
+        let mut headers = HeaderMap::new();
+        set_cors(&[], Some("https://other.com"), &mut headers);
+        assert!(headers.is_empty());
+
+        let etag = beacon_etag(&[1], 12, BeaconFormat::Json);
+        assert_eq!(etag, "\"01-12\"");
+        assert!(!etag_matches(
+            Some(&etag),
+            &beacon_etag(&[1], 12, BeaconFormat::Hex)
+        ));
+        assert!(!etag_matches(
+            Some(&etag),
+            &beacon_etag(&[2], 12, BeaconFormat::Json)
+        ));
+        assert!(etag_matches(Some("\"01-11\", W/\"01-12\""), &etag));
+        assert!(etag_matches(Some("*"), &etag));
+        assert!(!etag_matches(Some("\"01-1\""), &etag));
+        assert!(!etag_matches(None, &etag));
+    }
+
+    /// Chain with beacons up to round 5.
+    struct TestBackend;
+
+    #[tonic::async_trait]
+    impl Backend for TestBackend {
+        fn ids(&self) -> Vec<String> {
+            vec![DEFAULT_BEACON_ID.into()]
+        }
+
+        async fn chain_info(&self, _id: &str) -> Result<ChainInfoPacket, Status> {
+            Ok(ChainInfoPacket {
+                period: 3,
+                hash: vec![1; 32],
+                ..Default::default()
+            })
+        }
+
+        async fn public_rand(&self, _id: &str, round: u64) -> Result<PublicRandResponse, Status> {
+            if round > 5 {
+                return Err(Status::not_found("round is not stored"));
+            }
+            Ok(PublicRandResponse {
+                round: if round == 0 { 5 } else { round },
+                signature: vec![2; 48],
+                ..Default::default()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn not_modified_only_for_stored_rounds() {
+        let api = Api {
+            backend: TestBackend,
+            config: HttpConfig::default(),
+        };
+        let format = BeaconFormat::Json;
+
+        let etag = beacon_etag(&[1; 32], 4, format);
+        let response = api
+            .serve(Route::Beacon(Chain::Default, 4), format, Some(&etag))
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        // Client sends ETag of a round which does not exist yet.
+        let etag = beacon_etag(&[1; 32], 9, format);
+        let response = api
+            .serve(Route::Beacon(Chain::Default, 9), format, Some(&etag))
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // ETag of the same round of another chain.
+        let etag = beacon_etag(&[7; 32], 4, format);
+        let response = api
+            .serve(Route::Beacon(Chain::Default, 4), format, Some(&etag))
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
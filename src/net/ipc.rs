@@ -0,0 +1,174 @@
+//! Local broadcast of chain heads over a Unix socket, see `--ipc-socket` of `drand start`.
+//!
+//! Each connected client receives every newly stored beacon of all beacon ids as a JSON line:
+//! `{"beacon_id":"..","round":1,"randomness":"..","signature":"..","previous_signature":".."}`,
+//! fields of a beacon are as in [`BeaconFormat::Json`]. Nothing is read from clients. A client
+//! which falls behind skips the oldest beacons, a failed write closes the connection.
+//!
+//! Access is controlled by permissions of the socket file, which is created with mode `0660`.
+//! Named pipes are not supported, so the server is available on Unix platforms only.
+use super::utils::StartServerError;
+
+use crate::chain::format::BeaconFormat;
+use crate::core::events::ChainHead;
+use crate::core::events::EventSender;
+use crate::verify::randomness;
+
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+use tokio::net::UnixListener;
+use tokio::net::UnixStream;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+
+/// Mode of the socket file: read and write for the owner and the group.
+const SOCKET_MODE: u32 = 0o660;
+
+pub async fn start_server(
+    path: PathBuf,
+    events: EventSender,
+    token: CancellationToken,
+    tracker: TaskTracker,
+) -> Result<(), StartServerError> {
+    let listener = bind(&path).map_err(|err| {
+        error!(
+            "ipc socket {}: {}, {err}",
+            path.display(),
+            StartServerError::FailedToStartIpc
+        );
+        StartServerError::FailedToStartIpc
+    })?;
+    info!("ipc: broadcasting chain heads at {}", path.display());
+
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    debug!("ipc: failed to accept connection: {err}");
+                    continue;
+                }
+            },
+            () = token.cancelled() => break,
+        };
+        // Subscribed before the task is spawned, so no beacon is missed after accept.
+        let heads = events.subscribe_heads();
+        tracker.spawn(serve(stream, heads, token.clone()));
+    }
+    debug!("ipc server is shutting down");
+    let _ = std::fs::remove_file(&path);
+
+    Ok(())
+}
+
+/// Binds the socket, a stale socket of a previous run is replaced.
+fn bind(path: &Path) -> std::io::Result<UnixListener> {
+    if let Ok(meta) = std::fs::symlink_metadata(path) {
+        if !meta.file_type().is_socket() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                "path exists and is not a socket",
+            ));
+        }
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(SOCKET_MODE))?;
+
+    Ok(listener)
+}
+
+async fn serve(
+    mut stream: UnixStream,
+    mut heads: tokio::sync::broadcast::Receiver<ChainHead>,
+    token: CancellationToken,
+) {
+    loop {
+        let head = tokio::select! {
+            head = heads.recv() => match head {
+                Ok(head) => head,
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!("ipc: slow client skipped {skipped} beacons");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            },
+            () = token.cancelled() => break,
+        };
+        if let Err(err) = stream.write_all(&line(&head)).await {
+            debug!("ipc: client disconnected: {err}");
+            break;
+        }
+    }
+}
+
+/// Encodes the beacon as a JSON line with beacon id as the first field.
+fn line(head: &ChainHead) -> Vec<u8> {
+    let beacon = BeaconFormat::Json.encode(
+        head.round,
+        &randomness(&head.signature),
+        &head.signature,
+        &head.previous_signature,
+    );
+    // Beacon id is validated on load and never needs escaping.
+    let mut line = format!("{{\"beacon_id\":\"{}\",", head.beacon_id).into_bytes();
+    line.extend_from_slice(&beacon[1..]);
+    line.push(b'\n');
+
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncBufReadExt;
+    use tokio::io::BufReader;
+
+    #[tokio::test]
+    async fn broadcast_heads() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("drand.sock");
+        let events = EventSender::new();
+        let token = CancellationToken::new();
+        let tracker = TaskTracker::new();
+        let server = tokio::spawn(start_server(
+            path.clone(),
+            events.clone(),
+            token.clone(),
+            tracker.clone(),
+        ));
+
+        let client = loop {
+            match UnixStream::connect(&path).await {
+                Ok(client) => break client,
+                Err(_) => tokio::task::yield_now().await,
+            }
+        };
+        // Wait until the connection is accepted and subscribed.
+        while tracker.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        events.publish_head("quicknet", 7, &[0xbb], None);
+
+        let mut lines = BufReader::new(client).lines();
+        let line = lines.next_line().await.unwrap().unwrap();
+        assert_eq!(
+            line,
+            format!(
+                r#"{{"beacon_id":"quicknet","round":7,"randomness":"{}","signature":"bb"}}"#,
+                hex::encode(randomness(&[0xbb]))
+            )
+        );
+
+        token.cancel();
+        server.await.unwrap().unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -1,8 +1,26 @@
+pub mod bandwidth;
+#[cfg(any(test, feature = "chaos"))]
+pub mod chaos;
 pub mod control;
 pub mod dkg_control;
 pub mod dkg_public;
+pub mod error;
+pub mod handshake;
 pub mod health;
+pub mod http_api;
+#[cfg(unix)]
+pub mod ipc;
+pub mod peers;
 pub mod pool;
+#[cfg(feature = "pprof")]
+pub mod pprof;
 pub mod protocol;
 pub mod public;
+#[cfg(feature = "quic")]
+pub mod quic;
+pub mod relay;
+pub mod s3;
+#[cfg(test)]
+pub mod sim;
+pub mod top;
 pub mod utils;
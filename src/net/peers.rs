@@ -0,0 +1,264 @@
+//! Connections to sync peers shared by `follow` requests of all beacon ids.
+//!
+//! A gRPC channel multiplexes streams over a single HTTP/2 connection, so followers of several
+//! beacon ids reuse one connection per peer instead of connecting on their own. Concurrent sync
+//! streams are limited by [`MAX_STREAMS_PER_PEER`] per peer and beacon id, so followers of one
+//! beacon id can not hold streams needed by others. Followers waiting for a stream longer than
+//! [`ACQUIRE_TIMEOUT`] give up and move on to the next peer. Unary requests do not take a stream.
+//!
+//! Peers which are not used for [`PEER_IDLE`] are evicted together with their connection.
+use super::protocol::ProtocolClient;
+use super::public::PublicClient;
+use super::utils::Address;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+use tonic::transport::Channel;
+
+/// Concurrent sync streams opened to a peer by followers of a beacon id.
+pub const MAX_STREAMS_PER_PEER: usize = 2;
+/// Maximum time to wait for a free stream of a peer.
+pub const ACQUIRE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Peers without streams are evicted once not used for this time.
+pub const PEER_IDLE: Duration = Duration::from_secs(600);
+
+/// Pool shared by followers of the process.
+pub static SYNC_PEERS: LazyLock<PeerPool> = LazyLock::new(PeerPool::default);
+
+/// Pooled connection of a peer, generation is increased on each reconnect.
+#[derive(Default)]
+struct Connection {
+    channel: Option<Channel>,
+    generation: u64,
+}
+
+struct Peer {
+    /// Locked while connecting, so followers of a peer wait for a single connection.
+    connection: Arc<tokio::sync::Mutex<Connection>>,
+    /// Stream slots per beacon id.
+    streams: BTreeMap<String, Arc<Semaphore>>,
+    last_used: Instant,
+}
+
+impl Peer {
+    fn new() -> Self {
+        Self {
+            connection: Arc::default(),
+            streams: BTreeMap::new(),
+            last_used: Instant::now(),
+        }
+    }
+
+    /// Returns `true` if a stream is leased or the peer is being connected.
+    fn is_busy(&self) -> bool {
+        Arc::strong_count(&self.connection) > 1
+            || self
+                .streams
+                .values()
+                .any(|s| s.available_permits() < MAX_STREAMS_PER_PEER)
+    }
+}
+
+pub struct PeerPool {
+    peers: Mutex<BTreeMap<Address, Peer>>,
+    acquire_timeout: Duration,
+    idle: Duration,
+}
+
+impl Default for PeerPool {
+    fn default() -> Self {
+        Self::new(ACQUIRE_TIMEOUT, PEER_IDLE)
+    }
+}
+
+/// Client over the pooled connection of a peer. Lease of a stream holds a stream slot of the
+/// peer and beacon id, the slot is released once the lease is dropped.
+pub struct PeerLease {
+    channel: Channel,
+    peer: Address,
+    generation: u64,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl PeerLease {
+    pub fn protocol(&self) -> ProtocolClient {
+        ProtocolClient::with_channel(self.channel.clone(), &self.peer)
+    }
+
+    pub fn public(&self) -> PublicClient {
+        PublicClient::with_channel(self.channel.clone(), &self.peer)
+    }
+}
+
+impl PeerPool {
+    fn new(acquire_timeout: Duration, idle: Duration) -> Self {
+        Self {
+            peers: Mutex::default(),
+            acquire_timeout,
+            idle,
+        }
+    }
+
+    /// Returns client for unary requests to the peer without taking a stream slot.
+    pub async fn connect(&self, peer: &Address) -> anyhow::Result<PeerLease> {
+        let (connection, _) = self.slot(peer, None);
+        self.connected(peer, &connection, None).await
+    }
+
+    /// Waits for a free stream slot of the peer for the beacon id and returns it with a client,
+    /// the peer is connected if it has no pooled connection.
+    pub async fn lease(&self, peer: &Address, beacon_id: &str) -> anyhow::Result<PeerLease> {
+        let (connection, streams) = self.slot(peer, Some(beacon_id));
+        let streams = streams.expect("stream slots are returned for a beacon id");
+        let permit = tokio::time::timeout(self.acquire_timeout, streams.acquire_owned())
+            .await
+            .map_err(|_| anyhow::anyhow!("no free stream to {peer} for {beacon_id}"))??;
+
+        self.connected(peer, &connection, Some(permit)).await
+    }
+
+    /// Drops pooled connection of the lease after a transport error, next lease reconnects.
+    /// Connection is kept if it has been replaced meanwhile.
+    pub async fn reset(&self, lease: &PeerLease) {
+        let connection = {
+            let peers = self.peers.lock().unwrap_or_else(PoisonError::into_inner);
+            match peers.get(&lease.peer) {
+                Some(slot) => slot.connection.clone(),
+                None => return,
+            }
+        };
+        let mut connection = connection.lock().await;
+        if connection.generation == lease.generation {
+            connection.channel = None;
+        }
+    }
+
+    /// Returns pooled connection of the peer and stream slots of the beacon id, idle peers are evicted.
+    fn slot(
+        &self,
+        peer: &Address,
+        beacon_id: Option<&str>,
+    ) -> (Arc<tokio::sync::Mutex<Connection>>, Option<Arc<Semaphore>>) {
+        let mut peers = self.peers.lock().unwrap_or_else(PoisonError::into_inner);
+        peers.retain(|address, p| {
+            address == peer || p.is_busy() || p.last_used.elapsed() < self.idle
+        });
+        let slot = peers.entry(peer.clone()).or_insert_with(Peer::new);
+        slot.last_used = Instant::now();
+        let streams = beacon_id.map(|id| {
+            slot.streams
+                .entry(id.to_string())
+                .or_insert_with(|| Arc::new(Semaphore::new(MAX_STREAMS_PER_PEER)))
+                .clone()
+        });
+
+        (slot.connection.clone(), streams)
+    }
+
+    async fn connected(
+        &self,
+        peer: &Address,
+        connection: &tokio::sync::Mutex<Connection>,
+        permit: Option<OwnedSemaphorePermit>,
+    ) -> anyhow::Result<PeerLease> {
+        let mut connection = connection.lock().await;
+        let channel = match &connection.channel {
+            Some(channel) => channel.clone(),
+            None => {
+                let channel = super::utils::connect(peer).await?;
+                connection.generation += 1;
+                connection.channel.insert(channel).clone()
+            }
+        };
+
+        Ok(PeerLease {
+            channel,
+            peer: peer.clone(),
+            generation: connection.generation,
+            _permit: permit,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sets pooled channel of the peer up front, nothing is connected.
+    fn pooled(pool: &PeerPool, peer: &Address) {
+        let slot = Peer::new();
+        {
+            let mut connection = slot.connection.try_lock().unwrap();
+            connection.channel = Some(Channel::from_static("http://127.0.0.1:1").connect_lazy());
+            connection.generation = 1;
+        }
+        pool.peers.lock().unwrap().insert(peer.clone(), slot);
+    }
+
+    #[tokio::test]
+    async fn streams_per_peer_and_beacon_id() {
+        let pool = PeerPool::new(Duration::from_millis(50), PEER_IDLE);
+        let available = |peer: &Address, id: &str| {
+            pool.peers.lock().unwrap()[peer].streams[id].available_permits()
+        };
+        let busy = Address::precheck("127.0.0.1:1").unwrap();
+        let other = Address::precheck("127.0.0.1:2").unwrap();
+        pooled(&pool, &busy);
+        pooled(&pool, &other);
+
+        // Streams of each beacon id are limited on their own.
+        let ids = ["default", "quicknet", "evmnet", "testnet"];
+        let mut leases = vec![];
+        for id in ids {
+            for _ in 0..MAX_STREAMS_PER_PEER {
+                leases.push(pool.lease(&busy, id).await.unwrap());
+            }
+        }
+        for id in ids {
+            assert_eq!(available(&busy, id), 0);
+        }
+        // Busy beacon id times out, other peers and unary requests are not affected.
+        assert!(pool.lease(&busy, "default").await.is_err());
+        let _other = pool.lease(&other, "default").await.unwrap();
+        let _unary = pool.connect(&busy).await.unwrap();
+
+        leases.remove(0);
+        let _next = pool.lease(&busy, "default").await.unwrap();
+        assert_eq!(available(&busy, "default"), 0);
+        assert_eq!(available(&other, "default"), MAX_STREAMS_PER_PEER - 1);
+    }
+
+    #[tokio::test]
+    async fn reset_and_evict() {
+        let pool = PeerPool::new(ACQUIRE_TIMEOUT, Duration::ZERO);
+        let peer = Address::precheck("127.0.0.1:1").unwrap();
+        let idle = Address::precheck("127.0.0.1:2").unwrap();
+        pooled(&pool, &peer);
+        pooled(&pool, &idle);
+
+        let lease = pool.lease(&peer, "default").await.unwrap();
+        // Idle peer is evicted on the next request, peer with a stream is kept.
+        let _unary = pool.connect(&peer).await.unwrap();
+        assert!(!pool.peers.lock().unwrap().contains_key(&idle));
+        pooled(&pool, &idle);
+        let _unary = pool.connect(&idle).await.unwrap();
+        assert!(pool.peers.lock().unwrap().contains_key(&peer));
+
+        let connection = pool.peers.lock().unwrap()[&peer].connection.clone();
+        // Stale lease does not drop a newer connection.
+        connection.lock().await.generation += 1;
+        pool.reset(&lease).await;
+        assert!(connection.lock().await.channel.is_some());
+
+        connection.lock().await.generation = lease.generation;
+        pool.reset(&lease).await;
+        assert!(connection.lock().await.channel.is_none());
+    }
+}
@@ -9,6 +9,7 @@ use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tracing::{debug, error, trace, warn, Span};
 
+use super::handshake;
 use super::utils::Address;
 use crate::net::protocol::ProtocolClient;
 use crate::protobuf::drand::PartialBeaconPacket;
@@ -205,8 +206,16 @@ impl Pool {
         tokio::spawn(async move {
             let client = loop {
                 match ProtocolClient::new(&peer).await {
-                    Ok(client) => {
+                    Ok(mut client) => {
                         debug!(parent: &l, "connected to {peer}");
+                        let features = handshake::handshake(&mut client, &peer, &l).await;
+                        // Peers which do not accept QUIC, e.g. golang nodes, are served over gRPC.
+                        #[cfg(feature = "quic")]
+                        if features.iter().any(|f| f == handshake::QUIC) {
+                            client.enable_quic(&peer);
+                        }
+                        #[cfg(not(feature = "quic"))]
+                        let _ = features;
                         if let Ok(()) = rx.try_recv() {
                             debug!(parent: &l,"pending connection {peer} canceled");
                             break None;
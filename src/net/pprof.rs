@@ -0,0 +1,242 @@
+//! Profiling endpoints of the daemon, served with `--enable-pprof` at `--pprof-listen`.
+//!
+//! Routes:
+//! - `GET /debug/pprof/profile?seconds=<N>&frequency=<HZ>`: CPU profile sampled for `N` seconds
+//!   (30 by default), rendered as flamegraph SVG;
+//! - `GET /debug/pprof/heap`: jemalloc statistics in bytes.
+//!
+//! Sampling has an overhead on all threads, so only one profile is captured at a time. Endpoints
+//! are not authenticated and should be bound to a private interface, localhost by default.
+use super::utils::Address;
+use super::utils::StartServerError;
+
+use http::header;
+use http::request::Parts;
+use http::HeaderValue;
+use http::Method;
+use http::StatusCode;
+use http_body_util::Full;
+use hyper::body::Incoming;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper_util::rt::TokioIo;
+use prost::bytes::Bytes;
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+type Response = http::Response<Full<Bytes>>;
+
+const DEFAULT_SECONDS: u64 = 30;
+const MAX_SECONDS: u64 = 300;
+/// Sampling frequency in Hz, odd value avoids lockstep with periodic tasks.
+const DEFAULT_FREQUENCY: i32 = 99;
+const MAX_FREQUENCY: i32 = 1_000;
+
+#[derive(thiserror::Error, Debug)]
+enum PprofError {
+    #[error("invalid query parameter '{0}'")]
+    InvalidQuery(String),
+    #[error("profile is already being captured")]
+    Busy,
+    #[error("profiler: {0}")]
+    Profiler(#[from] pprof::Error),
+    #[error("jemalloc: {0}")]
+    Jemalloc(#[from] tikv_jemalloc_ctl::Error),
+    #[error("profiler task: {0}")]
+    Join(#[from] tokio::task::JoinError),
+}
+
+impl PprofError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::InvalidQuery(_) => StatusCode::BAD_REQUEST,
+            Self::Busy => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn start_server(
+    listen: Address,
+    token: CancellationToken,
+    tracker: TaskTracker,
+) -> Result<(), StartServerError> {
+    let listener = TcpListener::bind(listen.as_str()).await.map_err(|err| {
+        error!(
+            "pprof listener: {}, {err}",
+            StartServerError::FailedToStartHttp
+        );
+        StartServerError::FailedToStartHttp
+    })?;
+    info!("pprof: serving profiling endpoints at {listen}");
+    // Set while a CPU profile is captured.
+    let busy = Arc::new(AtomicBool::new(false));
+
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    debug!("pprof: failed to accept connection: {err}");
+                    continue;
+                }
+            },
+            () = token.cancelled() => break,
+        };
+        let busy = busy.clone();
+        let service = service_fn(move |request: http::Request<Incoming>| {
+            let busy = busy.clone();
+            // Body is not used, only request head is held across awaits.
+            let (head, _) = request.into_parts();
+            async move { Ok::<_, Infallible>(handle(&head, &busy).await) }
+        });
+        tracker.spawn(async move {
+            if let Err(err) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                debug!("pprof: connection closed: {err}");
+            }
+        });
+    }
+    debug!("pprof server is shutting down");
+
+    Ok(())
+}
+
+async fn handle(head: &Parts, busy: &Arc<AtomicBool>) -> Response {
+    if head.method != Method::GET {
+        return response(
+            StatusCode::METHOD_NOT_ALLOWED,
+            "text/plain",
+            "method not allowed\n",
+        );
+    }
+    let query = head.uri.query().unwrap_or_default();
+    let result = match head.uri.path() {
+        "/debug/pprof/profile" => match profile_params(query) {
+            Ok((seconds, frequency)) => cpu_profile(busy, seconds, frequency)
+                .await
+                .map(|svg| response(StatusCode::OK, "image/svg+xml", svg)),
+            Err(err) => Err(err),
+        },
+        "/debug/pprof/heap" => {
+            heap_stats().map(|stats| response(StatusCode::OK, "text/plain", stats))
+        }
+        _ => Ok(response(StatusCode::NOT_FOUND, "text/plain", "not found\n")),
+    };
+
+    result.unwrap_or_else(|err| {
+        warn!("pprof: {err}");
+        response(err.status(), "text/plain", format!("{err}\n"))
+    })
+}
+
+/// Returns duration in seconds and frequency of the requested CPU profile.
+fn profile_params(query: &str) -> Result<(u64, i32), PprofError> {
+    let mut seconds = DEFAULT_SECONDS;
+    let mut frequency = DEFAULT_FREQUENCY;
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let invalid = || PprofError::InvalidQuery(pair.to_string());
+        match pair.split_once('=').ok_or_else(invalid)? {
+            ("seconds", v) => seconds = v.parse().map_err(|_| invalid())?,
+            ("frequency", v) => frequency = v.parse().map_err(|_| invalid())?,
+            _ => return Err(invalid()),
+        }
+    }
+    if !(1..=MAX_SECONDS).contains(&seconds) {
+        return Err(PprofError::InvalidQuery(format!("seconds={seconds}")));
+    }
+    if !(1..=MAX_FREQUENCY).contains(&frequency) {
+        return Err(PprofError::InvalidQuery(format!("frequency={frequency}")));
+    }
+
+    Ok((seconds, frequency))
+}
+
+/// Samples all threads of the process and returns flamegraph SVG.
+async fn cpu_profile(
+    busy: &Arc<AtomicBool>,
+    seconds: u64,
+    frequency: i32,
+) -> Result<Vec<u8>, PprofError> {
+    if busy.swap(true, Ordering::AcqRel) {
+        return Err(PprofError::Busy);
+    }
+    info!("pprof: capturing CPU profile for {seconds}s at {frequency}Hz");
+    let capture = tokio::task::spawn_blocking(move || {
+        let guard = pprof::ProfilerGuardBuilder::default()
+            .frequency(frequency)
+            .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+            .build()?;
+        std::thread::sleep(Duration::from_secs(seconds));
+        let mut svg = vec![];
+        guard.report().build()?.flamegraph(&mut svg)?;
+
+        Ok::<_, PprofError>(svg)
+    })
+    .await;
+    busy.store(false, Ordering::Release);
+
+    capture?
+}
+
+/// Returns allocator statistics, values are refreshed on each request.
+fn heap_stats() -> Result<String, PprofError> {
+    use tikv_jemalloc_ctl::epoch;
+    use tikv_jemalloc_ctl::stats;
+
+    epoch::advance()?;
+    let mut out = String::new();
+    for (name, value) in [
+        ("allocated", stats::allocated::read()?),
+        ("active", stats::active::read()?),
+        ("metadata", stats::metadata::read()?),
+        ("resident", stats::resident::read()?),
+        ("mapped", stats::mapped::read()?),
+        ("retained", stats::retained::read()?),
+    ] {
+        let _ = writeln!(out, "{name}_bytes {value}");
+    }
+
+    Ok(out)
+}
+
+fn response(status: StatusCode, content_type: &'static str, body: impl Into<Bytes>) -> Response {
+    let mut response = Response::new(Full::new(body.into()));
+    *response.status_mut() = status;
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_profile_params() {
+        assert_eq!(
+            profile_params("").unwrap(),
+            (DEFAULT_SECONDS, DEFAULT_FREQUENCY)
+        );
+        assert_eq!(profile_params("seconds=5&frequency=200").unwrap(), (5, 200));
+        assert!(profile_params("seconds=0").is_err());
+        assert!(profile_params("seconds=301").is_err());
+        assert!(profile_params("seconds=five").is_err());
+        assert!(profile_params("debug=1").is_err());
+    }
+}
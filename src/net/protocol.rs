@@ -1,14 +1,18 @@
 //! This module provides server and client implementations for Protocol.
 use super::dkg_public::DkgPublicHandler;
+use super::error::NodeError;
+use super::handshake;
 use super::public::PublicHandler;
+use super::utils::peer_channel;
 use super::utils::Address;
 use super::utils::Callback;
 use super::utils::NewTcpListener;
+use super::utils::PeerChannel;
 use super::utils::StartServerError;
 use super::utils::ToStatus;
-use super::utils::ERR_METADATA_IS_MISSING;
 
 use crate::chain::ChainError;
+use crate::chain::StoreStreamResponse;
 use crate::core::beacon::BeaconCmd;
 use crate::core::daemon::Daemon;
 use crate::protobuf::dkg::dkg_public_server::DkgPublicServer;
@@ -20,20 +24,40 @@ use protobuf::protocol_server::Protocol;
 use protobuf::protocol_server::ProtocolServer;
 use protobuf::public_server::PublicServer;
 use protobuf::BeaconPacket;
+use protobuf::BeaconRound;
 use protobuf::Empty;
+use protobuf::GroupPacket;
+use protobuf::GroupRequest;
 use protobuf::IdentityRequest;
 use protobuf::IdentityResponse;
+use protobuf::LeaveNoticeRequest;
 use protobuf::PartialBeaconPacket;
+use protobuf::PingRequest;
+use protobuf::PingResponse;
 use protobuf::StatusRequest;
 use protobuf::StatusResponse;
 use protobuf::SyncRequest;
 
+use prost::Message;
+use std::future::Future;
 use std::ops::Deref;
 use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::wrappers::TcpListenerStream;
 use tokio_stream::Stream;
+use tokio_stream::StreamExt;
+use tonic::transport::server::Router;
 use tonic::transport::Channel;
 use tonic::transport::Server;
 use tonic::Request;
@@ -41,17 +65,293 @@ use tonic::Response;
 use tonic::Status;
 use tonic::Streaming;
 use tracing::error;
+use tracing::warn;
 
 /// Contains partial beacon packet and sender IP.
 pub struct PartialPacket {
     pub packet: PartialBeaconPacket,
-    // X-REAL-IP from request metadata.
+    /// IP of the connected peer, the claimed index must belong to a node with this IP.
     pub from: String,
 }
 
 /// Alias for partial beacon packet with callback.
 pub type PartialMsg = (PartialPacket, Callback<(), ChainError>);
 
+/// Default amount of beacons streamed to a follower before the stream task yields.
+pub const DEFAULT_SYNC_BATCH_SIZE: usize = 300;
+/// Default limit of concurrent sync streams served by the node.
+pub const DEFAULT_MAX_SYNC_STREAMS: usize = 32;
+/// Default cap of memory used by beacons buffered across all sync streams, in MiB.
+pub const DEFAULT_SYNC_BUFFER_MB: usize = 64;
+/// Time a follower may receive nothing while other streams wait for buffer memory.
+const SYNC_STALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Flow control of sync streams served to followers.
+///
+/// Each stream is buffered up to `batch_size` beacons, so a slow follower does not pin more
+/// than a batch in memory, and followers above `max_streams` are rejected with
+/// [`tonic::Code::ResourceExhausted`] instead of competing with the node for DB reads.
+///
+/// Buffered beacons of all streams are accounted against [`SyncMemory`]. Streams are paused
+/// while the cap is reached, and a follower which receives nothing for [`SYNC_STALL_TIMEOUT`]
+/// meanwhile is dropped with [`tonic::Code::ResourceExhausted`], so its buffer is released for
+/// the others.
+pub struct SyncLimits {
+    batch_size: usize,
+    max_streams: usize,
+    streams: Arc<Semaphore>,
+    memory: SyncMemory,
+    stall_timeout: Duration,
+}
+
+/// Memory cap shared by beacons buffered across all sync streams of the node: streams served
+/// to followers (see [`SyncLimits`]) and streams received by resync, follow requests and relays.
+///
+/// Received streams are paused while the cap is reached. Waiting for memory counts as pressure
+/// on served streams, so stalled followers are dropped to release it.
+#[derive(Clone)]
+pub struct SyncMemory {
+    /// Bytes available for buffered beacons.
+    available: Arc<Semaphore>,
+    cap: usize,
+    /// Streams waiting for buffer memory.
+    waiting: Arc<AtomicUsize>,
+}
+
+/// Received beacon which holds its buffer memory until it is taken by the consumer.
+pub struct Buffered<T> {
+    item: T,
+    _memory: Option<OwnedSemaphorePermit>,
+}
+
+/// Marks a stream as waiting for buffer memory while alive.
+struct Waiting<'a>(&'a AtomicUsize);
+
+impl<'a> Waiting<'a> {
+    fn new(waiting: &'a AtomicUsize) -> Self {
+        waiting.fetch_add(1, Ordering::Relaxed);
+        Self(waiting)
+    }
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl SyncMemory {
+    pub fn new(cap: usize) -> Self {
+        let cap = cap.clamp(1, Semaphore::MAX_PERMITS);
+        Self {
+            available: Arc::new(Semaphore::new(cap)),
+            cap,
+            waiting: Arc::default(),
+        }
+    }
+
+    /// Returns memory size of the message, a message above the cap takes the whole cap.
+    fn buffer_size<T: Message>(&self, item: &T) -> usize {
+        item.encoded_len().min(self.cap)
+    }
+
+    /// Reserves buffer memory for a received message, waiting while the cap is reached.
+    pub async fn buffer<T: Message>(&self, item: T) -> Buffered<T> {
+        let size = self.buffer_size(&item);
+        let _waiting = Waiting::new(&self.waiting);
+        let memory = self
+            .available
+            .clone()
+            .acquire_many_owned(u32::try_from(size).unwrap_or(u32::MAX))
+            .await
+            .ok();
+
+        Buffered {
+            item,
+            _memory: memory,
+        }
+    }
+}
+
+impl Default for SyncMemory {
+    fn default() -> Self {
+        Self::new(DEFAULT_SYNC_BUFFER_MB << 20)
+    }
+}
+
+impl<T> Buffered<T> {
+    /// Takes the message, its buffer memory is released.
+    pub fn into_inner(self) -> T {
+        self.item
+    }
+}
+
+/// Buffer memory held by a single stream, released as the follower receives beacons.
+struct StreamMemory {
+    memory: SyncMemory,
+    stall_timeout: Duration,
+    held: Mutex<usize>,
+    /// Time of the latest beacon received by the follower, or the stream start.
+    received: Mutex<Instant>,
+    /// Set if the stream is dropped as a slow consumer.
+    dropped: AtomicBool,
+}
+
+impl StreamMemory {
+    fn hold(&self, size: usize) {
+        *self.held.lock().unwrap_or_else(PoisonError::into_inner) += size;
+    }
+
+    /// Releases memory of a beacon received by the follower.
+    fn release(&self, size: usize) {
+        *self.received.lock().unwrap_or_else(PoisonError::into_inner) = Instant::now();
+        let mut held = self.held.lock().unwrap_or_else(PoisonError::into_inner);
+        let size = size.min(*held);
+        *held -= size;
+        self.memory.available.add_permits(size);
+    }
+
+    fn release_all(&self) {
+        let mut held = self.held.lock().unwrap_or_else(PoisonError::into_inner);
+        self.memory.available.add_permits(*held);
+        *held = 0;
+    }
+
+    /// Returns true if the follower holds buffer memory without receiving beacons while
+    /// other streams are waiting for it.
+    fn is_stalled(&self) -> bool {
+        self.memory.waiting.load(Ordering::Relaxed) > 0
+            && *self.held.lock().unwrap_or_else(PoisonError::into_inner) > 0
+            && self
+                .received
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .elapsed()
+                >= self.stall_timeout
+    }
+
+    /// Awaits `fut`, returns `None` if the stream is dropped as a slow consumer meanwhile.
+    async fn unless_stalled<F: Future>(&self, fut: F) -> Option<F::Output> {
+        tokio::pin!(fut);
+        loop {
+            match tokio::time::timeout(self.stall_timeout, &mut fut).await {
+                Ok(output) => return Some(output),
+                Err(_) if self.is_stalled() => {
+                    self.dropped.store(true, Ordering::Relaxed);
+                    self.release_all();
+                    return None;
+                }
+                Err(_) => (),
+            }
+        }
+    }
+}
+
+impl Drop for StreamMemory {
+    fn drop(&mut self) {
+        self.release_all();
+    }
+}
+
+impl SyncLimits {
+    pub fn new(batch_size: usize, max_streams: usize, memory: SyncMemory) -> Self {
+        let max_streams = max_streams.max(1);
+        Self {
+            batch_size: batch_size.max(1),
+            max_streams,
+            streams: Arc::new(Semaphore::new(max_streams)),
+            memory,
+            stall_timeout: SYNC_STALL_TIMEOUT,
+        }
+    }
+
+    /// Reserves a sync stream, the stream is released once the permit is dropped.
+    fn acquire(&self) -> Result<OwnedSemaphorePermit, Status> {
+        self.streams.clone().try_acquire_owned().map_err(|_| {
+            Status::resource_exhausted(format!(
+                "node is serving maximum of {} sync streams, retry later or use another peer",
+                self.max_streams
+            ))
+        })
+    }
+
+    /// Forwards beacons of the store stream, yielding after every batch.
+    fn throttle(
+        &self,
+        mut store_rx: mpsc::Receiver<StoreStreamResponse>,
+        permit: OwnedSemaphorePermit,
+    ) -> impl Stream<Item = StoreStreamResponse> + Send + 'static {
+        let batch_size = self.batch_size;
+        let memory_cap = self.memory.cap;
+        let stream_memory = Arc::new(StreamMemory {
+            memory: self.memory.clone(),
+            stall_timeout: self.stall_timeout,
+            held: Mutex::new(0),
+            received: Mutex::new(Instant::now()),
+            dropped: AtomicBool::new(false),
+        });
+        let (tx, rx) = mpsc::channel::<(StoreStreamResponse, usize)>(batch_size);
+        tokio::spawn({
+            let sm = stream_memory.clone();
+            async move {
+                let _permit = permit;
+                let mut sent = 0;
+                while let Some(item) = store_rx.recv().await {
+                    let Some(Ok(slot)) = sm.unless_stalled(tx.reserve()).await else {
+                        return;
+                    };
+                    let size = item.as_ref().map_or(0, |b| sm.memory.buffer_size(b));
+                    let waiting = Waiting::new(&sm.memory.waiting);
+                    let acquired = sm
+                        .unless_stalled(
+                            sm.memory
+                                .available
+                                .acquire_many(u32::try_from(size).unwrap_or(u32::MAX)),
+                        )
+                        .await;
+                    drop(waiting);
+                    let Some(Ok(memory)) = acquired else {
+                        return;
+                    };
+                    memory.forget();
+                    sm.hold(size);
+                    slot.send((item, size));
+                    sent += 1;
+                    if sent % batch_size == 0 {
+                        tokio::task::yield_now().await;
+                    }
+                }
+            }
+        });
+
+        let mut reported = false;
+        ReceiverStream::new(rx).map_while(move |(item, size)| {
+            stream_memory.release(size);
+            if !stream_memory.dropped.load(Ordering::Relaxed) {
+                return Some(item);
+            }
+            // The rest of buffered beacons is discarded with the stream.
+            if reported {
+                return None;
+            }
+            reported = true;
+            Some(Err(Status::resource_exhausted(format!(
+                "sync stream is dropped: follower is too slow while buffered beacons of all streams reached the cap of {memory_cap} bytes"
+            ))))
+        })
+    }
+}
+
+impl Default for SyncLimits {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_SYNC_BATCH_SIZE,
+            DEFAULT_MAX_SYNC_STREAMS,
+            SyncMemory::default(),
+        )
+    }
+}
+
 /// Implementor for [`Protocol`] trait for use with `ProtocolServer`.
 pub struct ProtocolHandler(Arc<Daemon>);
 
@@ -65,10 +365,21 @@ impl Protocol for ProtocolHandler {
         &self,
         request: Request<IdentityRequest>,
     ) -> Result<Response<IdentityResponse>, Status> {
-        let id = request.get_ref().metadata.as_ref().map_or_else(
-            || Err(Status::data_loss(ERR_METADATA_IS_MISSING)),
-            |meta| Ok(meta.beacon_id.as_str()),
-        )?;
+        let meta = request
+            .get_ref()
+            .metadata
+            .as_ref()
+            .ok_or_else(|| Status::from(NodeError::MetadataMissing))?;
+        if let Err(err) = handshake::check(meta) {
+            if err.is_fatal() {
+                return Err(Status::failed_precondition(err.to_string()));
+            }
+            let peer = request
+                .remote_addr()
+                .map_or_else(|| "unknown peer".into(), |addr| addr.to_string());
+            warn!("get_identity: {peer}: {err}");
+        }
+        let id = meta.beacon_id.as_str();
 
         let (tx, rx) = Callback::new();
         self.beacons()
@@ -89,25 +400,35 @@ impl Protocol for ProtocolHandler {
         &self,
         request: Request<PartialBeaconPacket>,
     ) -> Result<Response<Empty>, Status> {
-        let from = request
-            .metadata()
-            .get("x-real-ip")
-            .map_or_else(|| "", |v| v.to_str().unwrap_or_default())
-            .to_string();
+        // X-REAL-IP is taken only from a local reverse proxy, remote peers could set any value.
+        let remote = request.remote_addr().map(|addr| addr.ip().to_canonical());
+        let from = match (remote, request.metadata().get("x-real-ip")) {
+            (Some(ip), Some(real_ip)) if ip.is_loopback() => {
+                real_ip.to_str().unwrap_or_default().to_string()
+            }
+            (Some(ip), _) => ip.to_string(),
+            (None, _) => String::new(),
+        };
 
         let partial = PartialPacket {
             packet: request.into_inner(),
             from,
         };
+        let id = partial
+            .packet
+            .metadata
+            .as_ref()
+            .map(|meta| meta.beacon_id.clone())
+            .unwrap_or_default();
         let (tx, rx) = Callback::new();
 
         self.beacons()
             .send_partial((partial, tx))
             .await
-            .map_err(|err| Status::unknown(err.to_string()))?;
+            .map_err(NodeError::from)?;
         rx.await
-            .map_err(|err| Status::unknown(err.to_string()))?
-            .map_err(|err| Status::unknown(err.to_string()))?;
+            .map_err(|recv_err| recv_err.to_status(&id))?
+            .map_err(|chain_err| chain_err.to_status(&id))?;
 
         Ok(Response::new(Empty { metadata: None }))
     }
@@ -119,28 +440,128 @@ impl Protocol for ProtocolHandler {
         let request = request.into_inner();
 
         let id = request.metadata.as_ref().map_or_else(
-            || Err(Status::data_loss(ERR_METADATA_IS_MISSING)),
+            || Err(Status::from(NodeError::MetadataMissing)),
             |meta| Ok(meta.beacon_id.as_str()),
         )?;
+        let permit = self.sync_limits.acquire()?;
         let (tx, rx) = Callback::new();
 
         self.beacons()
             .cmd(BeaconCmd::Sync(request.from_round, tx), id)
             .await
-            .map_err(|err| Status::unknown(err.to_string()))?;
+            .map_err(|err| err.to_status(id))?;
         let stream_rx = rx
             .await
-            .map_err(|err| Status::unknown(err.to_string()))?
-            .map_err(|err| Status::unknown(err.to_string()))?;
+            .map_err(|recv_err| recv_err.to_status(id))?
+            .map_err(|store_err| store_err.to_status(id))?;
+        let stream = self.sync_limits.throttle(stream_rx, permit);
 
-        Ok(Response::new(Box::pin(ReceiverStream::new(stream_rx))))
+        Ok(Response::new(Box::pin(stream)))
     }
 
+    /// Returns status of beacon id to members of the latest group, see [`crate::core::remote_status`].
     async fn status(
         &self,
-        _request: Request<StatusRequest>,
+        request: Request<StatusRequest>,
     ) -> Result<Response<StatusResponse>, Status> {
-        Err(Status::unimplemented("status: StatusRequest"))
+        let request = request.into_inner();
+        let id = request.metadata.as_ref().map_or_else(
+            || Err(Status::from(NodeError::MetadataMissing)),
+            |meta| Ok(meta.beacon_id.clone()),
+        )?;
+
+        let (tx, rx) = Callback::new();
+        self.beacons()
+            .cmd(BeaconCmd::PeerStatus(request, tx), &id)
+            .await
+            .map_err(|err| err.to_status(&id))?;
+
+        let status = rx
+            .await
+            .map_err(|recv_err| recv_err.to_status(&id))?
+            .map_err(|status_err| status_err.to_status(&id))?;
+
+        Ok(Response::new(status))
+    }
+
+    /// Returns the group used at requested epoch, latest group is returned for epoch 0.
+    async fn group_for_epoch(
+        &self,
+        request: Request<GroupRequest>,
+    ) -> Result<Response<GroupPacket>, Status> {
+        let request = request.get_ref();
+        let id = request.metadata.as_ref().map_or_else(
+            || Err(Status::from(NodeError::MetadataMissing)),
+            |meta| Ok(meta.beacon_id.as_str()),
+        )?;
+
+        let (tx, rx) = Callback::new();
+        self.beacons()
+            .cmd(BeaconCmd::Group(request.epoch, tx), id)
+            .await
+            .map_err(|err| err.to_status(id))?;
+
+        let group = rx
+            .await
+            .map_err(|recv_err| recv_err.to_status(id))?
+            .map_err(|fs_err| fs_err.to_status(id))?;
+
+        Ok(Response::new(group))
+    }
+
+    /// Responds with the node version and the latest stored round of each loaded beacon id,
+    /// round is `0` if beacon id has no stored beacons yet.
+    async fn ping(&self, _request: Request<PingRequest>) -> Result<Response<PingResponse>, Status> {
+        let ids: Vec<String> = self
+            .beacons()
+            .snapshot()
+            .iter()
+            .map(|h| h.id().as_str().to_string())
+            .collect();
+
+        let mut beacons = Vec::with_capacity(ids.len());
+        for beacon_id in ids {
+            let (tx, rx) = Callback::new();
+            let round = match self.beacons().cmd(BeaconCmd::Status(tx), &beacon_id).await {
+                Ok(()) => rx
+                    .await
+                    .ok()
+                    .and_then(Result::ok)
+                    .map_or(0, |s| s.latest_stored_round),
+                // Beacon id is unloaded meanwhile.
+                Err(_) => continue,
+            };
+            beacons.push(BeaconRound { beacon_id, round });
+        }
+
+        Ok(Response::new(PingResponse {
+            beacons,
+            metadata: Some(protobuf::Metadata::with_default()),
+        }))
+    }
+
+    /// Accepts leave notice signed by a member of the latest group, see [`crate::core::leave`].
+    async fn leave_notice(
+        &self,
+        request: Request<LeaveNoticeRequest>,
+    ) -> Result<Response<Empty>, Status> {
+        let request = request.into_inner();
+        let id = request.metadata.as_ref().map_or_else(
+            || Err(Status::from(NodeError::MetadataMissing)),
+            |meta| Ok(meta.beacon_id.clone()),
+        )?;
+
+        let (tx, rx) = Callback::new();
+        self.beacons()
+            .cmd(BeaconCmd::LeaveNotice(request, tx), &id)
+            .await
+            .map_err(|err| err.to_status(&id))?;
+
+        rx.await
+            .map_err(|recv_err| recv_err.to_status(&id))?
+            .map_err(|notice_err| notice_err.to_status(&id))?;
+
+        Ok(Response::new(Empty { metadata: None }))
     }
 }
 
@@ -153,14 +574,13 @@ pub async fn start_server<N: NewTcpListener>(
         StartServerError::FailedToStartNode
     })?;
     let cancel = daemon.token.clone();
+    let incoming = TcpListenerStream::new(listener).map({
+        let daemon = daemon.clone();
+        move |stream| stream.map(|stream| daemon.bandwidth.meter(stream))
+    });
 
-    let (_health_reporter, health_service) = tonic_health::server::health_reporter();
-    Server::builder()
-        .add_service(ProtocolServer::new(ProtocolHandler(daemon.clone())))
-        .add_service(PublicServer::new(PublicHandler::new(daemon.clone())))
-        .add_service(DkgPublicServer::new(DkgPublicHandler::new(daemon)))
-        .add_service(health_service)
-        .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async move {
+    router(daemon)
+        .serve_with_incoming_shutdown(incoming, async move {
             let () = cancel.cancelled().await;
         })
         .await
@@ -172,17 +592,44 @@ pub async fn start_server<N: NewTcpListener>(
     Ok(())
 }
 
+/// Services exposed on node address.
+pub(super) fn router(daemon: Arc<Daemon>) -> Router {
+    let (_health_reporter, health_service) = tonic_health::server::health_reporter();
+    Server::builder()
+        .add_service(ProtocolServer::new(ProtocolHandler(daemon.clone())))
+        .add_service(PublicServer::new(PublicHandler::new(daemon.clone())))
+        .add_service(DkgPublicServer::new(DkgPublicHandler::new(daemon)))
+        .add_service(health_service)
+}
+
 #[derive(Clone)]
 pub struct ProtocolClient {
-    client: _ProtocolClient<Channel>,
+    client: _ProtocolClient<PeerChannel>,
+    /// Partial beacons are sent over QUIC if enabled, see [`Self::enable_quic`].
+    #[cfg(feature = "quic")]
+    quic: Option<super::quic::QuicPeer>,
 }
 
 impl ProtocolClient {
     pub async fn new(address: &Address) -> anyhow::Result<Self> {
         let channel = super::utils::connect(address).await?;
-        let client = _ProtocolClient::new(channel);
 
-        Ok(Self { client })
+        Ok(Self::with_channel(channel, address))
+    }
+
+    /// Returns client over existing connection to the peer, see [`super::peers`].
+    pub fn with_channel(channel: Channel, address: &Address) -> Self {
+        Self {
+            client: _ProtocolClient::new(peer_channel(channel, address)),
+            #[cfg(feature = "quic")]
+            quic: None,
+        }
+    }
+
+    /// Sends partial beacons over QUIC while peer accepts them, gRPC is used as fallback.
+    #[cfg(feature = "quic")]
+    pub fn enable_quic(&mut self, peer: &Address) {
+        self.quic = Some(super::quic::QuicPeer::new(peer.clone()));
     }
 
     pub async fn get_identity(
@@ -212,7 +659,55 @@ impl ProtocolClient {
         Ok(stream)
     }
 
+    /// Returns the group used at given epoch, `0` stands for the latest group.
+    pub async fn group_for_epoch(
+        &mut self,
+        epoch: u32,
+        beacon_id: String,
+    ) -> anyhow::Result<crate::transport::drand::GroupPacket> {
+        let request = GroupRequest {
+            metadata: Some(protobuf::Metadata::with_id(beacon_id)),
+            epoch,
+        };
+        let response = self.client.group_for_epoch(request).await?;
+        let inner = response.into_inner().validate()?;
+
+        Ok(inner)
+    }
+
+    /// Sends signed status request, see [`crate::core::remote_status`].
+    pub async fn status(&mut self, request: StatusRequest) -> anyhow::Result<StatusResponse> {
+        let response = self.client.status(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    /// Sends signed leave notice, see [`crate::core::leave`].
+    pub async fn leave_notice(&mut self, request: LeaveNoticeRequest) -> anyhow::Result<()> {
+        let _ = self.client.leave_notice(request).await?;
+
+        Ok(())
+    }
+
+    /// Returns ping response and measured round-trip time of the request.
+    pub async fn ping(&mut self) -> anyhow::Result<(PingResponse, Duration)> {
+        let request = PingRequest {
+            metadata: Some(protobuf::Metadata::with_default()),
+        };
+        let start = Instant::now();
+        let response = self.client.ping(request).await?;
+        let rtt = start.elapsed();
+
+        Ok((response.into_inner(), rtt))
+    }
+
     pub async fn partial_beacon(&mut self, packet: PartialBeaconPacket) -> anyhow::Result<()> {
+        #[cfg(feature = "quic")]
+        if let Some(quic) = self.quic.as_mut() {
+            if quic.send(&packet).await.is_ok() {
+                return Ok(());
+            }
+        }
         let _ = self.client.partial_beacon(packet).await?;
 
         Ok(())
@@ -226,3 +721,212 @@ impl Deref for ProtocolHandler {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn sync_limits() {
+        let limits = SyncLimits::new(2, 1, SyncMemory::new(1 << 20));
+        let permit = limits.acquire().unwrap();
+        assert_eq!(
+            limits.acquire().unwrap_err().code(),
+            tonic::Code::ResourceExhausted
+        );
+
+        let (store_tx, store_rx) = mpsc::channel(8);
+        for round in 1..=5 {
+            store_tx.send(Ok(beacon(round))).await.unwrap();
+        }
+        drop(store_tx);
+        let mut stream = Box::pin(limits.throttle(store_rx, permit));
+        let mut rounds = vec![];
+        while let Some(beacon) = stream.next().await {
+            rounds.push(beacon.unwrap().round);
+        }
+        assert_eq!(rounds, [1, 2, 3, 4, 5]);
+        // Permit and buffer memory are released once the stream is finished.
+        drop(stream);
+        assert!(limits.acquire().is_ok());
+        assert_eq!(limits.memory.available.available_permits(), 1 << 20);
+    }
+
+    #[tokio::test]
+    async fn slow_follower_is_dropped() {
+        let size = beacon(1).encoded_len();
+        // Memory cap fits two beacons.
+        let mut limits = SyncLimits::new(8, 2, SyncMemory::new(2 * size));
+        limits.stall_timeout = Duration::from_millis(50);
+        let (slow_tx, slow_rx) = mpsc::channel(8);
+        let (fast_tx, fast_rx) = mpsc::channel(8);
+        for round in 1..=4 {
+            slow_tx.send(Ok(beacon(round))).await.unwrap();
+            fast_tx.send(Ok(beacon(round))).await.unwrap();
+        }
+        let mut slow = Box::pin(limits.throttle(slow_rx, limits.acquire().unwrap()));
+        tokio::task::yield_now().await;
+        // Slow follower holds the whole cap, the other stream is paused.
+        let mut fast = Box::pin(limits.throttle(fast_rx, limits.acquire().unwrap()));
+        tokio::time::sleep(limits.stall_timeout * 4).await;
+
+        assert_eq!(fast.next().await.unwrap().unwrap().round, 1);
+        let err = slow.next().await.unwrap().unwrap_err();
+        assert_eq!(err.code(), tonic::Code::ResourceExhausted);
+        assert!(slow.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn received_beacons_share_cap() {
+        let size = beacon(1).encoded_len();
+        let memory = SyncMemory::new(2 * size);
+        // Batch of the served stream fits the whole cap.
+        let mut limits = SyncLimits::new(2, 1, memory.clone());
+        limits.stall_timeout = Duration::from_millis(50);
+        let (tx, rx) = mpsc::channel(8);
+        for round in 1..=3 {
+            tx.send(Ok(beacon(round))).await.unwrap();
+        }
+        let mut slow = Box::pin(limits.throttle(rx, limits.acquire().unwrap()));
+        tokio::task::yield_now().await;
+
+        // Received beacon is paused until the stalled follower is dropped.
+        let received = tokio::time::timeout(limits.stall_timeout * 4, memory.buffer(beacon(4)))
+            .await
+            .unwrap();
+        let err = slow.next().await.unwrap().unwrap_err();
+        assert_eq!(err.code(), tonic::Code::ResourceExhausted);
+        assert_eq!(memory.available.available_permits(), size);
+        assert_eq!(received.into_inner().round, 4);
+        assert_eq!(memory.available.available_permits(), 2 * size);
+    }
+
+    #[tokio::test]
+    async fn old_golang_peer_gets_identity() {
+        use super::super::handshake::FEATURES;
+        use super::super::sim::SimNode;
+        use super::super::utils::VERSION;
+        use crate::chain::time::time_now;
+        use crate::chain::time::MockClock;
+        use crate::core::beacon::DEFAULT_BEACON_ID;
+        use energon::drand::schemes::DefaultScheme;
+
+        let node = SimNode::start::<DefaultScheme>(
+            DEFAULT_BEACON_ID,
+            b"golang-v1",
+            Arc::new(MockClock::new(time_now())),
+        )
+        .unwrap();
+        let channel = super::super::utils::connect(&node.address).await.unwrap();
+        let mut client = _ProtocolClient::new(channel);
+        let request = |version: Option<(u32, u32)>, features: &[&str]| IdentityRequest {
+            metadata: Some(protobuf::Metadata {
+                node_version: version.map(|(major, minor)| protobuf::NodeVersion {
+                    major,
+                    minor,
+                    patch: 11,
+                    prerelease: String::new(),
+                }),
+                beacon_id: DEFAULT_BEACON_ID.into(),
+                features: features.iter().map(ToString::to_string).collect(),
+                ..Default::default()
+            }),
+        };
+
+        // Golang v1.5 node and a peer without version are served, mismatch is only logged.
+        let identity = client
+            .get_identity(request(Some((1, 5)), &[]))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(identity.address, node.address.as_str());
+        assert!(client.get_identity(request(None, &[])).await.is_ok());
+
+        // drand-rs node of another minor version is rejected.
+        let err = client
+            .get_identity(request(Some((0, VERSION.minor + 1)), FEATURES))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::FailedPrecondition);
+    }
+
+    #[tokio::test]
+    async fn ping_reports_beacon_ids() {
+        use super::super::sim::SimNode;
+        use crate::chain::time::time_now;
+        use crate::chain::time::MockClock;
+        use crate::core::beacon::DEFAULT_BEACON_ID;
+        use energon::drand::schemes::DefaultScheme;
+
+        let node = SimNode::start::<DefaultScheme>(
+            DEFAULT_BEACON_ID,
+            b"ping",
+            Arc::new(MockClock::new(time_now())),
+        )
+        .unwrap();
+
+        let mut client = ProtocolClient::new(&node.address).await.unwrap();
+        let (response, _rtt) = client.ping().await.unwrap();
+        assert!(response.metadata.is_some_and(|m| m.node_version.is_some()));
+        assert_eq!(response.beacons.len(), 1);
+        assert_eq!(response.beacons[0].beacon_id, DEFAULT_BEACON_ID);
+        assert_eq!(response.beacons[0].round, 0);
+    }
+
+    #[tokio::test]
+    async fn group_for_epoch_serves_history() {
+        use super::super::sim::SimNode;
+        use crate::chain::time::time_now;
+        use crate::chain::time::MockClock;
+        use crate::core::beacon::DEFAULT_BEACON_ID;
+        use crate::key::group::Group;
+        use crate::key::toml::tests::toml_samples;
+        use crate::key::toml::Toml;
+        use crate::key::Scheme;
+        use energon::drand::schemes::DefaultScheme;
+
+        let node = SimNode::start::<DefaultScheme>(
+            DEFAULT_BEACON_ID,
+            b"group-history",
+            Arc::new(MockClock::new(time_now())),
+        )
+        .unwrap();
+        let fs = node.store();
+        let first: Group<DefaultScheme> =
+            Toml::toml_decode(&toml_samples::group().parse().unwrap()).unwrap();
+        let mut second: Group<DefaultScheme> =
+            Toml::toml_decode(&toml_samples::group().parse().unwrap()).unwrap();
+        second.threshold = 3;
+        second.transition_time += 30;
+        second.nodes.pop();
+        fs.save_group_epoch(&first, 1).unwrap();
+        fs.save_group_epoch(&second, 2).unwrap();
+        fs.save_group(&second).unwrap();
+
+        let mut client = ProtocolClient::new(&node.address).await.unwrap();
+        for (epoch, expected) in [(1, &first), (2, &second), (0, &second)] {
+            let group = client
+                .group_for_epoch(epoch, DEFAULT_BEACON_ID.into())
+                .await
+                .unwrap();
+            assert_eq!(group.threshold, expected.threshold);
+            assert_eq!(group.transition_time, expected.transition_time);
+            assert_eq!(group.nodes.len(), expected.nodes.len());
+            assert_eq!(group.scheme_id, DefaultScheme::ID);
+        }
+        // Epoch without history is not served.
+        assert!(client
+            .group_for_epoch(3, DEFAULT_BEACON_ID.into())
+            .await
+            .is_err());
+        assert!(client.group_for_epoch(1, "unknown".into()).await.is_err());
+    }
+
+    fn beacon(round: u64) -> BeaconPacket {
+        BeaconPacket {
+            round,
+            signature: vec![0xaa; 48],
+            ..Default::default()
+        }
+    }
+}
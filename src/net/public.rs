@@ -1,9 +1,12 @@
 //! This module provides server and client implementations for RPC Public.
 
+use super::error::NodeError;
+use super::utils::peer_channel;
 use super::utils::Address;
 use super::utils::Callback;
+use super::utils::PeerChannel;
 use super::utils::ToStatus;
-use super::utils::ERR_METADATA_IS_MISSING;
+use crate::chain::merkle;
 use crate::core::beacon::BeaconCmd;
 use crate::core::daemon::Daemon;
 use crate::protobuf::drand as protobuf;
@@ -15,6 +18,8 @@ use protobuf::ChainInfoPacket;
 use protobuf::ChainInfoRequest;
 use protobuf::ListBeaconIDsRequest;
 use protobuf::ListBeaconIDsResponse;
+use protobuf::MerkleProofRequest;
+use protobuf::MerkleProofResponse;
 use protobuf::PublicRandRequest;
 use protobuf::PublicRandResponse;
 
@@ -45,11 +50,30 @@ impl Public for PublicHandler {
     /// Server streaming response type for the `public_rand_stream` method
     type PublicRandStreamStream = ResponseStream;
 
+    /// Returns stored beacon of the requested round with its randomness, the latest beacon
+    /// is returned for round 0.
     async fn public_rand(
         &self,
-        _request: Request<PublicRandRequest>,
+        request: Request<PublicRandRequest>,
     ) -> Result<Response<PublicRandResponse>, Status> {
-        Err(Status::unimplemented("public_rand: PublicRandRequest"))
+        let request = request.get_ref();
+        let id = request.metadata.as_ref().map_or_else(
+            || Err(Status::from(NodeError::MetadataMissing)),
+            |meta| Ok(meta.beacon_id.as_str()),
+        )?;
+
+        let (tx, rx) = Callback::new();
+        self.beacons()
+            .cmd(BeaconCmd::PublicRand(request.round, tx), id)
+            .await
+            .map_err(|err| err.to_status(id))?;
+
+        let beacon = rx
+            .await
+            .map_err(|recv_err| recv_err.to_status(id))?
+            .map_err(|store_err| store_err.to_status(id))?;
+
+        Ok(Response::new(beacon))
     }
 
     async fn public_rand_stream(
@@ -66,7 +90,7 @@ impl Public for PublicHandler {
         request: Request<ChainInfoRequest>,
     ) -> Result<Response<ChainInfoPacket>, Status> {
         let id = request.get_ref().metadata.as_ref().map_or_else(
-            || Err(Status::data_loss(ERR_METADATA_IS_MISSING)),
+            || Err(Status::from(NodeError::MetadataMissing)),
             |meta| Ok(meta.beacon_id.as_str()),
         )?;
 
@@ -92,17 +116,48 @@ impl Public for PublicHandler {
             "list_beacon_i_ds: ListBeaconIDsRequest",
         ))
     }
+
+    async fn merkle_proof(
+        &self,
+        request: Request<MerkleProofRequest>,
+    ) -> Result<Response<MerkleProofResponse>, Status> {
+        let request = request.get_ref();
+        let id = request.metadata.as_ref().map_or_else(
+            || Err(Status::from(NodeError::MetadataMissing)),
+            |meta| Ok(meta.beacon_id.as_str()),
+        )?;
+
+        let (tx, rx) = Callback::new();
+        self.beacons()
+            .cmd(BeaconCmd::MerkleProof(request.round, tx), id)
+            .await
+            .map_err(|err| err.to_status(id))?;
+
+        let proof = rx
+            .await
+            .map_err(|recv_err| recv_err.to_status(id))?
+            .map_err(|store_err| store_err.to_status(id))?;
+
+        Ok(Response::new(proof))
+    }
 }
 
 pub struct PublicClient {
-    client: _PublicClient<Channel>,
+    client: _PublicClient<PeerChannel>,
 }
 
 impl PublicClient {
     pub async fn new(address: &Address) -> anyhow::Result<Self> {
         let channel = super::utils::connect(address).await?;
-        let client = _PublicClient::new(channel);
-        Ok(Self { client })
+
+        Ok(Self::with_channel(channel, address))
+    }
+
+    /// Returns client over existing connection to the peer, see [`super::peers`].
+    pub fn with_channel(channel: Channel, address: &Address) -> Self {
+        Self {
+            client: _PublicClient::new(peer_channel(channel, address)),
+        }
     }
 
     pub async fn chain_info(&mut self, beacon_id: String) -> anyhow::Result<ChainInfoPacket> {
@@ -130,6 +185,46 @@ impl PublicClient {
 
         Ok(response)
     }
+
+    /// Returns beacon of the round, round 0 requests the latest beacon.
+    pub async fn public_rand(
+        &mut self,
+        round: u64,
+        beacon_id: String,
+    ) -> anyhow::Result<PublicRandResponse> {
+        let metadata = Some(Metadata::golang_node_version(beacon_id, None));
+        let request = PublicRandRequest { round, metadata };
+
+        Ok(self.client.public_rand(request).await?.into_inner())
+    }
+
+    /// Returns inclusion proof of the round, proof is verified against its own root.
+    pub async fn merkle_proof(
+        &mut self,
+        round: u64,
+        beacon_id: String,
+    ) -> anyhow::Result<MerkleProofResponse> {
+        let metadata = Some(Metadata::golang_node_version(beacon_id, None));
+        let request = MerkleProofRequest { round, metadata };
+        let response = self.client.merkle_proof(request).await?.into_inner();
+
+        let path = response
+            .path
+            .iter()
+            .map(|h| merkle::Hash::try_from(h.as_slice()))
+            .collect::<Result<Vec<_>, _>>()
+            .context("invalid length of merkle path node")?;
+        let root = merkle::Hash::try_from(response.root.as_slice())
+            .context("invalid length of merkle root")?;
+        let leaf = merkle::leaf_hash(response.round, &response.signature);
+        if response.round != round
+            || !merkle::verify(&leaf, round, response.tree_size, &path, &root)
+        {
+            bail!("received invalid merkle proof for round {round}")
+        }
+
+        Ok(response)
+    }
 }
 
 impl Deref for PublicHandler {
@@ -0,0 +1,425 @@
+//! Experimental QUIC transport for partial beacons, compiled with `quic` feature.
+//!
+//! Node binds UDP socket at its private listen address and accepts partial beacon packets over
+//! bidirectional streams, one stream per packet, so a lost datagram delays only its own round
+//! and reconnects do not wait for TCP and HTTP/2 handshakes. Only peers reporting QUIC support
+//! in handshake, see [`super::handshake::QUIC`], are connected lazily on the same address, and
+//! packets are sent over gRPC while QUIC connection is not available.
+//!
+//! Server replies with [`ACK`] once the packet is processed by the beacon process, the stream is
+//! reset if the packet can not be delivered. Packets which are not acknowledged within
+//! [`ACK_TIMEOUT`] are sent again over gRPC, duplicates are ignored by the partials cache.
+//!
+//! Note: server certificate is self-signed and is not verified by peers, partial beacons are
+//! authenticated by their signatures.
+use super::protocol::PartialPacket;
+use super::utils::Address;
+use super::utils::Callback;
+
+use crate::core::daemon::Daemon;
+use crate::protobuf::drand::PartialBeaconPacket;
+
+use prost::Message;
+use quinn::crypto::rustls::QuicClientConfig;
+use quinn::crypto::rustls::QuicServerConfig;
+use rustls::client::danger::HandshakeSignatureValid;
+use rustls::client::danger::ServerCertVerified;
+use rustls::client::danger::ServerCertVerifier;
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::CertificateDer;
+use rustls::pki_types::PrivatePkcs8KeyDer;
+use rustls::pki_types::ServerName;
+use rustls::pki_types::UnixTime;
+use rustls::DigitallySignedStruct;
+use rustls::SignatureScheme;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+use tracing::error;
+
+/// Application protocol of partial beacon streams.
+const ALPN: &[u8] = b"drand-partial/1";
+/// Server name of self-signed certificate.
+const SERVER_NAME: &str = "drand";
+/// Upper bound of encoded partial beacon packet.
+const MAX_PACKET_SIZE: usize = 4 * 1024;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+/// Reply of the server to a processed packet.
+const ACK: u8 = 1;
+/// Time to wait for [`ACK`] before falling back to gRPC.
+const ACK_TIMEOUT: Duration = Duration::from_secs(2);
+/// Delay before connecting again after QUIC connection is failed.
+const RETRY_AFTER: Duration = Duration::from_secs(30);
+
+#[derive(thiserror::Error, Debug)]
+pub enum QuicError {
+    #[error("address {0} is not resolved")]
+    Unresolved(String),
+    #[error("tls: {0}")]
+    Tls(String),
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("connect: {0}")]
+    Connect(#[from] quinn::ConnectError),
+    #[error("connection: {0}")]
+    Connection(#[from] quinn::ConnectionError),
+    #[error("write: {0}")]
+    Write(#[from] quinn::WriteError),
+    #[error("read: {0}")]
+    Read(#[from] quinn::ReadExactError),
+    #[error("stream is closed")]
+    ClosedStream(#[from] quinn::ClosedStream),
+    #[error("connection timeout")]
+    Timeout,
+    #[error("packet is not acknowledged in time")]
+    AckTimeout,
+    #[error("unexpected ack: {0}")]
+    InvalidAck(u8),
+    #[error("connection is not available")]
+    Unavailable,
+}
+
+/// Accepts partial beacons over QUIC until the daemon is stopped, errors are logged as
+/// gRPC server keeps serving partial beacons.
+pub async fn start_server(daemon: Arc<Daemon>, listen: Address) {
+    let token = daemon.token.clone();
+    // Packet is acknowledged once the beacon process replies, whatever the result.
+    let deliver = move |partial| {
+        let daemon = daemon.clone();
+        async move {
+            let (tx, rx) = Callback::new();
+            daemon.beacons().send_partial((partial, tx)).await.ok()?;
+            rx.await.ok().map(|_| ())
+        }
+    };
+    let endpoint = match bind(&listen).await {
+        Ok(endpoint) => endpoint,
+        Err(err) => {
+            error!("quic: server is stopped: {err}");
+            return;
+        }
+    };
+    serve(endpoint, token, deliver).await;
+}
+
+async fn bind(listen: &Address) -> Result<quinn::Endpoint, QuicError> {
+    let addr = resolve(listen).await?;
+    let endpoint = quinn::Endpoint::server(server_config()?, addr)?;
+    debug!("quic: listening for partial beacons at {addr}");
+
+    Ok(endpoint)
+}
+
+/// Passes accepted packets to `deliver`, which returns `None` if packet is not delivered.
+async fn serve<F, Fut>(endpoint: quinn::Endpoint, token: CancellationToken, deliver: F)
+where
+    F: Fn(PartialPacket) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Option<()>> + Send,
+{
+    loop {
+        let incoming = tokio::select! {
+            () = token.cancelled() => break,
+            incoming = endpoint.accept() => incoming,
+        };
+        let Some(incoming) = incoming else { break };
+        tokio::spawn(serve_connection(incoming, deliver.clone()));
+    }
+    endpoint.close(0u32.into(), b"shutdown");
+}
+
+async fn serve_connection<F, Fut>(incoming: quinn::Incoming, deliver: F)
+where
+    F: Fn(PartialPacket) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Option<()>> + Send,
+{
+    let conn = match incoming.await {
+        Ok(conn) => conn,
+        Err(err) => {
+            debug!("quic: incoming connection: {err}");
+            return;
+        }
+    };
+    let from = conn.remote_address().ip().to_canonical().to_string();
+
+    while let Ok((mut send, mut recv)) = conn.accept_bi().await {
+        let deliver = deliver.clone();
+        let from = from.clone();
+        tokio::spawn(async move {
+            let packet = match recv.read_to_end(MAX_PACKET_SIZE).await {
+                Ok(bytes) => match PartialBeaconPacket::decode(bytes.as_slice()) {
+                    Ok(packet) => packet,
+                    Err(err) => {
+                        debug!("quic: partial from {from}: {err}");
+                        let _ = send.reset(0u32.into());
+                        return;
+                    }
+                },
+                Err(err) => {
+                    debug!("quic: partial from {from}: {err}");
+                    let _ = send.reset(0u32.into());
+                    return;
+                }
+            };
+            if deliver(PartialPacket { packet, from }).await.is_some() {
+                if send.write_all(&[ACK]).await.is_ok() {
+                    let _ = send.finish();
+                }
+            } else {
+                let _ = send.reset(0u32.into());
+            }
+        });
+    }
+}
+
+/// QUIC connection of a peer, connected at first packet and after [`RETRY_AFTER`] on failures.
+#[derive(Clone)]
+pub struct QuicPeer {
+    peer: Address,
+    conn: Option<quinn::Connection>,
+    retry_at: Instant,
+}
+
+impl QuicPeer {
+    pub fn new(peer: Address) -> Self {
+        Self {
+            peer,
+            conn: None,
+            retry_at: Instant::now(),
+        }
+    }
+
+    /// Sends packet over QUIC and waits for [`ACK`], caller is expected to fall back to gRPC on
+    /// error.
+    pub async fn send(&mut self, packet: &PartialBeaconPacket) -> Result<(), QuicError> {
+        let result = self.try_send(packet).await;
+        match &result {
+            Ok(()) | Err(QuicError::Unavailable) => (),
+            Err(err) => {
+                debug!("quic: {}: {err}, falling back to gRPC", self.peer);
+                self.conn = None;
+                self.retry_at = Instant::now() + RETRY_AFTER;
+            }
+        }
+
+        result
+    }
+
+    async fn try_send(&mut self, packet: &PartialBeaconPacket) -> Result<(), QuicError> {
+        let conn = match &self.conn {
+            Some(conn) => conn.clone(),
+            None if Instant::now() < self.retry_at => return Err(QuicError::Unavailable),
+            None => {
+                let conn = connect(&self.peer).await?;
+                self.conn = Some(conn.clone());
+                conn
+            }
+        };
+        let (mut send, mut recv) = conn.open_bi().await?;
+        send.write_all(&packet.encode_to_vec()).await?;
+        send.finish()?;
+
+        let mut ack = [0; 1];
+        tokio::time::timeout(ACK_TIMEOUT, recv.read_exact(&mut ack))
+            .await
+            .map_err(|_| QuicError::AckTimeout)??;
+        if ack[0] != ACK {
+            return Err(QuicError::InvalidAck(ack[0]));
+        }
+
+        Ok(())
+    }
+}
+
+async fn connect(peer: &Address) -> Result<quinn::Connection, QuicError> {
+    let addr = resolve(peer).await?;
+    let bind: SocketAddr = if addr.is_ipv6() {
+        (std::net::Ipv6Addr::UNSPECIFIED, 0).into()
+    } else {
+        (std::net::Ipv4Addr::UNSPECIFIED, 0).into()
+    };
+    let endpoint = quinn::Endpoint::client(bind)?;
+    let connecting = endpoint.connect_with(client_config()?, addr, SERVER_NAME)?;
+
+    let conn = tokio::time::timeout(CONNECT_TIMEOUT, connecting)
+        .await
+        .map_err(|_| QuicError::Timeout)??;
+    debug!("quic: connected to {peer}");
+
+    Ok(conn)
+}
+
+async fn resolve(address: &Address) -> Result<SocketAddr, QuicError> {
+    tokio::net::lookup_host(address.as_str())
+        .await?
+        .next()
+        .ok_or_else(|| QuicError::Unresolved(address.to_string()))
+}
+
+fn tls_err(err: impl std::fmt::Display) -> QuicError {
+    QuicError::Tls(err.to_string())
+}
+
+fn server_config() -> Result<quinn::ServerConfig, QuicError> {
+    let cert = rcgen::generate_simple_self_signed(vec![SERVER_NAME.into()]).map_err(tls_err)?;
+    let key = PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der());
+
+    let mut tls = rustls::ServerConfig::builder_with_provider(provider())
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .map_err(tls_err)?
+        .with_no_client_auth()
+        .with_single_cert(vec![CertificateDer::from(cert.cert)], key.into())
+        .map_err(tls_err)?;
+    tls.alpn_protocols = vec![ALPN.to_vec()];
+    let crypto = QuicServerConfig::try_from(tls).map_err(tls_err)?;
+
+    Ok(quinn::ServerConfig::with_crypto(Arc::new(crypto)))
+}
+
+fn client_config() -> Result<quinn::ClientConfig, QuicError> {
+    let mut tls = rustls::ClientConfig::builder_with_provider(provider())
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .map_err(tls_err)?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(SkipServerVerification(provider())))
+        .with_no_client_auth();
+    tls.alpn_protocols = vec![ALPN.to_vec()];
+    let crypto = QuicClientConfig::try_from(tls).map_err(tls_err)?;
+
+    Ok(quinn::ClientConfig::new(Arc::new(crypto)))
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+/// Accepts any server certificate, handshake signatures are still verified.
+#[derive(Debug)]
+struct SkipServerVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn unavailable_peer_falls_back() {
+        let mut peer = QuicPeer::new(Address::precheck("127.0.0.1:1").unwrap());
+        let packet = PartialBeaconPacket::default();
+        assert!(peer.send(&packet).await.is_err());
+        // Connection is not retried before RETRY_AFTER.
+        assert!(matches!(
+            peer.send(&packet).await,
+            Err(QuicError::Unavailable)
+        ));
+    }
+
+    /// Starts server on a random local port, delivered packets are sent to the returned receiver.
+    async fn test_server(
+        delivered: bool,
+    ) -> (
+        Address,
+        tokio::sync::mpsc::UnboundedReceiver<PartialPacket>,
+        CancellationToken,
+    ) {
+        let endpoint = quinn::Endpoint::server(
+            server_config().unwrap(),
+            (std::net::Ipv4Addr::LOCALHOST, 0).into(),
+        )
+        .unwrap();
+        let addr = Address::precheck(&endpoint.local_addr().unwrap().to_string()).unwrap();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let token = CancellationToken::new();
+        let deliver = move |partial| {
+            let tx = tx.clone();
+            async move {
+                tx.send(partial).ok()?;
+                delivered.then_some(())
+            }
+        };
+        tokio::spawn(serve(endpoint, token.clone(), deliver));
+
+        (addr, rx, token)
+    }
+
+    #[tokio::test]
+    async fn partial_round_trip() {
+        let (addr, mut rx, token) = test_server(true).await;
+        let mut peer = QuicPeer::new(addr);
+        for round in 1..=3 {
+            let packet = PartialBeaconPacket {
+                round,
+                partial_sig: vec![7; 50],
+                ..Default::default()
+            };
+            peer.send(&packet).await.unwrap();
+
+            let received = rx.recv().await.unwrap();
+            assert_eq!(received.packet, packet);
+            assert_eq!(received.from, "127.0.0.1");
+        }
+        token.cancel();
+    }
+
+    #[tokio::test]
+    async fn undelivered_partial_is_not_acknowledged() {
+        let (addr, mut rx, token) = test_server(false).await;
+        let mut peer = QuicPeer::new(addr);
+        let packet = PartialBeaconPacket::default();
+        assert!(matches!(peer.send(&packet).await, Err(QuicError::Read(_))));
+        assert!(rx.recv().await.is_some());
+        // Packets are sent over gRPC until the connection is retried.
+        assert!(matches!(
+            peer.send(&packet).await,
+            Err(QuicError::Unavailable)
+        ));
+        token.cancel();
+    }
+}
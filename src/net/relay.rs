@@ -0,0 +1,388 @@
+//! Client of HTTP relays used as sync sources by `drand follow`.
+//!
+//! Relay URL should point to the chain root, e.g. `https://api.drand.sh/<chain hash>`, so chain
+//! info is fetched from `{url}/info` and beacons from `{url}/public/{round}`. Beacons are
+//! converted into [`BeaconPacket`] and verified by the syncer the same way as gRPC streams.
+//!
+//! Beacon archives of [`crate::chain::archive`] format are served over plain HTTP as well,
+//! see [`HttpArchive`].
+use crate::chain::archive::ArchiveError;
+use crate::chain::archive::Manifest;
+use crate::chain::archive::MANIFEST;
+use crate::net::protocol::Buffered;
+use crate::net::protocol::SyncMemory;
+use crate::protobuf::drand::BeaconPacket;
+use crate::protobuf::drand::ChainInfoPacket;
+use crate::protobuf::drand::Metadata;
+
+use serde_json::Value;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::debug;
+
+/// Timeout of a single relay request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Beacons fetched ahead of the syncer, accounted against [`SyncMemory`].
+const STREAM_BUFFER: usize = 32;
+
+#[derive(thiserror::Error, Debug)]
+pub enum RelayError {
+    #[error("relay url should start with http:// or https://")]
+    InvalidUrl,
+    #[error("http: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("unexpected status {0}")]
+    Status(reqwest::StatusCode),
+    #[error("json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("missing or invalid field '{0}'")]
+    Field(&'static str),
+    #[error("archive: {0}")]
+    Archive(#[from] ArchiveError),
+    #[error("archive belongs to beacon id {beacon_id}, chain hash {chain_hash}")]
+    ArchiveMismatch {
+        beacon_id: String,
+        chain_hash: String,
+    },
+}
+
+/// HTTP relay serving public beacons of a single chain.
+#[derive(Clone)]
+pub struct HttpRelay {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl HttpRelay {
+    /// Returns true if sync node is given as HTTP relay URL rather than gRPC address.
+    pub fn is_relay_url(node: &str) -> bool {
+        node.starts_with("http://") || node.starts_with("https://")
+    }
+
+    pub fn new(url: &str) -> Result<Self, RelayError> {
+        if !Self::is_relay_url(url) {
+            return Err(RelayError::InvalidUrl);
+        }
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+
+        Ok(Self {
+            url: url.trim_end_matches('/').to_string(),
+            client,
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub async fn chain_info(&self, beacon_id: &str) -> Result<ChainInfoPacket, RelayError> {
+        let json = self.get("info").await?;
+        parse_info(&json, beacon_id)
+    }
+
+    pub async fn beacon(&self, round: u64, beacon_id: &str) -> Result<BeaconPacket, RelayError> {
+        let json = self.get(&format!("public/{round}")).await?;
+        parse_beacon(&json, beacon_id)
+    }
+
+    /// Streams beacons within `[from, to]`, the stream ends at the first failed request.
+    pub fn stream(
+        self,
+        from: u64,
+        to: u64,
+        beacon_id: String,
+        memory: SyncMemory,
+    ) -> mpsc::Receiver<Buffered<BeaconPacket>> {
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        tokio::spawn(async move {
+            for round in from..=to {
+                match self.beacon(round, &beacon_id).await {
+                    Ok(packet) => {
+                        if tx.send(memory.buffer(packet).await).await.is_err() {
+                            return;
+                        }
+                    }
+                    Err(err) => {
+                        debug!("relay {}: round {round}: {err}", self.url);
+                        return;
+                    }
+                }
+            }
+        });
+
+        rx
+    }
+
+    async fn get(&self, path: &str) -> Result<Value, RelayError> {
+        Ok(serde_json::from_slice(&self.get_bytes(path).await?)?)
+    }
+
+    async fn get_bytes(&self, path: &str) -> Result<Vec<u8>, RelayError> {
+        let response = self
+            .client
+            .get(format!("{}/{path}", self.url))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(RelayError::Status(response.status()));
+        }
+
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+/// Beacon archive served over HTTP, e.g. public URL of the archiver bucket prefix.
+#[derive(Clone)]
+pub struct HttpArchive {
+    http: HttpRelay,
+    manifest: Manifest,
+}
+
+impl HttpArchive {
+    /// Downloads manifest of the archive, archive should belong to the requested chain.
+    pub async fn open(url: &str, beacon_id: &str, chain_hash: &[u8]) -> Result<Self, RelayError> {
+        let http = HttpRelay::new(url)?;
+        let manifest = Manifest::from_json(&http.get_bytes(MANIFEST).await?)?;
+        if manifest.beacon_id != beacon_id || manifest.chain_hash != hex::encode(chain_hash) {
+            return Err(RelayError::ArchiveMismatch {
+                beacon_id: manifest.beacon_id,
+                chain_hash: manifest.chain_hash,
+            });
+        }
+
+        Ok(Self { http, manifest })
+    }
+
+    pub fn url(&self) -> &str {
+        self.http.url()
+    }
+
+    /// Returns the latest archived round.
+    pub fn last_round(&self) -> u64 {
+        self.manifest.last_round()
+    }
+
+    /// Streams archived beacons within `[from, to]`, the stream ends at the first failed chunk.
+    pub fn stream(
+        self,
+        from: u64,
+        to: u64,
+        beacon_id: String,
+        memory: SyncMemory,
+    ) -> mpsc::Receiver<Buffered<BeaconPacket>> {
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        tokio::spawn(async move {
+            for chunk in self
+                .manifest
+                .chunks
+                .iter()
+                .filter(|c| c.to >= from && c.from <= to)
+            {
+                let beacons = match self.http.get_bytes(&chunk.name).await {
+                    Ok(data) => chunk.decode(&data, &beacon_id).map_err(RelayError::from),
+                    Err(err) => Err(err),
+                };
+                let beacons = match beacons {
+                    Ok(beacons) => beacons,
+                    Err(err) => {
+                        debug!("archive {}: {}: {err}", self.http.url, chunk.name);
+                        return;
+                    }
+                };
+                for beacon in beacons
+                    .into_iter()
+                    .filter(|b| (from..=to).contains(&b.round))
+                {
+                    if tx.send(memory.buffer(beacon).await).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        rx
+    }
+}
+
+fn hex_field(json: &Value, field: &'static str) -> Result<Vec<u8>, RelayError> {
+    json.get(field)
+        .and_then(Value::as_str)
+        .and_then(|s| hex::decode(s).ok())
+        .ok_or(RelayError::Field(field))
+}
+
+fn u64_field(json: &Value, field: &'static str) -> Result<u64, RelayError> {
+    json.get(field)
+        .and_then(Value::as_u64)
+        .ok_or(RelayError::Field(field))
+}
+
+/// Parses chain info JSON, beacon id of default chain is not reported by relays.
+pub fn parse_info(json: &Value, beacon_id: &str) -> Result<ChainInfoPacket, RelayError> {
+    let reported_id = json
+        .get("metadata")
+        .and_then(|m| m.get("beaconID"))
+        .and_then(Value::as_str)
+        .unwrap_or(beacon_id);
+    if reported_id != beacon_id {
+        return Err(RelayError::Field("metadata.beaconID"));
+    }
+
+    Ok(ChainInfoPacket {
+        public_key: hex_field(json, "public_key")?,
+        period: u32::try_from(u64_field(json, "period")?)
+            .map_err(|_| RelayError::Field("period"))?,
+        genesis_time: i64::try_from(u64_field(json, "genesis_time")?)
+            .map_err(|_| RelayError::Field("genesis_time"))?,
+        hash: hex_field(json, "hash")?,
+        group_hash: hex_field(json, "groupHash")?,
+        scheme_id: json
+            .get("schemeID")
+            .and_then(Value::as_str)
+            .ok_or(RelayError::Field("schemeID"))?
+            .to_string(),
+        metadata: Some(Metadata::with_id(beacon_id.to_string())),
+    })
+}
+
+fn parse_beacon(json: &Value, beacon_id: &str) -> Result<BeaconPacket, RelayError> {
+    // Previous signature is absent for unchained schemes.
+    let previous_signature = match json.get("previous_signature") {
+        Some(_) => hex_field(json, "previous_signature")?,
+        None => vec![],
+    };
+
+    Ok(BeaconPacket {
+        previous_signature: previous_signature.into(),
+        round: u64_field(json, "round")?,
+        signature: hex_field(json, "signature")?.into(),
+        metadata: Some(Metadata::with_id(beacon_id.to_string())),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+
+    /// Serves given files over plain HTTP, returns base URL.
+    async fn serve(files: HashMap<String, Vec<u8>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = vec![0; 4096];
+                let n = stream.read(&mut buf).await.unwrap_or_default();
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let path = request.split(' ').nth(1).unwrap_or_default();
+                let (status, body) = match files.get(path.trim_start_matches('/')) {
+                    Some(body) => ("200 OK", body.as_slice()),
+                    None => ("404 Not Found", [].as_slice()),
+                };
+                let head = format!(
+                    "HTTP/1.1 {status}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(head.as_bytes()).await;
+                let _ = stream.write_all(body).await;
+            }
+        });
+
+        url
+    }
+
+    fn beacons(from: u64, to: u64) -> Vec<BeaconPacket> {
+        (from..=to)
+            .map(|round| BeaconPacket {
+                previous_signature: round.to_be_bytes().to_vec().into(),
+                round,
+                signature: (round + 1).to_be_bytes().to_vec().into(),
+                metadata: Some(Metadata::with_id("default".to_string())),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn archive_roundtrip() {
+        let hash = [1, 2];
+        let mut manifest = Manifest::new("default", &hash, "pedersen-bls-chained", 4);
+        let mut files = HashMap::new();
+        for from in [1, 5, 9] {
+            let (name, data) = manifest.push(&beacons(from, from + 3)).unwrap();
+            files.insert(name, data);
+        }
+        files.insert(MANIFEST.to_string(), manifest.to_json());
+        let url = serve(files.clone()).await;
+
+        assert!(matches!(
+            HttpArchive::open(&url, "default", &[3, 4]).await,
+            Err(RelayError::ArchiveMismatch { .. })
+        ));
+        let archive = HttpArchive::open(&url, "default", &hash).await.unwrap();
+        assert_eq!(archive.last_round(), 12);
+
+        // Range crossing chunk bounds is streamed as is.
+        let memory = SyncMemory::default();
+        let mut stream = archive
+            .clone()
+            .stream(3, 10, "default".to_string(), memory.clone());
+        let mut received = vec![];
+        while let Some(beacon) = stream.recv().await {
+            received.push(beacon.into_inner());
+        }
+        assert_eq!(received, beacons(3, 10));
+
+        // Stream ends at the first chunk not matching the manifest.
+        let corrupted = &manifest.chunks[1].name;
+        let mut data = files[corrupted].clone();
+        data[0] = b' ';
+        files.insert(corrupted.clone(), data);
+        let archive = HttpArchive::open(&serve(files).await, "default", &hash)
+            .await
+            .unwrap();
+        let mut stream = archive.stream(1, 12, "default".to_string(), memory);
+        let mut received = vec![];
+        while let Some(beacon) = stream.recv().await {
+            received.push(beacon.into_inner());
+        }
+        assert_eq!(received, beacons(1, 4));
+    }
+
+    #[test]
+    fn parse_relay_json() {
+        let info: Value = serde_json::from_str(
+            r#"{"public_key":"8200","period":3,"genesis_time":1692803367,"hash":"52db",
+                "groupHash":"f477","schemeID":"bls-unchained-g1-rfc9380",
+                "metadata":{"beaconID":"quicknet"}}"#,
+        )
+        .unwrap();
+        let packet = parse_info(&info, "quicknet").unwrap();
+        assert_eq!(packet.period, 3);
+        assert_eq!(packet.hash, [0x52, 0xdb]);
+        assert_eq!(packet.scheme_id, "bls-unchained-g1-rfc9380");
+        assert!(matches!(
+            parse_info(&info, "default"),
+            Err(RelayError::Field("metadata.beaconID"))
+        ));
+
+        let beacon: Value = serde_json::from_str(
+            r#"{"round":7,"randomness":"aa","signature":"0102","previous_signature":"03"}"#,
+        )
+        .unwrap();
+        let packet = parse_beacon(&beacon, "default").unwrap();
+        assert_eq!(packet.round, 7);
+        assert_eq!(packet.signature.as_ref(), [1, 2]);
+        assert_eq!(packet.previous_signature.as_ref(), [3]);
+
+        let beacon: Value = serde_json::from_str(r#"{"round":7,"signature":"zz"}"#).unwrap();
+        assert!(matches!(
+            parse_beacon(&beacon, "default"),
+            Err(RelayError::Field("signature"))
+        ));
+    }
+}
@@ -0,0 +1,233 @@
+//! Minimal client of S3-compatible object storage used by the beacon archiver and backups.
+//!
+//! Objects are addressed in path style `{endpoint}/{bucket}/{key}`, which is supported by
+//! AWS and most compatible stores (MinIO, R2, Ceph). Requests are signed with AWS
+//! signature version 4 using payload hash, so no extra SDK is required.
+use crate::secrets::Secret;
+
+use hmac::Hmac;
+use hmac::Mac;
+use reqwest::StatusCode;
+use reqwest::Url;
+use sha2::Digest;
+use sha2::Sha256;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+/// Timeout of a single object request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(thiserror::Error, Debug)]
+pub enum S3Error {
+    #[error("invalid endpoint: {0}")]
+    Endpoint(String),
+    #[error("http: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("{key}: unexpected status {status}")]
+    Status { key: String, status: StatusCode },
+}
+
+/// Bucket location and credentials.
+#[derive(Debug, Clone)]
+pub struct S3Config {
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: Secret,
+}
+
+pub struct S3Client {
+    config: S3Config,
+    /// Value of `host` header, signed by every request.
+    host: String,
+    client: reqwest::Client,
+}
+
+impl S3Client {
+    pub fn new(config: S3Config) -> Result<Self, S3Error> {
+        let endpoint = Url::parse(&config.endpoint)
+            .map_err(|err| S3Error::Endpoint(format!("{}: {err}", config.endpoint)))?;
+        let host = match (endpoint.host_str(), endpoint.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(S3Error::Endpoint(config.endpoint)),
+        };
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+
+        Ok(Self {
+            config,
+            host,
+            client,
+        })
+    }
+
+    pub async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), S3Error> {
+        let response = self
+            .request(reqwest::Method::PUT, key, &body)
+            .body(body)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(S3Error::Status {
+                key: key.to_string(),
+                status: response.status(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns `None` if object does not exist.
+    pub async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, S3Error> {
+        let response = self.request(reqwest::Method::GET, key, &[]).send().await?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(Some(response.bytes().await?.to_vec())),
+            status => Err(S3Error::Status {
+                key: key.to_string(),
+                status,
+            }),
+        }
+    }
+
+    /// Deleting an absent object is not an error.
+    pub async fn delete(&self, key: &str) -> Result<(), S3Error> {
+        let response = self
+            .request(reqwest::Method::DELETE, key, &[])
+            .send()
+            .await?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(()),
+            status if status.is_success() => Ok(()),
+            status => Err(S3Error::Status {
+                key: key.to_string(),
+                status,
+            }),
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, key: &str, body: &[u8]) -> reqwest::RequestBuilder {
+        let path = format!("/{}/{}", self.config.bucket, uri_encode(key));
+        let payload_hash = hex::encode(Sha256::digest(body));
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let amz_date = amz_date(now);
+        let date = &amz_date[..8];
+
+        let canonical_request = format!(
+            "{method}\n{path}\n\nhost:{}\nx-amz-content-sha256:{payload_hash}\nx-amz-date:{amz_date}\n\n{SIGNED_HEADERS}\n{payload_hash}",
+            self.host
+        );
+        let scope = format!("{date}/{}/s3/aws4_request", self.config.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = signing_key(
+            self.config.secret_key.expose(),
+            date,
+            &self.config.region,
+            "s3",
+        );
+        let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={SIGNED_HEADERS}, Signature={signature}",
+            self.config.access_key
+        );
+
+        self.client
+            .request(
+                method,
+                format!("{}{path}", self.config.endpoint.trim_end_matches('/')),
+            )
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .header("authorization", authorization)
+    }
+}
+
+/// Headers covered by request signature.
+const SIGNED_HEADERS: &str = "host;x-amz-content-sha256;x-amz-date";
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Derives signing key of signature version 4.
+fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac(format!("AWS4{secret}").as_bytes(), date.as_bytes());
+    let key = hmac(&key, region.as_bytes());
+    let key = hmac(&key, service.as_bytes());
+    hmac(&key, b"aws4_request")
+}
+
+/// Encodes object key, unreserved characters and path separators are kept.
+fn uri_encode(key: &str) -> String {
+    key.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                char::from(b).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// Formats unix time as `YYYYMMDD'T'HHMMSS'Z'`.
+fn amz_date(unix_secs: u64) -> String {
+    let (days, secs) = (unix_secs / 86_400, unix_secs % 86_400);
+    // Civil date from days since epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sigv4_helpers() {
+        assert_eq!(amz_date(0), "19700101T000000Z");
+        assert_eq!(amz_date(1_329_264_000), "20120215T000000Z");
+        assert_eq!(amz_date(1_709_251_199), "20240229T235959Z");
+
+        // Example from AWS signature version 4 documentation.
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+
+        assert_eq!(
+            uri_encode("default/beacons 1.jsonl"),
+            "default/beacons%201.jsonl"
+        );
+    }
+}
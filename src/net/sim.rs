@@ -0,0 +1,571 @@
+//! In-process network for tests.
+//!
+//! Node servers are registered by address and accept in-memory duplex connections,
+//! [`super::utils::connect`] resolves registered addresses before falling back to TCP.
+//! This allows running several daemons within a single test without binding ports or
+//! spawning golang binaries.
+//!
+//! Daemons take the clock as [`SharedClock`], tests drive a mock clock shared by the nodes, so
+//! DKG and chain scenarios run without waiting for real time. Waits follow daemon events and
+//! pending sleeps of the clock, real time only bounds a stuck test, see [`WAIT_TIMEOUT`].
+//!
+//! Note: nodes of this implementation can not lead a DKG, the leader is played by the test with
+//! [`SimLeader`], nodes receive its packets and are commanded over the same RPCs as in production.
+use super::dkg_control::DkgControlHandler;
+use super::dkg_public::DkgPublicClient;
+use super::protocol;
+use super::protocol::ProtocolClient;
+use super::utils::Address;
+
+use crate::chain::time::MockClock;
+use crate::chain::time::SharedClock;
+use crate::cli::ArchiveArgs;
+use crate::cli::BackupArgs;
+use crate::cli::Config;
+use crate::cli::HttpArgs;
+use crate::cli::PprofArgs;
+use crate::cli::RuntimeArgs;
+use crate::cli::WebhookArgs;
+use crate::core::beacon::DEFAULT_BEACON_ID;
+use crate::core::daemon::Daemon;
+use crate::core::multibeacon;
+use crate::dkg::actions_signing::leader_packet;
+use crate::dkg::recovery::CHECK_INTERVAL;
+use crate::dkg::status::Status;
+use crate::key::keys::Pair;
+use crate::key::store::FileStore;
+use crate::key::Scheme;
+use crate::protobuf::dkg::dkg_command;
+use crate::protobuf::dkg::dkg_control_server::DkgControl;
+use crate::protobuf::dkg::CommandMetadata;
+use crate::protobuf::dkg::DkgCommand;
+use crate::protobuf::dkg::DkgStatusRequest;
+use crate::protobuf::dkg::JoinOptions;
+use crate::protobuf::drand::DaemonEvent;
+use crate::transport::dkg::GossipData;
+use crate::transport::dkg::Participant;
+use crate::transport::dkg::ProposalTerms;
+use crate::transport::dkg::StartExecution;
+use crate::transport::dkg::Timestamp;
+
+use energon::drand::schemes::DefaultScheme;
+use hyper_util::rt::TokioIo;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU16;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::io::DuplexStream;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::StreamExt;
+use tonic::transport::Channel;
+use tonic::transport::Endpoint;
+use tonic::transport::Uri;
+
+/// Buffer size of in-memory connection.
+const DUPLEX_BUF_SIZE: usize = 64 * 1024;
+/// Upper bound of a wait for simulated nodes, reached only if a test is stuck.
+pub const WAIT_TIMEOUT: Duration = Duration::from_secs(120);
+/// Recovery checks of [`recovered`] before giving up.
+const MAX_RECOVERY_CHECKS: usize = 600;
+
+type Incoming = mpsc::UnboundedSender<DuplexStream>;
+
+/// Registered node servers, each receives server halves of new connections.
+static NETWORK: LazyLock<Mutex<HashMap<String, Incoming>>> = LazyLock::new(Mutex::default);
+/// Tests within the process share the network, each node gets unique port.
+static NEXT_PORT: AtomicU16 = AtomicU16::new(1);
+
+/// Returns in-memory channel if peer is registered in simulated network.
+pub async fn connect(peer: &Address) -> anyhow::Result<Option<Channel>> {
+    let incoming = NETWORK
+        .lock()
+        .expect("sim network lock is poisoned")
+        .get(peer.as_str())
+        .cloned();
+    let Some(incoming) = incoming else {
+        return Ok(None);
+    };
+
+    let channel = Endpoint::from_shared(format!("http://{peer}"))?
+        .connect_with_connector(tower::service_fn(move |_: Uri| {
+            let incoming = incoming.clone();
+            async move {
+                let (client, server) = tokio::io::duplex(DUPLEX_BUF_SIZE);
+                incoming
+                    .send(server)
+                    .map_err(|_| std::io::Error::from(std::io::ErrorKind::ConnectionRefused))?;
+                Ok::<_, std::io::Error>(TokioIo::new(client))
+            }
+        }))
+        .await?;
+
+    Ok(Some(channel))
+}
+
+/// Daemon served on simulated network, control server is not started.
+pub struct SimNode {
+    pub address: Address,
+    pub daemon: Arc<Daemon>,
+    beacon_id: String,
+    // Node folder is removed on drop.
+    folder: TempDir,
+}
+
+impl SimNode {
+    /// Starts a daemon for given beacon id with key pair derived from `seed`.
+    pub fn start<S: Scheme>(
+        beacon_id: &str,
+        seed: &[u8],
+        clock: SharedClock,
+    ) -> anyhow::Result<Self> {
+        let port = NEXT_PORT.fetch_add(1, Ordering::Relaxed);
+        let address = Address::precheck(&format!("sim-node:{port}"))?;
+        let folder = tempfile::tempdir()?;
+        let folder_path = folder
+            .path()
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("temp folder path is not valid UTF-8"))?;
+
+        let pair = Pair::<S>::from_insecure_seed(address.clone(), seed)?;
+        FileStore::new_checked(folder_path, beacon_id)?.save_key_pair(&pair)?;
+        let daemon = serve(&address, folder_path, beacon_id, clock)?;
+
+        Ok(Self {
+            address,
+            daemon,
+            beacon_id: beacon_id.into(),
+            folder,
+        })
+    }
+
+    /// Stops the daemon and starts it again from the same folder and address, as after a crash.
+    pub async fn restart(&mut self, clock: SharedClock) -> anyhow::Result<()> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.daemon.stop_daemon(tx);
+        // Shutdown is not graceful without control server, which is fine for a restart.
+        let _ = rx.await;
+        let folder_path = self
+            .folder
+            .path()
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("temp folder path is not valid UTF-8"))?;
+        self.daemon = serve(&self.address, folder_path, &self.beacon_id, clock)?;
+
+        Ok(())
+    }
+
+    /// Returns file store of the beacon id.
+    pub fn store(&self) -> FileStore {
+        FileStore {
+            beacon_path: self.folder.path().join("multibeacon").join(&self.beacon_id),
+        }
+    }
+
+    /// Returns DKG folder of the beacon id.
+    pub fn dkg_dir(&self) -> PathBuf {
+        self.store().beacon_path.join("dkg")
+    }
+
+    /// Subscribes to events of the daemon, see [`crate::core::events`].
+    pub fn events(&self) -> broadcast::Receiver<DaemonEvent> {
+        self.daemon.beacons().events().subscribe()
+    }
+
+    /// Returns status of the current DKG, requested over `DkgControl`.
+    pub async fn dkg_status(&self) -> anyhow::Result<Status> {
+        let request = DkgStatusRequest {
+            beacon_id: self.beacon_id.clone(),
+        };
+        let current = DkgControlHandler::new(self.daemon.clone())
+            .dkg_status(tonic::Request::new(request))
+            .await?
+            .into_inner()
+            .current
+            .ok_or_else(|| anyhow::anyhow!("current DKG state is missing"))?;
+
+        Ok(Status::try_from(current.state)?)
+    }
+
+    /// Joins the pending proposal over `DkgControl`, as on command of the operator.
+    pub async fn join(&self) -> anyhow::Result<()> {
+        let command = DkgCommand {
+            metadata: Some(CommandMetadata {
+                beacon_id: self.beacon_id.clone(),
+            }),
+            command: Some(dkg_command::Command::Join(JoinOptions {
+                group_file: vec![],
+            })),
+        };
+        DkgControlHandler::new(self.daemon.clone())
+            .command(tonic::Request::new(command))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns the latest stored round, requested over `Protocol`.
+    pub async fn stored_round(&self) -> anyhow::Result<u64> {
+        let (response, _rtt) = ProtocolClient::new(&self.address).await?.ping().await?;
+        let beacon = response
+            .beacons
+            .iter()
+            .find(|b| b.beacon_id == self.beacon_id)
+            .ok_or_else(|| anyhow::anyhow!("beacon id is not served"))?;
+
+        Ok(beacon.round)
+    }
+}
+
+/// Leader of the first epoch played by a test.
+///
+/// Proposal and execution are signed with the key pair of the leader participant and sent
+/// to the nodes over `DkgPublic`, as golang leader does.
+pub struct SimLeader<S: Scheme> {
+    pair: Pair<S>,
+    terms: ProposalTerms,
+}
+
+impl<S: Scheme> SimLeader<S> {
+    /// Returns leader of the first epoch of `participants`, all of them are joiners. Key pair of
+    /// the leader is derived from `seed`, genesis is an hour after the proposal `timeout`.
+    pub fn first_epoch(
+        seed: &[u8],
+        participants: &[Participant],
+        leader: usize,
+        threshold: u32,
+        timeout: i64,
+    ) -> anyhow::Result<Self> {
+        let pair = Pair::from_insecure_seed(participants[leader].address.clone(), seed)?;
+        let at = |seconds: i64| Timestamp { seconds, nanos: 0 };
+        let terms = ProposalTerms {
+            beacon_id: DEFAULT_BEACON_ID.into(),
+            epoch: 1,
+            leader: participants[leader].clone(),
+            threshold,
+            timeout: at(timeout),
+            catchup_period_seconds: 1.into(),
+            beacon_period_seconds: 3.into(),
+            scheme_id: S::ID.into(),
+            genesis_time: at(timeout + 3600),
+            genesis_seed: vec![],
+            joining: participants.to_vec(),
+            remaining: vec![],
+            leaving: vec![],
+        };
+
+        Ok(Self { pair, terms })
+    }
+
+    /// Sends the proposal to the nodes.
+    pub async fn propose(&self, nodes: &[SimNode]) -> anyhow::Result<()> {
+        self.send(nodes, GossipData::Proposal(self.terms.clone()))
+            .await
+    }
+
+    /// Sends execution starting at `start` Unix time in seconds to the nodes.
+    pub async fn execute(&self, nodes: &[SimNode], start: i64) -> anyhow::Result<()> {
+        let time = Timestamp {
+            seconds: start,
+            nanos: 0,
+        };
+        self.send(nodes, GossipData::Execute(StartExecution { time }))
+            .await
+    }
+
+    /// Proposes the epoch, joins it by each node and executes it at `start`.
+    pub async fn run(&self, nodes: &[SimNode], start: i64) -> anyhow::Result<()> {
+        self.propose(nodes).await?;
+        for node in nodes {
+            node.join().await?;
+        }
+        self.execute(nodes, start).await
+    }
+
+    async fn send(&self, nodes: &[SimNode], data: GossipData) -> anyhow::Result<()> {
+        let packet = leader_packet::<S>(self.pair.private_key(), &self.terms, data)
+            .map_err(|err| anyhow::anyhow!("leader packet: {err}"))?;
+        for node in nodes {
+            let mut client = DkgPublicClient::new(&node.address).await?;
+            client.packet(packet.clone().into()).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns nodes and participants of key pairs derived from seeds, nodes are started with given
+/// clocks for the first seeds, other participants are offline.
+pub fn participants(seeds: &[&[u8]], clocks: &[SharedClock]) -> (Vec<SimNode>, Vec<Participant>) {
+    let nodes = seeds
+        .iter()
+        .zip(clocks)
+        .map(|(seed, clock)| {
+            SimNode::start::<DefaultScheme>(DEFAULT_BEACON_ID, seed, clock.clone())
+        })
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    let participants = seeds
+        .iter()
+        .enumerate()
+        .map(|(i, seed)| {
+            let address = nodes.get(i).map_or_else(
+                || Address::precheck(&format!("offline-node:{}", 1000 + i)).unwrap(),
+                |node| node.address.clone(),
+            );
+            let pair = Pair::<DefaultScheme>::from_insecure_seed(address, seed).unwrap();
+            Participant::try_from(pair.public_identity()).unwrap()
+        })
+        .collect();
+
+    (nodes, participants)
+}
+
+/// Waits for the next event of a node.
+async fn next_event(events: &mut broadcast::Receiver<DaemonEvent>) {
+    match tokio::time::timeout(WAIT_TIMEOUT, events.recv()).await {
+        Ok(Ok(_) | Err(RecvError::Lagged(_))) => (),
+        Ok(Err(RecvError::Closed)) => panic!("daemon is stopped"),
+        Err(_) => panic!("no events within {WAIT_TIMEOUT:?}"),
+    }
+}
+
+/// Waits until the DKG of all nodes is finished, returns statuses of the nodes.
+pub async fn finished(nodes: &[SimNode]) -> Vec<Status> {
+    let mut statuses = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        let mut events = node.events();
+        loop {
+            let status = node.dkg_status().await.unwrap();
+            if status != Status::Executing {
+                statuses.push(status);
+                break;
+            }
+            next_event(&mut events).await;
+        }
+    }
+
+    statuses
+}
+
+/// Advances the clock by interval of recovery checks until a node reaches `status`, then waits
+/// for the others to follow it. Recovery tasks of the nodes are the only sleeps on the clock,
+/// the clock is advanced once all of them handled the previous check.
+pub async fn recovered(nodes: &[SimNode], clock: &MockClock, status: Status) {
+    let mut events: Vec<_> = nodes.iter().map(SimNode::events).collect();
+    for _ in 0..MAX_RECOVERY_CHECKS {
+        clock.sleeping(nodes.len()).await;
+        let mut reached = false;
+        for node in nodes {
+            reached |= node.dkg_status().await.unwrap() == status;
+        }
+        if !reached {
+            clock.advance(CHECK_INTERVAL);
+            continue;
+        }
+        for (node, events) in nodes.iter().zip(&mut events) {
+            while node.dkg_status().await.unwrap() != status {
+                next_event(events).await;
+            }
+        }
+        return;
+    }
+    panic!("nodes are not {status} within {MAX_RECOVERY_CHECKS} recovery checks");
+}
+
+/// Waits until the node records `count` deals received from other dealers in evidence of the first epoch.
+pub async fn received_deals(node: &SimNode, count: usize) {
+    let log = node.dkg_dir().join("evidence").join("1.jsonl");
+    let received = async {
+        loop {
+            let received = std::fs::read_to_string(&log)
+                .unwrap_or_default()
+                .lines()
+                .filter_map(|l| serde_json::from_str::<serde_json::Value>(l).ok())
+                .filter(|r| r["kind"] == "Deal" && r["direction"] == "received")
+                .count();
+            if received >= count {
+                return;
+            }
+            tokio::task::yield_now().await;
+        }
+    };
+    if tokio::time::timeout(WAIT_TIMEOUT, received).await.is_err() {
+        panic!("{count} deals are not received within {WAIT_TIMEOUT:?}");
+    }
+}
+
+/// Advances the clock by beacon `period` once all nodes store the current round, until `round`
+/// is stored. The clock is expected at genesis time.
+pub async fn stored(nodes: &[SimNode], clock: &MockClock, period: Duration, round: u64) {
+    let mut events: Vec<_> = nodes.iter().map(SimNode::events).collect();
+    for current in 1..=round {
+        for (node, events) in nodes.iter().zip(&mut events) {
+            while node.stored_round().await.unwrap() < current {
+                next_event(events).await;
+            }
+        }
+        if current < round {
+            clock.advance(period);
+        }
+    }
+}
+
+/// Starts a daemon from the node folder and registers it in simulated network.
+fn serve(
+    address: &Address,
+    folder_path: &str,
+    beacon_id: &str,
+    clock: SharedClock,
+) -> anyhow::Result<Arc<Daemon>> {
+    let daemon = Daemon::new(
+        Config {
+            folder: folder_path.into(),
+            control: String::new(),
+            private_listen: address.to_string(),
+            id: Some(beacon_id.into()),
+            only: vec![],
+            log_level: vec![],
+            bulk_log_rate: crate::log::DEFAULT_BULK_LOG_RATE,
+            sync_batch_size: protocol::DEFAULT_SYNC_BATCH_SIZE,
+            max_sync_streams: protocol::DEFAULT_MAX_SYNC_STREAMS,
+            sync_buffer_mb: protocol::DEFAULT_SYNC_BUFFER_MB,
+            callback_timeout: multibeacon::DEFAULT_CALLBACK_TIMEOUT_SECS,
+            shadow: vec![],
+            derived_storage: vec![],
+            precomputed_storage: vec![],
+            ipc_socket: None,
+            dkg_fanout: vec![],
+            archive: ArchiveArgs::default(),
+            http: HttpArgs::default(),
+            backup: BackupArgs::default(),
+            runtime: RuntimeArgs::default(),
+            pprof: PprofArgs::default(),
+            webhooks: WebhookArgs::default(),
+        },
+        clock,
+    )?;
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    NETWORK
+        .lock()
+        .expect("sim network lock is poisoned")
+        .insert(address.to_string(), tx);
+
+    let cancel = daemon.token.clone();
+    daemon.tracker.spawn(
+        protocol::router(daemon.clone()).serve_with_incoming_shutdown(
+            UnboundedReceiverStream::new(rx).map(Ok::<_, std::io::Error>),
+            async move { cancel.cancelled().await },
+        ),
+    );
+
+    Ok(daemon)
+}
+
+impl Drop for SimNode {
+    fn drop(&mut self) {
+        if let Ok(mut network) = NETWORK.lock() {
+            network.remove(self.address.as_str());
+        }
+        self.daemon.token.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::time::time_now;
+    use crate::chain::time::Clock;
+
+    #[tokio::test]
+    async fn nodes_exchange_identities() {
+        // Nodes share the clock as they would share system time.
+        let clock: SharedClock = Arc::new(MockClock::new(time_now()));
+        let nodes = (0..3u8)
+            .map(|i| SimNode::start::<DefaultScheme>(DEFAULT_BEACON_ID, &[i], clock.clone()))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap();
+
+        for node in &nodes {
+            let mut client = ProtocolClient::new(&node.address).await.unwrap();
+            let identity = client.get_identity(DEFAULT_BEACON_ID.into()).await.unwrap();
+            assert_eq!(identity.address, node.address);
+        }
+    }
+
+    #[tokio::test]
+    async fn dropped_node_is_unregistered() {
+        let node = SimNode::start::<DefaultScheme>(
+            DEFAULT_BEACON_ID,
+            b"dropped",
+            Arc::new(MockClock::new(time_now())),
+        )
+        .unwrap();
+        let address = node.address.clone();
+        assert!(connect(&address).await.unwrap().is_some());
+
+        drop(node);
+        assert!(connect(&address).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn chain_progresses_after_dkg() {
+        use crate::key::group::Group;
+        use crate::net::public::PublicClient;
+        use energon::traits::Affine;
+
+        let mock = Arc::new(MockClock::new(time_now()));
+        let clock: SharedClock = mock.clone();
+        let seeds: [&[u8]; 3] = [b"chain-0", b"chain-1", b"chain-2"];
+        let (nodes, participants) = participants(&seeds, &vec![clock.clone(); 3]);
+        let now = i64::try_from(mock.now().as_secs()).unwrap();
+        let leader =
+            SimLeader::<DefaultScheme>::first_epoch(seeds[0], &participants, 0, 2, now + 3600)
+                .unwrap();
+        leader.run(&nodes, now + 1).await.unwrap();
+        mock.advance(Duration::from_secs(2));
+        assert!(finished(&nodes)
+            .await
+            .iter()
+            .all(|s| *s == Status::Complete));
+
+        // Genesis of the proposal is two hours ahead.
+        let group: Group<DefaultScheme> = nodes[0].store().load_group().unwrap();
+        mock.advance(Duration::from_secs(
+            group.genesis_time - mock.now().as_secs(),
+        ));
+        let period = Duration::from_secs(group.period.get_value().into());
+        stored(&nodes, &mock, period, 5).await;
+
+        // Nodes serve the same beacons, each verified against the distributed key.
+        let public_key = &group.dist_key.commits[0];
+        for round in 1..=5 {
+            let mut beacons = vec![];
+            for node in &nodes {
+                let mut client = PublicClient::new(&node.address).await.unwrap();
+                let beacon = client
+                    .public_rand(round, DEFAULT_BEACON_ID.into())
+                    .await
+                    .unwrap();
+                assert_eq!(beacon.round, round);
+                let signature = Affine::deserialize(&beacon.signature).unwrap();
+                assert!(crate::verify::verify_beacon::<DefaultScheme>(
+                    public_key,
+                    &beacon.previous_signature,
+                    round,
+                    &signature,
+                ));
+                beacons.push(beacon.signature);
+            }
+            assert!(beacons.iter().all(|b| *b == beacons[0]));
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! Terminal dashboard of the local daemon for `drand util top`.
+//!
+//! Beacon ids are polled over control RPCs at given interval, recent errors are collected
+//! from the events stream. Peer errors are counted by the daemon, events only list the latest. The view is redrawn in place using ANSI escape codes, so it
+//! works in any terminal without an alternate screen.
+use super::control::ControlClient;
+use super::dkg_control::DkgControlClient;
+
+use crate::chain::time::current_round;
+use crate::chain::time::time_now;
+use crate::dkg::status::Status;
+use crate::protobuf::drand::DaemonEvent;
+
+use std::collections::BTreeSet;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::io::Write as _;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+
+/// Number of recent errors kept in the view.
+const RECENT_ERRORS: usize = 10;
+/// Clears the screen and moves the cursor to the top left corner.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Polled state of a single beacon id.
+#[derive(Default)]
+pub struct BeaconView {
+    pub beacon_id: String,
+    pub latest_stored: Option<u64>,
+    /// Round expected at poll time, not known before the first DKG.
+    pub expected: Option<u64>,
+    pub dkg: Option<(u32, Status)>,
+    pub resync_peer_failures: u64,
+    pub clock_skew_ms: i64,
+    pub clock_skewed: bool,
+    /// Set if status of the beacon id could not be polled.
+    pub error: Option<String>,
+}
+
+/// State collected from the events stream.
+#[derive(Default)]
+pub struct Recent {
+    /// Beacon ids to poll, extended by ids seen in events if `follow_all` is set.
+    ids: BTreeSet<String>,
+    follow_all: bool,
+    errors: VecDeque<DaemonEvent>,
+    closed: bool,
+}
+
+impl Recent {
+    fn new(ids: Vec<String>) -> Self {
+        Self {
+            follow_all: ids.is_empty(),
+            ids: ids.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    fn push(&mut self, event: DaemonEvent) {
+        if self.follow_all {
+            self.ids.insert(event.beacon_id.clone());
+        } else if !self.ids.contains(&event.beacon_id) {
+            return;
+        }
+        // Resync which is stopped after reaching the target is not an error.
+        let is_error = match event.kind.as_str() {
+            "peer_error" => true,
+            "resync_stopped" => event.detail != "complete",
+            _ => false,
+        };
+        if !is_error {
+            return;
+        }
+        if self.errors.len() == RECENT_ERRORS {
+            self.errors.pop_front();
+        }
+        self.errors.push_back(event);
+    }
+}
+
+/// Redraws the view every `interval` until the process is interrupted.
+///
+/// Beacon ids seen in events are added to the view if `ids` is empty.
+pub async fn run(control: &str, ids: Vec<String>, interval: Duration) -> anyhow::Result<()> {
+    let mut client = ControlClient::new(control).await?;
+    let mut dkg = DkgControlClient::new(control).await?;
+
+    let recent = Arc::new(Mutex::new(Recent::new(ids)));
+    let mut events = client.events(None).await?;
+    tokio::spawn({
+        let recent = recent.clone();
+        async move {
+            while let Ok(Some(event)) = events.message().await {
+                lock(&recent).push(event);
+            }
+            lock(&recent).closed = true;
+        }
+    });
+
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let ids = lock(&recent).ids.clone();
+        let now = time_now();
+        let mut views = Vec::with_capacity(ids.len());
+        for id in ids {
+            views.push(poll(&mut client, &mut dkg, id, now).await);
+        }
+
+        let frame = render(control, &views, &lock(&recent), now);
+        let mut stdout = std::io::stdout().lock();
+        write!(stdout, "{CLEAR_SCREEN}{frame}")?;
+        stdout.flush()?;
+    }
+}
+
+async fn poll(
+    client: &mut ControlClient,
+    dkg: &mut DkgControlClient,
+    beacon_id: String,
+    now: Duration,
+) -> BeaconView {
+    let mut view = BeaconView {
+        beacon_id,
+        ..Default::default()
+    };
+    match client.status(view.beacon_id.clone()).await {
+        Ok(status) => {
+            view.latest_stored = Some(status.latest_stored_round);
+            view.resync_peer_failures = status.resync_peer_failures;
+            view.clock_skew_ms = status.clock_skew_ms;
+            view.clock_skewed = status.clock_skewed;
+        }
+        Err(err) => {
+            view.error = Some(err.to_string());
+            return view;
+        }
+    }
+    if let Ok(info) = client.chain_info(view.beacon_id.clone()).await {
+        if let Ok(genesis) = u64::try_from(info.genesis_time) {
+            view.expected = Some(current_round(now.as_secs(), info.period, genesis));
+        }
+    }
+    if let Ok(status) = dkg.dkg_status(&view.beacon_id).await {
+        view.dkg = status
+            .current
+            .and_then(|e| Status::try_from(e.state).ok().map(|s| (e.epoch, s)));
+    }
+
+    view
+}
+
+/// Renders a frame of the view.
+pub fn render(control: &str, views: &[BeaconView], recent: &Recent, now: Duration) -> String {
+    let mut f = String::new();
+    let _ = writeln!(
+        f,
+        "drand top - control port {control}{}\n",
+        if recent.closed {
+            ", events stream is closed"
+        } else {
+            ""
+        }
+    );
+    let _ = writeln!(
+        f,
+        "{:<16} {:>10} {:>10} {:>6}  {:<16} {:>11} {:>11}",
+        "BEACON ID", "HEIGHT", "EXPECTED", "LAG", "DKG", "PEER ERRORS", "CLOCK SKEW"
+    );
+    for v in views {
+        if let Some(err) = &v.error {
+            let _ = writeln!(f, "{:<16} unavailable: {err}", v.beacon_id);
+            continue;
+        }
+        let opt = |r: Option<u64>| r.map_or_else(|| "-".into(), |r| r.to_string());
+        let lag = v
+            .latest_stored
+            .zip(v.expected)
+            .map(|(stored, expected)| expected.saturating_sub(stored));
+        let dkg = v
+            .dkg
+            .map_or_else(|| "-".into(), |(epoch, s)| format!("{s} (epoch {epoch})"));
+        let _ = writeln!(
+            f,
+            "{:<16} {:>10} {:>10} {:>6}  {:<16} {:>11} {:>9}ms{}",
+            v.beacon_id,
+            opt(v.latest_stored),
+            opt(v.expected),
+            opt(lag),
+            dkg,
+            v.resync_peer_failures,
+            v.clock_skew_ms,
+            if v.clock_skewed { " !" } else { "" }
+        );
+    }
+
+    let _ = writeln!(f, "\nRecent errors:");
+    if recent.errors.is_empty() {
+        let _ = writeln!(f, "  none");
+    }
+    let now_ms = u64::try_from(now.as_millis()).unwrap_or_default();
+    for e in recent.errors.iter().rev() {
+        let _ = writeln!(
+            f,
+            "  {:>5}s ago [{}] {}: {}",
+            now_ms.saturating_sub(e.timestamp_ms) / 1000,
+            e.beacon_id,
+            e.kind,
+            e.detail
+        );
+    }
+
+    f
+}
+
+fn lock(recent: &Mutex<Recent>) -> std::sync::MutexGuard<'_, Recent> {
+    recent.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(beacon_id: &str, kind: &str, timestamp_ms: u64) -> DaemonEvent {
+        DaemonEvent {
+            beacon_id: beacon_id.into(),
+            kind: kind.into(),
+            round: 0,
+            detail: "peer:1: unavailable".into(),
+            timestamp_ms,
+        }
+    }
+
+    #[test]
+    fn render_view() {
+        let mut recent = Recent::new(vec!["default".into()]);
+        recent.push(event("default", "peer_error", 1_000));
+        recent.push(event("default", "beacon_stored", 1_000));
+        recent.push(event("other", "peer_error", 1_000));
+        assert_eq!(recent.errors.len(), 1);
+        assert_eq!(recent.ids.len(), 1);
+
+        let views = [BeaconView {
+            beacon_id: "default".into(),
+            latest_stored: Some(100),
+            expected: Some(103),
+            dkg: Some((2, Status::Complete)),
+            resync_peer_failures: 1,
+            ..Default::default()
+        }];
+        let frame = render("8888", &views, &recent, Duration::from_secs(5));
+        let row = frame.lines().find(|l| l.starts_with("default")).unwrap();
+        let columns: Vec<_> = row.split_whitespace().collect();
+        assert_eq!(columns[1..4], ["100", "103", "3"]);
+        assert!(row.contains("Complete (epoch 2)"));
+        // Counted by the daemon, the event of the same failure is not added.
+        assert_eq!(columns[7], "1");
+        assert!(frame.contains("4s ago [default] peer_error: peer:1: unavailable"));
+
+        let mut complete = event("default", "resync_stopped", 2_000);
+        complete.detail = "complete".into();
+        recent.push(complete);
+        assert_eq!(recent.errors.len(), 1);
+
+        for _ in 0..RECENT_ERRORS {
+            recent.push(event("default", "resync_stopped", 2_000));
+        }
+        assert_eq!(recent.errors.len(), RECENT_ERRORS);
+        assert!(recent.errors.iter().all(|e| e.kind == "resync_stopped"));
+    }
+}
@@ -1,9 +1,6 @@
-use crate::chain::ChainError;
-use crate::chain::StoreError;
-use crate::core::multibeacon::BeaconHandlerError;
-use crate::dkg::ActionsError;
-use crate::key::PointSerDeError;
 use crate::net::control::CONTROL_HOST;
+use crate::net::error::ErrorCode;
+use crate::net::handshake;
 use crate::protobuf::drand::Metadata;
 use crate::protobuf::drand::NodeVersion;
 
@@ -39,6 +36,10 @@ pub async fn connect(peer: &Address) -> anyhow::Result<Channel> {
 /// Returns a channel for a generic Tonic client without TLS configuration.
 /// Returns an error if the connection cannot be established.
 pub async fn connect(peer: &Address) -> anyhow::Result<Channel> {
+    #[cfg(test)]
+    if let Some(channel) = super::sim::connect(peer).await? {
+        return Ok(channel);
+    }
     let channel = Channel::from_shared(format!("http://{peer}"))?
         .connect_timeout(CONNECT_TIMEOUT)
         .connect()
@@ -46,6 +47,24 @@ pub async fn connect(peer: &Address) -> anyhow::Result<Channel> {
     Ok(channel)
 }
 
+/// Channel of peer clients, faults are injected into it for tests and with `chaos` feature.
+#[cfg(any(test, feature = "chaos"))]
+pub(super) type PeerChannel = super::chaos::ChaosChannel;
+#[cfg(not(any(test, feature = "chaos")))]
+pub(super) type PeerChannel = Channel;
+
+#[cfg(any(test, feature = "chaos"))]
+/// Returns channel of peer clients over the connection to the peer.
+pub(super) fn peer_channel(channel: Channel, peer: &Address) -> PeerChannel {
+    super::chaos::ChaosChannel::new(channel, peer)
+}
+
+#[cfg(not(any(test, feature = "chaos")))]
+/// Returns channel of peer clients over the connection to the peer.
+pub(super) fn peer_channel(channel: Channel, _peer: &Address) -> PeerChannel {
+    channel
+}
+
 /// Address is protected type of URI Authority which always contains host:port (see [`Address::precheck`]).
 #[derive(Eq, PartialEq, Clone)]
 pub struct Address(Authority);
@@ -66,6 +85,11 @@ impl Address {
     pub fn as_str(&self) -> &str {
         self.0.as_str()
     }
+
+    /// Returns host without port, IPv6 address is enclosed in brackets.
+    pub fn host(&self) -> &str {
+        self.0.host()
+    }
 }
 
 impl PartialOrd for Address {
@@ -96,13 +120,24 @@ impl Debug for Address {
 #[error("expected valid host:port, received {0}")]
 pub struct InvalidAddress(String);
 
-const VERSION: NodeVersion = NodeVersion {
+pub(super) const VERSION: NodeVersion = NodeVersion {
     major: 0,
     minor: 2,
     patch: 0,
     prerelease: String::new(),
 };
 
+impl Display for NodeVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.prerelease.is_empty() {
+            write!(f, "-{}", self.prerelease)?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Default, PartialEq, Copy, Clone)]
 pub struct Seconds {
     value: u32,
@@ -178,6 +213,7 @@ impl Metadata {
     pub(super) fn with_default() -> Self {
         Self {
             node_version: Some(VERSION),
+            features: handshake::features(),
             ..Default::default()
         }
     }
@@ -187,6 +223,7 @@ impl Metadata {
             node_version: Some(VERSION),
             beacon_id,
             chain_hash: vec![],
+            features: handshake::features(),
         }
     }
 
@@ -195,12 +232,15 @@ impl Metadata {
             node_version: Some(VERSION),
             beacon_id: beacon_id.into(),
             chain_hash: hex::decode(chain_hash)?,
+            features: handshake::features(),
         };
 
         Ok(metadata)
     }
 
     /// Bypass version check.
+    ///
+    /// Note: features are still reported, so drand-rs peers do not compare this version.
     pub fn golang_node_version(beacon_id: String, chain_hash: Option<&[u8]>) -> Self {
         Metadata {
             node_version: Some(NodeVersion {
@@ -211,6 +251,7 @@ impl Metadata {
             }),
             beacon_id,
             chain_hash: chain_hash.unwrap_or_default().into(),
+            features: handshake::features(),
         }
     }
 }
@@ -269,54 +310,22 @@ pub enum StartServerError {
     FailedToStartControl,
     #[error("failed to start node server")]
     FailedToStartNode,
+    #[error("failed to start http server")]
+    FailedToStartHttp,
+    #[error("failed to start ipc server")]
+    FailedToStartIpc,
 }
 
 /// Converts the underlying error into a [`Status`], including the provided beacon id.
+///
+/// Code of the status is canonical for the error, see [`super::error`].
 pub trait ToStatus {
     fn to_status(&self, id: &str) -> Status;
 }
 
-impl ToStatus for tokio::sync::oneshot::error::RecvError {
-    /// This error should not be possible. Means that callback sender is dropped without sending.
-    fn to_status(&self, id: &str) -> Status {
-        Status::internal(format!("beacon id '{id}' internal RecvError"))
-    }
-}
-
-impl ToStatus for ChainError {
-    fn to_status(&self, id: &str) -> Status {
-        Status::aborted(format!("beacon id '{id}', {self}"))
-    }
-}
-
-impl ToStatus for StoreError {
-    fn to_status(&self, id: &str) -> Status {
-        Status::aborted(format!("beacon id '{id}', {self}"))
-    }
-}
-
-impl ToStatus for BeaconHandlerError {
-    fn to_status(&self, id: &str) -> Status {
-        Status::unknown(format!("beacon id '{id}', {self}"))
-    }
-}
-
-impl ToStatus for PointSerDeError {
-    /// TODO: well-define error values, see [`ConversionError`]
-    fn to_status(&self, id: &str) -> Status {
-        Status::invalid_argument(format!("beacon id '{id}', conversion error: {self}"))
-    }
-}
-
-impl ToStatus for InvalidAddress {
-    fn to_status(&self, id: &str) -> Status {
-        Status::invalid_argument(format!("beacon id '{id}', {}", self.0))
-    }
-}
-
-impl ToStatus for ActionsError {
+impl<E: ErrorCode + Display> ToStatus for E {
     fn to_status(&self, id: &str) -> Status {
-        Status::aborted(format!("beacon id '{id}', {self}",))
+        Status::new(self.code(), format!("beacon id '{id}', {self}"))
     }
 }
 
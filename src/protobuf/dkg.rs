@@ -304,6 +304,29 @@ pub struct DkgStatusResponse {
     pub complete: ::core::option::Option<DkgEntry>,
     #[prost(message, optional, tag = "2")]
     pub current: ::core::option::Option<DkgEntry>,
+    /// history of participant identity changes observed across reshares
+    #[prost(message, repeated, tag = "3")]
+    pub identity_changes: ::prost::alloc::vec::Vec<IdentityChange>,
+}
+/// IdentityChange is recorded when a reshare changes the address or the key of
+/// a participant known from the previous epoch.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct IdentityChange {
+    /// epoch at which the change took effect
+    #[prost(uint32, tag = "1")]
+    pub epoch: u32,
+    /// either "address" or "key"
+    #[prost(string, tag = "2")]
+    pub kind: ::prost::alloc::string::String,
+    /// address of the participant in the new epoch
+    #[prost(string, tag = "3")]
+    pub address: ::prost::alloc::string::String,
+    /// previous address or hex encoded key
+    #[prost(string, tag = "4")]
+    pub previous: ::prost::alloc::string::String,
+    /// new address or hex encoded key
+    #[prost(string, tag = "5")]
+    pub current: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DkgEntry {
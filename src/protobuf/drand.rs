@@ -18,6 +18,9 @@ pub struct Metadata {
     pub beacon_id: ::prost::alloc::string::String,
     #[prost(bytes = "vec", tag = "3")]
     pub chain_hash: ::prost::alloc::vec::Vec<u8>,
+    /// protocol features supported by drand-rs nodes, empty for golang nodes
+    #[prost(string, repeated, tag = "4")]
+    pub features: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct DkgStatus {
@@ -55,6 +58,14 @@ pub struct Address {
 pub struct StatusRequest {
     #[prost(message, optional, tag = "1")]
     pub metadata: ::core::option::Option<Metadata>,
+    /// set by group members querying each other over protocol service: address of
+    /// the requester, request time and signature of the requester key over both
+    #[prost(string, tag = "2")]
+    pub address: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub timestamp_ms: u64,
+    #[prost(bytes = "vec", tag = "4")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
 }
 /// StatusResponse might contain different indicators of the status of the local
 /// drand node process.
@@ -62,10 +73,54 @@ pub struct StatusRequest {
 /// Currently, we only need the round of the latest stored beacon.
 /// Note: Fresh nodes might return such round if they have followed some
 /// chain node.
-#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StatusResponse {
     #[prost(uint64, tag = "1")]
     pub latest_stored_round: u64,
+    /// resync counters accumulated since the beacon process is started
+    #[prost(uint64, tag = "2")]
+    pub resync_started: u64,
+    #[prost(uint64, tag = "3")]
+    pub resync_rounds_fetched: u64,
+    #[prost(uint64, tag = "4")]
+    pub resync_invalid_beacons: u64,
+    #[prost(uint64, tag = "5")]
+    pub resync_peer_failures: u64,
+    /// median offset in milliseconds between local clock and round times observed from peers
+    #[prost(int64, tag = "6")]
+    pub clock_skew_ms: i64,
+    /// clock_skewed is true if clock_skew_ms exceeds the allowed threshold
+    #[prost(bool, tag = "7")]
+    pub clock_skewed: bool,
+    /// delays in milliseconds relative to round time, upper bounds of histogram buckets
+    #[prost(uint64, tag = "8")]
+    pub partial_delay_p50_ms: u64,
+    #[prost(uint64, tag = "9")]
+    pub partial_delay_p99_ms: u64,
+    #[prost(uint64, tag = "10")]
+    pub aggregation_delay_p50_ms: u64,
+    #[prost(uint64, tag = "11")]
+    pub aggregation_delay_p99_ms: u64,
+    /// time to threshold in milliseconds relative to round time
+    #[prost(uint64, tag = "12")]
+    pub threshold_delay_p50_ms: u64,
+    #[prost(uint64, tag = "13")]
+    pub threshold_delay_p99_ms: u64,
+    /// version of the node, set in responses to group members
+    #[prost(message, optional, tag = "14")]
+    pub metadata: ::core::option::Option<Metadata>,
+    /// replies of the beacon process to control commands which were not received
+    /// within callback timeout
+    #[prost(uint64, tag = "15")]
+    pub callback_timeouts: u64,
+    /// timed out replies which are still not received, non-zero if the beacon
+    /// process is wedged
+    #[prost(uint64, tag = "16")]
+    pub pending_replies: u64,
+    /// unix time in seconds of the latest successful scheduled backup of the
+    /// daemon, zero if none
+    #[prost(uint64, tag = "17")]
+    pub last_backup_time: u64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Empty {
@@ -125,6 +180,9 @@ pub struct GroupPacket {
 pub struct GroupRequest {
     #[prost(message, optional, tag = "1")]
     pub metadata: ::core::option::Option<Metadata>,
+    /// epoch of the group, 0 stands for the latest group
+    #[prost(uint32, tag = "2")]
+    pub epoch: u32,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChainInfoRequest {
@@ -193,10 +251,215 @@ pub struct RemoteStatusRequest {
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RemoteStatusResponse {
     #[prost(map = "string, message", tag = "1")]
-    pub statuses: ::std::collections::HashMap<
-        ::prost::alloc::string::String,
-        StatusResponse,
-    >,
+    pub statuses: ::std::collections::HashMap<::prost::alloc::string::String, StatusResponse>,
+}
+/// EventsRequest subscribes to daemon events, events of all beacons are sent if
+/// metadata is not set
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EventsRequest {
+    #[prost(message, optional, tag = "1")]
+    pub metadata: ::core::option::Option<Metadata>,
+}
+/// DaemonEvent is a structured event of a beacon process
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DaemonEvent {
+    #[prost(string, tag = "1")]
+    pub beacon_id: ::prost::alloc::string::String,
+    /// one of: beacon_stored, resync_started, resync_stopped, sync_summary, dkg_status,
+    /// peer_error, audit_mismatch, unknown_index, spoofed_partial, equivocation, panic
+    #[prost(string, tag = "2")]
+    pub kind: ::prost::alloc::string::String,
+    /// round related to the event, zero if not applicable
+    #[prost(uint64, tag = "3")]
+    pub round: u64,
+    /// human readable details of the event
+    #[prost(string, tag = "4")]
+    pub detail: ::prost::alloc::string::String,
+    /// unix time of the event in milliseconds
+    #[prost(uint64, tag = "5")]
+    pub timestamp_ms: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PeerBandwidthRequest {
+    #[prost(message, optional, tag = "1")]
+    pub metadata: ::core::option::Option<Metadata>,
+}
+/// PeerTraffic contains traffic of connections accepted from a peer IP since
+/// the daemon start, peers idle for an hour are dropped
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PeerTraffic {
+    #[prost(string, tag = "1")]
+    pub address: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub bytes_sent: u64,
+    #[prost(uint64, tag = "3")]
+    pub bytes_received: u64,
+}
+/// PeerBandwidthResponse is sorted by bytes sent in descending order
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PeerBandwidthResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub peers: ::prost::alloc::vec::Vec<PeerTraffic>,
+}
+/// ResyncRequest starts resync even if the node is not late, stored beacons
+/// starting from from_round are verified again and rewritten if they differ
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResyncRequest {
+    #[prost(uint64, tag = "1")]
+    pub from_round: u64,
+    #[prost(message, optional, tag = "2")]
+    pub metadata: ::core::option::Option<Metadata>,
+}
+/// ResyncResponse contains the round up to which resync is started
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResyncResponse {
+    #[prost(uint64, tag = "1")]
+    pub up_to: u64,
+    #[prost(message, optional, tag = "2")]
+    pub metadata: ::core::option::Option<Metadata>,
+}
+/// SetLogLevelRequest sets log level of the beacon id, empty level resets it
+/// to the default level of the daemon
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetLogLevelRequest {
+    #[prost(string, tag = "1")]
+    pub level: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub metadata: ::core::option::Option<Metadata>,
+}
+/// SetLogLevelResponse contains the effective log level of the beacon id
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetLogLevelResponse {
+    #[prost(string, tag = "1")]
+    pub level: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub metadata: ::core::option::Option<Metadata>,
+}
+/// QueueStatusRequest requests queues of all loaded beacon ids if beacon id of
+/// metadata is empty
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueueStatusRequest {
+    #[prost(message, optional, tag = "1")]
+    pub metadata: ::core::option::Option<Metadata>,
+}
+/// CommandQueue contains commands which are sent but not yet received by the
+/// beacon process
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CommandQueue {
+    #[prost(string, tag = "1")]
+    pub beacon_id: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "2")]
+    pub depth: u32,
+    /// age of the oldest queued command, 0 if the queue is empty
+    #[prost(uint64, tag = "3")]
+    pub oldest_age_ms: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueueStatusResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub queues: ::prost::alloc::vec::Vec<CommandQueue>,
+}
+/// DKGResetRequest is refused unless it is confirmed
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DkgResetRequest {
+    #[prost(message, optional, tag = "1")]
+    pub metadata: ::core::option::Option<Metadata>,
+    #[prost(bool, tag = "2")]
+    pub confirm: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DkgResetResponse {
+    #[prost(message, optional, tag = "1")]
+    pub metadata: ::core::option::Option<Metadata>,
+    /// status of the wiped state, empty if the state could not be decoded
+    #[prost(string, tag = "2")]
+    pub wiped_status: ::prost::alloc::string::String,
+    /// epoch of the current state after reset, 0 for a fresh state
+    #[prost(uint32, tag = "3")]
+    pub epoch: u32,
+    /// folder with wiped files and evidence log at the daemon host
+    #[prost(string, tag = "4")]
+    pub backup: ::prost::alloc::string::String,
+}
+/// StoreStatsRequest requests stores of all loaded beacon ids if beacon id of
+/// metadata is empty
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StoreStatsRequest {
+    #[prost(message, optional, tag = "1")]
+    pub metadata: ::core::option::Option<Metadata>,
+}
+/// BeaconStoreStats summarizes the chain store of a beacon id
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BeaconStoreStats {
+    #[prost(string, tag = "1")]
+    pub beacon_id: ::prost::alloc::string::String,
+    /// storage backend, e.g. "sqlite"
+    #[prost(string, tag = "2")]
+    pub backend: ::prost::alloc::string::String,
+    /// amount of stored beacons, including genesis
+    #[prost(uint64, tag = "3")]
+    pub total_rounds: u64,
+    /// first and last stored rounds, 0 if the store is empty
+    #[prost(uint64, tag = "4")]
+    pub first_round: u64,
+    #[prost(uint64, tag = "5")]
+    pub last_round: u64,
+    /// rounds missing in between of the first and the last stored rounds
+    #[prost(uint64, tag = "6")]
+    pub missing_rounds: u64,
+    /// size of database files on disk, including WAL
+    #[prost(uint64, tag = "7")]
+    pub disk_bytes: u64,
+    /// size of WAL which is not yet checkpointed into the database file
+    #[prost(uint64, tag = "8")]
+    pub wal_bytes: u64,
+    /// size of free pages which are reclaimed by compaction
+    #[prost(uint64, tag = "9")]
+    pub free_bytes: u64,
+    /// error of reading the store, other fields are empty if set
+    #[prost(string, tag = "10")]
+    pub error: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StoreStatsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub stores: ::prost::alloc::vec::Vec<BeaconStoreStats>,
+}
+/// BackupKeysRequest archives all beacon ids if beacon id of metadata is empty,
+/// output_file is a file name of a new archive in backups folder of the daemon
+/// base folder
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BackupKeysRequest {
+    #[prost(string, tag = "1")]
+    pub output_file: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub metadata: ::core::option::Option<Metadata>,
+    /// private keys and shares are encrypted with the passphrase if not empty
+    #[prost(string, tag = "3")]
+    pub passphrase: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BackupKeysResponse {
+    #[prost(string, repeated, tag = "1")]
+    pub beacon_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// amount of archived files
+    #[prost(uint32, tag = "2")]
+    pub files: u32,
+    /// path of the archive at the daemon host
+    #[prost(string, tag = "3")]
+    pub path: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DebugDumpRequest {
+    #[prost(message, optional, tag = "1")]
+    pub metadata: ::core::option::Option<Metadata>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DebugDumpResponse {
+    /// JSON document with version, sanitized config, status snapshots, store stats
+    /// and recent events of all loaded beacon ids
+    #[prost(string, tag = "1")]
+    pub dump: ::prost::alloc::string::String,
 }
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct ListSchemesRequest {}
@@ -242,6 +505,9 @@ pub struct ShutdownResponse {
 pub struct LoadBeaconRequest {
     #[prost(message, optional, tag = "1")]
     pub metadata: ::core::option::Option<Metadata>,
+    /// base folder to import the beacon id from, daemon folder is used if empty
+    #[prost(string, tag = "2")]
+    pub folder: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct LoadBeaconResponse {
@@ -249,6 +515,16 @@ pub struct LoadBeaconResponse {
     pub metadata: ::core::option::Option<Metadata>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnloadBeaconRequest {
+    #[prost(message, optional, tag = "1")]
+    pub metadata: ::core::option::Option<Metadata>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnloadBeaconResponse {
+    #[prost(message, optional, tag = "1")]
+    pub metadata: ::core::option::Option<Metadata>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StartSyncRequest {
     /// nodes to contact to
     #[prost(string, repeated, tag = "2")]
@@ -259,6 +535,27 @@ pub struct StartSyncRequest {
     pub up_to: u64,
     #[prost(message, optional, tag = "5")]
     pub metadata: ::core::option::Option<Metadata>,
+    /// verify_mode selects how fetched beacons are verified:
+    /// 0 - full (default), 1 - spot-check, 2 - trust.
+    #[prost(uint32, tag = "6")]
+    pub verify_mode: u32,
+    /// spot_check_every is the verification interval in rounds for spot-check mode.
+    #[prost(uint64, tag = "7")]
+    pub spot_check_every: u64,
+    /// checkpoint_round is the round of trusted checkpoint to start verification from.
+    /// if checkpoint_round is 0, verification starts from the latest stored beacon.
+    #[prost(uint64, tag = "8")]
+    pub checkpoint_round: u64,
+    /// checkpoint_signature is the trusted signature for checkpoint_round.
+    #[prost(bytes = "vec", tag = "9")]
+    pub checkpoint_signature: ::prost::alloc::vec::Vec<u8>,
+    /// archive_url is the URL of beacon archive imported before syncing from nodes.
+    #[prost(string, tag = "10")]
+    pub archive_url: ::prost::alloc::string::String,
+    /// chain_info is chain info JSON as served at /info of HTTP relays.
+    /// if set, it is used instead of chain info fetched from nodes.
+    #[prost(string, tag = "11")]
+    pub chain_info: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SyncProgress {
@@ -268,6 +565,12 @@ pub struct SyncProgress {
     pub target: u64,
     #[prost(message, optional, tag = "3")]
     pub metadata: ::core::option::Option<Metadata>,
+    /// verify_mode is the label of verification mode applied by the daemon.
+    #[prost(string, tag = "4")]
+    pub verify_mode: ::prost::alloc::string::String,
+    /// summary is the JSON summary of the session, set in the final message only.
+    #[prost(string, tag = "5")]
+    pub summary: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct BackupDbRequest {
@@ -288,10 +591,10 @@ pub mod control_client {
         dead_code,
         missing_docs,
         clippy::wildcard_imports,
-        clippy::let_unit_value,
+        clippy::let_unit_value
     )]
-    use tonic::codegen::*;
     use tonic::codegen::http::Uri;
+    use tonic::codegen::*;
     #[derive(Debug, Clone)]
     pub struct ControlClient<T> {
         inner: tonic::client::Grpc<T>,
@@ -335,9 +638,8 @@ pub mod control_client {
                     <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
                 >,
             >,
-            <T as tonic::codegen::Service<
-                http::Request<tonic::body::BoxBody>,
-            >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
+            <T as tonic::codegen::Service<http::Request<tonic::body::BoxBody>>>::Error:
+                Into<StdError> + std::marker::Send + std::marker::Sync,
         {
             ControlClient::new(InterceptedService::new(inner, interceptor))
         }
@@ -377,18 +679,14 @@ pub mod control_client {
             &mut self,
             request: impl tonic::IntoRequest<super::Ping>,
         ) -> std::result::Result<tonic::Response<super::Pong>, tonic::Status> {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static("/drand.Control/PingPong");
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("drand.Control", "PingPong"));
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "PingPong"));
             self.inner.unary(req, path, codec).await
         }
         /// Status responds with the actual status of drand process
@@ -396,64 +694,45 @@ pub mod control_client {
             &mut self,
             request: impl tonic::IntoRequest<super::StatusRequest>,
         ) -> std::result::Result<tonic::Response<super::StatusResponse>, tonic::Status> {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static("/drand.Control/Status");
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("drand.Control", "Status"));
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "Status"));
             self.inner.unary(req, path, codec).await
         }
         /// ListSchemes responds with the list of ids for the available schemes
         pub async fn list_schemes(
             &mut self,
             request: impl tonic::IntoRequest<super::ListSchemesRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListSchemesResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::ListSchemesResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/drand.Control/ListSchemes",
-            );
+            let path = http::uri::PathAndQuery::from_static("/drand.Control/ListSchemes");
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("drand.Control", "ListSchemes"));
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "ListSchemes"));
             self.inner.unary(req, path, codec).await
         }
         /// PublicKey returns the longterm public key of the drand node
         pub async fn public_key(
             &mut self,
             request: impl tonic::IntoRequest<super::PublicKeyRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::PublicKeyResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::PublicKeyResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static("/drand.Control/PublicKey");
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("drand.Control", "PublicKey"));
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "PublicKey"));
             self.inner.unary(req, path, codec).await
         }
         /// ChainInfo returns the chain info for the chain hash or beacon id requested
@@ -461,22 +740,15 @@ pub mod control_client {
         pub async fn chain_info(
             &mut self,
             request: impl tonic::IntoRequest<super::ChainInfoRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ChainInfoPacket>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::ChainInfoPacket>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static("/drand.Control/ChainInfo");
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("drand.Control", "ChainInfo"));
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "ChainInfo"));
             self.inner.unary(req, path, codec).await
         }
         /// GroupFile returns the TOML-encoded group file, containing the group public
@@ -485,60 +757,59 @@ pub mod control_client {
             &mut self,
             request: impl tonic::IntoRequest<super::GroupRequest>,
         ) -> std::result::Result<tonic::Response<super::GroupPacket>, tonic::Status> {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static("/drand.Control/GroupFile");
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("drand.Control", "GroupFile"));
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "GroupFile"));
             self.inner.unary(req, path, codec).await
         }
         pub async fn shutdown(
             &mut self,
             request: impl tonic::IntoRequest<super::ShutdownRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ShutdownResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::ShutdownResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static("/drand.Control/Shutdown");
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("drand.Control", "Shutdown"));
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "Shutdown"));
             self.inner.unary(req, path, codec).await
         }
         pub async fn load_beacon(
             &mut self,
             request: impl tonic::IntoRequest<super::LoadBeaconRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::LoadBeaconResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::LoadBeaconResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static("/drand.Control/LoadBeacon");
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("drand.Control", "LoadBeacon"));
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "LoadBeacon"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// UnloadBeacon stops the beacon id, the daemon keeps running
+        pub async fn unload_beacon(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UnloadBeaconRequest>,
+        ) -> std::result::Result<tonic::Response<super::UnloadBeaconResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/drand.Control/UnloadBeacon");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "UnloadBeacon"));
             self.inner.unary(req, path, codec).await
         }
         pub async fn start_follow_chain(
@@ -548,18 +819,11 @@ pub mod control_client {
             tonic::Response<tonic::codec::Streaming<super::SyncProgress>>,
             tonic::Status,
         > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/drand.Control/StartFollowChain",
-            );
+            let path = http::uri::PathAndQuery::from_static("/drand.Control/StartFollowChain");
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(GrpcMethod::new("drand.Control", "StartFollowChain"));
@@ -572,18 +836,11 @@ pub mod control_client {
             tonic::Response<tonic::codec::Streaming<super::SyncProgress>>,
             tonic::Status,
         > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/drand.Control/StartCheckChain",
-            );
+            let path = http::uri::PathAndQuery::from_static("/drand.Control/StartCheckChain");
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(GrpcMethod::new("drand.Control", "StartCheckChain"));
@@ -592,22 +849,12 @@ pub mod control_client {
         pub async fn backup_database(
             &mut self,
             request: impl tonic::IntoRequest<super::BackupDbRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::BackupDbResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::BackupDbResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/drand.Control/BackupDatabase",
-            );
+            let path = http::uri::PathAndQuery::from_static("/drand.Control/BackupDatabase");
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(GrpcMethod::new("drand.Control", "BackupDatabase"));
@@ -617,25 +864,159 @@ pub mod control_client {
         pub async fn remote_status(
             &mut self,
             request: impl tonic::IntoRequest<super::RemoteStatusRequest>,
+        ) -> std::result::Result<tonic::Response<super::RemoteStatusResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/drand.Control/RemoteStatus");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "RemoteStatus"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Events streams structured daemon events
+        pub async fn events(
+            &mut self,
+            request: impl tonic::IntoRequest<super::EventsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::RemoteStatusResponse>,
+            tonic::Response<tonic::codec::Streaming<super::DaemonEvent>>,
             tonic::Status,
         > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/drand.Control/RemoteStatus",
-            );
+            let path = http::uri::PathAndQuery::from_static("/drand.Control/Events");
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("drand.Control", "RemoteStatus"));
+                .insert(GrpcMethod::new("drand.Control", "Events"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        /// PeerBandwidth returns bytes sent to and received from each peer IP
+        pub async fn peer_bandwidth(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PeerBandwidthRequest>,
+        ) -> std::result::Result<tonic::Response<super::PeerBandwidthResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/drand.Control/PeerBandwidth");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "PeerBandwidth"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Resync forces a resync session of the chain from the given round
+        pub async fn resync(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ResyncRequest>,
+        ) -> std::result::Result<tonic::Response<super::ResyncResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/drand.Control/Resync");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "Resync"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// SetLogLevel changes log level of the beacon id at runtime
+        pub async fn set_log_level(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SetLogLevelRequest>,
+        ) -> std::result::Result<tonic::Response<super::SetLogLevelResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/drand.Control/SetLogLevel");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "SetLogLevel"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// QueueStatus reports command queues of beacon processes
+        pub async fn queue_status(
+            &mut self,
+            request: impl tonic::IntoRequest<super::QueueStatusRequest>,
+        ) -> std::result::Result<tonic::Response<super::QueueStatusResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/drand.Control/QueueStatus");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "QueueStatus"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// DKGReset wipes failed or stale DKG state of a beacon id
+        pub async fn dkg_reset(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DkgResetRequest>,
+        ) -> std::result::Result<tonic::Response<super::DkgResetResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/drand.Control/DKGReset");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "DKGReset"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// StoreStats reports chain stores of beacon ids
+        pub async fn store_stats(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StoreStatsRequest>,
+        ) -> std::result::Result<tonic::Response<super::StoreStatsResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/drand.Control/StoreStats");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "StoreStats"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// BackupKeys writes an archive of keys, group files and DKG store
+        pub async fn backup_keys(
+            &mut self,
+            request: impl tonic::IntoRequest<super::BackupKeysRequest>,
+        ) -> std::result::Result<tonic::Response<super::BackupKeysResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/drand.Control/BackupKeys");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "BackupKeys"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// DebugDump returns sanitized JSON dump of the daemon for bug reports
+        pub async fn debug_dump(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DebugDumpRequest>,
+        ) -> std::result::Result<tonic::Response<super::DebugDumpResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/drand.Control/DebugDump");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Control", "DebugDump"));
             self.inner.unary(req, path, codec).await
         }
     }
@@ -647,7 +1028,7 @@ pub mod control_server {
         dead_code,
         missing_docs,
         clippy::wildcard_imports,
-        clippy::let_unit_value,
+        clippy::let_unit_value
     )]
     use tonic::codegen::*;
     /// Generated trait containing gRPC methods that should be implemented for use with ControlServer.
@@ -667,18 +1048,12 @@ pub mod control_server {
         async fn list_schemes(
             &self,
             request: tonic::Request<super::ListSchemesRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListSchemesResponse>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<super::ListSchemesResponse>, tonic::Status>;
         /// PublicKey returns the longterm public key of the drand node
         async fn public_key(
             &self,
             request: tonic::Request<super::PublicKeyRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::PublicKeyResponse>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<super::PublicKeyResponse>, tonic::Status>;
         /// ChainInfo returns the chain info for the chain hash or beacon id requested
         /// in the metadata
         async fn chain_info(
@@ -694,58 +1069,93 @@ pub mod control_server {
         async fn shutdown(
             &self,
             request: tonic::Request<super::ShutdownRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ShutdownResponse>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<super::ShutdownResponse>, tonic::Status>;
         async fn load_beacon(
             &self,
             request: tonic::Request<super::LoadBeaconRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::LoadBeaconResponse>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<super::LoadBeaconResponse>, tonic::Status>;
+        /// UnloadBeacon stops the beacon id, the daemon keeps running
+        async fn unload_beacon(
+            &self,
+            request: tonic::Request<super::UnloadBeaconRequest>,
+        ) -> std::result::Result<tonic::Response<super::UnloadBeaconResponse>, tonic::Status>;
         /// Server streaming response type for the StartFollowChain method.
         type StartFollowChainStream: tonic::codegen::tokio_stream::Stream<
                 Item = std::result::Result<super::SyncProgress, tonic::Status>,
-            >
-            + std::marker::Send
+            > + std::marker::Send
             + 'static;
         async fn start_follow_chain(
             &self,
             request: tonic::Request<super::StartSyncRequest>,
-        ) -> std::result::Result<
-            tonic::Response<Self::StartFollowChainStream>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<Self::StartFollowChainStream>, tonic::Status>;
         /// Server streaming response type for the StartCheckChain method.
         type StartCheckChainStream: tonic::codegen::tokio_stream::Stream<
                 Item = std::result::Result<super::SyncProgress, tonic::Status>,
-            >
-            + std::marker::Send
+            > + std::marker::Send
             + 'static;
         async fn start_check_chain(
             &self,
             request: tonic::Request<super::StartSyncRequest>,
-        ) -> std::result::Result<
-            tonic::Response<Self::StartCheckChainStream>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<Self::StartCheckChainStream>, tonic::Status>;
         async fn backup_database(
             &self,
             request: tonic::Request<super::BackupDbRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::BackupDbResponse>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<super::BackupDbResponse>, tonic::Status>;
         /// RemoteStatus request the status of some remote drand nodes
         async fn remote_status(
             &self,
             request: tonic::Request<super::RemoteStatusRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::RemoteStatusResponse>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<super::RemoteStatusResponse>, tonic::Status>;
+        /// Server streaming response type for the Events method.
+        type EventsStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::DaemonEvent, tonic::Status>,
+            > + std::marker::Send
+            + 'static;
+        /// Events streams structured daemon events
+        async fn events(
+            &self,
+            request: tonic::Request<super::EventsRequest>,
+        ) -> std::result::Result<tonic::Response<Self::EventsStream>, tonic::Status>;
+        /// PeerBandwidth returns bytes sent to and received from each peer IP
+        async fn peer_bandwidth(
+            &self,
+            request: tonic::Request<super::PeerBandwidthRequest>,
+        ) -> std::result::Result<tonic::Response<super::PeerBandwidthResponse>, tonic::Status>;
+        /// Resync forces a resync session of the chain from the given round
+        async fn resync(
+            &self,
+            request: tonic::Request<super::ResyncRequest>,
+        ) -> std::result::Result<tonic::Response<super::ResyncResponse>, tonic::Status>;
+        /// SetLogLevel changes log level of the beacon id at runtime
+        async fn set_log_level(
+            &self,
+            request: tonic::Request<super::SetLogLevelRequest>,
+        ) -> std::result::Result<tonic::Response<super::SetLogLevelResponse>, tonic::Status>;
+        /// QueueStatus reports command queues of beacon processes
+        async fn queue_status(
+            &self,
+            request: tonic::Request<super::QueueStatusRequest>,
+        ) -> std::result::Result<tonic::Response<super::QueueStatusResponse>, tonic::Status>;
+        /// DKGReset wipes failed or stale DKG state of a beacon id
+        async fn dkg_reset(
+            &self,
+            request: tonic::Request<super::DkgResetRequest>,
+        ) -> std::result::Result<tonic::Response<super::DkgResetResponse>, tonic::Status>;
+        /// StoreStats reports chain stores of beacon ids
+        async fn store_stats(
+            &self,
+            request: tonic::Request<super::StoreStatsRequest>,
+        ) -> std::result::Result<tonic::Response<super::StoreStatsResponse>, tonic::Status>;
+        /// BackupKeys writes an archive of keys, group files and DKG store
+        async fn backup_keys(
+            &self,
+            request: tonic::Request<super::BackupKeysRequest>,
+        ) -> std::result::Result<tonic::Response<super::BackupKeysResponse>, tonic::Status>;
+        /// DebugDump returns sanitized JSON dump of the daemon for bug reports
+        async fn debug_dump(
+            &self,
+            request: tonic::Request<super::DebugDumpRequest>,
+        ) -> std::result::Result<tonic::Response<super::DebugDumpResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct ControlServer<T> {
@@ -768,10 +1178,7 @@ pub mod control_server {
                 max_encoding_message_size: None,
             }
         }
-        pub fn with_interceptor<F>(
-            inner: T,
-            interceptor: F,
-        ) -> InterceptedService<Self, F>
+        pub fn with_interceptor<F>(inner: T, interceptor: F) -> InterceptedService<Self, F>
         where
             F: tonic::service::Interceptor,
         {
@@ -826,21 +1233,13 @@ pub mod control_server {
                 "/drand.Control/PingPong" => {
                     #[allow(non_camel_case_types)]
                     struct PingPongSvc<T: Control>(pub Arc<T>);
-                    impl<T: Control> tonic::server::UnaryService<super::Ping>
-                    for PingPongSvc<T> {
+                    impl<T: Control> tonic::server::UnaryService<super::Ping> for PingPongSvc<T> {
                         type Response = super::Pong;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
-                        fn call(
-                            &mut self,
-                            request: tonic::Request<super::Ping>,
-                        ) -> Self::Future {
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(&mut self, request: tonic::Request<super::Ping>) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move {
-                                <T as Control>::ping_pong(&inner, request).await
-                            };
+                            let fut =
+                                async move { <T as Control>::ping_pong(&inner, request).await };
                             Box::pin(fut)
                         }
                     }
@@ -869,21 +1268,15 @@ pub mod control_server {
                 "/drand.Control/Status" => {
                     #[allow(non_camel_case_types)]
                     struct StatusSvc<T: Control>(pub Arc<T>);
-                    impl<T: Control> tonic::server::UnaryService<super::StatusRequest>
-                    for StatusSvc<T> {
+                    impl<T: Control> tonic::server::UnaryService<super::StatusRequest> for StatusSvc<T> {
                         type Response = super::StatusResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::StatusRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move {
-                                <T as Control>::status(&inner, request).await
-                            };
+                            let fut = async move { <T as Control>::status(&inner, request).await };
                             Box::pin(fut)
                         }
                     }
@@ -912,23 +1305,16 @@ pub mod control_server {
                 "/drand.Control/ListSchemes" => {
                     #[allow(non_camel_case_types)]
                     struct ListSchemesSvc<T: Control>(pub Arc<T>);
-                    impl<
-                        T: Control,
-                    > tonic::server::UnaryService<super::ListSchemesRequest>
-                    for ListSchemesSvc<T> {
+                    impl<T: Control> tonic::server::UnaryService<super::ListSchemesRequest> for ListSchemesSvc<T> {
                         type Response = super::ListSchemesResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::ListSchemesRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move {
-                                <T as Control>::list_schemes(&inner, request).await
-                            };
+                            let fut =
+                                async move { <T as Control>::list_schemes(&inner, request).await };
                             Box::pin(fut)
                         }
                     }
@@ -957,21 +1343,16 @@ pub mod control_server {
                 "/drand.Control/PublicKey" => {
                     #[allow(non_camel_case_types)]
                     struct PublicKeySvc<T: Control>(pub Arc<T>);
-                    impl<T: Control> tonic::server::UnaryService<super::PublicKeyRequest>
-                    for PublicKeySvc<T> {
+                    impl<T: Control> tonic::server::UnaryService<super::PublicKeyRequest> for PublicKeySvc<T> {
                         type Response = super::PublicKeyResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::PublicKeyRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move {
-                                <T as Control>::public_key(&inner, request).await
-                            };
+                            let fut =
+                                async move { <T as Control>::public_key(&inner, request).await };
                             Box::pin(fut)
                         }
                     }
@@ -1000,21 +1381,16 @@ pub mod control_server {
                 "/drand.Control/ChainInfo" => {
                     #[allow(non_camel_case_types)]
                     struct ChainInfoSvc<T: Control>(pub Arc<T>);
-                    impl<T: Control> tonic::server::UnaryService<super::ChainInfoRequest>
-                    for ChainInfoSvc<T> {
+                    impl<T: Control> tonic::server::UnaryService<super::ChainInfoRequest> for ChainInfoSvc<T> {
                         type Response = super::ChainInfoPacket;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::ChainInfoRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move {
-                                <T as Control>::chain_info(&inner, request).await
-                            };
+                            let fut =
+                                async move { <T as Control>::chain_info(&inner, request).await };
                             Box::pin(fut)
                         }
                     }
@@ -1043,21 +1419,16 @@ pub mod control_server {
                 "/drand.Control/GroupFile" => {
                     #[allow(non_camel_case_types)]
                     struct GroupFileSvc<T: Control>(pub Arc<T>);
-                    impl<T: Control> tonic::server::UnaryService<super::GroupRequest>
-                    for GroupFileSvc<T> {
+                    impl<T: Control> tonic::server::UnaryService<super::GroupRequest> for GroupFileSvc<T> {
                         type Response = super::GroupPacket;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::GroupRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move {
-                                <T as Control>::group_file(&inner, request).await
-                            };
+                            let fut =
+                                async move { <T as Control>::group_file(&inner, request).await };
                             Box::pin(fut)
                         }
                     }
@@ -1086,21 +1457,16 @@ pub mod control_server {
                 "/drand.Control/Shutdown" => {
                     #[allow(non_camel_case_types)]
                     struct ShutdownSvc<T: Control>(pub Arc<T>);
-                    impl<T: Control> tonic::server::UnaryService<super::ShutdownRequest>
-                    for ShutdownSvc<T> {
+                    impl<T: Control> tonic::server::UnaryService<super::ShutdownRequest> for ShutdownSvc<T> {
                         type Response = super::ShutdownResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::ShutdownRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move {
-                                <T as Control>::shutdown(&inner, request).await
-                            };
+                            let fut =
+                                async move { <T as Control>::shutdown(&inner, request).await };
                             Box::pin(fut)
                         }
                     }
@@ -1129,23 +1495,16 @@ pub mod control_server {
                 "/drand.Control/LoadBeacon" => {
                     #[allow(non_camel_case_types)]
                     struct LoadBeaconSvc<T: Control>(pub Arc<T>);
-                    impl<
-                        T: Control,
-                    > tonic::server::UnaryService<super::LoadBeaconRequest>
-                    for LoadBeaconSvc<T> {
+                    impl<T: Control> tonic::server::UnaryService<super::LoadBeaconRequest> for LoadBeaconSvc<T> {
                         type Response = super::LoadBeaconResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::LoadBeaconRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move {
-                                <T as Control>::load_beacon(&inner, request).await
-                            };
+                            let fut =
+                                async move { <T as Control>::load_beacon(&inner, request).await };
                             Box::pin(fut)
                         }
                     }
@@ -1171,19 +1530,54 @@ pub mod control_server {
                     };
                     Box::pin(fut)
                 }
+                "/drand.Control/UnloadBeacon" => {
+                    #[allow(non_camel_case_types)]
+                    struct UnloadBeaconSvc<T: Control>(pub Arc<T>);
+                    impl<T: Control> tonic::server::UnaryService<super::UnloadBeaconRequest> for UnloadBeaconSvc<T> {
+                        type Response = super::UnloadBeaconResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UnloadBeaconRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as Control>::unload_beacon(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = UnloadBeaconSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/drand.Control/StartFollowChain" => {
                     #[allow(non_camel_case_types)]
                     struct StartFollowChainSvc<T: Control>(pub Arc<T>);
-                    impl<
-                        T: Control,
-                    > tonic::server::ServerStreamingService<super::StartSyncRequest>
-                    for StartFollowChainSvc<T> {
+                    impl<T: Control> tonic::server::ServerStreamingService<super::StartSyncRequest>
+                        for StartFollowChainSvc<T>
+                    {
                         type Response = super::SyncProgress;
                         type ResponseStream = T::StartFollowChainStream;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::ResponseStream>,
-                            tonic::Status,
-                        >;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::StartSyncRequest>,
@@ -1217,19 +1611,55 @@ pub mod control_server {
                     };
                     Box::pin(fut)
                 }
+                "/drand.Control/Events" => {
+                    #[allow(non_camel_case_types)]
+                    struct EventsSvc<T: Control>(pub Arc<T>);
+                    impl<T: Control> tonic::server::ServerStreamingService<super::EventsRequest> for EventsSvc<T> {
+                        type Response = super::DaemonEvent;
+                        type ResponseStream = T::EventsStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::EventsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { <T as Control>::events(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = EventsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/drand.Control/StartCheckChain" => {
                     #[allow(non_camel_case_types)]
                     struct StartCheckChainSvc<T: Control>(pub Arc<T>);
-                    impl<
-                        T: Control,
-                    > tonic::server::ServerStreamingService<super::StartSyncRequest>
-                    for StartCheckChainSvc<T> {
+                    impl<T: Control> tonic::server::ServerStreamingService<super::StartSyncRequest>
+                        for StartCheckChainSvc<T>
+                    {
                         type Response = super::SyncProgress;
                         type ResponseStream = T::StartCheckChainStream;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::ResponseStream>,
-                            tonic::Status,
-                        >;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::StartSyncRequest>,
@@ -1266,13 +1696,9 @@ pub mod control_server {
                 "/drand.Control/BackupDatabase" => {
                     #[allow(non_camel_case_types)]
                     struct BackupDatabaseSvc<T: Control>(pub Arc<T>);
-                    impl<T: Control> tonic::server::UnaryService<super::BackupDbRequest>
-                    for BackupDatabaseSvc<T> {
+                    impl<T: Control> tonic::server::UnaryService<super::BackupDbRequest> for BackupDatabaseSvc<T> {
                         type Response = super::BackupDbResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::BackupDbRequest>,
@@ -1309,22 +1735,54 @@ pub mod control_server {
                 "/drand.Control/RemoteStatus" => {
                     #[allow(non_camel_case_types)]
                     struct RemoteStatusSvc<T: Control>(pub Arc<T>);
-                    impl<
-                        T: Control,
-                    > tonic::server::UnaryService<super::RemoteStatusRequest>
-                    for RemoteStatusSvc<T> {
+                    impl<T: Control> tonic::server::UnaryService<super::RemoteStatusRequest> for RemoteStatusSvc<T> {
                         type Response = super::RemoteStatusResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::RemoteStatusRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as Control>::remote_status(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RemoteStatusSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/drand.Control/PeerBandwidth" => {
+                    #[allow(non_camel_case_types)]
+                    struct PeerBandwidthSvc<T: Control>(pub Arc<T>);
+                    impl<T: Control> tonic::server::UnaryService<super::PeerBandwidthRequest> for PeerBandwidthSvc<T> {
+                        type Response = super::PeerBandwidthResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PeerBandwidthRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as Control>::remote_status(&inner, request).await
+                                <T as Control>::peer_bandwidth(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1335,7 +1793,272 @@ pub mod control_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = RemoteStatusSvc(inner);
+                        let method = PeerBandwidthSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/drand.Control/Resync" => {
+                    #[allow(non_camel_case_types)]
+                    struct ResyncSvc<T: Control>(pub Arc<T>);
+                    impl<T: Control> tonic::server::UnaryService<super::ResyncRequest> for ResyncSvc<T> {
+                        type Response = super::ResyncResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ResyncRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { <T as Control>::resync(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ResyncSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/drand.Control/SetLogLevel" => {
+                    #[allow(non_camel_case_types)]
+                    struct SetLogLevelSvc<T: Control>(pub Arc<T>);
+                    impl<T: Control> tonic::server::UnaryService<super::SetLogLevelRequest> for SetLogLevelSvc<T> {
+                        type Response = super::SetLogLevelResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SetLogLevelRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as Control>::set_log_level(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = SetLogLevelSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/drand.Control/QueueStatus" => {
+                    #[allow(non_camel_case_types)]
+                    struct QueueStatusSvc<T: Control>(pub Arc<T>);
+                    impl<T: Control> tonic::server::UnaryService<super::QueueStatusRequest> for QueueStatusSvc<T> {
+                        type Response = super::QueueStatusResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::QueueStatusRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as Control>::queue_status(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = QueueStatusSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/drand.Control/DKGReset" => {
+                    #[allow(non_camel_case_types)]
+                    struct DkgResetSvc<T: Control>(pub Arc<T>);
+                    impl<T: Control> tonic::server::UnaryService<super::DkgResetRequest> for DkgResetSvc<T> {
+                        type Response = super::DkgResetResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DkgResetRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as Control>::dkg_reset(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = DkgResetSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/drand.Control/StoreStats" => {
+                    #[allow(non_camel_case_types)]
+                    struct StoreStatsSvc<T: Control>(pub Arc<T>);
+                    impl<T: Control> tonic::server::UnaryService<super::StoreStatsRequest> for StoreStatsSvc<T> {
+                        type Response = super::StoreStatsResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::StoreStatsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as Control>::store_stats(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = StoreStatsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/drand.Control/BackupKeys" => {
+                    #[allow(non_camel_case_types)]
+                    struct BackupKeysSvc<T: Control>(pub Arc<T>);
+                    impl<T: Control> tonic::server::UnaryService<super::BackupKeysRequest> for BackupKeysSvc<T> {
+                        type Response = super::BackupKeysResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::BackupKeysRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as Control>::backup_keys(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = BackupKeysSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/drand.Control/DebugDump" => {
+                    #[allow(non_camel_case_types)]
+                    struct DebugDumpSvc<T: Control>(pub Arc<T>);
+                    impl<T: Control> tonic::server::UnaryService<super::DebugDumpRequest> for DebugDumpSvc<T> {
+                        type Response = super::DebugDumpResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DebugDumpRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as Control>::debug_dump(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = DebugDumpSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1351,23 +2074,19 @@ pub mod control_server {
                     };
                     Box::pin(fut)
                 }
-                _ => {
-                    Box::pin(async move {
-                        let mut response = http::Response::new(empty_body());
-                        let headers = response.headers_mut();
-                        headers
-                            .insert(
-                                tonic::Status::GRPC_STATUS,
-                                (tonic::Code::Unimplemented as i32).into(),
-                            );
-                        headers
-                            .insert(
-                                http::header::CONTENT_TYPE,
-                                tonic::metadata::GRPC_CONTENT_TYPE,
-                            );
-                        Ok(response)
-                    })
-                }
+                _ => Box::pin(async move {
+                    let mut response = http::Response::new(empty_body());
+                    let headers = response.headers_mut();
+                    headers.insert(
+                        tonic::Status::GRPC_STATUS,
+                        (tonic::Code::Unimplemented as i32).into(),
+                    );
+                    headers.insert(
+                        http::header::CONTENT_TYPE,
+                        tonic::metadata::GRPC_CONTENT_TYPE,
+                    );
+                    Ok(response)
+                }),
             }
         }
     }
@@ -1441,10 +2160,44 @@ pub struct SyncRequest {
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct BeaconPacket {
-    #[prost(bytes = "vec", tag = "1")]
-    pub previous_signature: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "bytes", tag = "1")]
+    pub previous_signature: ::prost::bytes::Bytes,
+    #[prost(uint64, tag = "2")]
+    pub round: u64,
+    #[prost(bytes = "bytes", tag = "3")]
+    pub signature: ::prost::bytes::Bytes,
+    #[prost(message, optional, tag = "4")]
+    pub metadata: ::core::option::Option<Metadata>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PingRequest {
+    #[prost(message, optional, tag = "1")]
+    pub metadata: ::core::option::Option<Metadata>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PingResponse {
+    /// latest stored round of each loaded beacon id
+    #[prost(message, repeated, tag = "1")]
+    pub beacons: ::prost::alloc::vec::Vec<BeaconRound>,
+    #[prost(message, optional, tag = "2")]
+    pub metadata: ::core::option::Option<Metadata>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BeaconRound {
+    #[prost(string, tag = "1")]
+    pub beacon_id: ::prost::alloc::string::String,
     #[prost(uint64, tag = "2")]
     pub round: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LeaveNoticeRequest {
+    /// address of the leaving member
+    #[prost(string, tag = "1")]
+    pub address: ::prost::alloc::string::String,
+    /// unix time of the notice in milliseconds
+    #[prost(uint64, tag = "2")]
+    pub timestamp_ms: u64,
+    /// signature of the leaving member key over beacon id, address and time
     #[prost(bytes = "vec", tag = "3")]
     pub signature: ::prost::alloc::vec::Vec<u8>,
     #[prost(message, optional, tag = "4")]
@@ -1457,10 +2210,10 @@ pub mod protocol_client {
         dead_code,
         missing_docs,
         clippy::wildcard_imports,
-        clippy::let_unit_value,
+        clippy::let_unit_value
     )]
-    use tonic::codegen::*;
     use tonic::codegen::http::Uri;
+    use tonic::codegen::*;
     #[derive(Debug, Clone)]
     pub struct ProtocolClient<T> {
         inner: tonic::client::Grpc<T>,
@@ -1504,9 +2257,8 @@ pub mod protocol_client {
                     <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
                 >,
             >,
-            <T as tonic::codegen::Service<
-                http::Request<tonic::body::BoxBody>,
-            >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
+            <T as tonic::codegen::Service<http::Request<tonic::body::BoxBody>>>::Error:
+                Into<StdError> + std::marker::Send + std::marker::Sync,
         {
             ProtocolClient::new(InterceptedService::new(inner, interceptor))
         }
@@ -1545,22 +2297,12 @@ pub mod protocol_client {
         pub async fn get_identity(
             &mut self,
             request: impl tonic::IntoRequest<super::IdentityRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::IdentityResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::IdentityResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/drand.Protocol/GetIdentity",
-            );
+            let path = http::uri::PathAndQuery::from_static("/drand.Protocol/GetIdentity");
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(GrpcMethod::new("drand.Protocol", "GetIdentity"));
@@ -1571,18 +2313,11 @@ pub mod protocol_client {
             &mut self,
             request: impl tonic::IntoRequest<super::PartialBeaconPacket>,
         ) -> std::result::Result<tonic::Response<super::Empty>, tonic::Status> {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/drand.Protocol/PartialBeacon",
-            );
+            let path = http::uri::PathAndQuery::from_static("/drand.Protocol/PartialBeacon");
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(GrpcMethod::new("drand.Protocol", "PartialBeacon"));
@@ -1596,18 +2331,14 @@ pub mod protocol_client {
             tonic::Response<tonic::codec::Streaming<super::BeaconPacket>>,
             tonic::Status,
         > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static("/drand.Protocol/SyncChain");
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("drand.Protocol", "SyncChain"));
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Protocol", "SyncChain"));
             self.inner.server_streaming(req, path, codec).await
         }
         /// Status responds with the actual status of drand process
@@ -1615,18 +2346,59 @@ pub mod protocol_client {
             &mut self,
             request: impl tonic::IntoRequest<super::StatusRequest>,
         ) -> std::result::Result<tonic::Response<super::StatusResponse>, tonic::Status> {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static("/drand.Protocol/Status");
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("drand.Protocol", "Status"));
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Protocol", "Status"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// GroupForEpoch returns the group which has been used at given epoch
+        pub async fn group_for_epoch(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GroupRequest>,
+        ) -> std::result::Result<tonic::Response<super::GroupPacket>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/drand.Protocol/GroupForEpoch");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Protocol", "GroupForEpoch"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Ping responds with the node version and the latest stored round of each beacon id
+        pub async fn ping(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PingRequest>,
+        ) -> std::result::Result<tonic::Response<super::PingResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/drand.Protocol/Ping");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Protocol", "Ping"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// LeaveNotice announces that a member of the group intends to leave it at the next reshare
+        pub async fn leave_notice(
+            &mut self,
+            request: impl tonic::IntoRequest<super::LeaveNoticeRequest>,
+        ) -> std::result::Result<tonic::Response<super::Empty>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/drand.Protocol/LeaveNotice");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Protocol", "LeaveNotice"));
             self.inner.unary(req, path, codec).await
         }
     }
@@ -1638,7 +2410,7 @@ pub mod protocol_server {
         dead_code,
         missing_docs,
         clippy::wildcard_imports,
-        clippy::let_unit_value,
+        clippy::let_unit_value
     )]
     use tonic::codegen::*;
     /// Generated trait containing gRPC methods that should be implemented for use with ProtocolServer.
@@ -1648,10 +2420,7 @@ pub mod protocol_server {
         async fn get_identity(
             &self,
             request: tonic::Request<super::IdentityRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::IdentityResponse>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<super::IdentityResponse>, tonic::Status>;
         /// PartialBeacon sends its partial beacon to another node
         async fn partial_beacon(
             &self,
@@ -1660,8 +2429,7 @@ pub mod protocol_server {
         /// Server streaming response type for the SyncChain method.
         type SyncChainStream: tonic::codegen::tokio_stream::Stream<
                 Item = std::result::Result<super::BeaconPacket, tonic::Status>,
-            >
-            + std::marker::Send
+            > + std::marker::Send
             + 'static;
         /// SyncRequest forces a daemon to sync up its chain with other nodes
         async fn sync_chain(
@@ -1673,6 +2441,21 @@ pub mod protocol_server {
             &self,
             request: tonic::Request<super::StatusRequest>,
         ) -> std::result::Result<tonic::Response<super::StatusResponse>, tonic::Status>;
+        /// GroupForEpoch returns the group which has been used at given epoch
+        async fn group_for_epoch(
+            &self,
+            request: tonic::Request<super::GroupRequest>,
+        ) -> std::result::Result<tonic::Response<super::GroupPacket>, tonic::Status>;
+        /// Ping responds with the node version and the latest stored round of each beacon id
+        async fn ping(
+            &self,
+            request: tonic::Request<super::PingRequest>,
+        ) -> std::result::Result<tonic::Response<super::PingResponse>, tonic::Status>;
+        /// LeaveNotice announces that a member of the group intends to leave it at the next reshare
+        async fn leave_notice(
+            &self,
+            request: tonic::Request<super::LeaveNoticeRequest>,
+        ) -> std::result::Result<tonic::Response<super::Empty>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct ProtocolServer<T> {
@@ -1695,10 +2478,7 @@ pub mod protocol_server {
                 max_encoding_message_size: None,
             }
         }
-        pub fn with_interceptor<F>(
-            inner: T,
-            interceptor: F,
-        ) -> InterceptedService<Self, F>
+        pub fn with_interceptor<F>(inner: T, interceptor: F) -> InterceptedService<Self, F>
         where
             F: tonic::service::Interceptor,
         {
@@ -1753,21 +2533,16 @@ pub mod protocol_server {
                 "/drand.Protocol/GetIdentity" => {
                     #[allow(non_camel_case_types)]
                     struct GetIdentitySvc<T: Protocol>(pub Arc<T>);
-                    impl<T: Protocol> tonic::server::UnaryService<super::IdentityRequest>
-                    for GetIdentitySvc<T> {
+                    impl<T: Protocol> tonic::server::UnaryService<super::IdentityRequest> for GetIdentitySvc<T> {
                         type Response = super::IdentityResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::IdentityRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move {
-                                <T as Protocol>::get_identity(&inner, request).await
-                            };
+                            let fut =
+                                async move { <T as Protocol>::get_identity(&inner, request).await };
                             Box::pin(fut)
                         }
                     }
@@ -1796,15 +2571,9 @@ pub mod protocol_server {
                 "/drand.Protocol/PartialBeacon" => {
                     #[allow(non_camel_case_types)]
                     struct PartialBeaconSvc<T: Protocol>(pub Arc<T>);
-                    impl<
-                        T: Protocol,
-                    > tonic::server::UnaryService<super::PartialBeaconPacket>
-                    for PartialBeaconSvc<T> {
+                    impl<T: Protocol> tonic::server::UnaryService<super::PartialBeaconPacket> for PartialBeaconSvc<T> {
                         type Response = super::Empty;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::PartialBeaconPacket>,
@@ -1841,24 +2610,18 @@ pub mod protocol_server {
                 "/drand.Protocol/SyncChain" => {
                     #[allow(non_camel_case_types)]
                     struct SyncChainSvc<T: Protocol>(pub Arc<T>);
-                    impl<
-                        T: Protocol,
-                    > tonic::server::ServerStreamingService<super::SyncRequest>
-                    for SyncChainSvc<T> {
+                    impl<T: Protocol> tonic::server::ServerStreamingService<super::SyncRequest> for SyncChainSvc<T> {
                         type Response = super::BeaconPacket;
                         type ResponseStream = T::SyncChainStream;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::ResponseStream>,
-                            tonic::Status,
-                        >;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::SyncRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move {
-                                <T as Protocol>::sync_chain(&inner, request).await
-                            };
+                            let fut =
+                                async move { <T as Protocol>::sync_chain(&inner, request).await };
                             Box::pin(fut)
                         }
                     }
@@ -1887,20 +2650,53 @@ pub mod protocol_server {
                 "/drand.Protocol/Status" => {
                     #[allow(non_camel_case_types)]
                     struct StatusSvc<T: Protocol>(pub Arc<T>);
-                    impl<T: Protocol> tonic::server::UnaryService<super::StatusRequest>
-                    for StatusSvc<T> {
+                    impl<T: Protocol> tonic::server::UnaryService<super::StatusRequest> for StatusSvc<T> {
                         type Response = super::StatusResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::StatusRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { <T as Protocol>::status(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = StatusSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/drand.Protocol/GroupForEpoch" => {
+                    #[allow(non_camel_case_types)]
+                    struct GroupForEpochSvc<T: Protocol>(pub Arc<T>);
+                    impl<T: Protocol> tonic::server::UnaryService<super::GroupRequest> for GroupForEpochSvc<T> {
+                        type Response = super::GroupPacket;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GroupRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as Protocol>::status(&inner, request).await
+                                <T as Protocol>::group_for_epoch(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1911,7 +2707,7 @@ pub mod protocol_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = StatusSvc(inner);
+                        let method = GroupForEpochSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1927,23 +2723,94 @@ pub mod protocol_server {
                     };
                     Box::pin(fut)
                 }
-                _ => {
-                    Box::pin(async move {
-                        let mut response = http::Response::new(empty_body());
-                        let headers = response.headers_mut();
-                        headers
-                            .insert(
-                                tonic::Status::GRPC_STATUS,
-                                (tonic::Code::Unimplemented as i32).into(),
+                "/drand.Protocol/Ping" => {
+                    #[allow(non_camel_case_types)]
+                    struct PingSvc<T: Protocol>(pub Arc<T>);
+                    impl<T: Protocol> tonic::server::UnaryService<super::PingRequest> for PingSvc<T> {
+                        type Response = super::PingResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PingRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { <T as Protocol>::ping(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = PingSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
                             );
-                        headers
-                            .insert(
-                                http::header::CONTENT_TYPE,
-                                tonic::metadata::GRPC_CONTENT_TYPE,
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/drand.Protocol/LeaveNotice" => {
+                    #[allow(non_camel_case_types)]
+                    struct LeaveNoticeSvc<T: Protocol>(pub Arc<T>);
+                    impl<T: Protocol> tonic::server::UnaryService<super::LeaveNoticeRequest> for LeaveNoticeSvc<T> {
+                        type Response = super::Empty;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::LeaveNoticeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as Protocol>::leave_notice(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = LeaveNoticeSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
                             );
-                        Ok(response)
-                    })
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
                 }
+                _ => Box::pin(async move {
+                    let mut response = http::Response::new(empty_body());
+                    let headers = response.headers_mut();
+                    headers.insert(
+                        tonic::Status::GRPC_STATUS,
+                        (tonic::Code::Unimplemented as i32).into(),
+                    );
+                    headers.insert(
+                        http::header::CONTENT_TYPE,
+                        tonic::metadata::GRPC_CONTENT_TYPE,
+                    );
+                    Ok(response)
+                }),
             }
         }
     }
@@ -1988,6 +2855,8 @@ pub struct PublicRandResponse {
     pub signature: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes = "vec", tag = "3")]
     pub previous_signature: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub randomness: ::prost::alloc::vec::Vec<u8>,
     #[prost(message, optional, tag = "5")]
     pub metadata: ::core::option::Option<Metadata>,
 }
@@ -2000,6 +2869,32 @@ pub struct ListBeaconIDsResponse {
     #[prost(message, repeated, tag = "2")]
     pub metadatas: ::prost::alloc::vec::Vec<Metadata>,
 }
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MerkleProofRequest {
+    #[prost(uint64, tag = "1")]
+    pub round: u64,
+    #[prost(message, optional, tag = "2")]
+    pub metadata: ::core::option::Option<Metadata>,
+}
+/// MerkleProofResponse is an RFC 6962 inclusion proof of the beacon into the tree
+/// over rounds \[1, tree_size\]. Leaf of the round is
+/// sha256(0x00 || round (big-endian) || signature), path is ordered from leaf to
+/// root.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MerkleProofResponse {
+    #[prost(uint64, tag = "1")]
+    pub round: u64,
+    #[prost(bytes = "vec", tag = "2")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint64, tag = "3")]
+    pub tree_size: u64,
+    #[prost(bytes = "vec", tag = "4")]
+    pub root: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", repeated, tag = "5")]
+    pub path: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(message, optional, tag = "6")]
+    pub metadata: ::core::option::Option<Metadata>,
+}
 /// Generated client implementations.
 pub mod public_client {
     #![allow(
@@ -2007,10 +2902,10 @@ pub mod public_client {
         dead_code,
         missing_docs,
         clippy::wildcard_imports,
-        clippy::let_unit_value,
+        clippy::let_unit_value
     )]
-    use tonic::codegen::*;
     use tonic::codegen::http::Uri;
+    use tonic::codegen::*;
     #[derive(Debug, Clone)]
     pub struct PublicClient<T> {
         inner: tonic::client::Grpc<T>,
@@ -2054,9 +2949,8 @@ pub mod public_client {
                     <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
                 >,
             >,
-            <T as tonic::codegen::Service<
-                http::Request<tonic::body::BoxBody>,
-            >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
+            <T as tonic::codegen::Service<http::Request<tonic::body::BoxBody>>>::Error:
+                Into<StdError> + std::marker::Send + std::marker::Sync,
         {
             PublicClient::new(InterceptedService::new(inner, interceptor))
         }
@@ -2096,22 +2990,16 @@ pub mod public_client {
         pub async fn public_rand(
             &mut self,
             request: impl tonic::IntoRequest<super::PublicRandRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::PublicRandResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::PublicRandResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static("/drand.Public/PublicRand");
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("drand.Public", "PublicRand"));
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Public", "PublicRand"));
             self.inner.unary(req, path, codec).await
         }
         pub async fn public_rand_stream(
@@ -2121,18 +3009,11 @@ pub mod public_client {
             tonic::Response<tonic::codec::Streaming<super::PublicRandResponse>>,
             tonic::Status,
         > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/drand.Public/PublicRandStream",
-            );
+            let path = http::uri::PathAndQuery::from_static("/drand.Public/PublicRandStream");
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(GrpcMethod::new("drand.Public", "PublicRandStream"));
@@ -2143,49 +3024,50 @@ pub mod public_client {
         pub async fn chain_info(
             &mut self,
             request: impl tonic::IntoRequest<super::ChainInfoRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ChainInfoPacket>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::ChainInfoPacket>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static("/drand.Public/ChainInfo");
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("drand.Public", "ChainInfo"));
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Public", "ChainInfo"));
             self.inner.unary(req, path, codec).await
         }
         /// ListBeaconIDs responds with the list of Beacon IDs running on that node
         pub async fn list_beacon_i_ds(
             &mut self,
             request: impl tonic::IntoRequest<super::ListBeaconIDsRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListBeaconIDsResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::ListBeaconIDsResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/drand.Public/ListBeaconIDs",
-            );
+            let path = http::uri::PathAndQuery::from_static("/drand.Public/ListBeaconIDs");
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(GrpcMethod::new("drand.Public", "ListBeaconIDs"));
             self.inner.unary(req, path, codec).await
         }
+        /// MerkleProof returns inclusion proof of the round into the Merkle tree over
+        /// beacons stored by the node
+        pub async fn merkle_proof(
+            &mut self,
+            request: impl tonic::IntoRequest<super::MerkleProofRequest>,
+        ) -> std::result::Result<tonic::Response<super::MerkleProofResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/drand.Public/MerkleProof");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Public", "MerkleProof"));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -2195,7 +3077,7 @@ pub mod public_server {
         dead_code,
         missing_docs,
         clippy::wildcard_imports,
-        clippy::let_unit_value,
+        clippy::let_unit_value
     )]
     use tonic::codegen::*;
     /// Generated trait containing gRPC methods that should be implemented for use with PublicServer.
@@ -2206,23 +3088,16 @@ pub mod public_server {
         async fn public_rand(
             &self,
             request: tonic::Request<super::PublicRandRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::PublicRandResponse>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<super::PublicRandResponse>, tonic::Status>;
         /// Server streaming response type for the PublicRandStream method.
         type PublicRandStreamStream: tonic::codegen::tokio_stream::Stream<
                 Item = std::result::Result<super::PublicRandResponse, tonic::Status>,
-            >
-            + std::marker::Send
+            > + std::marker::Send
             + 'static;
         async fn public_rand_stream(
             &self,
             request: tonic::Request<super::PublicRandRequest>,
-        ) -> std::result::Result<
-            tonic::Response<Self::PublicRandStreamStream>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<Self::PublicRandStreamStream>, tonic::Status>;
         /// ChainInfo returns the information related to the chain this node
         /// participates to
         async fn chain_info(
@@ -2233,10 +3108,13 @@ pub mod public_server {
         async fn list_beacon_i_ds(
             &self,
             request: tonic::Request<super::ListBeaconIDsRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListBeaconIDsResponse>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<super::ListBeaconIDsResponse>, tonic::Status>;
+        /// MerkleProof returns inclusion proof of the round into the Merkle tree over
+        /// beacons stored by the node
+        async fn merkle_proof(
+            &self,
+            request: tonic::Request<super::MerkleProofRequest>,
+        ) -> std::result::Result<tonic::Response<super::MerkleProofResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct PublicServer<T> {
@@ -2259,10 +3137,7 @@ pub mod public_server {
                 max_encoding_message_size: None,
             }
         }
-        pub fn with_interceptor<F>(
-            inner: T,
-            interceptor: F,
-        ) -> InterceptedService<Self, F>
+        pub fn with_interceptor<F>(inner: T, interceptor: F) -> InterceptedService<Self, F>
         where
             F: tonic::service::Interceptor,
         {
@@ -2317,21 +3192,16 @@ pub mod public_server {
                 "/drand.Public/PublicRand" => {
                     #[allow(non_camel_case_types)]
                     struct PublicRandSvc<T: Public>(pub Arc<T>);
-                    impl<T: Public> tonic::server::UnaryService<super::PublicRandRequest>
-                    for PublicRandSvc<T> {
+                    impl<T: Public> tonic::server::UnaryService<super::PublicRandRequest> for PublicRandSvc<T> {
                         type Response = super::PublicRandResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::PublicRandRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move {
-                                <T as Public>::public_rand(&inner, request).await
-                            };
+                            let fut =
+                                async move { <T as Public>::public_rand(&inner, request).await };
                             Box::pin(fut)
                         }
                     }
@@ -2360,16 +3230,13 @@ pub mod public_server {
                 "/drand.Public/PublicRandStream" => {
                     #[allow(non_camel_case_types)]
                     struct PublicRandStreamSvc<T: Public>(pub Arc<T>);
-                    impl<
-                        T: Public,
-                    > tonic::server::ServerStreamingService<super::PublicRandRequest>
-                    for PublicRandStreamSvc<T> {
+                    impl<T: Public> tonic::server::ServerStreamingService<super::PublicRandRequest>
+                        for PublicRandStreamSvc<T>
+                    {
                         type Response = super::PublicRandResponse;
                         type ResponseStream = T::PublicRandStreamStream;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::ResponseStream>,
-                            tonic::Status,
-                        >;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::PublicRandRequest>,
@@ -2406,21 +3273,16 @@ pub mod public_server {
                 "/drand.Public/ChainInfo" => {
                     #[allow(non_camel_case_types)]
                     struct ChainInfoSvc<T: Public>(pub Arc<T>);
-                    impl<T: Public> tonic::server::UnaryService<super::ChainInfoRequest>
-                    for ChainInfoSvc<T> {
+                    impl<T: Public> tonic::server::UnaryService<super::ChainInfoRequest> for ChainInfoSvc<T> {
                         type Response = super::ChainInfoPacket;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::ChainInfoRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move {
-                                <T as Public>::chain_info(&inner, request).await
-                            };
+                            let fut =
+                                async move { <T as Public>::chain_info(&inner, request).await };
                             Box::pin(fut)
                         }
                     }
@@ -2449,15 +3311,9 @@ pub mod public_server {
                 "/drand.Public/ListBeaconIDs" => {
                     #[allow(non_camel_case_types)]
                     struct ListBeaconIDsSvc<T: Public>(pub Arc<T>);
-                    impl<
-                        T: Public,
-                    > tonic::server::UnaryService<super::ListBeaconIDsRequest>
-                    for ListBeaconIDsSvc<T> {
+                    impl<T: Public> tonic::server::UnaryService<super::ListBeaconIDsRequest> for ListBeaconIDsSvc<T> {
                         type Response = super::ListBeaconIDsResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::ListBeaconIDsRequest>,
@@ -2491,23 +3347,57 @@ pub mod public_server {
                     };
                     Box::pin(fut)
                 }
-                _ => {
-                    Box::pin(async move {
-                        let mut response = http::Response::new(empty_body());
-                        let headers = response.headers_mut();
-                        headers
-                            .insert(
-                                tonic::Status::GRPC_STATUS,
-                                (tonic::Code::Unimplemented as i32).into(),
-                            );
-                        headers
-                            .insert(
-                                http::header::CONTENT_TYPE,
-                                tonic::metadata::GRPC_CONTENT_TYPE,
+                "/drand.Public/MerkleProof" => {
+                    #[allow(non_camel_case_types)]
+                    struct MerkleProofSvc<T: Public>(pub Arc<T>);
+                    impl<T: Public> tonic::server::UnaryService<super::MerkleProofRequest> for MerkleProofSvc<T> {
+                        type Response = super::MerkleProofResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::MerkleProofRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as Public>::merkle_proof(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = MerkleProofSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
                             );
-                        Ok(response)
-                    })
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
                 }
+                _ => Box::pin(async move {
+                    let mut response = http::Response::new(empty_body());
+                    let headers = response.headers_mut();
+                    headers.insert(
+                        tonic::Status::GRPC_STATUS,
+                        (tonic::Code::Unimplemented as i32).into(),
+                    );
+                    headers.insert(
+                        http::header::CONTENT_TYPE,
+                        tonic::metadata::GRPC_CONTENT_TYPE,
+                    );
+                    Ok(response)
+                }),
             }
         }
     }
@@ -2543,10 +3433,10 @@ pub mod metrics_client {
         dead_code,
         missing_docs,
         clippy::wildcard_imports,
-        clippy::let_unit_value,
+        clippy::let_unit_value
     )]
-    use tonic::codegen::*;
     use tonic::codegen::http::Uri;
+    use tonic::codegen::*;
     #[derive(Debug, Clone)]
     pub struct MetricsClient<T> {
         inner: tonic::client::Grpc<T>,
@@ -2590,9 +3480,8 @@ pub mod metrics_client {
                     <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
                 >,
             >,
-            <T as tonic::codegen::Service<
-                http::Request<tonic::body::BoxBody>,
-            >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
+            <T as tonic::codegen::Service<http::Request<tonic::body::BoxBody>>>::Error:
+                Into<StdError> + std::marker::Send + std::marker::Sync,
         {
             MetricsClient::new(InterceptedService::new(inner, interceptor))
         }
@@ -2630,22 +3519,15 @@ pub mod metrics_client {
         pub async fn metrics(
             &mut self,
             request: impl tonic::IntoRequest<super::MetricsRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::MetricsResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::MetricsResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static("/drand.Metrics/Metrics");
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("drand.Metrics", "Metrics"));
+            req.extensions_mut()
+                .insert(GrpcMethod::new("drand.Metrics", "Metrics"));
             self.inner.unary(req, path, codec).await
         }
     }
@@ -2657,7 +3539,7 @@ pub mod metrics_server {
         dead_code,
         missing_docs,
         clippy::wildcard_imports,
-        clippy::let_unit_value,
+        clippy::let_unit_value
     )]
     use tonic::codegen::*;
     /// Generated trait containing gRPC methods that should be implemented for use with MetricsServer.
@@ -2689,10 +3571,7 @@ pub mod metrics_server {
                 max_encoding_message_size: None,
             }
         }
-        pub fn with_interceptor<F>(
-            inner: T,
-            interceptor: F,
-        ) -> InterceptedService<Self, F>
+        pub fn with_interceptor<F>(inner: T, interceptor: F) -> InterceptedService<Self, F>
         where
             F: tonic::service::Interceptor,
         {
@@ -2747,21 +3626,15 @@ pub mod metrics_server {
                 "/drand.Metrics/Metrics" => {
                     #[allow(non_camel_case_types)]
                     struct MetricsSvc<T: Metrics>(pub Arc<T>);
-                    impl<T: Metrics> tonic::server::UnaryService<super::MetricsRequest>
-                    for MetricsSvc<T> {
+                    impl<T: Metrics> tonic::server::UnaryService<super::MetricsRequest> for MetricsSvc<T> {
                         type Response = super::MetricsResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::MetricsRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
-                            let fut = async move {
-                                <T as Metrics>::metrics(&inner, request).await
-                            };
+                            let fut = async move { <T as Metrics>::metrics(&inner, request).await };
                             Box::pin(fut)
                         }
                     }
@@ -2787,23 +3660,19 @@ pub mod metrics_server {
                     };
                     Box::pin(fut)
                 }
-                _ => {
-                    Box::pin(async move {
-                        let mut response = http::Response::new(empty_body());
-                        let headers = response.headers_mut();
-                        headers
-                            .insert(
-                                tonic::Status::GRPC_STATUS,
-                                (tonic::Code::Unimplemented as i32).into(),
-                            );
-                        headers
-                            .insert(
-                                http::header::CONTENT_TYPE,
-                                tonic::metadata::GRPC_CONTENT_TYPE,
-                            );
-                        Ok(response)
-                    })
-                }
+                _ => Box::pin(async move {
+                    let mut response = http::Response::new(empty_body());
+                    let headers = response.headers_mut();
+                    headers.insert(
+                        tonic::Status::GRPC_STATUS,
+                        (tonic::Code::Unimplemented as i32).into(),
+                    );
+                    headers.insert(
+                        http::header::CONTENT_TYPE,
+                        tonic::metadata::GRPC_CONTENT_TYPE,
+                    );
+                    Ok(response)
+                }),
             }
         }
     }
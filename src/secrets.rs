@@ -0,0 +1,150 @@
+//! Resolution of sensitive configuration values: storage credentials and key passphrases.
+//!
+//! Secrets are never passed as command line values, which are visible to other users of the
+//! host. Flags of sensitive values take a [`SecretSource`] instead:
+//! - `env:NAME`: environment variable `NAME`;
+//! - `file:PATH`: the first line of the file, a value without prefix is a file path as well;
+//! - `cred:NAME`: systemd credential `NAME`, i.e. the file `$CREDENTIALS_DIRECTORY/NAME`
+//!   provided by `LoadCredential=` or `SetCredentialEncrypted=` of the unit.
+//!
+//! Resolved [`Secret`] is redacted from `Debug` output.
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Environment variable set by systemd for services with credentials.
+const CREDENTIALS_DIRECTORY: &str = "CREDENTIALS_DIRECTORY";
+
+#[derive(thiserror::Error, Debug)]
+pub enum SecretError {
+    #[error("{0}: environment variable is not set")]
+    NotSet(SecretSource),
+    #[error("{0}: {CREDENTIALS_DIRECTORY} is not set, credentials are not provided by systemd")]
+    NoCredentials(SecretSource),
+    #[error("{0}: {1}")]
+    Read(SecretSource, std::io::Error),
+    #[error("{0}: secret is empty")]
+    Empty(SecretSource),
+}
+
+/// Location of a secret value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SecretSource {
+    Env(String),
+    File(PathBuf),
+    Credential(String),
+}
+
+impl SecretSource {
+    pub fn resolve(&self) -> Result<Secret, SecretError> {
+        let value = match self {
+            Self::Env(name) => {
+                std::env::var(name).map_err(|_| SecretError::NotSet(self.clone()))?
+            }
+            Self::File(path) => self.read_first_line(path)?,
+            Self::Credential(name) => {
+                let dir = std::env::var_os(CREDENTIALS_DIRECTORY)
+                    .ok_or_else(|| SecretError::NoCredentials(self.clone()))?;
+                self.read_first_line(&PathBuf::from(dir).join(name))?
+            }
+        };
+        if value.is_empty() {
+            return Err(SecretError::Empty(self.clone()));
+        }
+
+        Ok(Secret(value))
+    }
+
+    fn read_first_line(&self, path: &Path) -> Result<String, SecretError> {
+        let content =
+            std::fs::read_to_string(path).map_err(|err| SecretError::Read(self.clone(), err))?;
+
+        Ok(content.lines().next().unwrap_or_default().to_string())
+    }
+}
+
+impl fmt::Display for SecretSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Env(name) => write!(f, "env:{name}"),
+            Self::File(path) => write!(f, "file:{}", path.display()),
+            Self::Credential(name) => write!(f, "cred:{name}"),
+        }
+    }
+}
+
+/// Parses [`SecretSource`] of a command line flag.
+pub fn parse_source(s: &str) -> Result<SecretSource, String> {
+    let source = match s.split_once(':') {
+        Some(("env", name)) => SecretSource::Env(name.to_string()),
+        Some(("file", path)) => SecretSource::File(path.into()),
+        Some(("cred", name)) => SecretSource::Credential(name.to_string()),
+        _ => SecretSource::File(s.into()),
+    };
+    match &source {
+        SecretSource::Env(name) | SecretSource::Credential(name) if name.is_empty() => {
+            Err(format!("name is missing in secret source '{s}'"))
+        }
+        SecretSource::Credential(name) if name.contains('/') => {
+            Err(format!("credential name '{name}' must not contain '/'"))
+        }
+        SecretSource::File(path) if path.as_os_str().is_empty() => {
+            Err(format!("path is missing in secret source '{s}'"))
+        }
+        _ => Ok(source),
+    }
+}
+
+/// Resolved secret value.
+#[derive(Clone, PartialEq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(***)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_sources() {
+        assert_eq!(
+            parse_source("env:AWS_SECRET_ACCESS_KEY"),
+            Ok(SecretSource::Env("AWS_SECRET_ACCESS_KEY".into()))
+        );
+        assert_eq!(
+            parse_source("/run/keys/passphrase"),
+            Ok(SecretSource::File("/run/keys/passphrase".into()))
+        );
+        assert!(parse_source("env:").is_err());
+        assert!(parse_source("cred:../passphrase").is_err());
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("passphrase"), "secret\n").unwrap();
+        std::fs::write(temp_dir.path().join("empty"), "\n").unwrap();
+        let file = parse_source(&temp_dir.path().join("passphrase").display().to_string()).unwrap();
+        assert_eq!(file.resolve().unwrap().expose(), "secret");
+        assert!(matches!(
+            SecretSource::File(temp_dir.path().join("empty")).resolve(),
+            Err(SecretError::Empty(_))
+        ));
+        assert!(matches!(
+            SecretSource::File(temp_dir.path().join("absent")).resolve(),
+            Err(SecretError::Read(..))
+        ));
+        assert!(matches!(
+            SecretSource::Env("DRAND_TEST_UNSET_SECRET".into()).resolve(),
+            Err(SecretError::NotSet(_))
+        ));
+        assert_eq!(format!("{:?}", file.resolve().unwrap()), "Secret(***)");
+    }
+}
@@ -0,0 +1,241 @@
+//! Chain scenarios for mixed groups of Drand-rs and Drand-go nodes.
+use super::utils::*;
+use crate::chain::time;
+use crate::cli::Cli;
+use crate::dkg::status::Status;
+use crate::net::control::ControlClient;
+
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// Catchup coverage:
+/// - whole group is stopped for several rounds and restarted
+/// - missed rounds are produced with catchup pace (faster than period)
+///
+/// Latest stored round of Rust nodes is compared with expected chain height after catchup.
+#[ignore = "uses same ports and folders as DKG scenarios, run separately"]
+#[tokio::test]
+async fn catchup_after_downtime() {
+    // Setup: group: 4, thr: 3, period: 3s, catchup period: 1s
+    //
+    // FOLDER[i]_IMPL
+    //    node0_GO
+    //    node1_GO
+    //    node2_RS
+    //    node3_RS
+    let config = GroupConfig {
+        period: 3,
+        catchup_period: 1,
+        genesis_delay: "30s".into(),
+        ..GroupConfig::default()
+    };
+    let period = u64::from(config.period);
+    let group = run_fresh_dkg(4, None, config).await;
+    let id = group.config.id.clone();
+
+    let info = ControlClient::new(&group.nodes[2].control)
+        .await
+        .unwrap()
+        .chain_info(id.clone())
+        .await
+        .unwrap();
+
+    // Wait for few rounds after genesis.
+    let produced_until = info.genesis_time + 3 * period;
+    let now = time::time_now().as_secs();
+    sleep(Duration::from_secs(produced_until.saturating_sub(now))).await;
+
+    // Stop all nodes: no peers are available for resync, missed rounds are produced in catchup mode.
+    for n in &group.nodes {
+        n.stop().await;
+    }
+    let missed = 5;
+    sleep(Duration::from_secs(missed * period)).await;
+    group.start_daemons();
+
+    // Without catchup the chain stays behind for `missed` rounds.
+    sleep(Duration::from_secs(missed * period)).await;
+    let current = time::current_round(time::time_now().as_secs(), info.period, info.genesis_time);
+
+    // Note: Golang status response is not compatible with Rust implementation.
+    for n in group.nodes.iter().skip(2) {
+        let status = ControlClient::new(&n.control)
+            .await
+            .unwrap()
+            .status(id.clone())
+            .await
+            .unwrap();
+        assert!(
+            status.latest_stored_round + 1 >= current,
+            "node {} is behind: latest stored {}, current {current}",
+            n.private_listen,
+            status.latest_stored_round
+        );
+    }
+
+    group.stop_all().await;
+    remove_nodes_fs();
+}
+
+/// Resync across group transition:
+/// - reshare is completed while one Rust node is stopped before the transition round
+/// - restarted node resyncs rounds of both epochs and switches into new epoch
+///
+/// Latest stored round of the restarted node is compared with expected chain height.
+#[ignore = "uses same ports and folders as DKG scenarios, run separately"]
+#[tokio::test]
+async fn resync_across_transition() {
+    // Epoch: 1
+    // Setup: group: 4, thr: 3, period: 3s
+    //
+    // FOLDER[i]_IMPL
+    //    node0_GO
+    //    node1_GO
+    //    node2_RS
+    //    node3_RS
+    let config = GroupConfig {
+        period: 3,
+        genesis_delay: "30s".into(),
+        ..GroupConfig::default()
+    };
+    let period = u64::from(config.period);
+    let mut group = run_fresh_dkg(4, None, config).await;
+    let id = group.config.id.clone();
+
+    let info = ControlClient::new(&group.nodes[2].control)
+        .await
+        .unwrap()
+        .chain_info(id.clone())
+        .await
+        .unwrap();
+
+    // Wait for few rounds after genesis.
+    let produced_until = info.genesis_time + 3 * period;
+    let now = time::time_now().as_secs();
+    sleep(Duration::from_secs(produced_until.saturating_sub(now))).await;
+
+    // Epoch: 2
+    // Scenario: all nodes are remainers
+    // Setup: group: 4, thr: 3
+    group.setup_scenario(&[], &[0, 1, 2, 3], &[], 3);
+    group.leader_generate_proposal().await;
+    group.members_proceed_proposal().await;
+    group.leader_dkg_execute().await;
+    // Sleep:
+    // 5 until execution time (protocol)
+    // + 3 for fast_sync mode
+    // + 5 (CI/CD)
+    sleep(Duration::from_secs(13)).await;
+    let finished = get_finished_state(&group.nodes[0].control, &id).await;
+    assert_eq!(finished.epoch, 2);
+    assert_eq!(finished.state, Status::Complete as u32);
+
+    // DKG output is received, stop node3_RS before the transition round.
+    group.nodes[3].stop().await;
+    sleep(Duration::from_secs(
+        (time::ROUNDS_UNTIL_TRANSITION + 3) * period,
+    ))
+    .await;
+
+    // Node is restarted within new epoch, missed rounds of both epochs are resynced.
+    group.nodes[3].start();
+    sleep(Duration::from_secs(5 * period)).await;
+    let current = time::current_round(time::time_now().as_secs(), info.period, info.genesis_time);
+
+    let status = ControlClient::new(&group.nodes[3].control)
+        .await
+        .unwrap()
+        .status(id)
+        .await
+        .unwrap();
+    assert!(
+        status.latest_stored_round + 1 >= current,
+        "node is behind: latest stored {}, current {current}",
+        status.latest_stored_round
+    );
+
+    group.stop_all().await;
+    remove_nodes_fs();
+}
+
+/// Share refresh:
+/// - reshare with the same members and threshold is proposed to leader-go by Rust CLI
+/// - group public key and chain hash are kept, beacons are produced across the transition
+///
+/// Latest stored round of Rust nodes is compared with expected chain height after transition.
+#[ignore = "uses same ports and folders as DKG scenarios, run separately"]
+#[tokio::test]
+async fn share_refresh() {
+    // Epoch: 1
+    // Setup: group: 4, thr: 3, period: 3s
+    //
+    // FOLDER[i]_IMPL
+    //    node0_GO
+    //    node1_GO
+    //    node2_RS
+    //    node3_RS
+    let config = GroupConfig {
+        period: 3,
+        genesis_delay: "30s".into(),
+        ..GroupConfig::default()
+    };
+    let period = u64::from(config.period);
+    let mut group = run_fresh_dkg(4, None, config).await;
+    let id = group.config.id.clone();
+
+    let info = ControlClient::new(&group.nodes[2].control)
+        .await
+        .unwrap()
+        .chain_info(id.clone())
+        .await
+        .unwrap();
+
+    // Wait for few rounds after genesis.
+    let produced_until = info.genesis_time + 3 * period;
+    let now = time::time_now().as_secs();
+    sleep(Duration::from_secs(produced_until.saturating_sub(now))).await;
+
+    // Epoch: 2
+    // Scenario: all nodes are remainers, threshold is kept
+    group.setup_scenario(&[], &[0, 1, 2, 3], &[], group.sn.thr);
+    Cli::dkg_refresh(&group.nodes[0].control, &id, &group.nodes[2].private_listen)
+        .run()
+        .await
+        .unwrap();
+    group.members_proceed_proposal().await;
+    group.leader_dkg_execute().await;
+    // Sleep:
+    // 5 until execution time (protocol)
+    // + 3 for fast_sync mode
+    // + 5 (CI/CD)
+    sleep(Duration::from_secs(13)).await;
+    let finished = get_finished_state(&group.nodes[0].control, &id).await;
+    assert_eq!(finished.epoch, 2);
+    assert_eq!(finished.state, Status::Complete as u32);
+    group.assert_groupfiles_with_leader();
+
+    // Wait for the transition round and few rounds of the new epoch.
+    sleep(Duration::from_secs(
+        (time::ROUNDS_UNTIL_TRANSITION + 3) * period,
+    ))
+    .await;
+    let current = time::current_round(time::time_now().as_secs(), info.period, info.genesis_time);
+
+    for n in group.nodes.iter().skip(2) {
+        let mut client = ControlClient::new(&n.control).await.unwrap();
+        let refreshed = client.chain_info(id.clone()).await.unwrap();
+        assert_eq!(refreshed.public_key, info.public_key);
+        assert_eq!(refreshed.hash, info.hash);
+
+        let status = client.status(id.clone()).await.unwrap();
+        assert!(
+            status.latest_stored_round + 1 >= current,
+            "node {} is behind: latest stored {}, current {current}",
+            n.private_listen,
+            status.latest_stored_round
+        );
+    }
+
+    group.stop_all().await;
+    remove_nodes_fs();
+}
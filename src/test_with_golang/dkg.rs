@@ -2,7 +2,10 @@
 //! or generated randomly within the DKG state machine rules (see: [`NodesGroup::generate_roles`]),
 //! which is useful for continuous resharing with different thresholds and participant roles (see: [`random_scenarios`]).
 use super::utils::*;
+use crate::dkg::proposal::ProposalFile;
 use crate::dkg::status::Status;
+use crate::key::migration;
+use crate::key::toml::Toml;
 use std::time::Duration;
 use tokio::time::sleep;
 
@@ -117,6 +120,62 @@ async fn all_roles_dkg() {
     remove_nodes_fs();
 }
 
+/// Proposal files are byte-compatible between implementations:
+/// - proposal generated by leader-go is decoded and encoded back without changes,
+/// - proposal assembled from public key files of the same participants is equal to leader-go proposal.
+#[ignore = "uses same ports and folders as DKG scenarios, run separately"]
+#[tokio::test]
+async fn proposal_file_roundtrip() {
+    let group = run_fresh_dkg(4, None, GroupConfig::default()).await;
+    let go_proposal =
+        std::fs::read_to_string(format!("{}/proposal.toml", group.nodes[0].folder_path)).unwrap();
+
+    let decoded = ProposalFile::toml_decode(&go_proposal.parse().unwrap()).unwrap();
+    assert_eq!(decoded.joining.len(), 4);
+    assert_eq!(decoded.toml_encode().unwrap().to_string(), go_proposal);
+
+    let public_files: Vec<String> = group
+        .nodes
+        .iter()
+        .map(|n| {
+            format!(
+                "{}/multibeacon/{}/key/drand_id.public",
+                n.folder_path, group.config.id
+            )
+        })
+        .collect();
+    let (assembled, scheme) = ProposalFile::from_public_files(&public_files, &[], &[]).unwrap();
+    assert_eq!(scheme, group.config.scheme);
+    assert_eq!(assembled, decoded);
+    assert_eq!(assembled.toml_encode().unwrap().to_string(), go_proposal);
+
+    group.stop_all().await;
+    remove_nodes_fs();
+}
+
+/// Folder of golang node can be loaded in place once DKG state is reconstructed from the group file.
+#[ignore = "uses same ports and folders as DKG scenarios, run separately"]
+#[tokio::test]
+async fn migration_check_golang_folder() {
+    let group = run_fresh_dkg(4, None, GroupConfig::default()).await;
+    // Node0 is golang node in default proportion.
+    let go_folder = &group.nodes[0].folder_path;
+    let id = Some(group.config.id.as_str());
+
+    // DKG state of golang node is not readable.
+    let reports = migration::check_folder(go_folder, id, None).unwrap();
+    assert!(!reports[0].is_ready(), "{}", reports[0]);
+
+    let report = migration::migrate_folder(go_folder, &group.config.id, 1).unwrap();
+    assert!(report.is_ready(), "{report}");
+    // Reconstructed state is found by the next check.
+    let reports = migration::check_folder(go_folder, id, None).unwrap();
+    assert!(reports[0].is_ready(), "{}", reports[0]);
+
+    group.stop_all().await;
+    remove_nodes_fs();
+}
+
 #[ignore = "example for release build"]
 #[tokio::test]
 async fn random_scenarios() {
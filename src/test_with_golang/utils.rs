@@ -1,9 +1,15 @@
-//! Utilities for testing Drand-rs with Drand-go (v2.1.2-insecure bebad8fc).
+//! Utilities for testing Drand-rs with Drand-go.
+//!
+//! Bundled binary (v2.1.2-insecure bebad8fc) is used by default, other golang releases
+//! are downloaded on first use if [`GO_VERSION_ENV`] is set, for example:
+//! `DRAND_GO_VERSION=v1.5.11 cargo test -- --ignored --test-threads 1`.
 
 use crate::cli::*;
+use crate::core::multibeacon;
 use crate::dkg::status::Status;
 use crate::key::Scheme;
 use crate::net::dkg_control::DkgControlClient;
+use crate::net::protocol;
 use crate::protobuf::dkg::DkgEntry;
 
 use energon::kyber::dkg::minimum_t;
@@ -14,18 +20,30 @@ use std::env;
 use std::fmt::Write as _;
 use std::fs::File;
 use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::LazyLock;
 use std::time::Duration;
 use tokio::time::sleep;
 use tracing::*;
 
-/// Absolute path for Drand-go v2.1.2-insecure bebad8fc
+/// Golang release to test against, for example `v2.1.2`.
+pub const GO_VERSION_ENV: &str = "DRAND_GO_VERSION";
+/// Base URL of golang release assets.
+const GO_RELEASES_URL: &str = "https://github.com/drand/drand/releases/download";
+
+/// Absolute path for Drand-go binary, see [`GO_VERSION_ENV`].
 static DRAND_BIN_PATH: LazyLock<String> = LazyLock::new(|| {
-    format!(
-        "{}/{INNER_PATH}drand_go",
-        env::var("CARGO_MANIFEST_DIR").unwrap()
-    )
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    match env::var(GO_VERSION_ENV) {
+        Ok(version) => golang_release(&manifest_dir, &version),
+        Err(_) => format!("{manifest_dir}/{INNER_PATH}drand_go"),
+    }
+});
+/// Releases prior to v2 require TLS to be disabled explicitly.
+static GO_TLS_FLAG: LazyLock<&str> = LazyLock::new(|| match env::var(GO_VERSION_ENV) {
+    Ok(version) if version.starts_with("v1.") => "--tls-disable",
+    _ => "",
 });
 /// Inner path for current directory
 pub const INNER_PATH: &str = "src/test_with_golang/";
@@ -54,6 +72,8 @@ impl Cli {
             control: control.to_string(),
             id: id.to_string(),
             group: groupfile_path.map(ToString::to_string),
+            wait: false,
+            follow: false,
         }))
     }
 
@@ -61,6 +81,20 @@ impl Cli {
         Self::new(Cmd::Dkg(crate::cli::Dkg::Accept {
             control: control.to_string(),
             id: id.to_string(),
+            wait: false,
+        }))
+    }
+
+    /// Share refresh is proposed to the leader at `control`, group is fetched from `node`.
+    pub fn dkg_refresh(control: &str, id: &str, node: &str) -> Self {
+        Self::new(Cmd::Dkg(crate::cli::Dkg::Refresh {
+            control: control.to_string(),
+            id: id.to_string(),
+            node: node.to_string(),
+            threshold: None,
+            timeout: 3600,
+            execute_after: None,
+            every: None,
         }))
     }
 
@@ -86,7 +120,7 @@ pub struct NodeConfig {
     /// Base folder name: `node<cmd_i>_<lang>`
     folder_name: String,
     /// Base folder absolute path
-    folder_path: String,
+    pub folder_path: String,
     /// Groupfile absolute path
     pub groupfile_path: String,
     /// Node private-listen address (in tests is same to URI, no TLS termination).
@@ -659,13 +693,14 @@ impl NodeConfig {
                     id: id.to_string(),
                     scheme: scheme.to_string(),
                     address: self.private_listen.to_string(),
+                    insecure_seed: None,
                 };
                 Cli::keygen(config).run().await.unwrap();
             }
             Lang::GO => {
                 let args = format!(
-                    "generate-keypair --folder {} --control {} --id {} --scheme {} {}",
-                    self.folder_path, self.control, id, scheme, self.private_listen
+                    "generate-keypair {} --folder {} --control {} --id {} --scheme {} {}",
+                    *GO_TLS_FLAG, self.folder_path, self.control, id, scheme, self.private_listen
                 );
                 run_cmd_golang(&args).await;
             }
@@ -676,8 +711,9 @@ impl NodeConfig {
         match self.implementation {
             Lang::GO => {
                 let args = format!(
-                    "{} start --folder {} --private-listen {} --verbose --control {} >> {}/node{}.log 2>&1",
+                    "{} start {} --folder {} --private-listen {} --verbose --control {} >> {}/node{}.log 2>&1",
                     DRAND_BIN_PATH.as_str(),
+                    *GO_TLS_FLAG,
                     self.folder_path,
                     self.private_listen,
                     self.control,
@@ -694,6 +730,24 @@ impl NodeConfig {
                     private_listen: self.private_listen.clone(),
                     // Load all ids.
                     id: None,
+                    only: vec![],
+                    log_level: vec![],
+                    bulk_log_rate: crate::log::DEFAULT_BULK_LOG_RATE,
+                    sync_batch_size: protocol::DEFAULT_SYNC_BATCH_SIZE,
+                    max_sync_streams: protocol::DEFAULT_MAX_SYNC_STREAMS,
+                    sync_buffer_mb: protocol::DEFAULT_SYNC_BUFFER_MB,
+                    callback_timeout: multibeacon::DEFAULT_CALLBACK_TIMEOUT_SECS,
+                    shadow: vec![],
+                    derived_storage: vec![],
+                    precomputed_storage: vec![],
+                    ipc_socket: None,
+                    dkg_fanout: vec![],
+                    archive: ArchiveArgs::default(),
+                    http: HttpArgs::default(),
+                    backup: BackupArgs::default(),
+                    runtime: RuntimeArgs::default(),
+                    pprof: PprofArgs::default(),
+                    webhooks: WebhookArgs::default(),
                 };
                 tokio::task::spawn(async move { Cli::start(config).run().await.unwrap() });
             }
@@ -757,6 +811,37 @@ impl NodeConfig {
     }
 }
 
+/// Returns path to binary of given golang release, the release is downloaded into
+/// `target/drand_go/<version>` if it is not cached yet.
+fn golang_release(manifest_dir: &str, version: &str) -> String {
+    let dir = format!("{manifest_dir}/target/drand_go/{version}");
+    let bin = format!("{dir}/drand");
+    if Path::new(&bin).exists() {
+        return bin;
+    }
+
+    std::fs::create_dir_all(&dir).unwrap();
+    let asset = format!(
+        "drand_{}_linux_amd64.tar.gz",
+        version.trim_start_matches('v')
+    );
+    info!("downloading golang release {version}: {asset}");
+    let status = std::process::Command::new("/bin/bash")
+        .arg("-c")
+        .arg(format!(
+            "curl -sSfL {GO_RELEASES_URL}/{version}/{asset} | tar -xz -C {dir} drand"
+        ))
+        .status()
+        .unwrap();
+    assert!(
+        status.success(),
+        "failed to download golang release {version}, code: {:?}",
+        status.code()
+    );
+
+    bin
+}
+
 async fn run_cmd_golang(args: &str) {
     let mut cmd = async_std::process::Command::new("/bin/bash");
     cmd.arg("-c")
@@ -9,6 +9,7 @@ pub use protobuf::dkg::AbortOptions;
 pub use protobuf::dkg::AcceptOptions;
 pub use protobuf::dkg::CommandMetadata;
 pub use protobuf::dkg::ExecutionOptions;
+pub use protobuf::dkg::IdentityChange;
 pub use protobuf::dkg::JoinOptions;
 pub use protobuf::dkg::RejectOptions;
 
@@ -707,28 +708,39 @@ impl From<DkgEntry> for protobuf::dkg::DkgEntry {
 pub struct DkgStatusResponse {
     pub complete: DkgEntry,
     pub current: DkgEntry,
+    pub identity_changes: Vec<IdentityChange>,
 }
 
 impl ConvertProto for protobuf::dkg::DkgStatusResponse {
     type Inner = DkgStatusResponse;
 
     fn validate(self) -> Result<Self::Inner, TransportError> {
-        let Self { complete, current } = self;
+        let Self {
+            complete,
+            current,
+            identity_changes,
+        } = self;
 
         Ok(Self::Inner {
             complete: complete.require_some()?.validate()?,
             current: current.require_some()?.validate()?,
+            identity_changes,
         })
     }
 }
 
 impl From<DkgStatusResponse> for protobuf::dkg::DkgStatusResponse {
     fn from(value: DkgStatusResponse) -> Self {
-        let DkgStatusResponse { complete, current } = value;
+        let DkgStatusResponse {
+            complete,
+            current,
+            identity_changes,
+        } = value;
 
         Self {
             complete: Some(complete.into()),
             current: Some(current.into()),
+            identity_changes,
         }
     }
 }
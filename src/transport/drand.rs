@@ -13,6 +13,8 @@ use crate::net::utils::Seconds;
 use crate::protobuf;
 use crate::protobuf::drand::Metadata;
 
+use prost::bytes::Bytes;
+
 impl ConvertProto for protobuf::drand::Address {
     type Inner = Address;
 
@@ -311,6 +313,12 @@ pub struct StartSyncRequest {
     pub nodes: Vec<String>,
     pub up_to: u64,
     pub metadata: Metadata,
+    pub verify_mode: u32,
+    pub spot_check_every: u64,
+    pub checkpoint_round: u64,
+    pub checkpoint_signature: Vec<u8>,
+    pub archive_url: String,
+    pub chain_info: String,
 }
 
 impl ConvertProto for crate::protobuf::drand::StartSyncRequest {
@@ -321,12 +329,24 @@ impl ConvertProto for crate::protobuf::drand::StartSyncRequest {
             nodes,
             up_to,
             metadata,
+            verify_mode,
+            spot_check_every,
+            checkpoint_round,
+            checkpoint_signature,
+            archive_url,
+            chain_info,
         } = self;
 
         Ok(Self::Inner {
             nodes,
             up_to,
             metadata: metadata.require_some()?,
+            verify_mode,
+            spot_check_every,
+            checkpoint_round,
+            checkpoint_signature,
+            archive_url,
+            chain_info,
         })
     }
 }
@@ -337,12 +357,24 @@ impl From<StartSyncRequest> for crate::protobuf::drand::StartSyncRequest {
             nodes,
             up_to,
             metadata,
+            verify_mode,
+            spot_check_every,
+            checkpoint_round,
+            checkpoint_signature,
+            archive_url,
+            chain_info,
         } = value;
 
         Self {
             nodes,
             up_to,
             metadata: Some(metadata),
+            verify_mode,
+            spot_check_every,
+            checkpoint_round,
+            checkpoint_signature,
+            archive_url,
+            chain_info,
         }
     }
 }
@@ -479,9 +511,9 @@ impl From<SyncRequest> for crate::protobuf::drand::SyncRequest {
 }
 
 pub struct BeaconPacket {
-    pub previous_signature: Vec<u8>,
+    pub previous_signature: Bytes,
     pub round: u64,
-    pub signature: Vec<u8>,
+    pub signature: Bytes,
     pub metadata: Metadata,
 }
 
@@ -556,6 +588,7 @@ pub struct PublicRandResponse {
     pub round: u64,
     pub signature: Vec<u8>,
     pub previous_signature: Vec<u8>,
+    pub randomness: Vec<u8>,
     pub metadata: Metadata,
 }
 
@@ -567,6 +600,7 @@ impl ConvertProto for crate::protobuf::drand::PublicRandResponse {
             round,
             signature,
             previous_signature,
+            randomness,
             metadata,
         } = self;
 
@@ -574,6 +608,7 @@ impl ConvertProto for crate::protobuf::drand::PublicRandResponse {
             round,
             signature,
             previous_signature,
+            randomness,
             metadata: metadata.require_some()?,
         })
     }
@@ -585,6 +620,7 @@ impl From<PublicRandResponse> for crate::protobuf::drand::PublicRandResponse {
             round,
             signature,
             previous_signature,
+            randomness,
             metadata,
         } = value;
 
@@ -592,6 +628,7 @@ impl From<PublicRandResponse> for crate::protobuf::drand::PublicRandResponse {
             round,
             signature,
             previous_signature,
+            randomness,
             metadata: Some(metadata),
         }
     }
@@ -0,0 +1,365 @@
+//! Verification core of drand beacons: signature check of a round, links of a chain segment,
+//! randomness, chain hash and round at a given time.
+//!
+//! The module does not allocate and is the single item of the library target built without
+//! other features, so wasm and FFI consumers verify beacons with the code used by the daemon.
+//!
+//! Signatures of the functions are stable: explorers and auditors can check beacons and chain
+//! info served by any drand node or relay exactly as the daemon does. Chain info packets are
+//! checked by `client::verify_chain_info`, available with the `client` feature.
+use core::fmt;
+use energon::drand::schemes::DefaultScheme;
+use energon::drand::schemes::SigsOnG1Scheme;
+use energon::drand::schemes::UnchainedScheme;
+use energon::drand::traits::BeaconDigest;
+use energon::drand::traits::DrandScheme as Scheme;
+use energon::points::KeyPoint;
+use energon::points::SigPoint;
+use energon::traits::Affine;
+use sha2::Digest;
+
+/// Beacon id which is omitted from the chain hash.
+pub const DEFAULT_BEACON_ID: &str = "default";
+
+/// Errors of [`verify_serialized`], an invalid signature is not an error.
+#[derive(Debug, PartialEq)]
+pub enum VerifyError {
+    UnknownScheme,
+    InvalidKey,
+    InvalidSignature,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownScheme => f.write_str("unknown scheme"),
+            Self::InvalidKey => f.write_str("failed to deserialize public key"),
+            Self::InvalidSignature => f.write_str("failed to deserialize signature"),
+        }
+    }
+}
+
+/// Errors of [`verify_chain`], with the round of the first beacon which is not valid.
+#[derive(Debug, PartialEq)]
+pub enum ChainError {
+    /// Signature is malformed or not valid for the round.
+    InvalidSignature { round: u64 },
+    /// Previous signature of a chained beacon differs from signature of the preceding one.
+    NotLinked { round: u64 },
+    /// Rounds are not increasing, or not consecutive for chained schemes.
+    UnexpectedRound { expected: u64, round: u64 },
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSignature { round } => write!(f, "invalid signature of round {round}"),
+            Self::NotLinked { round } => write!(
+                f,
+                "previous signature of round {round} does not match the preceding beacon"
+            ),
+            Self::UnexpectedRound { expected, round } => {
+                write!(f, "unexpected round {round}, expected {expected}")
+            }
+        }
+    }
+}
+
+/// Serialized beacon of a chain segment, see [`verify_chain`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Beacon<'a> {
+    pub round: u64,
+    pub signature: &'a [u8],
+    /// Empty for unchained schemes.
+    pub previous_signature: &'a [u8],
+}
+
+impl Beacon<'_> {
+    /// Returns randomness of the beacon, see [`randomness`].
+    #[must_use]
+    pub fn randomness(&self) -> [u8; 32] {
+        randomness(self.signature)
+    }
+}
+
+/// BLS signature check of a beacon, suitable for chained and unchained schemes.
+///
+/// The signed message is the round digest of the scheme, `prev_sig` is ignored by
+/// unchained schemes.
+#[must_use]
+pub fn verify_beacon<S: Scheme>(
+    public_key: &KeyPoint<S>,
+    prev_sig: &[u8],
+    round: u64,
+    signature: &SigPoint<S>,
+) -> bool {
+    let msg = S::Beacon::digest(prev_sig, round);
+    S::bls_verify(public_key, signature, &msg).is_ok()
+}
+
+/// Checks serialized beacon of the scheme with given id, see [`verify_beacon`].
+///
+/// # Errors
+///
+/// Returns an error if the scheme is unknown or the points are malformed.
+pub fn verify_serialized(
+    scheme_id: &str,
+    public_key: &[u8],
+    prev_sig: &[u8],
+    round: u64,
+    signature: &[u8],
+) -> Result<bool, VerifyError> {
+    fn verify<S: Scheme>(
+        public_key: &[u8],
+        prev_sig: &[u8],
+        round: u64,
+        signature: &[u8],
+    ) -> Result<bool, VerifyError> {
+        let public_key = Affine::deserialize(public_key).map_err(|_| VerifyError::InvalidKey)?;
+        let signature =
+            Affine::deserialize(signature).map_err(|_| VerifyError::InvalidSignature)?;
+
+        Ok(verify_beacon::<S>(&public_key, prev_sig, round, &signature))
+    }
+
+    match scheme_id {
+        DefaultScheme::ID => verify::<DefaultScheme>(public_key, prev_sig, round, signature),
+        UnchainedScheme::ID => verify::<UnchainedScheme>(public_key, prev_sig, round, signature),
+        SigsOnG1Scheme::ID => verify::<SigsOnG1Scheme>(public_key, prev_sig, round, signature),
+        _ => Err(VerifyError::UnknownScheme),
+    }
+}
+
+/// Checks a segment of beacons ordered by round, returns the last verified round.
+///
+/// Every signature is checked with [`verify_beacon`]. For chained schemes rounds must be
+/// consecutive and each beacon must link to the preceding one: to `previous` (signature of the
+/// round before the segment) for the first beacon if given, otherwise its previous signature is
+/// trusted. For unchained schemes rounds must be increasing and previous signatures are ignored.
+/// An empty segment returns 0.
+///
+/// # Errors
+///
+/// Returns the first beacon which is not valid, beacons after it are not checked.
+pub fn verify_chain<'a, S: Scheme>(
+    public_key: &KeyPoint<S>,
+    previous: Option<&'a [u8]>,
+    beacons: impl IntoIterator<Item = Beacon<'a>>,
+) -> Result<u64, ChainError> {
+    let chained = S::Beacon::is_chained();
+    let mut last: Option<Beacon<'a>> = None;
+
+    for beacon in beacons {
+        let round = beacon.round;
+        if let Some(last) = last {
+            let expected = last.round.saturating_add(1);
+            if (chained && round != expected) || round < expected {
+                return Err(ChainError::UnexpectedRound { expected, round });
+            }
+        }
+        let prev_sig = if chained {
+            let expected = last.map(|b| b.signature).or(previous);
+            if expected.is_some_and(|sig| sig != beacon.previous_signature) {
+                return Err(ChainError::NotLinked { round });
+            }
+            beacon.previous_signature
+        } else {
+            &[]
+        };
+        let valid = Affine::deserialize(beacon.signature)
+            .is_ok_and(|sig| verify_beacon::<S>(public_key, prev_sig, round, &sig));
+        if !valid {
+            return Err(ChainError::InvalidSignature { round });
+        }
+        last = Some(beacon);
+    }
+
+    Ok(last.map_or(0, |b| b.round))
+}
+
+/// Returns true if beacons of the scheme with given id link to the previous signature.
+///
+/// # Errors
+///
+/// Returns an error if the scheme is unknown.
+pub fn is_chained(scheme_id: &str) -> Result<bool, VerifyError> {
+    match scheme_id {
+        DefaultScheme::ID => Ok(<DefaultScheme as Scheme>::Beacon::is_chained()),
+        UnchainedScheme::ID => Ok(<UnchainedScheme as Scheme>::Beacon::is_chained()),
+        SigsOnG1Scheme::ID => Ok(<SigsOnG1Scheme as Scheme>::Beacon::is_chained()),
+        _ => Err(VerifyError::UnknownScheme),
+    }
+}
+
+/// Derives randomness from the beacon signature as `sha256(signature)`.
+///
+/// Go drand derives randomness in the same way for chained and unchained schemes, the scheme
+/// only affects the message which is signed: `sha256(previous_signature || round)` for chained
+/// and `sha256(round)` for unchained beacons.
+#[must_use]
+pub fn randomness(signature: &[u8]) -> [u8; 32] {
+    sha2::Sha256::digest(signature).into()
+}
+
+/// Derives randomness of a beacon of the scheme with given id, see [`randomness`].
+///
+/// Unlike [`randomness`], the signature is checked to be a point of the scheme, so that the
+/// previous signature, a hex string or a truncated signature is not hashed by mistake. The
+/// signature itself is not verified, see [`verify_serialized`].
+///
+/// # Errors
+///
+/// Returns an error if the scheme is unknown or the signature is malformed.
+pub fn derive_randomness(signature: &[u8], scheme_id: &str) -> Result<[u8; 32], VerifyError> {
+    fn check<S: Scheme>(signature: &[u8]) -> Result<(), VerifyError> {
+        let _: SigPoint<S> =
+            Affine::deserialize(signature).map_err(|_| VerifyError::InvalidSignature)?;
+        Ok(())
+    }
+
+    match scheme_id {
+        DefaultScheme::ID => check::<DefaultScheme>(signature)?,
+        UnchainedScheme::ID => check::<UnchainedScheme>(signature)?,
+        SigsOnG1Scheme::ID => check::<SigsOnG1Scheme>(signature)?,
+        _ => return Err(VerifyError::UnknownScheme),
+    }
+
+    Ok(randomness(signature))
+}
+
+/// Returns canonical hash of chain info, as computed by Go drand.
+///
+/// Beacon id is hashed for all chains except the default one.
+#[must_use]
+pub fn chain_hash(
+    period: u32,
+    genesis_time: i64,
+    public_key: &[u8],
+    group_hash: &[u8],
+    beacon_id: &str,
+) -> [u8; 32] {
+    let mut h = sha2::Sha256::new();
+    h.update(period.to_be_bytes());
+    h.update(genesis_time.to_be_bytes());
+    h.update(public_key);
+    h.update(group_hash);
+
+    if beacon_id != DEFAULT_BEACON_ID && !beacon_id.is_empty() {
+        h.update(beacon_id.as_bytes());
+    }
+    h.finalize().into()
+}
+
+/// Returns the round active at UNIX time `now`, round 1 starts at genesis.
+///
+/// Times before genesis map to round 1 as in Go drand, zero period gives round 0.
+#[must_use]
+pub fn round_at(now: u64, period: u32, genesis: u64) -> u64 {
+    if period == 0 {
+        return 0;
+    }
+
+    now.saturating_sub(genesis) / u64::from(period) + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_at_time() {
+        // Quicknet: period 3s.
+        let genesis = 1692803367;
+        assert_eq!(round_at(genesis - 1, 3, genesis), 1);
+        assert_eq!(round_at(genesis, 3, genesis), 1);
+        assert_eq!(round_at(genesis + 2, 3, genesis), 1);
+        assert_eq!(round_at(genesis + 3, 3, genesis), 2);
+        assert_eq!(round_at(1745308647, 3, genesis), 17501761);
+        assert_eq!(round_at(genesis, 0, genesis), 0);
+
+        assert_eq!(
+            verify_serialized("unknown", &[], &[], 1, &[]),
+            Err(VerifyError::UnknownScheme)
+        );
+        assert_eq!(
+            verify_serialized(DefaultScheme::ID, &[1, 2], &[], 1, &[]),
+            Err(VerifyError::InvalidKey)
+        );
+
+        assert_eq!(is_chained(DefaultScheme::ID), Ok(true));
+        assert_eq!(is_chained(UnchainedScheme::ID), Ok(false));
+        assert_eq!(is_chained(SigsOnG1Scheme::ID), Ok(false));
+        assert_eq!(is_chained("unknown"), Err(VerifyError::UnknownScheme));
+    }
+
+    #[test]
+    fn chain_segment() {
+        use energon::traits::ScalarField;
+        type S = DefaultScheme;
+
+        let private = <S as Scheme>::Scalar::random();
+        let public_key = S::sk_to_pk(&private);
+        let genesis_seed = [0xaa; 32];
+        let mut signatures = vec![genesis_seed.to_vec()];
+        for round in 1..=3 {
+            let msg = <S as Scheme>::Beacon::digest(&signatures[round - 1], round as u64);
+            let sig = S::bls_sign(&msg, &private).unwrap();
+            signatures.push(sig.serialize().unwrap().to_vec());
+        }
+        let beacon = |round: usize| Beacon {
+            round: round as u64,
+            signature: &signatures[round],
+            previous_signature: &signatures[round - 1],
+        };
+
+        let segment = [beacon(2), beacon(3)];
+        assert_eq!(verify_chain::<S>(&public_key, None, segment), Ok(3));
+        assert_eq!(
+            verify_chain::<S>(&public_key, Some(&signatures[1]), segment),
+            Ok(3)
+        );
+        assert_eq!(
+            verify_chain::<S>(&public_key, Some(&genesis_seed), segment),
+            Err(ChainError::NotLinked { round: 2 })
+        );
+        assert_eq!(
+            verify_chain::<S>(&public_key, None, [beacon(1), beacon(3)]),
+            Err(ChainError::UnexpectedRound {
+                expected: 2,
+                round: 3
+            })
+        );
+        let forged = Beacon {
+            signature: &signatures[1],
+            ..beacon(2)
+        };
+        assert_eq!(
+            verify_chain::<S>(&public_key, None, [beacon(1), forged]),
+            Err(ChainError::NotLinked { round: 2 })
+        );
+        assert_eq!(
+            verify_chain::<S>(&public_key, None, [forged]),
+            Err(ChainError::InvalidSignature { round: 2 })
+        );
+        assert_eq!(verify_chain::<S>(&public_key, None, []), Ok(0));
+
+        assert_eq!(beacon(3).randomness(), randomness(&signatures[3]));
+        assert_eq!(
+            derive_randomness(&signatures[3], S::ID),
+            Ok(randomness(&signatures[3]))
+        );
+        // Seed of the chain is not a signature.
+        assert_eq!(
+            derive_randomness(&genesis_seed, S::ID),
+            Err(VerifyError::InvalidSignature)
+        );
+        assert_eq!(
+            derive_randomness(&signatures[3][..48], S::ID),
+            Err(VerifyError::InvalidSignature)
+        );
+        assert_eq!(
+            derive_randomness(&signatures[3], "unknown"),
+            Err(VerifyError::UnknownScheme)
+        );
+    }
+}
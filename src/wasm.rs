@@ -0,0 +1,191 @@
+//! Bindings for `wasm32-unknown-unknown`: beacon verification and a verifying HTTP client.
+//!
+//! Build with `cargo rustc --lib --release --target wasm32-unknown-unknown --no-default-features
+//! --features arkworks,wasm --crate-type cdylib` and pass `drand.wasm` to `wasm-bindgen`, the
+//! arkworks backend is pure Rust and needs no C toolchain.
+//!
+//! The client reads the HTTP API of a node or relay rooted at the chain, e.g.
+//! `https://api.drand.sh/<chain hash>`. Chain info is accepted only if its hash is the expected
+//! one, so a beacon is trusted only if it is signed by the group of the pinned chain.
+use crate::verify;
+
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+use wasm_bindgen_futures::js_sys::Promise;
+
+/// Checks BLS signature of the beacon for the given scheme id.
+///
+/// # Errors
+///
+/// Returns an error if the scheme is unknown or the points are malformed.
+#[wasm_bindgen(js_name = verifyBeacon)]
+pub fn verify_beacon(
+    scheme_id: &str,
+    public_key: &[u8],
+    round: u64,
+    signature: &[u8],
+    previous_signature: &[u8],
+) -> Result<bool, JsError> {
+    verify::verify_serialized(scheme_id, public_key, previous_signature, round, signature)
+        .map_err(|err| JsError::new(&err.to_string()))
+}
+
+/// Returns canonical hash of chain info, see [`verify::chain_hash`].
+#[must_use]
+#[wasm_bindgen(js_name = chainHash)]
+pub fn chain_hash(
+    period: u32,
+    genesis_time: i64,
+    public_key: &[u8],
+    group_hash: &[u8],
+    beacon_id: &str,
+) -> Vec<u8> {
+    verify::chain_hash(period, genesis_time, public_key, group_hash, beacon_id).to_vec()
+}
+
+/// Verified beacon, binary fields are hex encoded.
+#[wasm_bindgen(getter_with_clone)]
+pub struct Beacon {
+    pub round: u64,
+    pub randomness: String,
+    pub signature: String,
+}
+
+/// Client of the HTTP API of a single chain.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct HttpClient {
+    url: String,
+    scheme_id: String,
+    public_key: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl HttpClient {
+    /// Fetches chain info from `{url}/info`, its hash should be equal to hex encoded
+    /// `chain_hash`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the chain info does not match the chain hash.
+    #[allow(
+        clippy::needless_pass_by_value,
+        reason = "arguments of async bindings are owned"
+    )]
+    pub async fn connect(url: String, chain_hash: String) -> Result<HttpClient, JsError> {
+        let url = url.trim_end_matches('/').to_string();
+        let info = get_json(&format!("{url}/info")).await?;
+        let beacon_id = info
+            .get("metadata")
+            .and_then(|m| m.get("beaconID"))
+            .and_then(Value::as_str)
+            .unwrap_or(verify::DEFAULT_BEACON_ID);
+        let public_key = hex_field(&info, "public_key")?;
+        let period = u32::try_from(u64_field(&info, "period")?)
+            .map_err(|_| JsError::new("invalid field 'period'"))?;
+        let genesis_time = i64::try_from(u64_field(&info, "genesis_time")?)
+            .map_err(|_| JsError::new("invalid field 'genesis_time'"))?;
+        let group_hash = hex_field(&info, "groupHash")?;
+
+        let computed =
+            verify::chain_hash(period, genesis_time, &public_key, &group_hash, beacon_id);
+        if hex::encode(computed) != chain_hash.to_lowercase() {
+            return Err(JsError::new(&format!(
+                "chain hash expected {chain_hash}, computed {}",
+                hex::encode(computed)
+            )));
+        }
+        let scheme_id = info
+            .get("schemeID")
+            .and_then(Value::as_str)
+            .ok_or_else(|| JsError::new("missing field 'schemeID'"))?
+            .to_string();
+
+        Ok(Self {
+            url,
+            scheme_id,
+            public_key,
+        })
+    }
+
+    /// Resolves to verified [`Beacon`] of the round, round 0 requests the latest beacon.
+    #[must_use]
+    pub fn get(&self, round: u64) -> Promise {
+        let client = self.clone();
+        future_to_promise(
+            async move { Ok(client.fetch(round).await.map_err(JsValue::from)?.into()) },
+        )
+    }
+}
+
+impl HttpClient {
+    async fn fetch(&self, round: u64) -> Result<Beacon, JsError> {
+        let path = if round == 0 {
+            "latest".to_string()
+        } else {
+            round.to_string()
+        };
+        let json = get_json(&format!("{}/public/{path}", self.url)).await?;
+        let received = u64_field(&json, "round")?;
+        if round != 0 && received != round {
+            return Err(JsError::new(&format!(
+                "received round {received}, requested {round}"
+            )));
+        }
+        let signature = hex_field(&json, "signature")?;
+        // Previous signature is absent for unchained schemes.
+        let previous_signature = match json.get("previous_signature") {
+            Some(_) => hex_field(&json, "previous_signature")?,
+            None => vec![],
+        };
+        if !verify_beacon(
+            &self.scheme_id,
+            &self.public_key,
+            received,
+            &signature,
+            &previous_signature,
+        )? {
+            return Err(JsError::new(&format!(
+                "invalid signature of round {received}"
+            )));
+        }
+
+        Ok(Beacon {
+            round: received,
+            randomness: hex::encode(verify::randomness(&signature)),
+            signature: hex::encode(signature),
+        })
+    }
+}
+
+async fn get_json(url: &str) -> Result<Value, JsError> {
+    let response = reqwest::get(url)
+        .await
+        .map_err(|err| JsError::new(&format!("{url}: {err}")))?;
+    if !response.status().is_success() {
+        return Err(JsError::new(&format!(
+            "{url}: unexpected status {}",
+            response.status()
+        )));
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|err| JsError::new(&format!("{url}: {err}")))?;
+
+    serde_json::from_slice(&bytes).map_err(|err| JsError::new(&format!("{url}: {err}")))
+}
+
+fn hex_field(json: &Value, field: &str) -> Result<Vec<u8>, JsError> {
+    json.get(field)
+        .and_then(Value::as_str)
+        .and_then(|s| hex::decode(s).ok())
+        .ok_or_else(|| JsError::new(&format!("missing or invalid field '{field}'")))
+}
+
+fn u64_field(json: &Value, field: &str) -> Result<u64, JsError> {
+    json.get(field)
+        .and_then(Value::as_u64)
+        .ok_or_else(|| JsError::new(&format!("missing or invalid field '{field}'")))
+}